@@ -0,0 +1,356 @@
+// A11y Rules - Accessibility checks for HTML, JSX and Vue templates
+// Markup is read into elements first: JSX from the syntax tree, HTML and templates by a tag scanner
+
+use std::collections::HashSet;
+use tree_sitter::Node;
+
+use crate::syntax;
+use crate::CodeSuggestion;
+
+/// Elements without a closing tag
+const VOID_ELEMENTS: [&str; 13] =
+    ["area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr"];
+
+/// Elements that take focus and handle the keyboard themselves
+const INTERACTIVE_ELEMENTS: [&str; 10] =
+    ["a", "button", "input", "select", "textarea", "option", "summary", "details", "label", "area"];
+
+/// Inputs labelled by their own value or never shown
+const SELF_LABELLED_INPUTS: [&str; 5] = ["hidden", "submit", "button", "reset", "image"];
+
+/// Link text that says nothing about the target when read out of context
+const VAGUE_LINK_TEXT: [&str; 10] =
+    ["click here", "here", "read more", "more", "learn more", "link", "this", "this link", "go", "details"];
+
+#[derive(Debug)]
+struct Element {
+    /// Lowercase tag name
+    name: String,
+    /// Normalized names (`onclick`, `for`, `alt`) with raw values
+    attributes: Vec<(String, Option<String>)>,
+    /// Attributes spread from an object, so any attribute may be present
+    spread: bool,
+    line: usize,
+    column: usize,
+    /// Static text content; `None` when it includes expressions or the element is never closed
+    text: Option<String>,
+    in_label: bool,
+}
+
+impl Element {
+    fn value(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(n, _)| n == name).and_then(|(_, v)| v.as_deref())
+    }
+
+    fn has(&self, names: &[&str]) -> bool {
+        self.spread || self.attributes.iter().any(|(n, _)| names.contains(&n.as_str()))
+    }
+}
+
+/// One attribute name across dialects: `onClick`, `@click.prevent` and `v-on:click` are all `onclick`
+fn normalize_attribute(name: &str) -> String {
+    let name = name.strip_prefix("v-bind:").or_else(|| name.strip_prefix(':')).unwrap_or(name);
+    let name = match name.strip_prefix("v-on:").or_else(|| name.strip_prefix('@')) {
+        Some(event) => format!("on{}", event.split('.').next().unwrap_or(event)),
+        None => name.to_string(),
+    };
+    match name.to_ascii_lowercase().as_str() {
+        "htmlfor" => "for".to_string(),
+        lower => lower.to_string(),
+    }
+}
+
+/// `name="value" flag name=value` inside a tag
+fn parse_attributes(tag: &str) -> Vec<(String, Option<String>)> {
+    let mut attributes = Vec::new();
+    let mut chars = tag.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace() || *c == '/') {
+            chars.next();
+        }
+        let mut name = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || c == '=' || c == '/' {
+                break;
+            }
+            name.push(c);
+            chars.next();
+        }
+        if name.is_empty() {
+            return attributes;
+        }
+        let mut value = None;
+        if chars.peek() == Some(&'=') {
+            chars.next();
+            let mut raw = String::new();
+            match chars.peek().copied() {
+                Some(quote @ ('"' | '\'')) => {
+                    chars.next();
+                    raw.extend(chars.by_ref().take_while(|c| *c != quote));
+                }
+                _ => {
+                    while let Some(c) = chars.peek().copied().filter(|c| !c.is_whitespace()) {
+                        raw.push(c);
+                        chars.next();
+                    }
+                }
+            }
+            value = Some(raw);
+        }
+        attributes.push((normalize_attribute(&name), value));
+    }
+}
+
+/// 1-based line and byte column of `offset`
+pub fn position(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset];
+    let column = before.rfind('\n').map(|newline| offset - newline - 1).unwrap_or(offset);
+    (before.matches('\n').count() + 1, column)
+}
+
+/// Byte offset of the `>` ending the tag that opens at `open`, skipping quoted values
+pub fn tag_end(content: &str, open: usize, end: usize) -> usize {
+    let mut quote = None;
+    for (i, c) in content[open..end].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return open + i,
+            _ => {}
+        }
+    }
+    end
+}
+
+/// Elements of HTML markup in `content[start..end]`
+fn scan_markup(content: &str, start: usize, end: usize) -> Vec<Element> {
+    let mut elements: Vec<Element> = Vec::new();
+    // Open elements as (name, index into `elements`, text so far)
+    let mut open: Vec<(String, usize, Option<String>)> = Vec::new();
+    let mut offset = start;
+    while offset < end {
+        let next = match content[offset..end].find('<') {
+            Some(at) => offset + at,
+            None => end,
+        };
+        let text = &content[offset..next];
+        for (_, _, buffer) in open.iter_mut() {
+            match buffer {
+                // Interpolation makes the text dynamic
+                Some(_) if text.contains("{{") => *buffer = None,
+                Some(buffer) => buffer.push_str(text),
+                None => {}
+            }
+        }
+        if next >= end {
+            break;
+        }
+        let rest = &content[next..end];
+        if rest.starts_with("<!--") {
+            offset = rest.find("-->").map(|at| next + at + 3).unwrap_or(end);
+            continue;
+        }
+        let close = tag_end(content, next, end);
+        offset = close + 1;
+        if let Some(name) = rest.strip_prefix("</") {
+            let name: String = name.chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+            let name = name.to_ascii_lowercase();
+            if let Some(depth) = open.iter().rposition(|(n, _, _)| *n == name) {
+                for (_, index, text) in open.drain(depth..) {
+                    if text.is_some() && elements[index].name == name {
+                        elements[index].text = text;
+                    }
+                }
+            }
+            continue;
+        }
+        let tag = &content[next + 1..close.max(next + 1)];
+        let name: String = tag.chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            continue;
+        }
+        let name = name.to_ascii_lowercase();
+        let (line, column) = position(content, next);
+        elements.push(Element {
+            attributes: parse_attributes(&tag[name.len()..]),
+            spread: false,
+            line,
+            column,
+            text: None,
+            in_label: open.iter().any(|(n, _, _)| n == "label"),
+            name: name.clone(),
+        });
+        if name == "script" || name == "style" {
+            let closing = format!("</{}", name);
+            offset = content[offset.min(end)..end].find(&closing).map(|at| offset + at).unwrap_or(end);
+        } else if !tag.ends_with('/') && !VOID_ELEMENTS.contains(&name.as_str()) {
+            open.push((name, elements.len() - 1, Some(String::new())));
+        }
+    }
+    elements
+}
+
+/// Text of a JSX element's children, `None` if any child is an expression
+fn jsx_text(element: Node, content: &str) -> Option<String> {
+    let mut text = String::new();
+    for child in syntax::named_children(element) {
+        match child.kind() {
+            "jsx_opening_element" | "jsx_closing_element" | "jsx_self_closing_element" => {}
+            "jsx_text" => text.push_str(&content[child.byte_range()]),
+            "jsx_element" => text.push_str(&jsx_text(child, content)?),
+            _ => return None,
+        }
+    }
+    Some(text)
+}
+
+/// Intrinsic (lowercase) JSX elements; components are left to their own implementation
+fn collect_jsx(node: Node, content: &str, in_label: bool, elements: &mut Vec<Element>) {
+    let mut in_label = in_label;
+    let tag = match node.kind() {
+        "jsx_element" => node.child_by_field_name("open_tag"),
+        "jsx_self_closing_element" => Some(node),
+        _ => None,
+    };
+    if let Some(tag) = tag {
+        let name = tag.child_by_field_name("name").map(|n| &content[n.byte_range()]).unwrap_or("");
+        if name.starts_with(|c: char| c.is_ascii_lowercase()) && !name.contains('.') {
+            let mut element = Element {
+                name: name.to_string(),
+                attributes: Vec::new(),
+                spread: false,
+                line: tag.start_position().row + 1,
+                column: tag.start_position().column,
+                text: None,
+                in_label,
+            };
+            for attribute in syntax::named_children(tag) {
+                match attribute.kind() {
+                    "jsx_attribute" => {
+                        let parts = syntax::named_children(attribute);
+                        let name = parts.first().map(|n| &content[n.byte_range()]).unwrap_or("");
+                        let value = parts.get(1).map(|v| content[v.byte_range()].trim_matches(['"', '\'']).to_string());
+                        element.attributes.push((normalize_attribute(name), value));
+                    }
+                    "jsx_expression" => element.spread = true,
+                    _ => {}
+                }
+            }
+            if node.kind() == "jsx_element" {
+                element.text = jsx_text(node, content);
+            }
+            in_label = in_label || name == "label";
+            elements.push(element);
+        }
+    }
+    for child in syntax::named_children(node) {
+        collect_jsx(child, content, in_label, elements);
+    }
+}
+
+fn suggestion(element: &Element, severity: &str, message: String) -> CodeSuggestion {
+    CodeSuggestion {
+        kind: "accessibility".to_string(),
+        message,
+        line: element.line,
+        column: element.column,
+        severity: severity.to_string(),
+        fix: None,
+    }
+}
+
+fn check(elements: &[Element]) -> Vec<CodeSuggestion> {
+    let labelled: HashSet<&str> = elements
+        .iter()
+        .filter(|e| e.name == "label")
+        .filter_map(|e| e.value("for"))
+        .collect();
+    let mut suggestions = Vec::new();
+    for element in elements {
+        let name = element.name.as_str();
+        let hidden = element.value("aria-hidden") == Some("true")
+            || element.value("role").is_some_and(|r| r == "presentation" || r == "none");
+
+        if name == "img" && !hidden && !element.has(&["alt"]) {
+            let message = "<img> without alt text - describe the image, or use alt=\"\" if it is decorative".to_string();
+            suggestions.push(suggestion(element, "warning", message));
+        }
+
+        let custom = name.contains('-');
+        if element.has(&["onclick"]) && !element.spread && !custom && !INTERACTIVE_ELEMENTS.contains(&name) && !element.has(&["role"]) {
+            let message = format!(
+                "Click handler on <{}> - keyboard and screen reader users cannot use it; use a <button>, or add a role, tabindex and key handler",
+                name
+            );
+            suggestions.push(suggestion(element, "warning", message));
+        }
+
+        let field = matches!(name, "input" | "select" | "textarea")
+            && !element.value("type").is_some_and(|t| SELF_LABELLED_INPUTS.contains(&t.to_ascii_lowercase().as_str()));
+        let labelled_by_id = element.value("id").is_some_and(|id| labelled.contains(id));
+        if field && !hidden && !element.in_label && !labelled_by_id && !element.has(&["aria-label", "aria-labelledby", "title"]) {
+            let message = format!("<{}> has no label - wrap it in a <label>, point a label's for at its id, or add aria-label", name);
+            suggestions.push(suggestion(element, "warning", message));
+        }
+
+        if name == "a" && !element.has(&["aria-label", "aria-labelledby"]) {
+            let text = element
+                .text
+                .as_deref()
+                .map(|t| t.split_whitespace().collect::<Vec<_>>().join(" ").trim_end_matches(['.', ':', '!', '>']).to_lowercase());
+            if let Some(text) = text.filter(|t| VAGUE_LINK_TEXT.contains(&t.as_str())) {
+                let message = format!("Link text \"{}\" does not say where it goes - describe the target", text);
+                suggestions.push(suggestion(element, "info", message));
+            }
+        }
+    }
+    suggestions
+}
+
+/// Accessibility findings for HTML, JSX and the template of a Vue component
+pub fn analyze(file_path: &str, content: &str) -> Vec<CodeSuggestion> {
+    let extension = file_path.rsplit('.').next().unwrap_or("");
+    let elements = match extension {
+        "html" | "htm" => scan_markup(content, 0, content.len()),
+        "vue" => {
+            let start = content.find("<template").unwrap_or(content.len());
+            let end = content.rfind("</template>").unwrap_or(content.len()).max(start);
+            scan_markup(content, start, end)
+        }
+        "tsx" | "jsx" | "js" => match syntax::parse(file_path, content) {
+            Some(tree) => {
+                let mut elements = Vec::new();
+                collect_jsx(tree.root_node(), content, false, &mut elements);
+                elements
+            }
+            None => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+    let mut suggestions = check(&elements);
+    suggestions.sort_by_key(|s| (s.line, s.column));
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(file_path: &str, content: &str) -> Vec<usize> {
+        analyze(file_path, content).into_iter().map(|s| s.line).collect()
+    }
+
+    #[test]
+    fn test_html_rules() {
+        let source = "<main>\n  <img src=\"logo.png\">\n  <img src=\"line.png\" alt=\"\">\n  <div class=\"card\" onclick=\"open()\">Open</div>\n  <label>Name <input name=\"name\"></label>\n  <label for=\"email\">Email</label><input id=\"email\">\n  <input type=\"search\" data-x=\"a > b\">\n  <a href=\"/docs\">Read more.</a>\n  <a href=\"/docs\">Documentation</a>\n  <!-- <img src=\"old.png\"> -->\n</main>\n";
+        assert_eq!(lines("index.html", source), vec![2, 4, 7, 8]);
+    }
+
+    #[test]
+    fn test_jsx_and_vue_templates() {
+        let jsx = "export const Card = ({ id, photo }) => (\n  <section>\n    <img src={photo} />\n    <span onClick={() => select(id)}>Select</span>\n    <button onClick={save}>Save</button>\n    <Avatar onClick={open} />\n    <a href={`/cards/${id}`}>here</a>\n  </section>\n);\n";
+        assert_eq!(lines("src/Card.jsx", jsx), vec![3, 4, 7]);
+        let vue = "<template>\n  <div @click.stop=\"toggle\">Toggle</div>\n  <textarea v-model=\"note\" :aria-label=\"label\"></textarea>\n  <img :src=\"src\">\n</template>\n";
+        assert_eq!(lines("Note.vue", vue), vec![2, 4]);
+    }
+}
@@ -0,0 +1,513 @@
+// Agent - Multi-step plan/act loop with typed tool calls
+// Writes and process execution are gated behind user approval
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::ai_provider::{self, AiProvider, ChatMessage, CompletionRequest};
+use crate::audit;
+use crate::changeset::{Changeset, FileChange};
+use crate::documents;
+use crate::events::Event;
+use crate::file_access;
+use crate::stats;
+use crate::storage;
+use crate::task_runner::{self, TaskSpec};
+use crate::AppState;
+
+/// Default number of model round-trips before a run is stopped
+pub const DEFAULT_MAX_STEPS: usize = 12;
+
+/// Maximum characters of a tool observation fed back to the model
+const MAX_OBSERVATION_CHARS: usize = 12_000;
+
+/// Shared state available to tools
+pub struct ToolContext<'a> {
+    pub workspace: &'a Path,
+    pub state: &'a AppState,
+}
+
+/// A capability the model may invoke
+#[async_trait]
+pub trait AgentTool: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    /// JSON example of the expected arguments
+    fn args_schema(&self) -> Value;
+    /// Whether a user must approve each invocation
+    fn requires_approval(&self) -> bool {
+        false
+    }
+    async fn execute(&self, args: &Value, ctx: &ToolContext<'_>) -> Result<String>;
+}
+
+/// A tool invocation requested by the model
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ToolCall {
+    pub tool: String,
+    #[serde(default)]
+    pub args: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentStatus {
+    Running,
+    AwaitingApproval,
+    Completed,
+    Failed,
+    StepLimitReached,
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AuditEntry {
+    pub step: usize,
+    pub timestamp: u64,
+    /// "thought", "tool_call", "approval", "rejection", "observation", "error" or "final"
+    pub kind: String,
+    pub tool: Option<String>,
+    pub detail: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AgentRun {
+    pub id: String,
+    pub goal: String,
+    pub status: AgentStatus,
+    pub steps: usize,
+    pub max_steps: usize,
+    /// Tool call waiting for user approval
+    pub pending: Option<ToolCall>,
+    pub audit: Vec<AuditEntry>,
+    pub result: Option<String>,
+    /// Conversation with the model (not sent to the frontend)
+    #[serde(skip)]
+    messages: Vec<ChatMessage>,
+}
+
+/// Model response: either a tool call or a final answer
+#[derive(Deserialize)]
+struct AgentAction {
+    #[serde(default)]
+    thought: Option<String>,
+    #[serde(default)]
+    tool: Option<String>,
+    #[serde(default)]
+    args: Value,
+    #[serde(default, rename = "final")]
+    final_answer: Option<String>,
+}
+
+impl AgentRun {
+    pub fn new(goal: &str, max_steps: usize, tools: &ToolRegistry) -> Self {
+        Self {
+            id: storage::new_id("agent"),
+            goal: goal.to_string(),
+            status: AgentStatus::Running,
+            steps: 0,
+            max_steps,
+            pending: None,
+            audit: Vec::new(),
+            result: None,
+            messages: vec![
+                ChatMessage::system(system_prompt(tools)),
+                ChatMessage::user(goal),
+            ],
+        }
+    }
+
+    fn record(&mut self, kind: &str, tool: Option<&str>, detail: impl Into<String>) {
+        self.audit.push(AuditEntry {
+            step: self.steps,
+            timestamp: storage::now_millis(),
+            kind: kind.to_string(),
+            tool: tool.map(|t| t.to_string()),
+            detail: detail.into(),
+        });
+    }
+
+    fn observe(&mut self, tool: &str, observation: String) {
+        let observation = truncate(&observation, MAX_OBSERVATION_CHARS);
+        self.record("observation", Some(tool), observation.clone());
+        self.messages
+            .push(ChatMessage::user(format!("Result of {}:\n{}", tool, observation)));
+    }
+
+    async fn execute(&mut self, call: &ToolCall, tools: &ToolRegistry, ctx: &ToolContext<'_>) {
+        let tool = match tools.get(&call.tool) {
+            Some(tool) => tool,
+            None => {
+                self.observe(&call.tool, format!("Error: unknown tool '{}'", call.tool));
+                return;
+            }
+        };
+        match tool.execute(&call.args, ctx).await {
+            Ok(output) => self.observe(&call.tool, output),
+            Err(e) => {
+                self.record("error", Some(&call.tool), e.to_string());
+                self.observe(&call.tool, format!("Error: {}", e));
+            }
+        }
+    }
+}
+
+/// Registry of tools available to the agent
+pub struct ToolRegistry {
+    tools: HashMap<&'static str, Box<dyn AgentTool>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self {
+            tools: HashMap::new(),
+        }
+    }
+
+    /// Registry with the built-in workspace tools
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(SearchTool));
+        registry.register(Box::new(ReadFileTool));
+        registry.register(Box::new(ApplyChangesetTool));
+        registry.register(Box::new(RunTaskTool));
+        registry
+    }
+
+    pub fn register(&mut self, tool: Box<dyn AgentTool>) {
+        self.tools.insert(tool.name(), tool);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn AgentTool> {
+        self.tools.get(name).map(|t| t.as_ref())
+    }
+
+    fn sorted(&self) -> Vec<&dyn AgentTool> {
+        let mut tools: Vec<&dyn AgentTool> = self.tools.values().map(|t| t.as_ref()).collect();
+        tools.sort_by_key(|t| t.name());
+        tools
+    }
+}
+
+fn system_prompt(tools: &ToolRegistry) -> String {
+    let mut prompt = String::from(
+        "You are an autonomous coding agent working inside the user's workspace. \
+         Work step by step. Each reply must be a single JSON object, either\n\
+         {\"thought\": \"...\", \"tool\": \"<name>\", \"args\": {...}} to call a tool, or\n\
+         {\"thought\": \"...\", \"final\": \"<summary for the user>\"} when the goal is reached.\n\
+         Paths are relative to the workspace root.\n\nTools:\n",
+    );
+    for tool in tools.sorted() {
+        prompt.push_str(&format!(
+            "- {}: {} Args: {}\n",
+            tool.name(),
+            tool.description(),
+            tool.args_schema()
+        ));
+    }
+    prompt
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars).collect();
+    truncated.push_str("\n... (truncated)");
+    truncated
+}
+
+/// Drive a run until it completes, fails, hits the step limit or needs approval
+pub async fn drive(run: &mut AgentRun, provider: &dyn AiProvider, tools: &ToolRegistry, ctx: &ToolContext<'_>) {
+    while run.status == AgentStatus::Running {
+        if run.steps >= run.max_steps {
+            run.status = AgentStatus::StepLimitReached;
+            run.record("error", None, format!("Step limit of {} reached", run.max_steps));
+            break;
+        }
+        run.steps += 1;
+
+        let request = CompletionRequest::new(run.messages.clone()).json();
+        let response = match provider.complete(&request).await {
+            Ok(response) => response,
+            Err(e) => {
+                run.status = AgentStatus::Failed;
+                run.record("error", None, format!("Provider error: {}", e));
+                break;
+            }
+        };
+        run.messages.push(ChatMessage::assistant(response.clone()));
+
+        let action = ai_provider::extract_json(&response)
+            .and_then(|json| serde_json::from_str::<AgentAction>(json).ok());
+        let action = match action {
+            Some(action) => action,
+            None => {
+                run.record("error", None, "Model reply was not a valid action");
+                run.messages.push(ChatMessage::user(
+                    "Your reply was not valid JSON. Reply with a single JSON action object.",
+                ));
+                continue;
+            }
+        };
+
+        if let Some(thought) = &action.thought {
+            run.record("thought", None, thought.clone());
+        }
+
+        if let Some(answer) = action.final_answer {
+            run.record("final", None, answer.clone());
+            run.result = Some(answer);
+            run.status = AgentStatus::Completed;
+            break;
+        }
+
+        let call = match action.tool {
+            Some(tool) => ToolCall { tool, args: action.args },
+            None => {
+                run.messages.push(ChatMessage::user(
+                    "Reply with either a tool call or a final answer.",
+                ));
+                continue;
+            }
+        };
+        run.record("tool_call", Some(&call.tool), call.args.to_string());
+
+        if tools.get(&call.tool).map(|t| t.requires_approval()).unwrap_or(false) {
+            run.pending = Some(call);
+            run.status = AgentStatus::AwaitingApproval;
+            break;
+        }
+
+        run.execute(&call, tools, ctx).await;
+    }
+}
+
+/// Resolve a pending approval; the caller continues with `drive`
+pub async fn resolve_approval(
+    run: &mut AgentRun,
+    approved: bool,
+    tools: &ToolRegistry,
+    ctx: &ToolContext<'_>,
+) -> Result<()> {
+    if run.status != AgentStatus::AwaitingApproval {
+        return Err(anyhow!("Agent run {} is not waiting for approval", run.id));
+    }
+    let call = run.pending.take().ok_or_else(|| anyhow!("No pending tool call"))?;
+    run.status = AgentStatus::Running;
+
+    if approved {
+        run.record("approval", Some(&call.tool), "Approved by user");
+        run.execute(&call, tools, ctx).await;
+    } else {
+        run.record("rejection", Some(&call.tool), "Rejected by user");
+        run.observe(&call.tool, "The user rejected this action. Choose a different approach or finish.".to_string());
+    }
+    Ok(())
+}
+
+/// Persist the audit trail of a run
+pub fn save_run(workspace: &Path, run: &AgentRun) -> Result<()> {
+    let path = storage::data_dir(workspace).join("agent").join(format!("{}.json", run.id));
+    storage::write_json(&path, run)
+}
+
+/// Resolve a tool path, refusing anything outside the workspace; the sandbox's allowed paths are not the agent's
+fn workspace_path(workspace: &Path, path: &str) -> Result<PathBuf> {
+    Ok(file_access::confine(Some(workspace), &[], path)?)
+}
+
+// ==================== TOOLS ====================
+
+struct SearchTool;
+
+#[async_trait]
+impl AgentTool for SearchTool {
+    fn name(&self) -> &'static str {
+        "search"
+    }
+
+    fn description(&self) -> &'static str {
+        "Find workspace files by name or path."
+    }
+
+    fn args_schema(&self) -> Value {
+        json!({ "query": "string" })
+    }
+
+    async fn execute(&self, args: &Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let query = args["query"].as_str().ok_or_else(|| anyhow!("Missing 'query'"))?;
+        let index = ctx.state.file_index.lock().unwrap();
+        let matches: Vec<String> = index
+            .search(query)
+            .into_iter()
+            .take(20)
+            .map(|m| {
+                Path::new(&m.path)
+                    .strip_prefix(ctx.workspace)
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_else(|_| m.path.clone())
+            })
+            .collect();
+
+        if matches.is_empty() {
+            Ok("No matching files".to_string())
+        } else {
+            Ok(matches.join("\n"))
+        }
+    }
+}
+
+struct ReadFileTool;
+
+#[async_trait]
+impl AgentTool for ReadFileTool {
+    fn name(&self) -> &'static str {
+        "read_file"
+    }
+
+    fn description(&self) -> &'static str {
+        "Read the content of a workspace file."
+    }
+
+    fn args_schema(&self) -> Value {
+        json!({ "path": "string" })
+    }
+
+    async fn execute(&self, args: &Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let path = args["path"].as_str().ok_or_else(|| anyhow!("Missing 'path'"))?;
+        Ok(documents::read_source(&ctx.state.documents, &workspace_path(ctx.workspace, path)?)?)
+    }
+}
+
+struct ApplyChangesetTool;
+
+#[async_trait]
+impl AgentTool for ApplyChangesetTool {
+    fn name(&self) -> &'static str {
+        "apply_changeset"
+    }
+
+    fn description(&self) -> &'static str {
+        "Create, edit (line/column ranges, 1-based lines), delete or rename files."
+    }
+
+    fn args_schema(&self) -> Value {
+        json!({
+            "description": "string",
+            "changes": [
+                { "type": "create", "path": "string", "content": "string" },
+                { "type": "edit", "path": "string", "edits": [{
+                    "start": { "line": 1, "column": 0 },
+                    "end": { "line": 1, "column": 0 },
+                    "new_text": "string"
+                }] },
+                { "type": "delete", "path": "string" },
+                { "type": "rename", "from": "string", "to": "string" }
+            ]
+        })
+    }
+
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: &Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let changes: Vec<FileChange> = serde_json::from_value(args["changes"].clone())?;
+        let description = args["description"].as_str().unwrap_or("Agent changes");
+        let changeset = Changeset::new(description, changes);
+
+        for path in changeset.paths() {
+            workspace_path(ctx.workspace, &path)?;
+        }
+        let previews = changeset.preview(ctx.workspace)?;
+        changeset.apply(ctx.workspace)?;
+        let files = audit::from_previews(&previews);
+        if let Err(e) = audit::record(ctx.workspace, audit::Actor::Agent, "changeset", description, files) {
+            log::warn!("Failed to record agent changes in the audit log: {}", e);
+        }
+        stats::refresh_after_write(ctx.state, ctx.workspace, &changeset.paths());
+        Ok(format!("Applied {} changes: {}", changeset.changes.len(), changeset.paths().join(", ")))
+    }
+}
+
+struct RunTaskTool;
+
+#[async_trait]
+impl AgentTool for RunTaskTool {
+    fn name(&self) -> &'static str {
+        "run_task"
+    }
+
+    fn description(&self) -> &'static str {
+        "Run a command (e.g. tests or a build) in the workspace and return its output."
+    }
+
+    fn args_schema(&self) -> Value {
+        json!({ "command": "string", "args": ["string"], "cwd": "optional relative path" })
+    }
+
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: &Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let spec: TaskSpec = serde_json::from_value(args.clone())?;
+        if let Some(cwd) = &spec.cwd {
+            workspace_path(ctx.workspace, cwd)?;
+        }
+        ctx.state.trust.lock().unwrap().ensure(ctx.workspace, "tasks")?;
+        let task_id = storage::new_id("task");
+        ctx.state.events.publish(Event::TaskStarted {
+            task_id: task_id.clone(),
+            command: std::iter::once(&spec.command).chain(&spec.args).cloned().collect::<Vec<_>>().join(" "),
+        });
+        let workspace = ctx.workspace.to_path_buf();
+        let output =
+            tauri::async_runtime::spawn_blocking(move || task_runner::run(&spec, &workspace, Duration::from_secs(300)))
+                .await??;
+        ctx.state.events.publish(Event::TaskFinished {
+            task_id,
+            exit_code: output.exit_code,
+            duration_ms: output.duration_ms,
+            timed_out: output.timed_out,
+        });
+        Ok(format!(
+            "exit code: {:?}{}\nstdout:\n{}\nstderr:\n{}",
+            output.exit_code,
+            if output.timed_out { " (timed out)" } else { "" },
+            output.stdout,
+            output.stderr
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_tools_require_approval() {
+        let tools = ToolRegistry::with_defaults();
+        assert!(!tools.get("search").unwrap().requires_approval());
+        assert!(!tools.get("read_file").unwrap().requires_approval());
+        assert!(tools.get("apply_changeset").unwrap().requires_approval());
+        assert!(tools.get("run_task").unwrap().requires_approval());
+        assert!(system_prompt(&tools).contains("apply_changeset"));
+    }
+
+    #[test]
+    fn test_workspace_path_stays_inside_workspace() {
+        let workspace = std::env::temp_dir().join(storage::new_id("agent-test"));
+        std::fs::create_dir_all(&workspace).unwrap();
+        assert!(workspace_path(&workspace, "../etc/passwd").is_err());
+        assert!(workspace_path(&workspace, "/etc/passwd").is_err());
+        assert!(workspace_path(&workspace, "src/main.rs").is_ok());
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+}
@@ -0,0 +1,104 @@
+// AI Edit - Transform a selection with a natural-language instruction
+// Results are returned as validated, previewable changesets
+
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ai_provider::{self, AiProvider, ChatMessage, CompletionRequest};
+use crate::changeset::{self, Changeset, FileChange, FilePreview, Position, TextEdit};
+use crate::history;
+use crate::syntax::{self, SyntaxCheck};
+
+/// Lines of surrounding code sent along with the selection
+const CONTEXT_LINES: usize = 30;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AiEditProposal {
+    pub changeset: Changeset,
+    pub preview: Vec<FilePreview>,
+    pub replacement: String,
+    /// Syntax check of the whole file after the edit
+    pub syntax: SyntaxCheck,
+    /// The edit introduces syntax errors the original did not have
+    pub introduces_errors: bool,
+}
+
+fn build_prompt(path: &str, before: &str, selection: &str, after: &str, instruction: &str) -> Vec<ChatMessage> {
+    let system = "You edit code. Rewrite only the SELECTION according to the instruction. \
+        Keep the surrounding code's style and indentation. \
+        Reply with the replacement for the selection in a single code block and nothing else.";
+    let user = format!(
+        "File: {}\n\nCode before the selection:\n```\n{}\n```\n\nSELECTION:\n```\n{}\n```\n\n\
+         Code after the selection:\n```\n{}\n```\n\nInstruction: {}",
+        path, before, selection, after, instruction
+    );
+    vec![ChatMessage::system(system), ChatMessage::user(user)]
+}
+
+/// Ask the provider to rewrite `start..end` of `content` and build a previewable edit
+pub async fn edit_selection(
+    provider: &dyn AiProvider,
+    workspace: &Path,
+    path: &str,
+    content: &str,
+    start: Position,
+    end: Position,
+    instruction: &str,
+) -> Result<AiEditProposal> {
+    let start_offset = changeset::offset_of(content, start)?;
+    let end_offset = changeset::offset_of(content, end)?;
+    if end_offset < start_offset {
+        return Err(anyhow!("Selection end precedes start"));
+    }
+
+    let selection = &content[start_offset..end_offset];
+    let before_lines: Vec<&str> = content[..start_offset].lines().collect();
+    let before = before_lines[before_lines.len().saturating_sub(CONTEXT_LINES)..].join("\n");
+    let after = content[end_offset..]
+        .lines()
+        .take(CONTEXT_LINES)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let request = CompletionRequest::new(build_prompt(path, &before, selection, &after, instruction));
+    let response = provider.complete(&request).await?;
+
+    let mut replacement = ai_provider::extract_code_block(&response);
+    if selection.ends_with('\n') && !replacement.ends_with('\n') {
+        replacement.push('\n');
+    }
+
+    let edit = TextEdit {
+        start,
+        end,
+        new_text: replacement.clone(),
+    };
+    let updated = changeset::apply_edits(content, std::slice::from_ref(&edit))?;
+
+    let before_check = syntax::check(path, content);
+    let syntax = syntax::check(path, &updated);
+    let introduces_errors = syntax.errors.len() > before_check.errors.len();
+
+    let changeset = Changeset::new(
+        format!("AI edit: {}", instruction),
+        vec![FileChange::Edit {
+            path: path.to_string(),
+            edits: vec![edit],
+        }],
+    )
+    .expecting(path, content);
+    // Keeps the original available as the merge base if the file changes before the edit is applied
+    if let Err(e) = history::record_snapshot(workspace, path, content, "Before AI edit") {
+        log::warn!("Failed to snapshot {}: {}", path, e);
+    }
+    let preview = changeset.preview(workspace)?;
+
+    Ok(AiEditProposal {
+        changeset,
+        preview,
+        replacement,
+        syntax,
+        introduces_errors,
+    })
+}
@@ -0,0 +1,342 @@
+// AI Middleware - Rate limiting, retries and response caching
+// Wraps every provider handed out by the registry
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Sha256, Digest};
+
+use crate::ai_provider::{AiProvider, CompletionRequest};
+use crate::settings::AiSettings;
+
+/// Sliding one-minute window of request start times
+pub struct RateLimiter {
+    window: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self {
+            window: VecDeque::new(),
+        }
+    }
+
+    /// Reserve a request slot, or return how long to wait for one
+    pub fn try_acquire(&mut self, per_minute: u32, now: Instant) -> Option<Duration> {
+        if per_minute == 0 {
+            return None;
+        }
+        let minute = Duration::from_secs(60);
+        while let Some(oldest) = self.window.front() {
+            if now.duration_since(*oldest) >= minute {
+                self.window.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if self.window.len() < per_minute as usize {
+            self.window.push_back(now);
+            None
+        } else {
+            let oldest = self.window.front().copied().unwrap_or(now);
+            Some(minute.saturating_sub(now.duration_since(oldest)))
+        }
+    }
+}
+
+struct CacheEntry {
+    response: String,
+    created: Instant,
+    /// Insertion order for eviction
+    seq: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Content-addressed response cache keyed by prompt hash
+pub struct ResponseCache {
+    entries: HashMap<String, CacheEntry>,
+    next_seq: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl ResponseCache {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            next_seq: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Hash of everything that influences a response
+    pub fn key(provider: &dyn AiProvider, request: &CompletionRequest) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(provider.id().as_bytes());
+        hasher.update(provider.model().as_bytes());
+        hasher.update(serde_json::to_string(&request.messages).unwrap_or_default().as_bytes());
+        hasher.update(
+            format!("{}|{:?}|{}", request.temperature, request.max_tokens, request.json_mode).as_bytes(),
+        );
+        hex::encode(hasher.finalize())
+    }
+
+    pub fn get(&mut self, key: &str, ttl: Duration) -> Option<String> {
+        let fresh = self
+            .entries
+            .get(key)
+            .filter(|e| e.created.elapsed() < ttl)
+            .map(|e| e.response.clone());
+        match fresh {
+            Some(response) => {
+                self.hits += 1;
+                Some(response)
+            }
+            None => {
+                self.entries.remove(key);
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, key: String, response: String, max_entries: usize) {
+        if max_entries == 0 {
+            return;
+        }
+        while self.entries.len() >= max_entries {
+            // Evict the oldest entry
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.seq)
+                .map(|(k, _)| k.clone());
+            match oldest {
+                Some(k) => {
+                    self.entries.remove(&k);
+                }
+                None => break,
+            }
+        }
+        self.next_seq += 1;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                response,
+                created: Instant::now(),
+                seq: self.next_seq,
+            },
+        );
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.len(),
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+}
+
+/// Whether an error is worth retrying
+fn is_retryable(error: &anyhow::Error) -> bool {
+    match error.downcast_ref::<reqwest::Error>() {
+        Some(e) => {
+            e.is_timeout()
+                || e.is_connect()
+                || e.status()
+                    .map(|s| s.as_u16() == 429 || s.is_server_error())
+                    .unwrap_or(false)
+        }
+        None => false,
+    }
+}
+
+/// Exponential backoff with a little jitter
+fn backoff(attempt: u32) -> Duration {
+    let base = 500u64.saturating_mul(1 << attempt.min(6));
+    let jitter = crate::storage::now_millis() % 250;
+    Duration::from_millis(base + jitter)
+}
+
+/// Provider decorator applying the registry's AI policy
+pub struct ManagedProvider {
+    pub inner: Arc<dyn AiProvider>,
+    pub limiter: Arc<Mutex<RateLimiter>>,
+    pub cache: Arc<Mutex<ResponseCache>>,
+    pub policy: Arc<Mutex<AiSettings>>,
+}
+
+impl ManagedProvider {
+    fn policy(&self) -> AiSettings {
+        self.policy.lock().unwrap().clone()
+    }
+
+    async fn wait_for_slot(&self, per_minute: u32) {
+        loop {
+            let wait = self.limiter.lock().unwrap().try_acquire(per_minute, Instant::now());
+            match wait {
+                Some(wait) => {
+                    log::debug!("Rate limit reached for {}, waiting {:?}", self.inner.id(), wait);
+                    tokio::time::sleep(wait).await;
+                }
+                None => return,
+            }
+        }
+    }
+
+    fn cached(&self, key: &str, policy: &AiSettings) -> Option<String> {
+        if !policy.cache_enabled {
+            return None;
+        }
+        self.cache
+            .lock()
+            .unwrap()
+            .get(key, Duration::from_secs(policy.cache_ttl_secs))
+    }
+
+    fn store(&self, key: String, response: &str, policy: &AiSettings) {
+        if policy.cache_enabled {
+            self.cache
+                .lock()
+                .unwrap()
+                .insert(key, response.to_string(), policy.cache_max_entries);
+        }
+    }
+}
+
+#[async_trait]
+impl AiProvider for ManagedProvider {
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<String> {
+        let policy = self.policy();
+        let key = ResponseCache::key(self.inner.as_ref(), request);
+        if let Some(response) = self.cached(&key, &policy) {
+            return Ok(response);
+        }
+
+        let mut attempt = 0;
+        let response = loop {
+            self.wait_for_slot(policy.requests_per_minute).await;
+            match self.inner.complete(request).await {
+                Ok(response) => break response,
+                Err(e) if attempt < policy.max_retries && is_retryable(&e) => {
+                    log::warn!("AI request failed (attempt {}): {}", attempt + 1, e);
+                    tokio::time::sleep(backoff(attempt)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+
+        self.store(key, &response, &policy);
+        Ok(response)
+    }
+
+    async fn stream(
+        &self,
+        request: &CompletionRequest,
+        on_chunk: &(dyn Fn(&str) + Send + Sync),
+    ) -> Result<String> {
+        let policy = self.policy();
+        let key = ResponseCache::key(self.inner.as_ref(), request);
+        if let Some(response) = self.cached(&key, &policy) {
+            on_chunk(&response);
+            return Ok(response);
+        }
+
+        // Only retry while nothing has reached the caller
+        let emitted = AtomicBool::new(false);
+        let forward = |chunk: &str| {
+            emitted.store(true, Ordering::Relaxed);
+            on_chunk(chunk);
+        };
+
+        let mut attempt = 0;
+        let response = loop {
+            self.wait_for_slot(policy.requests_per_minute).await;
+            match self.inner.stream(request, &forward).await {
+                Ok(response) => break response,
+                Err(e)
+                    if attempt < policy.max_retries
+                        && !emitted.load(Ordering::Relaxed)
+                        && is_retryable(&e) =>
+                {
+                    log::warn!("AI stream failed (attempt {}): {}", attempt + 1, e);
+                    tokio::time::sleep(backoff(attempt)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+
+        self.store(key, &response, &policy);
+        Ok(response)
+    }
+
+    async fn embed(&self, input: &str) -> Result<Vec<f32>> {
+        let policy = self.policy();
+        let mut attempt = 0;
+        loop {
+            self.wait_for_slot(policy.requests_per_minute).await;
+            match self.inner.embed(input).await {
+                Ok(embedding) => return Ok(embedding),
+                Err(e) if attempt < policy.max_retries && is_retryable(&e) => {
+                    tokio::time::sleep(backoff(attempt)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_window() {
+        let mut limiter = RateLimiter::new();
+        let now = Instant::now();
+        assert!(limiter.try_acquire(2, now).is_none());
+        assert!(limiter.try_acquire(2, now).is_none());
+        assert!(limiter.try_acquire(2, now).is_some());
+        assert!(limiter.try_acquire(2, now + Duration::from_secs(61)).is_none());
+    }
+
+    #[test]
+    fn test_cache_evicts_oldest() {
+        let mut cache = ResponseCache::new();
+        cache.insert("a".to_string(), "1".to_string(), 2);
+        cache.insert("b".to_string(), "2".to_string(), 2);
+        cache.insert("c".to_string(), "3".to_string(), 2);
+        let ttl = Duration::from_secs(60);
+        assert_eq!(cache.get("a", ttl), None);
+        assert_eq!(cache.get("c", ttl), Some("3".to_string()));
+        assert_eq!(cache.stats().entries, 2);
+    }
+}
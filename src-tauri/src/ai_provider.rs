@@ -0,0 +1,585 @@
+// AI Provider - Pluggable model backends for AI features
+// Local-first (Ollama) with OpenAI-compatible endpoints as fallback
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::ai_middleware::{CacheStats, ManagedProvider, RateLimiter, ResponseCache};
+use crate::secrets;
+use crate::settings::AiSettings;
+
+/// Role of a chat message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChatMessage {
+    pub role: Role,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self { role: Role::System, content: content.into() }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self { role: Role::User, content: content.into() }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self { role: Role::Assistant, content: content.into() }
+    }
+}
+
+/// A single completion request sent to a provider
+#[derive(Clone, Debug)]
+pub struct CompletionRequest {
+    pub messages: Vec<ChatMessage>,
+    pub temperature: f32,
+    pub max_tokens: Option<usize>,
+    /// Ask the model to answer with a JSON document
+    pub json_mode: bool,
+}
+
+impl CompletionRequest {
+    pub fn new(messages: Vec<ChatMessage>) -> Self {
+        Self {
+            messages,
+            temperature: 0.2,
+            max_tokens: None,
+            json_mode: false,
+        }
+    }
+
+    pub fn json(mut self) -> Self {
+        self.json_mode = true;
+        self
+    }
+}
+
+/// Common interface of all model backends
+#[async_trait]
+pub trait AiProvider: Send + Sync {
+    /// Registry identifier (e.g. "ollama")
+    fn id(&self) -> &str;
+
+    /// Model used for chat completions
+    fn model(&self) -> &str;
+
+    /// Run a completion and return the full response text
+    async fn complete(&self, request: &CompletionRequest) -> Result<String>;
+
+    /// Run a completion, reporting partial output through `on_chunk`
+    async fn stream(
+        &self,
+        request: &CompletionRequest,
+        on_chunk: &(dyn Fn(&str) + Send + Sync),
+    ) -> Result<String> {
+        let text = self.complete(request).await?;
+        on_chunk(&text);
+        Ok(text)
+    }
+
+    /// Compute an embedding vector for the input
+    async fn embed(&self, _input: &str) -> Result<Vec<f32>> {
+        Err(anyhow!("Provider '{}' does not support embeddings", self.id()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProviderInfo {
+    pub id: String,
+    pub model: String,
+    pub active: bool,
+}
+
+/// Registry of configured providers with one active provider.
+/// Providers are handed out wrapped in the rate limit/retry/cache middleware.
+pub struct ProviderRegistry {
+    providers: HashMap<String, Arc<dyn AiProvider>>,
+    active: Option<String>,
+    limiters: HashMap<String, Arc<Mutex<RateLimiter>>>,
+    cache: Arc<Mutex<ResponseCache>>,
+    policy: Arc<Mutex<AiSettings>>,
+}
+
+impl ProviderRegistry {
+    pub fn new() -> Self {
+        Self {
+            providers: HashMap::new(),
+            active: None,
+            limiters: HashMap::new(),
+            cache: Arc::new(Mutex::new(ResponseCache::new())),
+            policy: Arc::new(Mutex::new(AiSettings::default())),
+        }
+    }
+
+    /// Build the default registry from environment and vault configuration
+    pub fn from_env() -> Self {
+        let mut registry = Self::new();
+
+        registry.register(Arc::new(OllamaProvider::new(
+            &env_or("OLLAMA_BASE_URL", "http://localhost:11434"),
+            &env_or("OLLAMA_CHAT_MODEL", "qwen3-coder:30b"),
+            &env_or("OLLAMA_EMBEDDING_MODEL", "nomic-embed-text"),
+        )));
+        registry.refresh_credentials();
+
+        registry
+    }
+
+    /// Re-register providers that need an API key after the vault changed
+    pub fn refresh_credentials(&mut self) {
+        let was_active = self.active.as_deref() == Some("openai");
+        self.unregister("openai");
+
+        if let Some(api_key) = configured_api_key("openai", "OPENAI_API_KEY") {
+            self.register(Arc::new(OpenAiProvider::new(
+                &env_or("OPENAI_BASE_URL", "https://api.openai.com/v1"),
+                &env_or("OPENAI_CHAT_MODEL", "gpt-4o-mini"),
+                &env_or("OPENAI_EMBEDDING_MODEL", "text-embedding-3-small"),
+                Some(api_key),
+            )));
+            if was_active {
+                self.active = Some("openai".to_string());
+            }
+        }
+    }
+
+    /// Remove a provider; the active provider falls back to the first remaining one
+    pub fn unregister(&mut self, id: &str) {
+        self.providers.remove(id);
+        if self.active.as_deref() == Some(id) {
+            self.active = self.providers.keys().min().cloned();
+        }
+    }
+
+    /// Register a provider; the first registered provider becomes active
+    pub fn register(&mut self, provider: Arc<dyn AiProvider>) {
+        let id = provider.id().to_string();
+        if self.active.is_none() {
+            self.active = Some(id.clone());
+        }
+        self.limiters
+            .entry(id.clone())
+            .or_insert_with(|| Arc::new(Mutex::new(RateLimiter::new())));
+        self.providers.insert(id, provider);
+    }
+
+    pub fn set_active(&mut self, id: &str) -> Result<()> {
+        if !self.providers.contains_key(id) {
+            return Err(anyhow!("Unknown AI provider: {}", id));
+        }
+        self.active = Some(id.to_string());
+        Ok(())
+    }
+
+    /// Get a provider wrapped in the AI middleware
+    pub fn get(&self, id: &str) -> Option<Arc<dyn AiProvider>> {
+        let inner = self.providers.get(id)?.clone();
+        let limiter = self
+            .limiters
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| Arc::new(Mutex::new(RateLimiter::new())));
+        Some(Arc::new(ManagedProvider {
+            inner,
+            limiter,
+            cache: self.cache.clone(),
+            policy: self.policy.clone(),
+        }))
+    }
+
+    /// Apply rate limit, retry and cache settings
+    pub fn set_policy(&self, policy: AiSettings) {
+        if !policy.cache_enabled {
+            self.cache.lock().unwrap().clear();
+        }
+        *self.policy.lock().unwrap() = policy;
+    }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.lock().unwrap().stats()
+    }
+
+    pub fn clear_cache(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    /// Get the active provider
+    pub fn active(&self) -> Result<Arc<dyn AiProvider>> {
+        self.active
+            .as_ref()
+            .and_then(|id| self.get(id))
+            .ok_or_else(|| anyhow!("No AI provider configured"))
+    }
+
+    pub fn list(&self) -> Vec<ProviderInfo> {
+        let mut infos: Vec<ProviderInfo> = self
+            .providers
+            .values()
+            .map(|p| ProviderInfo {
+                id: p.id().to_string(),
+                model: p.model().to_string(),
+                active: self.active.as_deref() == Some(p.id()),
+            })
+            .collect();
+        infos.sort_by(|a, b| a.id.cmp(&b.id));
+        infos
+    }
+}
+
+/// API key of a provider: secrets vault first, environment variable as fallback
+fn configured_api_key(provider_id: &str, env_key: &str) -> Option<String> {
+    match secrets::get(&secrets::provider_key(provider_id)) {
+        Ok(Some(key)) => return Some(key),
+        Ok(None) => {}
+        Err(e) => log::warn!("Secrets vault unavailable: {}", e),
+    }
+    std::env::var(env_key).ok().filter(|k| !k.is_empty())
+}
+
+fn env_or(key: &str, default: &str) -> String {
+    std::env::var(key).unwrap_or_else(|_| default.to_string())
+}
+
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(300))
+        .build()
+        .unwrap_or_default()
+}
+
+/// Split buffered stream bytes into complete lines, keeping the remainder.
+/// Decoding waits for the newline so characters split across chunks stay intact.
+fn drain_lines(buffer: &mut Vec<u8>) -> Vec<String> {
+    let mut lines = Vec::new();
+    while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
+        let line: Vec<u8> = buffer.drain(..=pos).collect();
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim();
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+// ==================== OLLAMA ====================
+
+/// Ollama backend (local models, default for Mimiverse)
+pub struct OllamaProvider {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    embedding_model: String,
+}
+
+impl OllamaProvider {
+    pub fn new(base_url: &str, model: &str, embedding_model: &str) -> Self {
+        Self {
+            client: http_client(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            embedding_model: embedding_model.to_string(),
+        }
+    }
+
+    fn body(&self, request: &CompletionRequest, stream: bool) -> Value {
+        let mut options = json!({ "temperature": request.temperature });
+        if let Some(max_tokens) = request.max_tokens {
+            options["num_predict"] = json!(max_tokens);
+        }
+
+        let mut body = json!({
+            "model": self.model,
+            "messages": request.messages,
+            "stream": stream,
+            "options": options,
+        });
+        if request.json_mode {
+            body["format"] = json!("json");
+        }
+        body
+    }
+}
+
+#[async_trait]
+impl AiProvider for OllamaProvider {
+    fn id(&self) -> &str {
+        "ollama"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<String> {
+        let response: Value = self
+            .client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&self.body(request, false))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        response["message"]["content"]
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| anyhow!("Malformed Ollama response"))
+    }
+
+    async fn stream(
+        &self,
+        request: &CompletionRequest,
+        on_chunk: &(dyn Fn(&str) + Send + Sync),
+    ) -> Result<String> {
+        let mut response = self
+            .client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&self.body(request, true))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        // Ollama streams newline-delimited JSON objects
+        let mut buffer = Vec::new();
+        let mut text = String::new();
+        while let Some(chunk) = response.chunk().await? {
+            buffer.extend_from_slice(&chunk);
+            for line in drain_lines(&mut buffer) {
+                let event: Value = serde_json::from_str(&line)?;
+                if let Some(content) = event["message"]["content"].as_str() {
+                    if !content.is_empty() {
+                        on_chunk(content);
+                        text.push_str(content);
+                    }
+                }
+            }
+        }
+
+        Ok(text)
+    }
+
+    async fn embed(&self, input: &str) -> Result<Vec<f32>> {
+        let response: Value = self
+            .client
+            .post(format!("{}/api/embeddings", self.base_url))
+            .json(&json!({ "model": self.embedding_model, "prompt": input }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        parse_vector(&response["embedding"])
+    }
+}
+
+// ==================== OPENAI-COMPATIBLE ====================
+
+/// OpenAI-compatible backend (OpenAI, vLLM, LM Studio, Triton frontends)
+pub struct OpenAiProvider {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    embedding_model: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiProvider {
+    pub fn new(base_url: &str, model: &str, embedding_model: &str, api_key: Option<String>) -> Self {
+        Self {
+            client: http_client(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            embedding_model: embedding_model.to_string(),
+            api_key,
+        }
+    }
+
+    fn post(&self, endpoint: &str) -> reqwest::RequestBuilder {
+        let builder = self.client.post(format!("{}/{}", self.base_url, endpoint));
+        match &self.api_key {
+            Some(key) => builder.bearer_auth(key),
+            None => builder,
+        }
+    }
+
+    fn body(&self, request: &CompletionRequest, stream: bool) -> Value {
+        let mut body = json!({
+            "model": self.model,
+            "messages": request.messages,
+            "temperature": request.temperature,
+            "stream": stream,
+        });
+        if let Some(max_tokens) = request.max_tokens {
+            body["max_tokens"] = json!(max_tokens);
+        }
+        if request.json_mode {
+            body["response_format"] = json!({ "type": "json_object" });
+        }
+        body
+    }
+}
+
+#[async_trait]
+impl AiProvider for OpenAiProvider {
+    fn id(&self) -> &str {
+        "openai"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<String> {
+        let response: Value = self
+            .post("chat/completions")
+            .json(&self.body(request, false))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        response["choices"][0]["message"]["content"]
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| anyhow!("Malformed completion response"))
+    }
+
+    async fn stream(
+        &self,
+        request: &CompletionRequest,
+        on_chunk: &(dyn Fn(&str) + Send + Sync),
+    ) -> Result<String> {
+        let mut response = self
+            .post("chat/completions")
+            .json(&self.body(request, true))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        // Server-sent events: `data: {...}` lines terminated by `data: [DONE]`
+        let mut buffer = Vec::new();
+        let mut text = String::new();
+        while let Some(chunk) = response.chunk().await? {
+            buffer.extend_from_slice(&chunk);
+            for line in drain_lines(&mut buffer) {
+                let data = match line.strip_prefix("data:") {
+                    Some(data) => data.trim(),
+                    None => continue,
+                };
+                if data == "[DONE]" {
+                    return Ok(text);
+                }
+                let event: Value = serde_json::from_str(data)?;
+                if let Some(content) = event["choices"][0]["delta"]["content"].as_str() {
+                    on_chunk(content);
+                    text.push_str(content);
+                }
+            }
+        }
+
+        Ok(text)
+    }
+
+    async fn embed(&self, input: &str) -> Result<Vec<f32>> {
+        let response: Value = self
+            .post("embeddings")
+            .json(&json!({ "model": self.embedding_model, "input": input }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        parse_vector(&response["data"][0]["embedding"])
+    }
+}
+
+fn parse_vector(value: &Value) -> Result<Vec<f32>> {
+    value
+        .as_array()
+        .map(|values| values.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect())
+        .ok_or_else(|| anyhow!("Malformed embedding response"))
+}
+
+/// Extract the JSON document from a model response
+/// (models often wrap JSON in markdown fences or prose)
+pub fn extract_json(text: &str) -> Option<&str> {
+    let start = text.find(|c: char| c == '{' || c == '[')?;
+    let close = if text[start..].starts_with('{') { '}' } else { ']' };
+    let end = text.rfind(close)?;
+    if end < start {
+        return None;
+    }
+    Some(&text[start..=end])
+}
+
+/// Extract the first fenced code block from a model response
+/// (falls back to the whole trimmed response)
+pub fn extract_code_block(text: &str) -> String {
+    if let Some(start) = text.find("```") {
+        let after_fence = &text[start + 3..];
+        // Skip the language tag line
+        let body_start = after_fence.find('\n').map(|i| i + 1).unwrap_or(0);
+        let body = &after_fence[body_start..];
+        let body_end = body.find("```").unwrap_or(body.len());
+        return body[..body_end].trim_end_matches('\n').to_string();
+    }
+    text.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_json_from_fenced_response() {
+        let text = "Here you go:\n```json\n{\"a\": [1, 2]}\n```";
+        assert_eq!(extract_json(text), Some("{\"a\": [1, 2]}"));
+        assert_eq!(extract_json("no json here"), None);
+    }
+
+    #[test]
+    fn test_extract_code_block() {
+        let response = "Sure:\n```rust\nlet y = 2;\n```\nDone.";
+        assert_eq!(extract_code_block(response), "let y = 2;");
+        assert_eq!(extract_code_block("  let z = 3;  "), "let z = 3;");
+    }
+
+    #[test]
+    fn test_registry_first_provider_is_active() {
+        let mut registry = ProviderRegistry::new();
+        assert!(registry.active().is_err());
+        registry.register(Arc::new(OllamaProvider::new("http://localhost:11434", "m", "e")));
+        assert_eq!(registry.active().unwrap().id(), "ollama");
+        assert!(registry.set_active("missing").is_err());
+    }
+
+    #[test]
+    fn test_drain_lines_keeps_split_characters() {
+        let bytes = "{\"content\": \"héllo\"}\n{\"content\"".as_bytes();
+        let split = bytes.iter().position(|&b| b >= 0x80).unwrap() + 1;
+        let mut buffer = bytes[..split].to_vec();
+        assert!(drain_lines(&mut buffer).is_empty());
+        buffer.extend_from_slice(&bytes[split..]);
+        assert_eq!(drain_lines(&mut buffer), vec!["{\"content\": \"héllo\"}"]);
+        assert_eq!(buffer, b"{\"content\"");
+    }
+}
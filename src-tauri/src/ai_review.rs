@@ -0,0 +1,160 @@
+// AI Review - Model-assisted code review over a diff
+// Combines the changeset with dependency graph context
+
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ai_provider::{self, AiProvider, ChatMessage, CompletionRequest};
+use crate::git::FileDiff;
+use crate::mimi_engine::CodeGraph;
+use crate::tokens::ContextBudget;
+use crate::CodeSuggestion;
+
+/// Diagnostics source for review comments
+pub const SOURCE: &str = "ai-review";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReviewComment {
+    /// File path relative to the workspace
+    pub file: String,
+    pub line: usize,
+    #[serde(default = "default_severity")]
+    pub severity: String,
+    pub message: String,
+    #[serde(default)]
+    pub suggestion: Option<String>,
+}
+
+fn default_severity() -> String {
+    "info".to_string()
+}
+
+impl ReviewComment {
+    pub fn to_suggestion(&self) -> CodeSuggestion {
+        CodeSuggestion {
+            kind: "review".to_string(),
+            message: self.message.clone(),
+            line: self.line,
+            column: 0,
+            severity: self.severity.clone(),
+            fix: self.suggestion.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ReviewResponse {
+    #[serde(default)]
+    comments: Vec<ReviewComment>,
+}
+
+/// Describe how the changed files are wired into the workspace
+pub fn graph_context(graph: &CodeGraph, workspace: &Path, files: &[FileDiff]) -> String {
+    let mut context = String::new();
+
+    for file in files {
+        let absolute = workspace.join(&file.path).to_string_lossy().to_string();
+        let relative = |p: &String| {
+            Path::new(p)
+                .strip_prefix(workspace)
+                .map(|r| r.to_string_lossy().to_string())
+                .unwrap_or_else(|_| p.clone())
+        };
+
+        let deps: Vec<String> = graph.get_dependencies(&absolute).iter().map(relative).collect();
+        let dependents: Vec<String> = graph.get_dependents(&absolute).iter().map(relative).collect();
+
+        if deps.is_empty() && dependents.is_empty() {
+            continue;
+        }
+
+        context.push_str(&format!("{}:\n", file.path));
+        if !deps.is_empty() {
+            context.push_str(&format!("  imports: {}\n", deps.join(", ")));
+        }
+        if !dependents.is_empty() {
+            context.push_str(&format!("  imported by: {}\n", dependents.join(", ")));
+        }
+    }
+
+    context
+}
+
+fn build_prompt(diff: &str, context: &str, model: &str) -> Vec<ChatMessage> {
+    let system = "You are a senior code reviewer. Review the diff for bugs, security issues, \
+        performance problems and unclear code. Only comment on changed lines. \
+        Respond with JSON only: {\"comments\": [{\"file\": \"path\", \"line\": <new line number>, \
+        \"severity\": \"error|warning|info\", \"message\": \"...\", \"suggestion\": \"replacement code or null\"}]}. \
+        Return an empty list if the changes look good.";
+
+    // The diff has priority over graph context when the window is tight
+    let mut budget = ContextBudget::for_model(model);
+    budget.try_add(system);
+    let diff = budget.add_truncated(diff);
+    let context = budget.add_truncated(context);
+
+    let mut user = String::new();
+    if !context.is_empty() {
+        user.push_str("Dependency context of the changed files:\n");
+        user.push_str(&context);
+        user.push('\n');
+    }
+    user.push_str("Diff:\n");
+    user.push_str(&diff);
+
+    vec![ChatMessage::system(system), ChatMessage::user(user)]
+}
+
+/// Parse the model response into review comments
+pub fn parse_review(text: &str, files: &[FileDiff]) -> Result<Vec<ReviewComment>> {
+    let json = ai_provider::extract_json(text).ok_or_else(|| anyhow!("Review response contained no JSON"))?;
+
+    let comments = if json.starts_with('[') {
+        serde_json::from_str::<Vec<ReviewComment>>(json)?
+    } else {
+        serde_json::from_str::<ReviewResponse>(json)?.comments
+    };
+
+    // Drop comments on files that are not part of the diff
+    Ok(comments
+        .into_iter()
+        .filter(|c| files.iter().any(|f| f.path == c.file))
+        .map(|mut c| {
+            c.severity = match c.severity.to_lowercase().as_str() {
+                "error" | "warning" | "info" => c.severity.to_lowercase(),
+                _ => default_severity(),
+            };
+            c
+        })
+        .collect())
+}
+
+/// Ask the provider to review a diff
+pub async fn review_changes(
+    provider: &dyn AiProvider,
+    diff: &str,
+    files: &[FileDiff],
+    context: &str,
+) -> Result<Vec<ReviewComment>> {
+    let request = CompletionRequest::new(build_prompt(diff, context, provider.model())).json();
+    let response = provider.complete(&request).await?;
+    parse_review(&response, files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_review_filters_unknown_files() {
+        let files = vec![FileDiff { path: "src/a.ts".to_string(), hunks: Vec::new() }];
+        let text = r#"{"comments": [
+            {"file": "src/a.ts", "line": 3, "severity": "WARNING", "message": "possible null"},
+            {"file": "src/other.ts", "line": 1, "message": "not in diff"}
+        ]}"#;
+        let comments = parse_review(text, &files).unwrap();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].severity, "warning");
+    }
+}
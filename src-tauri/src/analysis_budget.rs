@@ -0,0 +1,83 @@
+// Analysis Budget - Per-workspace limits that keep one pathological file from stalling analysis
+// Oversized files are skipped and slow rules abandoned; the offenders are listed as slow files
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+/// Limits of analyzing one file; 0 means unlimited
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AnalysisBudget {
+    /// Larger files are not analyzed at all
+    pub max_file_bytes: usize,
+    pub max_file_lines: usize,
+    /// Time for all rules on one file; rules not started by then are skipped
+    pub file_time_ms: u64,
+    /// Time after which one rule is abandoned and the file keeps the other rules' findings
+    pub rule_timeout_ms: u64,
+}
+
+impl Default for AnalysisBudget {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 2 * 1024 * 1024,
+            max_file_lines: 50_000,
+            file_time_ms: 2_000,
+            rule_timeout_ms: 1_000,
+        }
+    }
+}
+
+impl AnalysisBudget {
+    pub fn too_large(&self, bytes: usize, lines: usize) -> bool {
+        let over = |value: usize, limit: usize| limit > 0 && value > limit;
+        over(bytes, self.max_file_bytes) || over(lines, self.max_file_lines)
+    }
+
+    pub fn file_time(&self) -> Option<Duration> {
+        (self.file_time_ms > 0).then(|| Duration::from_millis(self.file_time_ms))
+    }
+
+    pub fn rule_timeout(&self) -> Option<Duration> {
+        (self.rule_timeout_ms > 0).then(|| Duration::from_millis(self.rule_timeout_ms))
+    }
+}
+
+fn budget_path(workspace: &Path) -> PathBuf {
+    storage::data_dir(workspace).join("analysis_budget.json")
+}
+
+pub fn load(workspace: &Path) -> Result<AnalysisBudget> {
+    Ok(storage::read_json(&budget_path(workspace))?.unwrap_or_default())
+}
+
+pub fn save(workspace: &Path, budget: &AnalysisBudget) -> Result<()> {
+    storage::write_json(&budget_path(workspace), budget)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_defaults_and_limits() {
+        let workspace = std::env::temp_dir().join(storage::new_id("budget-test"));
+        assert_eq!(load(&workspace).unwrap(), AnalysisBudget::default());
+
+        let budget = AnalysisBudget {
+            max_file_lines: 0,
+            rule_timeout_ms: 0,
+            ..AnalysisBudget::default()
+        };
+        save(&workspace, &budget).unwrap();
+        let loaded = load(&workspace).unwrap();
+        assert!(loaded.rule_timeout().is_none());
+        assert!(!loaded.too_large(1024, 1_000_000));
+        assert!(loaded.too_large(3 * 1024 * 1024, 10));
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+}
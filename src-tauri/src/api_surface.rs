@@ -0,0 +1,239 @@
+// API Surface - Exported symbols of a package and breaking changes against a git ref
+// Computed from the symbol table; a git ref is re-extracted file by file from `git show`
+
+use std::collections::HashMap;
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::git;
+use crate::symbols::{self, SymbolInfo, SymbolKind};
+
+/// Files the symbol extractor understands, as in the dependency graph
+const SOURCE_EXTENSIONS: [&str; 6] = ["ts", "tsx", "js", "jsx", "rs", "py"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ApiSymbol {
+    pub qualified_name: String,
+    pub name: String,
+    pub kind: SymbolKind,
+    pub container: Option<String>,
+    /// Relative to the workspace
+    pub file: String,
+    pub line: usize,
+    /// Declaration line without its body opener
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiChangeKind {
+    Added,
+    Removed,
+    Renamed,
+    SignatureChanged,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiChange {
+    pub kind: ApiChangeKind,
+    /// The name at the ref for removals and renames, the current one otherwise
+    pub qualified_name: String,
+    pub breaking: bool,
+    pub before: Option<ApiSymbol>,
+    pub after: Option<ApiSymbol>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiDiff {
+    pub package: String,
+    pub base: String,
+    pub changes: Vec<ApiChange>,
+    pub breaking_count: usize,
+}
+
+/// Workspace-relative path below `package`; an empty package is the whole workspace
+fn in_package(relative: &str, package: &str) -> bool {
+    let package = package.trim_start_matches("./").trim_end_matches('/');
+    package.is_empty()
+        || relative == package
+        || relative.strip_prefix(package).is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn is_source(relative: &str) -> bool {
+    let extension = Path::new(relative).extension().and_then(|e| e.to_str()).unwrap_or("");
+    SOURCE_EXTENSIONS.contains(&extension) && !relative.contains("node_modules")
+}
+
+/// Collapse whitespace and drop the `{`, `:` or `;` that opens or ends the declaration
+fn normalize_signature(detail: &str) -> String {
+    let collapsed = detail.split_whitespace().collect::<Vec<_>>().join(" ");
+    collapsed.trim_end_matches(['{', ':', ';']).trim_end().to_string()
+}
+
+/// Exported and visible outside its crate: `pub(crate)` and friends are internal
+fn is_public(symbol: &SymbolInfo) -> bool {
+    symbol.exported && !(symbol.file.ends_with(".rs") && symbol.detail.contains("pub("))
+}
+
+fn to_api(workspace: &Path, symbol: &SymbolInfo) -> ApiSymbol {
+    let file = Path::new(&symbol.file);
+    ApiSymbol {
+        qualified_name: symbol.qualified_name.clone(),
+        name: symbol.name.clone(),
+        kind: symbol.kind,
+        container: symbol.container.clone(),
+        file: file.strip_prefix(workspace).unwrap_or(file).to_string_lossy().replace('\\', "/"),
+        line: symbol.line,
+        signature: normalize_signature(&symbol.detail),
+    }
+}
+
+/// Public symbols of `package` among `symbols`, by file and line
+pub fn surface<'a>(workspace: &Path, package: &str, symbols: impl IntoIterator<Item = &'a SymbolInfo>) -> Vec<ApiSymbol> {
+    let mut api: Vec<ApiSymbol> = symbols
+        .into_iter()
+        .filter(|s| is_public(s))
+        .map(|s| to_api(workspace, s))
+        .filter(|s| in_package(&s.file, package))
+        .collect();
+    api.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    api
+}
+
+/// Public symbols of `package` as of the git revision `rev`
+fn at_ref(workspace: &Path, rev: &str, package: &str) -> Result<Vec<ApiSymbol>> {
+    let rev = git::revision(rev)?;
+    git::run(workspace, &["rev-parse", "--verify", "--quiet", "--end-of-options", &format!("{}^{{commit}}", rev)])
+        .map_err(|_| anyhow!("Not a git ref: {}", rev))?;
+    let tree = git::run(workspace, &["ls-tree", "-r", "-z", "--name-only", "--end-of-options", rev])?;
+    let mut extracted = Vec::new();
+    for relative in tree.split('\0').filter(|p| is_source(p) && in_package(p, package)) {
+        let content = match git::run(workspace, &["show", "--end-of-options", &format!("{}:{}", rev, relative)]) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        // Extract as if checked out, so qualified names line up with the working tree
+        extracted.extend(symbols::extract(workspace, &workspace.join(relative), &content));
+    }
+    Ok(surface(workspace, package, &extracted))
+}
+
+/// The signature with the symbol's own name blanked, for spotting renames
+fn shape(symbol: &ApiSymbol) -> (SymbolKind, &Option<String>, &str, String) {
+    (symbol.kind, &symbol.container, symbol.file.as_str(), symbol.signature.replacen(&symbol.name, "_", 1))
+}
+
+/// Changes from `base` to `current`: removals, renames and signature changes break callers
+fn diff(base: &[ApiSymbol], current: &[ApiSymbol]) -> Vec<ApiChange> {
+    let before: HashMap<&str, &ApiSymbol> = base.iter().map(|s| (s.qualified_name.as_str(), s)).collect();
+    let after: HashMap<&str, &ApiSymbol> = current.iter().map(|s| (s.qualified_name.as_str(), s)).collect();
+    let mut changes = Vec::new();
+    let mut added: Vec<&ApiSymbol> = current.iter().filter(|s| !before.contains_key(s.qualified_name.as_str())).collect();
+
+    for old in base {
+        match after.get(old.qualified_name.as_str()) {
+            Some(new) if new.signature != old.signature || new.kind != old.kind => changes.push(ApiChange {
+                kind: ApiChangeKind::SignatureChanged,
+                qualified_name: old.qualified_name.clone(),
+                breaking: true,
+                before: Some(old.clone()),
+                after: Some((*new).clone()),
+            }),
+            Some(_) => {}
+            None => {
+                // A single added symbol of the same shape in the same place is taken as the new name
+                let candidates: Vec<usize> = (0..added.len()).filter(|&i| shape(added[i]) == shape(old)).collect();
+                let renamed = if candidates.len() == 1 { Some(added.remove(candidates[0])) } else { None };
+                changes.push(ApiChange {
+                    kind: if renamed.is_some() { ApiChangeKind::Renamed } else { ApiChangeKind::Removed },
+                    qualified_name: old.qualified_name.clone(),
+                    breaking: true,
+                    before: Some(old.clone()),
+                    after: renamed.cloned(),
+                });
+            }
+        }
+    }
+    changes.extend(added.into_iter().map(|new| ApiChange {
+        kind: ApiChangeKind::Added,
+        qualified_name: new.qualified_name.clone(),
+        breaking: false,
+        before: None,
+        after: Some(new.clone()),
+    }));
+    changes
+}
+
+/// Diff the `current` surface of `package` against its surface at `rev`
+pub fn diff_against(workspace: &Path, rev: &str, package: &str, current: &[ApiSymbol]) -> Result<ApiDiff> {
+    let changes = diff(&at_ref(workspace, rev, package)?, current);
+    Ok(ApiDiff {
+        package: package.to_string(),
+        base: rev.to_string(),
+        breaking_count: changes.iter().filter(|c| c.breaking).count(),
+        changes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api(qualified_name: &str, signature: &str) -> ApiSymbol {
+        let name = qualified_name.rsplit("::").next().unwrap().to_string();
+        ApiSymbol {
+            qualified_name: qualified_name.to_string(),
+            name,
+            kind: SymbolKind::Function,
+            container: None,
+            file: "src/lib.rs".to_string(),
+            line: 1,
+            signature: signature.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff_flags_breaking_changes() {
+        let base = vec![
+            api("crate::open", "pub fn open(path: &Path) -> Result<File>"),
+            api("crate::close", "pub fn close(file: File)"),
+            api("crate::flush", "pub fn flush(file: &File)"),
+            api("crate::sync", "pub fn sync()"),
+        ];
+        let current = vec![
+            api("crate::open", "pub fn open(path: &Path, mode: Mode) -> Result<File>"),
+            api("crate::shut", "pub fn shut(file: File)"),
+            api("crate::flush", "pub fn flush(file: &File)"),
+            api("crate::stat", "pub fn stat(path: &Path) -> Metadata"),
+        ];
+        let changes = diff(&base, &current);
+        let summary: Vec<(ApiChangeKind, &str, bool)> =
+            changes.iter().map(|c| (c.kind, c.qualified_name.as_str(), c.breaking)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ApiChangeKind::SignatureChanged, "crate::open", true),
+                (ApiChangeKind::Renamed, "crate::close", true),
+                (ApiChangeKind::Removed, "crate::sync", true),
+                (ApiChangeKind::Added, "crate::stat", false),
+            ]
+        );
+        assert_eq!(changes[1].after.as_ref().map(|s| s.name.as_str()), Some("shut"));
+    }
+
+    #[test]
+    fn test_package_prefix_and_signature_normalization() {
+        assert!(in_package("packages/core/src/index.ts", "./packages/core/"));
+        assert!(!in_package("packages/core-utils/index.ts", "packages/core"));
+        assert!(in_package("src/main.rs", ""));
+        assert_eq!(normalize_signature("pub fn new()  -> Self {"), "pub fn new() -> Self");
+        assert_eq!(normalize_signature("def save(self):"), "def save(self)");
+    }
+
+    #[test]
+    fn test_option_like_ref_is_refused() {
+        let err = at_ref(&std::env::temp_dir(), "--output=/tmp/x", "").unwrap_err();
+        assert!(err.to_string().contains("Invalid revision"));
+    }
+}
@@ -0,0 +1,324 @@
+// Asset Metadata - Header-level facts about binary assets
+// Image dimensions, font family names and audio durations without decoding
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Bytes read for header-based formats
+const HEADER_BYTES: u64 = 64 * 1024;
+/// Fonts keep their name table anywhere in the file
+const MAX_FONT_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AssetMetadata {
+    Image {
+        format: String,
+        width: Option<u32>,
+        height: Option<u32>,
+    },
+    Font {
+        format: String,
+        family: Option<String>,
+    },
+    Audio {
+        format: String,
+        duration_secs: Option<f64>,
+        sample_rate: Option<u32>,
+        channels: Option<u16>,
+    },
+}
+
+/// Whether `extension` is an asset type with extractable metadata
+pub fn is_asset(extension: &str) -> bool {
+    matches!(
+        extension.to_lowercase().as_str(),
+        "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "svg" | "ico"
+            | "ttf" | "otf" | "woff" | "woff2"
+            | "wav" | "flac" | "mp3" | "ogg" | "m4a"
+    )
+}
+
+fn read_prefix(path: &Path, limit: u64) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    File::open(path)?.take(limit).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn be_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn be_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Extract metadata for an asset file, `None` for unsupported types
+pub fn extract(path: &Path) -> Result<Option<AssetMetadata>> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if !is_asset(&extension) {
+        return Ok(None);
+    }
+
+    let metadata = match extension.as_str() {
+        "ttf" | "otf" => {
+            let bytes = read_prefix(path, MAX_FONT_BYTES)?;
+            AssetMetadata::Font {
+                format: extension.clone(),
+                family: font_family(&bytes),
+            }
+        }
+        // Compressed font containers; the family needs decompression
+        "woff" | "woff2" => AssetMetadata::Font {
+            format: extension.clone(),
+            family: None,
+        },
+        "wav" | "flac" | "mp3" | "ogg" | "m4a" => {
+            let bytes = read_prefix(path, HEADER_BYTES)?;
+            let (duration_secs, sample_rate, channels) = match extension.as_str() {
+                "wav" => wav_info(&bytes),
+                "flac" => flac_info(&bytes),
+                _ => None,
+            }
+            .map(|(duration, rate, channels)| (Some(duration), Some(rate), Some(channels)))
+            .unwrap_or((None, None, None));
+            AssetMetadata::Audio {
+                format: extension.clone(),
+                duration_secs,
+                sample_rate,
+                channels,
+            }
+        }
+        _ => {
+            let bytes = read_prefix(path, HEADER_BYTES)?;
+            let dimensions = match extension.as_str() {
+                "png" => png_dimensions(&bytes),
+                "jpg" | "jpeg" => jpeg_dimensions(&bytes),
+                "gif" => gif_dimensions(&bytes),
+                "webp" => webp_dimensions(&bytes),
+                "bmp" => bmp_dimensions(&bytes),
+                "svg" => svg_dimensions(&String::from_utf8_lossy(&bytes)),
+                _ => None,
+            };
+            AssetMetadata::Image {
+                format: if extension == "jpg" { "jpeg".to_string() } else { extension.clone() },
+                width: dimensions.map(|d| d.0),
+                height: dimensions.map(|d| d.1),
+            }
+        }
+    };
+    Ok(Some(metadata))
+}
+
+fn png_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if !bytes.starts_with(b"\x89PNG\r\n\x1a\n") || bytes.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    Some((be_u32(bytes, 16)?, be_u32(bytes, 20)?))
+}
+
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut at = 2;
+    while at + 9 < bytes.len() {
+        if bytes[at] != 0xFF {
+            return None;
+        }
+        let marker = bytes[at + 1];
+        let length = be_u16(bytes, at + 2)? as usize;
+        // SOF0..SOF15 except DHT (C4), JPG (C8) and DAC (CC)
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let height = be_u16(bytes, at + 5)? as u32;
+            let width = be_u16(bytes, at + 7)? as u32;
+            return Some((width, height));
+        }
+        at += 2 + length;
+    }
+    None
+}
+
+fn gif_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if !bytes.starts_with(b"GIF8") {
+        return None;
+    }
+    Some((le_u16(bytes, 6)? as u32, le_u16(bytes, 8)? as u32))
+}
+
+fn webp_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.get(0..4)? != b"RIFF" || bytes.get(8..12)? != b"WEBP" {
+        return None;
+    }
+    match bytes.get(12..16)? {
+        b"VP8 " => Some(((le_u16(bytes, 26)? & 0x3FFF) as u32, (le_u16(bytes, 28)? & 0x3FFF) as u32)),
+        b"VP8L" => {
+            let bits = le_u32(bytes, 21)?;
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+        }
+        b"VP8X" => {
+            let canvas = bytes.get(24..30)?;
+            let width = u32::from_le_bytes([canvas[0], canvas[1], canvas[2], 0]) + 1;
+            let height = u32::from_le_bytes([canvas[3], canvas[4], canvas[5], 0]) + 1;
+            Some((width, height))
+        }
+        _ => None,
+    }
+}
+
+fn bmp_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if !bytes.starts_with(b"BM") {
+        return None;
+    }
+    let width = le_u32(bytes, 18)? as i32;
+    let height = le_u32(bytes, 22)? as i32;
+    // Negative height marks a top-down bitmap
+    Some((width.unsigned_abs(), height.unsigned_abs()))
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!(" {}=", name);
+    let at = tag.find(&pattern)? + pattern.len();
+    let quote = tag[at..].chars().next()?;
+    let value = &tag[at + 1..];
+    Some(&value[..value.find(quote)?])
+}
+
+/// Width/height attributes of the root element, falling back to the viewBox
+fn svg_dimensions(text: &str) -> Option<(u32, u32)> {
+    let start = text.find("<svg")?;
+    let tag = &text[start..start + text[start..].find('>')?];
+    let number = |value: &str| -> Option<u32> {
+        let digits: String = value.chars().take_while(|c| c.is_ascii_digit() || *c == '.').collect();
+        digits.parse::<f64>().ok().map(|n| n.round() as u32)
+    };
+
+    if let (Some(width), Some(height)) = (attribute(tag, "width").and_then(number), attribute(tag, "height").and_then(number)) {
+        return Some((width, height));
+    }
+    let view_box: Vec<f64> = attribute(tag, "viewBox")?
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter_map(|v| v.parse().ok())
+        .collect();
+    match view_box.as_slice() {
+        [_, _, width, height] => Some((width.round() as u32, height.round() as u32)),
+        _ => None,
+    }
+}
+
+/// Family name (name ID 1) from the sfnt `name` table
+fn font_family(bytes: &[u8]) -> Option<String> {
+    let tables = be_u16(bytes, 4)? as usize;
+    let name_offset = (0..tables).find_map(|i| {
+        let record = 12 + i * 16;
+        if bytes.get(record..record + 4)? == b"name" {
+            be_u32(bytes, record + 8)
+        } else {
+            None
+        }
+    })? as usize;
+
+    let count = be_u16(bytes, name_offset + 2)? as usize;
+    let storage = name_offset + be_u16(bytes, name_offset + 4)? as usize;
+    let mut fallback = None;
+    for i in 0..count {
+        let record = name_offset + 6 + i * 12;
+        let platform = be_u16(bytes, record)?;
+        let name_id = be_u16(bytes, record + 6)?;
+        if name_id != 1 {
+            continue;
+        }
+        let length = be_u16(bytes, record + 8)? as usize;
+        let offset = storage + be_u16(bytes, record + 10)? as usize;
+        let raw = bytes.get(offset..offset + length)?;
+        match platform {
+            // Unicode / Windows: UTF-16BE
+            0 | 3 => {
+                let units: Vec<u16> = raw.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+                return Some(String::from_utf16_lossy(&units));
+            }
+            // Macintosh Roman, ASCII-compatible for typical names
+            1 => fallback = Some(String::from_utf8_lossy(raw).to_string()),
+            _ => {}
+        }
+    }
+    fallback
+}
+
+/// (duration, sample rate, channels) from a RIFF/WAVE header
+fn wav_info(bytes: &[u8]) -> Option<(f64, u32, u16)> {
+    if bytes.get(0..4)? != b"RIFF" || bytes.get(8..12)? != b"WAVE" {
+        return None;
+    }
+    let mut at = 12;
+    let mut format = None;
+    while at + 8 <= bytes.len() {
+        let id = &bytes[at..at + 4];
+        let size = le_u32(bytes, at + 4)? as usize;
+        match id {
+            b"fmt " => {
+                let channels = le_u16(bytes, at + 10)?;
+                let sample_rate = le_u32(bytes, at + 12)?;
+                let byte_rate = le_u32(bytes, at + 16)?;
+                format = Some((channels, sample_rate, byte_rate));
+            }
+            b"data" => {
+                let (channels, sample_rate, byte_rate) = format?;
+                if byte_rate == 0 {
+                    return None;
+                }
+                return Some((size as f64 / byte_rate as f64, sample_rate, channels));
+            }
+            _ => {}
+        }
+        // Chunks are padded to even sizes
+        at += 8 + size + (size & 1);
+    }
+    None
+}
+
+/// (duration, sample rate, channels) from the FLAC STREAMINFO block
+fn flac_info(bytes: &[u8]) -> Option<(f64, u32, u16)> {
+    if !bytes.starts_with(b"fLaC") {
+        return None;
+    }
+    // STREAMINFO is always the first metadata block
+    let info = bytes.get(8..26)?;
+    let sample_rate = (u32::from(info[10]) << 12) | (u32::from(info[11]) << 4) | (u32::from(info[12]) >> 4);
+    let channels = ((info[12] >> 1) & 0x07) as u16 + 1;
+    let total_samples = (u64::from(info[13] & 0x0F) << 32) | u64::from(be_u32(info, 14)?);
+    if sample_rate == 0 {
+        return None;
+    }
+    Some((total_samples as f64 / sample_rate as f64, sample_rate, channels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_headers() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.extend_from_slice(&640u32.to_be_bytes());
+        png.extend_from_slice(&480u32.to_be_bytes());
+        assert_eq!(png_dimensions(&png), Some((640, 480)));
+
+        let svg = r#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 16">"#;
+        assert_eq!(svg_dimensions(svg), Some((24, 16)));
+    }
+}
@@ -0,0 +1,209 @@
+// Audit Log - Append-only record of the file writes the engine performs
+// One JSON line per write in .mimiverse/audit.jsonl, with content hashes instead of content
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::changeset::{self, FilePreview};
+use crate::storage;
+use crate::transaction::Operation;
+
+/// Who a write is recorded for, decided by the code path that performed it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", content = "name", rename_all = "snake_case")]
+pub enum Actor {
+    User,
+    Agent,
+    Plugin(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileTouch {
+    pub path: String,
+    /// Target path for renames
+    pub new_path: Option<String>,
+    /// SHA-256 of the content before and after; `None` where the file did not or no longer exists
+    pub before_hash: Option<String>,
+    pub after_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AuditEntry {
+    pub id: String,
+    pub timestamp: u64,
+    pub actor: Actor,
+    /// What wrote, e.g. `changeset` or `transaction`
+    pub action: String,
+    pub description: String,
+    pub files: Vec<FileTouch>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct AuditFilter {
+    pub actor: Option<Actor>,
+    pub action: Option<String>,
+    /// Entries touching this path, as either side of a rename
+    pub path: Option<String>,
+    /// Milliseconds since the epoch, inclusive
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.actor.as_ref().is_none_or(|actor| *actor == entry.actor)
+            && self.action.as_ref().is_none_or(|action| *action == entry.action)
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp <= until)
+            && self.path.as_ref().is_none_or(|path| {
+                entry.files.iter().any(|file| file.path == *path || file.new_path.as_ref() == Some(path))
+            })
+    }
+}
+
+pub fn audit_path(workspace: &Path) -> PathBuf {
+    storage::data_dir(workspace).join("audit.jsonl")
+}
+
+fn hash(content: &str) -> String {
+    changeset::content_hash(content.as_bytes())
+}
+
+/// Touched files of a changeset, from the previews taken before it was applied
+pub fn from_previews(previews: &[FilePreview]) -> Vec<FileTouch> {
+    previews
+        .iter()
+        .map(|preview| FileTouch {
+            path: preview.path.clone(),
+            new_path: preview.new_path.clone(),
+            before_hash: preview.before.as_deref().map(hash),
+            after_hash: preview.after.as_deref().map(hash),
+        })
+        .collect()
+}
+
+/// Touched files of transaction operations; call before committing, while the old content is still on disk
+pub fn from_operations(workspace: &Path, operations: &[Operation]) -> Vec<FileTouch> {
+    let current = |path: &str| {
+        let bytes = fs::read(changeset::resolve(workspace, path)).ok()?;
+        Some(changeset::content_hash(&bytes))
+    };
+    operations
+        .iter()
+        .map(|operation| match operation {
+            Operation::Write { path, content } => FileTouch {
+                path: path.clone(),
+                new_path: None,
+                before_hash: current(path),
+                after_hash: Some(hash(content)),
+            },
+            Operation::Rename { from, to } => {
+                let moved = current(from);
+                FileTouch {
+                    path: from.clone(),
+                    new_path: Some(to.clone()),
+                    before_hash: moved.clone(),
+                    after_hash: moved,
+                }
+            }
+            Operation::Delete { path } => FileTouch {
+                path: path.clone(),
+                new_path: None,
+                before_hash: current(path),
+                after_hash: None,
+            },
+        })
+        .collect()
+}
+
+/// Append an entry; earlier entries are never rewritten
+pub fn record(
+    workspace: &Path,
+    actor: Actor,
+    action: &str,
+    description: &str,
+    files: Vec<FileTouch>,
+) -> Result<AuditEntry> {
+    let entry = AuditEntry {
+        id: storage::new_id("audit"),
+        timestamp: storage::now_millis(),
+        actor,
+        action: action.to_string(),
+        description: description.to_string(),
+        files,
+    };
+    let path = audit_path(workspace);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(&entry)?;
+    line.push('\n');
+    // One write per entry, so concurrent appends don't interleave
+    OpenOptions::new().create(true).append(true).open(&path)?.write_all(line.as_bytes())?;
+    Ok(entry)
+}
+
+/// Matching entries, newest first
+pub fn query(workspace: &Path, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+    let content = match fs::read_to_string(audit_path(workspace)) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let entries = content
+        .lines()
+        .rev()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str::<AuditEntry>(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                log::warn!("Skipping unreadable audit entry: {}", e);
+                None
+            }
+        })
+        .filter(|entry| filter.matches(entry))
+        .take(filter.limit.unwrap_or(usize::MAX))
+        .collect();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_and_filters_entries() {
+        let workspace = std::env::temp_dir().join(storage::new_id("audit-test"));
+        fs::create_dir_all(&workspace).unwrap();
+        fs::write(workspace.join("a.txt"), "old").unwrap();
+
+        let operations = vec![
+            Operation::Write { path: "a.txt".to_string(), content: "new".to_string() },
+            Operation::Rename { from: "a.txt".to_string(), to: "b.txt".to_string() },
+        ];
+        let files = from_operations(&workspace, &operations);
+        assert_eq!(files[0].before_hash, Some(hash("old")));
+        assert_eq!(files[0].after_hash, Some(hash("new")));
+        record(&workspace, Actor::User, "transaction", "Rename a", files).unwrap();
+        record(&workspace, Actor::Agent, "changeset", "Agent edit", Vec::new()).unwrap();
+
+        let all = query(&workspace, &AuditFilter::default()).unwrap();
+        assert_eq!(all.iter().map(|e| e.description.as_str()).collect::<Vec<_>>(), ["Agent edit", "Rename a"]);
+        let by_path = AuditFilter {
+            path: Some("b.txt".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(query(&workspace, &by_path).unwrap().len(), 1);
+        let by_actor = AuditFilter {
+            actor: Some(Actor::Plugin("fmt".to_string())),
+            ..Default::default()
+        };
+        assert!(query(&workspace, &by_actor).unwrap().is_empty());
+        let _ = fs::remove_dir_all(&workspace);
+    }
+}
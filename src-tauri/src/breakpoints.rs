@@ -0,0 +1,291 @@
+// Breakpoints - Persistent per-workspace breakpoint store
+// Lines follow file edits by diffing against the content they were anchored to
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::debugger::{BreakpointSpec, SourceBreakpoints};
+use crate::storage;
+use crate::text_diff::{self, DiffOp};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredBreakpoint {
+    pub id: String,
+    /// Workspace-relative path with forward slashes
+    pub path: String,
+    /// 1-based
+    pub line: u32,
+    pub enabled: bool,
+    #[serde(default)]
+    pub condition: Option<String>,
+    #[serde(default)]
+    pub hit_condition: Option<String>,
+    #[serde(default)]
+    pub log_message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+struct Store {
+    breakpoints: Vec<StoredBreakpoint>,
+    /// Relative path -> hash of the content the file's lines refer to
+    anchors: HashMap<String, String>,
+}
+
+fn store_dir(workspace: &Path) -> PathBuf {
+    storage::data_dir(workspace).join("breakpoints")
+}
+
+fn hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Copy of the anchored content, needed to diff against later edits
+fn anchor_file(workspace: &Path, relative: &str) -> PathBuf {
+    store_dir(workspace).join(&hash(relative)[..16])
+}
+
+fn relative(workspace: &Path, path: &str) -> String {
+    let path = Path::new(path);
+    path.strip_prefix(workspace)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn load(workspace: &Path) -> Result<Store> {
+    Ok(storage::read_json(&store_dir(workspace).join("breakpoints.json"))?.unwrap_or_default())
+}
+
+fn save(workspace: &Path, store: &Store) -> Result<()> {
+    storage::write_json(&store_dir(workspace).join("breakpoints.json"), store)
+}
+
+/// New 1-based position of `line` after the edit; deleted lines move to the line now in their place
+fn map_line(ops: &[DiffOp], line: u32, new_len: usize) -> u32 {
+    let target = line.saturating_sub(1) as usize;
+    let (mut old, mut new) = (0usize, 0usize);
+    for op in ops {
+        match op {
+            DiffOp::Equal(_) => {
+                if old == target {
+                    return new as u32 + 1;
+                }
+                old += 1;
+                new += 1;
+            }
+            DiffOp::Delete(_) => {
+                if old == target {
+                    return (new + 1).min(new_len.max(1)) as u32;
+                }
+                old += 1;
+            }
+            DiffOp::Insert(_) => new += 1,
+        }
+    }
+    new_len.max(1) as u32
+}
+
+/// Move the breakpoints of `relative` to match the file on disk; `true` if the store changed
+fn reanchor(workspace: &Path, store: &mut Store, relative: &str) -> Result<bool> {
+    let current = match fs::read_to_string(workspace.join(relative)) {
+        Ok(content) => content,
+        // Keep breakpoints of missing files; the file may come back on a branch switch
+        Err(_) => return Ok(false),
+    };
+    let current_hash = hash(&current);
+    if store.anchors.get(relative) == Some(&current_hash) {
+        return Ok(false);
+    }
+
+    let new_len = current.lines().count();
+    let anchored = fs::read_to_string(anchor_file(workspace, relative)).ok();
+    let ops = anchored.as_deref().map(|old| text_diff::diff_lines(old, &current));
+    let mut seen = Vec::new();
+    store.breakpoints.retain_mut(|bp| {
+        if bp.path != relative {
+            return true;
+        }
+        bp.line = match &ops {
+            Some(ops) => map_line(ops, bp.line, new_len),
+            None => bp.line.min(new_len.max(1) as u32),
+        };
+        // Two breakpoints can land on one line when the code between them is deleted
+        let unique = !seen.contains(&bp.line);
+        seen.push(bp.line);
+        unique
+    });
+
+    fs::create_dir_all(store_dir(workspace))?;
+    fs::write(anchor_file(workspace, relative), &current)?;
+    store.anchors.insert(relative.to_string(), current_hash);
+    Ok(true)
+}
+
+/// Re-anchor every file that has breakpoints
+fn refresh(workspace: &Path) -> Result<Store> {
+    let mut store = load(workspace)?;
+    let mut paths: Vec<String> = store.breakpoints.iter().map(|bp| bp.path.clone()).collect();
+    paths.sort();
+    paths.dedup();
+    let mut changed = false;
+    for path in paths {
+        changed |= reanchor(workspace, &mut store, &path)?;
+    }
+    if changed {
+        save(workspace, &store)?;
+    }
+    Ok(store)
+}
+
+/// Breakpoints at their current lines, optionally for one file
+pub fn list(workspace: &Path, path: Option<&str>) -> Result<Vec<StoredBreakpoint>> {
+    let filter = path.map(|p| relative(workspace, p));
+    let mut breakpoints: Vec<StoredBreakpoint> = refresh(workspace)?
+        .breakpoints
+        .into_iter()
+        .filter(|bp| filter.as_ref().map(|f| &bp.path == f).unwrap_or(true))
+        .collect();
+    breakpoints.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+    Ok(breakpoints)
+}
+
+/// Add a breakpoint or replace the one already on that line
+pub fn set(workspace: &Path, path: &str, line: u32, spec: &BreakpointSpec, enabled: bool) -> Result<StoredBreakpoint> {
+    if line == 0 {
+        return Err(anyhow!("Lines start at 1"));
+    }
+    let relative = relative(workspace, path);
+    let mut store = refresh(workspace)?;
+    // Anchor before the first breakpoint of a file so later edits can be followed
+    reanchor(workspace, &mut store, &relative)?;
+    let id = store
+        .breakpoints
+        .iter()
+        .find(|bp| bp.path == relative && bp.line == line)
+        .map(|bp| bp.id.clone())
+        .unwrap_or_else(|| storage::new_id("bp"));
+    let breakpoint = StoredBreakpoint {
+        id: id.clone(),
+        path: relative,
+        line,
+        enabled,
+        condition: spec.condition.clone(),
+        hit_condition: spec.hit_condition.clone(),
+        log_message: spec.log_message.clone(),
+    };
+    store.breakpoints.retain(|bp| bp.id != id);
+    store.breakpoints.push(breakpoint.clone());
+    save(workspace, &store)?;
+    Ok(breakpoint)
+}
+
+/// Gutter click: remove the breakpoint on `line`, or add a plain one
+pub fn toggle(workspace: &Path, path: &str, line: u32) -> Result<Option<StoredBreakpoint>> {
+    let relative = relative(workspace, path);
+    let mut store = refresh(workspace)?;
+    let before = store.breakpoints.len();
+    store.breakpoints.retain(|bp| !(bp.path == relative && bp.line == line));
+    if store.breakpoints.len() < before {
+        save(workspace, &store)?;
+        return Ok(None);
+    }
+    let spec = BreakpointSpec {
+        line,
+        condition: None,
+        hit_condition: None,
+        log_message: None,
+    };
+    set(workspace, path, line, &spec, true).map(Some)
+}
+
+/// Follow edits the engine itself made, without waiting for the next listing
+pub fn reanchor_paths(workspace: &Path, paths: &[String]) -> Result<()> {
+    let mut store = load(workspace)?;
+    let mut changed = false;
+    for path in paths {
+        let relative = relative(workspace, path);
+        if store.breakpoints.iter().any(|bp| bp.path == relative) {
+            changed |= reanchor(workspace, &mut store, &relative)?;
+        }
+    }
+    if changed {
+        save(workspace, &store)?;
+    }
+    Ok(())
+}
+
+/// Move the breakpoints of `from` and their anchor to `to`, after the file moved
+pub fn rename(workspace: &Path, from: &str, to: &str) -> Result<()> {
+    let (from, to) = (relative(workspace, from), relative(workspace, to));
+    let mut store = load(workspace)?;
+    if from == to || !store.breakpoints.iter().any(|bp| bp.path == from) {
+        return Ok(());
+    }
+    store.breakpoints.retain(|bp| bp.path != to);
+    for bp in store.breakpoints.iter_mut().filter(|bp| bp.path == from) {
+        bp.path = to.clone();
+    }
+    match store.anchors.remove(&from) {
+        Some(anchor) => {
+            fs::rename(anchor_file(workspace, &from), anchor_file(workspace, &to))?;
+            store.anchors.insert(to, anchor);
+        }
+        None => {
+            store.anchors.remove(&to);
+        }
+    }
+    save(workspace, &store)
+}
+
+/// Enabled breakpoints grouped by absolute path, as sent to a debug adapter on launch
+pub fn for_launch(workspace: &Path) -> Result<Vec<SourceBreakpoints>> {
+    let mut grouped: Vec<SourceBreakpoints> = Vec::new();
+    for bp in list(workspace, None)?.into_iter().filter(|bp| bp.enabled) {
+        let path = workspace.join(&bp.path).to_string_lossy().to_string();
+        let spec = BreakpointSpec {
+            line: bp.line,
+            condition: bp.condition,
+            hit_condition: bp.hit_condition,
+            log_message: bp.log_message,
+        };
+        match grouped.iter_mut().find(|source| source.path == path) {
+            Some(source) => source.breakpoints.push(spec),
+            None => grouped.push(SourceBreakpoints {
+                path,
+                breakpoints: vec![spec],
+            }),
+        }
+    }
+    Ok(grouped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breakpoints_follow_edits() {
+        let workspace = std::env::temp_dir().join(storage::new_id("breakpoints-test"));
+        fs::create_dir_all(&workspace).unwrap();
+        let file = workspace.join("main.py");
+        fs::write(&file, "a = 1\nb = 2\nc = 3\nd = 4\n").unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        toggle(&workspace, &path, 2).unwrap();
+        toggle(&workspace, &path, 4).unwrap();
+        fs::write(&file, "import os\n\na = 1\nb = 2\nd = 4\n").unwrap();
+        let lines: Vec<u32> = list(&workspace, Some(&path)).unwrap().iter().map(|bp| bp.line).collect();
+        assert_eq!(lines, vec![4, 5]);
+
+        assert!(toggle(&workspace, &path, 4).unwrap().is_none());
+        let launch = for_launch(&workspace).unwrap();
+        assert_eq!(launch[0].breakpoints.iter().map(|b| b.line).collect::<Vec<_>>(), vec![5]);
+        let _ = fs::remove_dir_all(&workspace);
+    }
+}
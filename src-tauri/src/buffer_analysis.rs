@@ -0,0 +1,283 @@
+// Buffer Analysis - Incremental diagnostics for unsaved editor buffers
+// Only edited lines and the functions around them are checked again; other findings move with their lines
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::code_analyzer::{CodeAnalyzer, RuleFinding};
+use crate::syntax;
+use crate::CodeSuggestion;
+
+/// Buffers whose last analysis is kept as the base of the next incremental run
+const MAX_BUFFERS: usize = 32;
+
+/// Node kinds checked again as a whole when any of their lines changes
+const FUNCTION_KINDS: &[&str] = &[
+    "function_item",
+    "closure_expression",
+    "function_declaration",
+    "generator_function_declaration",
+    "method_definition",
+    "function",
+    "function_expression",
+    "arrow_function",
+];
+
+/// 1-based inclusive line range
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct LineRange {
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct BufferAnalysis {
+    /// Every suggestion for the buffer: fresh in the analyzed ranges, carried over elsewhere
+    pub suggestions: Vec<CodeSuggestion>,
+    /// The edited lines widened to their enclosing functions
+    pub analyzed: Vec<LineRange>,
+    /// No earlier analysis of the file was kept, so all of it was analyzed
+    pub full: bool,
+    /// Rules that need the whole file; their findings are from the last full analysis
+    pub deferred_rules: Vec<String>,
+}
+
+struct CachedBuffer {
+    content: String,
+    findings: Vec<RuleFinding>,
+    used: Instant,
+}
+
+/// Last analyzed content and findings of recently edited files
+pub struct BufferCache {
+    buffers: HashMap<String, CachedBuffer>,
+}
+
+impl BufferCache {
+    pub fn new() -> Self {
+        Self {
+            buffers: HashMap::new(),
+        }
+    }
+
+    /// Keep the findings for `content` as the base of incremental runs, evicting the least recent buffer
+    pub fn store(&mut self, path: &str, content: &str, findings: Vec<RuleFinding>) {
+        if self.buffers.len() >= MAX_BUFFERS && !self.buffers.contains_key(path) {
+            let oldest = self.buffers.iter().min_by_key(|(_, buffer)| buffer.used).map(|(path, _)| path.clone());
+            if let Some(oldest) = oldest {
+                self.buffers.remove(&oldest);
+            }
+        }
+        self.buffers.insert(
+            path.to_string(),
+            CachedBuffer {
+                content: content.to_string(),
+                findings,
+                used: Instant::now(),
+            },
+        );
+    }
+}
+
+/// Line indices of the outermost `def` around line `index`, by indentation
+fn python_function_at(lines: &[&str], index: usize) -> Option<(usize, usize)> {
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let mut outermost = None;
+    let mut level = usize::MAX;
+    for i in (0..=index.min(lines.len().checked_sub(1)?)).rev() {
+        let line = lines[i];
+        if line.trim().is_empty() || indent(line) >= level {
+            continue;
+        }
+        level = indent(line);
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("def ") || trimmed.starts_with("async def ") {
+            outermost = Some(i);
+        }
+        if level == 0 {
+            break;
+        }
+    }
+
+    let start = outermost?;
+    let level = indent(lines[start]);
+    let mut end = (start + 1..lines.len())
+        .find(|&j| !lines[j].trim().is_empty() && indent(lines[j]) <= level)
+        .map(|j| j - 1)
+        .unwrap_or(lines.len() - 1);
+    while end > start && lines[end].trim().is_empty() {
+        end -= 1;
+    }
+    Some((start, end))
+}
+
+/// Grow `range` over the outermost function nodes it touches
+fn widen_to_functions(node: Node, range: &mut LineRange) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        let (start, end) = (child.start_position().row + 1, child.end_position().row + 1);
+        if end < range.start_line || start > range.end_line {
+            continue;
+        }
+        if FUNCTION_KINDS.contains(&child.kind()) {
+            range.start_line = range.start_line.min(start);
+            range.end_line = range.end_line.max(end);
+        } else {
+            widen_to_functions(child, range);
+        }
+    }
+}
+
+/// `ranges` clamped to the buffer, widened to their enclosing functions and merged
+fn enclosing(path: &str, content: &str, ranges: &[LineRange]) -> Vec<LineRange> {
+    let lines: Vec<&str> = content.lines().collect();
+    let line_count = lines.len().max(1);
+    let tree = syntax::parse(path, content);
+    let mut widened: Vec<LineRange> = ranges
+        .iter()
+        .map(|range| {
+            let start_line = range.start_line.clamp(1, line_count);
+            let mut range = LineRange {
+                start_line,
+                end_line: range.end_line.clamp(start_line, line_count),
+            };
+            match &tree {
+                Some(tree) => widen_to_functions(tree.root_node(), &mut range),
+                None if path.ends_with(".py") => {
+                    if let Some((start, _)) = python_function_at(&lines, range.start_line - 1) {
+                        range.start_line = start + 1;
+                    }
+                    if let Some((_, end)) = python_function_at(&lines, range.end_line - 1) {
+                        range.end_line = range.end_line.max(end + 1);
+                    }
+                }
+                None => {}
+            }
+            range
+        })
+        .collect();
+
+    widened.sort_by_key(|range| range.start_line);
+    let mut merged: Vec<LineRange> = Vec::new();
+    for range in widened {
+        match merged.last_mut() {
+            Some(last) if range.start_line <= last.end_line + 1 => last.end_line = last.end_line.max(range.end_line),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Analyze `content` of `path`, checking again only `dirty` lines, lines changed since the last run and the
+/// functions around them; without an earlier run of the file the whole buffer is analyzed
+pub fn analyze(
+    cache: &Mutex<BufferCache>,
+    analyzer: &CodeAnalyzer,
+    path: &str,
+    content: &str,
+    dirty: &[LineRange],
+) -> BufferAnalysis {
+    let previous = cache.lock().unwrap().buffers.remove(path);
+    let Some(previous) = previous else {
+        let findings = analyzer.findings(path, content, false);
+        let analysis = BufferAnalysis {
+            suggestions: findings.iter().map(|f| f.suggestion.clone()).collect(),
+            analyzed: vec![LineRange {
+                start_line: 1,
+                end_line: content.lines().count().max(1),
+            }],
+            full: true,
+            deferred_rules: Vec::new(),
+        };
+        cache.lock().unwrap().store(path, content, findings);
+        return analysis;
+    };
+
+    // Lines before the first and after the last change keep their findings, shifted by the lines added
+    let old_lines: Vec<&str> = previous.content.lines().collect();
+    let new_lines: Vec<&str> = content.lines().collect();
+    let prefix = old_lines.iter().zip(&new_lines).take_while(|(a, b)| a == b).count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut ranges = dirty.to_vec();
+    if prefix < old_lines.len().max(new_lines.len()) {
+        // A deletion changes no remaining line; the lines it joined are checked
+        ranges.push(LineRange {
+            start_line: prefix + 1,
+            end_line: (new_lines.len() - suffix).max(prefix + 1),
+        });
+    }
+    let analyzed = enclosing(path, content, &ranges);
+    let in_analyzed = |line: usize| analyzed.iter().any(|range| range.start_line <= line && line <= range.end_line);
+
+    let deferred_rules = analyzer.file_rules(path);
+    let old_suffix_start = old_lines.len() - suffix;
+    let mut findings: Vec<RuleFinding> = previous
+        .findings
+        .into_iter()
+        .filter_map(|mut finding| {
+            let line = finding.suggestion.line;
+            finding.suggestion.line = if line <= prefix {
+                line
+            } else if line > old_suffix_start {
+                line + new_lines.len() - old_lines.len()
+            } else {
+                return None;
+            };
+            let local = !deferred_rules.contains(&finding.rule);
+            (!local || !in_analyzed(finding.suggestion.line)).then_some(finding)
+        })
+        .collect();
+
+    // Lines outside the analyzed ranges are blanked, so findings keep their line numbers
+    let excerpt: Vec<&str> =
+        new_lines.iter().enumerate().map(|(i, line)| if in_analyzed(i + 1) { *line } else { "" }).collect();
+    let fresh = analyzer.findings(path, &excerpt.join("\n"), true);
+    findings.extend(fresh.into_iter().filter(|f| in_analyzed(f.suggestion.line)));
+    findings.sort_by_key(|f| (f.suggestion.line, f.suggestion.column));
+
+    let analysis = BufferAnalysis {
+        suggestions: findings.iter().map(|f| f.suggestion.clone()).collect(),
+        analyzed,
+        full: false,
+        deferred_rules,
+    };
+    cache.lock().unwrap().store(path, content, findings);
+    analysis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_edited_functions_are_analyzed_again() {
+        let cache = Mutex::new(BufferCache::new());
+        let analyzer = CodeAnalyzer::new();
+        let eval_lines = |analysis: &BufferAnalysis| -> Vec<usize> {
+            analysis.suggestions.iter().filter(|s| s.message.contains("exec/eval")).map(|s| s.line).collect()
+        };
+        let before = "def load(path):\n    return eval(path)\n\n\ndef save(path, data):\n    return data\n";
+        assert!(analyze(&cache, &analyzer, "app.py", before, &[]).full);
+
+        let edited = before.replace("return data", "return exec(data)");
+        let analysis = analyze(&cache, &analyzer, "app.py", &edited, &[]);
+        assert!(!analysis.full);
+        assert_eq!(analysis.analyzed, vec![LineRange { start_line: 5, end_line: 6 }]);
+        assert_eq!(eval_lines(&analysis), vec![2, 6]);
+        assert!(analysis.deferred_rules.contains(&"unused_imports".to_string()));
+
+        let inserted = format!("import os\n{}", edited);
+        let dirty = [LineRange { start_line: 1, end_line: 1 }];
+        let analysis = analyze(&cache, &analyzer, "app.py", &inserted, &dirty);
+        assert_eq!(analysis.analyzed, vec![LineRange { start_line: 1, end_line: 1 }]);
+        assert_eq!(eval_lines(&analysis), vec![3, 7]);
+    }
+}
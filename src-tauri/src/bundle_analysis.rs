@@ -0,0 +1,452 @@
+// Bundle Analysis - Output bytes of a build attributed back to workspace files
+// Reads webpack stats, rollup-plugin-visualizer data or source maps; each gives chunks and a treemap
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use walkdir::WalkDir;
+
+use crate::source_map::{self, SourceMap};
+use crate::storage;
+
+/// Name of output bytes no source map segment covers
+const UNMAPPED: &str = "(unmapped)";
+/// Source maps read from one build directory
+const MAX_MAPS: usize = 500;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleFormat {
+    WebpackStats,
+    RollupVisualizer,
+    SourceMap,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModuleBytes {
+    /// Workspace-relative path, `node_modules/...` for packages, else the name the bundler reported
+    pub path: String,
+    pub bytes: u64,
+    /// True for files of the workspace, false for packages and generated code
+    pub workspace_file: bool,
+    pub package: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Chunk {
+    pub name: String,
+    pub files: Vec<String>,
+    pub bytes: u64,
+    /// Largest first
+    pub modules: Vec<ModuleBytes>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TreemapNode {
+    pub name: String,
+    pub path: String,
+    pub bytes: u64,
+    /// Largest first; empty for modules
+    pub children: Vec<TreemapNode>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BundleAnalysis {
+    pub path: String,
+    pub format: BundleFormat,
+    /// Sizes as the input reports them: webpack module sizes, rendered lengths, or output bytes for source maps
+    pub total_bytes: u64,
+    pub chunks: Vec<Chunk>,
+    /// Module bytes of all chunks by directory
+    pub treemap: TreemapNode,
+    pub analyzed_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Contribution {
+    pub path: String,
+    pub bytes: u64,
+    /// Fraction of the chunk's bytes
+    pub share: f64,
+}
+
+/// A chunk as read from the input, with module names still unresolved
+struct RawChunk {
+    name: String,
+    files: Vec<String>,
+    modules: Vec<(String, u64)>,
+}
+
+fn as_name(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Leaf modules of a webpack module; concatenated modules list their parts under `modules`
+fn webpack_leaves(module: &Value, out: &mut Vec<(String, u64)>) {
+    match module["modules"].as_array().filter(|inner| !inner.is_empty()) {
+        Some(inner) => inner.iter().for_each(|m| webpack_leaves(m, out)),
+        None => {
+            let name = module["name"].as_str().or_else(|| module["identifier"].as_str()).unwrap_or_default();
+            out.push((name.to_string(), module["size"].as_u64().unwrap_or(0)));
+        }
+    }
+}
+
+/// Chunks of webpack stats; multi-compiler stats nest one compilation per `children` entry
+fn webpack_chunks(stats: &Value, out: &mut Vec<RawChunk>) {
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+    for chunk in stats["chunks"].as_array().into_iter().flatten() {
+        let id = as_name(&chunk["id"]).unwrap_or_default();
+        let name = chunk["names"].as_array().and_then(|names| names.first()).and_then(as_name);
+        let name = name.unwrap_or_else(|| id.clone());
+        let files = chunk["files"].as_array().into_iter().flatten().filter_map(as_name).collect();
+        let mut modules = Vec::new();
+        // Stats written with `chunkModules` carry modules per chunk instead of at the top level
+        for module in chunk["modules"].as_array().into_iter().flatten() {
+            webpack_leaves(module, &mut modules);
+        }
+        by_id.insert(id, out.len());
+        out.push(RawChunk { name, files, modules });
+    }
+    for module in stats["modules"].as_array().into_iter().flatten() {
+        let mut leaves = Vec::new();
+        webpack_leaves(module, &mut leaves);
+        for id in module["chunks"].as_array().into_iter().flatten().filter_map(as_name) {
+            if let Some(&i) = by_id.get(&id) {
+                out[i].modules.extend(leaves.iter().cloned());
+            }
+        }
+    }
+    for child in stats["children"].as_array().into_iter().flatten() {
+        webpack_chunks(child, out);
+    }
+}
+
+/// Chunks of rollup-plugin-visualizer raw data, where every module part names its output file
+fn visualizer_chunks(data: &Value) -> Vec<RawChunk> {
+    let mut chunks: BTreeMap<String, Vec<(String, u64)>> = BTreeMap::new();
+    for meta in data["nodeMetas"].as_object().into_iter().flat_map(|metas| metas.values()) {
+        let id = meta["id"].as_str().unwrap_or_default();
+        for (bundle, part) in meta["moduleParts"].as_object().into_iter().flatten() {
+            let part = part.as_str().map(|uid| &data["nodeParts"][uid]).unwrap_or(&Value::Null);
+            let bytes = part["renderedLength"].as_u64().unwrap_or(0);
+            chunks.entry(bundle.clone()).or_default().push((id.to_string(), bytes));
+        }
+    }
+    chunks
+        .into_iter()
+        .map(|(name, modules)| RawChunk { files: vec![name.clone()], name, modules })
+        .collect()
+}
+
+/// Byte offset of each UTF-16 column of a generated line
+fn column_offsets(line: &str) -> Option<Vec<usize>> {
+    if line.is_ascii() {
+        return None;
+    }
+    let mut offsets = Vec::with_capacity(line.len() + 1);
+    for (i, c) in line.char_indices() {
+        offsets.extend(std::iter::repeat(i).take(c.len_utf16()));
+    }
+    offsets.push(line.len());
+    Some(offsets)
+}
+
+/// Bytes of `generated` per source of `map`, as absolute paths; line breaks count as unmapped
+fn attribute(map: &SourceMap, generated: &str, map_dir: &Path, workspace: &Path) -> Vec<(String, u64)> {
+    let sources: Vec<String> = map
+        .sources
+        .iter()
+        .map(|source| source_map::source_path(map_dir, workspace, source).to_string_lossy().to_string())
+        .collect();
+    let mut bytes: HashMap<Option<u32>, u64> = HashMap::new();
+    for (i, line) in generated.split_inclusive('\n').enumerate() {
+        let content = line.trim_end_matches(['\n', '\r']);
+        *bytes.entry(None).or_default() += (line.len() - content.len()) as u64;
+        let offsets = column_offsets(content);
+        let offset = |column: u32| match &offsets {
+            Some(offsets) => offsets.get(column as usize).copied().unwrap_or(content.len()),
+            None => (column as usize).min(content.len()),
+        };
+        let segments = map.lines.get(i).map(Vec::as_slice).unwrap_or_default();
+        let mut start = 0;
+        let mut source = None;
+        for segment in segments {
+            let end = offset(segment.column);
+            *bytes.entry(source).or_default() += end.saturating_sub(start) as u64;
+            start = end.max(start);
+            source = segment.source;
+        }
+        *bytes.entry(source).or_default() += content.len().saturating_sub(start) as u64;
+    }
+    bytes
+        .into_iter()
+        .filter(|(_, bytes)| *bytes > 0)
+        .map(|(source, bytes)| {
+            let name = source.and_then(|s| sources.get(s as usize)).cloned();
+            (name.unwrap_or_else(|| UNMAPPED.to_string()), bytes)
+        })
+        .collect()
+}
+
+/// One chunk per source map in `maps`, measured against the generated file next to it
+fn source_map_chunks(maps: &[&Path], root: &Path, workspace: &Path) -> Result<Vec<RawChunk>> {
+    let mut chunks = Vec::new();
+    for map_path in maps {
+        let generated_path = map_path.with_extension("");
+        let Ok(generated) = fs::read_to_string(&generated_path) else {
+            log::debug!("Skipping {}: generated file missing", map_path.display());
+            continue;
+        };
+        let map = SourceMap::parse(&fs::read_to_string(map_path)?)
+            .map_err(|e| anyhow!("Invalid source map {}: {}", map_path.display(), e))?;
+        let name = generated_path.strip_prefix(root).unwrap_or(&generated_path).to_string_lossy().replace('\\', "/");
+        let dir = map_path.parent().unwrap_or(root);
+        chunks.push(RawChunk {
+            files: vec![name.clone()],
+            name,
+            modules: attribute(&map, &generated, dir, workspace),
+        });
+    }
+    Ok(chunks)
+}
+
+/// Package name of the path after its last `node_modules`, with the scope of scoped packages
+fn package_after_node_modules(rest: &[&str]) -> Option<String> {
+    match rest {
+        [scope, name, ..] if scope.starts_with('@') => Some(format!("{}/{}", scope, name)),
+        [name, ..] => Some(name.to_string()),
+        [] => None,
+    }
+}
+
+/// Where a reported module lives: a workspace file, a package, or code the bundler generated
+fn classify(workspace: &Path, name: &str, bytes: u64) -> ModuleBytes {
+    // Loader requests and concatenation notes wrap the module path
+    let name = name.rsplit('!').next().unwrap_or(name);
+    let name = name.split(" + ").next().unwrap_or(name).trim();
+    let generated = ModuleBytes {
+        path: name.to_string(),
+        bytes,
+        workspace_file: false,
+        package: None,
+    };
+    if name.is_empty() || name.starts_with('(') || name.starts_with("webpack/") || name.starts_with('\0') {
+        return generated;
+    }
+    let path = source_map::source_path(workspace, workspace, name);
+    let parts: Vec<&str> = path
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect();
+    if let Some(i) = parts.iter().rposition(|part| *part == "node_modules") {
+        return ModuleBytes {
+            path: format!("node_modules/{}", parts[i + 1..].join("/")),
+            bytes,
+            workspace_file: false,
+            package: package_after_node_modules(&parts[i + 1..]),
+        };
+    }
+    match path.strip_prefix(workspace) {
+        Ok(relative) if !relative.as_os_str().is_empty() => ModuleBytes {
+            path: relative.to_string_lossy().replace('\\', "/"),
+            bytes,
+            workspace_file: true,
+            package: None,
+        },
+        _ => generated,
+    }
+}
+
+fn resolve_chunk(workspace: &Path, raw: RawChunk) -> Chunk {
+    let mut modules: HashMap<String, ModuleBytes> = HashMap::new();
+    for (name, bytes) in raw.modules {
+        let module = classify(workspace, &name, bytes);
+        match modules.get_mut(&module.path) {
+            Some(existing) => existing.bytes += module.bytes,
+            None => {
+                modules.insert(module.path.clone(), module);
+            }
+        }
+    }
+    let mut modules: Vec<ModuleBytes> = modules.into_values().collect();
+    modules.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.path.cmp(&b.path)));
+    Chunk {
+        name: raw.name,
+        files: raw.files,
+        bytes: modules.iter().map(|m| m.bytes).sum(),
+        modules,
+    }
+}
+
+#[derive(Default)]
+struct TreeBuilder {
+    bytes: u64,
+    children: BTreeMap<String, TreeBuilder>,
+}
+
+impl TreeBuilder {
+    fn insert(&mut self, parts: &[&str], bytes: u64) {
+        self.bytes += bytes;
+        if let [first, rest @ ..] = parts {
+            self.children.entry(first.to_string()).or_default().insert(rest, bytes);
+        }
+    }
+
+    fn build(self, name: String, path: String) -> TreemapNode {
+        let mut children: Vec<TreemapNode> = self
+            .children
+            .into_iter()
+            .map(|(child, builder)| {
+                let child_path = if path.is_empty() { child.clone() } else { format!("{}/{}", path, child) };
+                builder.build(child, child_path)
+            })
+            .collect();
+        children.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+        TreemapNode {
+            name,
+            path,
+            bytes: self.bytes,
+            children,
+        }
+    }
+}
+
+fn treemap(name: String, chunks: &[Chunk]) -> TreemapNode {
+    let mut root = TreeBuilder::default();
+    for module in chunks.iter().flat_map(|chunk| &chunk.modules) {
+        let parts: Vec<&str> = module.path.split('/').filter(|part| !part.is_empty()).collect();
+        root.insert(&parts, module.bytes);
+    }
+    root.build(name, String::new())
+}
+
+/// Analyze a webpack stats file, rollup-plugin-visualizer raw data, a source map, or a build directory
+/// whose source maps sit next to their generated files
+pub fn analyze(workspace: &Path, path: &Path) -> Result<BundleAnalysis> {
+    let (format, raw) = if path.is_dir() {
+        let maps: Vec<_> = WalkDir::new(path)
+            .into_iter()
+            .filter_entry(|e| e.file_name() != "node_modules")
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|ext| ext == "map"))
+            .take(MAX_MAPS)
+            .map(|e| e.into_path())
+            .collect();
+        let maps: Vec<&Path> = maps.iter().map(|p| p.as_path()).collect();
+        (BundleFormat::SourceMap, source_map_chunks(&maps, path, workspace)?)
+    } else {
+        let text = fs::read_to_string(path)?;
+        let data: Value = serde_json::from_str(&text).map_err(|e| anyhow!("Not a stats or source map file: {}", e))?;
+        if data.get("nodeParts").is_some() && data.get("nodeMetas").is_some() {
+            (BundleFormat::RollupVisualizer, visualizer_chunks(&data))
+        } else if data.get("mappings").is_some() || data.get("sections").is_some() {
+            let root = path.parent().unwrap_or(workspace);
+            (BundleFormat::SourceMap, source_map_chunks(&[path], root, workspace)?)
+        } else if ["chunks", "modules", "children"].iter().any(|key| data.get(key).is_some()) {
+            let mut chunks = Vec::new();
+            webpack_chunks(&data, &mut chunks);
+            (BundleFormat::WebpackStats, chunks)
+        } else {
+            return Err(anyhow!("{} is neither bundle stats nor a source map", path.display()));
+        }
+    };
+    if raw.is_empty() {
+        return Err(anyhow!("No chunks found in {}", path.display()));
+    }
+    let chunks: Vec<Chunk> = raw.into_iter().map(|chunk| resolve_chunk(workspace, chunk)).collect();
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    Ok(BundleAnalysis {
+        path: path.to_string_lossy().to_string(),
+        format,
+        total_bytes: chunks.iter().map(|chunk| chunk.bytes).sum(),
+        treemap: treemap(name, &chunks),
+        chunks,
+        analyzed_at: storage::now_millis(),
+    })
+}
+
+impl BundleAnalysis {
+    /// Largest modules of a chunk, found by chunk name or output file
+    pub fn contributors(&self, chunk: &str, workspace_only: bool, limit: usize) -> Result<Vec<Contribution>> {
+        let found = self
+            .chunks
+            .iter()
+            .find(|c| c.name == chunk)
+            .or_else(|| {
+                self.chunks.iter().find(|c| {
+                    c.files.iter().any(|file| file == chunk || file.rsplit('/').next() == Some(chunk))
+                })
+            })
+            .ok_or_else(|| {
+                let names: Vec<&str> = self.chunks.iter().map(|c| c.name.as_str()).collect();
+                anyhow!("No chunk {} (chunks: {})", chunk, names.join(", "))
+            })?;
+        Ok(found
+            .modules
+            .iter()
+            .filter(|module| !workspace_only || module.workspace_file)
+            .take(limit)
+            .map(|module| Contribution {
+                path: module.path.clone(),
+                bytes: module.bytes,
+                share: if found.bytes == 0 { 0.0 } else { module.bytes as f64 / found.bytes as f64 },
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webpack_stats_and_source_map_attribution() {
+        let dir = std::env::temp_dir().join(format!("mimi-bundle-{}", storage::new_id("t")));
+        fs::create_dir_all(dir.join("dist")).unwrap();
+        let stats = serde_json::json!({
+            "chunks": [{"id": 0, "names": ["main"], "files": ["main.js"]}],
+            "modules": [
+                {"name": "./src/index.js + 2 modules", "chunks": [0], "modules": [
+                    {"name": "./src/index.js", "size": 300},
+                    {"name": "./src/util/format.js", "size": 100}
+                ]},
+                {"name": "./node_modules/@scope/lib/index.js", "size": 700, "chunks": [0]},
+                {"name": "webpack/runtime/define property getters", "size": 50, "chunks": [0]}
+            ]
+        });
+        fs::write(dir.join("stats.json"), stats.to_string()).unwrap();
+        let analysis = analyze(&dir, &dir.join("stats.json")).unwrap();
+        assert_eq!(analysis.format, BundleFormat::WebpackStats);
+        assert_eq!(analysis.total_bytes, 1150);
+        assert_eq!(analysis.chunks[0].modules[0].package.as_deref(), Some("@scope/lib"));
+        let mine = analysis.contributors("main.js", true, 10).unwrap();
+        assert_eq!(mine.iter().map(|c| c.path.as_str()).collect::<Vec<_>>(), ["src/index.js", "src/util/format.js"]);
+        assert!(analysis.contributors("vendor", true, 10).is_err());
+        assert_eq!(analysis.treemap.children[0].name, "node_modules");
+        assert_eq!(analysis.treemap.children[1].path, "src");
+        assert_eq!(analysis.treemap.children[1].bytes, 400);
+
+        // `abc;` from a.ts, `de` from b.ts, then a line break
+        fs::write(dir.join("dist/app.js"), "abc;de\n").unwrap();
+        let map = r#"{"version": 3, "sources": ["../src/a.ts", "../src/b.ts"], "mappings": "AAAA,ICAA"}"#;
+        fs::write(dir.join("dist/app.js.map"), map).unwrap();
+        let analysis = analyze(&dir, &dir.join("dist")).unwrap();
+        assert_eq!(analysis.total_bytes, 7);
+        let bytes: HashMap<_, _> = analysis.chunks[0].modules.iter().map(|m| (m.path.as_str(), m.bytes)).collect();
+        assert_eq!(bytes["src/a.ts"], 4);
+        assert_eq!(bytes["src/b.ts"], 2);
+        assert_eq!(bytes[UNMAPPED], 1);
+        fs::remove_dir_all(&dir).ok();
+    }
+}
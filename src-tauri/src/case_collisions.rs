@@ -0,0 +1,121 @@
+// Case Collisions - Paths that differ only in letter case
+// Both spellings live side by side on Linux but clash after a checkout on macOS or Windows
+
+use std::collections::{BTreeMap, BTreeSet};
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+use crate::CodeSuggestion;
+
+pub const SOURCE: &str = "case_collision";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CaseCollision {
+    /// Spellings of the same file or directory, relative to the workspace
+    pub paths: Vec<String>,
+    /// First file under each spelling, where the issue is reported
+    pub files: Vec<String>,
+    pub directory: bool,
+}
+
+/// Collisions among workspace-relative `files`; a clashing directory is reported once, not for every file inside
+pub fn find(files: &[String]) -> Vec<CaseCollision> {
+    let mut files: Vec<String> = files.iter().map(|f| paths::composed(&f.replace('\\', "/"))).collect();
+    files.sort();
+    files.dedup();
+
+    // Lowercase file or directory path -> its spellings
+    let mut spellings: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for file in &files {
+        let mut prefix = String::new();
+        for part in file.split('/').filter(|p| !p.is_empty()) {
+            if !prefix.is_empty() {
+                prefix.push('/');
+            }
+            prefix.push_str(part);
+            spellings.entry(prefix.to_lowercase()).or_default().insert(prefix.clone());
+        }
+    }
+
+    let mut collisions = Vec::new();
+    for spelled in spellings.into_values() {
+        // Files inside a clashing directory collide only if their own names differ in case
+        let names: BTreeSet<&str> = spelled.iter().filter_map(|s| s.rsplit('/').next()).collect();
+        if names.len() < 2 {
+            continue;
+        }
+        let first_file = |spelling: &String| {
+            let inside = format!("{}/", spelling);
+            files.iter().find(|f| *f == spelling || f.starts_with(&inside)).cloned()
+        };
+        let directory = spelled.iter().any(|spelling| !files.contains(spelling));
+        collisions.push(CaseCollision {
+            files: spelled.iter().filter_map(first_file).collect(),
+            paths: spelled.into_iter().collect(),
+            directory,
+        });
+    }
+    collisions
+}
+
+/// Per-file suggestions for the diagnostics store
+pub fn suggestions(collisions: &[CaseCollision]) -> BTreeMap<String, Vec<CodeSuggestion>> {
+    let mut by_file: BTreeMap<String, Vec<CodeSuggestion>> = BTreeMap::new();
+    for collision in collisions {
+        for (spelling, file) in collision.paths.iter().zip(&collision.files) {
+            let others: Vec<String> =
+                collision.paths.iter().filter(|p| *p != spelling).map(|p| format!("`{}`", p)).collect();
+            by_file.entry(file.clone()).or_default().push(CodeSuggestion {
+                kind: "portability".to_string(),
+                message: format!(
+                    "`{}` clashes with {} on case-insensitive file systems",
+                    spelling,
+                    others.join(", ")
+                ),
+                line: 1,
+                column: 0,
+                severity: "warning".to_string(),
+                fix: None,
+            });
+        }
+    }
+    by_file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finds_file_and_directory_collisions() {
+        let files = ["src/Button.tsx", "src/button.tsx", "Docs/a.md", "docs/b.md", "docs/B.md", "src/app.ts"];
+        let files = files.map(str::to_string);
+        let collisions = find(&files);
+        assert_eq!(
+            collisions,
+            vec![
+                CaseCollision {
+                    paths: vec!["Docs".to_string(), "docs".to_string()],
+                    files: vec!["Docs/a.md".to_string(), "docs/B.md".to_string()],
+                    directory: true,
+                },
+                CaseCollision {
+                    paths: vec!["docs/B.md".to_string(), "docs/b.md".to_string()],
+                    files: vec!["docs/B.md".to_string(), "docs/b.md".to_string()],
+                    directory: false,
+                },
+                CaseCollision {
+                    paths: vec!["src/Button.tsx".to_string(), "src/button.tsx".to_string()],
+                    files: vec!["src/Button.tsx".to_string(), "src/button.tsx".to_string()],
+                    directory: false,
+                },
+            ]
+        );
+        let suggestions = suggestions(&collisions);
+        assert_eq!(
+            suggestions["src/button.tsx"][0].message,
+            "`src/button.tsx` clashes with `src/Button.tsx` on case-insensitive file systems"
+        );
+        assert_eq!(suggestions["docs/B.md"].len(), 2);
+    }
+}
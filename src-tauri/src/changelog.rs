@@ -0,0 +1,215 @@
+// Changelog - Draft release notes from conventional commits and the public API diff
+// Grouped into Breaking, Features, Fixes and Other; the AI provider can polish the wording
+
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ai_provider::{self, AiProvider, ChatMessage, CompletionRequest};
+use crate::api_surface::{self, ApiSymbol};
+use crate::git;
+use crate::version_advisor;
+
+/// Conventional commit types that stay out of a changelog
+const INTERNAL_TYPES: [&str; 8] = ["chore", "ci", "build", "docs", "style", "test", "refactor", "revert"];
+
+#[derive(Clone, Debug, PartialEq)]
+struct Commit {
+    sha: String,
+    /// `feat`, `fix`, ...; `None` for subjects that do not follow the convention
+    kind: Option<String>,
+    scope: Option<String>,
+    breaking: bool,
+    description: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChangelogDraft {
+    /// The range as resolved, e.g. `v1.2.0..HEAD`
+    pub range: String,
+    pub markdown: String,
+    pub commits: usize,
+    /// Breaking commits plus breaking API changes
+    pub breaking: usize,
+    pub polished: bool,
+}
+
+/// `type(scope)!: description`, with `BREAKING CHANGE:` footers in the body
+fn parse_commit(sha: &str, subject: &str, body: &str) -> Commit {
+    let footer_breaking = body
+        .lines()
+        .any(|l| l.starts_with("BREAKING CHANGE:") || l.starts_with("BREAKING-CHANGE:"));
+    let conventional = subject.split_once(": ").and_then(|(header, description)| {
+        let (header, bang) = match header.strip_suffix('!') {
+            Some(header) => (header, true),
+            None => (header, false),
+        };
+        let (kind, scope) = match header.split_once('(') {
+            Some((kind, scope)) => (kind, Some(scope.strip_suffix(')')?.to_string())),
+            None => (header, None),
+        };
+        let valid = !kind.is_empty() && kind.chars().all(|c| c.is_ascii_alphabetic());
+        valid.then(|| (kind.to_lowercase(), scope, bang, description.trim().to_string()))
+    });
+    match conventional {
+        Some((kind, scope, bang, description)) => Commit {
+            sha: sha.to_string(),
+            kind: Some(kind),
+            scope,
+            breaking: bang || footer_breaking,
+            description,
+        },
+        None => Commit {
+            sha: sha.to_string(),
+            kind: None,
+            scope: None,
+            breaking: footer_breaking,
+            description: subject.trim().to_string(),
+        },
+    }
+}
+
+/// Non-merge commits in `range`, oldest first, limited to `package` when given
+fn commits(workspace: &Path, range: &str, package: &str) -> Result<Vec<Commit>> {
+    let range = git::revision(range)?;
+    let mut args = vec!["log", "--no-color", "--no-merges", "--reverse", "--format=%h%x1f%s%x1f%b%x1e"];
+    args.extend(["--end-of-options", range]);
+    if !package.is_empty() {
+        args.extend(["--", package]);
+    }
+    let log = git::run(workspace, &args)?;
+    Ok(log
+        .split('\x1e')
+        .filter_map(|entry| {
+            let mut fields = entry.trim_start_matches('\n').splitn(3, '\x1f');
+            let sha = fields.next().filter(|s| !s.is_empty())?;
+            let subject = fields.next().unwrap_or("");
+            Some(parse_commit(sha, subject, fields.next().unwrap_or("")))
+        })
+        .collect())
+}
+
+fn entry(commit: &Commit) -> String {
+    match &commit.scope {
+        Some(scope) => format!("- **{}:** {} ({})", scope, commit.description, commit.sha),
+        None => format!("- {} ({})", commit.description, commit.sha),
+    }
+}
+
+/// Markdown grouped by section; `api_breaking` lines come from the API diff
+fn render(title: &str, commits: &[Commit], api_breaking: &[String]) -> String {
+    let mut breaking: Vec<String> = commits.iter().filter(|c| c.breaking).map(entry).collect();
+    breaking.extend(api_breaking.iter().map(|line| format!("- {}", line)));
+    let of_kind = |kinds: &[&str]| -> Vec<String> {
+        commits
+            .iter()
+            .filter(|c| !c.breaking && c.kind.as_deref().is_some_and(|k| kinds.contains(&k)))
+            .map(entry)
+            .collect()
+    };
+    let other: Vec<String> = commits
+        .iter()
+        .filter(|c| {
+            !c.breaking && c.kind.as_deref().is_none_or(|k| !matches!(k, "feat" | "fix" | "perf") && !INTERNAL_TYPES.contains(&k))
+        })
+        .map(entry)
+        .collect();
+
+    let mut markdown = format!("## {}\n", title);
+    for (heading, lines) in [
+        ("Breaking", breaking),
+        ("Features", of_kind(&["feat"])),
+        ("Fixes", of_kind(&["fix", "perf"])),
+        ("Other", other),
+    ] {
+        if !lines.is_empty() {
+            markdown.push_str(&format!("\n### {}\n\n{}\n", heading, lines.join("\n")));
+        }
+    }
+    markdown
+}
+
+/// Draft a changelog for `range` (default: last tag to HEAD). API changes are
+/// included when the range ends at HEAD, since `current` is the working tree's surface.
+pub fn draft(workspace: &Path, range: Option<&str>, package: &str, current: &[ApiSymbol]) -> Result<ChangelogDraft> {
+    let range = match range.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(range) => range.to_string(),
+        None => format!("{}..HEAD", version_advisor::last_tag(workspace, None)?),
+    };
+    let commits = commits(workspace, &range, package)?;
+
+    let (base, head) = match range.split_once("..") {
+        Some((base, head)) => (base, head.trim_start_matches('.')),
+        None => (range.as_str(), "HEAD"),
+    };
+    let api_breaking: Vec<String> = if head.is_empty() || head == "HEAD" {
+        let api = api_surface::diff_against(workspace, base, package, current)?;
+        api.changes.iter().filter(|c| c.breaking).filter_map(version_advisor::explain).collect()
+    } else {
+        Vec::new()
+    };
+
+    let title = if head.is_empty() || head == "HEAD" {
+        format!("Changes since {}", base)
+    } else {
+        format!("Changes from {} to {}", base, head)
+    };
+    Ok(ChangelogDraft {
+        markdown: render(&title, &commits, &api_breaking),
+        commits: commits.len(),
+        breaking: commits.iter().filter(|c| c.breaking).count() + api_breaking.len(),
+        range,
+        polished: false,
+    })
+}
+
+/// Reword the draft for readers while keeping its sections and entries
+pub async fn polish(provider: &dyn AiProvider, draft: &ChangelogDraft) -> Result<ChangelogDraft> {
+    let system = "You edit release notes. Rewrite each entry as a clear, user-facing sentence. \
+        Keep every heading, every entry and the commit references; do not invent changes. \
+        Reply with the Markdown only.";
+    let request = CompletionRequest::new(vec![ChatMessage::system(system), ChatMessage::user(draft.markdown.clone())]);
+    let response = provider.complete(&request).await?;
+    let markdown = ai_provider::extract_code_block(&response);
+    if markdown.is_empty() {
+        return Err(anyhow!("The model returned no changelog"));
+    }
+    Ok(ChangelogDraft {
+        markdown: format!("{}\n", markdown),
+        polished: true,
+        ..draft.clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_conventional_subjects() {
+        let commit = parse_commit("a1b2c3d", "feat(parser)!: accept globs", "");
+        assert_eq!(
+            (commit.kind.as_deref(), commit.scope.as_deref(), commit.breaking, commit.description.as_str()),
+            (Some("feat"), Some("parser"), true, "accept globs")
+        );
+        let footer = parse_commit("e4f5a6b", "fix: keep order", "Details\n\nBREAKING CHANGE: results are sorted");
+        assert!(footer.breaking);
+        let plain = parse_commit("c7d8e9f", "Update README: typo", "");
+        assert_eq!((plain.kind, plain.description.as_str()), (None, "Update README: typo"));
+    }
+
+    #[test]
+    fn test_render_groups_sections() {
+        let commits = vec![
+            parse_commit("1111111", "feat(ui): dark mode", ""),
+            parse_commit("2222222", "fix: crash on empty file", ""),
+            parse_commit("3333333", "chore: bump deps", ""),
+            parse_commit("4444444", "Tidy up", ""),
+        ];
+        let markdown = render("Changes since v1.0.0", &commits, &["`crate::sync` was removed".to_string()]);
+        assert_eq!(
+            markdown,
+            "## Changes since v1.0.0\n\n### Breaking\n\n- `crate::sync` was removed\n\n### Features\n\n- **ui:** dark mode (1111111)\n\n### Fixes\n\n- crash on empty file (2222222)\n\n### Other\n\n- Tidy up (4444444)\n"
+        );
+    }
+}
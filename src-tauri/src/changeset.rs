@@ -0,0 +1,383 @@
+// Changesets - Previewable multi-file edits
+// Used by AI edits, refactorings and the agent
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::history;
+use crate::paths;
+use crate::text_diff;
+use crate::transaction::{Operation, Transaction};
+
+/// Position in a text document (1-based line, 0-based character column)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Replace the text between `start` and `end` with `new_text`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TextEdit {
+    pub start: Position,
+    pub end: Position,
+    pub new_text: String,
+}
+
+impl TextEdit {
+    pub fn insert(at: Position, text: impl Into<String>) -> Self {
+        Self {
+            start: at,
+            end: at,
+            new_text: text.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FileChange {
+    Create { path: String, content: String },
+    Edit { path: String, edits: Vec<TextEdit> },
+    Delete { path: String },
+    Rename { from: String, to: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Changeset {
+    pub id: String,
+    pub description: String,
+    pub changes: Vec<FileChange>,
+    /// SHA-256 of the content each path had when the changes were computed.
+    /// A path whose content differs on disk is not overwritten.
+    #[serde(default)]
+    pub expected_hashes: HashMap<String, String>,
+}
+
+/// A file modified outside the engine since its changes were computed
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WriteConflict {
+    pub path: String,
+    pub expected_hash: String,
+    /// `None` if the file no longer exists
+    pub actual_hash: Option<String>,
+    /// Current content on disk
+    pub theirs: Option<String>,
+    /// Three-way merge of the change into the current content, when the original is in local history
+    pub merged: Option<String>,
+    /// Conflict regions in `merged`
+    pub merge_conflicts: usize,
+}
+
+pub fn content_hash(content: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content);
+    hex::encode(hasher.finalize())
+}
+
+/// Before/after view of one file of a changeset
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FilePreview {
+    pub path: String,
+    /// Target path for renames
+    pub new_path: Option<String>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub diff: String,
+}
+
+impl Changeset {
+    pub fn new(description: impl Into<String>, changes: Vec<FileChange>) -> Self {
+        Self {
+            id: crate::storage::new_id("changeset"),
+            description: description.into(),
+            changes,
+            expected_hashes: HashMap::new(),
+        }
+    }
+
+    /// Record the content `path` had when the changes were computed
+    pub fn expecting(mut self, path: &str, content: &str) -> Self {
+        self.expected_hashes.insert(path.to_string(), content_hash(content.as_bytes()));
+        self
+    }
+
+    /// Paths whose content changed on disk since the changeset was computed
+    pub fn conflicts(&self, workspace: &Path) -> Result<Vec<WriteConflict>> {
+        let mut conflicts = Vec::new();
+        for change in &self.changes {
+            let path = match change {
+                FileChange::Edit { path, .. } | FileChange::Delete { path } => path,
+                FileChange::Rename { from, .. } => from,
+                FileChange::Create { .. } => continue,
+            };
+            let expected = match self.expected_hashes.get(path) {
+                Some(expected) => expected,
+                None => continue,
+            };
+            let bytes = fs::read(resolve(workspace, path)).ok();
+            let actual_hash = bytes.as_deref().map(content_hash);
+            if actual_hash.as_ref() == Some(expected) {
+                continue;
+            }
+
+            let theirs = bytes.map(|b| String::from_utf8_lossy(&b).to_string());
+            let mut merge = None;
+            if let (FileChange::Edit { edits, .. }, Some(theirs)) = (change, &theirs) {
+                if let Some(base) = history::content_with_hash(workspace, path, expected)? {
+                    let ours = apply_edits(&base, edits)?;
+                    merge = Some(text_diff::merge3(&base, &ours, theirs));
+                }
+            }
+            conflicts.push(WriteConflict {
+                path: path.clone(),
+                expected_hash: expected.clone(),
+                actual_hash,
+                theirs,
+                merge_conflicts: merge.as_ref().map_or(0, |m| m.conflicts),
+                merged: merge.map(|m| m.text),
+            });
+        }
+        Ok(conflicts)
+    }
+
+    /// Files touched by the changeset (relative to the workspace)
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        for change in &self.changes {
+            match change {
+                FileChange::Create { path, .. }
+                | FileChange::Edit { path, .. }
+                | FileChange::Delete { path } => paths.push(path.clone()),
+                FileChange::Rename { from, to } => {
+                    paths.push(from.clone());
+                    paths.push(to.clone());
+                }
+            }
+        }
+        paths
+    }
+
+    /// Compute the resulting content of every change without touching disk
+    pub fn preview(&self, workspace: &Path) -> Result<Vec<FilePreview>> {
+        let mut previews = Vec::new();
+
+        for change in &self.changes {
+            let preview = match change {
+                FileChange::Create { path, content } => {
+                    if resolve(workspace, path).exists() {
+                        return Err(anyhow!("File already exists: {}", path));
+                    }
+                    FilePreview {
+                        path: path.clone(),
+                        new_path: None,
+                        before: None,
+                        after: Some(content.clone()),
+                        diff: text_diff::unified_diff(path, "", content, 3),
+                    }
+                }
+                FileChange::Edit { path, edits } => {
+                    let before = fs::read_to_string(resolve(workspace, path))?;
+                    let after = apply_edits(&before, edits)?;
+                    FilePreview {
+                        path: path.clone(),
+                        new_path: None,
+                        diff: text_diff::unified_diff(path, &before, &after, 3),
+                        before: Some(before),
+                        after: Some(after),
+                    }
+                }
+                FileChange::Delete { path } => {
+                    let before = fs::read_to_string(resolve(workspace, path)).unwrap_or_default();
+                    FilePreview {
+                        path: path.clone(),
+                        new_path: None,
+                        diff: text_diff::unified_diff(path, &before, "", 3),
+                        before: Some(before),
+                        after: None,
+                    }
+                }
+                FileChange::Rename { from, to } => {
+                    if !resolve(workspace, from).exists() {
+                        return Err(anyhow!("File not found: {}", from));
+                    }
+                    if resolve(workspace, to).exists() {
+                        return Err(anyhow!("Rename target already exists: {}", to));
+                    }
+                    FilePreview {
+                        path: from.clone(),
+                        new_path: Some(to.clone()),
+                        before: None,
+                        after: None,
+                        diff: String::new(),
+                    }
+                }
+            };
+            previews.push(preview);
+        }
+
+        Ok(previews)
+    }
+
+    /// Apply the changeset to disk as one transaction.
+    /// All edits are computed and validated before the first write, and a failure undoes the rest.
+    pub fn apply(&self, workspace: &Path) -> Result<()> {
+        if let Some(conflict) = self.conflicts(workspace)?.first() {
+            return Err(anyhow!("{} was modified outside the editor", conflict.path));
+        }
+        let previews = self.preview(workspace)?;
+
+        let operations = self
+            .changes
+            .iter()
+            .zip(previews)
+            .map(|(change, preview)| match change {
+                FileChange::Create { path, .. } | FileChange::Edit { path, .. } => Operation::Write {
+                    path: path.clone(),
+                    content: preview.after.unwrap_or_default(),
+                },
+                FileChange::Delete { path } => Operation::Delete { path: path.clone() },
+                FileChange::Rename { from, to } => Operation::Rename {
+                    from: from.clone(),
+                    to: to.clone(),
+                },
+            })
+            .collect();
+        Transaction::new(self.description.clone())
+            .with_operations(operations)
+            .commit(workspace)?;
+
+        log::info!("Applied changeset {} ({} changes)", self.id, self.changes.len());
+        Ok(())
+    }
+}
+
+/// Resolve a changeset path against the workspace root; WSL and Windows forms of a path are translated
+pub fn resolve(workspace: &Path, path: &str) -> PathBuf {
+    let path = paths::native(path);
+    if path.is_absolute() {
+        path
+    } else {
+        workspace.join(path)
+    }
+}
+
+/// Convert a position to a byte offset in `text`
+pub fn offset_of(text: &str, position: Position) -> Result<usize> {
+    let mut offset = 0;
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        if i + 1 == position.line {
+            let content = line.trim_end_matches('\n').trim_end_matches('\r');
+            let column = content
+                .char_indices()
+                .nth(position.column)
+                .map(|(idx, _)| idx)
+                .unwrap_or(content.len());
+            return Ok(offset + column);
+        }
+        offset += line.len();
+    }
+
+    // Allow positions at the very end of the document
+    let line_count = text.split_inclusive('\n').count();
+    if position.line == line_count + 1 || (text.is_empty() && position.line == 1) {
+        return Ok(text.len());
+    }
+    Err(anyhow!("Position {}:{} is out of range", position.line, position.column))
+}
+
+/// Position of byte `offset` in `text`
+pub fn position_at(text: &str, offset: usize) -> Position {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    Position {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count(),
+    }
+}
+
+/// Position just past the end of `text`
+pub fn end_of(text: &str) -> Position {
+    Position {
+        line: text.split_inclusive('\n').count() + 1,
+        column: 0,
+    }
+}
+
+/// Apply non-overlapping text edits to a document
+pub fn apply_edits(text: &str, edits: &[TextEdit]) -> Result<String> {
+    let mut ranges = Vec::with_capacity(edits.len());
+    for edit in edits {
+        let start = offset_of(text, edit.start)?;
+        let end = offset_of(text, edit.end)?;
+        if end < start {
+            return Err(anyhow!("Edit range end precedes start"));
+        }
+        ranges.push((start, end, edit.new_text.as_str()));
+    }
+
+    ranges.sort_by_key(|(start, end, _)| (*start, *end));
+    for pair in ranges.windows(2) {
+        if pair[1].0 < pair[0].1 {
+            return Err(anyhow!("Overlapping edits"));
+        }
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for (start, end, new_text) in ranges {
+        result.push_str(&text[cursor..start]);
+        result.push_str(new_text);
+        cursor = end;
+    }
+    result.push_str(&text[cursor..]);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_edits() {
+        let text = "let a = 1;\nlet b = 2;\n";
+        let edits = vec![
+            TextEdit {
+                start: Position { line: 2, column: 4 },
+                end: Position { line: 2, column: 5 },
+                new_text: "c".to_string(),
+            },
+            TextEdit::insert(Position { line: 1, column: 0 }, "// header\n"),
+        ];
+        assert_eq!(apply_edits(text, &edits).unwrap(), "// header\nlet a = 1;\nlet c = 2;\n");
+    }
+
+    #[test]
+    fn test_external_modification_conflicts() {
+        let workspace = std::env::temp_dir().join(crate::storage::new_id("changeset-conflict-test"));
+        fs::create_dir_all(&workspace).unwrap();
+        let base = "a\nb\nc\n";
+        fs::write(workspace.join("f.txt"), base).unwrap();
+        history::record_snapshot(&workspace, "f.txt", base, "test").unwrap();
+
+        let changeset = Changeset::new(
+            "edit",
+            vec![FileChange::Edit {
+                path: "f.txt".to_string(),
+                edits: vec![TextEdit::insert(Position { line: 1, column: 0 }, "top\n")],
+            }],
+        )
+        .expecting("f.txt", base);
+        assert!(changeset.conflicts(&workspace).unwrap().is_empty());
+
+        fs::write(workspace.join("f.txt"), "a\nb\nc\nbottom\n").unwrap();
+        let conflicts = changeset.conflicts(&workspace).unwrap();
+        assert_eq!(conflicts[0].merged.as_deref(), Some("top\na\nb\nc\nbottom\n"));
+        assert!(changeset.apply(&workspace).is_err());
+        let _ = fs::remove_dir_all(&workspace);
+    }
+}
@@ -0,0 +1,312 @@
+// Chat Sessions - Persistent AI conversations per workspace
+// Backend for the IDE chat panel
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ai_provider::{ChatMessage, Role};
+use crate::storage;
+use crate::tokens::ContextBudget;
+
+const SYSTEM_PROMPT: &str = "You are Mimi, the AI assistant of the Mimiverse IDE. \
+    Answer questions about the user's code precisely and concisely. \
+    Use markdown code blocks with language tags for code.";
+
+/// File or selection attached to a message as context
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ContextAttachment {
+    pub path: String,
+    /// 1-based inclusive line range of a selection (whole file if absent)
+    pub start_line: Option<usize>,
+    pub end_line: Option<usize>,
+    pub content: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChatEntry {
+    pub id: String,
+    pub role: Role,
+    pub content: String,
+    #[serde(default)]
+    pub context: Vec<ContextAttachment>,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChatSession {
+    pub id: String,
+    pub title: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub messages: Vec<ChatEntry>,
+    /// Context attached since the last user message
+    #[serde(default)]
+    pub pending_context: Vec<ContextAttachment>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChatSessionSummary {
+    pub id: String,
+    pub title: String,
+    pub message_count: usize,
+    pub updated_at: u64,
+}
+
+/// Streaming payload emitted as `chat-stream`
+#[derive(Serialize, Clone, Debug)]
+pub struct ChatStreamEvent {
+    pub session_id: String,
+    pub delta: String,
+    pub done: bool,
+}
+
+/// Manages the chat sessions of the open workspace
+pub struct ChatManager {
+    /// Directory holding one transcript file per session
+    dir: Option<PathBuf>,
+    sessions: HashMap<String, ChatSession>,
+}
+
+impl ChatManager {
+    pub fn new() -> Self {
+        Self {
+            dir: None,
+            sessions: HashMap::new(),
+        }
+    }
+
+    /// Load persisted sessions of a workspace
+    pub fn load_workspace(&mut self, workspace: &Path) -> Result<()> {
+        let dir = storage::data_dir(workspace).join("chat");
+        self.sessions.clear();
+
+        if dir.exists() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                    continue;
+                }
+                match storage::read_json::<ChatSession>(&path) {
+                    Ok(Some(session)) => {
+                        self.sessions.insert(session.id.clone(), session);
+                    }
+                    Ok(None) => {}
+                    Err(e) => log::warn!("Skipping corrupt chat transcript {:?}: {}", path, e),
+                }
+            }
+        }
+
+        log::info!("Loaded {} chat sessions", self.sessions.len());
+        self.dir = Some(dir);
+        Ok(())
+    }
+
+    pub fn create_session(&mut self, title: Option<String>) -> Result<ChatSession> {
+        let now = storage::now_millis();
+        let session = ChatSession {
+            id: storage::new_id("chat"),
+            title: title.unwrap_or_else(|| "New chat".to_string()),
+            created_at: now,
+            updated_at: now,
+            messages: Vec::new(),
+            pending_context: Vec::new(),
+        };
+        self.sessions.insert(session.id.clone(), session.clone());
+        self.save(&session.id)?;
+        Ok(session)
+    }
+
+    pub fn rename_session(&mut self, id: &str, title: &str) -> Result<()> {
+        let session = self.session_mut(id)?;
+        session.title = title.to_string();
+        session.updated_at = storage::now_millis();
+        self.save(id)
+    }
+
+    pub fn delete_session(&mut self, id: &str) -> Result<()> {
+        self.sessions
+            .remove(id)
+            .ok_or_else(|| anyhow!("Unknown chat session: {}", id))?;
+        if let Some(dir) = &self.dir {
+            let path = dir.join(format!("{}.json", id));
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn session_count(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// List sessions, most recently updated first
+    pub fn list_sessions(&self) -> Vec<ChatSessionSummary> {
+        let mut summaries: Vec<ChatSessionSummary> = self
+            .sessions
+            .values()
+            .map(|s| ChatSessionSummary {
+                id: s.id.clone(),
+                title: s.title.clone(),
+                message_count: s.messages.len(),
+                updated_at: s.updated_at,
+            })
+            .collect();
+        summaries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        summaries
+    }
+
+    pub fn get_session(&self, id: &str) -> Option<ChatSession> {
+        self.sessions.get(id).cloned()
+    }
+
+    /// Attach file or selection context to the next user message
+    pub fn attach_context(&mut self, id: &str, attachment: ContextAttachment) -> Result<()> {
+        self.session_mut(id)?.pending_context.push(attachment);
+        self.save(id)
+    }
+
+    /// Append a message; user messages take over pending context
+    pub fn append_message(&mut self, id: &str, role: Role, content: &str) -> Result<ChatEntry> {
+        let session = self.session_mut(id)?;
+        let context = if role == Role::User {
+            std::mem::take(&mut session.pending_context)
+        } else {
+            Vec::new()
+        };
+
+        let entry = ChatEntry {
+            id: storage::new_id("msg"),
+            role,
+            content: content.to_string(),
+            context,
+            timestamp: storage::now_millis(),
+        };
+
+        // Name untitled sessions after their first question
+        if session.messages.is_empty() && session.title == "New chat" && entry.role == Role::User {
+            session.title = content.lines().next().unwrap_or("").chars().take(60).collect();
+        }
+
+        session.messages.push(entry.clone());
+        session.updated_at = entry.timestamp;
+        self.save(id)?;
+        Ok(entry)
+    }
+
+    /// Build the provider conversation for a session.
+    /// The oldest messages are dropped once the model's context budget is used up.
+    pub fn prompt_messages(&self, id: &str, model: &str) -> Result<Vec<ChatMessage>> {
+        let session = self
+            .sessions
+            .get(id)
+            .ok_or_else(|| anyhow!("Unknown chat session: {}", id))?;
+
+        let mut budget = ContextBudget::for_model(model);
+        budget.try_add(SYSTEM_PROMPT);
+
+        let mut history = Vec::new();
+        for entry in session.messages.iter().rev() {
+            let mut content = String::new();
+            for attachment in &entry.context {
+                content.push_str(&render_attachment(attachment));
+            }
+            content.push_str(&entry.content);
+
+            if !budget.try_add(&content) {
+                // Always keep the latest message, even if it has to be cut
+                if history.is_empty() {
+                    content = budget.add_truncated(&content);
+                } else {
+                    break;
+                }
+            }
+            history.push(ChatMessage {
+                role: entry.role.clone(),
+                content,
+            });
+        }
+
+        let mut messages = vec![ChatMessage::system(SYSTEM_PROMPT)];
+        messages.extend(history.into_iter().rev());
+        Ok(messages)
+    }
+
+    fn session_mut(&mut self, id: &str) -> Result<&mut ChatSession> {
+        self.sessions
+            .get_mut(id)
+            .ok_or_else(|| anyhow!("Unknown chat session: {}", id))
+    }
+
+    /// Persist a session transcript (in-memory only without a workspace)
+    fn save(&self, id: &str) -> Result<()> {
+        if let (Some(dir), Some(session)) = (&self.dir, self.sessions.get(id)) {
+            storage::write_json(&dir.join(format!("{}.json", id)), session)?;
+        }
+        Ok(())
+    }
+}
+
+/// A file's content (or a line range of it) as a context attachment
+pub fn attachment(path: &str, content: String, start_line: Option<usize>, end_line: Option<usize>) -> ContextAttachment {
+    let content = match start_line {
+        Some(start) => {
+            let end = end_line.unwrap_or(start);
+            content
+                .lines()
+                .skip(start.saturating_sub(1))
+                .take(end.saturating_sub(start) + 1)
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        None => content,
+    };
+
+    ContextAttachment {
+        path: path.to_string(),
+        start_line,
+        end_line,
+        content,
+    }
+}
+
+fn render_attachment(attachment: &ContextAttachment) -> String {
+    let location = match (attachment.start_line, attachment.end_line) {
+        (Some(start), Some(end)) => format!("{} (lines {}-{})", attachment.path, start, end),
+        (Some(start), None) => format!("{} (line {})", attachment.path, start),
+        _ => attachment.path.clone(),
+    };
+    format!("Context from {}:\n```\n{}\n```\n\n", location, attachment.content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_context_moves_to_user_message() {
+        let mut chat = ChatManager::new();
+        let session = chat.create_session(None).unwrap();
+        chat.attach_context(
+            &session.id,
+            ContextAttachment {
+                path: "src/a.ts".to_string(),
+                start_line: Some(1),
+                end_line: Some(2),
+                content: "let a = 1;".to_string(),
+            },
+        )
+        .unwrap();
+
+        let entry = chat.append_message(&session.id, Role::User, "What does a do?").unwrap();
+        assert_eq!(entry.context.len(), 1);
+        assert_eq!(chat.get_session(&session.id).unwrap().title, "What does a do?");
+
+        let messages = chat.prompt_messages(&session.id, "gpt-4o").unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[1].content.contains("let a = 1;"));
+    }
+}
@@ -1,17 +1,36 @@
 // Code Analyzer - Static analysis for code suggestions
 // Provides intelligent code insights without full LSP
 
+use std::collections::{HashMap, HashSet};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+use crate::a11y_rules;
+use crate::analysis_budget::AnalysisBudget;
+use crate::framework_rules::{self, Framework};
+use crate::imports::{self, ImportLanguage};
+use crate::python_rules;
+use crate::rust_rules;
+use crate::storage;
 use crate::CodeSuggestion;
 
 /// Lightweight code analyzer for quick suggestions
 pub struct CodeAnalyzer {
     enabled_rules: Vec<AnalysisRule>,
+    /// Frontend frameworks whose rules apply
+    frameworks: Vec<Framework>,
+    /// Rules to run, the built-in ones unless replaced with `with_registry`
+    registry: Arc<RuleRegistry>,
+    /// Rules switched off by id
+    disabled: Vec<String>,
+    budget: AnalysisBudget,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
 pub enum AnalysisRule {
     UnusedImports,
     MissingTypes,
@@ -20,50 +39,404 @@ pub enum AnalysisRule {
     DuplicateCode,
     SecurityPatterns,
     PerformanceHints,
+    Accessibility,
+}
+
+impl AnalysisRule {
+    pub const DEFAULT: [AnalysisRule; 6] = [
+        AnalysisRule::UnusedImports,
+        AnalysisRule::MissingTypes,
+        AnalysisRule::LongFunctions,
+        AnalysisRule::SecurityPatterns,
+        AnalysisRule::PerformanceHints,
+        AnalysisRule::Accessibility,
+    ];
+
+    /// Rule producing suggestions of `kind`
+    fn for_kind(kind: &str) -> Option<AnalysisRule> {
+        match kind {
+            "unused" => Some(AnalysisRule::UnusedImports),
+            "type" => Some(AnalysisRule::MissingTypes),
+            "complexity" => Some(AnalysisRule::LongFunctions),
+            "security" => Some(AnalysisRule::SecurityPatterns),
+            "performance" => Some(AnalysisRule::PerformanceHints),
+            "accessibility" => Some(AnalysisRule::Accessibility),
+            _ => None,
+        }
+    }
+}
+
+/// The file a rule checks
+pub struct FileContext<'a> {
+    pub path: &'a str,
+    /// Without the dot
+    pub extension: &'a str,
+    pub content: &'a str,
+    /// Frontend frameworks detected in the workspace
+    pub frameworks: &'a [Framework],
+}
+
+/// One check of the analyzer; built-in, or registered by an extension
+pub trait Rule: Send + Sync {
+    /// Stable identifier used to enable, disable and time the rule
+    fn id(&self) -> &str;
+    /// File extensions checked, without the dot; empty for every file
+    fn languages(&self) -> &[&str];
+    /// Findings depend only on their line and its enclosing function, so an excerpt of the file can be checked
+    fn local(&self) -> bool {
+        false
+    }
+    fn check(&self, file: &FileContext) -> Result<Vec<CodeSuggestion>>;
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RuleInfo {
+    pub id: String,
+    pub languages: Vec<String>,
+    pub enabled: bool,
+    /// Re-run on the edited regions of unsaved buffers; other rules wait for a full analysis
+    pub local: bool,
+}
+
+/// A suggestion with the id of the rule that made it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RuleFinding {
+    pub rule: String,
+    pub suggestion: CodeSuggestion,
+}
+
+/// Time spent in one rule since startup
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RuleTiming {
+    pub id: String,
+    pub runs: u64,
+    pub total_micros: u64,
+    pub max_micros: u64,
+}
+
+/// A file whose last analysis went over its budget
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SlowFile {
+    pub path: String,
+    pub bytes: usize,
+    pub lines: usize,
+    pub duration_ms: u64,
+    /// Over the size budget, so no rule ran
+    pub too_large: bool,
+    /// Rules abandoned after the rule timeout
+    pub timed_out_rules: Vec<String>,
+    /// Rules not started because the file's time budget was used up
+    pub skipped_rules: Vec<String>,
+    pub analyzed_at: u64,
+}
+
+/// Owned copy of the checked file, for rules run on a watchdog thread
+struct OwnedFile {
+    path: String,
+    extension: String,
+    content: String,
+    frameworks: Vec<Framework>,
+}
+
+impl OwnedFile {
+    fn context(&self) -> FileContext<'_> {
+        FileContext {
+            path: &self.path,
+            extension: &self.extension,
+            content: &self.content,
+            frameworks: &self.frameworks,
+        }
+    }
+}
+
+/// Registry of analyzer rules, run in registration order
+pub struct RuleRegistry {
+    rules: Vec<Box<dyn Rule>>,
+    timings: Mutex<HashMap<String, RuleTiming>>,
+    /// Files over budget by path; a file analyzed within budget again leaves the list
+    slow_files: Mutex<HashMap<String, SlowFile>>,
+}
+
+impl RuleRegistry {
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            timings: Mutex::new(HashMap::new()),
+            slow_files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Registry with the built-in rules
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(UnusedImportsRule));
+        registry.register(Box::new(TypeScriptRule));
+        registry.register(Box::new(LongFunctionsRule));
+        registry.register(Box::new(RustRule));
+        registry.register(Box::new(PythonRule));
+        registry.register(Box::new(FrameworkRule));
+        registry.register(Box::new(AccessibilityRule));
+        registry
+    }
+
+    /// Add a rule; one with the same id is replaced in place
+    pub fn register(&mut self, rule: Box<dyn Rule>) {
+        match self.rules.iter().position(|r| r.id() == rule.id()) {
+            Some(i) => self.rules[i] = rule,
+            None => self.rules.push(rule),
+        }
+    }
+
+    pub fn list(&self, disabled: &[String]) -> Vec<RuleInfo> {
+        self.rules
+            .iter()
+            .map(|rule| RuleInfo {
+                id: rule.id().to_string(),
+                languages: rule.languages().iter().map(|l| l.to_string()).collect(),
+                enabled: !disabled.iter().any(|id| id == rule.id()),
+                local: rule.local(),
+            })
+            .collect()
+    }
+
+    /// Slowest rules first
+    pub fn timings(&self) -> Vec<RuleTiming> {
+        let mut timings: Vec<RuleTiming> = self.timings.lock().unwrap().values().cloned().collect();
+        timings.sort_by(|a, b| b.total_micros.cmp(&a.total_micros).then_with(|| a.id.cmp(&b.id)));
+        timings
+    }
+
+    /// Files over their analysis budget, slowest first
+    pub fn slow_files(&self) -> Vec<SlowFile> {
+        let mut files: Vec<SlowFile> = self.slow_files.lock().unwrap().values().cloned().collect();
+        files.sort_by(|a, b| b.duration_ms.cmp(&a.duration_ms).then_with(|| a.path.cmp(&b.path)));
+        files
+    }
+
+    /// Check with rule `index` on a watchdog thread; `None` if it did not finish within `timeout`.
+    /// An abandoned rule runs to completion in the background and its result is dropped.
+    fn check_with_timeout(
+        self: &Arc<Self>,
+        index: usize,
+        file: &Arc<OwnedFile>,
+        timeout: Duration,
+    ) -> Option<Result<Vec<CodeSuggestion>>> {
+        let (sender, receiver) = mpsc::channel();
+        let (registry, file) = (Arc::clone(self), Arc::clone(file));
+        thread::spawn(move || {
+            let _ = sender.send(registry.rules[index].check(&file.context()));
+        });
+        receiver.recv_timeout(timeout).ok()
+    }
+
+    /// Run the enabled rules for the file's language, only the local ones if `local_only`, within `budget`.
+    /// A failing rule is logged and skipped; a timed-out one leaves the findings of the others.
+    fn run(
+        self: &Arc<Self>,
+        file: &FileContext,
+        disabled: &[String],
+        local_only: bool,
+        budget: &AnalysisBudget,
+    ) -> Vec<RuleFinding> {
+        let file_started = Instant::now();
+        let mut slow = SlowFile {
+            path: file.path.to_string(),
+            bytes: file.content.len(),
+            lines: file.content.lines().count(),
+            duration_ms: 0,
+            too_large: false,
+            timed_out_rules: Vec::new(),
+            skipped_rules: Vec::new(),
+            analyzed_at: storage::now_millis(),
+        };
+        if budget.too_large(slow.bytes, slow.lines) {
+            log::warn!("Not analyzing {}: {} bytes, {} lines is over the budget", file.path, slow.bytes, slow.lines);
+            slow.too_large = true;
+            self.slow_files.lock().unwrap().insert(slow.path.clone(), slow);
+            return Vec::new();
+        }
+        let owned = budget.rule_timeout().map(|_| {
+            Arc::new(OwnedFile {
+                path: file.path.to_string(),
+                extension: file.extension.to_string(),
+                content: file.content.to_string(),
+                frameworks: file.frameworks.to_vec(),
+            })
+        });
+
+        let mut findings = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let languages = rule.languages();
+            let applies = languages.is_empty() || languages.contains(&file.extension);
+            if !applies || disabled.iter().any(|id| id == rule.id()) || (local_only && !rule.local()) {
+                continue;
+            }
+            if budget.file_time().is_some_and(|limit| file_started.elapsed() > limit) {
+                slow.skipped_rules.push(rule.id().to_string());
+                continue;
+            }
+            let started = Instant::now();
+            let result = match (&owned, budget.rule_timeout()) {
+                (Some(owned), Some(timeout)) => self.check_with_timeout(index, owned, timeout),
+                _ => Some(rule.check(file)),
+            };
+            let micros = started.elapsed().as_micros() as u64;
+            {
+                let mut timings = self.timings.lock().unwrap();
+                let timing = timings.entry(rule.id().to_string()).or_insert_with(|| RuleTiming {
+                    id: rule.id().to_string(),
+                    ..RuleTiming::default()
+                });
+                timing.runs += 1;
+                timing.total_micros += micros;
+                timing.max_micros = timing.max_micros.max(micros);
+            }
+            match result {
+                Some(Ok(found)) => findings.extend(found.into_iter().map(|suggestion| RuleFinding {
+                    rule: rule.id().to_string(),
+                    suggestion,
+                })),
+                Some(Err(e)) => log::warn!("Rule {} failed on {}: {}", rule.id(), file.path, e),
+                None => {
+                    log::warn!("Rule {} timed out on {}", rule.id(), file.path);
+                    slow.timed_out_rules.push(rule.id().to_string());
+                }
+            }
+        }
+
+        slow.duration_ms = file_started.elapsed().as_millis() as u64;
+        let mut slow_files = self.slow_files.lock().unwrap();
+        if slow.timed_out_rules.is_empty() && slow.skipped_rules.is_empty() {
+            slow_files.remove(&slow.path);
+        } else {
+            slow_files.insert(slow.path.clone(), slow);
+        }
+        findings
+    }
 }
 
 impl CodeAnalyzer {
     pub fn new() -> Self {
+        Self::with_rules(AnalysisRule::DEFAULT.to_vec())
+    }
+
+    pub fn with_rules(enabled_rules: Vec<AnalysisRule>) -> Self {
         Self {
-            enabled_rules: vec![
-                AnalysisRule::UnusedImports,
-                AnalysisRule::MissingTypes,
-                AnalysisRule::LongFunctions,
-                AnalysisRule::SecurityPatterns,
-                AnalysisRule::PerformanceHints,
-            ],
+            enabled_rules,
+            frameworks: Vec::new(),
+            registry: Arc::new(RuleRegistry::with_defaults()),
+            disabled: Vec::new(),
+            budget: AnalysisBudget::default(),
         }
     }
 
+    pub fn with_frameworks(mut self, frameworks: Vec<Framework>) -> Self {
+        self.frameworks = frameworks;
+        self
+    }
+
+    /// Limit the size and time of analyzing one file
+    pub fn with_budget(mut self, budget: AnalysisBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Run the rules of `registry`, skipping those whose id is in `disabled`
+    pub fn with_registry(mut self, registry: Arc<RuleRegistry>, disabled: Vec<String>) -> Self {
+        self.registry = registry;
+        self.disabled = disabled;
+        self
+    }
+
     /// Analyze code content and return suggestions
     pub fn analyze(&self, file_path: &str, content: &str) -> Result<Vec<CodeSuggestion>> {
-        let mut suggestions = Vec::new();
+        Ok(self.findings(file_path, content, false).into_iter().map(|f| f.suggestion).collect())
+    }
 
+    /// Suggestions with the rule that made each; `local_only` runs just the rules that can check an excerpt
+    pub fn findings(&self, file_path: &str, content: &str, local_only: bool) -> Vec<RuleFinding> {
         let extension = file_path
             .split('.')
             .last()
             .unwrap_or("");
+        let file = FileContext {
+            path: file_path,
+            extension,
+            content,
+            frameworks: &self.frameworks,
+        };
+        let mut findings = self.registry.run(&file, &self.disabled, local_only, &self.budget);
+
+        // Quality and style hints are not tied to a rule and always apply
+        findings.retain(|f| {
+            AnalysisRule::for_kind(&f.suggestion.kind)
+                .map(|rule| self.enabled_rules.contains(&rule))
+                .unwrap_or(true)
+        });
+        findings
+    }
 
-        match extension {
-            "ts" | "tsx" | "js" | "jsx" => {
-                suggestions.extend(self.analyze_typescript(content)?);
-            }
-            "rs" => {
-                suggestions.extend(self.analyze_rust(content)?);
-            }
-            "py" => {
-                suggestions.extend(self.analyze_python(content)?);
-            }
-            _ => {}
-        }
+    /// Enabled rules for `file_path` that are not local, so an excerpt does not re-run them
+    pub fn file_rules(&self, file_path: &str) -> Vec<String> {
+        let extension = file_path.split('.').last().unwrap_or("");
+        self.registry
+            .list(&self.disabled)
+            .into_iter()
+            .filter(|rule| rule.enabled && !rule.local)
+            .filter(|rule| rule.languages.is_empty() || rule.languages.iter().any(|l| l == extension))
+            .map(|rule| rule.id)
+            .collect()
+    }
+}
 
-        Ok(suggestions)
+const IMPORT_LANGUAGES: &[&str] = &["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs", "py", "rs"];
+const TYPESCRIPT: &[&str] = &["ts", "tsx", "js", "jsx"];
+
+struct UnusedImportsRule;
+
+impl Rule for UnusedImportsRule {
+    fn id(&self) -> &str {
+        "unused_imports"
+    }
+
+    fn languages(&self) -> &[&str] {
+        IMPORT_LANGUAGES
     }
 
-    /// Analyze TypeScript/JavaScript code
-    fn analyze_typescript(&self, content: &str) -> Result<Vec<CodeSuggestion>> {
+    fn check(&self, file: &FileContext) -> Result<Vec<CodeSuggestion>> {
+        Ok(unused_imports(file.path, file.content)
+            .into_iter()
+            .map(|unused| CodeSuggestion {
+                kind: "unused".to_string(),
+                message: format!("'{}' is imported but never used", unused.name),
+                line: unused.line,
+                column: 0,
+                severity: "warning".to_string(),
+                fix: None,
+            })
+            .collect())
+    }
+}
+
+/// Line checks of TypeScript/JavaScript code
+struct TypeScriptRule;
+
+impl Rule for TypeScriptRule {
+    fn id(&self) -> &str {
+        "typescript"
+    }
+
+    fn languages(&self) -> &[&str] {
+        TYPESCRIPT
+    }
+
+    fn local(&self) -> bool {
+        true
+    }
+
+    fn check(&self, file: &FileContext) -> Result<Vec<CodeSuggestion>> {
         let mut suggestions = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
+        let lines: Vec<&str> = file.content.lines().collect();
 
         for (i, line) in lines.iter().enumerate() {
             let line_num = i + 1;
@@ -142,9 +515,28 @@ impl CodeAnalyzer {
             }
         }
 
-        // Check for long functions
-        let function_lengths = self.detect_function_lengths(content);
-        for (name, start_line, length) in function_lengths {
+        Ok(suggestions)
+    }
+}
+
+struct LongFunctionsRule;
+
+impl Rule for LongFunctionsRule {
+    fn id(&self) -> &str {
+        "long_functions"
+    }
+
+    fn languages(&self) -> &[&str] {
+        TYPESCRIPT
+    }
+
+    fn local(&self) -> bool {
+        true
+    }
+
+    fn check(&self, file: &FileContext) -> Result<Vec<CodeSuggestion>> {
+        let mut suggestions = Vec::new();
+        for (name, start_line, length) in detect_function_lengths(file.content) {
             if length > 50 {
                 suggestions.push(CodeSuggestion {
                     kind: "complexity".to_string(),
@@ -156,63 +548,45 @@ impl CodeAnalyzer {
                 });
             }
         }
-
         Ok(suggestions)
     }
+}
 
-    /// Analyze Rust code
-    fn analyze_rust(&self, content: &str) -> Result<Vec<CodeSuggestion>> {
-        let mut suggestions = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
+/// Rust checks on the syntax tree
+struct RustRule;
 
-        for (i, line) in lines.iter().enumerate() {
-            let line_num = i + 1;
-            let trimmed = line.trim();
+impl Rule for RustRule {
+    fn id(&self) -> &str {
+        "rust"
+    }
 
-            // Check for unwrap() usage
-            if trimmed.contains(".unwrap()") {
-                suggestions.push(CodeSuggestion {
-                    kind: "quality".to_string(),
-                    message: "Consider using ? operator or proper error handling instead of unwrap()".to_string(),
-                    line: line_num,
-                    column: line.find("unwrap").unwrap_or(0),
-                    severity: "warning".to_string(),
-                    fix: None,
-                });
-            }
+    fn languages(&self) -> &[&str] {
+        &["rs"]
+    }
 
-            // Check for panic!
-            if trimmed.contains("panic!") && !trimmed.starts_with("//") {
-                suggestions.push(CodeSuggestion {
-                    kind: "quality".to_string(),
-                    message: "Consider returning Result instead of using panic!".to_string(),
-                    line: line_num,
-                    column: line.find("panic").unwrap_or(0),
-                    severity: "warning".to_string(),
-                    fix: None,
-                });
-            }
+    fn check(&self, file: &FileContext) -> Result<Vec<CodeSuggestion>> {
+        Ok(rust_rules::analyze(file.path, file.content))
+    }
+}
 
-            // Check for unsafe blocks
-            if trimmed.starts_with("unsafe") {
-                suggestions.push(CodeSuggestion {
-                    kind: "security".to_string(),
-                    message: "Unsafe block detected - ensure memory safety is maintained".to_string(),
-                    line: line_num,
-                    column: 0,
-                    severity: "info".to_string(),
-                    fix: None,
-                });
-            }
-        }
+struct PythonRule;
 
-        Ok(suggestions)
+impl Rule for PythonRule {
+    fn id(&self) -> &str {
+        "python"
     }
 
-    /// Analyze Python code
-    fn analyze_python(&self, content: &str) -> Result<Vec<CodeSuggestion>> {
+    fn languages(&self) -> &[&str] {
+        &["py"]
+    }
+
+    fn local(&self) -> bool {
+        true
+    }
+
+    fn check(&self, file: &FileContext) -> Result<Vec<CodeSuggestion>> {
         let mut suggestions = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
+        let lines: Vec<&str> = file.content.lines().collect();
 
         for (i, line) in lines.iter().enumerate() {
             let line_num = i + 1;
@@ -243,62 +617,156 @@ impl CodeAnalyzer {
             }
         }
 
+        suggestions.extend(python_rules::analyze(file.content));
         Ok(suggestions)
     }
+}
 
-    /// Detect function lengths (simplified)
-    fn detect_function_lengths(&self, content: &str) -> Vec<(String, usize, usize)> {
-        let mut results = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
-        
-        let mut in_function = false;
-        let mut function_name = String::new();
-        let mut function_start = 0;
-        let mut brace_count = 0;
+/// Rules of the frameworks detected in the workspace
+struct FrameworkRule;
 
-        for (i, line) in lines.iter().enumerate() {
-            let trimmed = line.trim();
+impl Rule for FrameworkRule {
+    fn id(&self) -> &str {
+        "frameworks"
+    }
 
-            // Detect function start (simplified)
-            if (trimmed.starts_with("function ") || 
-                trimmed.starts_with("async function ") ||
-                trimmed.contains("= function") ||
-                trimmed.contains("=> {") ||
-                (trimmed.contains("(") && trimmed.contains(") {") && !trimmed.starts_with("//")))
-                && !in_function
-            {
-                in_function = true;
-                function_start = i + 1;
-                
-                // Extract name (simplified)
-                if let Some(start) = trimmed.find("function ") {
-                    let rest = &trimmed[start + 9..];
-                    function_name = rest
-                        .chars()
-                        .take_while(|c| c.is_alphanumeric() || *c == '_')
-                        .collect();
-                } else {
-                    function_name = format!("anonymous@{}", i + 1);
-                }
+    fn languages(&self) -> &[&str] {
+        &[]
+    }
+
+    fn check(&self, file: &FileContext) -> Result<Vec<CodeSuggestion>> {
+        Ok(framework_rules::analyze(file.path, file.content, file.frameworks))
+    }
+}
+
+struct AccessibilityRule;
+
+impl Rule for AccessibilityRule {
+    fn id(&self) -> &str {
+        "accessibility"
+    }
+
+    fn languages(&self) -> &[&str] {
+        &[]
+    }
+
+    fn check(&self, file: &FileContext) -> Result<Vec<CodeSuggestion>> {
+        Ok(a11y_rules::analyze(file.path, file.content))
+    }
+}
+
+/// Detect function lengths (simplified)
+fn detect_function_lengths(content: &str) -> Vec<(String, usize, usize)> {
+    let mut results = Vec::new();
+    let lines: Vec<&str> = content.lines().collect();
+    
+    let mut in_function = false;
+    let mut function_name = String::new();
+    let mut function_start = 0;
+    let mut brace_count = 0;
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+
+        // Detect function start (simplified)
+        if (trimmed.starts_with("function ") || 
+            trimmed.starts_with("async function ") ||
+            trimmed.contains("= function") ||
+            trimmed.contains("=> {") ||
+            (trimmed.contains("(") && trimmed.contains(") {") && !trimmed.starts_with("//")))
+            && !in_function
+        {
+            in_function = true;
+            function_start = i + 1;
+            
+            // Extract name (simplified)
+            if let Some(start) = trimmed.find("function ") {
+                let rest = &trimmed[start + 9..];
+                function_name = rest
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect();
+            } else {
+                function_name = format!("anonymous@{}", i + 1);
             }
+        }
 
-            // Count braces
-            for c in line.chars() {
-                if c == '{' {
-                    brace_count += 1;
-                } else if c == '}' {
-                    brace_count -= 1;
-                    if brace_count == 0 && in_function {
-                        let length = i + 1 - function_start;
-                        results.push((function_name.clone(), function_start, length));
-                        in_function = false;
-                    }
+        // Count braces
+        for c in line.chars() {
+            if c == '{' {
+                brace_count += 1;
+            } else if c == '}' {
+                brace_count -= 1;
+                if brace_count == 0 && in_function {
+                    let length = i + 1 - function_start;
+                    results.push((function_name.clone(), function_start, length));
+                    in_function = false;
                 }
             }
         }
+    }
 
-        results
+    results
+}
+
+/// An imported binding never referenced outside the import block
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UnusedImport {
+    /// First line of the import statement
+    pub line: usize,
+    pub name: String,
+}
+
+/// Traits are used through method calls without naming them
+fn is_likely_trait(name: &str) -> bool {
+    name.ends_with("Ext")
+        || matches!(
+            name,
+            "Read" | "Write" | "BufRead" | "Seek" | "Digest" | "FromStr" | "Hasher" | "Itertools"
+                | "Context" | "Manager" | "Emitter" | "Borrow" | "Deref" | "DerefMut"
+        )
+}
+
+/// Imported names that do not occur anywhere else in the file
+pub fn unused_imports(file_path: &str, content: &str) -> Vec<UnusedImport> {
+    let language = match ImportLanguage::for_path(file_path) {
+        Some(language) => language,
+        None => return Vec::new(),
+    };
+    // Package `__init__` files import to re-export
+    if file_path.ends_with("__init__.py") {
+        return Vec::new();
+    }
+
+    let parsed = imports::parse(file_path, content);
+    let import_lines: HashSet<usize> = parsed.iter().flat_map(|i| i.start_line..=i.end_line).collect();
+    let body = content
+        .lines()
+        .enumerate()
+        .filter(|(i, _)| !import_lines.contains(&(i + 1)))
+        .map(|(_, line)| line)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let words: HashSet<&str> = body
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .collect();
+
+    let mut unused = Vec::new();
+    for import in &parsed {
+        if import.public || import.opaque || import.side_effect {
+            continue;
+        }
+        for name in import.bindings() {
+            if words.contains(name.as_str()) || (language == ImportLanguage::Rust && is_likely_trait(&name)) {
+                continue;
+            }
+            unused.push(UnusedImport {
+                line: import.start_line,
+                name,
+            });
+        }
     }
+    unused
 }
 
 fn file_path_contains(content: &str, pattern: &str) -> bool {
@@ -322,4 +790,99 @@ mod tests {
         let suggestions = analyzer.analyze("test.ts", code).unwrap();
         assert!(suggestions.iter().any(|s| s.message.contains("any")));
     }
+
+    struct TodoRule;
+
+    impl Rule for TodoRule {
+        fn id(&self) -> &str {
+            "todo"
+        }
+
+        fn languages(&self) -> &[&str] {
+            &["ts"]
+        }
+
+        fn check(&self, file: &FileContext) -> Result<Vec<CodeSuggestion>> {
+            Ok(file
+                .content
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| line.contains("TODO"))
+                .map(|(i, _)| CodeSuggestion {
+                    kind: "todo".to_string(),
+                    message: "Unfinished work".to_string(),
+                    line: i + 1,
+                    column: 0,
+                    severity: "info".to_string(),
+                    fix: None,
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_registered_rules_can_be_disabled_and_are_timed() {
+        let mut registry = RuleRegistry::with_defaults();
+        registry.register(Box::new(TodoRule));
+        let registry = Arc::new(registry);
+        let code = "// TODO: type this\nconst x: any = 5;";
+
+        let analyzer = CodeAnalyzer::new().with_registry(registry.clone(), Vec::new());
+        let kinds: Vec<String> = analyzer.analyze("a.ts", code).unwrap().into_iter().map(|s| s.kind).collect();
+        assert_eq!(kinds, vec!["type", "todo"]);
+        assert!(analyzer.analyze("a.py", code).unwrap().iter().all(|s| s.kind != "todo"));
+
+        let disabled = vec!["typescript".to_string()];
+        let analyzer = CodeAnalyzer::new().with_registry(registry.clone(), disabled.clone());
+        let kinds: Vec<String> = analyzer.analyze("a.ts", code).unwrap().into_iter().map(|s| s.kind).collect();
+        assert_eq!(kinds, vec!["todo"]);
+        assert!(!registry.list(&disabled).iter().find(|r| r.id == "typescript").unwrap().enabled);
+        assert_eq!(registry.timings().iter().find(|t| t.id == "todo").unwrap().runs, 2);
+    }
+
+    struct StuckRule;
+
+    impl Rule for StuckRule {
+        fn id(&self) -> &str {
+            "stuck"
+        }
+
+        fn languages(&self) -> &[&str] {
+            &["ts"]
+        }
+
+        fn check(&self, _file: &FileContext) -> Result<Vec<CodeSuggestion>> {
+            thread::sleep(Duration::from_millis(500));
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_budget_abandons_slow_rules_and_large_files() {
+        let mut registry = RuleRegistry::with_defaults();
+        registry.register(Box::new(StuckRule));
+        let registry = Arc::new(registry);
+        let budget = AnalysisBudget {
+            rule_timeout_ms: 50,
+            max_file_lines: 100,
+            ..AnalysisBudget::default()
+        };
+        let analyzer = CodeAnalyzer::new().with_registry(registry.clone(), Vec::new()).with_budget(budget);
+
+        let suggestions = analyzer.analyze("a.ts", "const x: any = 5;").unwrap();
+        assert!(suggestions.iter().any(|s| s.kind == "type"));
+        assert!(analyzer.analyze("big.ts", &"const x: any = 5;\n".repeat(200)).unwrap().is_empty());
+
+        let slow = registry.slow_files();
+        assert_eq!(slow.len(), 2);
+        assert_eq!(slow.iter().find(|f| f.path == "a.ts").unwrap().timed_out_rules, vec!["stuck"]);
+        assert!(slow.iter().find(|f| f.path == "big.ts").unwrap().too_large);
+    }
+
+    #[test]
+    fn test_unused_imports() {
+        let code = "import React, { useState, useMemo } from 'react';\n\nexport const App = () => useState(0);\n";
+        let unused: Vec<String> = unused_imports("app.tsx", code).into_iter().map(|u| u.name).collect();
+        assert_eq!(unused, vec!["React", "useMemo"]);
+    }
 }
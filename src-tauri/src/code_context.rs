@@ -0,0 +1,563 @@
+// Code Context - Locate symbol definitions and call sites
+// Shared context builder for the AI generation commands
+
+use std::fs;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::mimi_engine::CodeGraph;
+use crate::syntax;
+
+/// Lines of context shown around a call site
+const REFERENCE_CONTEXT_LINES: usize = 2;
+
+/// Source range of a symbol definition
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SymbolSpan {
+    pub name: String,
+    pub kind: String,
+    /// 1-based inclusive line range, including attributes and `export`
+    pub start_line: usize,
+    pub end_line: usize,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    /// Declaration up to (not including) the body
+    pub signature: String,
+    /// Leading whitespace of the declaration line
+    pub indent: String,
+}
+
+impl SymbolSpan {
+    pub fn text<'a>(&self, content: &'a str) -> &'a str {
+        &content[self.start_byte..self.end_byte]
+    }
+}
+
+/// Declaration of a type and the file it lives in
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TypeDefinition {
+    pub file: String,
+    pub span: SymbolSpan,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SymbolReference {
+    pub file: String,
+    pub line: usize,
+    pub snippet: String,
+}
+
+/// Node kinds that declare a type
+const TYPE_KINDS: &[&str] = &[
+    "struct_item",
+    "enum_item",
+    "union_item",
+    "trait_item",
+    "type_item",
+    "class_declaration",
+    "abstract_class_declaration",
+    "interface_declaration",
+    "type_alias_declaration",
+    "enum_declaration",
+];
+
+/// Node kinds that declare a named symbol
+const DEFINITION_KINDS: &[&str] = &[
+    // Rust
+    "function_item",
+    "function_signature_item",
+    "struct_item",
+    "enum_item",
+    "trait_item",
+    "mod_item",
+    "const_item",
+    "static_item",
+    "type_item",
+    "macro_definition",
+    // TypeScript / JavaScript
+    "function_declaration",
+    "generator_function_declaration",
+    "class_declaration",
+    "abstract_class_declaration",
+    "method_definition",
+    "interface_declaration",
+    "type_alias_declaration",
+    "enum_declaration",
+    "variable_declarator",
+];
+
+/// Find the definition of `name` in a document
+pub fn find_definition(path: &str, content: &str, name: &str) -> Option<SymbolSpan> {
+    if path.ends_with(".py") {
+        return find_python_definition(content, name);
+    }
+    let tree = syntax::parse(path, content)?;
+    let node = find_named_node(tree.root_node(), content.as_bytes(), name, DEFINITION_KINDS)?;
+    Some(span_for(node, content, name))
+}
+
+/// Find the declaration of the type `name` (struct, enum, trait, class, interface, alias)
+pub fn find_type_definition(path: &str, content: &str, name: &str) -> Option<SymbolSpan> {
+    if path.ends_with(".py") {
+        return find_python_definition(content, name).filter(|span| span.kind == "class");
+    }
+    let tree = syntax::parse(path, content)?;
+    let node = find_named_node(tree.root_node(), content.as_bytes(), name, TYPE_KINDS)?;
+    Some(span_for(node, content, name))
+}
+
+/// Declaration of type `name` among `candidates`, checking the files that mention it
+pub fn locate_type(candidates: &[String], name: &str) -> Option<TypeDefinition> {
+    candidates.iter().find_map(|file| {
+        if syntax::language_for(file).is_none() && !file.ends_with(".py") {
+            return None;
+        }
+        let content = fs::read_to_string(file).ok()?;
+        if !content.contains(name) {
+            return None;
+        }
+        find_type_definition(file, &content, name).map(|span| TypeDefinition {
+            file: file.clone(),
+            span,
+        })
+    })
+}
+
+/// Generic wrappers skipped when naming the type of a value
+const WRAPPER_TYPES: &[&str] = &[
+    "Option", "Result", "Vec", "Box", "Rc", "Arc", "RefCell", "Cell", "Mutex", "RwLock", "HashMap",
+    "Promise", "Array", "ReadonlyArray", "Partial", "Readonly", "Required", "Record", "Set", "Map",
+    "Optional", "List", "Dict",
+];
+
+/// Declarations that may carry a type annotation, with the field naming what they bind
+const TYPED_BINDINGS: &[(&str, &str)] = &[
+    ("required_parameter", "pattern"),
+    ("optional_parameter", "pattern"),
+    ("variable_declarator", "name"),
+    ("public_field_definition", "name"),
+    ("property_signature", "name"),
+    ("parameter", "pattern"),
+    ("let_declaration", "pattern"),
+    ("field_declaration", "name"),
+];
+
+fn last_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path).trim()
+}
+
+/// Principal type named by a type node: `Arc<Mutex<Foo>>` is `Foo`
+fn principal_type(node: Node, source: &[u8]) -> Option<String> {
+    fn collect(node: Node, source: &[u8], names: &mut Vec<String>) {
+        if node.kind() == "type_identifier" {
+            if let Ok(text) = node.utf8_text(source) {
+                names.push(text.to_string());
+            }
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        for child in children {
+            collect(child, source, names);
+        }
+    }
+    let mut names = Vec::new();
+    collect(node, source, &mut names);
+    names
+        .iter()
+        .find(|n| !WRAPPER_TYPES.contains(&n.as_str()))
+        .or_else(|| names.first())
+        .cloned()
+}
+
+/// Type constructed by an initializer: `new Foo()`, `Foo::new()`, `Foo { .. }`
+fn constructed_type(value: Node, source: &[u8]) -> Option<String> {
+    let text = |node: Node| node.utf8_text(source).ok().map(str::to_string);
+    match value.kind() {
+        "new_expression" => text(value.child_by_field_name("constructor")?),
+        "struct_expression" => text(value.child_by_field_name("name")?).map(|n| last_segment(&n).to_string()),
+        "call_expression" => {
+            let function = value.child_by_field_name("function")?;
+            let path = function.child_by_field_name("path")?;
+            let name = text(path)?;
+            let name = last_segment(&name);
+            name.starts_with(|c: char| c.is_uppercase()).then(|| name.to_string())
+        }
+        "try_expression" | "await_expression" | "parenthesized_expression" => constructed_type(value.named_child(0)?, source),
+        _ => None,
+    }
+}
+
+/// Name of the type of the symbol at byte `offset`, from annotations or constructors
+pub fn type_name_at(path: &str, content: &str, offset: usize) -> Option<String> {
+    if path.ends_with(".py") {
+        return python_type_name_at(content, offset);
+    }
+    let tree = syntax::parse(path, content)?;
+    let source = content.as_bytes();
+    let root = tree.root_node();
+    let node = root.descendant_for_byte_range(offset, offset)?;
+    let name = node.utf8_text(source).ok()?;
+    match node.kind() {
+        "type_identifier" => return Some(name.to_string()),
+        "identifier" | "property_identifier" | "field_identifier" | "shorthand_property_identifier" => {}
+        _ => return None,
+    }
+
+    // Nearest typed binding of the name, preferring ones declared before the cursor
+    fn bindings<'t>(node: Node<'t>, source: &[u8], name: &str, found: &mut Vec<Node<'t>>) {
+        let binds = TYPED_BINDINGS.iter().any(|(kind, field)| {
+            node.kind() == *kind
+                && node
+                    .child_by_field_name(field)
+                    .and_then(|n| n.utf8_text(source).ok())
+                    .map(|text| text == name)
+                    .unwrap_or(false)
+        });
+        if binds {
+            found.push(node);
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node<'t>> = node.named_children(&mut cursor).collect();
+        for child in children {
+            bindings(child, source, name, found);
+        }
+    }
+    let mut found = Vec::new();
+    bindings(root, source, name, &mut found);
+    found.sort_by_key(|b| (b.start_byte() > offset, std::cmp::Reverse(b.start_byte())));
+
+    found.into_iter().find_map(|binding| match binding.child_by_field_name("type") {
+        Some(annotation) => principal_type(annotation, source),
+        None => constructed_type(binding.child_by_field_name("value")?, source),
+    })
+}
+
+/// `name: Type` annotations and `name = Type(...)` assignments above the cursor
+fn python_type_name_at(content: &str, offset: usize) -> Option<String> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let start = content[..offset].rfind(|c: char| !is_word(c)).map(|i| i + 1).unwrap_or(0);
+    let end = content[offset..].find(|c: char| !is_word(c)).map(|i| offset + i).unwrap_or(content.len());
+    let name = &content[start..end];
+    if name.is_empty() {
+        return None;
+    }
+    // A capitalized name is taken to be the type itself
+    if name.starts_with(|c: char| c.is_uppercase()) {
+        return Some(name.to_string());
+    }
+
+    let type_word = |text: &str| -> Option<String> {
+        let words: Vec<&str> = text
+            .split(|c: char| !(is_word(c) || c == '.'))
+            .filter(|w| !w.is_empty())
+            .collect();
+        words
+            .iter()
+            .map(|w| w.rsplit('.').next().unwrap_or(w))
+            .find(|w| w.starts_with(|c: char| c.is_uppercase()) && !WRAPPER_TYPES.contains(w))
+            .map(str::to_string)
+    };
+    let line_end = content[offset..].find('\n').map(|i| offset + i).unwrap_or(content.len());
+    for line in content[..line_end].lines().rev() {
+        for (index, _) in line.match_indices(name) {
+            let before = line[..index].chars().last();
+            if before.map(|c| is_word(c) || c == '.').unwrap_or(false) {
+                continue;
+            }
+            let rest = &line[index + name.len()..];
+            if rest.starts_with(|c: char| is_word(c)) {
+                continue;
+            }
+            let rest = rest.trim_start();
+            if let Some(annotation) = rest.strip_prefix(':') {
+                let annotation = annotation.split(['=', ',', ')']).next().unwrap_or("");
+                if let Some(found) = type_word(annotation) {
+                    return Some(found);
+                }
+            } else if let Some(value) = rest.strip_prefix('=').filter(|v| !v.starts_with('=')) {
+                let callee = value.trim().split('(').next().unwrap_or("");
+                if value.contains('(') {
+                    if let Some(found) = type_word(callee) {
+                        return Some(found);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+fn find_named_node<'t>(node: Node<'t>, source: &[u8], name: &str, kinds: &[&str]) -> Option<Node<'t>> {
+    if kinds.contains(&node.kind()) {
+        let named = node
+            .child_by_field_name("name")
+            .and_then(|n| n.utf8_text(source).ok())
+            .map(|text| text == name)
+            .unwrap_or(false);
+        if named {
+            return Some(node);
+        }
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node<'t>> = node.children(&mut cursor).collect();
+    children
+        .into_iter()
+        .find_map(|child| find_named_node(child, source, name, kinds))
+}
+
+fn span_for(node: Node, content: &str, name: &str) -> SymbolSpan {
+    let kind = node.kind().to_string();
+    let body_start = match node.child_by_field_name("body") {
+        Some(body) => Some(body.start_byte()),
+        // `const f = (x) => ...` keeps the parameters in the signature
+        None => node.child_by_field_name("value").map(|value| {
+            value
+                .child_by_field_name("body")
+                .unwrap_or(value)
+                .start_byte()
+        }),
+    };
+
+    // Widen to the full statement: `const x = ...;`, `export ...`
+    let mut outer = node;
+    if outer.kind() == "variable_declarator" {
+        if let Some(parent) = outer.parent() {
+            outer = parent;
+        }
+    }
+    if let Some(parent) = outer.parent() {
+        if parent.kind() == "export_statement" {
+            outer = parent;
+        }
+    }
+
+    // Rust attributes belong to the item they precede
+    let mut start = outer;
+    while let Some(previous) = start.prev_sibling() {
+        if previous.kind() == "attribute_item" {
+            start = previous;
+        } else {
+            break;
+        }
+    }
+
+    let start_byte = start.start_byte();
+    let end_byte = outer.end_byte();
+    let signature_end = body_start.unwrap_or(end_byte);
+    let line_start = content[..start_byte].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let indent: String = content[line_start..]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect();
+
+    SymbolSpan {
+        name: name.to_string(),
+        kind,
+        start_line: start.start_position().row + 1,
+        end_line: outer.end_position().row + 1,
+        start_byte,
+        end_byte,
+        signature: content[outer.start_byte()..signature_end].trim_end().to_string(),
+        indent,
+    }
+}
+
+/// Indentation-based lookup; no Python grammar is bundled
+fn find_python_definition(content: &str, name: &str) -> Option<SymbolSpan> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let mut offset = 0;
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        let declaration = trimmed.strip_prefix("async ").unwrap_or(trimmed);
+        let kind = if declaration.starts_with("def ") {
+            "function"
+        } else if declaration.starts_with("class ") {
+            "class"
+        } else {
+            offset += line.len();
+            continue;
+        };
+        let rest = declaration.splitn(2, ' ').nth(1).unwrap_or("");
+        let declared: String = rest
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        if declared != name {
+            offset += line.len();
+            continue;
+        }
+
+        // Decorators directly above the declaration
+        let mut first = i;
+        let mut start_byte = offset;
+        while first > 0 && lines[first - 1].trim_start().starts_with('@') {
+            first -= 1;
+            start_byte -= lines[first].len();
+        }
+
+        // The body extends while lines are blank or more indented
+        let mut last = i;
+        let mut end_byte = offset + line.len();
+        for (j, next) in lines.iter().enumerate().skip(i + 1) {
+            let next_indent = next.len() - next.trim_start().len();
+            if next.trim().is_empty() || next_indent > indent.len() {
+                if !next.trim().is_empty() {
+                    last = j;
+                    end_byte = lines[..=j].iter().map(|l| l.len()).sum();
+                }
+            } else {
+                break;
+            }
+        }
+
+        return Some(SymbolSpan {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            start_line: first + 1,
+            end_line: last + 1,
+            start_byte,
+            end_byte,
+            signature: trimmed.trim_end().trim_end_matches(':').to_string(),
+            indent: indent.to_string(),
+        });
+    }
+    None
+}
+
+/// Whether `line[index..]` starts a call of `name`
+fn is_call_at(line: &str, index: usize, name: &str) -> bool {
+    let boundary_before = line[..index]
+        .chars()
+        .last()
+        .map(|c| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(true);
+    let after = line[index + name.len()..].trim_start();
+    boundary_before && (after.starts_with('(') || after.starts_with("::<") || after.starts_with('<'))
+}
+
+/// Call sites of `name` in the file and the files that import it
+pub fn find_callers(
+    graph: &CodeGraph,
+    file: &str,
+    name: &str,
+    definition: Option<&SymbolSpan>,
+    limit: usize,
+) -> Vec<SymbolReference> {
+    let mut candidates = vec![file.to_string()];
+    candidates.extend(graph.get_dependents(file));
+
+    let mut references = Vec::new();
+    for candidate in candidates {
+        let content = match fs::read_to_string(&candidate) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        for (i, line) in lines.iter().enumerate() {
+            let line_number = i + 1;
+            let inside_definition = candidate == file
+                && definition
+                    .map(|d| line_number >= d.start_line && line_number <= d.end_line)
+                    .unwrap_or(false);
+            if inside_definition {
+                continue;
+            }
+            let called = line
+                .match_indices(name)
+                .any(|(index, _)| is_call_at(line, index, name));
+            if !called {
+                continue;
+            }
+
+            let from = i.saturating_sub(REFERENCE_CONTEXT_LINES);
+            let to = (i + REFERENCE_CONTEXT_LINES + 1).min(lines.len());
+            references.push(SymbolReference {
+                file: candidate.clone(),
+                line: line_number,
+                snippet: lines[from..to].join("\n"),
+            });
+            if references.len() >= limit {
+                return references;
+            }
+        }
+    }
+    references
+}
+
+/// Definitions of the identifiers used on `line`, local ones first
+pub fn related_definitions(
+    graph: &CodeGraph,
+    file: &str,
+    content: &str,
+    line: usize,
+    limit: usize,
+) -> Vec<(String, SymbolSpan)> {
+    let text = content.lines().nth(line.saturating_sub(1)).unwrap_or("");
+    let mut seen = Vec::new();
+    let mut related = Vec::new();
+
+    for word in text.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+        if word.len() < 3 || word.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(true) {
+            continue;
+        }
+        if seen.iter().any(|w| w == word) {
+            continue;
+        }
+        seen.push(word.to_string());
+
+        let local = find_definition(file, content, word)
+            .filter(|span| line < span.start_line || line > span.end_line);
+        let found = match local {
+            Some(span) => Some((file.to_string(), span)),
+            None => graph.find_symbol(word).into_iter().find_map(|symbol| {
+                let source = fs::read_to_string(&symbol.file).ok()?;
+                find_definition(&symbol.file, &source, word).map(|span| (symbol.file.clone(), span))
+            }),
+        };
+        if let Some(definition) = found {
+            related.push(definition);
+            if related.len() >= limit {
+                break;
+            }
+        }
+    }
+    related
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_definition() {
+        let rust = "use x;\n\n#[inline]\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n";
+        let span = find_definition("lib.rs", rust, "add").unwrap();
+        assert_eq!((span.start_line, span.end_line), (3, 6));
+        assert_eq!(span.signature, "pub fn add(a: i32, b: i32) -> i32");
+
+        let python = "import os\n\n@cache\ndef load(path):\n    return open(path)\n\nx = 1\n";
+        let span = find_definition("mod.py", python, "load").unwrap();
+        assert_eq!((span.start_line, span.end_line), (3, 5));
+    }
+
+    #[test]
+    fn test_type_name_at() {
+        let ts = "interface Config { port: number }
+function start(config: Readonly<Config>) {
+  return config.port;
+}
+";
+        let offset = ts.rfind("config").unwrap();
+        assert_eq!(type_name_at("app.ts", ts, offset).as_deref(), Some("Config"));
+
+        let rust = "fn main() {
+    let graph = CodeGraph::new();
+    graph.edge_count();
+}
+";
+        let offset = rust.rfind("graph").unwrap();
+        assert_eq!(type_name_at("main.rs", rust, offset).as_deref(), Some("CodeGraph"));
+    }
+}
@@ -0,0 +1,100 @@
+// Code Owners - CODEOWNERS rules mapping workspace paths to owning teams
+// Used to narrow workspace-wide results such as diagnostics to code a team owns
+
+use std::fs;
+use std::path::Path;
+use anyhow::Result;
+
+use crate::search_query::glob_match;
+
+/// Where GitHub and GitLab look for the file, in order
+const LOCATIONS: [&str; 4] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS", ".gitlab/CODEOWNERS"];
+
+struct Rule {
+    pattern: String,
+    owners: Vec<String>,
+}
+
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+/// Gitignore-style pattern semantics: a leading or inner slash anchors the pattern at the root,
+/// otherwise it matches at any depth; a match on a directory covers everything below it
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.contains('/');
+    let pattern = trimmed.trim_start_matches('/');
+    let pattern = if anchored { pattern.to_string() } else { format!("**/{}", pattern) };
+    glob_match(&pattern, path) || glob_match(&format!("{}/**", pattern), path)
+}
+
+/// `@org/team` and `org/team` name the same owner
+fn same_owner(a: &str, b: &str) -> bool {
+    a.trim_start_matches('@').eq_ignore_ascii_case(b.trim_start_matches('@'))
+}
+
+impl CodeOwners {
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let pattern = parts.next()?.to_string();
+                // GitLab section headers like `[Docs]` group rules and are not patterns
+                if pattern.starts_with('[') {
+                    return None;
+                }
+                let owners = parts.take_while(|p| !p.starts_with('#')).map(str::to_string).collect();
+                Some(Rule { pattern, owners })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// The workspace CODEOWNERS file, if it has one
+    pub fn load(workspace: &Path) -> Result<Option<Self>> {
+        for location in LOCATIONS {
+            let path = workspace.join(location);
+            if path.is_file() {
+                return Ok(Some(Self::parse(&fs::read_to_string(path)?)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Owners of a workspace-relative path; the last matching rule wins, and a
+    /// matching rule without owners leaves the path unowned
+    pub fn owners_of(&self, path: &str) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| pattern_matches(&rule.pattern, path))
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn is_owned_by(&self, path: &str, owner: &str) -> bool {
+        self.owners_of(path).iter().any(|o| same_owner(o, owner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_matching_rule_wins() {
+        let owners = CodeOwners::parse(
+            "# Default owners\n* @org/core\n*.md @org/docs\n/src/ui/ @org/frontend @alice\nsrc/ui/generated/\n[Infra]\ndeploy @org/ops # cluster\n",
+        );
+        assert_eq!(owners.owners_of("README.md"), ["@org/docs"]);
+        assert_eq!(owners.owners_of("src/lib.rs"), ["@org/core"]);
+        assert_eq!(owners.owners_of("src/ui/button.tsx"), ["@org/frontend", "@alice"]);
+        assert!(owners.owners_of("src/ui/generated/api.ts").is_empty());
+        assert!(owners.is_owned_by("tools/deploy/run.sh", "org/ops"));
+        assert!(!owners.is_owned_by("src/ui/button.tsx", "@org/core"));
+    }
+}
@@ -0,0 +1,273 @@
+// Workspace Comparison - Index-level diff of two folders or git refs
+// File sets, content hashes, language stats and import/symbol structure
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::file_indexer::FileIndex;
+use crate::git;
+use crate::imports;
+use crate::symbols;
+
+/// Changed source files inspected for import and symbol changes
+const MAX_STRUCTURAL_FILES: usize = 500;
+
+/// One side of a comparison
+#[derive(Clone, Debug)]
+pub enum Side {
+    Folder(PathBuf),
+    GitRef { repo: PathBuf, rev: String },
+}
+
+impl Side {
+    /// An existing directory, or else a revision of the repository at `repo`
+    pub fn detect(spec: &str, repo: Option<&Path>) -> Result<Side> {
+        let path = Path::new(spec);
+        if path.is_dir() {
+            return Ok(Side::Folder(path.to_path_buf()));
+        }
+        let repo = repo.ok_or_else(|| anyhow!("{} is not a folder and no workspace is open", spec))?;
+        let rev = git::revision(spec)?;
+        git::run(repo, &["rev-parse", "--verify", "--quiet", "--end-of-options", &format!("{}^{{commit}}", rev)])
+            .map_err(|_| anyhow!("{} is neither a folder nor a git ref", spec))?;
+        Ok(Side::GitRef {
+            repo: repo.to_path_buf(),
+            rev: rev.to_string(),
+        })
+    }
+
+    /// Relative path -> git blob id, so folders and refs compare alike
+    fn files(&self) -> Result<HashMap<String, String>> {
+        match self {
+            Side::Folder(root) => {
+                let paths: Vec<String> = WalkDir::new(root)
+                    .into_iter()
+                    .filter_entry(|e| {
+                        let name = e.file_name().to_string_lossy();
+                        !matches!(name.as_ref(), "node_modules" | ".git" | "target") && name != crate::storage::DATA_DIR
+                    })
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.file_type().is_file())
+                    .filter_map(|e| {
+                        let relative = e.path().strip_prefix(root).ok()?;
+                        Some(relative.to_string_lossy().replace('\\', "/"))
+                    })
+                    .collect();
+                if paths.is_empty() {
+                    return Ok(HashMap::new());
+                }
+                let hashes = git::run_with_input(root, &["hash-object", "--no-filters", "--stdin-paths"], &(paths.join("\n") + "\n"))?;
+                Ok(paths.into_iter().zip(hashes.lines().map(str::to_string)).collect())
+            }
+            Side::GitRef { repo, rev } => {
+                let tree = git::run(repo, &["ls-tree", "-r", "-z", "--end-of-options", rev])?;
+                Ok(tree
+                    .split('\0')
+                    .filter_map(|entry| {
+                        let (meta, path) = entry.split_once('\t')?;
+                        let mut fields = meta.split_whitespace();
+                        let kind = fields.nth(1)?;
+                        let hash = fields.next()?;
+                        (kind == "blob").then(|| (path.to_string(), hash.to_string()))
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    fn read(&self, relative: &str) -> Option<String> {
+        match self {
+            Side::Folder(root) => fs::read_to_string(root.join(relative)).ok(),
+            Side::GitRef { repo, rev } => {
+                git::run(repo, &["show", "--end-of-options", &format!("{}:{}", rev, relative)]).ok()
+            }
+        }
+    }
+
+    /// Root that relative paths are resolved against, for module naming
+    fn root(&self) -> &Path {
+        match self {
+            Side::Folder(root) => root,
+            Side::GitRef { repo, .. } => repo,
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Side::Folder(root) => root.to_string_lossy().to_string(),
+            Side::GitRef { rev, .. } => rev.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LanguageDelta {
+    pub language: String,
+    pub left: usize,
+    pub right: usize,
+}
+
+/// Import and declaration changes of one file
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StructuralChange {
+    pub path: String,
+    pub added_imports: Vec<String>,
+    pub removed_imports: Vec<String>,
+    pub added_symbols: Vec<String>,
+    pub removed_symbols: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WorkspaceComparison {
+    pub left: String,
+    pub right: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+    pub unchanged: usize,
+    pub languages: Vec<LanguageDelta>,
+    pub structural: Vec<StructuralChange>,
+    /// Changed source files beyond `MAX_STRUCTURAL_FILES` were not inspected
+    pub structural_truncated: bool,
+}
+
+fn extension(path: &str) -> &str {
+    Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("")
+}
+
+fn language_counts(files: &HashMap<String, String>) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for path in files.keys() {
+        *counts.entry(FileIndex::detect_language(extension(path))).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Imported modules and `Container.name` declarations of a file
+fn structure(side: &Side, relative: &str) -> (HashSet<String>, HashSet<String>) {
+    let content = match side.read(relative) {
+        Some(content) => content,
+        None => return (HashSet::new(), HashSet::new()),
+    };
+    let path = side.root().join(relative);
+    let imports = imports::parse(relative, &content).into_iter().map(|i| i.module).collect();
+    let declarations = symbols::extract(side.root(), &path, &content)
+        .into_iter()
+        .map(|s| match s.container {
+            Some(container) => format!("{}.{}", container, s.name),
+            None => s.name,
+        })
+        .collect();
+    (imports, declarations)
+}
+
+fn sorted_difference(a: &HashSet<String>, b: &HashSet<String>) -> Vec<String> {
+    let mut difference: Vec<String> = a.difference(b).cloned().collect();
+    difference.sort();
+    difference
+}
+
+fn structural_change(left: &Side, right: &Side, path: &str) -> Option<StructuralChange> {
+    let (left_imports, left_symbols) = structure(left, path);
+    let (right_imports, right_symbols) = structure(right, path);
+    let change = StructuralChange {
+        path: path.to_string(),
+        added_imports: sorted_difference(&right_imports, &left_imports),
+        removed_imports: sorted_difference(&left_imports, &right_imports),
+        added_symbols: sorted_difference(&right_symbols, &left_symbols),
+        removed_symbols: sorted_difference(&left_symbols, &right_symbols),
+    };
+    let unchanged = change.added_imports.is_empty()
+        && change.removed_imports.is_empty()
+        && change.added_symbols.is_empty()
+        && change.removed_symbols.is_empty();
+    (!unchanged).then_some(change)
+}
+
+/// Compare two sides file by file, then structurally for changed source files
+pub fn compare(left: &Side, right: &Side) -> Result<WorkspaceComparison> {
+    let left_files = left.files()?;
+    let right_files = right.files()?;
+
+    let mut added: Vec<String> = right_files.keys().filter(|p| !left_files.contains_key(*p)).cloned().collect();
+    let mut removed: Vec<String> = left_files.keys().filter(|p| !right_files.contains_key(*p)).cloned().collect();
+    let mut changed: Vec<String> = left_files
+        .iter()
+        .filter(|(path, hash)| right_files.get(*path).map(|h| h != *hash).unwrap_or(false))
+        .map(|(path, _)| path.clone())
+        .collect();
+    added.sort();
+    removed.sort();
+    changed.sort();
+    let unchanged = left_files.len() - removed.len() - changed.len();
+
+    let left_languages = language_counts(&left_files);
+    let right_languages = language_counts(&right_files);
+    let names: BTreeSet<&String> = left_languages.keys().chain(right_languages.keys()).collect();
+    let languages = names
+        .into_iter()
+        .map(|language| LanguageDelta {
+            language: language.clone(),
+            left: left_languages.get(language).copied().unwrap_or(0),
+            right: right_languages.get(language).copied().unwrap_or(0),
+        })
+        .filter(|delta| delta.left != delta.right)
+        .collect();
+
+    let sources: Vec<&String> = added
+        .iter()
+        .chain(&removed)
+        .chain(&changed)
+        .filter(|p| matches!(extension(p), "ts" | "tsx" | "js" | "jsx" | "rs" | "py"))
+        .collect();
+    let structural = sources
+        .iter()
+        .take(MAX_STRUCTURAL_FILES)
+        .filter_map(|path| structural_change(left, right, path))
+        .collect();
+
+    Ok(WorkspaceComparison {
+        left: left.label(),
+        right: right.label(),
+        added,
+        removed,
+        changed,
+        unchanged,
+        languages,
+        structural,
+        structural_truncated: sources.len() > MAX_STRUCTURAL_FILES,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_folders() {
+        let root = std::env::temp_dir().join(crate::storage::new_id("compare-test"));
+        let (a, b) = (root.join("a"), root.join("b"));
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        fs::write(a.join("same.md"), "same").unwrap();
+        fs::write(b.join("same.md"), "same").unwrap();
+        fs::write(a.join("api.ts"), "export function get() {}\n").unwrap();
+        fs::write(b.join("api.ts"), "import { x } from './x';\nexport function post() {}\n").unwrap();
+        fs::write(b.join("new.rs"), "fn main() {}\n").unwrap();
+
+        let result = compare(&Side::Folder(a), &Side::Folder(b)).unwrap();
+        assert_eq!(result.added, vec!["new.rs"]);
+        assert_eq!(result.changed, vec!["api.ts"]);
+        assert_eq!(result.unchanged, 1);
+        let api = result.structural.iter().find(|c| c.path == "api.ts").unwrap();
+        assert_eq!(api.added_imports, vec!["./x"]);
+        assert_eq!((api.added_symbols.clone(), api.removed_symbols.clone()), (vec!["post".to_string()], vec!["get".to_string()]));
+        let err = Side::detect("--output=/tmp/x", Some(&root)).unwrap_err();
+        assert!(err.to_string().contains("Invalid revision"));
+        let _ = fs::remove_dir_all(&root);
+    }
+}
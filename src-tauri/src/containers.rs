@@ -0,0 +1,362 @@
+// Containers - Dev container and docker-compose setups of the workspace
+// Lists the running containers that belong to it and runs tasks inside one through `docker exec`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const DEVCONTAINER_FILES: [&str; 2] = [".devcontainer/devcontainer.json", ".devcontainer.json"];
+const COMPOSE_FILES: [&str; 4] = ["compose.yaml", "compose.yml", "docker-compose.yml", "docker-compose.yaml"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DevContainerConfig {
+    /// Relative to the workspace
+    pub file: String,
+    pub name: Option<String>,
+    pub image: Option<String>,
+    /// Relative to the workspace
+    pub compose_files: Vec<String>,
+    /// Compose service the editor attaches to
+    pub service: Option<String>,
+    pub workspace_folder: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ComposeFile {
+    /// Relative to the workspace
+    pub file: String,
+    pub services: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ContainerSetup {
+    pub devcontainer: Option<DevContainerConfig>,
+    pub compose: Vec<ComposeFile>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Container {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    pub status: String,
+    pub project: Option<String>,
+    pub service: Option<String>,
+    /// Started by a dev container tool for this workspace
+    pub devcontainer: bool,
+    /// Where the workspace root is mounted inside the container
+    pub workspace_folder: Option<String>,
+}
+
+/// Drop `//` and `/* */` comments and trailing commas outside of strings
+fn strip_jsonc(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '"' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(chars.len());
+                out.extend(&chars[start..i]);
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            ',' => {
+                let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+                if !matches!(next, Some('}') | Some(']')) {
+                    out.push(',');
+                }
+            }
+            c => out.push(c),
+        }
+        i += 1;
+    }
+    out
+}
+
+fn relative(workspace: &Path, path: &Path) -> String {
+    path.strip_prefix(workspace).unwrap_or(path).to_string_lossy().replace('\\', "/")
+}
+
+fn devcontainer(workspace: &Path) -> Option<DevContainerConfig> {
+    let (file, text) = DEVCONTAINER_FILES
+        .iter()
+        .find_map(|name| fs::read_to_string(workspace.join(name)).ok().map(|text| (workspace.join(name), text)))?;
+    let config: Value = match serde_json::from_str(&strip_jsonc(&text)) {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("Ignoring unparsable {:?}: {}", file, e);
+            return None;
+        }
+    };
+    let text_of = |key: &str| config[key].as_str().map(str::to_string);
+    // Compose files are relative to the devcontainer.json
+    let base = file.parent().unwrap_or(workspace);
+    let compose_files = match &config["dockerComposeFile"] {
+        Value::String(single) => vec![single.clone()],
+        Value::Array(files) => files.iter().filter_map(|f| f.as_str().map(str::to_string)).collect(),
+        _ => Vec::new(),
+    };
+    Some(DevContainerConfig {
+        file: relative(workspace, &file),
+        name: text_of("name"),
+        image: text_of("image"),
+        compose_files: compose_files.iter().map(|f| relative(workspace, &normalize(&base.join(f)))).collect(),
+        service: text_of("service"),
+        workspace_folder: text_of("workspaceFolder"),
+    })
+}
+
+/// Resolve `..` and `.` without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                out.pop();
+            }
+            std::path::Component::CurDir => {}
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Keys of the top-level `services:` mapping
+fn compose_services(content: &str) -> Vec<String> {
+    let mut services = Vec::new();
+    let mut in_services = false;
+    let mut level = None;
+    for raw in content.lines() {
+        let line = raw.split(" #").next().unwrap_or(raw).trim_end();
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        if indent == 0 {
+            in_services = trimmed == "services:";
+            level = None;
+            continue;
+        }
+        if !in_services || level.is_some_and(|level| indent > level) {
+            continue;
+        }
+        level = Some(indent);
+        if let Some(name) = trimmed.strip_suffix(':') {
+            services.push(name.trim_matches(['"', '\'']).to_string());
+        }
+    }
+    services
+}
+
+/// The workspace's dev container configuration and compose files, read from disk only
+pub fn detect(workspace: &Path) -> ContainerSetup {
+    let devcontainer = devcontainer(workspace);
+    let mut files: Vec<String> = COMPOSE_FILES.iter().map(|f| f.to_string()).collect();
+    files.extend(devcontainer.iter().flat_map(|d| d.compose_files.clone()));
+    let mut compose: Vec<ComposeFile> = Vec::new();
+    for file in files {
+        if compose.iter().any(|c| c.file == file) {
+            continue;
+        }
+        if let Ok(content) = fs::read_to_string(workspace.join(&file)) {
+            compose.push(ComposeFile { services: compose_services(&content), file });
+        }
+    }
+    ContainerSetup { devcontainer, compose }
+}
+
+fn docker(args: &[&str]) -> Result<String> {
+    let output = Command::new("docker").args(args).output().map_err(|e| anyhow!("docker is not available: {}", e))?;
+    if !output.status.success() {
+        bail!("docker {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn inspect(ids: &[&str]) -> Result<Vec<Value>> {
+    let mut args = vec!["inspect"];
+    args.extend(ids);
+    let inspected: Value = serde_json::from_str(&docker(&args)?)?;
+    Ok(inspected.as_array().cloned().unwrap_or_default())
+}
+
+/// Bind mounts of an inspected container as (host source, container destination)
+fn mounts(container: &Value) -> Vec<(PathBuf, String)> {
+    container["Mounts"]
+        .as_array()
+        .map(|mounts| {
+            mounts
+                .iter()
+                .filter(|m| m["Type"].as_str() == Some("bind"))
+                .filter_map(|m| Some((PathBuf::from(m["Source"].as_str()?), m["Destination"].as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Container path of a host path through the most specific mount containing it
+fn map_path(mounts: &[(PathBuf, String)], host: &Path) -> Option<String> {
+    let (source, destination) = mounts
+        .iter()
+        .filter(|(source, _)| host.starts_with(source))
+        .max_by_key(|(source, _)| source.components().count())?;
+    let rest = relative(source, host);
+    Some(match rest.as_str() {
+        "" => destination.clone(),
+        _ => format!("{}/{}", destination.trim_end_matches('/'), rest),
+    })
+}
+
+/// An inspected container, if compose labels, dev container labels or mounts tie it to the workspace
+fn relevant(workspace: &Path, container: &Value) -> Option<Container> {
+    let labels = &container["Config"]["Labels"];
+    let label = |key: &str| labels[key].as_str().map(str::to_string);
+    let mounts = mounts(container);
+    let devcontainer = label("devcontainer.local_folder").is_some_and(|folder| Path::new(&folder) == workspace);
+    let compose = label("com.docker.compose.project.working_dir")
+        .is_some_and(|dir| Path::new(&dir).starts_with(workspace));
+    let mounted = mounts.iter().any(|(source, _)| source.starts_with(workspace));
+    if !devcontainer && !compose && !mounted {
+        return None;
+    }
+    let id = container["Id"].as_str()?;
+    Some(Container {
+        id: id.chars().take(12).collect(),
+        name: container["Name"].as_str().unwrap_or(id).trim_start_matches('/').to_string(),
+        image: container["Config"]["Image"].as_str().unwrap_or("").to_string(),
+        status: container["State"]["Status"].as_str().unwrap_or("").to_string(),
+        project: label("com.docker.compose.project"),
+        service: label("com.docker.compose.service"),
+        devcontainer,
+        workspace_folder: map_path(&mounts, workspace),
+    })
+}
+
+/// Running containers of the workspace, dev containers first
+pub fn list(workspace: &Path) -> Result<Vec<Container>> {
+    let output = docker(&["ps", "-q", "--no-trunc"])?;
+    let ids: Vec<&str> = output.lines().map(str::trim).filter(|id| !id.is_empty()).collect();
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let workspace = workspace.canonicalize().unwrap_or_else(|_| workspace.to_path_buf());
+    let mut containers: Vec<Container> = inspect(&ids)?.iter().filter_map(|c| relevant(&workspace, c)).collect();
+    containers.sort_by(|a, b| b.devcontainer.cmp(&a.devcontainer).then_with(|| a.name.cmp(&b.name)));
+    Ok(containers)
+}
+
+/// `docker exec` running `program` in `container`, in the container path of `host_cwd` when it is mounted.
+/// Variables are passed by name only; the caller sets their values on the returned command's environment.
+pub fn exec(container: &str, host_cwd: &Path, program: &str, args: &[String], env_names: &[String]) -> Command {
+    let host_cwd = host_cwd.canonicalize().unwrap_or_else(|_| host_cwd.to_path_buf());
+    let workdir = match inspect(&[container]) {
+        Ok(inspected) => inspected.first().and_then(|c| map_path(&mounts(c), &host_cwd)),
+        Err(e) => {
+            log::warn!("Running in the default directory of container {}: {}", container, e);
+            None
+        }
+    };
+    let mut cmd = Command::new("docker");
+    cmd.args(["exec", "-i"]);
+    if let Some(workdir) = workdir {
+        cmd.args(["-w", &workdir]);
+    }
+    for name in env_names {
+        cmd.args(["-e", name]);
+    }
+    cmd.arg(container).arg(program).args(args);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_devcontainer_with_compose() {
+        let workspace = std::env::temp_dir().join(crate::storage::new_id("containers-test"));
+        fs::create_dir_all(workspace.join(".devcontainer")).unwrap();
+        fs::write(
+            workspace.join(".devcontainer/devcontainer.json"),
+            concat!(
+                "{\n",
+                "  // Editor container\n",
+                "  \"name\": \"API\",\n",
+                "  \"dockerComposeFile\": [\"../docker-compose.yml\"],\n",
+                "  \"service\": \"app\", /* attached */\n",
+                "  \"workspaceFolder\": \"/workspaces/api\",\n",
+                "}\n",
+            ),
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("docker-compose.yml"),
+            concat!(
+                "services:\n",
+                "  app:\n",
+                "    image: rust:1\n",
+                "    ports:\n",
+                "      - \"8080:8080\"\n",
+                "  db: # postgres\n",
+                "    image: postgres\n",
+            ),
+        )
+        .unwrap();
+
+        let setup = detect(&workspace);
+        let devcontainer = setup.devcontainer.unwrap();
+        assert_eq!(devcontainer.name.as_deref(), Some("API"));
+        assert_eq!(devcontainer.compose_files, vec!["docker-compose.yml".to_string()]);
+        assert_eq!(devcontainer.service.as_deref(), Some("app"));
+        assert_eq!(
+            setup.compose,
+            vec![ComposeFile { file: "docker-compose.yml".to_string(), services: vec!["app".into(), "db".into()] }]
+        );
+        let _ = fs::remove_dir_all(&workspace);
+    }
+
+    #[test]
+    fn test_relevant_container_maps_workspace() {
+        let inspected: Value = serde_json::from_str(concat!(
+            "{\"Id\": \"0123456789abcdef\", \"Name\": \"/api-app-1\",",
+            " \"Config\": {\"Image\": \"rust:1\", \"Labels\": {\"com.docker.compose.service\": \"app\",",
+            " \"com.docker.compose.project.working_dir\": \"/home/dev/api\"}},",
+            " \"State\": {\"Status\": \"running\"},",
+            " \"Mounts\": [{\"Type\": \"bind\", \"Source\": \"/home/dev/api\", \"Destination\": \"/workspaces/api\"}]}",
+        ))
+        .unwrap();
+        let container = relevant(Path::new("/home/dev/api"), &inspected).unwrap();
+        assert_eq!(container.id, "0123456789ab");
+        assert_eq!(container.name, "api-app-1");
+        assert_eq!(container.service.as_deref(), Some("app"));
+        assert_eq!(container.workspace_folder.as_deref(), Some("/workspaces/api"));
+        assert_eq!(
+            map_path(&mounts(&inspected), Path::new("/home/dev/api/crates/core")).as_deref(),
+            Some("/workspaces/api/crates/core")
+        );
+        assert!(relevant(Path::new("/home/dev/other"), &inspected).is_none());
+    }
+}
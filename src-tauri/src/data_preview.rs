@@ -0,0 +1,394 @@
+// Data Preview - Structured previews of CSV/TSV/JSONL and large logs
+// Reads only what is shown; large files are never loaded whole
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::SystemTime;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// One checkpoint every this many lines in a line index
+const CHECKPOINT_INTERVAL: usize = 1000;
+/// Longest line returned by `read_lines`
+const MAX_LINE_BYTES: usize = 16 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TableFormat {
+    Csv,
+    Tsv,
+    Jsonl,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    Integer,
+    Float,
+    Boolean,
+    Date,
+    String,
+    /// No non-empty values seen
+    Empty,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Column {
+    pub name: String,
+    pub kind: ColumnType,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TablePreview {
+    pub format: TableFormat,
+    pub columns: Vec<Column>,
+    pub rows: Vec<Vec<String>>,
+    /// More rows or columns exist than were returned
+    pub truncated: bool,
+}
+
+pub fn table_format(path: &str) -> Option<TableFormat> {
+    match path.rsplit('.').next().unwrap_or("").to_lowercase().as_str() {
+        "csv" => Some(TableFormat::Csv),
+        "tsv" | "tab" => Some(TableFormat::Tsv),
+        "jsonl" | "ndjson" => Some(TableFormat::Jsonl),
+        _ => None,
+    }
+}
+
+fn value_type(value: &str) -> Option<ColumnType> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let kind = if value.parse::<i64>().is_ok() {
+        ColumnType::Integer
+    } else if value.parse::<f64>().is_ok() {
+        ColumnType::Float
+    } else if matches!(value.to_lowercase().as_str(), "true" | "false") {
+        ColumnType::Boolean
+    } else if is_date(value) {
+        ColumnType::Date
+    } else {
+        ColumnType::String
+    };
+    Some(kind)
+}
+
+/// ISO-8601 date prefix (`YYYY-MM-DD`)
+fn is_date(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() >= 10
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[5..7].iter().all(u8::is_ascii_digit)
+        && bytes[7] == b'-'
+        && bytes[8..10].iter().all(u8::is_ascii_digit)
+}
+
+/// Most specific type consistent with both
+fn merge_types(current: ColumnType, next: ColumnType) -> ColumnType {
+    match (current, next) {
+        (ColumnType::Empty, next) => next,
+        (a, b) if a == b => a,
+        (ColumnType::Integer, ColumnType::Float) | (ColumnType::Float, ColumnType::Integer) => ColumnType::Float,
+        _ => ColumnType::String,
+    }
+}
+
+/// Read one delimited record, following quoted fields across line breaks
+fn read_record(reader: &mut impl BufRead, delimiter: char) -> Result<Option<Vec<String>>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    loop {
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if quoted {
+                if c == '"' {
+                    if chars.peek() == Some(&'"') {
+                        field.push('"');
+                        chars.next();
+                    } else {
+                        quoted = false;
+                    }
+                } else {
+                    field.push(c);
+                }
+            } else if c == '"' && field.is_empty() {
+                quoted = true;
+            } else if c == delimiter {
+                fields.push(std::mem::take(&mut field));
+            } else if c != '\n' && c != '\r' {
+                field.push(c);
+            }
+        }
+        if !quoted {
+            break;
+        }
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+    }
+    fields.push(field);
+    Ok(Some(fields))
+}
+
+fn json_cell(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// First `max_rows` rows and `max_cols` columns of a table file, with inferred column types
+pub fn preview_table(path: &Path, max_rows: usize, max_cols: usize) -> Result<TablePreview> {
+    let format = table_format(&path.to_string_lossy())
+        .ok_or_else(|| anyhow!("Unsupported table format: {:?}", path))?;
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut names: Vec<String> = Vec::new();
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut truncated = false;
+
+    match format {
+        TableFormat::Csv | TableFormat::Tsv => {
+            let delimiter = if format == TableFormat::Csv { ',' } else { '\t' };
+            names = read_record(&mut reader, delimiter)?.unwrap_or_default();
+            while let Some(record) = read_record(&mut reader, delimiter)? {
+                if record.len() == 1 && record[0].is_empty() {
+                    continue;
+                }
+                if rows.len() == max_rows {
+                    truncated = true;
+                    break;
+                }
+                rows.push(record);
+            }
+        }
+        TableFormat::Jsonl => {
+            let mut objects = Vec::new();
+            let mut line = String::new();
+            while reader.read_line(&mut line)? > 0 {
+                let text = line.trim();
+                if !text.is_empty() {
+                    if objects.len() == max_rows {
+                        truncated = true;
+                        break;
+                    }
+                    let value: serde_json::Value =
+                        serde_json::from_str(text).unwrap_or_else(|_| serde_json::Value::String(text.to_string()));
+                    // Columns in order of first appearance
+                    if let Some(object) = value.as_object() {
+                        for key in object.keys() {
+                            if !names.contains(key) {
+                                names.push(key.clone());
+                            }
+                        }
+                    }
+                    objects.push(value);
+                }
+                line.clear();
+            }
+            if names.is_empty() && !objects.is_empty() {
+                names.push("value".to_string());
+            }
+            rows = objects
+                .iter()
+                .map(|value| match value.as_object() {
+                    Some(object) => names.iter().map(|n| object.get(n).map(json_cell).unwrap_or_default()).collect(),
+                    None => vec![json_cell(value)],
+                })
+                .collect();
+        }
+    }
+
+    if names.len() > max_cols {
+        names.truncate(max_cols);
+        truncated = true;
+    }
+    for row in &mut rows {
+        row.resize(names.len(), String::new());
+    }
+
+    let columns = names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let kind = rows
+                .iter()
+                .filter_map(|row| value_type(&row[i]))
+                .fold(ColumnType::Empty, merge_types);
+            Column { name, kind }
+        })
+        .collect();
+
+    Ok(TablePreview {
+        format,
+        columns,
+        rows,
+        truncated,
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LineIndexInfo {
+    pub path: String,
+    pub size: u64,
+    pub line_count: usize,
+}
+
+/// Sparse line-offset table for jumping into large files
+pub struct LineIndex {
+    size: u64,
+    modified: Option<SystemTime>,
+    line_count: usize,
+    /// Byte offset of every `CHECKPOINT_INTERVAL`th line
+    checkpoints: Vec<u64>,
+}
+
+impl LineIndex {
+    pub fn build(path: &Path) -> Result<Self> {
+        let metadata = fs::metadata(path)?;
+        let mut reader = BufReader::with_capacity(1 << 20, File::open(path)?);
+        let mut checkpoints = vec![0];
+        let mut line_count = 0;
+        let mut offset: u64 = 0;
+        let mut pending = false;
+
+        loop {
+            let buffer = reader.fill_buf()?;
+            if buffer.is_empty() {
+                break;
+            }
+            let length = buffer.len();
+            for (i, byte) in buffer.iter().enumerate() {
+                if *byte == b'\n' {
+                    line_count += 1;
+                    if line_count % CHECKPOINT_INTERVAL == 0 {
+                        checkpoints.push(offset + i as u64 + 1);
+                    }
+                }
+            }
+            pending = buffer[length - 1] != b'\n';
+            offset += length as u64;
+            reader.consume(length);
+        }
+        // A final line without a trailing newline still counts
+        if pending {
+            line_count += 1;
+        }
+
+        Ok(Self {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            line_count,
+            checkpoints,
+        })
+    }
+
+    /// Whether the file changed since the index was built
+    pub fn is_stale(&self, path: &Path) -> bool {
+        match fs::metadata(path) {
+            Ok(metadata) => metadata.len() != self.size || metadata.modified().ok() != self.modified,
+            Err(_) => true,
+        }
+    }
+
+    pub fn info(&self, path: &Path) -> LineIndexInfo {
+        LineIndexInfo {
+            path: path.to_string_lossy().to_string(),
+            size: self.size,
+            line_count: self.line_count,
+        }
+    }
+
+    /// Read `count` lines starting at 0-based line `start`
+    pub fn read_lines(&self, path: &Path, start: usize, count: usize) -> Result<Vec<String>> {
+        if start >= self.line_count {
+            return Ok(Vec::new());
+        }
+        let checkpoint = (start / CHECKPOINT_INTERVAL).min(self.checkpoints.len() - 1);
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(self.checkpoints[checkpoint]))?;
+        let mut reader = BufReader::new(file);
+
+        let mut skip = start - checkpoint * CHECKPOINT_INTERVAL;
+        let mut lines = Vec::with_capacity(count);
+        let mut buffer = Vec::new();
+        while lines.len() < count {
+            buffer.clear();
+            // Cap per-line memory; the remainder of an over-long line is skipped
+            let read = (&mut reader).take(MAX_LINE_BYTES as u64).read_until(b'\n', &mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            if !buffer.ends_with(b"\n") && read == MAX_LINE_BYTES {
+                let mut rest = Vec::new();
+                reader.read_until(b'\n', &mut rest)?;
+            }
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let text = String::from_utf8_lossy(&buffer);
+            lines.push(text.trim_end_matches(['\n', '\r']).to_string());
+        }
+        Ok(lines)
+    }
+}
+
+/// Line indexes of recently opened files, rebuilt when a file changes
+pub struct LineIndexCache {
+    indexes: HashMap<String, LineIndex>,
+}
+
+impl LineIndexCache {
+    pub fn new() -> Self {
+        Self {
+            indexes: HashMap::new(),
+        }
+    }
+
+    pub fn get_or_build(&mut self, path: &Path) -> Result<&LineIndex> {
+        let key = path.to_string_lossy().to_string();
+        let stale = self.indexes.get(&key).map(|index| index.is_stale(path)).unwrap_or(true);
+        if stale {
+            let index = LineIndex::build(path)?;
+            log::info!("Indexed {} lines of {:?}", index.line_count, path);
+            self.indexes.insert(key.clone(), index);
+        }
+        Ok(&self.indexes[&key])
+    }
+
+    pub fn cached_count(&self) -> usize {
+        self.indexes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quoted_csv_record_and_types() {
+        let mut reader = BufReader::new("a,\"b, \"\"c\"\"\nd\",3\n".as_bytes());
+        let record = read_record(&mut reader, ',').unwrap().unwrap();
+        assert_eq!(record, vec!["a", "b, \"c\"\nd", "3"]);
+
+        let kind = ["1", "2.5", ""]
+            .iter()
+            .filter_map(|v| value_type(v))
+            .fold(ColumnType::Empty, merge_types);
+        assert_eq!(kind, ColumnType::Float);
+    }
+}
@@ -0,0 +1,602 @@
+// Debugger - Debug Adapter Protocol client
+// Talks to codelldb, debugpy and node-debug2 over stdio, publishing stop events on the bus
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::events::Event;
+use crate::storage;
+use crate::workspace_env;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Adapters may compile or start an interpreter before answering `launch`
+const LAUNCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AdapterKind {
+    Codelldb,
+    Debugpy,
+    Node,
+}
+
+impl AdapterKind {
+    fn adapter_id(self) -> &'static str {
+        match self {
+            AdapterKind::Codelldb => "lldb",
+            AdapterKind::Debugpy => "debugpy",
+            AdapterKind::Node => "node2",
+        }
+    }
+
+    /// Adapter command line when the request does not name one
+    fn default_command(self) -> Result<Vec<String>> {
+        let python = if cfg!(windows) { "python" } else { "python3" };
+        match self {
+            AdapterKind::Codelldb => Ok(vec!["codelldb".to_string()]),
+            AdapterKind::Debugpy => Ok(vec![python.to_string(), "-m".to_string(), "debugpy.adapter".to_string()]),
+            AdapterKind::Node => Err(anyhow!(
+                "node-debug2 has no standard install location; set adapter_command, e.g. [\"node\", \"<path>/out/src/nodeDebug.js\"]"
+            )),
+        }
+    }
+}
+
+/// Line breakpoint, serialized as a DAP `SourceBreakpoint`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakpointSpec {
+    pub line: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SourceBreakpoints {
+    /// Absolute path of the source file
+    pub path: String,
+    pub breakpoints: Vec<BreakpointSpec>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LaunchRequest {
+    pub adapter: AdapterKind,
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory relative to the workspace (workspace root if absent)
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Applied over the workspace environment
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub stop_on_entry: bool,
+    #[serde(default)]
+    pub breakpoints: Vec<SourceBreakpoints>,
+    /// Overrides the adapter's default command line
+    #[serde(default)]
+    pub adapter_command: Option<Vec<String>>,
+    /// Extra adapter-specific launch arguments, merged over the generated ones
+    #[serde(default)]
+    pub options: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugStatus {
+    Starting,
+    Running,
+    Stopped,
+    Terminated,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DebugSessionInfo {
+    pub id: String,
+    pub adapter: AdapterKind,
+    pub program: String,
+    pub status: DebugStatus,
+    pub stopped_thread: Option<i64>,
+    pub stop_reason: Option<String>,
+    pub exit_code: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepAction {
+    Continue,
+    Pause,
+    StepOver,
+    StepIn,
+    StepOut,
+}
+
+impl StepAction {
+    fn command(self) -> &'static str {
+        match self {
+            StepAction::Continue => "continue",
+            StepAction::Pause => "pause",
+            StepAction::StepOver => "next",
+            StepAction::StepIn => "stepIn",
+            StepAction::StepOut => "stepOut",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Breakpoint {
+    #[serde(default)]
+    pub id: Option<i64>,
+    #[serde(default)]
+    pub verified: bool,
+    #[serde(default)]
+    pub line: Option<i64>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Thread {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Source {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StackFrame {
+    pub id: i64,
+    pub name: String,
+    #[serde(default)]
+    pub source: Option<Source>,
+    pub line: i64,
+    pub column: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Scope {
+    pub name: String,
+    pub variables_reference: i64,
+    #[serde(default)]
+    pub expensive: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+    /// Non-zero when the variable has children
+    #[serde(default)]
+    pub variables_reference: i64,
+}
+
+/// Write one `Content-Length` framed message
+fn write_message(writer: &mut impl Write, message: &Value) -> Result<()> {
+    let body = serde_json::to_vec(message)?;
+    write!(writer, "Content-Length: {}\r\n\r\n", body.len())?;
+    writer.write_all(&body)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read one framed message; `None` at end of stream
+fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = Some(value.trim().parse::<usize>()?);
+            }
+        }
+    }
+    let length = length.ok_or_else(|| anyhow!("Debug adapter message without Content-Length"))?;
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+pub type EventCallback = Arc<dyn Fn(Event) + Send + Sync>;
+
+/// State shared with the reader thread
+struct Shared {
+    stdin: Mutex<ChildStdin>,
+    seq: AtomicI64,
+    pending: Mutex<HashMap<i64, mpsc::Sender<Value>>>,
+    initialized: Mutex<Option<mpsc::Sender<()>>>,
+    info: Mutex<DebugSessionInfo>,
+}
+
+impl Shared {
+    fn send(&self, command: &str, arguments: Value) -> Result<mpsc::Receiver<Value>> {
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = mpsc::channel();
+        self.pending.lock().unwrap().insert(seq, tx);
+        let message = json!({ "seq": seq, "type": "request", "command": command, "arguments": arguments });
+        write_message(&mut *self.stdin.lock().unwrap(), &message)?;
+        Ok(rx)
+    }
+
+    fn handle_event(&self, session_id: &str, message: &Value, on_event: &EventCallback) {
+        let body = &message["body"];
+        let event = message["event"].as_str().unwrap_or("");
+        let mut info = self.info.lock().unwrap();
+        let published = match event {
+            "initialized" => {
+                if let Some(tx) = self.initialized.lock().unwrap().take() {
+                    let _ = tx.send(());
+                }
+                None
+            }
+            "stopped" => {
+                info.status = DebugStatus::Stopped;
+                info.stopped_thread = body["threadId"].as_i64();
+                info.stop_reason = body["reason"].as_str().map(str::to_string);
+                Some(Event::DebugStopped {
+                    session_id: session_id.to_string(),
+                    thread_id: info.stopped_thread,
+                    reason: info.stop_reason.clone().unwrap_or_default(),
+                    description: body["description"].as_str().map(str::to_string),
+                })
+            }
+            "continued" => {
+                info.status = DebugStatus::Running;
+                info.stopped_thread = None;
+                Some(Event::DebugContinued {
+                    session_id: session_id.to_string(),
+                    thread_id: body["threadId"].as_i64(),
+                })
+            }
+            "output" if body["category"] != "telemetry" => Some(Event::DebugOutput {
+                session_id: session_id.to_string(),
+                category: body["category"].as_str().unwrap_or("console").to_string(),
+                output: body["output"].as_str().unwrap_or("").to_string(),
+            }),
+            "exited" => {
+                info.exit_code = body["exitCode"].as_i64();
+                None
+            }
+            "terminated" => self.terminate(&mut info, session_id),
+            _ => None,
+        };
+        drop(info);
+        if let Some(event) = published {
+            on_event(event);
+        }
+    }
+
+    /// Mark the session finished; the event is only produced once
+    fn terminate(&self, info: &mut DebugSessionInfo, session_id: &str) -> Option<Event> {
+        if info.status == DebugStatus::Terminated {
+            return None;
+        }
+        info.status = DebugStatus::Terminated;
+        info.stopped_thread = None;
+        Some(Event::DebugTerminated {
+            session_id: session_id.to_string(),
+            exit_code: info.exit_code,
+        })
+    }
+}
+
+pub struct DebugSession {
+    pub id: String,
+    shared: Arc<Shared>,
+    child: Mutex<Child>,
+}
+
+impl DebugSession {
+    /// Start the adapter, launch the program with its breakpoints and wait until it runs; callers check that the
+    /// workspace is trusted
+    pub fn launch(request: &LaunchRequest, workspace: &Path, on_event: EventCallback) -> Result<Arc<DebugSession>> {
+        let command_line = match &request.adapter_command {
+            Some(command) if !command.is_empty() => command.clone(),
+            _ => request.adapter.default_command()?,
+        };
+        log::info!("Starting debug adapter: {}", command_line.join(" "));
+        let mut child = Command::new(&command_line[0])
+            .args(&command_line[1..])
+            .current_dir(workspace)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("Failed to start debug adapter {}: {}", command_line[0], e))?;
+
+        let id = storage::new_id("debug");
+        let (initialized_tx, initialized_rx) = mpsc::channel();
+        let shared = Arc::new(Shared {
+            stdin: Mutex::new(child.stdin.take().ok_or_else(|| anyhow!("Debug adapter has no stdin"))?),
+            seq: AtomicI64::new(1),
+            pending: Mutex::new(HashMap::new()),
+            initialized: Mutex::new(Some(initialized_tx)),
+            info: Mutex::new(DebugSessionInfo {
+                id: id.clone(),
+                adapter: request.adapter,
+                program: request.program.clone(),
+                status: DebugStatus::Starting,
+                stopped_thread: None,
+                stop_reason: None,
+                exit_code: None,
+            }),
+        });
+        let stdout = child.stdout.take().ok_or_else(|| anyhow!("Debug adapter has no stdout"))?;
+        let stderr = child.stderr.take();
+        spawn_reader(id.clone(), Arc::clone(&shared), stdout, on_event);
+        if let Some(stderr) = stderr {
+            thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(|l| l.ok()) {
+                    log::debug!("debug adapter: {}", line);
+                }
+            });
+        }
+
+        let session = Arc::new(DebugSession {
+            id,
+            shared,
+            child: Mutex::new(child),
+        });
+        if let Err(e) = session.start(request, workspace, initialized_rx) {
+            session.kill();
+            return Err(e);
+        }
+        Ok(session)
+    }
+
+    fn start(&self, request: &LaunchRequest, workspace: &Path, initialized: mpsc::Receiver<()>) -> Result<()> {
+        self.request(
+            "initialize",
+            json!({
+                "clientID": "mimiverse",
+                "clientName": "Mimiverse",
+                "adapterID": request.adapter.adapter_id(),
+                "pathFormat": "path",
+                "linesStartAt1": true,
+                "columnsStartAt1": true,
+                "supportsVariableType": true,
+                "supportsRunInTerminalRequest": false,
+            }),
+        )?;
+
+        // Adapters answer `launch` only after `configurationDone`, which follows `initialized`
+        let launch = self.shared.send("launch", launch_arguments(request, workspace))?;
+        initialized
+            .recv_timeout(LAUNCH_TIMEOUT)
+            .map_err(|_| anyhow!("Debug adapter never reported initialized"))?;
+        for source in &request.breakpoints {
+            self.set_breakpoints(&source.path, &source.breakpoints)?;
+        }
+        self.request("setExceptionBreakpoints", json!({ "filters": [] }))?;
+        self.request("configurationDone", json!({}))?;
+        wait(launch, "launch", LAUNCH_TIMEOUT)?;
+
+        let mut info = self.shared.info.lock().unwrap();
+        if info.status == DebugStatus::Starting {
+            info.status = DebugStatus::Running;
+        }
+        Ok(())
+    }
+
+    pub fn request(&self, command: &str, arguments: Value) -> Result<Value> {
+        let rx = self.shared.send(command, arguments)?;
+        wait(rx, command, REQUEST_TIMEOUT)
+    }
+
+    pub fn info(&self) -> DebugSessionInfo {
+        self.shared.info.lock().unwrap().clone()
+    }
+
+    /// Replace all breakpoints of a file; returns what the adapter verified
+    pub fn set_breakpoints(&self, path: &str, breakpoints: &[BreakpointSpec]) -> Result<Vec<Breakpoint>> {
+        let body = self.request(
+            "setBreakpoints",
+            json!({ "source": { "path": path }, "breakpoints": breakpoints }),
+        )?;
+        Ok(serde_json::from_value(body["breakpoints"].clone()).unwrap_or_default())
+    }
+
+    pub fn threads(&self) -> Result<Vec<Thread>> {
+        let body = self.request("threads", json!({}))?;
+        Ok(serde_json::from_value(body["threads"].clone())?)
+    }
+
+    /// Continue, pause or step a thread; defaults to the thread that stopped last
+    pub fn control(&self, action: StepAction, thread_id: Option<i64>) -> Result<()> {
+        let thread_id = match thread_id.or(self.info().stopped_thread) {
+            Some(id) => id,
+            None => self.threads()?.first().map(|t| t.id).ok_or_else(|| anyhow!("Debuggee has no threads"))?,
+        };
+        self.request(action.command(), json!({ "threadId": thread_id }))?;
+        if action != StepAction::Pause {
+            let mut info = self.shared.info.lock().unwrap();
+            if info.status == DebugStatus::Stopped {
+                info.status = DebugStatus::Running;
+                info.stopped_thread = None;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn stack_trace(&self, thread_id: i64, levels: usize) -> Result<Vec<StackFrame>> {
+        let body = self.request("stackTrace", json!({ "threadId": thread_id, "startFrame": 0, "levels": levels }))?;
+        Ok(serde_json::from_value(body["stackFrames"].clone())?)
+    }
+
+    pub fn scopes(&self, frame_id: i64) -> Result<Vec<Scope>> {
+        let body = self.request("scopes", json!({ "frameId": frame_id }))?;
+        Ok(serde_json::from_value(body["scopes"].clone())?)
+    }
+
+    pub fn variables(&self, variables_reference: i64) -> Result<Vec<Variable>> {
+        let body = self.request("variables", json!({ "variablesReference": variables_reference }))?;
+        Ok(serde_json::from_value(body["variables"].clone())?)
+    }
+
+    pub fn evaluate(&self, expression: &str, frame_id: Option<i64>) -> Result<Variable> {
+        let body = self.request(
+            "evaluate",
+            json!({ "expression": expression, "frameId": frame_id, "context": "repl" }),
+        )?;
+        Ok(Variable {
+            name: expression.to_string(),
+            value: body["result"].as_str().unwrap_or("").to_string(),
+            kind: body["type"].as_str().map(str::to_string),
+            variables_reference: body["variablesReference"].as_i64().unwrap_or(0),
+        })
+    }
+
+    /// Ask the adapter to end the debuggee, then make sure the adapter is gone
+    pub fn disconnect(&self) {
+        if self.info().status != DebugStatus::Terminated {
+            if let Err(e) = self.request("disconnect", json!({ "terminateDebuggee": true })) {
+                log::warn!("Debug session {} did not disconnect cleanly: {}", self.id, e);
+            }
+        }
+        self.kill();
+    }
+
+    fn kill(&self) {
+        let mut child = self.child.lock().unwrap();
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+fn wait(rx: mpsc::Receiver<Value>, command: &str, timeout: Duration) -> Result<Value> {
+    let response = rx
+        .recv_timeout(timeout)
+        .map_err(|_| anyhow!("Debug adapter did not answer {}", command))?;
+    if response["success"].as_bool() == Some(true) {
+        Ok(response["body"].clone())
+    } else {
+        Err(anyhow!(
+            "{} failed: {}",
+            command,
+            response["message"].as_str().unwrap_or("unknown error")
+        ))
+    }
+}
+
+fn launch_arguments(request: &LaunchRequest, workspace: &Path) -> Value {
+    let cwd = match &request.cwd {
+        Some(cwd) => workspace.join(cwd),
+        None => workspace.to_path_buf(),
+    };
+    let mut env = workspace_env::resolve(workspace).unwrap_or_else(|e| {
+        log::warn!("Ignoring workspace environment of {:?}: {}", workspace, e);
+        HashMap::new()
+    });
+    env.extend(request.env.clone());
+
+    let mut arguments = json!({
+        "request": "launch",
+        "program": request.program,
+        "args": request.args,
+        "cwd": cwd.to_string_lossy(),
+        "env": env,
+        "stopOnEntry": request.stop_on_entry,
+    });
+    if request.adapter != AdapterKind::Codelldb {
+        arguments["console"] = json!("internalConsole");
+    }
+    if let (Some(arguments), Some(Value::Object(options))) = (arguments.as_object_mut(), &request.options) {
+        for (key, value) in options {
+            arguments.insert(key.clone(), value.clone());
+        }
+    }
+    arguments
+}
+
+fn spawn_reader(id: String, shared: Arc<Shared>, stdout: impl Read + Send + 'static, on_event: EventCallback) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stdout);
+        loop {
+            let message = match read_message(&mut reader) {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                Err(e) => {
+                    log::warn!("Debug session {}: {}", id, e);
+                    break;
+                }
+            };
+            match message["type"].as_str() {
+                Some("response") => {
+                    let seq = message["request_seq"].as_i64().unwrap_or(-1);
+                    if let Some(tx) = shared.pending.lock().unwrap().remove(&seq) {
+                        let _ = tx.send(message);
+                    }
+                }
+                Some("event") => shared.handle_event(&id, &message, &on_event),
+                Some("request") => {
+                    // Reverse requests (runInTerminal, startDebugging) are not supported
+                    let seq = shared.seq.fetch_add(1, Ordering::Relaxed);
+                    let reply = json!({
+                        "seq": seq,
+                        "type": "response",
+                        "request_seq": message["seq"],
+                        "command": message["command"],
+                        "success": false,
+                        "message": "Not supported",
+                    });
+                    let _ = write_message(&mut *shared.stdin.lock().unwrap(), &reply);
+                }
+                _ => {}
+            }
+        }
+        // Fail pending requests right away instead of letting them time out
+        shared.pending.lock().unwrap().clear();
+        let event = shared.terminate(&mut shared.info.lock().unwrap(), &id);
+        if let Some(event) = event {
+            on_event(event);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_framing() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &json!({ "seq": 1, "type": "event", "event": "initialized" })).unwrap();
+        write_message(&mut buffer, &json!({ "seq": 2, "type": "response", "success": true })).unwrap();
+        assert!(buffer.starts_with(b"Content-Length: "));
+
+        let mut reader = BufReader::new(buffer.as_slice());
+        assert_eq!(read_message(&mut reader).unwrap().unwrap()["event"], "initialized");
+        assert_eq!(read_message(&mut reader).unwrap().unwrap()["seq"], 2);
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+}
@@ -0,0 +1,160 @@
+// Diagnostics Store - Aggregated problems from all analysis sources
+// Single source of truth for the problems panel
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Sha256, Digest};
+
+use crate::CodeSuggestion;
+
+/// Source name used by the built-in static analyzer
+pub const ANALYZER_SOURCE: &str = "analyzer";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// Stable identifier derived from source, file, position and message
+    pub id: String,
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub severity: String,
+    pub kind: String,
+    pub message: String,
+    /// Producer of the diagnostic (e.g. "analyzer", "ai-review")
+    pub source: String,
+    pub fix: Option<String>,
+}
+
+impl Diagnostic {
+    /// Wrap an analyzer-style suggestion as a diagnostic of `source`
+    pub fn from_suggestion(file: &str, source: &str, suggestion: &CodeSuggestion) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(
+            format!(
+                "{}|{}|{}|{}|{}",
+                source, file, suggestion.line, suggestion.column, suggestion.message
+            )
+            .as_bytes(),
+        );
+        let id = format!("{}-{}", source, &hex::encode(hasher.finalize())[..12]);
+
+        Self {
+            id,
+            file: file.to_string(),
+            line: suggestion.line,
+            column: suggestion.column,
+            severity: suggestion.severity.clone(),
+            kind: suggestion.kind.clone(),
+            message: suggestion.message.clone(),
+            source: source.to_string(),
+            fix: suggestion.fix.clone(),
+        }
+    }
+}
+
+/// Diagnostics grouped by file and source
+pub struct DiagnosticsStore {
+    /// file path -> source -> diagnostics
+    by_file: HashMap<String, HashMap<String, Vec<Diagnostic>>>,
+}
+
+impl DiagnosticsStore {
+    pub fn new() -> Self {
+        Self {
+            by_file: HashMap::new(),
+        }
+    }
+
+    /// Replace all diagnostics of one source for a file
+    pub fn publish(&mut self, file: &str, source: &str, diagnostics: Vec<Diagnostic>) {
+        let sources = self.by_file.entry(file.to_string()).or_insert_with(HashMap::new);
+        if diagnostics.is_empty() {
+            sources.remove(source);
+        } else {
+            sources.insert(source.to_string(), diagnostics);
+        }
+        if sources.is_empty() {
+            self.by_file.remove(file);
+        }
+    }
+
+    /// Drop every diagnostic produced by a source
+    pub fn clear_source(&mut self, source: &str) {
+        for sources in self.by_file.values_mut() {
+            sources.remove(source);
+        }
+        self.by_file.retain(|_, sources| !sources.is_empty());
+    }
+
+    /// Get all diagnostics for a file, ordered by position
+    pub fn get_file(&self, file: &str) -> Vec<Diagnostic> {
+        let mut result: Vec<Diagnostic> = self
+            .by_file
+            .get(file)
+            .map(|sources| sources.values().flatten().cloned().collect())
+            .unwrap_or_default();
+        result.sort_by_key(|d| (d.line, d.column));
+        result
+    }
+
+    /// Find a diagnostic by id within a file
+    pub fn get(&self, file: &str, id: &str) -> Option<Diagnostic> {
+        self.by_file
+            .get(file)?
+            .values()
+            .flatten()
+            .find(|d| d.id == id)
+            .cloned()
+    }
+
+    /// Get all diagnostics in the workspace
+    pub fn all(&self) -> Vec<Diagnostic> {
+        let mut files: Vec<&String> = self.by_file.keys().collect();
+        files.sort();
+        files.into_iter().flat_map(|file| self.get_file(file)).collect()
+    }
+
+    /// Number of diagnostics per file
+    pub fn count_by_file(&self) -> HashMap<String, usize> {
+        self.by_file
+            .iter()
+            .map(|(file, sources)| (file.clone(), sources.values().map(|d| d.len()).sum()))
+            .collect()
+    }
+
+    pub fn count(&self) -> usize {
+        self.by_file
+            .values()
+            .flat_map(|sources| sources.values())
+            .map(|d| d.len())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_replaces_only_same_source() {
+        let mut store = DiagnosticsStore::new();
+        let suggestion = |line: usize, message: &str| CodeSuggestion {
+            kind: "quality".to_string(),
+            message: message.to_string(),
+            line,
+            column: 0,
+            severity: "warning".to_string(),
+            fix: None,
+        };
+        let a = Diagnostic::from_suggestion("a.ts", "analyzer", &suggestion(1, "one"));
+        let b = Diagnostic::from_suggestion("a.ts", "ai-review", &suggestion(2, "two"));
+        store.publish("a.ts", "analyzer", vec![a.clone()]);
+        store.publish("a.ts", "ai-review", vec![b]);
+        store.publish("a.ts", "analyzer", vec![a.clone()]);
+        assert_eq!(store.count(), 2);
+
+        store.clear_source("ai-review");
+        assert_eq!(store.get_file("a.ts").len(), 1);
+        assert!(store.get("a.ts", &a.id).is_some());
+    }
+}
@@ -0,0 +1,222 @@
+// Disk Usage - Directory sizes, largest files and growth between runs
+// Produces a size tree the frontend can render as a treemap
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::git;
+use crate::storage;
+
+/// Children kept per directory; the rest are merged into one "(other)" node
+const MAX_CHILDREN: usize = 40;
+/// Directory levels below the root included in the tree
+const MAX_DEPTH: usize = 8;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DiskNode {
+    pub name: String,
+    /// Workspace-relative, empty for the root
+    pub path: String,
+    pub size: u64,
+    pub files: usize,
+    pub is_dir: bool,
+    /// Change since the previous analysis, if this path was in it
+    pub growth_bytes: Option<i64>,
+    pub children: Vec<DiskNode>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileSize {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DiskUsage {
+    pub root: DiskNode,
+    pub largest_files: Vec<FileSize>,
+    pub respect_ignores: bool,
+    /// When the snapshot that growth is measured against was taken
+    pub previous_snapshot_at: Option<u64>,
+}
+
+/// Sizes of the previous run, by relative path
+#[derive(Serialize, Deserialize, Default)]
+struct SizeSnapshot {
+    timestamp: u64,
+    sizes: HashMap<String, u64>,
+}
+
+fn snapshot_path(workspace: &Path, respect_ignores: bool) -> PathBuf {
+    let name = if respect_ignores { "disk_usage.json" } else { "disk_usage_all.json" };
+    storage::data_dir(workspace).join(name)
+}
+
+#[derive(Default)]
+struct Builder {
+    size: u64,
+    files: usize,
+    is_dir: bool,
+    children: BTreeMap<String, Builder>,
+}
+
+impl Builder {
+    fn insert(&mut self, components: &[&str], size: u64) {
+        self.size += size;
+        self.files += 1;
+        self.is_dir = true;
+        match components {
+            [] => {}
+            [file] => {
+                let child = self.children.entry(file.to_string()).or_default();
+                child.size += size;
+                child.files += 1;
+            }
+            [dir, rest @ ..] => self.children.entry(dir.to_string()).or_default().insert(rest, size),
+        }
+    }
+
+    fn build(self, name: String, path: String, depth: usize, previous: &HashMap<String, u64>) -> DiskNode {
+        let mut children: Vec<(String, Builder)> = if depth < MAX_DEPTH { self.children.into_iter().collect() } else { Vec::new() };
+        children.sort_by(|a, b| b.1.size.cmp(&a.1.size).then(a.0.cmp(&b.0)));
+        let rest: Vec<(String, Builder)> = children.split_off(children.len().min(MAX_CHILDREN));
+
+        let child_path = |child: &str| if path.is_empty() { child.to_string() } else { format!("{}/{}", path, child) };
+        let mut nodes: Vec<DiskNode> = children
+            .into_iter()
+            .map(|(child, builder)| {
+                let child_path = child_path(&child);
+                builder.build(child, child_path, depth + 1, previous)
+            })
+            .collect();
+        if !rest.is_empty() {
+            nodes.push(DiskNode {
+                name: format!("({} more)", rest.len()),
+                path: child_path("(other)"),
+                size: rest.iter().map(|(_, b)| b.size).sum(),
+                files: rest.iter().map(|(_, b)| b.files).sum(),
+                is_dir: false,
+                growth_bytes: None,
+                children: Vec::new(),
+            });
+        }
+        DiskNode {
+            growth_bytes: previous.get(&path).map(|before| self.size as i64 - *before as i64),
+            name,
+            path,
+            size: self.size,
+            files: self.files,
+            is_dir: self.is_dir,
+            children: nodes,
+        }
+    }
+}
+
+/// Every file with its size; with `respect_ignores`, git's view of tracked and unignored files
+fn collect_files(workspace: &Path, respect_ignores: bool) -> Result<Vec<(String, u64)>> {
+    if respect_ignores && workspace.join(".git").exists() {
+        let listing = git::run(workspace, &["ls-files", "-z", "--cached", "--others", "--exclude-standard"])?;
+        return Ok(listing
+            .split('\0')
+            .filter(|p| !p.is_empty())
+            .filter_map(|p| {
+                let metadata = fs::symlink_metadata(workspace.join(p)).ok()?;
+                metadata.is_file().then(|| (p.to_string(), metadata.len()))
+            })
+            .collect());
+    }
+    Ok(WalkDir::new(workspace)
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            !respect_ignores || !matches!(name.as_ref(), "node_modules" | ".git" | "target" | storage::DATA_DIR)
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let size = e.metadata().ok()?.len();
+            let relative = e.path().strip_prefix(workspace).ok()?.to_string_lossy().replace('\\', "/");
+            Some((relative, size))
+        })
+        .collect())
+}
+
+fn flatten(node: &DiskNode, sizes: &mut HashMap<String, u64>) {
+    sizes.insert(node.path.clone(), node.size);
+    for child in &node.children {
+        flatten(child, sizes);
+    }
+}
+
+fn analyze_files(files: Vec<(String, u64)>, top_files: usize, previous: &HashMap<String, u64>) -> (DiskNode, Vec<FileSize>) {
+    let mut root = Builder::default();
+    for (path, size) in &files {
+        let components: Vec<&str> = path.split('/').collect();
+        root.insert(&components, *size);
+    }
+    let mut largest: Vec<FileSize> = files.into_iter().map(|(path, size)| FileSize { path, size }).collect();
+    largest.sort_by(|a, b| b.size.cmp(&a.size).then(a.path.cmp(&b.path)));
+    largest.truncate(top_files);
+    (root.build(String::new(), String::new(), 0, previous), largest)
+}
+
+/// Analyze the workspace and remember the sizes for the next run's growth figures
+pub fn analyze(workspace: &Path, respect_ignores: bool, top_files: usize) -> Result<DiskUsage> {
+    let snapshot_file = snapshot_path(workspace, respect_ignores);
+    let previous: Option<SizeSnapshot> = storage::read_json(&snapshot_file).unwrap_or_else(|e| {
+        log::warn!("Ignoring unreadable disk usage snapshot: {}", e);
+        None
+    });
+    let previous_sizes = previous.as_ref().map(|s| s.sizes.clone()).unwrap_or_default();
+
+    let files = collect_files(workspace, respect_ignores)?;
+    let (mut root, largest_files) = analyze_files(files, top_files, &previous_sizes);
+    root.name = workspace
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut sizes = HashMap::new();
+    flatten(&root, &mut sizes);
+    storage::write_json(
+        &snapshot_file,
+        &SizeSnapshot {
+            timestamp: storage::now_millis(),
+            sizes,
+        },
+    )?;
+
+    Ok(DiskUsage {
+        root,
+        largest_files,
+        respect_ignores,
+        previous_snapshot_at: previous.map(|s| s.timestamp),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_sizes_and_growth() {
+        let files = vec![
+            ("src/main.rs".to_string(), 100),
+            ("src/lib/a.rs".to_string(), 50),
+            ("assets/logo.png".to_string(), 1000),
+            ("README.md".to_string(), 10),
+        ];
+        let previous = HashMap::from([("src".to_string(), 120u64)]);
+        let (root, largest) = analyze_files(files, 2, &previous);
+        assert_eq!((root.size, root.files), (1160, 4));
+        assert_eq!(root.children[0].path, "assets");
+        let src = root.children.iter().find(|c| c.path == "src").unwrap();
+        assert_eq!((src.size, src.growth_bytes), (150, Some(30)));
+        assert!(src.children.iter().any(|c| c.path == "src/lib/a.rs" || c.path == "src/lib"));
+        assert_eq!(largest.iter().map(|f| f.size).collect::<Vec<_>>(), vec![1000, 100]);
+    }
+}
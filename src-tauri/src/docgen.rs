@@ -0,0 +1,268 @@
+// Doc Generation - AI-written doc comments for a symbol
+// Rendered as rustdoc, JSDoc or Python docstrings
+
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ai_provider::{self, AiProvider, ChatMessage, CompletionRequest};
+use crate::changeset::{Changeset, FileChange, FilePreview, Position, TextEdit};
+use crate::code_context::SymbolSpan;
+use crate::tokens;
+
+/// Token cap for the symbol source included in the prompt
+const MAX_SOURCE_TOKENS: usize = 4000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DocStyle {
+    Rustdoc,
+    JsDoc,
+    Docstring,
+}
+
+impl DocStyle {
+    pub fn for_path(path: &str) -> Option<Self> {
+        match path.rsplit('.').next().unwrap_or("") {
+            "rs" => Some(DocStyle::Rustdoc),
+            "ts" | "tsx" | "mts" | "cts" | "js" | "jsx" | "mjs" | "cjs" => Some(DocStyle::JsDoc),
+            "py" => Some(DocStyle::Docstring),
+            _ => None,
+        }
+    }
+
+    fn conventions(&self) -> &'static str {
+        match self {
+            DocStyle::Rustdoc => {
+                "Use rustdoc conventions: a one-line summary, then details if needed, \
+                 and `# Errors` / `# Panics` sections only when they apply. Use markdown for code."
+            }
+            DocStyle::JsDoc => {
+                "Use JSDoc conventions: a summary, then one `@param name description` per parameter \
+                 and `@returns` when a value is returned. Omit type annotations already in the signature."
+            }
+            DocStyle::Docstring => {
+                "Use Google-style docstrings: a one-line summary, then `Args:`, `Returns:` and \
+                 `Raises:` sections when they apply."
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GeneratedDocs {
+    pub style: DocStyle,
+    /// Rendered comment as inserted into the file
+    pub comment: String,
+    pub changeset: Changeset,
+    pub preview: Vec<FilePreview>,
+}
+
+/// Render plain documentation text as a comment in `style`
+pub fn render(style: DocStyle, text: &str, indent: &str) -> String {
+    let lines: Vec<&str> = text.trim().lines().map(|l| l.trim_end()).collect();
+    let mut out = String::new();
+    match style {
+        DocStyle::Rustdoc => {
+            for line in lines {
+                if line.is_empty() {
+                    out.push_str(&format!("{}///\n", indent));
+                } else {
+                    out.push_str(&format!("{}/// {}\n", indent, line));
+                }
+            }
+        }
+        DocStyle::JsDoc => {
+            out.push_str(&format!("{}/**\n", indent));
+            for line in lines {
+                if line.is_empty() {
+                    out.push_str(&format!("{} *\n", indent));
+                } else {
+                    out.push_str(&format!("{} * {}\n", indent, line));
+                }
+            }
+            out.push_str(&format!("{} */\n", indent));
+        }
+        DocStyle::Docstring => {
+            if lines.len() == 1 {
+                out.push_str(&format!("{}\"\"\"{}\"\"\"\n", indent, lines[0]));
+            } else {
+                out.push_str(&format!("{}\"\"\"{}\n", indent, lines.first().copied().unwrap_or("")));
+                for line in lines.iter().skip(1) {
+                    if line.is_empty() {
+                        out.push('\n');
+                    } else {
+                        out.push_str(&format!("{}{}\n", indent, line));
+                    }
+                }
+                out.push_str(&format!("{}\"\"\"\n", indent));
+            }
+        }
+    }
+    out
+}
+
+/// 0-based line range of an existing doc comment directly above line `start` (0-based)
+fn doc_comment_above(lines: &[&str], start: usize, style: DocStyle) -> Option<(usize, usize)> {
+    let above = start.checked_sub(1)?;
+    match style {
+        DocStyle::Rustdoc => {
+            let mut first = start;
+            while first > 0 && lines[first - 1].trim_start().starts_with("///") {
+                first -= 1;
+            }
+            (first < start).then_some((first, above))
+        }
+        DocStyle::JsDoc => {
+            if !lines[above].trim_end().ends_with("*/") {
+                return None;
+            }
+            // Only the comment that ends here; a plain `/* */` one is not documentation
+            let first = (0..=above).rev().find(|&i| lines[i].contains("/*"))?;
+            lines[first].trim_start().starts_with("/**").then_some((first, above))
+        }
+        DocStyle::Docstring => None,
+    }
+}
+
+/// 0-based line range of an existing docstring starting at line `first` (0-based)
+fn docstring_at(lines: &[&str], first: usize) -> Option<(usize, usize)> {
+    let line = lines.get(first)?.trim();
+    let quote = ["\"\"\"", "'''"].into_iter().find(|q| line.starts_with(q))?;
+    if line.len() >= 6 && line[3..].contains(quote) {
+        return Some((first, first));
+    }
+    (first + 1..lines.len())
+        .find(|&i| lines[i].contains(quote))
+        .map(|last| (first, last))
+}
+
+/// Edit inserting `comment`, or replacing the existing doc comment of `span`
+fn doc_edit(content: &str, span: &SymbolSpan, style: DocStyle, comment: String) -> TextEdit {
+    let lines: Vec<&str> = content.lines().collect();
+    let start = span.start_line - 1;
+
+    let (anchor, existing) = match style {
+        DocStyle::Docstring => {
+            // Docstrings go on the first line of the body, after the (possibly wrapped) signature
+            let header_end = (start..=span.end_line.saturating_sub(1).max(start))
+                .find(|&i| lines.get(i).map(|l| l.trim_end().ends_with(':')).unwrap_or(false))
+                .unwrap_or(start);
+            (header_end + 1, docstring_at(&lines, header_end + 1))
+        }
+        _ => (start, doc_comment_above(&lines, start, style)),
+    };
+
+    match existing {
+        Some((first, last)) => TextEdit {
+            start: Position { line: first + 1, column: 0 },
+            end: Position { line: last + 2, column: 0 },
+            new_text: comment,
+        },
+        None => TextEdit::insert(Position { line: anchor + 1, column: 0 }, comment),
+    }
+}
+
+/// Ask the provider to document `span` and build the edit inserting the comment
+pub async fn generate(
+    provider: &dyn AiProvider,
+    workspace: &Path,
+    path: &str,
+    content: &str,
+    span: &SymbolSpan,
+) -> Result<GeneratedDocs> {
+    let style = DocStyle::for_path(path).ok_or_else(|| anyhow!("Unsupported language: {}", path))?;
+
+    let source = tokens::truncate_to_tokens(span.text(content), MAX_SOURCE_TOKENS, provider.model());
+    let system = format!(
+        "You write concise, accurate API documentation. {} \
+         Describe behaviour, not implementation details. \
+         Reply with the documentation text only: no comment markers, quotes or code fences.",
+        style.conventions()
+    );
+    let user = format!(
+        "Document `{}` in {}.\n\nSignature:\n```\n{}\n```\n\nSource:\n```\n{}\n```",
+        span.name, path, span.signature, source
+    );
+    let request = CompletionRequest::new(vec![ChatMessage::system(system), ChatMessage::user(user)]);
+    let response = provider.complete(&request).await?;
+
+    let text = strip_comment_markers(&ai_provider::extract_code_block(&response));
+    if text.is_empty() {
+        return Err(anyhow!("The model returned no documentation"));
+    }
+
+    let indent = match style {
+        DocStyle::Docstring => format!("{}    ", span.indent),
+        _ => span.indent.clone(),
+    };
+    let comment = render(style, &text, &indent);
+    let edit = doc_edit(content, span, style, comment.clone());
+
+    let changeset = Changeset::new(
+        format!("Document {}", span.name),
+        vec![FileChange::Edit {
+            path: path.to_string(),
+            edits: vec![edit],
+        }],
+    );
+    let preview = changeset.preview(workspace)?;
+    Ok(GeneratedDocs {
+        style,
+        comment,
+        changeset,
+        preview,
+    })
+}
+
+/// Models sometimes answer with a ready-made comment despite instructions
+fn strip_comment_markers(text: &str) -> String {
+    text.trim()
+        .trim_start_matches("/**")
+        .trim_end_matches("*/")
+        .trim_start_matches("\"\"\"")
+        .trim_end_matches("\"\"\"")
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            trimmed
+                .strip_prefix("///")
+                .or_else(|| trimmed.strip_prefix('*'))
+                .map(|rest| rest.strip_prefix(' ').unwrap_or(rest))
+                .unwrap_or(line)
+                .trim_end()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_styles() {
+        assert_eq!(render(DocStyle::Rustdoc, "Adds.\n\nFast.", "    "), "    /// Adds.\n    ///\n    /// Fast.\n");
+        assert_eq!(render(DocStyle::JsDoc, "Adds.", ""), "/**\n * Adds.\n */\n");
+        assert_eq!(render(DocStyle::Docstring, "Adds.", "    "), "    \"\"\"Adds.\"\"\"\n");
+        assert_eq!(strip_comment_markers("/// Adds.\n/// More."), "Adds.\nMore.");
+    }
+
+    #[test]
+    fn test_plain_comment_is_not_docs() {
+        let source = "/** Adds. */
+function add() {}
+/* eslint-disable */
+function sub() {}
+/**
+ * Muls.
+ */
+function mul() {}";
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(doc_comment_above(&lines, 1, DocStyle::JsDoc), Some((0, 0)));
+        assert_eq!(doc_comment_above(&lines, 3, DocStyle::JsDoc), None);
+        assert_eq!(doc_comment_above(&lines, 7, DocStyle::JsDoc), Some((4, 6)));
+    }
+}
@@ -0,0 +1,265 @@
+// Document Buffers - Server-side text buffers edited by deltas
+// Large files stay in the engine; the frontend fetches line ranges and sends edits
+// Open buffers are the source of truth for their files until closed, so analysis,
+// symbols and AI context see unsaved edits (didOpen / didChange / didClose)
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::changeset::TextEdit;
+use crate::file_access;
+use crate::rope::Rope;
+use crate::storage;
+
+/// Buffers larger than this are not re-analyzed on every change
+pub const LIVE_ANALYSIS_MAX_BYTES: usize = 2 * 1024 * 1024;
+
+fn write_via_temp(path: &Path, temp: &Path, rope: &Rope) -> Result<()> {
+    let mut writer = BufWriter::new(fs::File::create(temp)?);
+    for chunk in rope.chunks() {
+        writer.write_all(chunk.as_bytes())?;
+    }
+    writer.flush()?;
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(temp, metadata.permissions())?;
+    }
+    fs::rename(temp, path)?;
+    Ok(())
+}
+
+struct Document {
+    path: PathBuf,
+    rope: Rope,
+    /// Incremented by every edit
+    version: u64,
+    /// Edited since opened or last saved
+    dirty: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DocumentInfo {
+    pub id: String,
+    pub path: String,
+    pub version: u64,
+    pub size: usize,
+    pub line_count: usize,
+    pub dirty: bool,
+}
+
+pub struct DocumentStore {
+    documents: HashMap<String, Document>,
+}
+
+impl Document {
+    fn info(&self, id: &str) -> DocumentInfo {
+        DocumentInfo {
+            id: id.to_string(),
+            path: self.path.to_string_lossy().to_string(),
+            version: self.version,
+            size: self.rope.len_bytes(),
+            line_count: self.rope.line_count(),
+            dirty: self.dirty,
+        }
+    }
+}
+
+impl DocumentStore {
+    pub fn new() -> Self {
+        Self {
+            documents: HashMap::new(),
+        }
+    }
+
+    fn get_mut(&mut self, id: &str) -> Result<&mut Document> {
+        self.documents.get_mut(id).ok_or_else(|| anyhow!("Unknown document: {}", id))
+    }
+
+    fn get(&self, id: &str) -> Result<&Document> {
+        self.documents.get(id).ok_or_else(|| anyhow!("Unknown document: {}", id))
+    }
+
+    fn find_path(&self, path: &Path) -> Option<(&String, &Document)> {
+        self.documents.iter().find(|(_, d)| d.path == path)
+    }
+
+    /// Load a file into a buffer; a file that is already open returns its existing buffer
+    pub fn open(&mut self, path: &Path) -> Result<DocumentInfo> {
+        if let Some((id, document)) = self.find_path(path) {
+            return Ok(document.info(id));
+        }
+        let text = fs::read_to_string(path)?;
+        let id = storage::new_id("doc");
+        let document = Document {
+            path: path.to_path_buf(),
+            rope: Rope::from_text(&text),
+            version: 0,
+            dirty: false,
+        };
+        let info = document.info(&id);
+        self.documents.insert(id, document);
+        Ok(info)
+    }
+
+    /// Take the editor's text as the buffer of `path` (didOpen).
+    /// An already open buffer is replaced and moves to the next version.
+    pub fn sync_open(&mut self, path: &Path, text: &str) -> DocumentInfo {
+        let dirty = fs::read_to_string(path).map(|disk| disk != text).unwrap_or(true);
+        if let Some((id, document)) = self.documents.iter_mut().find(|(_, d)| d.path == path) {
+            document.rope = Rope::from_text(text);
+            document.version += 1;
+            document.dirty = dirty;
+            return document.info(id);
+        }
+        let id = storage::new_id("doc");
+        let document = Document {
+            path: path.to_path_buf(),
+            rope: Rope::from_text(text),
+            version: 0,
+            dirty,
+        };
+        let info = document.info(&id);
+        self.documents.insert(id, document);
+        info
+    }
+
+    /// Apply non-overlapping edits, positioned against the current version.
+    /// With `expected_version`, edits made against an older version are rejected.
+    pub fn apply_edits(&mut self, id: &str, edits: &[TextEdit], expected_version: Option<u64>) -> Result<DocumentInfo> {
+        let document = self.get_mut(id)?;
+        if let Some(expected) = expected_version {
+            if expected != document.version {
+                return Err(anyhow!("Document is at version {}, not {}", document.version, expected));
+            }
+        }
+        let mut ranges = Vec::with_capacity(edits.len());
+        for edit in edits {
+            let start = document.rope.offset_of(edit.start)?;
+            let end = document.rope.offset_of(edit.end)?;
+            if end < start {
+                return Err(anyhow!("Edit range end precedes start"));
+            }
+            ranges.push((start, end, edit.new_text.as_str()));
+        }
+        ranges.sort_by_key(|(start, end, _)| (*start, *end));
+        if ranges.windows(2).any(|pair| pair[1].0 < pair[0].1) {
+            return Err(anyhow!("Overlapping edits"));
+        }
+        // Back to front, so earlier offsets stay valid
+        for (start, end, text) in ranges.into_iter().rev() {
+            document.rope.replace(start, end, text)?;
+        }
+        document.version += 1;
+        document.dirty = true;
+        Ok(document.info(id))
+    }
+
+    /// Text of the 1-based lines `start_line..=end_line`, clamped to the document
+    pub fn text_range(&self, id: &str, start_line: usize, end_line: usize) -> Result<String> {
+        let rope = &self.get(id)?.rope;
+        let line_count = rope.line_count();
+        let start_line = start_line.clamp(1, line_count);
+        let start = rope.line_start(start_line).unwrap_or(rope.len_bytes());
+        let end = if end_line >= line_count {
+            rope.len_bytes()
+        } else {
+            rope.line_start(end_line.max(start_line) + 1).unwrap_or(rope.len_bytes())
+        };
+        rope.slice(start, end)
+    }
+
+    /// Write the buffer back through a temp file next to it
+    pub fn save(&mut self, id: &str) -> Result<DocumentInfo> {
+        let document = self.get_mut(id)?;
+        let display = document.path.to_string_lossy().to_string();
+        file_access::check(&document.path, &display)?;
+        let name = document.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let temp = document.path.with_file_name(format!(".{}.tmp-{}", name, id));
+        if let Err(e) = write_via_temp(&document.path, &temp, &document.rope) {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+        document.dirty = false;
+        Ok(document.info(id))
+    }
+
+    /// Current text of the buffer open for `path`, if any
+    pub fn live_text(&self, path: &Path) -> Option<String> {
+        self.find_path(path).map(|(_, d)| d.rope.chunks().collect())
+    }
+
+    /// Drop a buffer (didClose); the file on disk is the source of truth again
+    pub fn close(&mut self, id: &str) -> Result<DocumentInfo> {
+        self.documents
+            .remove(id)
+            .map(|d| d.info(id))
+            .ok_or_else(|| anyhow!("Unknown document: {}", id))
+    }
+
+    /// Files with an open buffer
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.documents.values().map(|d| d.path.clone()).collect()
+    }
+
+    pub fn count(&self) -> usize {
+        self.documents.len()
+    }
+}
+
+/// Content of `path` as the editor sees it: the open buffer, else the file on disk
+pub fn read_source(store: &Mutex<DocumentStore>, path: &Path) -> std::io::Result<String> {
+    match store.lock().unwrap().live_text(path) {
+        Some(text) => Ok(text),
+        None => fs::read_to_string(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changeset::Position;
+
+    #[test]
+    fn test_edit_range_and_save() {
+        let file = std::env::temp_dir().join(storage::new_id("document-test"));
+        fs::write(&file, "one\ntwo\nthree\n").unwrap();
+        let mut store = DocumentStore::new();
+        let info = store.open(&file).unwrap();
+        assert_eq!(info.line_count, 4);
+
+        let edit = TextEdit {
+            start: Position { line: 2, column: 0 },
+            end: Position { line: 2, column: 3 },
+            new_text: "TWO".to_string(),
+        };
+        let info = store.apply_edits(&info.id, &[edit.clone()], Some(0)).unwrap();
+        assert_eq!(info.version, 1);
+        assert!(store.apply_edits(&info.id, &[edit], Some(0)).is_err());
+        assert_eq!(store.text_range(&info.id, 2, 3).unwrap(), "TWO\nthree\n");
+
+        store.save(&info.id).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "one\nTWO\nthree\n");
+        let _ = fs::remove_file(&file);
+    }
+
+    #[test]
+    fn test_live_buffer_shadows_disk() {
+        let file = std::env::temp_dir().join(storage::new_id("document-sync-test"));
+        fs::write(&file, "saved\n").unwrap();
+        let store = Mutex::new(DocumentStore::new());
+        let info = store.lock().unwrap().sync_open(&file, "unsaved\n");
+        assert!(info.dirty);
+        assert_eq!(read_source(&store, &file).unwrap(), "unsaved\n");
+
+        let reopened = store.lock().unwrap().sync_open(&file, "saved\n");
+        assert_eq!((reopened.id.as_str(), reopened.version, reopened.dirty), (info.id.as_str(), 1, false));
+        store.lock().unwrap().close(&info.id).unwrap();
+        fs::write(&file, "changed on disk\n").unwrap();
+        assert_eq!(read_source(&store, &file).unwrap(), "changed on disk\n");
+        let _ = fs::remove_file(&file);
+    }
+}
@@ -0,0 +1,547 @@
+// Endpoints - HTTP route declarations of backend frameworks
+// Express/Fastify calls, axum/actix routers and macros, FastAPI/Flask decorators; exportable as OpenAPI
+
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::sloc;
+
+const METHODS: [&str; 8] = ["get", "post", "put", "patch", "delete", "head", "options", "all"];
+
+/// Receivers of `.get('/path')` calls that send requests instead of declaring routes
+const HTTP_CLIENTS: [&str; 10] = ["axios", "http", "https", "request", "client", "fetch", "ky", "got", "superagent", "agent"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Endpoint {
+    /// Uppercase; `ANY` when the route accepts every method
+    pub method: String,
+    /// As declared, e.g. `/users/:id` or `/users/<int:id>`
+    pub path: String,
+    pub handler: Option<String>,
+    pub framework: String,
+    pub file: String,
+    pub line: usize,
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Contents of the string literal at the start of `text`
+fn literal_at(text: &str) -> Option<&str> {
+    let text = text.trim_start();
+    let quote = text.chars().next().filter(|c| matches!(c, '"' | '\'' | '`'))?;
+    let rest = &text[1..];
+    rest.find(quote).map(|end| &rest[..end])
+}
+
+/// Byte offset of the bracket closing the one at `open`, skipping string literals
+fn closing(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (i, c) in text[open..].char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Arguments split at top-level commas
+fn arguments(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0i32, 0);
+    let mut quote: Option<char> = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                parts.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(text[start..].trim());
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+/// A handler argument given by name (`listUsers`, `users::list`), `None` for inline functions
+fn handler_name(argument: &str) -> Option<String> {
+    let argument = argument.trim();
+    let named = !argument.is_empty() && argument.chars().all(|c| is_identifier_char(c) || c == '.' || c == ':');
+    named.then(|| argument.to_string())
+}
+
+fn is_inline_function(argument: &str) -> bool {
+    argument.contains("=>") || argument.starts_with("function") || argument.starts_with("async")
+}
+
+fn line_at(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+/// Identifier ending right before `offset`
+fn identifier_before(content: &str, offset: usize) -> &str {
+    let before = &content[..offset];
+    let start = before.rfind(|c: char| !is_identifier_char(c)).map(|i| i + 1).unwrap_or(0);
+    &before[start..]
+}
+
+/// Name of the first `keyword` item after `offset`: the function a decorator or attribute applies to
+fn next_definition(content: &str, offset: usize, keyword: &str) -> Option<String> {
+    let rest = &content[offset..];
+    let at = rest.match_indices(keyword).map(|(at, _)| at).find(|&at| {
+        rest[..at].chars().next_back().is_none_or(|c| !is_identifier_char(c))
+    })?;
+    let name: String = rest[at + keyword.len()..].trim_start().chars().take_while(|c| is_identifier_char(*c)).collect();
+    (!name.is_empty()).then_some(name)
+}
+
+struct Scan<'a> {
+    content: &'a str,
+    file: &'a str,
+    framework: &'a str,
+    endpoints: Vec<Endpoint>,
+}
+
+impl<'a> Scan<'a> {
+    fn push(&mut self, method: &str, path: &str, handler: Option<String>, offset: usize) {
+        self.endpoints.push(Endpoint {
+            method: if method.eq_ignore_ascii_case("all") { "ANY".to_string() } else { method.to_uppercase() },
+            path: path.to_string(),
+            handler,
+            framework: self.framework.to_string(),
+            file: self.file.to_string(),
+            line: line_at(self.content, offset),
+        });
+    }
+
+    /// `app.get('/users', auth, listUsers)` and `router.route('/users').get(list).post(create)`
+    fn javascript(&mut self) {
+        let content = self.content;
+        for (dot, _) in content.match_indices('.') {
+            let rest = &content[dot + 1..];
+            let method = METHODS.iter().chain(["route"].iter()).find(|m| {
+                rest.starts_with(*m) && rest[m.len()..].starts_with('(')
+            });
+            let method = match method {
+                Some(method) => *method,
+                None => continue,
+            };
+            if HTTP_CLIENTS.contains(&identifier_before(content, dot)) {
+                continue;
+            }
+            let open = dot + 1 + method.len();
+            let close = match closing(content, open) {
+                Some(close) => close,
+                None => continue,
+            };
+            let args = arguments(&content[open + 1..close]);
+            if method == "route" {
+                match args.first() {
+                    Some(first) if first.starts_with('{') => self.fastify_route(first, dot),
+                    Some(first) => {
+                        if let Some(path) = literal_at(first).filter(|p| p.starts_with('/')) {
+                            self.chained(close + 1, path, dot);
+                        }
+                    }
+                    None => {}
+                }
+                continue;
+            }
+            let path = match args.first().and_then(|first| literal_at(first)) {
+                Some(path) if path.starts_with('/') => path,
+                _ => continue,
+            };
+            let last = args.last().copied().unwrap_or("");
+            if args.len() < 2 || !(handler_name(last).is_some() || is_inline_function(last)) {
+                continue;
+            }
+            self.push(method, path, handler_name(last), dot);
+        }
+    }
+
+    /// `.get(list).post(create)` chained on a route at `from`
+    fn chained(&mut self, from: usize, path: &str, offset: usize) {
+        let mut at = from;
+        loop {
+            let rest = &self.content[at..];
+            let trimmed = rest.trim_start();
+            let method = match trimmed.strip_prefix('.').and_then(|r| METHODS.iter().find(|m| r.starts_with(*m))) {
+                Some(method) => *method,
+                None => return,
+            };
+            let open = at + (rest.len() - trimmed.len()) + 1 + method.len();
+            if !self.content[open..].starts_with('(') {
+                return;
+            }
+            let close = match closing(self.content, open) {
+                Some(close) => close,
+                None => return,
+            };
+            let handler = arguments(&self.content[open + 1..close]).last().and_then(|a| handler_name(a));
+            self.push(method, path, handler, offset);
+            at = close + 1;
+        }
+    }
+
+    /// `fastify.route({ method: ['GET', 'HEAD'], url: '/users', handler: list })`
+    fn fastify_route(&mut self, options: &str, offset: usize) {
+        let body = options.trim_start_matches('{').trim_end_matches('}');
+        let field = |name: &str| {
+            arguments(body)
+                .into_iter()
+                .find_map(|part| part.split_once(':').filter(|(key, _)| key.trim() == name).map(|(_, value)| value.trim()))
+        };
+        let path = match field("url").or_else(|| field("path")).and_then(literal_at) {
+            Some(path) => path,
+            None => return,
+        };
+        let handler = field("handler").and_then(handler_name);
+        let methods = field("method").unwrap_or("'GET'");
+        for method in arguments(methods.trim_start_matches('[').trim_end_matches(']')) {
+            if let Some(method) = literal_at(method) {
+                self.push(method, path, handler.clone(), offset);
+            }
+        }
+    }
+
+    /// `.route("/users", get(list).post(create))`, actix `web::get().to(list)`, `#[get("/users")]`
+    /// and `web::resource("/users").route(web::post().to(create))`
+    fn rust(&mut self) {
+        let content = self.content;
+        for (at, _) in content.match_indices(".route(") {
+            let open = at + ".route".len();
+            let close = match closing(content, open) {
+                Some(close) => close,
+                None => continue,
+            };
+            let args = arguments(&content[open + 1..close]);
+            if let (Some(path), Some(router)) = (args.first().and_then(|a| literal_at(a)), args.get(1)) {
+                for (method, handler) in method_routers(router) {
+                    self.push(&method, path, handler, at);
+                }
+            }
+        }
+        for (at, _) in content.match_indices("resource(") {
+            if content[..at].chars().next_back().is_some_and(is_identifier_char) {
+                continue;
+            }
+            let open = at + "resource".len();
+            let (close, path) = match (closing(content, open), literal_at(&content[open + 1..])) {
+                (Some(close), Some(path)) => (close, path),
+                _ => continue,
+            };
+            let chain_end = content[close..].find(';').map(|end| close + end).unwrap_or(content.len());
+            let chain = &content[close + 1..chain_end];
+            let chain = chain.find("resource(").map(|next| &chain[..next]).unwrap_or(chain);
+            let mut routes = method_routers(chain);
+            if routes.is_empty() && chain.trim_start().starts_with(".to(") {
+                routes.push(("ANY".to_string(), to_handler(chain)));
+            }
+            for (method, handler) in routes {
+                self.push(&method, path, handler, at);
+            }
+        }
+        for (at, _) in content.match_indices("#[") {
+            let attribute: String = content[at + 2..].chars().take_while(|c| is_identifier_char(*c) || *c == ':').collect();
+            let method = attribute.rsplit("::").next().unwrap_or("");
+            if !METHODS.contains(&method) && method != "route" {
+                continue;
+            }
+            let open = at + 2 + attribute.len();
+            let close = match content[open..].starts_with('(').then(|| closing(content, open)).flatten() {
+                Some(close) => close,
+                None => continue,
+            };
+            let args = arguments(&content[open + 1..close]);
+            let path = match args.first().and_then(|a| literal_at(a)) {
+                Some(path) if path.starts_with('/') => path,
+                _ => continue,
+            };
+            let handler = next_definition(content, close, "fn ");
+            if method == "route" {
+                // `#[route("/", method = "GET", method = "HEAD")]`
+                for value in args.iter().filter_map(|a| a.strip_prefix("method")) {
+                    if let Some(method) = literal_at(value.trim_start().trim_start_matches('=')) {
+                        self.push(method, path, handler.clone(), at);
+                    }
+                }
+            } else {
+                self.push(method, path, handler, at);
+            }
+        }
+    }
+
+    /// `@app.get("/users")`, `@router.post(...)` and Flask's `@bp.route("/users", methods=["GET", "POST"])`
+    fn python(&mut self) {
+        let content = self.content;
+        for (at, _) in content.match_indices('@') {
+            let decorator: String = content[at + 1..].chars().take_while(|c| is_identifier_char(*c) || *c == '.').collect();
+            let method = decorator.rsplit('.').next().unwrap_or("");
+            if !decorator.contains('.') || (!METHODS.contains(&method) && method != "route" && method != "api_route") {
+                continue;
+            }
+            let open = at + 1 + decorator.len();
+            let close = match content[open..].starts_with('(').then(|| closing(content, open)).flatten() {
+                Some(close) => close,
+                None => continue,
+            };
+            let args = arguments(&content[open + 1..close]);
+            let path = match args.first().and_then(|a| literal_at(a)) {
+                Some(path) if path.starts_with('/') => path,
+                _ => continue,
+            };
+            let handler = next_definition(content, close, "def ");
+            if METHODS.contains(&method) {
+                self.push(method, path, handler, at);
+                continue;
+            }
+            let methods = args.iter().find_map(|a| a.split_once('=').filter(|(key, _)| key.trim() == "methods"));
+            let methods = match methods {
+                Some((_, list)) => arguments(list.trim().trim_start_matches('[').trim_end_matches(']')),
+                None => vec!["'GET'"],
+            };
+            for method in methods {
+                if let Some(method) = literal_at(method) {
+                    self.push(method, path, handler.clone(), at);
+                }
+            }
+        }
+    }
+}
+
+/// `.to(handler)` at the start of a chain
+fn to_handler(chain: &str) -> Option<String> {
+    let rest = chain.trim_start().strip_prefix(".to(")?;
+    rest.split(')').next().and_then(handler_name)
+}
+
+/// Methods of an axum method router (`get(list).post(create)`) or actix routes (`web::get().to(list)`)
+fn method_routers(text: &str) -> Vec<(String, Option<String>)> {
+    let mut routes = Vec::new();
+    for method in METHODS.iter().chain(["any"].iter()) {
+        let call = format!("{}(", method);
+        for (at, _) in text.match_indices(&call) {
+            if text[..at].chars().next_back().is_some_and(is_identifier_char) {
+                continue;
+            }
+            let open = at + method.len();
+            let close = match closing(text, open) {
+                Some(close) => close,
+                None => continue,
+            };
+            let inner = text[open + 1..close].trim();
+            let handler = if inner.is_empty() { to_handler(&text[close + 1..]) } else { handler_name(inner) };
+            routes.push((at, method.to_uppercase().replace("ALL", "ANY"), handler));
+        }
+    }
+    routes.sort_by_key(|(at, _, _)| *at);
+    routes.into_iter().map(|(_, method, handler)| (method, handler)).collect()
+}
+
+/// Best guess at the framework from what the file imports
+fn framework(language: &str, content: &str) -> &'static str {
+    match language {
+        "Rust" if content.contains("actix") => "actix",
+        "Rust" => "axum",
+        "Python" if content.contains("flask") || content.contains("Flask") => "flask",
+        "Python" => "fastapi",
+        _ if content.contains("fastify") => "fastify",
+        _ => "express",
+    }
+}
+
+fn language_for(extension: &str) -> Option<&'static str> {
+    match extension {
+        "ts" | "mts" | "cts" => Some("TypeScript"),
+        "js" | "mjs" | "cjs" => Some("JavaScript"),
+        "rs" => Some("Rust"),
+        "py" => Some("Python"),
+        _ => None,
+    }
+}
+
+/// Whether files with `extension` may declare routes
+pub fn is_source(extension: &str) -> bool {
+    language_for(extension).is_some()
+}
+
+/// Endpoints declared in a source file
+pub fn extract(file: &str, content: &str) -> Vec<Endpoint> {
+    let language = match language_for(file.rsplit('.').next().unwrap_or("")) {
+        Some(language) => language,
+        None => return Vec::new(),
+    };
+    let masked = sloc::without_comments(content, language).join("\n");
+    let mut scan = Scan {
+        content: &masked,
+        file,
+        framework: framework(language, content),
+        endpoints: Vec::new(),
+    };
+    match language {
+        "Rust" => scan.rust(),
+        "Python" => scan.python(),
+        _ => scan.javascript(),
+    }
+    let mut endpoints = scan.endpoints;
+    endpoints.sort_by_key(|e| e.line);
+    endpoints
+}
+
+/// Endpoints matching every word of `text`: a word naming a method filters by method,
+/// any other must appear in the path or handler
+pub fn query(endpoints: &[Endpoint], text: &str) -> Vec<Endpoint> {
+    endpoints
+        .iter()
+        .filter(|endpoint| {
+            text.split_whitespace().all(|word| {
+                if METHODS.contains(&word.to_lowercase().as_str()) {
+                    return endpoint.method.eq_ignore_ascii_case(word);
+                }
+                let word = word.to_lowercase();
+                endpoint.path.to_lowercase().contains(&word)
+                    || endpoint.handler.as_deref().is_some_and(|h| h.to_lowercase().contains(&word))
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// OpenAPI template path and its parameters: `/users/:id`, `/users/<int:id>` and `/users/{id}` give `/users/{id}`
+fn openapi_path(path: &str) -> (String, Vec<String>) {
+    let mut parameters = Vec::new();
+    let segments: Vec<String> = path
+        .split('/')
+        .map(|segment| {
+            let name = if let Some(name) = segment.strip_prefix(':') {
+                Some(name.trim_end_matches('?'))
+            } else if segment.starts_with('<') && segment.ends_with('>') {
+                Some(segment[1..segment.len() - 1].rsplit(':').next().unwrap_or(""))
+            } else if segment.starts_with('{') && segment.ends_with('}') {
+                Some(segment[1..segment.len() - 1].split(':').next().unwrap_or("").trim_start_matches('*'))
+            } else {
+                None
+            };
+            match name {
+                Some(name) => {
+                    parameters.push(name.to_string());
+                    format!("{{{}}}", name)
+                }
+                None => segment.to_string(),
+            }
+        })
+        .collect();
+    (segments.join("/"), parameters)
+}
+
+/// An OpenAPI 3 document with one operation per endpoint and responses left to fill in
+pub fn to_openapi(endpoints: &[Endpoint], title: &str) -> Value {
+    let mut paths: BTreeMap<String, serde_json::Map<String, Value>> = BTreeMap::new();
+    for endpoint in endpoints {
+        let (path, parameters) = openapi_path(&endpoint.path);
+        let parameters: Vec<Value> = parameters
+            .iter()
+            .map(|name| json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } }))
+            .collect();
+        let methods: Vec<String> = if endpoint.method == "ANY" {
+            ["get", "post", "put", "patch", "delete"].iter().map(|m| m.to_string()).collect()
+        } else {
+            vec![endpoint.method.to_lowercase()]
+        };
+        for method in methods {
+            let mut operation = json!({
+                "parameters": parameters,
+                "responses": { "200": { "description": "OK" } },
+                "x-source": format!("{}:{}", endpoint.file, endpoint.line),
+            });
+            if let Some(handler) = &endpoint.handler {
+                operation["operationId"] = json!(handler);
+            }
+            paths.entry(path.clone()).or_default().insert(method, operation);
+        }
+    }
+    json!({
+        "openapi": "3.0.3",
+        "info": { "title": title, "version": "0.1.0" },
+        "paths": paths,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routes(file: &str, content: &str) -> Vec<(String, String, Option<String>)> {
+        extract(file, content).into_iter().map(|e| (e.method, e.path, e.handler)).collect()
+    }
+
+    fn route(method: &str, path: &str, handler: Option<&str>) -> (String, String, Option<String>) {
+        (method.to_string(), path.to_string(), handler.map(str::to_string))
+    }
+
+    #[test]
+    fn test_javascript_and_python_routes() {
+        let js = "const app = express();\napp.get('/users/:id', auth, getUser);\n// app.get('/old', legacy);\nrouter.post(\"/users\", async (req, res) => {\n  res.send(await axios.get('/remote', options));\n});\napp.route('/items').get(listItems).delete(clear);\n";
+        assert_eq!(
+            routes("src/server.js", js),
+            vec![
+                route("GET", "/users/:id", Some("getUser")),
+                route("POST", "/users", None),
+                route("GET", "/items", Some("listItems")),
+                route("DELETE", "/items", Some("clear")),
+            ]
+        );
+        let py = "from flask import Flask\n\n@app.route(\"/login\", methods=[\"GET\", \"POST\"])\n@login_required\ndef login():\n    pass\n\n@bp.delete('/users/<int:id>')\ndef remove(id):\n    pass\n";
+        assert_eq!(
+            routes("app.py", py),
+            vec![
+                route("GET", "/login", Some("login")),
+                route("POST", "/login", Some("login")),
+                route("DELETE", "/users/<int:id>", Some("remove")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_routes_and_openapi() {
+        let rust = "let app = Router::new()\n    .route(\"/users\", get(list_users).post(create_user))\n    .route(\"/users/:id\", delete(users::remove));\n\n#[get(\"/health\")]\nasync fn health() -> &'static str { \"ok\" }\n";
+        let endpoints = extract("src/main.rs", rust);
+        let found: Vec<(String, String, Option<String>)> =
+            endpoints.iter().map(|e| (e.method.clone(), e.path.clone(), e.handler.clone())).collect();
+        assert_eq!(
+            found,
+            vec![
+                route("GET", "/users", Some("list_users")),
+                route("POST", "/users", Some("create_user")),
+                route("DELETE", "/users/:id", Some("users::remove")),
+                route("GET", "/health", Some("health")),
+            ]
+        );
+        assert_eq!(query(&endpoints, "get users").len(), 1);
+        let spec = to_openapi(&endpoints, "api");
+        assert_eq!(spec["paths"]["/users/{id}"]["delete"]["parameters"][0]["name"], "id");
+        assert_eq!(spec["paths"]["/health"]["get"]["operationId"], "health");
+    }
+}
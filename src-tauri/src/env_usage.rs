@@ -0,0 +1,320 @@
+// Env Usage - Environment variables read by the code against those documented for it
+// Reads: process.env, std::env::var, os.environ; documentation: .env.example files and docker-compose
+
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+use crate::sloc;
+
+/// Example env files checked into a repository
+const EXAMPLE_FILES: [&str; 5] = [".env.example", ".env.sample", ".env.template", ".env.dist", ".env.defaults"];
+
+/// Set by the OS, CI or the toolchain rather than by the project
+const IMPLICIT: [&str; 10] = ["NODE_ENV", "PATH", "HOME", "USER", "PWD", "SHELL", "TERM", "TMPDIR", "CI", "RUST_LOG"];
+const IMPLICIT_PREFIXES: [&str; 3] = ["CARGO_", "npm_", "GITHUB_"];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Argument {
+    /// `process.env.NAME`
+    Identifier,
+    /// `env::var("NAME")`
+    Literal,
+}
+
+const JS_READS: [(&str, Argument); 4] = [
+    ("process.env.", Argument::Identifier),
+    ("process.env[", Argument::Literal),
+    ("import.meta.env.", Argument::Identifier),
+    ("Deno.env.get(", Argument::Literal),
+];
+
+const RUST_READS: [(&str, Argument); 4] = [
+    ("env::var(", Argument::Literal),
+    ("env::var_os(", Argument::Literal),
+    ("env!(", Argument::Literal),
+    ("option_env!(", Argument::Literal),
+];
+
+const PYTHON_READS: [(&str, Argument); 3] =
+    [("environ[", Argument::Literal), ("environ.get(", Argument::Literal), ("getenv(", Argument::Literal)];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvStatus {
+    Documented,
+    /// Read by the code but in no example file or compose service
+    Undocumented,
+    /// In an example file but never read
+    Unused,
+    /// Provided by the OS, CI or toolchain
+    Implicit,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EnvReference {
+    pub file: String,
+    pub line: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EnvVariable {
+    pub name: String,
+    pub status: EnvStatus,
+    pub used_in: Vec<EnvReference>,
+    pub documented_in: Vec<EnvReference>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EnvUsageReport {
+    pub variables: Vec<EnvVariable>,
+    pub undocumented: usize,
+    pub unused: usize,
+    pub documentation_files: Vec<String>,
+}
+
+fn is_compose(file_name: &str) -> bool {
+    let yaml = file_name.ends_with(".yml") || file_name.ends_with(".yaml");
+    yaml && (file_name.starts_with("docker-compose") || file_name.starts_with("compose."))
+}
+
+/// Whether a file documents environment variables
+pub fn is_documentation(file_name: &str) -> bool {
+    EXAMPLE_FILES.contains(&file_name) || is_compose(file_name)
+}
+
+fn is_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_implicit(name: &str) -> bool {
+    IMPLICIT.contains(&name) || IMPLICIT_PREFIXES.iter().any(|p| name.starts_with(p))
+}
+
+fn identifier_at(text: &str) -> &str {
+    let end = text.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(text.len());
+    &text[..end]
+}
+
+/// Contents of a quoted string at the start of `text`
+fn literal_at(text: &str) -> Option<&str> {
+    let text = text.trim_start();
+    let quote = text.chars().next().filter(|c| matches!(c, '"' | '\'' | '`'))?;
+    let rest = &text[1..];
+    rest.find(quote).map(|end| &rest[..end])
+}
+
+/// Names in `const { A, B: alias, C = "x" } = process.env` on one line
+fn destructured(line: &str) -> Vec<&str> {
+    let at = match line.find("} = process.env") {
+        Some(at) if !line[at..].starts_with("} = process.env.") && !line[at..].starts_with("} = process.env[") => at,
+        _ => return Vec::new(),
+    };
+    let open = match line[..at].rfind('{') {
+        Some(open) => open,
+        None => return Vec::new(),
+    };
+    line[open + 1..at]
+        .split(',')
+        .map(|part| identifier_at(part.trim()))
+        .filter(|name| is_name(name))
+        .collect()
+}
+
+/// Comment syntax and read patterns for a file extension
+fn reads_for(extension: &str) -> Option<(&'static str, &'static [(&'static str, Argument)])> {
+    match extension {
+        "ts" | "tsx" | "mts" | "cts" | "vue" => Some(("TypeScript", &JS_READS)),
+        "js" | "jsx" | "mjs" | "cjs" => Some(("JavaScript", &JS_READS)),
+        "rs" => Some(("Rust", &RUST_READS)),
+        "py" => Some(("Python", &PYTHON_READS)),
+        _ => None,
+    }
+}
+
+/// Whether files with `extension` are scanned for reads
+pub fn is_source(extension: &str) -> bool {
+    reads_for(extension).is_some()
+}
+
+/// Variables read by a source file, with 1-based lines
+fn reads(file: &str, content: &str) -> Vec<(String, usize)> {
+    let (language, patterns) = match reads_for(file.rsplit('.').next().unwrap_or("")) {
+        Some(reads) => reads,
+        None => return Vec::new(),
+    };
+    let mut found = Vec::new();
+    for (i, line) in sloc::without_comments(content, language).iter().enumerate() {
+        for (marker, argument) in patterns {
+            for (at, _) in line.match_indices(marker) {
+                let preceded = line[..at].chars().next_back().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$');
+                if preceded {
+                    continue;
+                }
+                let rest = &line[at + marker.len()..];
+                let name = match argument {
+                    Argument::Identifier => Some(identifier_at(rest)),
+                    Argument::Literal => literal_at(rest),
+                };
+                if let Some(name) = name.filter(|n| is_name(n)) {
+                    found.push((name.to_string(), i + 1));
+                }
+            }
+        }
+        if matches!(language, "TypeScript" | "JavaScript") {
+            found.extend(destructured(line).into_iter().map(|name| (name.to_string(), i + 1)));
+        }
+    }
+    found
+}
+
+/// `NAME=value` entries of a dotenv file; commented-out entries document optional variables
+fn dotenv_names(content: &str) -> Vec<(String, usize)> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line = line.trim().trim_start_matches('#').trim_start();
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (name, _) = line.split_once('=')?;
+            is_name(name.trim()).then(|| (name.trim().to_string(), i + 1))
+        })
+        .collect()
+}
+
+/// Keys of `environment:` blocks and `${NAME}` interpolations of a compose file
+fn compose_names(content: &str) -> Vec<(String, usize)> {
+    let mut found = Vec::new();
+    let mut block: Option<usize> = None;
+    for (i, raw) in content.lines().enumerate() {
+        let line = raw.split(" #").next().unwrap_or(raw);
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            if block.is_some_and(|level| indent <= level) {
+                block = None;
+            }
+            if block.is_some() {
+                let entry = trimmed.trim_start_matches("- ").trim_matches(['"', '\'']);
+                let name = entry.split(['=', ':']).next().unwrap_or("").trim();
+                if is_name(name) {
+                    found.push((name.to_string(), i + 1));
+                }
+            }
+            if trimmed.trim_end() == "environment:" {
+                block = Some(indent);
+            }
+        }
+        let mut rest = line;
+        while let Some(at) = rest.find("${") {
+            let name = identifier_at(&rest[at + 2..]);
+            if is_name(name) {
+                found.push((name.to_string(), i + 1));
+            }
+            rest = &rest[at + 2..];
+        }
+    }
+    found
+}
+
+/// Cross-reference reads in `sources` with documentation files, both as (workspace-relative path, content)
+pub fn report(sources: &[(String, String)], documentation: &[(String, String)]) -> EnvUsageReport {
+    // name -> (reads, example-file entries, compose entries)
+    let mut variables: BTreeMap<String, (Vec<EnvReference>, Vec<EnvReference>, Vec<EnvReference>)> = BTreeMap::new();
+    for (file, content) in sources {
+        for (name, line) in reads(file, content) {
+            let reference = EnvReference { file: file.clone(), line };
+            let used = &mut variables.entry(name).or_default().0;
+            if !used.contains(&reference) {
+                used.push(reference);
+            }
+        }
+    }
+    for (file, content) in documentation {
+        let file_name = file.rsplit('/').next().unwrap_or(file);
+        let compose = is_compose(file_name);
+        let names = if compose { compose_names(content) } else { dotenv_names(content) };
+        for (name, line) in names {
+            let entry = variables.entry(name).or_default();
+            let references = if compose { &mut entry.2 } else { &mut entry.1 };
+            references.push(EnvReference { file: file.clone(), line });
+        }
+    }
+
+    let variables: Vec<EnvVariable> = variables
+        .into_iter()
+        .filter_map(|(name, (used_in, examples, compose))| {
+            // Compose entries often configure third-party images, so they only count as documentation
+            let status = if is_implicit(&name) {
+                EnvStatus::Implicit
+            } else if used_in.is_empty() && examples.is_empty() {
+                return None;
+            } else if used_in.is_empty() {
+                EnvStatus::Unused
+            } else if examples.is_empty() && compose.is_empty() {
+                EnvStatus::Undocumented
+            } else {
+                EnvStatus::Documented
+            };
+            let mut documented_in = examples;
+            documented_in.extend(compose);
+            Some(EnvVariable {
+                name,
+                status,
+                used_in,
+                documented_in,
+            })
+        })
+        .collect();
+    EnvUsageReport {
+        undocumented: variables.iter().filter(|v| v.status == EnvStatus::Undocumented).count(),
+        unused: variables.iter().filter(|v| v.status == EnvStatus::Unused).count(),
+        documentation_files: documentation.iter().map(|(file, _)| file.clone()).collect(),
+        variables,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_by_language() {
+        let js = "const url = process.env.API_URL;\nconst { PORT, HOST: host = 'x' } = process.env;\n// process.env.OLD_FLAG\nconst key = process.env['API_KEY'] ?? import.meta.env.VITE_MODE;\n";
+        let names: Vec<String> = reads("src/config.ts", js).into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["API_URL", "PORT", "HOST", "API_KEY", "VITE_MODE"]);
+        let rust = "let url = std::env::var(\"DATABASE_URL\")?;\nconst VERSION: &str = env!(\"CARGO_PKG_VERSION\");\n";
+        let names: Vec<String> = reads("src/main.rs", rust).into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["DATABASE_URL", "CARGO_PKG_VERSION"]);
+        let python = "import os\ntoken = os.environ[\"TOKEN\"]\ndebug = os.getenv('DEBUG', False)  # os.getenv('X')\n";
+        let names: Vec<String> = reads("app.py", python).into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["TOKEN", "DEBUG"]);
+    }
+
+    #[test]
+    fn test_report_statuses() {
+        let sources = vec![
+            ("src/db.rs".to_string(), "let url = env::var(\"DATABASE_URL\");\nlet key = env::var(\"SECRET_KEY\");\nlet log = env::var(\"RUST_LOG\");\n".to_string()),
+            ("web/api.ts".to_string(), "fetch(process.env.API_URL)\n".to_string()),
+        ];
+        let documentation = vec![
+            (".env.example".to_string(), "DATABASE_URL=postgres://localhost/app\n# SENTRY_DSN=\n".to_string()),
+            (
+                "docker-compose.yml".to_string(),
+                "services:\n  web:\n    environment:\n      - API_URL=http://api\n      POSTGRES_PASSWORD: ${DB_PASSWORD:-secret}\n    ports:\n      - \"80:80\"\n".to_string(),
+            ),
+        ];
+        let report = report(&sources, &documentation);
+        let statuses: Vec<(&str, EnvStatus)> = report.variables.iter().map(|v| (v.name.as_str(), v.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("API_URL", EnvStatus::Documented),
+                ("DATABASE_URL", EnvStatus::Documented),
+                ("RUST_LOG", EnvStatus::Implicit),
+                ("SECRET_KEY", EnvStatus::Undocumented),
+                ("SENTRY_DSN", EnvStatus::Unused),
+            ]
+        );
+        assert_eq!((report.undocumented, report.unused), (1, 1));
+    }
+}
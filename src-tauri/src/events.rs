@@ -0,0 +1,648 @@
+// Event Bus - Typed, versioned engine events for the frontend
+// Subscribers pull batches from bounded queues at their own pace
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use crate::storage;
+
+/// Bumped whenever an event is removed or its payload changes incompatibly
+pub const CATALOG_VERSION: u32 = 1;
+
+/// Queued events per subscription before the oldest are dropped
+const DEFAULT_CAPACITY: usize = 256;
+const MAX_CAPACITY: usize = 10_000;
+/// Pending file, git status and diagnostics updates of a subscriber above which they fold into one `BulkChange`
+const BULK_THRESHOLD: usize = 500;
+/// Changed paths a `BulkChange` names
+const BULK_SAMPLE: usize = 20;
+/// `next` holds back file and diagnostics events until none arrived for this long, so storms arrive coalesced,
+/// but never longer than `SETTLE_MAX` after the first
+const SETTLE_QUIET: Duration = Duration::from_millis(100);
+const SETTLE_MAX: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Indexing,
+    Diagnostics,
+    Git,
+    Tasks,
+    Watcher,
+    Debug,
+    Documents,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    IndexingStarted {
+        workspace: String,
+    },
+    /// Open files, their imports and the manifests are searchable; the rest is still indexing
+    IndexingPrioritized {
+        workspace: String,
+        files: usize,
+        duration_ms: u64,
+    },
+    IndexingFinished {
+        workspace: String,
+        files: usize,
+        duration_ms: u64,
+    },
+    /// Workspace statistics after an incremental re-index; only changed fields are set
+    StatsUpdated {
+        changes: crate::stats::StatsChanges,
+    },
+    DiagnosticsChanged {
+        file: String,
+        count: usize,
+    },
+    /// Working tree changed by the app; the frontend should refresh git status
+    GitStatusChanged {
+        paths: Vec<String>,
+    },
+    TaskStarted {
+        task_id: String,
+        command: String,
+    },
+    TaskFinished {
+        task_id: String,
+        exit_code: Option<i32>,
+        duration_ms: u64,
+        timed_out: bool,
+    },
+    /// A background process printed a local URL it serves
+    DevServerDetected {
+        process_id: String,
+        url: String,
+    },
+    FilesChanged {
+        paths: Vec<String>,
+    },
+    /// A deleted and a created file had the same content; graph edges, history, breakpoints and pins moved along
+    FileRenamed {
+        from: String,
+        to: String,
+        /// Files whose imports may still name the old path
+        dependents: Vec<String>,
+    },
+    DebugStopped {
+        session_id: String,
+        thread_id: Option<i64>,
+        reason: String,
+        description: Option<String>,
+    },
+    DebugContinued {
+        session_id: String,
+        thread_id: Option<i64>,
+    },
+    DebugOutput {
+        session_id: String,
+        category: String,
+        output: String,
+    },
+    DebugTerminated {
+        session_id: String,
+        exit_code: Option<i64>,
+    },
+    /// Edits applied to a server-side document buffer, positioned against `version - 1`
+    DocumentChanged {
+        document_id: String,
+        version: u64,
+        edits: Vec<crate::changeset::TextEdit>,
+    },
+    /// Stands in for a subscriber's pending file, rename, git status and diagnostics events once there are too many
+    /// (e.g. after a branch checkout); refresh everything. Subscribers of either kind get it
+    BulkChange {
+        files: usize,
+        renamed: usize,
+        /// Files whose diagnostics changed
+        diagnostics: usize,
+        git_status_changed: bool,
+        /// A few of the changed paths
+        sample: Vec<String>,
+    },
+}
+
+impl Event {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Event::IndexingStarted { .. }
+            | Event::IndexingPrioritized { .. }
+            | Event::IndexingFinished { .. }
+            | Event::StatsUpdated { .. } => EventKind::Indexing,
+            Event::DiagnosticsChanged { .. } => EventKind::Diagnostics,
+            Event::GitStatusChanged { .. } => EventKind::Git,
+            Event::TaskStarted { .. } | Event::TaskFinished { .. } | Event::DevServerDetected { .. } => EventKind::Tasks,
+            Event::FilesChanged { .. } | Event::FileRenamed { .. } | Event::BulkChange { .. } => EventKind::Watcher,
+            Event::DebugStopped { .. }
+            | Event::DebugContinued { .. }
+            | Event::DebugOutput { .. }
+            | Event::DebugTerminated { .. } => EventKind::Debug,
+            Event::DocumentChanged { .. } => EventKind::Documents,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CatalogEntry {
+    pub kind: EventKind,
+    /// Event types as serialized in `Event`'s `type` field
+    pub events: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EventCatalog {
+    pub version: u32,
+    pub kinds: Vec<CatalogEntry>,
+}
+
+pub fn catalog() -> EventCatalog {
+    let entry = |kind: EventKind, events: &[&str]| CatalogEntry {
+        kind,
+        events: events.iter().map(|e| e.to_string()).collect(),
+    };
+    EventCatalog {
+        version: CATALOG_VERSION,
+        kinds: vec![
+            entry(
+                EventKind::Indexing,
+                &["indexing_started", "indexing_prioritized", "indexing_finished", "stats_updated"],
+            ),
+            entry(EventKind::Diagnostics, &["diagnostics_changed"]),
+            entry(EventKind::Git, &["git_status_changed"]),
+            entry(EventKind::Tasks, &["task_started", "task_finished", "dev_server_detected"]),
+            entry(EventKind::Watcher, &["files_changed", "file_renamed", "bulk_change"]),
+            entry(
+                EventKind::Debug,
+                &["debug_stopped", "debug_continued", "debug_output", "debug_terminated"],
+            ),
+            entry(EventKind::Documents, &["document_changed"]),
+        ],
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EventEnvelope {
+    pub version: u32,
+    /// Bus-wide sequence number; gaps mean events were coalesced or, as `dropped` tells, lost
+    pub seq: u64,
+    pub timestamp: u64,
+    pub kind: EventKind,
+    pub event: Event,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EventBatch {
+    pub events: Vec<EventEnvelope>,
+    /// Events dropped since the previous batch because the queue was full
+    pub dropped: u64,
+}
+
+/// Events that may be merged with queued ones or folded into a `BulkChange`
+fn foldable(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::FilesChanged { .. }
+            | Event::GitStatusChanged { .. }
+            | Event::FileRenamed { .. }
+            | Event::DiagnosticsChanged { .. }
+    )
+}
+
+/// What a queued event counts towards the bulk threshold
+fn weight(event: &Event) -> usize {
+    match event {
+        Event::FilesChanged { paths } | Event::GitStatusChanged { paths } => paths.len(),
+        Event::FileRenamed { .. } | Event::DiagnosticsChanged { .. } => 1,
+        _ => 0,
+    }
+}
+
+/// Whether `event` can be merged into `queued` instead of queueing it
+fn coalesces_with(queued: &Event, event: &Event) -> bool {
+    match (queued, event) {
+        (Event::DiagnosticsChanged { file: a, .. }, Event::DiagnosticsChanged { file: b, .. }) => a == b,
+        (Event::FilesChanged { .. }, Event::FilesChanged { .. })
+        | (Event::GitStatusChanged { .. }, Event::GitStatusChanged { .. }) => true,
+        _ => false,
+    }
+}
+
+/// Summary of the events folded into a queued `BulkChange`
+#[derive(Default)]
+struct Bulk {
+    files: HashSet<String>,
+    sample: Vec<String>,
+    renamed: usize,
+    diagnostics: HashSet<String>,
+    git_status_changed: bool,
+}
+
+impl Bulk {
+    fn add_paths<'a>(&mut self, paths: impl IntoIterator<Item = &'a String>) {
+        for path in paths {
+            if self.files.insert(path.clone()) && self.sample.len() < BULK_SAMPLE {
+                self.sample.push(path.clone());
+            }
+        }
+    }
+
+    fn fold(&mut self, event: &Event) {
+        match event {
+            Event::FilesChanged { paths } => self.add_paths(paths),
+            Event::GitStatusChanged { paths } => {
+                self.git_status_changed = true;
+                self.add_paths(paths);
+            }
+            Event::FileRenamed { from, to, .. } => {
+                self.renamed += 1;
+                self.add_paths([from, to]);
+            }
+            Event::DiagnosticsChanged { file, .. } => {
+                self.diagnostics.insert(file.clone());
+            }
+            _ => {}
+        }
+    }
+
+    fn event(&self) -> Event {
+        Event::BulkChange {
+            files: self.files.len(),
+            renamed: self.renamed,
+            diagnostics: self.diagnostics.len(),
+            git_status_changed: self.git_status_changed,
+            sample: self.sample.clone(),
+        }
+    }
+}
+
+struct Subscriber {
+    /// Empty means every kind
+    kinds: HashSet<EventKind>,
+    queue: VecDeque<EventEnvelope>,
+    capacity: usize,
+    dropped: u64,
+    /// Total weight of the queued foldable events
+    pending: usize,
+    /// Set while a `BulkChange` is queued; later foldable events go into it
+    bulk: Option<Bulk>,
+    /// When the queue last went from empty to non-empty, and when it last grew
+    first_queued: Option<Instant>,
+    last_queued: Option<Instant>,
+}
+
+impl Subscriber {
+    fn new(kinds: &[EventKind], capacity: usize) -> Self {
+        Self {
+            kinds: kinds.iter().copied().collect(),
+            queue: VecDeque::new(),
+            capacity,
+            dropped: 0,
+            pending: 0,
+            bulk: None,
+            first_queued: None,
+            last_queued: None,
+        }
+    }
+
+    fn push(&mut self, envelope: EventEnvelope) {
+        let now = Instant::now();
+        if self.queue.is_empty() {
+            self.first_queued = Some(now);
+        }
+        self.last_queued = Some(now);
+
+        if !foldable(&envelope.event) {
+            // Make room by folding before dropping anything
+            if self.queue.len() >= self.capacity && self.pending > 0 {
+                self.start_bulk(&envelope);
+            }
+            self.enqueue(envelope);
+            return;
+        }
+        if self.bulk.is_none() {
+            if let Some(added) = self.coalesce(&envelope) {
+                self.pending += added;
+                if self.pending > BULK_THRESHOLD {
+                    self.start_bulk(&envelope);
+                }
+                return;
+            }
+            let weight = weight(&envelope.event);
+            if self.queue.len() < self.capacity && self.pending + weight <= BULK_THRESHOLD {
+                self.pending += weight;
+                self.queue.push_back(envelope);
+                return;
+            }
+            self.start_bulk(&envelope);
+        }
+        self.fold(&envelope);
+    }
+
+    /// Merge into a queued event of the same type; returns the weight added. Nothing merges across a queued
+    /// rename, so changes published before it are delivered before it and later ones after it
+    fn coalesce(&mut self, envelope: &EventEnvelope) -> Option<usize> {
+        let queued = self
+            .queue
+            .iter_mut()
+            .rev()
+            .take_while(|queued| !matches!(queued.event, Event::FileRenamed { .. }))
+            .find(|queued| coalesces_with(&queued.event, &envelope.event))?;
+        let added = match (&mut queued.event, &envelope.event) {
+            (Event::DiagnosticsChanged { count, .. }, Event::DiagnosticsChanged { count: latest, .. }) => {
+                *count = *latest;
+                0
+            }
+            (Event::FilesChanged { paths }, Event::FilesChanged { paths: more })
+            | (Event::GitStatusChanged { paths }, Event::GitStatusChanged { paths: more }) => {
+                let known: HashSet<String> = paths.iter().cloned().collect();
+                let before = paths.len();
+                paths.extend(more.iter().filter(|path| !known.contains(*path)).cloned());
+                paths.len() - before
+            }
+            _ => return None,
+        };
+        queued.timestamp = envelope.timestamp;
+        Some(added)
+    }
+
+    /// Replace every queued foldable event by one `BulkChange` where the first of them was
+    fn start_bulk(&mut self, at: &EventEnvelope) {
+        let mut bulk = Bulk::default();
+        let mut first = None;
+        let mut kept = VecDeque::with_capacity(self.queue.len());
+        for envelope in self.queue.drain(..) {
+            if foldable(&envelope.event) {
+                bulk.fold(&envelope.event);
+                if first.is_none() {
+                    first = Some((kept.len(), envelope.seq));
+                }
+            } else {
+                kept.push_back(envelope);
+            }
+        }
+        self.queue = kept;
+        self.pending = 0;
+        let envelope = EventEnvelope {
+            version: CATALOG_VERSION,
+            seq: first.map_or(at.seq, |(_, seq)| seq),
+            timestamp: at.timestamp,
+            kind: EventKind::Watcher,
+            event: bulk.event(),
+        };
+        self.bulk = Some(bulk);
+        match first {
+            Some((index, _)) => self.queue.insert(index, envelope),
+            None => self.enqueue(envelope),
+        }
+    }
+
+    fn fold(&mut self, envelope: &EventEnvelope) {
+        let Some(bulk) = &mut self.bulk else {
+            return;
+        };
+        bulk.fold(&envelope.event);
+        let event = bulk.event();
+        if let Some(queued) = self.queue.iter_mut().find(|queued| matches!(queued.event, Event::BulkChange { .. })) {
+            queued.event = event;
+            queued.timestamp = envelope.timestamp;
+        }
+    }
+
+    fn enqueue(&mut self, envelope: EventEnvelope) {
+        // Slow consumers lose the oldest events, never block publishers
+        if self.queue.len() >= self.capacity {
+            if let Some(oldest) = self.queue.pop_front() {
+                self.forget(&oldest);
+            }
+            self.dropped += 1;
+        }
+        self.queue.push_back(envelope);
+    }
+
+    /// Account for an envelope leaving the queue
+    fn forget(&mut self, envelope: &EventEnvelope) {
+        self.pending = self.pending.saturating_sub(weight(&envelope.event));
+        if matches!(envelope.event, Event::BulkChange { .. }) {
+            self.bulk = None;
+        }
+    }
+
+    fn take(&mut self, max: usize) -> EventBatch {
+        let count = max.min(self.queue.len());
+        let events: Vec<EventEnvelope> = self.queue.drain(..count).collect();
+        for envelope in &events {
+            self.forget(envelope);
+        }
+        if self.queue.is_empty() {
+            self.first_queued = None;
+        }
+        EventBatch {
+            events,
+            dropped: std::mem::take(&mut self.dropped),
+        }
+    }
+
+    /// How long to wait for a storm to settle; `None` once it settled or anything else is queued. A queued rename
+    /// flushes too, with the changes coalesced before it
+    fn settle_delay(&self) -> Option<Duration> {
+        let (first, last) = (self.first_queued?, self.last_queued?);
+        let settling = !self.queue.is_empty()
+            && self.queue.iter().all(|queued| match queued.event {
+                Event::FileRenamed { .. } => false,
+                ref event => foldable(event) || matches!(event, Event::BulkChange { .. }),
+            });
+        if !settling {
+            return None;
+        }
+        let wait = SETTLE_QUIET.saturating_sub(last.elapsed()).min(SETTLE_MAX.saturating_sub(first.elapsed()));
+        (!wait.is_zero()).then_some(wait)
+    }
+}
+
+pub struct EventBus {
+    seq: AtomicU64,
+    subscribers: Mutex<HashMap<String, Subscriber>>,
+    notify: Notify,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self {
+            seq: AtomicU64::new(0),
+            subscribers: Mutex::new(HashMap::new()),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Queue an event for every subscriber of its kind
+    pub fn publish(&self, event: Event) {
+        let kind = event.kind();
+        let envelope = EventEnvelope {
+            version: CATALOG_VERSION,
+            seq: self.seq.fetch_add(1, Ordering::Relaxed),
+            timestamp: storage::now_millis(),
+            kind,
+            event,
+        };
+
+        let mut subscribers = self.subscribers.lock().unwrap();
+        for subscriber in subscribers.values_mut() {
+            if !subscriber.kinds.is_empty() && !subscriber.kinds.contains(&kind) {
+                continue;
+            }
+            subscriber.push(envelope.clone());
+        }
+        drop(subscribers);
+        self.notify.notify_waiters();
+    }
+
+    pub fn subscribe(&self, kinds: &[EventKind], capacity: Option<usize>) -> String {
+        let id = storage::new_id("sub");
+        let capacity = capacity.unwrap_or(DEFAULT_CAPACITY).clamp(1, MAX_CAPACITY);
+        self.subscribers.lock().unwrap().insert(id.clone(), Subscriber::new(kinds, capacity));
+        id
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().unwrap().len()
+    }
+
+    pub fn unsubscribe(&self, id: &str) -> bool {
+        let removed = self.subscribers.lock().unwrap().remove(id).is_some();
+        // Wake a pending `next` so it notices the subscription is gone
+        self.notify.notify_waiters();
+        removed
+    }
+
+    /// Take up to `max` queued events without waiting
+    pub fn take(&self, id: &str, max: usize) -> Result<EventBatch> {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        let subscriber = subscribers.get_mut(id).ok_or_else(|| anyhow!("Unknown subscription: {}", id))?;
+        Ok(subscriber.take(max))
+    }
+
+    fn settle_delay(&self, id: &str) -> Result<Option<Duration>> {
+        let subscribers = self.subscribers.lock().unwrap();
+        let subscriber = subscribers.get(id).ok_or_else(|| anyhow!("Unknown subscription: {}", id))?;
+        Ok(subscriber.settle_delay())
+    }
+
+    /// Wait up to `timeout` for events, then take up to `max`; file and diagnostics events are held back briefly
+    /// so a storm arrives as a few coalesced events
+    pub async fn next(&self, id: &str, max: usize, timeout: Duration) -> Result<EventBatch> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Created before checking so a publish in between still wakes us
+            let notified = self.notify.notified();
+            if let Some(wait) = self.settle_delay(id)? {
+                if tokio::time::Instant::now() + wait < deadline {
+                    let _ = tokio::time::timeout(wait, notified).await;
+                    continue;
+                }
+            }
+            let batch = self.take(id, max)?;
+            if !batch.events.is_empty() || batch.dropped > 0 {
+                return Ok(batch);
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return Ok(batch);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filtered_bounded_subscriptions() {
+        let bus = EventBus::new();
+        // Task events never fold, so a full queue drops them
+        let tasks = bus.subscribe(&[EventKind::Tasks], Some(2));
+        let all = bus.subscribe(&[], None);
+
+        for i in 0..3 {
+            bus.publish(Event::TaskStarted {
+                task_id: i.to_string(),
+                command: "npm test".to_string(),
+            });
+        }
+        bus.publish(Event::FilesChanged { paths: Vec::new() });
+
+        let batch = bus.take(&tasks, 10).unwrap();
+        assert_eq!((batch.events.len(), batch.dropped), (2, 1));
+        assert_eq!(batch.events[0].seq, 1);
+        assert_eq!(bus.take(&all, 10).unwrap().events.len(), 4);
+        assert!(bus.unsubscribe(&all));
+        assert!(bus.take(&all, 10).is_err());
+    }
+
+    #[test]
+    fn test_coalesces_updates_and_folds_storms() {
+        let bus = EventBus::new();
+        let id = bus.subscribe(&[], None);
+        let diagnostics = |file: &str, count| Event::DiagnosticsChanged {
+            file: file.to_string(),
+            count,
+        };
+        let files = |paths: &[&str]| Event::FilesChanged {
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+        };
+        bus.publish(diagnostics("a.rs", 1));
+        bus.publish(files(&["a.rs"]));
+        bus.publish(diagnostics("a.rs", 3));
+        bus.publish(files(&["a.rs", "b.rs"]));
+        let batch = bus.take(&id, 10).unwrap();
+        assert_eq!(batch.events.len(), 2);
+        assert!(matches!(batch.events[0].event, Event::DiagnosticsChanged { count: 3, .. }));
+        assert!(matches!(&batch.events[1].event, Event::FilesChanged { paths } if paths.len() == 2));
+
+        bus.publish(files(&["a.rs"]));
+        bus.publish(Event::FileRenamed {
+            from: "a.rs".to_string(),
+            to: "b.rs".to_string(),
+            dependents: Vec::new(),
+        });
+        bus.publish(files(&["b.rs"]));
+        let batch = bus.take(&id, 10).unwrap();
+        assert_eq!(batch.events.len(), 3);
+        assert!(matches!(batch.events[1].event, Event::FileRenamed { .. }));
+
+        bus.publish(Event::TaskStarted {
+            task_id: "t".to_string(),
+            command: "git checkout main".to_string(),
+        });
+        for i in 0..1000 {
+            bus.publish(diagnostics(&format!("{}.rs", i), 0));
+        }
+        bus.publish(files(&["x.rs", "y.rs"]));
+        bus.publish(Event::GitStatusChanged { paths: Vec::new() });
+        let batch = bus.take(&id, 10).unwrap();
+        assert_eq!((batch.events.len(), batch.dropped), (2, 0));
+        match &batch.events[1].event {
+            Event::BulkChange {
+                files,
+                diagnostics,
+                git_status_changed,
+                sample,
+                ..
+            } => {
+                assert_eq!((*files, *diagnostics, *git_status_changed), (2, 1000, true));
+                assert_eq!(sample, &["x.rs", "y.rs"]);
+            }
+            other => panic!("expected a bulk change, got {:?}", other),
+        }
+
+        bus.publish(diagnostics("a.rs", 1));
+        assert!(matches!(bus.take(&id, 10).unwrap().events[0].event, Event::DiagnosticsChanged { .. }));
+    }
+}
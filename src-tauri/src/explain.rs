@@ -0,0 +1,185 @@
+// Explain Diagnostic - Plain-language explanations for analyzer findings
+// Bridges the diagnostics store and the AI layer
+
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ai_provider::{self, AiProvider, ChatMessage, CompletionRequest};
+use crate::changeset::{self, Changeset, FileChange, FilePreview, Position, TextEdit};
+use crate::code_context::SymbolSpan;
+use crate::diagnostics::Diagnostic;
+use crate::syntax::{self, SyntaxCheck};
+use crate::tokens::ContextBudget;
+
+/// Lines shown on each side of the offending line
+const REGION_LINES: usize = 12;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProposedFix {
+    pub changeset: Changeset,
+    pub preview: Vec<FilePreview>,
+    pub syntax: SyntaxCheck,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DiagnosticExplanation {
+    pub diagnostic: Diagnostic,
+    pub explanation: String,
+    pub fix: Option<ProposedFix>,
+}
+
+#[derive(Deserialize)]
+struct ExplainResponse {
+    explanation: String,
+    #[serde(default)]
+    fix: Option<FixResponse>,
+}
+
+#[derive(Deserialize)]
+struct FixResponse {
+    /// 1-based inclusive line range to replace
+    start_line: usize,
+    end_line: usize,
+    replacement: String,
+}
+
+/// Numbered source lines around `line`
+fn region(content: &str, line: usize) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let first = line.saturating_sub(REGION_LINES).max(1);
+    let last = (line + REGION_LINES).min(lines.len());
+    (first..=last)
+        .map(|n| format!("{:>5} | {}", n, lines.get(n - 1).unwrap_or(&"")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn build_prompt(
+    diagnostic: &Diagnostic,
+    content: &str,
+    related: &[(String, SymbolSpan)],
+    model: &str,
+) -> Vec<ChatMessage> {
+    let system = "You explain code problems to developers in plain language. \
+        Say what is wrong, why it matters and how to fix it, in a few sentences. \
+        If a safe local fix exists, include it. Reply with JSON only: \
+        {\"explanation\": string, \"fix\": {\"start_line\": number, \"end_line\": number, \
+        \"replacement\": string} | null}. Line numbers refer to the numbered code; \
+        the replacement substitutes those whole lines.";
+    let mut budget = ContextBudget::for_model(model);
+    budget.try_add(system);
+
+    let code = region(content, diagnostic.line);
+    let mut user = format!(
+        "File: {}\nProblem ({} {}, from {}): {} at line {}, column {}\n",
+        diagnostic.file,
+        diagnostic.severity,
+        diagnostic.kind,
+        diagnostic.source,
+        diagnostic.message,
+        diagnostic.line,
+        diagnostic.column
+    );
+    if let Some(fix) = &diagnostic.fix {
+        user.push_str(&format!("Suggested fix from the analyzer: {}\n", fix));
+    }
+    user.push_str(&format!("\nCode:\n```\n{}\n```\n", budget.add_truncated(&code)));
+
+    if !related.is_empty() {
+        user.push_str("\nRelated symbols:\n");
+        for (file, span) in related {
+            let entry = format!("- `{}` ({}:{}): `{}`\n", span.name, file, span.start_line, span.signature);
+            if !budget.try_add(&entry) {
+                break;
+            }
+            user.push_str(&entry);
+        }
+    }
+
+    vec![ChatMessage::system(system), ChatMessage::user(user)]
+}
+
+fn fix_changeset(
+    workspace: &Path,
+    diagnostic: &Diagnostic,
+    content: &str,
+    fix: FixResponse,
+) -> Result<ProposedFix> {
+    let line_count = content.lines().count();
+    if fix.start_line == 0 || fix.end_line < fix.start_line || fix.end_line > line_count {
+        return Err(anyhow!("Fix range {}-{} is out of bounds", fix.start_line, fix.end_line));
+    }
+
+    let mut replacement = fix.replacement;
+    if !replacement.ends_with('\n') {
+        replacement.push('\n');
+    }
+    let edit = TextEdit {
+        start: Position { line: fix.start_line, column: 0 },
+        end: Position { line: fix.end_line + 1, column: 0 },
+        new_text: replacement,
+    };
+    let updated = changeset::apply_edits(content, std::slice::from_ref(&edit))?;
+
+    let changeset = Changeset::new(
+        format!("Fix: {}", diagnostic.message),
+        vec![FileChange::Edit {
+            path: diagnostic.file.clone(),
+            edits: vec![edit],
+        }],
+    );
+    Ok(ProposedFix {
+        preview: changeset.preview(workspace)?,
+        syntax: syntax::check(&diagnostic.file, &updated),
+        changeset,
+    })
+}
+
+/// Explain `diagnostic` and propose an optional fix
+pub async fn explain(
+    provider: &dyn AiProvider,
+    workspace: &Path,
+    diagnostic: Diagnostic,
+    content: &str,
+    related: &[(String, SymbolSpan)],
+) -> Result<DiagnosticExplanation> {
+    let request = CompletionRequest::new(build_prompt(&diagnostic, content, related, provider.model())).json();
+    let response = provider.complete(&request).await?;
+
+    let parsed = match ai_provider::extract_json(&response).map(serde_json::from_str::<ExplainResponse>) {
+        Some(Ok(parsed)) => parsed,
+        // Not JSON: treat the whole answer as the explanation
+        _ => ExplainResponse {
+            explanation: response.trim().to_string(),
+            fix: None,
+        },
+    };
+
+    let fix = match parsed.fix {
+        Some(fix) => match fix_changeset(workspace, &diagnostic, content, fix) {
+            Ok(fix) => Some(fix),
+            Err(e) => {
+                log::warn!("Discarding proposed fix for {}: {}", diagnostic.id, e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    Ok(DiagnosticExplanation {
+        diagnostic,
+        explanation: parsed.explanation,
+        fix,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_is_numbered_and_clamped() {
+        assert_eq!(region("a\nb\nc\n", 2), "    1 | a\n    2 | b\n    3 | c");
+    }
+}
@@ -0,0 +1,192 @@
+// File Access - Workspace confinement and read-only or locked file detection before writes
+// File commands report these as typed errors the frontend can offer to fix
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{changeset, paths, storage};
+
+#[derive(Error, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AccessError {
+    /// Cleared by `make_writable`
+    #[error("{path} is read-only")]
+    FileReadOnly { path: String },
+    /// Opened exclusively by another process
+    #[error("{path} is locked by another process")]
+    FileLocked { path: String },
+    /// Resolves, through symlinks and `..`, outside the workspace and the allowed paths
+    #[error("{path} is outside the workspace")]
+    OutsideWorkspace { path: String },
+    /// Inside the workspace's engine data, which holds the audit log and is written by the engine alone
+    #[error("{path} is engine data")]
+    EngineData { path: String },
+}
+
+/// Canonical form of a path that may not exist yet: its nearest existing ancestor canonicalized, with the
+/// missing names appended
+fn canonical(path: &Path) -> Option<PathBuf> {
+    let mut missing = Vec::new();
+    let mut current = path;
+    loop {
+        if let Ok(canonical) = current.canonicalize() {
+            return Some(missing.iter().rev().fold(canonical, |path, name| path.join(name)));
+        }
+        // `file_name` is `None` for a trailing `..`, which can't be resolved without the directory
+        missing.push(current.file_name()?);
+        current = current.parent()?;
+    }
+}
+
+/// `path` resolved against the workspace, refused unless it stays inside the workspace or one of `allowed`;
+/// without a workspace only absolute paths under `allowed` pass
+pub fn confine(workspace: Option<&Path>, allowed: &[String], path: &str) -> Result<PathBuf, AccessError> {
+    let outside = || AccessError::OutsideWorkspace {
+        path: path.to_string(),
+    };
+    let resolved = match workspace {
+        Some(workspace) => changeset::resolve(workspace, path),
+        None => paths::native(path),
+    };
+    if !resolved.is_absolute() {
+        return Err(outside());
+    }
+    let target = canonical(&resolved).ok_or_else(outside)?;
+    let inside = workspace
+        .map(Path::to_path_buf)
+        .into_iter()
+        .chain(allowed.iter().map(|root| paths::native(root)))
+        .filter_map(|root| root.canonicalize().ok())
+        .any(|root| target.starts_with(root));
+    if inside {
+        Ok(resolved)
+    } else {
+        Err(outside())
+    }
+}
+
+/// `confine` for writes, which may not touch the workspace's engine data either
+pub fn confine_write(workspace: &Path, path: &str) -> Result<PathBuf, AccessError> {
+    let resolved = confine(Some(workspace), &[], path)?;
+    let data_dir = canonical(&storage::data_dir(workspace));
+    if data_dir.is_some_and(|dir| canonical(&resolved).is_some_and(|target| target.starts_with(dir))) {
+        return Err(AccessError::EngineData {
+            path: path.to_string(),
+        });
+    }
+    Ok(resolved)
+}
+
+/// Windows reports files opened without write sharing as a sharing or lock violation
+#[cfg(windows)]
+fn is_locked(path: &Path) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    match fs::OpenOptions::new().write(true).open(path) {
+        Ok(_) => false,
+        Err(e) => matches!(e.raw_os_error(), Some(ERROR_SHARING_VIOLATION) | Some(ERROR_LOCK_VIOLATION)),
+    }
+}
+
+/// Advisory locks on Unix do not prevent writes
+#[cfg(not(windows))]
+fn is_locked(_path: &Path) -> bool {
+    false
+}
+
+/// Why `path` cannot be written, if it exists and cannot; `display` names it in the error
+pub fn check(path: &Path, display: &str) -> Result<(), AccessError> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return Ok(()),
+    };
+    if metadata.permissions().readonly() {
+        return Err(AccessError::FileReadOnly {
+            path: display.to_string(),
+        });
+    }
+    if metadata.is_file() && is_locked(path) {
+        return Err(AccessError::FileLocked {
+            path: display.to_string(),
+        });
+    }
+    Ok(())
+}
+
+pub fn is_writable(path: &Path) -> bool {
+    check(path, "").is_ok()
+}
+
+/// Clear the read-only flag; on Unix only the owner gains write permission
+pub fn make_writable(path: &Path) -> Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.set_mode(permissions.mode() | 0o200);
+    }
+    #[cfg(not(unix))]
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(path, permissions)?;
+    Ok(())
+}
+
+/// Command error text: typed access errors as JSON, anything else as its message
+pub fn command_error(error: anyhow::Error) -> String {
+    match error.downcast_ref::<AccessError>() {
+        Some(access) => serde_json::to_string(access).unwrap_or_else(|_| access.to_string()),
+        None => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_only_detection_and_fix() {
+        let file = std::env::temp_dir().join(crate::storage::new_id("read-only-test"));
+        fs::write(&file, "locked").unwrap();
+        let mut permissions = fs::metadata(&file).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&file, permissions).unwrap();
+
+        let error = check(&file, "f.txt").unwrap_err();
+        assert_eq!(error, AccessError::FileReadOnly { path: "f.txt".to_string() });
+        assert_eq!(command_error(error.into()), r#"{"type":"file_read_only","path":"f.txt"}"#);
+        make_writable(&file).unwrap();
+        assert!(is_writable(&file));
+        let _ = fs::remove_file(&file);
+    }
+
+    #[test]
+    fn test_confine_resolves_before_checking() {
+        let root = std::env::temp_dir().join(crate::storage::new_id("confine-test"));
+        let workspace = root.join("workspace");
+        let shared = root.join("shared");
+        fs::create_dir_all(workspace.join("src")).unwrap();
+        fs::create_dir_all(&shared).unwrap();
+
+        assert!(confine(Some(&workspace), &[], "src/new/main.rs").is_ok());
+        let escape = confine(Some(&workspace), &[], "src/../../shared/notes.txt").unwrap_err();
+        assert!(matches!(escape, AccessError::OutsideWorkspace { .. }));
+        let absolute = shared.join("notes.txt").to_string_lossy().to_string();
+        assert!(confine(Some(&workspace), &[], &absolute).is_err());
+        let allowed = [shared.to_string_lossy().to_string()];
+        assert!(confine(Some(&workspace), &allowed, &absolute).is_ok());
+        assert!(confine(None, &allowed, "notes.txt").is_err());
+        assert!(confine_write(&workspace, "src/main.rs").is_ok());
+        let audit_log = confine_write(&workspace, "src/../.mimiverse/audit.jsonl").unwrap_err();
+        assert!(matches!(audit_log, AccessError::EngineData { .. }));
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&shared, workspace.join("link")).unwrap();
+            assert!(confine(Some(&workspace), &[], "link/notes.txt").is_err());
+        }
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -1,27 +1,105 @@
 // File Indexer - Fast parallel file indexing for workspace search
 // Optimized for large codebases using Rayon
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::fs;
 use anyhow::Result;
 use rayon::prelude::*;
 use walkdir::WalkDir;
 use sha2::{Sha256, Digest};
-
-use crate::FileMatch;
+use serde::{Deserialize, Serialize};
+
+use crate::asset_metadata::{self, AssetMetadata};
+use crate::file_access;
+use crate::generated::{self, GeneratedReason};
+use crate::paths::{self, CanonicalPath};
+use crate::sloc::{self, LineCounts};
+use crate::sparse;
+use crate::stats;
+use crate::{FileMatch, MatchRange};
+
+/// Score added to matching files of the working set
+const PINNED_BOOST: f64 = 20.0;
+
+/// Top-level files describing the project, indexed ahead of the rest of the workspace
+const MANIFESTS: &[&str] = &[
+    "package.json",
+    "tsconfig.json",
+    "Cargo.toml",
+    "pyproject.toml",
+    "requirements.txt",
+    "go.mod",
+    "pom.xml",
+    "build.gradle",
+    "Makefile",
+    "Dockerfile",
+    "docker-compose.yml",
+    "README.md",
+];
 
 /// File index for fast workspace search
 pub struct FileIndex {
     /// Map from file path to file info
-    files: HashMap<String, FileInfo>,
-    /// Inverted index for content search
+    files: HashMap<CanonicalPath, FileInfo>,
+    /// Inverted index for content search: lowercase word or identifier sub-token -> files
     content_index: HashMap<String, Vec<String>>,
-    /// Total lines of code
-    total_lines: usize,
+    /// Aggregates kept up to date as files are added and removed
+    totals: IndexTotals,
+    /// Extra globs of generated files, on top of the built-in heuristics
+    generated_patterns: Vec<String>,
+    /// Index the content of generated files like any other file
+    index_generated: bool,
+    /// Working-set files, boosted in search results
+    pinned: HashSet<CanonicalPath>,
+    /// Workspace-relative directories indexed fully in sparse mode; `None` indexes everything
+    sparse: Option<Vec<String>>,
+}
+
+/// Workspace-wide aggregates; generated files count only towards `files` and `generated_files`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct IndexTotals {
+    pub files: usize,
+    pub lines: usize,
+    pub generated_files: usize,
+    pub by_language: HashMap<String, usize>,
+    pub line_counts_by_language: HashMap<String, LineCounts>,
+}
+
+impl IndexTotals {
+    fn add(&mut self, info: &FileInfo) {
+        self.files += 1;
+        if info.generated.is_some() {
+            self.generated_files += 1;
+            return;
+        }
+        self.lines += info.lines;
+        *self.by_language.entry(info.language.clone()).or_insert(0) += 1;
+        *self.line_counts_by_language.entry(info.language.clone()).or_default() += info.line_counts;
+    }
+
+    fn remove(&mut self, info: &FileInfo) {
+        self.files = self.files.saturating_sub(1);
+        if info.generated.is_some() {
+            self.generated_files = self.generated_files.saturating_sub(1);
+            return;
+        }
+        self.lines = self.lines.saturating_sub(info.lines);
+        if let Some(count) = self.by_language.get_mut(&info.language) {
+            *count -= 1;
+            if *count == 0 {
+                self.by_language.remove(&info.language);
+                self.line_counts_by_language.remove(&info.language);
+                return;
+            }
+        }
+        if let Some(counts) = self.line_counts_by_language.get_mut(&info.language) {
+            *counts -= info.line_counts;
+        }
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct FileInfo {
     pub path: String,
     pub name: String,
@@ -30,6 +108,62 @@ pub struct FileInfo {
     pub lines: usize,
     pub hash: String,
     pub language: String,
+    /// Image/font/audio metadata for binary assets
+    pub asset: Option<AssetMetadata>,
+    /// Set for minified or generated files, whose content is not indexed
+    #[serde(default)]
+    pub generated: Option<GeneratedReason>,
+    /// Path of the byte-identical file whose indexed content this file shares
+    #[serde(default)]
+    pub duplicate_of: Option<String>,
+    /// Estimated cyclomatic complexity, for text files that are not generated
+    #[serde(default)]
+    pub complexity: Option<usize>,
+    /// Code, comment and blank lines
+    #[serde(default)]
+    pub line_counts: LineCounts,
+    /// False for read-only files and files locked by another process
+    #[serde(default = "writable_by_default")]
+    pub is_writable: bool,
+    /// Outside the sparse index: only name, size and modification time were read
+    #[serde(default)]
+    pub shallow: bool,
+}
+
+fn writable_by_default() -> bool {
+    true
+}
+
+/// A deleted and a created file with identical content, taken to be one file that moved
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FileRename {
+    pub from: String,
+    pub to: String,
+}
+
+/// Byte-identical files; the first path holds the indexed content
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DuplicateCluster {
+    pub hash: String,
+    pub size: u64,
+    pub paths: Vec<String>,
+    /// Bytes taken by the copies beyond the first
+    pub wasted_bytes: u64,
+}
+
+/// Pair deleted and created files by content hash; several files with one hash pair up in path order
+fn pair_renames(mut deleted: Vec<(String, String)>, mut created: Vec<(String, String)>) -> Vec<FileRename> {
+    deleted.sort();
+    created.sort();
+    let mut renames = Vec::new();
+    let mut created = created.into_iter().peekable();
+    for (hash, from) in deleted {
+        while created.next_if(|(created_hash, _)| *created_hash < hash).is_some() {}
+        if let Some((_, to)) = created.next_if(|(created_hash, _)| *created_hash == hash) {
+            renames.push(FileRename { from, to });
+        }
+    }
+    renames
 }
 
 impl FileIndex {
@@ -37,10 +171,30 @@ impl FileIndex {
         Self {
             files: HashMap::new(),
             content_index: HashMap::new(),
-            total_lines: 0,
+            totals: IndexTotals::default(),
+            generated_patterns: Vec::new(),
+            index_generated: false,
+            pinned: HashSet::new(),
+            sparse: None,
         }
     }
 
+    /// Configure generated-file handling for the next indexing run
+    pub fn configure_generated(&mut self, patterns: Vec<String>, index_generated: bool) {
+        self.generated_patterns = patterns;
+        self.index_generated = index_generated;
+    }
+
+    /// Restrict full indexing to `directories` and their top-level files, or lift the restriction with `None`
+    pub fn configure_sparse(&mut self, directories: Option<Vec<String>>) {
+        self.sparse = directories;
+    }
+
+    /// Replace the set of pinned (working-set) files
+    pub fn set_pinned(&mut self, paths: &[PathBuf]) {
+        self.pinned = paths.iter().map(CanonicalPath::new).collect();
+    }
+
     /// Index all files in directory
     pub fn index_directory(&mut self, dir: &Path) -> Result<()> {
         log::info!("Indexing directory: {:?}", dir);
@@ -49,54 +203,129 @@ impl FileIndex {
         let files: Vec<PathBuf> = WalkDir::new(dir)
             .into_iter()
             .filter_map(|e| e.ok())
-            .filter(|e| {
-                e.file_type().is_file()
-                    && !e.path().to_string_lossy().contains("node_modules")
-                    && !e.path().to_string_lossy().contains(".git")
-                    && !e.path().to_string_lossy().contains("target")
-            })
+            .filter(|e| e.file_type().is_file() && !Self::is_excluded(e.path()))
             .map(|e| e.path().to_path_buf())
             .collect();
 
         log::info!("Found {} files to index", files.len());
 
         // Index files in parallel
-        let indexed: Vec<FileInfo> = files
+        let mut indexed: Vec<(FileInfo, HashSet<String>)> = files
             .par_iter()
-            .filter_map(|path| self.index_file(path).ok())
+            .filter_map(|path| self.index_file(dir, path).ok())
             .collect();
 
-        // Store in index
-        self.total_lines = 0;
-        for info in indexed {
-            self.total_lines += info.lines;
-            
-            // Build content index (words -> files)
-            let words = self.extract_words(&info.name);
-            for word in words {
-                self.content_index
-                    .entry(word.to_lowercase())
-                    .or_insert_with(Vec::new)
-                    .push(info.path.clone());
+        // Identical content is indexed once, under the first path in sort order
+        indexed.sort_by(|a, b| a.0.path.cmp(&b.0.path));
+        let mut canonical: HashMap<String, String> = HashMap::new();
+        for (info, words) in indexed.iter_mut() {
+            if info.size == 0 || info.shallow {
+                continue;
             }
+            match canonical.get(&info.hash) {
+                Some(original) => {
+                    info.duplicate_of = Some(original.clone());
+                    *words = self.extract_words(&info.name).into_iter().collect();
+                }
+                None => {
+                    canonical.insert(info.hash.clone(), info.path.clone());
+                }
+            }
+        }
 
-            self.files.insert(info.path.clone(), info);
+        // Store in index
+        self.files.clear();
+        self.content_index.clear();
+        self.totals = IndexTotals::default();
+        for (info, words) in indexed {
+            self.insert(info, words);
         }
 
         log::info!(
             "Indexed {} files, {} lines total",
             self.files.len(),
-            self.total_lines
+            self.totals.lines
         );
 
         Ok(())
     }
 
-    /// Index a single file
-    fn index_file(&self, path: &Path) -> Result<FileInfo> {
-        let metadata = fs::metadata(path)?;
-        let content = fs::read_to_string(path).unwrap_or_default();
-        
+    /// Directories never indexed: dependencies, build output, VCS and engine data
+    pub fn is_excluded(path: &Path) -> bool {
+        let path = path.to_string_lossy();
+        path.contains("node_modules")
+            || path.contains(".git")
+            || path.contains(crate::storage::DATA_DIR)
+            || path.contains("target")
+    }
+
+    fn insert(&mut self, info: FileInfo, words: HashSet<String>) {
+        self.totals.add(&info);
+        // Build content index (words -> files)
+        for word in words {
+            self.content_index
+                .entry(word)
+                .or_insert_with(Vec::new)
+                .push(info.path.clone());
+        }
+        self.files.insert(CanonicalPath::new(&info.path), info);
+    }
+
+    /// Re-index changed, created or deleted files without walking the workspace, returning the detected renames.
+    /// Copies of a changed file are re-indexed with their own content.
+    pub fn update_files(&mut self, root: &Path, paths: &[PathBuf]) -> Vec<FileRename> {
+        let mut stale: HashSet<CanonicalPath> = paths.iter().map(CanonicalPath::new).collect();
+        let copies: Vec<CanonicalPath> = self
+            .files
+            .values()
+            .filter(|info| {
+                let original = info.duplicate_of.as_ref();
+                original.is_some_and(|original| stale.contains(&CanonicalPath::new(original)))
+            })
+            .map(|info| CanonicalPath::new(&info.path))
+            .collect();
+        stale.extend(copies);
+
+        // The content index lists files under the path they were indexed with
+        let mut removed: HashSet<String> = HashSet::new();
+        let mut deleted: Vec<(String, String)> = Vec::new();
+        for path in &stale {
+            if let Some(info) = self.files.remove(path) {
+                self.totals.remove(&info);
+                if info.size > 0 && !Path::new(&info.path).exists() {
+                    deleted.push((info.hash.clone(), info.path.clone()));
+                }
+                removed.insert(info.path);
+            }
+        }
+        self.content_index.retain(|_, files| {
+            files.retain(|file| !removed.contains(file));
+            !files.is_empty()
+        });
+
+        let mut created: Vec<(String, String)> = Vec::new();
+        for path in &stale {
+            let path = Path::new(path.as_str());
+            if !path.is_file() || Self::is_excluded(path) {
+                continue;
+            }
+            match self.index_file(root, path) {
+                Ok((info, words)) => {
+                    if info.size > 0 && !removed.contains(&info.path) {
+                        created.push((info.hash.clone(), info.path.clone()));
+                    }
+                    self.insert(info, words);
+                }
+                Err(e) => log::warn!("Failed to index {:?}: {}", path, e),
+            }
+        }
+        pair_renames(deleted, created)
+    }
+
+    /// Index a single file, returning its info and the words of its name and content
+    fn index_file(&self, root: &Path, path: &Path) -> Result<(FileInfo, HashSet<String>)> {
+        let metadata = fs::metadata(paths::for_io(path))?;
+
         let name = path
             .file_name()
             .map(|n| n.to_string_lossy().to_string())
@@ -107,15 +336,67 @@ impl FileIndex {
             .map(|e| e.to_string_lossy().to_string())
             .unwrap_or_default();
 
-        let language = self.detect_language(&extension);
+        let relative = path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace('\\', "/");
+        if self.sparse.as_ref().is_some_and(|directories| !sparse::includes(directories, &relative)) {
+            // The modification time stands in for the content hash, so copies are never matched
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map_or(0, |since| since.as_millis());
+            let info = FileInfo {
+                path: path.to_string_lossy().to_string(),
+                name: name.clone(),
+                language: Self::detect_language(&extension),
+                extension,
+                size: metadata.len(),
+                lines: 0,
+                hash: format!("shallow-{}-{}", metadata.len(), modified),
+                asset: None,
+                generated: None,
+                duplicate_of: None,
+                complexity: None,
+                line_counts: LineCounts::default(),
+                is_writable: !metadata.permissions().readonly(),
+                shallow: true,
+            };
+            return Ok((info, self.extract_words(&name).into_iter().collect()));
+        }
+
+        // Binary assets are hashed as bytes and described by their headers
+        let is_asset = asset_metadata::is_asset(&extension);
+        let bytes = fs::read(paths::for_io(path))?;
+        let content = if is_asset { "" } else { std::str::from_utf8(&bytes).unwrap_or("") };
+        let asset = if is_asset {
+            asset_metadata::extract(path).unwrap_or_else(|e| {
+                log::debug!("No asset metadata for {:?}: {}", path, e);
+                None
+            })
+        } else {
+            None
+        };
+
+        let language = Self::detect_language(&extension);
         let lines = content.lines().count();
+        let line_counts = sloc::count(content, &language);
         
         // Compute hash for change detection
         let mut hasher = Sha256::new();
-        hasher.update(content.as_bytes());
+        hasher.update(&bytes);
         let hash = hex::encode(hasher.finalize());
 
-        Ok(FileInfo {
+        let generated = if is_asset { None } else { generated::classify(&relative, content, &self.generated_patterns) };
+
+        // Generated files stay findable by name only
+        let mut words: HashSet<String> = self.extract_words(&name).into_iter().collect();
+        if generated.is_none() || self.index_generated {
+            words.extend(self.extract_words(content));
+        }
+
+        let complexity = (!is_asset && generated.is_none() && !content.is_empty())
+            .then(|| stats::estimate_complexity(content));
+
+        let info = FileInfo {
             path: path.to_string_lossy().to_string(),
             name,
             extension,
@@ -123,11 +404,19 @@ impl FileIndex {
             lines,
             hash,
             language,
-        })
+            asset,
+            generated,
+            duplicate_of: None,
+            complexity,
+            line_counts,
+            is_writable: file_access::is_writable(path),
+            shallow: false,
+        };
+        Ok((info, words))
     }
 
     /// Detect language from extension
-    fn detect_language(&self, ext: &str) -> String {
+    pub fn detect_language(ext: &str) -> String {
         match ext {
             "ts" | "tsx" => "TypeScript".to_string(),
             "js" | "jsx" => "JavaScript".to_string(),
@@ -148,22 +437,57 @@ impl FileIndex {
         }
     }
 
-    /// Extract searchable words from text
+    /// Extract searchable words from text: whole identifiers plus their sub-tokens, lowercased
     fn extract_words(&self, text: &str) -> Vec<String> {
-        text.split(|c: char| !c.is_alphanumeric() && c != '_')
-            .filter(|w| w.len() >= 2)
-            .map(|w| w.to_string())
-            .collect()
+        let mut words = Vec::new();
+        for identifier in text.split(|c: char| !c.is_alphanumeric() && c != '_') {
+            if identifier.len() < 2 || identifier.chars().all(|c| c.is_ascii_digit()) {
+                continue;
+            }
+            words.push(identifier.to_lowercase());
+            let tokens = split_identifier(identifier);
+            if tokens.len() > 1 {
+                words.extend(tokens.into_iter().filter(|t| t.len() >= 2));
+            }
+        }
+        words
+    }
+
+    /// Files containing every word, by whole identifier or sub-token
+    fn files_with_words(&self, words: &[String]) -> HashSet<&String> {
+        let mut sets = words
+            .iter()
+            .map(|word| self.content_index.get(word).map(|files| files.iter().collect::<HashSet<_>>()).unwrap_or_default());
+        let first = sets.next().unwrap_or_default();
+        sets.fold(first, |acc, set| acc.intersection(&set).copied().collect())
+    }
+
+    /// Files whose content has `identifier` as a whole identifier or sub-token
+    pub fn files_containing(&self, identifier: &str) -> Vec<String> {
+        self.content_index.get(&identifier.to_lowercase()).cloned().unwrap_or_default()
     }
 
     /// Fuzzy search files
     pub fn search(&self, query: &str) -> Vec<FileMatch> {
+        self.search_within(query, 50, |_| true)
+    }
+
+    /// Fuzzy search the files accepted by `filter`
+    pub fn search_within(&self, query: &str, limit: usize, filter: impl Fn(&FileInfo) -> bool) -> Vec<FileMatch> {
         let query_lower = query.to_lowercase();
         let query_words: Vec<&str> = query_lower.split_whitespace().collect();
+        // "user id" and "userId" both look for the tokens user + id
+        let query_tokens: Vec<String> = query
+            .split_whitespace()
+            .flat_map(split_identifier)
+            .filter(|t| t.len() >= 2)
+            .collect();
+        let in_content = self.files_with_words(&query_tokens);
 
         let mut results: Vec<FileMatch> = self
             .files
             .values()
+            .filter(|info| filter(info))
             .filter_map(|info| {
                 let name_lower = info.name.to_lowercase();
                 let path_lower = info.path.to_lowercase();
@@ -193,6 +517,15 @@ impl FileIndex {
                             score += 5.0;
                         }
                     }
+                    // Identifiers in the file (or the copy holding its content) cover every query token
+                    let content_path = info.duplicate_of.as_ref().unwrap_or(&info.path);
+                    if in_content.contains(content_path) {
+                        score += 3.0 * query_tokens.len() as f64;
+                    }
+                }
+
+                if score > 0.0 && self.pinned.contains(&CanonicalPath::new(&info.path)) {
+                    score += PINNED_BOOST;
                 }
 
                 if score > 0.0 {
@@ -202,6 +535,9 @@ impl FileIndex {
                         line: None,
                         snippet: None,
                         score: score as f32,
+                        path_ranges: highlight(&info.path, &query_lower, &query_words),
+                        name_ranges: highlight(&info.name, &query_lower, &query_words),
+                        snippet_ranges: Vec::new(),
                     })
                 } else {
                     None
@@ -211,39 +547,298 @@ impl FileIndex {
 
         // Sort by score
         results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
-        results.truncate(50); // Limit results
+        results.truncate(limit);
 
         results
     }
 
+    /// Files accepted by `include` with the words each is found by, for persisting the index
+    pub fn entries(&self, include: impl Fn(&str) -> bool) -> Vec<(FileInfo, Vec<String>)> {
+        let mut words: HashMap<&str, Vec<String>> = HashMap::new();
+        for (word, files) in &self.content_index {
+            for file in files.iter().filter(|file| include(file.as_str())) {
+                words.entry(file.as_str()).or_default().push(word.clone());
+            }
+        }
+        self.files
+            .values()
+            .filter(|info| include(&info.path))
+            .map(|info| {
+                let mut file_words = words.remove(info.path.as_str()).unwrap_or_default();
+                file_words.sort();
+                (info.clone(), file_words)
+            })
+            .collect()
+    }
+
+    /// Index of persisted entries, as returned by `entries`
+    pub fn from_entries(entries: Vec<(FileInfo, Vec<String>)>) -> Self {
+        let mut index = Self::new();
+        for (info, words) in entries {
+            index.insert(info, words.into_iter().collect());
+        }
+        index
+    }
+
+    /// Get indexed info for a file
+    pub fn get(&self, path: &str) -> Option<&FileInfo> {
+        self.files.get(&CanonicalPath::new(path))
+    }
+
+    /// All indexed files
+    pub fn files(&self) -> impl Iterator<Item = &FileInfo> {
+        self.files.values()
+    }
+
+    /// Paths of all indexed files
+    pub fn paths(&self) -> impl Iterator<Item = &String> {
+        self.files.values().map(|info| &info.path)
+    }
+
     /// Get file count
     pub fn file_count(&self) -> usize {
         self.files.len()
     }
 
+    /// Distinct words and identifier tokens in the content index
+    pub fn indexed_word_count(&self) -> usize {
+        self.content_index.len()
+    }
+
+    /// Rough heap size of the index, for memory budgets
+    pub fn approximate_bytes(&self) -> usize {
+        let files: usize =
+            self.files.values().map(|info| 2 * info.path.len() + info.name.len() + info.hash.len() + 160).sum();
+        let words: usize = self
+            .content_index
+            .iter()
+            .map(|(word, files)| word.len() + 48 + files.iter().map(|file| file.len() + 24).sum::<usize>())
+            .sum();
+        files + words
+    }
+
     /// Get total lines
     pub fn total_lines(&self) -> usize {
-        self.total_lines
+        self.totals.lines
+    }
+
+    pub fn totals(&self) -> &IndexTotals {
+        &self.totals
     }
 
-    /// Get files by language
+    /// Get files by language, excluding generated files
     pub fn files_by_language(&self) -> HashMap<String, usize> {
-        let mut by_lang: HashMap<String, usize> = HashMap::new();
-        for info in self.files.values() {
-            *by_lang.entry(info.language.clone()).or_insert(0) += 1;
+        self.totals.by_language.clone()
+    }
+
+    /// Code, comment and blank lines per language, excluding generated files
+    pub fn line_counts_by_language(&self) -> HashMap<String, LineCounts> {
+        self.totals.line_counts_by_language.clone()
+    }
+
+    /// Files outside the sparse index, and files classified as generated unless their content is indexed anyway
+    pub fn excluded_from_analysis(&self) -> HashSet<String> {
+        self.files
+            .values()
+            .filter(|info| info.shallow || (info.generated.is_some() && !self.index_generated))
+            .map(|info| info.path.clone())
+            .collect()
+    }
+
+    pub fn generated_count(&self) -> usize {
+        self.totals.generated_files
+    }
+
+    /// Clusters of byte-identical non-empty files, most wasted space first
+    pub fn duplicate_clusters(&self) -> Vec<DuplicateCluster> {
+        let mut by_hash: HashMap<&str, Vec<&FileInfo>> = HashMap::new();
+        for info in self.files.values().filter(|info| info.size > 0 && !info.shallow) {
+            by_hash.entry(&info.hash).or_default().push(info);
         }
-        by_lang
+        let mut clusters: Vec<DuplicateCluster> = by_hash
+            .into_iter()
+            .filter(|(_, files)| files.len() > 1)
+            .map(|(hash, files)| {
+                let size = files[0].size;
+                let mut paths: Vec<String> = files.iter().map(|info| info.path.clone()).collect();
+                paths.sort();
+                DuplicateCluster {
+                    hash: hash.to_string(),
+                    size,
+                    wasted_bytes: size * (paths.len() as u64 - 1),
+                    paths,
+                }
+            })
+            .collect();
+        clusters.sort_by(|a, b| b.wasted_bytes.cmp(&a.wasted_bytes).then(a.paths.cmp(&b.paths)));
+        clusters
     }
 
     /// Check if file has changed (by hash)
     pub fn has_changed(&self, path: &str, new_hash: &str) -> bool {
         self.files
-            .get(path)
+            .get(&CanonicalPath::new(path))
             .map(|info| info.hash != new_hash)
             .unwrap_or(true)
     }
 }
 
+/// Byte ranges of every case-insensitive occurrence of `terms` in `text`, merged and sorted
+fn find_ranges(text: &str, terms: &[&str]) -> Vec<(usize, usize)> {
+    // ASCII lowercasing keeps byte offsets aligned with the original text
+    let haystack = text.to_ascii_lowercase();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for term in terms.iter().filter(|t| !t.is_empty()) {
+        let term = term.to_ascii_lowercase();
+        ranges.extend(haystack.match_indices(&term).map(|(start, m)| (start, start + m.len())));
+    }
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Convert byte ranges within `text` to UTF-16 offsets
+fn to_utf16(text: &str, ranges: &[(usize, usize)]) -> Vec<MatchRange> {
+    let offset = |byte: usize| text[..byte].encode_utf16().count();
+    ranges
+        .iter()
+        .map(|&(start, end)| MatchRange {
+            start: offset(start),
+            end: offset(end),
+        })
+        .collect()
+}
+
+/// Ranges of the whole query in `text`, or of its words when the whole query is absent
+fn highlight(text: &str, query: &str, words: &[&str]) -> Vec<MatchRange> {
+    let mut ranges = find_ranges(text, &[query]);
+    if ranges.is_empty() {
+        ranges = find_ranges(text, words);
+    }
+    to_utf16(text, &ranges)
+}
+
+/// Longest snippet kept around a match, in bytes
+const SNIPPET_WIDTH: usize = 240;
+
+/// Trimmed line, windowed around the first match when it is long
+fn snippet_window(line: &str, first_match: usize) -> (usize, &str) {
+    // Trim the end first, so a whitespace-only line leaves an empty snippet rather than a start past its end
+    let line = line.trim_end();
+    let mut start = line.len() - line.trim_start().len();
+    if line.len() - start > SNIPPET_WIDTH {
+        // A match in the trailing whitespace falls outside the trimmed line
+        start = start.max(first_match.saturating_sub(SNIPPET_WIDTH / 3)).min(line.len());
+        while !line.is_char_boundary(start) {
+            start -= 1;
+        }
+        let mut end = (start + SNIPPET_WIDTH).min(line.len());
+        while !line.is_char_boundary(end) {
+            end += 1;
+        }
+        return (start, &line[start..end]);
+    }
+    (start, &line[start..])
+}
+
+/// Lines of `paths` containing `query`, with highlight ranges within each snippet
+pub fn grep(paths: &[String], query: &str, limit: usize) -> Vec<FileMatch> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut matches: Vec<FileMatch> = paths
+        .par_iter()
+        .flat_map_iter(|path| {
+            // Binary and unreadable files have no text to match
+            let content = fs::read(path).ok().and_then(|bytes| String::from_utf8(bytes).ok()).unwrap_or_default();
+            let name = Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            content
+                .lines()
+                .enumerate()
+                .filter_map(|(i, line)| {
+                    let ranges = find_ranges(line, &[query]);
+                    let first = ranges.first()?.0;
+                    let (start, snippet) = snippet_window(line, first);
+                    let end = start + snippet.len();
+                    let within: Vec<(usize, usize)> = ranges
+                        .iter()
+                        .filter(|(s, e)| *s >= start && *e <= end)
+                        .map(|(s, e)| (s - start, e - start))
+                        .collect();
+                    // Exact-case matches rank above case-insensitive ones
+                    let score = if line.contains(query) { 2.0 } else { 1.0 };
+                    Some(FileMatch {
+                        path: path.clone(),
+                        name: name.clone(),
+                        line: Some(i + 1),
+                        snippet: Some(snippet.to_string()),
+                        score,
+                        path_ranges: Vec::new(),
+                        name_ranges: Vec::new(),
+                        snippet_ranges: to_utf16(snippet, &within),
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    matches.truncate(limit);
+    matches
+}
+
+/// Lowercase sub-tokens of an identifier: `getUserById` -> get, user, by, id;
+/// `HTTPServer_v2` -> http, server, v2
+pub fn split_identifier(identifier: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for part in identifier.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (prev, cur) = (chars[i - 1], chars[i]);
+            let next_lower = chars.get(i + 1).map(|c| c.is_lowercase()).unwrap_or(false);
+            // fooBar, or the last capital of an acronym: HTTPServer
+            let boundary = cur.is_uppercase() && (prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower));
+            if boundary {
+                tokens.push(chars[start..i].iter().collect::<String>().to_lowercase());
+                start = i;
+            }
+        }
+        if start < chars.len() {
+            tokens.push(chars[start..].iter().collect::<String>().to_lowercase());
+        }
+    }
+    tokens
+}
+
+/// Files to index first: `open` files, then their `neighbors`, then the top-level manifests, without repeats
+pub fn priority_files(root: &Path, open: &[PathBuf], neighbors: &[PathBuf]) -> Vec<PathBuf> {
+    let manifests = MANIFESTS.iter().map(|name| root.join(name)).filter(|path| path.is_file());
+    let mut seen: HashSet<CanonicalPath> = HashSet::new();
+    open.iter()
+        .chain(neighbors)
+        .cloned()
+        .chain(manifests)
+        .filter(|path| path.starts_with(root) && !FileIndex::is_excluded(path))
+        .filter(|path| seen.insert(CanonicalPath::new(path)))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,4 +848,82 @@ mod tests {
         let index = FileIndex::new();
         assert_eq!(index.file_count(), 0);
     }
+
+    #[test]
+    fn test_highlight_uses_utf16_offsets() {
+        let ranges = highlight("café_user.ts", "user", &["user"]);
+        assert_eq!(ranges, vec![MatchRange { start: 5, end: 9 }]);
+        assert_eq!(highlight("user_id_map", "user id", &["user", "id"]).len(), 2);
+    }
+
+    #[test]
+    fn test_snippet_window_trims_whitespace() {
+        assert_eq!(snippet_window("    let x = 1;  ", 8), (4, "let x = 1;"));
+        assert_eq!(snippet_window("   \t ", 1), (0, ""));
+        let long = format!("{}{}", "a".repeat(SNIPPET_WIDTH * 2), " ".repeat(SNIPPET_WIDTH * 2));
+        assert_eq!(snippet_window(&long, SNIPPET_WIDTH * 3).1, "");
+    }
+
+    #[test]
+    fn test_duplicates_share_indexed_content() {
+        let root = std::env::temp_dir().join(crate::storage::new_id("dedup-test"));
+        fs::create_dir_all(root.join("vendor")).unwrap();
+        fs::write(root.join("util.js"), "export function parseWidget() {}").unwrap();
+        fs::write(root.join("vendor").join("util.js"), "export function parseWidget() {}").unwrap();
+        fs::write(root.join("other.js"), "export function other() {}").unwrap();
+
+        let mut index = FileIndex::new();
+        index.index_directory(&root).unwrap();
+        let clusters = index.duplicate_clusters();
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].wasted_bytes, clusters[0].size);
+        let copy = root.join("vendor").join("util.js").to_string_lossy().to_string();
+        assert_eq!(index.get(&copy).unwrap().duplicate_of, Some(root.join("util.js").to_string_lossy().to_string()));
+        assert_eq!(index.search("parse widget").len(), 2);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_update_files_detects_renames() {
+        let root = std::env::temp_dir().join(crate::storage::new_id("rename-test"));
+        fs::create_dir_all(root.join("lib")).unwrap();
+        fs::write(root.join("util.ts"), "export const util = 1").unwrap();
+        fs::write(root.join("other.ts"), "export const other = 1").unwrap();
+        let mut index = FileIndex::new();
+        index.index_directory(&root).unwrap();
+
+        fs::rename(root.join("util.ts"), root.join("lib/util.ts")).unwrap();
+        fs::remove_file(root.join("other.ts")).unwrap();
+        fs::write(root.join("new.ts"), "export const new = 1").unwrap();
+        let paths = [root.join("util.ts"), root.join("lib/util.ts"), root.join("other.ts"), root.join("new.ts")];
+        let renames = index.update_files(&root, &paths);
+        assert_eq!(renames, vec![FileRename {
+            from: root.join("util.ts").to_string_lossy().to_string(),
+            to: root.join("lib/util.ts").to_string_lossy().to_string(),
+        }]);
+        assert_eq!(index.file_count(), 2);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_priority_files() {
+        let root = std::env::temp_dir().join(crate::storage::new_id("priority-test"));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("package.json"), "{}").unwrap();
+        fs::write(root.join("src").join("app.ts"), "import { a } from './a'").unwrap();
+        fs::write(root.join("src").join("a.ts"), "export const a = 1").unwrap();
+
+        let open = [root.join("src/app.ts"), root.join("package.json")];
+        let neighbors = [root.join("src/a.ts"), root.join("node_modules/react/index.js"), root.join("src/app.ts")];
+        let files = priority_files(&root, &open, &neighbors);
+        assert_eq!(files, vec![root.join("src/app.ts"), root.join("package.json"), root.join("src/a.ts")]);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_split_identifier() {
+        assert_eq!(split_identifier("getUserById"), vec!["get", "user", "by", "id"]);
+        assert_eq!(split_identifier("user_id_map"), vec!["user", "id", "map"]);
+        assert_eq!(split_identifier("HTTPServer"), vec!["http", "server"]);
+    }
 }
@@ -0,0 +1,270 @@
+// File Templates - Multi-file units like components and modules
+// Creates the files and wires them into their parent module
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::changeset::{Changeset, FileChange, FilePreview, Position, TextEdit};
+use crate::scaffold::{self, TemplateFile, TemplateVariable};
+
+const MANIFEST: &str = "template.json";
+
+/// How created files are registered with the surrounding code
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Wiring {
+    /// `mod <name>;` in the parent Rust module
+    RustMod,
+    /// `export` line in the directory's index.ts/js barrel
+    Barrel,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileTemplate {
+    pub kind: String,
+    pub description: String,
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+    #[serde(default)]
+    pub files: Vec<TemplateFile>,
+    #[serde(default)]
+    pub wiring: Option<Wiring>,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileTemplateResult {
+    pub changeset: Changeset,
+    pub preview: Vec<FilePreview>,
+    /// File that received the import/mod statement, if any
+    pub wired_into: Option<String>,
+}
+
+fn file(path: &str, content: &str) -> TemplateFile {
+    TemplateFile {
+        path: path.to_string(),
+        content: content.to_string(),
+    }
+}
+
+fn builtin_templates() -> Vec<FileTemplate> {
+    vec![
+        FileTemplate {
+            kind: "react-component".to_string(),
+            description: "React component with a test and CSS module".to_string(),
+            variables: Vec::new(),
+            files: vec![
+                file(
+                    "{{name_pascal}}.tsx",
+                    "import styles from './{{name_pascal}}.module.css';\n\nexport interface {{name_pascal}}Props {}\n\nexport default function {{name_pascal}}(props: {{name_pascal}}Props) {\n  return <div className={styles.root}>{{name_pascal}}</div>;\n}\n",
+                ),
+                file(
+                    "{{name_pascal}}.test.tsx",
+                    "import { render, screen } from '@testing-library/react';\nimport {{name_pascal}} from './{{name_pascal}}';\n\ntest('renders', () => {\n  render(<{{name_pascal}} />);\n  expect(screen.getByText('{{name_pascal}}')).toBeTruthy();\n});\n",
+                ),
+                file("{{name_pascal}}.module.css", ".root {\n}\n"),
+            ],
+            wiring: Some(Wiring::Barrel),
+            builtin: true,
+        },
+        FileTemplate {
+            kind: "rust-module".to_string(),
+            description: "Rust module declared in its parent".to_string(),
+            variables: Vec::new(),
+            files: vec![file(
+                "{{name_snake}}.rs",
+                "// {{name_pascal}}\n",
+            )],
+            wiring: Some(Wiring::RustMod),
+            builtin: true,
+        },
+    ]
+}
+
+/// User templates: `<dir>/<kind>/template.json` plus files under `<dir>/<kind>/files/`
+fn load_user_templates(dir: &Path) -> Vec<FileTemplate> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut templates = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let root = entry.path();
+        let manifest = match fs::read_to_string(root.join(MANIFEST)) {
+            Ok(manifest) => manifest,
+            Err(_) => continue,
+        };
+        let mut template: FileTemplate = match serde_json::from_str(&manifest) {
+            Ok(template) => template,
+            Err(e) => {
+                log::warn!("Skipping invalid file template {:?}: {}", root, e);
+                continue;
+            }
+        };
+        template.kind = entry.file_name().to_string_lossy().to_string();
+        template.builtin = false;
+
+        let files_dir = root.join("files");
+        for file in WalkDir::new(&files_dir).into_iter().filter_map(|e| e.ok()) {
+            if !file.file_type().is_file() {
+                continue;
+            }
+            let relative = file.path().strip_prefix(&files_dir).unwrap_or(file.path());
+            if let Ok(content) = fs::read_to_string(file.path()) {
+                template.files.push(TemplateFile {
+                    path: relative.to_string_lossy().replace('\\', "/"),
+                    content,
+                });
+            }
+        }
+        templates.push(template);
+    }
+    templates
+}
+
+/// All file templates; user templates override built-ins of the same kind
+pub fn templates(user_dir: &Path) -> Vec<FileTemplate> {
+    let mut templates = builtin_templates();
+    for user in load_user_templates(user_dir) {
+        templates.retain(|t| t.kind != user.kind);
+        templates.push(user);
+    }
+    templates
+}
+
+/// Parent module file declaring Rust modules of `dir`
+fn rust_parent_module(dir: &Path) -> Option<PathBuf> {
+    for candidate in ["mod.rs", "lib.rs", "main.rs"] {
+        let path = dir.join(candidate);
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+    // 2018-style `foo.rs` next to `foo/`
+    let name = dir.file_name()?.to_string_lossy().to_string();
+    let sibling = dir.parent()?.join(format!("{}.rs", name));
+    sibling.is_file().then_some(sibling)
+}
+
+fn barrel_file(dir: &Path) -> Option<PathBuf> {
+    ["index.ts", "index.tsx", "index.js"]
+        .into_iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Insert `statement` after the last line matching `is_anchor`, or before the first line
+fn insertion_edit(content: &str, statement: &str, is_anchor: impl Fn(&str) -> bool) -> TextEdit {
+    let after = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| is_anchor(line.trim_start()))
+        .map(|(i, _)| i + 1)
+        .last();
+    let line = after.map(|l| l + 1).unwrap_or(1);
+    let needs_newline = after.is_some() && line > content.lines().count() && !content.ends_with('\n');
+    let text = if needs_newline {
+        format!("\n{}\n", statement)
+    } else {
+        format!("{}\n", statement)
+    };
+    TextEdit::insert(Position { line, column: 0 }, text)
+}
+
+/// Statement wiring `module` into the parent file, `None` if it is already present
+fn wiring_edit(wiring: Wiring, parent: &str, module: &str) -> Option<TextEdit> {
+    match wiring {
+        Wiring::RustMod => {
+            let declared = parent.lines().any(|line| {
+                let line = line.trim();
+                line == format!("mod {};", module) || line == format!("pub mod {};", module)
+            });
+            (!declared).then(|| {
+                insertion_edit(parent, &format!("mod {};", module), |line| {
+                    (line.starts_with("mod ") || line.starts_with("pub mod ")) && line.ends_with(';')
+                })
+            })
+        }
+        Wiring::Barrel => {
+            let from = format!("'./{}'", module);
+            let exported = parent.lines().any(|line| line.contains(&from));
+            (!exported).then(|| {
+                insertion_edit(
+                    parent,
+                    &format!("export {{ default as {} }} from {};", module, from),
+                    |line| line.starts_with("export ") && line.contains(" from "),
+                )
+            })
+        }
+    }
+}
+
+/// Create the files of template `kind` for `name` in `dir` as a previewable changeset
+pub fn create(user_dir: &Path, workspace: &Path, kind: &str, name: &str, dir: &Path) -> Result<FileTemplateResult> {
+    let template = templates(user_dir)
+        .into_iter()
+        .find(|t| t.kind == kind)
+        .ok_or_else(|| anyhow!("Unknown file template: {}", kind))?;
+
+    let mut provided = std::collections::HashMap::new();
+    provided.insert("name".to_string(), name.to_string());
+    let vars = scaffold::resolve_variables(&template.variables, &provided)?;
+
+    let mut changes = Vec::new();
+    for file in &template.files {
+        let relative = scaffold::relative_path(&scaffold::interpolate(&file.path, &vars)?)?;
+        changes.push(FileChange::Create {
+            path: dir.join(relative).to_string_lossy().to_string(),
+            content: scaffold::interpolate(&file.content, &vars)?,
+        });
+    }
+
+    let mut wired_into = None;
+    if let Some(wiring) = template.wiring {
+        let (parent, module) = match wiring {
+            Wiring::RustMod => (rust_parent_module(dir), vars["name_snake"].clone()),
+            Wiring::Barrel => (barrel_file(dir), vars["name_pascal"].clone()),
+        };
+        match parent {
+            Some(parent) => {
+                let content = fs::read_to_string(&parent)?;
+                if let Some(edit) = wiring_edit(wiring, &content, &module) {
+                    let path = parent.to_string_lossy().to_string();
+                    changes.push(FileChange::Edit {
+                        path: path.clone(),
+                        edits: vec![edit],
+                    });
+                    wired_into = Some(path);
+                }
+            }
+            None => log::info!("No parent module found in {:?}; {} left unwired", dir, module),
+        }
+    }
+
+    let changeset = Changeset::new(format!("New {} {}", kind, name), changes);
+    let preview = changeset.preview(workspace)?;
+    Ok(FileTemplateResult {
+        changeset,
+        preview,
+        wired_into,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_mod_wiring() {
+        let parent = "mod a;\nmod b;\n\nfn main() {}\n";
+        let edit = wiring_edit(Wiring::RustMod, parent, "c").unwrap();
+        assert_eq!(edit.start, Position { line: 3, column: 0 });
+        assert_eq!(edit.new_text, "mod c;\n");
+        assert!(wiring_edit(Wiring::RustMod, parent, "b").is_none());
+    }
+}
@@ -0,0 +1,268 @@
+// Framework Rules - React and Vue checks enabled by the workspace's dependencies
+// React rules run tree-sitter queries over the TSX tree; Vue templates are scanned tag by tag
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Language, Node, Query, QueryCursor};
+
+use crate::syntax;
+use crate::CodeSuggestion;
+
+/// Hooks whose last argument is the dependency array
+const DEPENDENCY_HOOKS: [&str; 4] = ["useEffect", "useLayoutEffect", "useMemo", "useCallback"];
+
+/// Array methods that change the array in place
+const MUTATING_METHODS: [&str; 9] = ["push", "pop", "shift", "unshift", "splice", "sort", "reverse", "fill", "copyWithin"];
+
+const HOOK_CALLS: &str = "(call_expression function: (_) @hook arguments: (arguments) @arguments)";
+
+const STATE_DECLARATIONS: &str =
+    "(variable_declarator name: (array_pattern . (identifier) @state) value: (call_expression function: (_) @hook))";
+
+const STATE_MUTATIONS: &str = r#"
+(assignment_expression left: [(member_expression object: (identifier) @object) (subscript_expression object: (identifier) @object)]) @mutation
+(augmented_assignment_expression left: [(member_expression object: (identifier) @object) (subscript_expression object: (identifier) @object)]) @mutation
+(update_expression argument: [(member_expression object: (identifier) @object) (subscript_expression object: (identifier) @object)]) @mutation
+(call_expression function: (member_expression object: (identifier) @object property: (property_identifier) @method)) @mutation
+"#;
+
+const KEY_ATTRIBUTES: &str = "(jsx_attribute (property_identifier) @name (jsx_expression (identifier) @value))";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Framework {
+    React,
+    Vue,
+}
+
+/// Frameworks declared in the workspace's package.json
+pub fn detect(workspace: &Path) -> Vec<Framework> {
+    let manifest = fs::read_to_string(workspace.join("package.json")).unwrap_or_default();
+    let package: serde_json::Value = serde_json::from_str(&manifest).unwrap_or_default();
+    let declares = |dependencies: &[&str]| {
+        ["dependencies", "devDependencies", "peerDependencies"]
+            .iter()
+            .any(|section| dependencies.iter().any(|d| package[section].get(d).is_some()))
+    };
+    let mut frameworks = Vec::new();
+    if declares(&["react", "preact", "next"]) {
+        frameworks.push(Framework::React);
+    }
+    if declares(&["vue", "nuxt"]) {
+        frameworks.push(Framework::Vue);
+    }
+    frameworks
+}
+
+fn suggestion(node: Node, severity: &str, message: String) -> CodeSuggestion {
+    CodeSuggestion {
+        kind: "quality".to_string(),
+        message,
+        line: node.start_position().row + 1,
+        column: node.start_position().column,
+        severity: severity.to_string(),
+        fix: None,
+    }
+}
+
+/// Captures of every match by name; no matches when the grammar lacks the query's node kinds (JSX in `.ts`)
+fn query_matches<'t>(language: Language, pattern: &str, root: Node<'t>, content: &str) -> Vec<HashMap<String, Node<'t>>> {
+    let query = match Query::new(language, pattern) {
+        Ok(query) => query,
+        Err(_) => return Vec::new(),
+    };
+    let names = query.capture_names();
+    let mut cursor = QueryCursor::new();
+    let found = cursor
+        .matches(&query, root, content.as_bytes())
+        .map(|m| m.captures.iter().map(|c| (names[c.index as usize].to_string(), c.node)).collect())
+        .collect();
+    found
+}
+
+/// Hook name of a callee, `React.useEffect` included
+fn hook_name(callee: &str) -> &str {
+    callee.rsplit('.').next().unwrap_or(callee)
+}
+
+fn is_function(node: Node) -> bool {
+    matches!(node.kind(), "arrow_function" | "function" | "function_expression")
+}
+
+fn parameter_names<'c>(function: Node, content: &'c str) -> Vec<&'c str> {
+    let parameters = match function.child_by_field_name("parameters") {
+        Some(parameters) => syntax::named_children(parameters),
+        // `item => ...`
+        None => function.child_by_field_name("parameter").into_iter().collect(),
+    };
+    parameters
+        .into_iter()
+        .map(|p| &content[p.child_by_field_name("pattern").unwrap_or(p).byte_range()])
+        .collect()
+}
+
+/// Whether `function` is the callback of an `.map(...)` call
+fn is_map_callback(function: Node, content: &str) -> bool {
+    function
+        .parent()
+        .filter(|arguments| arguments.kind() == "arguments")
+        .and_then(|arguments| arguments.parent())
+        .and_then(|call| call.child_by_field_name("function"))
+        .and_then(|callee| callee.child_by_field_name("property"))
+        .is_some_and(|property| &content[property.byte_range()] == "map")
+}
+
+/// Whether `name` at `node` resolves to the index parameter of an enclosing `.map` callback
+fn is_map_index(node: Node, name: &str, content: &str) -> bool {
+    let mut ancestor = node.parent();
+    while let Some(current) = ancestor {
+        if is_function(current) {
+            let parameters = parameter_names(current, content);
+            if let Some(position) = parameters.iter().position(|p| *p == name) {
+                return position == 1 && is_map_callback(current, content);
+            }
+        }
+        ancestor = current.parent();
+    }
+    false
+}
+
+fn react(file_path: &str, content: &str) -> Vec<CodeSuggestion> {
+    let (language, tree) = match (syntax::language_for(file_path), syntax::parse(file_path, content)) {
+        (Some(language), Some(tree)) => (language, tree),
+        _ => return Vec::new(),
+    };
+    let root = tree.root_node();
+    let text = |node: &Node| &content[node.byte_range()];
+    let mut suggestions = Vec::new();
+
+    for found in query_matches(language, HOOK_CALLS, root, content) {
+        let (hook, arguments) = match (found.get("hook"), found.get("arguments")) {
+            (Some(hook), Some(arguments)) => (hook_name(text(hook)), *arguments),
+            _ => continue,
+        };
+        if DEPENDENCY_HOOKS.contains(&hook) && syntax::named_children(arguments).len() == 1 {
+            let message = if hook.ends_with("Effect") {
+                format!("{} without a dependency array runs after every render - list the values it reads", hook)
+            } else {
+                format!("{} without a dependency array recomputes on every render - list the values it reads", hook)
+            };
+            suggestions.push(suggestion(arguments, "warning", message));
+        }
+    }
+
+    let state: HashSet<&str> = query_matches(language, STATE_DECLARATIONS, root, content)
+        .iter()
+        .filter(|found| found.get("hook").is_some_and(|hook| hook_name(text(hook)) == "useState"))
+        .filter_map(|found| found.get("state").map(text))
+        .collect();
+    if !state.is_empty() {
+        for found in query_matches(language, STATE_MUTATIONS, root, content) {
+            let (object, mutation) = match (found.get("object"), found.get("mutation")) {
+                (Some(object), Some(mutation)) => (text(object), *mutation),
+                _ => continue,
+            };
+            let mutates = found.get("method").is_none_or(|method| MUTATING_METHODS.contains(&text(method)));
+            if state.contains(object) && mutates {
+                let message = format!(
+                    "`{}` is React state and is mutated in place - React will not re-render; pass a new value to its setter",
+                    object
+                );
+                suggestions.push(suggestion(mutation, "warning", message));
+            }
+        }
+    }
+
+    for found in query_matches(language, KEY_ATTRIBUTES, root, content) {
+        let (name, value) = match (found.get("name"), found.get("value")) {
+            (Some(name), Some(value)) => (text(name), *value),
+            _ => continue,
+        };
+        if name == "key" && is_map_index(value, text(&value), content) {
+            let message = "Array index used as key - items reordered or removed keep the wrong state; use a stable id".to_string();
+            suggestions.push(suggestion(value, "warning", message));
+        }
+    }
+    suggestions
+}
+
+/// 1-based line and byte column of `offset`
+fn position(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset];
+    let column = before.rfind('\n').map(|newline| offset - newline - 1).unwrap_or(offset);
+    (before.matches('\n').count() + 1, column)
+}
+
+/// `v-for` elements in a single-file component's template that have no `:key`
+fn vue(content: &str) -> Vec<CodeSuggestion> {
+    let start = content.find("<template").unwrap_or(content.len());
+    let end = content.rfind("</template>").unwrap_or(content.len()).max(start);
+    let mut suggestions = Vec::new();
+    let mut offset = start;
+    while let Some(open) = content[offset..end].find('<').map(|at| offset + at) {
+        // Attribute values may contain `>`, so the tag ends at the first one outside quotes
+        let mut quote = None;
+        let mut close = end;
+        for (i, c) in content[open..end].char_indices() {
+            match (quote, c) {
+                (None, '"' | '\'') => quote = Some(c),
+                (Some(q), _) if c == q => quote = None,
+                (None, '>') => {
+                    close = open + i;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let tag = &content[open..close];
+        let attributes: Vec<&str> = tag.split_whitespace().collect();
+        let has = |name: &str| attributes.iter().any(|a| a.split('=').next() == Some(name));
+        if has("v-for") && !has(":key") && !has("v-bind:key") {
+            let (line, column) = position(content, open);
+            suggestions.push(CodeSuggestion {
+                kind: "quality".to_string(),
+                message: "v-for without :key - Vue reuses elements by position; bind a unique key".to_string(),
+                line,
+                column,
+                severity: "warning".to_string(),
+                fix: None,
+            });
+        }
+        offset = close.max(open + 1);
+    }
+    suggestions
+}
+
+/// Findings of the rules for `frameworks` that apply to the file's type
+pub fn analyze(file_path: &str, content: &str, frameworks: &[Framework]) -> Vec<CodeSuggestion> {
+    let extension = file_path.rsplit('.').next().unwrap_or("");
+    let mut suggestions = match extension {
+        "tsx" | "jsx" | "ts" | "js" if frameworks.contains(&Framework::React) => react(file_path, content),
+        "vue" if frameworks.contains(&Framework::Vue) => vue(content),
+        _ => Vec::new(),
+    };
+    suggestions.sort_by_key(|s| (s.line, s.column));
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_react_rules() {
+        let source = "export function List({ rows }) {\n  const [items, setItems] = useState([]);\n  useEffect(() => {\n    items.push(rows.length);\n  });\n  const total = useMemo(() => rows.length, [rows]);\n  return rows.map((row, index) => <Row key={index} />);\n}\n";
+        let lines: Vec<usize> = analyze("src/List.tsx", source, &[Framework::React]).into_iter().map(|s| s.line).collect();
+        assert_eq!(lines, vec![3, 4, 7]);
+        assert!(analyze("src/List.tsx", source, &[Framework::Vue]).is_empty());
+    }
+
+    #[test]
+    fn test_vue_for_without_key() {
+        let source = "<template>\n  <ul>\n    <li v-for=\"item in items\" :class=\"{ wide: width > 10 }\">{{ item }}</li>\n    <li\n      v-for=\"item in items\"\n      :key=\"item.id\"\n    >{{ item }}</li>\n  </ul>\n</template>\n<script>\nconst html = '<p v-for=\"x in y\">';\n</script>\n";
+        let found: Vec<(usize, usize)> = analyze("App.vue", source, &[Framework::Vue]).into_iter().map(|s| (s.line, s.column)).collect();
+        assert_eq!(found, vec![(3, 4)]);
+    }
+}
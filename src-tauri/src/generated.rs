@@ -0,0 +1,107 @@
+// Generated Files - Detection of minified bundles, lockfiles and generated code
+// Such files are indexed by metadata only and skipped by analysis
+
+use serde::{Deserialize, Serialize};
+
+use crate::search_query::glob_match;
+
+/// Bytes inspected for generator markers
+const MARKER_SCAN_BYTES: usize = 2048;
+
+/// A file longer than this whose longest line exceeds `MINIFIED_LINE_LENGTH` is treated as minified
+const MINIFIED_MIN_BYTES: usize = 4096;
+const MINIFIED_LINE_LENGTH: usize = 2000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GeneratedReason {
+    /// Well-known generated file name (`*.min.js`, `*_pb2.py`, lockfiles)
+    NamePattern,
+    /// Matches one of the configured `indexer.generated_patterns`
+    ConfiguredPattern,
+    /// Very long lines, as in minified bundles
+    Minified,
+    /// Header comment such as `@generated` or `DO NOT EDIT`
+    GeneratorMarker,
+}
+
+const NAME_SUFFIXES: &[&str] = &[
+    ".min.js", ".min.mjs", ".min.css", ".bundle.js", ".chunk.js", ".js.map", ".css.map",
+    ".pb.go", ".pb.cc", ".pb.h", "_pb2.py", "_pb2_grpc.py", "_pb.js", "_pb.d.ts", ".pb.swift",
+    ".g.dart", ".freezed.dart", ".designer.cs", ".g.cs",
+];
+
+const LOCKFILES: &[&str] = &[
+    "package-lock.json", "yarn.lock", "pnpm-lock.yaml", "Cargo.lock", "poetry.lock", "Pipfile.lock",
+    "composer.lock", "Gemfile.lock", "go.sum",
+];
+
+const MARKERS: &[&str] = &[
+    "@generated",
+    "code generated by",
+    "do not edit",
+    "generated by the protocol buffer compiler",
+    "<auto-generated",
+    "autogenerated",
+    "this file is automatically generated",
+];
+
+fn matches_name(name: &str) -> bool {
+    LOCKFILES.contains(&name) || NAME_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) || name.contains(".generated.")
+}
+
+fn has_marker(content: &str) -> bool {
+    let mut end = content.len().min(MARKER_SCAN_BYTES);
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    let head = content[..end].to_lowercase();
+    MARKERS.iter().any(|marker| head.contains(marker))
+}
+
+fn is_minified(content: &str) -> bool {
+    content.len() > MINIFIED_MIN_BYTES && content.lines().any(|line| line.len() > MINIFIED_LINE_LENGTH)
+}
+
+/// Classify a file by its workspace-relative path and content.
+/// Patterns without a `/` also match the bare file name.
+pub fn classify(relative: &str, content: &str, patterns: &[String]) -> Option<GeneratedReason> {
+    let name = relative.rsplit('/').next().unwrap_or(relative);
+    if patterns
+        .iter()
+        .any(|p| glob_match(p, relative) || (!p.contains('/') && glob_match(p, name)))
+    {
+        return Some(GeneratedReason::ConfiguredPattern);
+    }
+    if matches_name(name) {
+        return Some(GeneratedReason::NamePattern);
+    }
+    if has_marker(content) {
+        return Some(GeneratedReason::GeneratorMarker);
+    }
+    if is_minified(content) {
+        return Some(GeneratedReason::Minified);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("dist/app.min.js", "", &[]), Some(GeneratedReason::NamePattern));
+        assert_eq!(classify("Cargo.lock", "", &[]), Some(GeneratedReason::NamePattern));
+        assert_eq!(
+            classify("api/user.go", "// Code generated by protoc-gen-go. DO NOT EDIT.\n", &[]),
+            Some(GeneratedReason::GeneratorMarker)
+        );
+        assert_eq!(classify("vendor/lib.js", &"x".repeat(5000), &[]), Some(GeneratedReason::Minified));
+        assert_eq!(
+            classify("src/schema.ts", "export {}", &["*.ts".to_string()]),
+            Some(GeneratedReason::ConfiguredPattern)
+        );
+        assert_eq!(classify("src/main.rs", "fn main() {}\n", &[]), None);
+    }
+}
@@ -0,0 +1,272 @@
+// Git Integration - Thin wrapper around the git CLI
+// Diffs and history for review and context features
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use anyhow::{anyhow, Result};
+
+/// A changed file within a unified diff
+#[derive(Clone, Debug)]
+pub struct FileDiff {
+    /// Path relative to the repository root (new side)
+    pub path: String,
+    pub hunks: Vec<Hunk>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    /// Raw hunk lines including their ' ', '+' or '-' prefix
+    pub lines: Vec<String>,
+}
+
+impl FileDiff {
+    /// Line numbers (new side) added or modified by this diff
+    pub fn changed_lines(&self) -> Vec<usize> {
+        let mut changed = Vec::new();
+        for hunk in &self.hunks {
+            let mut line = hunk.new_start;
+            for raw in &hunk.lines {
+                if raw.starts_with('+') {
+                    changed.push(line);
+                    line += 1;
+                } else if !raw.starts_with('-') {
+                    line += 1;
+                }
+            }
+        }
+        changed
+    }
+}
+
+/// Run a git command in `repo` and return stdout
+pub fn run(repo: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git").args(args).current_dir(repo).output()?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Run a git command in `repo` with `input` on stdin and return stdout
+pub fn run_with_input(repo: &Path, args: &[&str], input: &str) -> Result<String> {
+    let mut child = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Write from another thread so a full stdout pipe cannot block the writer
+    let writer = child.stdin.take().map(|mut stdin| {
+        let input = input.to_string();
+        std::thread::spawn(move || stdin.write_all(input.as_bytes()))
+    });
+    let output = child.wait_with_output()?;
+    if let Some(writer) = writer {
+        writer.join().map_err(|_| anyhow!("git stdin writer panicked"))??;
+    }
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// A revision or range from the frontend; refused when git would read it as an option.
+/// Pass it after `--end-of-options` as well.
+pub fn revision(range: &str) -> Result<&str> {
+    if range.starts_with('-') {
+        return Err(anyhow!("Invalid revision: {}", range));
+    }
+    Ok(range)
+}
+
+/// Get a unified diff.
+/// `None`/empty range diffs the working tree (staged + unstaged) against HEAD,
+/// otherwise the range is passed to `git diff` as-is (e.g. "main...HEAD").
+pub fn diff(repo: &Path, range: Option<&str>) -> Result<String> {
+    match range.map(|r| r.trim()).filter(|r| !r.is_empty()) {
+        Some(range) => run(repo, &["diff", "--no-color", "--unified=3", "--end-of-options", revision(range)?]),
+        None => run(repo, &["diff", "--no-color", "--unified=3", "HEAD"]),
+    }
+}
+
+/// Files changed together with `path` in its recent history.
+/// Returns the number of commits inspected and per-file co-change counts
+/// (paths relative to the repository root).
+pub fn co_changes(repo: &Path, path: &str, max_commits: usize) -> Result<(usize, HashMap<String, usize>)> {
+    let limit = format!("-n{}", max_commits);
+    let log = run(
+        repo,
+        &["log", "--no-color", "--format=%x1e", "--name-only", "--full-diff", &limit, "--", path],
+    )?;
+
+    let mut commits = 0;
+    let mut counts = HashMap::new();
+    for commit in log.split('\x1e').filter(|c| !c.trim().is_empty()) {
+        commits += 1;
+        for file in commit.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            *counts.entry(file.to_string()).or_insert(0) += 1;
+        }
+    }
+    Ok((commits, counts))
+}
+
+/// A commit touching a file
+#[derive(Clone, Debug)]
+pub struct CommitInfo {
+    pub sha: String,
+    pub author: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub subject: String,
+}
+
+/// Recent commits touching `path`, newest first, following renames
+pub fn file_log(repo: &Path, path: &str, max_commits: usize) -> Result<Vec<CommitInfo>> {
+    let limit = format!("-n{}", max_commits);
+    let log = run(
+        repo,
+        &["log", "--no-color", "--follow", "--format=%H%x1f%an%x1f%at%x1f%s", &limit, "--", path],
+    )?;
+
+    Ok(log
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\x1f');
+            let sha = fields.next()?.to_string();
+            let author = fields.next()?.to_string();
+            let seconds: u64 = fields.next()?.parse().ok()?;
+            let subject = fields.next().unwrap_or("").to_string();
+            Some(CommitInfo {
+                sha,
+                author,
+                timestamp: seconds * 1000,
+                subject,
+            })
+        })
+        .collect())
+}
+
+/// Commit counts per file over the last `max_commits` commits (paths relative to the repository root)
+pub fn churn(repo: &Path, max_commits: usize) -> Result<HashMap<String, usize>> {
+    let limit = format!("-n{}", max_commits);
+    let log = run(repo, &["log", "--no-color", "--format=", "--name-only", &limit])?;
+
+    let mut counts = HashMap::new();
+    for file in log.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        *counts.entry(file.to_string()).or_insert(0) += 1;
+    }
+    Ok(counts)
+}
+
+/// Parse unified diff output into per-file hunks
+pub fn parse_diff(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    // Between "diff --git" and the first hunk of a file
+    let mut in_header = false;
+
+    for line in diff.lines() {
+        if line.starts_with("diff --git") {
+            in_header = true;
+        } else if in_header {
+            if let Some(path) = line.strip_prefix("+++ ") {
+                // Deleted files have "+++ /dev/null"; they have no new-side lines
+                let path = path.strip_prefix("b/").unwrap_or(path);
+                files.push(FileDiff {
+                    path: path.to_string(),
+                    hunks: Vec::new(),
+                });
+            } else if line.starts_with("@@") {
+                in_header = false;
+            }
+        }
+
+        if in_header {
+            continue;
+        }
+
+        if line.starts_with("@@") {
+            if let (Some(file), Some(hunk)) = (files.last_mut(), parse_hunk_header(line)) {
+                file.hunks.push(hunk);
+            }
+        } else if let Some(hunk) = files.last_mut().and_then(|f| f.hunks.last_mut()) {
+            if line.starts_with(' ') || line.starts_with('+') || line.starts_with('-') {
+                hunk.lines.push(line.to_string());
+            }
+        }
+    }
+
+    files.retain(|f| f.path != "/dev/null");
+    files
+}
+
+/// Parse "@@ -a,b +c,d @@"
+fn parse_hunk_header(line: &str) -> Option<Hunk> {
+    let mut parts = line.split_whitespace().skip(1);
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+
+    let range = |s: &str| -> Option<(usize, usize)> {
+        let mut it = s.splitn(2, ',');
+        let start = it.next()?.parse().ok()?;
+        let count = it.next().map(|c| c.parse().ok()).unwrap_or(Some(1))?;
+        Some((start, count))
+    };
+
+    let (old_start, old_lines) = range(old)?;
+    let (new_start, new_lines) = range(new)?;
+
+    Some(Hunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        lines: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_diff_changed_lines() {
+        let diff = "diff --git a/src/a.ts b/src/a.ts\n\
+                    --- a/src/a.ts\n\
+                    +++ b/src/a.ts\n\
+                    @@ -1,3 +1,4 @@\n \
+                    one\n\
+                    -two\n\
+                    +TWO\n\
+                    +three\n \
+                    four\n";
+        let files = parse_diff(diff);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "src/a.ts");
+        assert_eq!(files[0].changed_lines(), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_revision_refuses_options() {
+        assert!(revision("--output=/tmp/x").is_err());
+        assert_eq!(revision("main...HEAD").unwrap(), "main...HEAD");
+    }
+}
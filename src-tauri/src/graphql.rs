@@ -0,0 +1,732 @@
+// GraphQL - Schema and operation index for .graphql files and gql template literals
+// Types, root fields and named operations feed the symbol table; operations are checked against the schema
+
+use std::collections::{BTreeMap, HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::a11y_rules;
+use crate::symbols::SymbolKind;
+use crate::CodeSuggestion;
+
+/// Diagnostics source for GraphQL validation
+pub const SOURCE: &str = "graphql";
+
+/// Template tags and the magic comment that mark embedded GraphQL
+const TAGS: [&str; 3] = ["gql", "graphql", "/* GraphQL */"];
+
+/// Fields every type answers, and the introspection entry points on the query root
+const META_FIELDS: [&str; 3] = ["__typename", "__schema", "__type"];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OperationInfo {
+    /// `query`, `mutation`, `subscription` or `fragment`
+    pub kind: String,
+    pub name: Option<String>,
+    pub file: String,
+    pub line: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UnknownField {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub field: String,
+    /// Type the field was selected on
+    pub parent: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GraphqlReport {
+    /// Files that declare schema types
+    pub schema_files: Vec<String>,
+    pub types: usize,
+    pub operations: Vec<OperationInfo>,
+    pub unknown_fields: Vec<UnknownField>,
+}
+
+/// A declaration for the symbol table
+pub struct Declaration {
+    pub name: String,
+    /// Root type for schema fields, operation kind for named operations
+    pub container: Option<String>,
+    pub kind: SymbolKind,
+    pub line: usize,
+}
+
+/// GraphQL text within a file; interpolations are blanked so offsets still match the file
+struct Document {
+    text: String,
+    offset: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Name(String),
+    Punct(char),
+    Spread,
+    /// Strings, numbers and descriptions
+    Literal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TypeKind {
+    Object,
+    Interface,
+    Input,
+    Enum,
+    Union,
+    Scalar,
+}
+
+#[derive(Debug)]
+struct TypeDef {
+    name: String,
+    kind: TypeKind,
+    extension: bool,
+    /// Field name to its named type, with list and non-null wrappers removed
+    fields: Vec<(String, String, usize)>,
+    offset: usize,
+}
+
+#[derive(Debug)]
+enum Selection {
+    Field { name: String, offset: usize, children: Vec<Selection> },
+    Inline { on: Option<String>, children: Vec<Selection> },
+    Spread,
+}
+
+#[derive(Debug)]
+struct Operation {
+    kind: &'static str,
+    name: Option<String>,
+    /// Type condition of a fragment
+    on: Option<String>,
+    selections: Vec<Selection>,
+    offset: usize,
+}
+
+#[derive(Debug, Default)]
+struct Definitions {
+    types: Vec<TypeDef>,
+    roots: Vec<(String, String)>,
+    operations: Vec<Operation>,
+}
+
+pub fn is_document_file(extension: &str) -> bool {
+    matches!(extension, "graphql" | "gql" | "graphqls")
+}
+
+/// Files that can hold GraphQL: schema and operation files, and JS/TS with tagged templates
+pub fn is_source(extension: &str) -> bool {
+    is_document_file(extension) || matches!(extension, "ts" | "mts" | "cts" | "tsx" | "js" | "jsx" | "mjs" | "cjs")
+}
+
+fn documents(path: &str, content: &str) -> Vec<Document> {
+    let extension = path.rsplit('.').next().unwrap_or("");
+    if is_document_file(extension) {
+        return vec![Document { text: content.to_string(), offset: 0 }];
+    }
+    let mut found = Vec::new();
+    for tag in TAGS {
+        for (start, _) in content.match_indices(tag) {
+            let word = tag.starts_with(|c: char| c.is_alphabetic());
+            let before = content[..start].chars().next_back();
+            if word && before.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$' || c == '.') {
+                continue;
+            }
+            let rest = &content[start + tag.len()..];
+            let open = start + tag.len() + (rest.len() - rest.trim_start().len());
+            if !content[open..].starts_with('`') {
+                continue;
+            }
+            if let Some(document) = template(content, open + 1) {
+                found.push(document);
+            }
+        }
+    }
+    found.sort_by_key(|d| d.offset);
+    found
+}
+
+/// Body of the template literal starting at `start`, with `${...}` replaced by spaces
+fn template(content: &str, start: usize) -> Option<Document> {
+    let mut text = String::new();
+    let mut depth = 0;
+    let mut chars = content[start..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '`' if depth == 0 => return Some(Document { text, offset: start }),
+            '\\' if depth == 0 => {
+                text.push(' ');
+                if let Some((_, escaped)) = chars.next() {
+                    text.push_str(&" ".repeat(escaped.len_utf8()));
+                }
+            }
+            '$' if depth == 0 && content[start + i..].starts_with("${") => {
+                depth = 1;
+                text.push(' ');
+                chars.next();
+                text.push(' ');
+            }
+            '{' if depth > 0 => {
+                depth += 1;
+                text.push(' ');
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                text.push(' ');
+            }
+            '\n' => text.push('\n'),
+            _ if depth > 0 => text.push_str(&" ".repeat(c.len_utf8())),
+            _ => text.push(c),
+        }
+    }
+    None
+}
+
+/// Tokens with their byte offset in the file
+fn tokenize(document: &Document) -> Vec<(Token, usize)> {
+    let text = &document.text;
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        if c.is_ascii_whitespace() || c == b',' {
+            i += 1;
+            continue;
+        }
+        if c == b'#' {
+            i = text[i..].find('\n').map(|n| i + n).unwrap_or(bytes.len());
+            continue;
+        }
+        let token = if text[i..].starts_with("\"\"\"") {
+            i = text[i + 3..].find("\"\"\"").map(|n| i + n + 6).unwrap_or(bytes.len());
+            Token::Literal
+        } else if c == b'"' {
+            i += 1;
+            while i < bytes.len() && bytes[i] != b'"' && bytes[i] != b'\n' {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            i += 1;
+            Token::Literal
+        } else if text[i..].starts_with("...") {
+            i += 3;
+            Token::Spread
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            Token::Name(text[start..i].to_string())
+        } else if c.is_ascii_digit() || c == b'-' {
+            i += 1;
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.' || bytes[i] == b'-') {
+                i += 1;
+            }
+            Token::Literal
+        } else {
+            let c = text[i..].chars().next().unwrap_or(' ');
+            i += c.len_utf8();
+            Token::Punct(c)
+        };
+        tokens.push((token, document.offset + start));
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    at: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.at).map(|(t, _)| t)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens.get(self.at).or(self.tokens.last()).map(|(_, o)| *o).unwrap_or(0)
+    }
+
+    fn punct(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(&Token::Punct(c));
+        if found {
+            self.at += 1;
+        }
+        found
+    }
+
+    fn name(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(Token::Name(name)) => {
+                let name = name.clone();
+                self.at += 1;
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Name(name)) if name == word);
+        if found {
+            self.at += 1;
+        }
+        found
+    }
+
+    /// Skip a bracketed group opening at the current token
+    fn skip_group(&mut self, open: char, close: char) {
+        if !self.punct(open) {
+            return;
+        }
+        let mut depth = 1;
+        while depth > 0 {
+            match self.peek() {
+                None => return,
+                Some(Token::Punct(c)) if *c == open => depth += 1,
+                Some(Token::Punct(c)) if *c == close => depth -= 1,
+                _ => {}
+            }
+            self.at += 1;
+        }
+    }
+
+    fn skip_directives(&mut self) {
+        while self.punct('@') {
+            self.name();
+            self.skip_group('(', ')');
+        }
+    }
+
+    fn skip_value(&mut self) {
+        match self.peek() {
+            Some(Token::Punct('[')) => self.skip_group('[', ']'),
+            Some(Token::Punct('{')) => self.skip_group('{', '}'),
+            Some(Token::Punct('$')) => {
+                self.at += 1;
+                self.name();
+            }
+            Some(_) => self.at += 1,
+            None => {}
+        }
+    }
+
+    /// Named type of a reference like `[User!]!`
+    fn type_reference(&mut self) -> Option<String> {
+        while self.punct('[') {}
+        let name = self.name();
+        while self.punct(']') || self.punct('!') {}
+        name
+    }
+
+    fn definitions(&mut self) -> Definitions {
+        let mut definitions = Definitions::default();
+        while let Some(token) = self.peek().cloned() {
+            let offset = self.offset();
+            match token {
+                Token::Punct('{') => {
+                    let selections = self.selection_set();
+                    definitions.operations.push(Operation { kind: "query", name: None, on: None, selections, offset });
+                }
+                Token::Name(word) => {
+                    self.at += 1;
+                    let extension = word == "extend";
+                    let word = if extension { self.name().unwrap_or_default() } else { word };
+                    match word.as_str() {
+                        "query" | "mutation" | "subscription" => {
+                            let kind = match word.as_str() {
+                                "query" => "query",
+                                "mutation" => "mutation",
+                                _ => "subscription",
+                            };
+                            let name = self.name();
+                            self.skip_group('(', ')');
+                            self.skip_directives();
+                            let selections = self.selection_set();
+                            definitions.operations.push(Operation { kind, name, on: None, selections, offset });
+                        }
+                        "fragment" => {
+                            let name = self.name();
+                            self.keyword("on");
+                            let on = self.name();
+                            self.skip_directives();
+                            let selections = self.selection_set();
+                            definitions.operations.push(Operation { kind: "fragment", name, on, selections, offset });
+                        }
+                        "schema" => {
+                            self.skip_directives();
+                            if self.punct('{') {
+                                while let Some(operation) = self.name() {
+                                    self.punct(':');
+                                    if let Some(root) = self.name() {
+                                        definitions.roots.push((operation, root));
+                                    }
+                                }
+                                self.punct('}');
+                            }
+                        }
+                        "directive" => {
+                            self.punct('@');
+                            self.name();
+                            self.skip_group('(', ')');
+                            self.keyword("repeatable");
+                            self.keyword("on");
+                            // Locations are upper case, so the next lower-case keyword ends the list
+                            let location = |t: Option<&Token>| match t {
+                                Some(Token::Name(n)) => n.chars().all(|c| c.is_ascii_uppercase() || c == '_'),
+                                _ => false,
+                            };
+                            while self.punct('|') || location(self.peek()) {
+                                self.name();
+                            }
+                        }
+                        "type" | "interface" | "input" | "enum" | "union" | "scalar" => {
+                            if let Some(definition) = self.type_definition(&word, extension, offset) {
+                                definitions.types.push(definition);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                _ => self.at += 1,
+            }
+        }
+        definitions
+    }
+
+    fn type_definition(&mut self, word: &str, extension: bool, offset: usize) -> Option<TypeDef> {
+        let kind = match word {
+            "type" => TypeKind::Object,
+            "interface" => TypeKind::Interface,
+            "input" => TypeKind::Input,
+            "enum" => TypeKind::Enum,
+            "union" => TypeKind::Union,
+            _ => TypeKind::Scalar,
+        };
+        let name = self.name()?;
+        let mut definition = TypeDef { name, kind, extension, fields: Vec::new(), offset };
+        if self.keyword("implements") {
+            self.punct('&');
+            while self.name().is_some() && self.punct('&') {}
+        }
+        self.skip_directives();
+        match kind {
+            TypeKind::Enum => self.skip_group('{', '}'),
+            TypeKind::Union if self.punct('=') => {
+                self.punct('|');
+                while self.name().is_some() && self.punct('|') {}
+            }
+            TypeKind::Scalar => {}
+            _ if self.punct('{') => {
+                while !self.punct('}') {
+                    if self.peek() == Some(&Token::Literal) {
+                        self.at += 1;
+                        continue;
+                    }
+                    let offset = self.offset();
+                    let Some(field) = self.name() else {
+                        if self.peek().is_none() {
+                            break;
+                        }
+                        self.at += 1;
+                        continue;
+                    };
+                    self.skip_group('(', ')');
+                    self.punct(':');
+                    let named = self.type_reference().unwrap_or_default();
+                    if self.punct('=') {
+                        self.skip_value();
+                    }
+                    self.skip_directives();
+                    definition.fields.push((field, named, offset));
+                }
+            }
+            _ => {}
+        }
+        Some(definition)
+    }
+
+    fn selection_set(&mut self) -> Vec<Selection> {
+        let mut selections = Vec::new();
+        if !self.punct('{') {
+            return selections;
+        }
+        while !self.punct('}') {
+            match self.peek().cloned() {
+                None => break,
+                Some(Token::Spread) => {
+                    self.at += 1;
+                    if self.keyword("on") {
+                        let on = self.name();
+                        self.skip_directives();
+                        selections.push(Selection::Inline { on, children: self.selection_set() });
+                    } else if self.name().is_some() {
+                        self.skip_directives();
+                        selections.push(Selection::Spread);
+                    } else {
+                        self.skip_directives();
+                        selections.push(Selection::Inline { on: None, children: self.selection_set() });
+                    }
+                }
+                Some(Token::Name(_)) => {
+                    let mut offset = self.offset();
+                    let mut name = self.name().unwrap_or_default();
+                    if self.punct(':') {
+                        offset = self.offset();
+                        name = self.name().unwrap_or_default();
+                    }
+                    self.skip_group('(', ')');
+                    self.skip_directives();
+                    let children = self.selection_set();
+                    selections.push(Selection::Field { name, offset, children });
+                }
+                Some(_) => self.at += 1,
+            }
+        }
+        selections
+    }
+}
+
+fn parse(path: &str, content: &str) -> Vec<Definitions> {
+    documents(path, content)
+        .iter()
+        .map(|document| Parser { tokens: tokenize(document), at: 0 }.definitions())
+        .collect()
+}
+
+/// Types, root fields and named operations declared in a file
+pub fn declarations(path: &str, content: &str) -> Vec<Declaration> {
+    let mut found = Vec::new();
+    for definitions in parse(path, content) {
+        let roots = root_names(&definitions.roots);
+        for definition in &definitions.types {
+            let line = a11y_rules::position(content, definition.offset).0;
+            if !definition.extension {
+                let kind = match definition.kind {
+                    TypeKind::Interface => SymbolKind::Interface,
+                    TypeKind::Enum => SymbolKind::Enum,
+                    _ => SymbolKind::Type,
+                };
+                found.push(Declaration { name: definition.name.clone(), container: None, kind, line });
+            }
+            if roots.values().any(|root| *root == definition.name) {
+                for (field, _, offset) in &definition.fields {
+                    found.push(Declaration {
+                        name: field.clone(),
+                        container: Some(definition.name.clone()),
+                        kind: SymbolKind::Function,
+                        line: a11y_rules::position(content, *offset).0,
+                    });
+                }
+            }
+        }
+        for operation in &definitions.operations {
+            if let Some(name) = operation.name.as_ref().filter(|_| operation.kind != "fragment") {
+                found.push(Declaration {
+                    name: name.clone(),
+                    container: Some(operation.kind.to_string()),
+                    kind: SymbolKind::Function,
+                    line: a11y_rules::position(content, operation.offset).0,
+                });
+            }
+        }
+    }
+    found
+}
+
+/// Root type per operation kind, defaulting to `Query`, `Mutation` and `Subscription`
+fn root_names(declared: &[(String, String)]) -> HashMap<String, String> {
+    let defaults = [("query", "Query"), ("mutation", "Mutation"), ("subscription", "Subscription")];
+    let mut roots: HashMap<String, String> = defaults
+        .iter()
+        .map(|(kind, root)| (kind.to_string(), root.to_string()))
+        .collect();
+    roots.extend(declared.iter().cloned());
+    roots
+}
+
+struct Schema {
+    types: HashMap<String, (TypeKind, HashMap<String, String>)>,
+    roots: HashMap<String, String>,
+}
+
+impl Schema {
+    fn check(
+        &self,
+        selections: &[Selection],
+        parent: &str,
+        root: bool,
+        file: &str,
+        content: &str,
+        out: &mut Vec<UnknownField>,
+    ) {
+        let Some((kind, fields)) = self.types.get(parent) else {
+            return;
+        };
+        for selection in selections {
+            match selection {
+                Selection::Field { name, offset, children } => {
+                    let meta = name == "__typename" || (root && META_FIELDS.contains(&name.as_str()));
+                    let selectable = matches!(kind, TypeKind::Object | TypeKind::Interface | TypeKind::Union);
+                    if meta || !selectable {
+                        continue;
+                    }
+                    match fields.get(name) {
+                        Some(named) => self.check(children, named, false, file, content, out),
+                        None => {
+                            let (line, column) = a11y_rules::position(content, *offset);
+                            out.push(UnknownField {
+                                file: file.to_string(),
+                                line,
+                                column,
+                                field: name.clone(),
+                                parent: parent.to_string(),
+                            });
+                        }
+                    }
+                }
+                Selection::Inline { on, children } => {
+                    self.check(children, on.as_deref().unwrap_or(parent), root, file, content, out)
+                }
+                Selection::Spread => {}
+            }
+        }
+    }
+}
+
+/// Index GraphQL across `(relative path, content)` sources and check operations against the schema
+pub fn report(sources: &[(String, String)]) -> GraphqlReport {
+    let parsed: Vec<(&String, &String, Vec<Definitions>)> =
+        sources.iter().map(|(file, content)| (file, content, parse(file, content))).collect();
+
+    let mut schema = Schema { types: HashMap::new(), roots: HashMap::new() };
+    let mut declared_roots = Vec::new();
+    let mut report = GraphqlReport::default();
+    for (file, _, documents) in &parsed {
+        let mut declares = false;
+        for definition in documents.iter().flat_map(|d| &d.types) {
+            declares = true;
+            let entry = schema.types.entry(definition.name.clone()).or_insert((definition.kind, HashMap::new()));
+            if !definition.extension {
+                entry.0 = definition.kind;
+            }
+            entry.1.extend(definition.fields.iter().map(|(field, named, _)| (field.clone(), named.clone())));
+        }
+        declared_roots.extend(documents.iter().flat_map(|d| d.roots.iter().cloned()));
+        if declares {
+            report.schema_files.push(file.to_string());
+        }
+    }
+    schema.roots = root_names(&declared_roots);
+    report.types = schema.types.len();
+
+    for (file, content, documents) in &parsed {
+        for operation in documents.iter().flat_map(|d| &d.operations) {
+            report.operations.push(OperationInfo {
+                kind: operation.kind.to_string(),
+                name: operation.name.clone(),
+                file: file.to_string(),
+                line: a11y_rules::position(content, operation.offset).0,
+            });
+            let (parent, root) = match &operation.on {
+                Some(on) => (Some(on), false),
+                None => (schema.roots.get(operation.kind), true),
+            };
+            if let Some(parent) = parent {
+                schema.check(&operation.selections, parent, root, file, content, &mut report.unknown_fields);
+            }
+        }
+    }
+    report
+}
+
+impl GraphqlReport {
+    /// Unknown fields as diagnostics, by file
+    pub fn suggestions(&self) -> BTreeMap<String, Vec<CodeSuggestion>> {
+        let mut by_file: BTreeMap<String, Vec<CodeSuggestion>> = BTreeMap::new();
+        for unknown in &self.unknown_fields {
+            by_file.entry(unknown.file.clone()).or_default().push(CodeSuggestion {
+                kind: "type".to_string(),
+                message: format!("Cannot query field `{}` on type `{}`", unknown.field, unknown.parent),
+                line: unknown.line,
+                column: unknown.column,
+                severity: "error".to_string(),
+                fix: None,
+            });
+        }
+        by_file
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = concat!(
+        "\"\"\"Root\"\"\"\n",
+        "type Query {\n",
+        "  user(id: ID!): User\n",
+        "  users(first: Int = 10): [User!]!\n",
+        "}\n",
+        "type User implements Node @key(fields: \"id\") {\n",
+        "  id: ID!\n",
+        "  name: String\n",
+        "  friends: [User]\n",
+        "}\n",
+        "interface Node { id: ID! }\n",
+        "extend type Query { me: User }\n",
+        "type Mutation { rename(name: String): User }\n",
+    );
+
+    #[test]
+    fn test_operations_are_checked_against_the_schema() {
+        let client = concat!(
+            "import { gql } from '@apollo/client';\n",
+            "const FIELDS = gql`fragment U on User { id nickname }`;\n",
+            "export const GET_USER = gql`\n",
+            "  query GetUser($id: ID!) {\n",
+            "    user(id: $id) {\n",
+            "      handle: name\n",
+            "      friends { ...U email }\n",
+            "      ${FIELDS}\n",
+            "    }\n",
+            "    me { __typename ... on User { age } }\n",
+            "  }\n",
+            "`;\n",
+        );
+        let sources = vec![
+            ("schema.graphql".to_string(), SCHEMA.to_string()),
+            ("src/user.ts".to_string(), client.to_string()),
+        ];
+        let report = report(&sources);
+        assert_eq!(report.schema_files, vec!["schema.graphql"]);
+        assert_eq!(report.types, 4);
+        let unknown: Vec<_> =
+            report.unknown_fields.iter().map(|u| (u.field.as_str(), u.parent.as_str(), u.line)).collect();
+        assert_eq!(unknown, vec![("nickname", "User", 2), ("email", "User", 7), ("age", "User", 10)]);
+        assert_eq!(report.unknown_fields[1].column, client.lines().nth(6).unwrap().find("email").unwrap());
+        assert_eq!(report.operations.len(), 2);
+        assert_eq!(report.suggestions()["src/user.ts"].len(), 3);
+    }
+
+    #[test]
+    fn test_declarations_include_types_root_fields_and_operations() {
+        let names: Vec<_> = declarations("schema.graphql", SCHEMA)
+            .into_iter()
+            .map(|d| (d.container.unwrap_or_default(), d.name, d.line))
+            .collect();
+        assert!(names.contains(&(String::new(), "User".to_string(), 6)));
+        assert!(names.contains(&("Query".to_string(), "users".to_string(), 4)));
+        assert!(names.contains(&("Query".to_string(), "me".to_string(), 12)));
+        assert!(names.contains(&("Mutation".to_string(), "rename".to_string(), 13)));
+        assert!(!names.iter().any(|(_, name, _)| name == "id"));
+
+        let client = "const q = graphql`mutation Rename { rename(name: \"x\") { id } }`;";
+        let operations: Vec<_> =
+            declarations("src/rename.js", client).into_iter().map(|d| (d.container, d.name)).collect();
+        assert_eq!(operations, vec![(Some("mutation".to_string()), "Rename".to_string())]);
+    }
+}
@@ -0,0 +1,323 @@
+// Local History - Per-file snapshots and analysis runs
+// Merged with git commits into the timeline of a file
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::git;
+use crate::storage;
+
+/// Snapshots kept per file; the oldest are dropped first
+const MAX_SNAPSHOTS: usize = 50;
+/// Analysis runs kept per file
+const MAX_ANALYSES: usize = 50;
+/// Commits inspected for the timeline
+const MAX_COMMITS: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Snapshot {
+    pub id: String,
+    pub timestamp: u64,
+    /// What wrote the file, e.g. the changeset description
+    pub reason: String,
+    pub hash: String,
+    pub size: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AnalysisRun {
+    pub timestamp: u64,
+    pub source: String,
+    pub errors: usize,
+    pub warnings: usize,
+    pub infos: usize,
+    /// Hash of the analyzed content, so re-analyzing unchanged text is not recorded twice
+    pub hash: String,
+}
+
+/// Stored history of one file
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+struct FileHistory {
+    path: String,
+    snapshots: Vec<Snapshot>,
+    analyses: Vec<AnalysisRun>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineEvent {
+    Snapshot {
+        timestamp: u64,
+        id: String,
+        reason: String,
+        size: usize,
+    },
+    Commit {
+        timestamp: u64,
+        sha: String,
+        author: String,
+        message: String,
+    },
+    Analysis {
+        timestamp: u64,
+        source: String,
+        errors: usize,
+        warnings: usize,
+        infos: usize,
+    },
+}
+
+impl TimelineEvent {
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            TimelineEvent::Snapshot { timestamp, .. }
+            | TimelineEvent::Commit { timestamp, .. }
+            | TimelineEvent::Analysis { timestamp, .. } => *timestamp,
+        }
+    }
+}
+
+fn content_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Workspace-relative path with forward slashes
+fn relative(workspace: &Path, path: &str) -> String {
+    let path = Path::new(path);
+    path.strip_prefix(workspace)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Directory holding the history of one file
+fn history_dir(workspace: &Path, relative: &str) -> PathBuf {
+    let key = content_hash(relative);
+    storage::data_dir(workspace).join("history").join(&key[..16])
+}
+
+fn load(workspace: &Path, relative: &str) -> Result<FileHistory> {
+    let history = storage::read_json(&history_dir(workspace, relative).join("history.json"))?;
+    Ok(history.unwrap_or_else(|| FileHistory {
+        path: relative.to_string(),
+        ..Default::default()
+    }))
+}
+
+fn save(workspace: &Path, history: &FileHistory) -> Result<()> {
+    storage::write_json(&history_dir(workspace, &history.path).join("history.json"), history)
+}
+
+/// Record the content of `path`, unless it equals the latest snapshot
+pub fn record_snapshot(workspace: &Path, path: &str, content: &str, reason: &str) -> Result<()> {
+    let relative = relative(workspace, path);
+    let mut history = load(workspace, &relative)?;
+    let hash = content_hash(content);
+    if history.snapshots.last().map(|s| s.hash == hash).unwrap_or(false) {
+        return Ok(());
+    }
+
+    let dir = history_dir(workspace, &relative);
+    let snapshot = Snapshot {
+        id: storage::new_id("snapshot"),
+        timestamp: storage::now_millis(),
+        reason: reason.to_string(),
+        hash,
+        size: content.len(),
+    };
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(&snapshot.id), content)?;
+    history.snapshots.push(snapshot);
+
+    while history.snapshots.len() > MAX_SNAPSHOTS {
+        let dropped = history.snapshots.remove(0);
+        let _ = fs::remove_file(dir.join(&dropped.id));
+    }
+    save(workspace, &history)
+}
+
+/// Content of a stored snapshot
+pub fn snapshot_content(workspace: &Path, path: &str, id: &str) -> Result<Option<String>> {
+    // Ids are generated by `storage::new_id`; anything else could escape the history dir
+    if !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Ok(None);
+    }
+    let file = history_dir(workspace, &relative(workspace, path)).join(id);
+    if !file.is_file() {
+        return Ok(None);
+    }
+    Ok(Some(fs::read_to_string(file)?))
+}
+
+/// Content of the most recent snapshot of `path` whose content hash is `hash`
+pub fn content_with_hash(workspace: &Path, path: &str, hash: &str) -> Result<Option<String>> {
+    let relative = relative(workspace, path);
+    let history = load(workspace, &relative)?;
+    match history.snapshots.iter().rev().find(|s| s.hash == hash) {
+        Some(snapshot) => snapshot_content(workspace, &relative, &snapshot.id),
+        None => Ok(None),
+    }
+}
+
+/// Latest snapshot of every file snapshotted at or after `since`, as (path, snapshot)
+pub fn snapshots_since(workspace: &Path, since: u64) -> Vec<(String, Snapshot)> {
+    let dir = storage::data_dir(workspace).join("history");
+    let mut latest = Vec::new();
+    for entry in fs::read_dir(dir).into_iter().flatten().filter_map(|e| e.ok()) {
+        let history: Option<FileHistory> = storage::read_json(&entry.path().join("history.json")).ok().flatten();
+        if let Some(mut history) = history {
+            if let Some(snapshot) = history.snapshots.pop().filter(|s| s.timestamp >= since) {
+                latest.push((history.path, snapshot));
+            }
+        }
+    }
+    latest.sort_by(|a, b| a.0.cmp(&b.0));
+    latest
+}
+
+/// Record an analysis run over `content`, unless the same content was just analyzed
+pub fn record_analysis(workspace: &Path, path: &str, content: &str, source: &str, severities: &[&str]) -> Result<()> {
+    let relative = relative(workspace, path);
+    let mut history = load(workspace, &relative)?;
+    let hash = content_hash(content);
+    if history
+        .analyses
+        .last()
+        .map(|a| a.hash == hash && a.source == source)
+        .unwrap_or(false)
+    {
+        return Ok(());
+    }
+
+    let count = |severity: &str| severities.iter().filter(|s| **s == severity).count();
+    history.analyses.push(AnalysisRun {
+        timestamp: storage::now_millis(),
+        source: source.to_string(),
+        errors: count("error"),
+        warnings: count("warning"),
+        infos: severities.len() - count("error") - count("warning"),
+        hash,
+    });
+    if history.analyses.len() > MAX_ANALYSES {
+        let excess = history.analyses.len() - MAX_ANALYSES;
+        history.analyses.drain(..excess);
+    }
+    save(workspace, &history)
+}
+
+/// Carry the snapshots and analysis runs of `from` over to `to`, after the file moved
+pub fn rename(workspace: &Path, from: &str, to: &str) -> Result<()> {
+    let (from, to) = (relative(workspace, from), relative(workspace, to));
+    let old_dir = history_dir(workspace, &from);
+    if from == to || !old_dir.is_dir() {
+        return Ok(());
+    }
+    let old = load(workspace, &from)?;
+    let mut history = load(workspace, &to)?;
+    let new_dir = history_dir(workspace, &to);
+    fs::create_dir_all(&new_dir)?;
+    for snapshot in &old.snapshots {
+        fs::rename(old_dir.join(&snapshot.id), new_dir.join(&snapshot.id))?;
+    }
+
+    // A file that lived at `to` before keeps its own history, merged in time order
+    history.snapshots.extend(old.snapshots);
+    history.snapshots.sort_by_key(|s| s.timestamp);
+    while history.snapshots.len() > MAX_SNAPSHOTS {
+        let dropped = history.snapshots.remove(0);
+        let _ = fs::remove_file(new_dir.join(&dropped.id));
+    }
+    history.analyses.extend(old.analyses);
+    history.analyses.sort_by_key(|a| a.timestamp);
+    if history.analyses.len() > MAX_ANALYSES {
+        let excess = history.analyses.len() - MAX_ANALYSES;
+        history.analyses.drain(..excess);
+    }
+    save(workspace, &history)?;
+    fs::remove_dir_all(&old_dir)?;
+    Ok(())
+}
+
+/// Snapshots, commits and analysis runs of a file, newest first
+pub fn timeline(workspace: &Path, path: &str) -> Result<Vec<TimelineEvent>> {
+    let relative = relative(workspace, path);
+    let history = load(workspace, &relative)?;
+
+    let mut events: Vec<TimelineEvent> = history
+        .snapshots
+        .into_iter()
+        .map(|s| TimelineEvent::Snapshot {
+            timestamp: s.timestamp,
+            id: s.id,
+            reason: s.reason,
+            size: s.size,
+        })
+        .collect();
+    events.extend(history.analyses.into_iter().map(|a| TimelineEvent::Analysis {
+        timestamp: a.timestamp,
+        source: a.source,
+        errors: a.errors,
+        warnings: a.warnings,
+        infos: a.infos,
+    }));
+
+    // Not every workspace is a git repository
+    match git::file_log(workspace, &relative, MAX_COMMITS) {
+        Ok(commits) => events.extend(commits.into_iter().map(|c| TimelineEvent::Commit {
+            timestamp: c.timestamp,
+            sha: c.sha,
+            author: c.author,
+            message: c.subject,
+        })),
+        Err(e) => log::debug!("No git history for {}: {}", relative, e),
+    }
+
+    events.sort_by_key(|e| std::cmp::Reverse(e.timestamp()));
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshots_and_analyses_on_timeline() {
+        let workspace = std::env::temp_dir().join(storage::new_id("history-test"));
+        let path = workspace.join("src/a.ts").to_string_lossy().to_string();
+
+        record_snapshot(&workspace, &path, "one", "edit").unwrap();
+        record_snapshot(&workspace, &path, "one", "edit").unwrap();
+        record_analysis(&workspace, &path, "one", "analyzer", &["error", "info"]).unwrap();
+
+        let events = timeline(&workspace, &path).unwrap();
+        assert_eq!(events.len(), 2);
+        let id = events
+            .iter()
+            .find_map(|e| match e {
+                TimelineEvent::Snapshot { id, .. } => Some(id.clone()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(snapshot_content(&workspace, &path, &id).unwrap().as_deref(), Some("one"));
+        let _ = fs::remove_dir_all(&workspace);
+    }
+
+    #[test]
+    fn test_rename_moves_history() {
+        let workspace = std::env::temp_dir().join(storage::new_id("history-rename-test"));
+        record_snapshot(&workspace, "src/a.ts", "one", "edit").unwrap();
+        record_snapshot(&workspace, "src/a.ts", "two", "edit").unwrap();
+        rename(&workspace, "src/a.ts", "lib/a.ts").unwrap();
+
+        let snapshots = load(&workspace, "lib/a.ts").unwrap().snapshots;
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshot_content(&workspace, "lib/a.ts", &snapshots[1].id).unwrap().as_deref(), Some("two"));
+        assert!(load(&workspace, "src/a.ts").unwrap().snapshots.is_empty());
+        let _ = fs::remove_dir_all(&workspace);
+    }
+}
@@ -0,0 +1,312 @@
+// HTTP Files - Request blocks of .http/.rest scratchpads and their execution
+// `###` separates requests; `@name = value` and the workspace env fill `{{name}}` placeholders
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{file_access, workspace_env};
+
+/// Request methods accepted at the start of a request line
+const METHODS: [&str; 9] = ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS", "TRACE", "CONNECT"];
+
+/// Response bodies beyond this are cut off in the result
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HttpRequestBlock {
+    pub index: usize,
+    /// From `### name` or `# @name name`
+    pub name: Option<String>,
+    pub method: String,
+    /// As written, before placeholder substitution
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    /// 1-based line of the request line
+    pub line: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct HttpFile {
+    /// `@name = value` definitions in file order
+    pub variables: Vec<(String, String)>,
+    pub requests: Vec<HttpRequestBlock>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HttpTiming {
+    /// Until the status line and headers arrived
+    pub headers_ms: u64,
+    pub total_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HttpResponse {
+    pub method: String,
+    /// After placeholder substitution and redirects
+    pub url: String,
+    pub status: u16,
+    pub status_text: String,
+    pub version: String,
+    pub headers: Vec<(String, String)>,
+    /// Pretty-printed for JSON, text otherwise, or a size note for binary content
+    pub body: String,
+    pub size: usize,
+    pub truncated: bool,
+    pub timing: HttpTiming,
+}
+
+pub fn is_http_file(path: &str) -> bool {
+    path.ends_with(".http") || path.ends_with(".rest")
+}
+
+/// Variable definition line: `@host = localhost:8080`
+fn variable(line: &str) -> Option<(String, String)> {
+    let (name, value) = line.strip_prefix('@')?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.') {
+        return None;
+    }
+    Some((name.to_string(), value.trim().to_string()))
+}
+
+fn request_line(line: &str) -> Option<(String, String)> {
+    let mut parts = line.split_whitespace();
+    let first = parts.next()?;
+    if METHODS.contains(&first) {
+        let url = parts.next()?;
+        return Some((first.to_string(), url.to_string()));
+    }
+    // A bare URL is a GET
+    let is_url = first.starts_with("http://") || first.starts_with("https://") || first.starts_with("{{");
+    is_url.then(|| ("GET".to_string(), first.to_string()))
+}
+
+pub fn parse(content: &str) -> HttpFile {
+    let mut file = HttpFile::default();
+    let lines: Vec<&str> = content.lines().collect();
+    let mut start = 0;
+    while start < lines.len() {
+        let end = (start + 1..lines.len()).find(|&i| lines[i].starts_with("###")).unwrap_or(lines.len());
+        let mut name = lines[start].strip_prefix("###").map(str::trim).filter(|n| !n.is_empty()).map(str::to_string);
+        let mut i = if lines[start].starts_with("###") { start + 1 } else { start };
+
+        // Comments, variables and `# @name` before the request line
+        let mut request = None;
+        while i < end && request.is_none() {
+            let line = lines[i].trim();
+            if let Some(tag) = line.strip_prefix('#').or_else(|| line.strip_prefix("//")) {
+                if let Some(tagged) = tag.trim().strip_prefix("@name") {
+                    name = Some(tagged.trim().to_string());
+                }
+            } else if let Some(definition) = variable(line) {
+                file.variables.push(definition);
+            } else if !line.is_empty() {
+                request = request_line(line).map(|r| (r, i));
+            }
+            i += 1;
+        }
+        let Some(((method, mut url), request_at)) = request else {
+            start = end;
+            continue;
+        };
+        // Query continuation lines: `  ?page=2` / `  &size=10`
+        while i < end && (lines[i].trim_start().starts_with('?') || lines[i].trim_start().starts_with('&')) {
+            url.push_str(lines[i].trim());
+            i += 1;
+        }
+        let mut headers = Vec::new();
+        while i < end && !lines[i].trim().is_empty() {
+            let line = lines[i].trim();
+            if !line.starts_with('#') && !line.starts_with("//") {
+                if let Some((header, value)) = line.split_once(':') {
+                    headers.push((header.trim().to_string(), value.trim().to_string()));
+                }
+            }
+            i += 1;
+        }
+        let body = lines[i.min(end)..end].join("\n");
+        let body = body.trim_matches('\n');
+        file.requests.push(HttpRequestBlock {
+            index: file.requests.len(),
+            name,
+            method,
+            url,
+            headers,
+            body: (!body.trim().is_empty()).then(|| body.to_string()),
+            line: request_at + 1,
+        });
+        start = end;
+    }
+    file
+}
+
+/// Replace `{{name}}` placeholders; `{{$timestamp}}` is the current Unix time
+fn substitute(text: &str, variables: &HashMap<String, String>) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(open) = rest.find("{{") {
+        let close = rest[open..].find("}}").map(|c| open + c).ok_or_else(|| anyhow!("Unclosed `{{{{` in `{}`", text))?;
+        out.push_str(&rest[..open]);
+        let name = rest[open + 2..close].trim();
+        let value = match name {
+            "$timestamp" => (crate::storage::now_millis() / 1000).to_string(),
+            _ => variables.get(name).cloned().ok_or_else(|| anyhow!("Unknown variable `{}`", name))?,
+        };
+        out.push_str(&value);
+        rest = &rest[close + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Workspace env overlaid with the file's definitions, each resolved against the ones before it
+fn variables(workspace: &Path, file: &HttpFile) -> Result<HashMap<String, String>> {
+    let mut variables = workspace_env::resolve(workspace).unwrap_or_default();
+    for (name, value) in &file.variables {
+        let value = substitute(value, &variables)?;
+        variables.insert(name.clone(), value);
+    }
+    Ok(variables)
+}
+
+fn format_body(bytes: &[u8], content_type: &str) -> String {
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(_) => return format!("<{} bytes of binary data>", bytes.len()),
+    };
+    if content_type.contains("json") {
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(text) {
+            return serde_json::to_string_pretty(&value).unwrap_or_else(|_| text.to_string());
+        }
+    }
+    text.to_string()
+}
+
+/// Execute request `index` of the .http file at `path`; a `< ./file` body is read relative to it and must stay
+/// inside the workspace, so a cloned .http file can't post the user's keys
+pub async fn execute(workspace: &Path, path: &Path, content: &str, index: usize) -> Result<HttpResponse> {
+    let file = parse(content);
+    let block = file.requests.get(index).ok_or_else(|| anyhow!("No request #{} in the file", index))?;
+    let variables = variables(workspace, &file)?;
+    let url = substitute(&block.url, &variables)?;
+    let method = reqwest::Method::from_bytes(block.method.as_bytes())?;
+
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(60)).build()?;
+    let mut request = client.request(method, &url);
+    for (name, value) in &block.headers {
+        request = request.header(name.as_str(), substitute(value, &variables)?);
+    }
+    if let Some(body) = &block.body {
+        let body = match body.trim().strip_prefix("< ") {
+            Some(reference) if !reference.contains('\n') => {
+                let base = path.parent().unwrap_or(workspace);
+                let referenced = base.join(substitute(reference.trim(), &variables)?);
+                let confined = file_access::confine(Some(workspace), &[], &referenced.to_string_lossy())?;
+                std::fs::read(confined)?
+            }
+            _ => substitute(body, &variables)?.into_bytes(),
+        };
+        request = request.body(body);
+    }
+
+    let started = Instant::now();
+    let response = request.send().await?;
+    let headers_ms = started.elapsed().as_millis() as u64;
+    let status = response.status();
+    let version = format!("{:?}", response.version());
+    let final_url = response.url().to_string();
+    let headers: Vec<(String, String)> = response
+        .headers()
+        .iter()
+        .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).to_string()))
+        .collect();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_lowercase();
+    let bytes = response.bytes().await?;
+    let total_ms = started.elapsed().as_millis() as u64;
+
+    let truncated = bytes.len() > MAX_BODY_BYTES;
+    let shown = &bytes[..bytes.len().min(MAX_BODY_BYTES)];
+    Ok(HttpResponse {
+        method: block.method.clone(),
+        url: final_url,
+        status: status.as_u16(),
+        status_text: status.canonical_reason().unwrap_or("").to_string(),
+        version,
+        headers,
+        body: if truncated { String::from_utf8_lossy(shown).to_string() } else { format_body(shown, &content_type) },
+        size: bytes.len(),
+        truncated,
+        timing: HttpTiming { headers_ms, total_ms },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = concat!(
+        "@host = localhost:8080\n",
+        "@base = http://{{host}}/api\n",
+        "\n",
+        "### List users\n",
+        "GET {{base}}/users\n",
+        "    ?page=2\n",
+        "    &size=10\n",
+        "Accept: application/json\n",
+        "\n",
+        "###\n",
+        "# @name create\n",
+        "POST {{base}}/users HTTP/1.1\n",
+        "Content-Type: application/json\n",
+        "Authorization: Bearer {{TOKEN}}\n",
+        "\n",
+        "{\n",
+        "  \"name\": \"Ada\"\n",
+        "}\n",
+        "\n",
+        "### Health\n",
+        "https://example.com/health\n",
+    );
+
+    #[test]
+    fn test_parse_request_blocks() {
+        let file = parse(FILE);
+        assert_eq!(file.variables.len(), 2);
+        let summary: Vec<_> = file
+            .requests
+            .iter()
+            .map(|r| (r.name.as_deref(), r.method.as_str(), r.url.as_str(), r.line))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Some("List users"), "GET", "{{base}}/users?page=2&size=10", 5),
+                (Some("create"), "POST", "{{base}}/users", 12),
+                (Some("Health"), "GET", "https://example.com/health", 21),
+            ]
+        );
+        assert_eq!(file.requests[0].headers, vec![("Accept".to_string(), "application/json".to_string())]);
+        assert_eq!(file.requests[0].body, None);
+        assert_eq!(file.requests[1].body.as_deref(), Some("{\n  \"name\": \"Ada\"\n}"));
+    }
+
+    #[test]
+    fn test_substitution_chains_file_variables() {
+        let file = parse(FILE);
+        let mut variables = variables(Path::new("/nonexistent-workspace"), &file).unwrap();
+        variables.insert("TOKEN".to_string(), "abc".to_string());
+        let url = substitute(&file.requests[0].url, &variables).unwrap();
+        assert_eq!(url, "http://localhost:8080/api/users?page=2&size=10");
+        assert_eq!(substitute("Bearer {{ TOKEN }}", &variables).unwrap(), "Bearer abc");
+        assert!(substitute("{{missing}}", &variables).unwrap_err().to_string().contains("missing"));
+    }
+}
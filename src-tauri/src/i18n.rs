@@ -0,0 +1,764 @@
+// I18n - Hard-coded user-facing strings and translation catalog gaps
+// Catalogs are JSON, YAML or gettext files; keys are matched against t()/$t()/gettext() calls
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::a11y_rules;
+use crate::syntax;
+use crate::CodeSuggestion;
+
+/// Diagnostics source for the i18n report
+pub const SOURCE: &str = "i18n";
+
+/// Directories that hold translation catalogs
+const CATALOG_DIRS: [&str; 6] = ["locales", "locale", "i18n", "lang", "translations", "messages"];
+
+/// Calls and attributes whose first string is a translation key
+const KEY_MARKERS: [&str; 6] = ["t(", "$t(", "gettext(", "_(", "__(", "i18nKey="];
+
+/// Attributes whose values are shown to users
+const USER_FACING_ATTRIBUTES: [&str; 5] = ["placeholder", "title", "alt", "aria-label", "label"];
+
+/// Elements whose text is not translated
+const SKIPPED_ELEMENTS: [&str; 5] = ["Trans", "code", "pre", "script", "style"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogFormat {
+    Json,
+    Yaml,
+    Gettext,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CatalogSummary {
+    pub file: String,
+    pub locale: String,
+    pub format: CatalogFormat,
+    pub keys: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HardcodedString {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    /// An existing key with the same text, or a new one from the file and the words
+    pub suggested_key: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MissingKey {
+    pub key: String,
+    /// First use of the key, or its first definition when it is unused
+    pub file: String,
+    pub line: usize,
+    /// Locales without a translation
+    pub locales: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UnusedKey {
+    pub key: String,
+    pub file: String,
+    pub line: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct I18nReport {
+    pub locales: Vec<String>,
+    pub catalogs: Vec<CatalogSummary>,
+    pub hardcoded: Vec<HardcodedString>,
+    pub missing: Vec<MissingKey>,
+    pub unused: Vec<UnusedKey>,
+    /// Catalog fragment (JSON) with the new suggested keys
+    pub extraction: String,
+}
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    /// Dotted path for nested catalogs, the msgid for gettext
+    key: String,
+    value: String,
+    line: usize,
+}
+
+struct Usage {
+    key: String,
+    /// Template literals like `errors.${code}` only fix a prefix
+    prefix: bool,
+    line: usize,
+}
+
+/// Whether a workspace-relative path is a translation catalog
+pub fn is_catalog(relative: &str) -> bool {
+    let extension = relative.rsplit('.').next().unwrap_or("");
+    match extension {
+        "po" => true,
+        "json" | "yaml" | "yml" => relative
+            .split('/')
+            .rev()
+            .skip(1)
+            .any(|dir| CATALOG_DIRS.contains(&dir.to_ascii_lowercase().as_str())),
+        _ => false,
+    }
+}
+
+fn is_locale_code(name: &str) -> bool {
+    let (language, region) = match name.split_once(['-', '_']) {
+        Some((language, region)) => (language, Some(region)),
+        None => (name, None),
+    };
+    (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_alphabetic())
+        && region.is_none_or(|r| (2..=4).contains(&r.len()) && r.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// `en` from `locales/en.json`, `locales/en/common.json` or `locale/en/LC_MESSAGES/app.po`
+fn locale_of(file: &str) -> String {
+    let path = Path::new(file);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let directories = path.parent().into_iter().flat_map(|p| p.iter().rev()).filter_map(|c| c.to_str());
+    // gettext keeps the domain in the file name: `<locale>/LC_MESSAGES/<domain>.po`
+    let gettext_domain = path.parent().and_then(|p| p.file_name()).is_some_and(|n| n == "LC_MESSAGES");
+    std::iter::once(stem)
+        .filter(|_| !gettext_domain)
+        .chain(directories)
+        .find(|name| is_locale_code(name))
+        .unwrap_or(stem)
+        .to_string()
+}
+
+/// Leaf entries of a JSON catalog, nested objects joined with dots
+fn json_entries(content: &str) -> Vec<Entry> {
+    let bytes = content.as_bytes();
+    let mut entries = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    // Whether each open container pushed a key onto `path`
+    let mut containers: Vec<bool> = Vec::new();
+    let mut pending: Option<(String, usize)> = None;
+    let (mut i, mut line) = (0, 1);
+    let mut leaf = |path: &[String], key: String, value: String, line: usize| {
+        let key = path.iter().cloned().chain(std::iter::once(key)).collect::<Vec<_>>().join(".");
+        entries.push(Entry { key, value, line });
+    };
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\n' => line += 1,
+            b'"' => {
+                let start = i;
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                let end = (i + 1).min(bytes.len());
+                let text: String = serde_json::from_str(&content[start..end]).unwrap_or_default();
+                let next = content[end..].trim_start();
+                if next.starts_with(':') {
+                    pending = Some((text, line));
+                } else if let Some((key, at)) = pending.take() {
+                    leaf(&path, key, text, at);
+                }
+            }
+            b'{' | b'[' => match pending.take() {
+                Some((key, _)) => {
+                    path.push(key);
+                    containers.push(true);
+                }
+                None => containers.push(false),
+            },
+            b'}' | b']' => {
+                if containers.pop() == Some(true) {
+                    path.pop();
+                }
+            }
+            b',' | b':' => {}
+            c if !c.is_ascii_whitespace() => {
+                let start = i;
+                while i + 1 < bytes.len() && !matches!(bytes[i + 1], b',' | b'}' | b']') && !bytes[i + 1].is_ascii_whitespace() {
+                    i += 1;
+                }
+                if let Some((key, at)) = pending.take() {
+                    leaf(&path, key, content[start..=i].to_string(), at);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    entries
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    match value.chars().next() {
+        Some('"') if value.len() > 1 && value.ends_with('"') => {
+            serde_json::from_str(value).unwrap_or_else(|_| value[1..value.len() - 1].to_string())
+        }
+        Some('\'') if value.len() > 1 && value.ends_with('\'') => value[1..value.len() - 1].replace("''", "'"),
+        _ => value.to_string(),
+    }
+}
+
+/// Mapping entries of a YAML catalog; lists and anchors are not translations and are skipped
+fn yaml_entries(content: &str) -> Vec<Entry> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut entries = Vec::new();
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let raw = lines[i];
+        let trimmed = raw.trim_start();
+        i += 1;
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('-') {
+            continue;
+        }
+        let indent = raw.len() - trimmed.len();
+        let (key, value) = match trimmed.strip_prefix(['"', '\'']) {
+            Some(quoted) => {
+                let quote = trimmed.chars().next().unwrap_or('"');
+                match quoted.split_once(quote) {
+                    Some((key, rest)) => (key.to_string(), rest.trim_start().strip_prefix(':').map(str::trim)),
+                    None => continue,
+                }
+            }
+            None => match trimmed.split_once(':') {
+                Some((key, rest)) => (key.trim().to_string(), Some(rest.trim())),
+                None => continue,
+            },
+        };
+        let value = match value {
+            Some(value) => value,
+            None => continue,
+        };
+        while stack.last().is_some_and(|(level, _)| *level >= indent) {
+            stack.pop();
+        }
+        let line = i;
+        let dotted = stack.iter().map(|(_, k)| k.as_str()).chain(std::iter::once(key.as_str())).collect::<Vec<_>>().join(".");
+        if value.is_empty() {
+            stack.push((indent, key));
+            continue;
+        }
+        let value = if value.starts_with('|') || value.starts_with('>') {
+            // Block scalar: the more indented lines that follow
+            let mut block = Vec::new();
+            while i < lines.len() && (lines[i].trim().is_empty() || lines[i].len() - lines[i].trim_start().len() > indent) {
+                block.push(lines[i].trim());
+                i += 1;
+            }
+            block.join(if value.starts_with('>') { " " } else { "\n" }).trim().to_string()
+        } else if value.starts_with(['"', '\'']) {
+            unquote(value)
+        } else {
+            value.split(" #").next().unwrap_or(value).trim().to_string()
+        };
+        entries.push(Entry { key: dotted, value, line });
+    }
+    entries
+}
+
+fn po_string(text: &str) -> String {
+    let text = text.trim();
+    serde_json::from_str(text).unwrap_or_else(|_| text.trim_matches('"').to_string())
+}
+
+/// msgid/msgstr pairs of a gettext catalog; the header entry is skipped
+fn po_entries(content: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut current: Option<Entry> = None;
+    // Which string a continuation line extends: 0 msgid, 1 msgstr, 2 anything else
+    let mut field = 2;
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("msgid ") {
+            entries.extend(current.take());
+            current = Some(Entry {
+                key: po_string(rest),
+                value: String::new(),
+                line: i + 1,
+            });
+            field = 0;
+        } else if let Some(rest) = line.strip_prefix("msgstr ").or_else(|| line.strip_prefix("msgstr[0] ")) {
+            if let Some(entry) = current.as_mut() {
+                entry.value = po_string(rest);
+            }
+            field = 1;
+        } else if line.starts_with('"') {
+            if let Some(entry) = current.as_mut() {
+                match field {
+                    0 => entry.key.push_str(&po_string(line)),
+                    1 => entry.value.push_str(&po_string(line)),
+                    _ => {}
+                }
+            }
+        } else if !line.is_empty() {
+            field = 2;
+        }
+    }
+    entries.extend(current);
+    entries.retain(|e| !e.key.is_empty());
+    entries
+}
+
+/// Translation keys used in source code
+fn usages(content: &str) -> Vec<Usage> {
+    let mut found = Vec::new();
+    for (at, _) in content.char_indices() {
+        let preceded_by_identifier = content[..at]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$');
+        if preceded_by_identifier {
+            continue;
+        }
+        let marker = match KEY_MARKERS.iter().find(|m| content[at..].starts_with(*m)) {
+            Some(marker) => marker,
+            None => continue,
+        };
+        let rest = &content[at + marker.len()..];
+        let quote = match rest.chars().next() {
+            Some(quote @ ('"' | '\'' | '`')) => quote,
+            _ => continue,
+        };
+        let mut literal = String::new();
+        let mut escaped = false;
+        let mut closed = false;
+        for c in rest[1..].chars() {
+            match c {
+                _ if escaped => {
+                    literal.push(c);
+                    escaped = false;
+                }
+                '\\' => escaped = true,
+                _ if c == quote => {
+                    closed = true;
+                    break;
+                }
+                _ => literal.push(c),
+            }
+        }
+        if !closed || literal.is_empty() {
+            continue;
+        }
+        let (key, prefix) = match literal.split_once("${") {
+            Some((prefix, _)) if quote == '`' => (prefix.to_string(), true),
+            _ => (literal, false),
+        };
+        let (line, _) = a11y_rules::position(content, at);
+        found.push(Usage { key, prefix, line });
+    }
+    found
+}
+
+/// Text a user would read: some letters, and not an identifier, path or markup fragment
+fn is_user_text(text: &str) -> bool {
+    let text = text.trim();
+    let letters = text.chars().filter(|c| c.is_alphabetic()).count();
+    let code_like = !text.contains(' ') && text.contains(['_', '/', '\\', '{', '}', '=', '<', '>', '.']);
+    let entity = text.starts_with('&') && text.ends_with(';');
+    letters >= 2 && !code_like && !entity
+}
+
+/// User text of a markup text node at `offset`, split around `{{ }}` interpolations
+fn text_strings(offset: usize, text: &str, found: &mut Vec<(usize, String)>) {
+    let (mut offset, mut chunk) = (offset, text);
+    loop {
+        let (head, tail) = match chunk.find("{{") {
+            Some(open) => (&chunk[..open], chunk[open..].find("}}").map(|close| open + close + 2)),
+            None => (chunk, None),
+        };
+        if is_user_text(head) {
+            found.push((offset + head.len() - head.trim_start().len(), head.trim().to_string()));
+        }
+        match tail {
+            Some(after) => {
+                offset += after;
+                chunk = &chunk[after..];
+            }
+            None => return,
+        }
+    }
+}
+
+/// Static text and user-facing attribute values of markup between `start` and `end`
+fn markup_strings(content: &str, start: usize, end: usize) -> Vec<(usize, String)> {
+    let mut found = Vec::new();
+    let mut offset = start;
+    while offset < end {
+        let next = content[offset..end].find('<').map(|at| offset + at).unwrap_or(end);
+        text_strings(offset, &content[offset..next], &mut found);
+        if next >= end {
+            break;
+        }
+        let rest = &content[next..end];
+        if rest.starts_with("<!--") {
+            offset = rest.find("-->").map(|at| next + at + 3).unwrap_or(end);
+            continue;
+        }
+        let close = a11y_rules::tag_end(content, next, end);
+        let tag = &content[next..close];
+        for name in USER_FACING_ATTRIBUTES {
+            for quote in ['"', '\''] {
+                let pattern = format!("{}={}", name, quote);
+                for (at, _) in tag.match_indices(&pattern) {
+                    // `:title="expr"` is bound, not literal
+                    if !tag[..at].ends_with(char::is_whitespace) {
+                        continue;
+                    }
+                    let value_start = at + pattern.len();
+                    let value = tag[value_start..].split(quote).next().unwrap_or("");
+                    if is_user_text(value) {
+                        found.push((next + value_start, value.to_string()));
+                    }
+                }
+            }
+        }
+        offset = close + 1;
+        let name: String = tag[1..].chars().take_while(|c| c.is_ascii_alphanumeric()).collect();
+        if SKIPPED_ELEMENTS.contains(&name.to_ascii_lowercase().as_str()) && !tag.ends_with('/') {
+            let closing = format!("</{}", name);
+            offset = content[offset.min(end)..end].find(&closing).map(|at| offset + at).unwrap_or(end);
+        }
+    }
+    found
+}
+
+/// JSX text and user-facing string attributes, outside `<Trans>`, `<code>` and friends
+fn jsx_strings(node: Node, content: &str, found: &mut Vec<(usize, String)>) {
+    match node.kind() {
+        "jsx_element" => {
+            let name = node
+                .child_by_field_name("open_tag")
+                .and_then(|tag| tag.child_by_field_name("name"))
+                .map(|n| &content[n.byte_range()])
+                .unwrap_or("");
+            if SKIPPED_ELEMENTS.contains(&name) {
+                return;
+            }
+        }
+        "jsx_text" => {
+            let text = &content[node.byte_range()];
+            if is_user_text(text) {
+                found.push((node.start_byte() + text.len() - text.trim_start().len(), text.trim().to_string()));
+            }
+            return;
+        }
+        "jsx_attribute" => {
+            let parts = syntax::named_children(node);
+            let name = parts.first().map(|n| &content[n.byte_range()]).unwrap_or("");
+            if let Some(value) = parts.get(1).filter(|v| v.kind() == "string") {
+                let text = content[value.byte_range()].trim_matches(['"', '\'']);
+                if USER_FACING_ATTRIBUTES.contains(&name.to_ascii_lowercase().as_str()) && is_user_text(text) {
+                    found.push((value.start_byte() + 1, text.to_string()));
+                }
+            }
+            return;
+        }
+        _ => {}
+    }
+    for child in syntax::named_children(node) {
+        jsx_strings(child, content, found);
+    }
+}
+
+/// Hard-coded strings of a source file, by byte offset
+fn hardcoded_strings(file: &str, content: &str) -> Vec<(usize, String)> {
+    let extension = file.rsplit('.').next().unwrap_or("");
+    match extension {
+        "html" | "htm" => markup_strings(content, 0, content.len()),
+        "vue" => {
+            let start = content.find("<template").unwrap_or(content.len());
+            let end = content.rfind("</template>").unwrap_or(content.len()).max(start);
+            markup_strings(content, start, end)
+        }
+        "tsx" | "jsx" => match syntax::parse(file, content) {
+            Some(tree) => {
+                let mut found = Vec::new();
+                jsx_strings(tree.root_node(), content, &mut found);
+                found
+            }
+            None => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// `LoginForm.tsx` -> `login_form`
+fn namespace(file: &str) -> String {
+    let stem = Path::new(file).file_stem().and_then(|s| s.to_str()).unwrap_or("app");
+    let mut snake = String::new();
+    let mut previous_lower = false;
+    for c in stem.chars() {
+        if c.is_uppercase() && previous_lower {
+            snake.push('_');
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        snake.push(if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '_' });
+    }
+    snake
+}
+
+fn suggest_key(file: &str, text: &str) -> String {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .take(4)
+        .map(str::to_lowercase)
+        .collect();
+    let slug = if words.is_empty() { "text".to_string() } else { words.join("_") };
+    format!("{}.{}", namespace(file), slug)
+}
+
+fn insert_nested(object: &mut serde_json::Map<String, serde_json::Value>, segments: &[&str], text: &str) {
+    match segments {
+        [] => {}
+        [leaf] => {
+            object.insert(leaf.to_string(), serde_json::Value::String(text.to_string()));
+        }
+        [head, rest @ ..] => {
+            let child = object
+                .entry(head.to_string())
+                .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+            if !child.is_object() {
+                *child = serde_json::Value::Object(serde_json::Map::new());
+            }
+            if let Some(child) = child.as_object_mut() {
+                insert_nested(child, rest, text);
+            }
+        }
+    }
+}
+
+/// Nested JSON object from dotted keys
+fn extraction(keys: &BTreeMap<String, String>) -> String {
+    let mut root = serde_json::Map::new();
+    for (key, text) in keys {
+        insert_nested(&mut root, &key.split('.').collect::<Vec<_>>(), text);
+    }
+    serde_json::to_string_pretty(&serde_json::Value::Object(root)).unwrap_or_default()
+}
+
+/// Report over source files and catalogs, both given as (workspace-relative path, content)
+pub fn report(sources: &[(String, String)], catalogs: &[(String, String)]) -> I18nReport {
+    let parsed: Vec<(&str, String, CatalogFormat, Vec<Entry>)> = catalogs
+        .iter()
+        .map(|(file, content)| {
+            let (format, entries) = match file.rsplit('.').next().unwrap_or("") {
+                "po" => (CatalogFormat::Gettext, po_entries(content)),
+                "yaml" | "yml" => (CatalogFormat::Yaml, yaml_entries(content)),
+                _ => (CatalogFormat::Json, json_entries(content)),
+            };
+            let locale = locale_of(file);
+            // Rails-style catalogs nest everything under the locale
+            let root = format!("{}.", locale);
+            let entries = if !entries.is_empty() && entries.iter().all(|e| e.key.starts_with(&root)) {
+                entries.into_iter().map(|e| Entry { key: e.key[root.len()..].to_string(), ..e }).collect()
+            } else {
+                entries
+            };
+            (file.as_str(), locale, format, entries)
+        })
+        .collect();
+
+    let locales: Vec<String> = parsed.iter().map(|(_, l, _, _)| l.clone()).collect::<BTreeSet<_>>().into_iter().collect();
+    let mut translated: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    let mut by_text: HashMap<&str, &str> = HashMap::new();
+    for (_, locale, _, entries) in &parsed {
+        for entry in entries.iter().filter(|e| !e.value.is_empty()) {
+            translated.entry(locale.as_str()).or_default().insert(entry.key.as_str());
+            by_text.entry(entry.value.as_str()).or_insert(entry.key.as_str());
+        }
+    }
+
+    let mut hardcoded = Vec::new();
+    let mut new_keys: BTreeMap<String, String> = BTreeMap::new();
+    let mut used: BTreeMap<String, (String, usize)> = BTreeMap::new();
+    let mut prefixes: Vec<String> = Vec::new();
+    for (file, content) in sources {
+        for (offset, text) in hardcoded_strings(file, content) {
+            let suggested_key = match by_text.get(text.as_str()) {
+                Some(key) => key.to_string(),
+                None => {
+                    let base = suggest_key(file, &text);
+                    let mut key = base.clone();
+                    let mut n = 2;
+                    while new_keys.get(&key).is_some_and(|existing| *existing != text) {
+                        key = format!("{}_{}", base, n);
+                        n += 1;
+                    }
+                    new_keys.insert(key.clone(), text.clone());
+                    key
+                }
+            };
+            let (line, column) = a11y_rules::position(content, offset);
+            hardcoded.push(HardcodedString {
+                file: file.clone(),
+                line,
+                column,
+                text,
+                suggested_key,
+            });
+        }
+        for usage in usages(content) {
+            if usage.prefix {
+                prefixes.push(usage.key);
+            } else {
+                used.entry(usage.key).or_insert_with(|| (file.clone(), usage.line));
+            }
+        }
+    }
+
+    let mut missing = Vec::new();
+    let mut unused = Vec::new();
+    if !parsed.is_empty() {
+        let mut defined: BTreeMap<&str, (&str, usize)> = BTreeMap::new();
+        for (file, _, _, entries) in &parsed {
+            for entry in entries {
+                defined.entry(entry.key.as_str()).or_insert((file, entry.line));
+                let is_used = used.contains_key(&entry.key) || prefixes.iter().any(|p| entry.key.starts_with(p.as_str()));
+                if !is_used {
+                    unused.push(UnusedKey {
+                        key: entry.key.clone(),
+                        file: file.to_string(),
+                        line: entry.line,
+                    });
+                }
+            }
+        }
+        let keys: BTreeSet<&str> = used.keys().map(String::as_str).chain(defined.keys().copied()).collect();
+        for key in keys {
+            let absent: Vec<String> = locales
+                .iter()
+                .filter(|l| !translated.get(l.as_str()).is_some_and(|keys| keys.contains(key)))
+                .cloned()
+                .collect();
+            if absent.is_empty() {
+                continue;
+            }
+            let (file, line) = match (used.get(key), defined.get(key)) {
+                (Some((file, line)), _) => (file.clone(), *line),
+                (None, Some((file, line))) => (file.to_string(), *line),
+                (None, None) => continue,
+            };
+            missing.push(MissingKey {
+                key: key.to_string(),
+                file,
+                line,
+                locales: absent,
+            });
+        }
+    }
+
+    I18nReport {
+        locales,
+        catalogs: parsed
+            .iter()
+            .map(|(file, locale, format, entries)| CatalogSummary {
+                file: file.to_string(),
+                locale: locale.clone(),
+                format: *format,
+                keys: entries.len(),
+            })
+            .collect(),
+        hardcoded,
+        missing,
+        unused,
+        extraction: extraction(&new_keys),
+    }
+}
+
+impl I18nReport {
+    /// Findings as suggestions, by workspace-relative file
+    pub fn suggestions(&self) -> BTreeMap<String, Vec<CodeSuggestion>> {
+        let mut by_file: BTreeMap<String, Vec<CodeSuggestion>> = BTreeMap::new();
+        let mut add = |file: &str, line: usize, column: usize, severity: &str, message: String, fix: Option<String>| {
+            by_file.entry(file.to_string()).or_default().push(CodeSuggestion {
+                kind: "quality".to_string(),
+                message,
+                line,
+                column,
+                severity: severity.to_string(),
+                fix,
+            });
+        };
+        for string in &self.hardcoded {
+            let message = format!("Hard-coded text \"{}\" - move it to the catalog as `{}`", string.text, string.suggested_key);
+            add(&string.file, string.line, string.column, "info", message, Some(string.suggested_key.clone()));
+        }
+        for key in &self.missing {
+            let message = format!("Translation `{}` is missing for {}", key.key, key.locales.join(", "));
+            add(&key.file, key.line, 0, "warning", message, None);
+        }
+        for key in &self.unused {
+            add(&key.file, key.line, 0, "info", format!("Translation `{}` is never used", key.key), None);
+        }
+        by_file
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_formats() {
+        let json = "{\n  \"nav\": {\n    \"home\": \"Home\",\n    \"about\": \"About \\\"us\\\"\"\n  },\n  \"count\": 3\n}\n";
+        let keys: Vec<(String, String, usize)> = json_entries(json).into_iter().map(|e| (e.key, e.value, e.line)).collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("nav.home".to_string(), "Home".to_string(), 3),
+                ("nav.about".to_string(), "About \"us\"".to_string(), 4),
+                ("count".to_string(), "3".to_string(), 6),
+            ]
+        );
+        let yaml = "de:\n  nav:\n    home: Startseite # top\n    about: 'Über ''uns'''\n  intro: |\n    Hallo\n    Welt\n";
+        let keys: Vec<(String, String)> = yaml_entries(yaml).into_iter().map(|e| (e.key, e.value)).collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("de.nav.home".to_string(), "Startseite".to_string()),
+                ("de.nav.about".to_string(), "Über 'uns'".to_string()),
+                ("de.intro".to_string(), "Hallo\nWelt".to_string()),
+            ]
+        );
+        let po = "msgid \"\"\nmsgstr \"\"\n\"Language: fr\\n\"\n\n#: app.py:3\nmsgid \"Save\"\nmsgstr \"Enregistrer\"\n\nmsgid \"Long \"\n\"text\"\nmsgstr \"\"\n";
+        let keys: Vec<(String, String)> = po_entries(po).into_iter().map(|e| (e.key, e.value)).collect();
+        assert_eq!(keys, vec![("Save".to_string(), "Enregistrer".to_string()), ("Long text".to_string(), String::new())]);
+        assert_eq!(locale_of("locale/fr/LC_MESSAGES/app.po"), "fr");
+        assert!(is_catalog("src/locales/en/common.json") && !is_catalog("src/config.json"));
+    }
+
+    #[test]
+    fn test_report() {
+        let sources = vec![(
+            "src/Nav.vue".to_string(),
+            "<template>\n  <nav :title=\"label\">\n    <a>{{ $t('nav.home') }}</a>\n    <a>Contact us</a>\n    <input placeholder=\"Search\">\n    <span>{{ $t(`errors.${code}`) }}</span>\n  </nav>\n</template>\n".to_string(),
+        )];
+        let catalogs = vec![
+            ("src/locales/en.json".to_string(), "{ \"nav\": { \"home\": \"Home\", \"search\": \"Search\" }, \"errors\": { \"e1\": \"Oops\" } }".to_string()),
+            ("src/locales/de.json".to_string(), "{ \"nav\": { \"home\": \"\" } }".to_string()),
+        ];
+        let report = report(&sources, &catalogs);
+        let hardcoded: Vec<(usize, &str, &str)> =
+            report.hardcoded.iter().map(|h| (h.line, h.text.as_str(), h.suggested_key.as_str())).collect();
+        assert_eq!(hardcoded, vec![(4, "Contact us", "nav.contact_us"), (5, "Search", "nav.search")]);
+        let missing: Vec<(&str, Vec<String>)> = report.missing.iter().map(|m| (m.key.as_str(), m.locales.clone())).collect();
+        assert_eq!(
+            missing,
+            vec![
+                ("errors.e1", vec!["de".to_string()]),
+                ("nav.home", vec!["de".to_string()]),
+                ("nav.search", vec!["de".to_string()]),
+            ]
+        );
+        let unused: Vec<&str> = report.unused.iter().map(|u| u.key.as_str()).collect();
+        assert_eq!(unused, vec!["nav.search"]);
+        assert!(report.extraction.contains("\"contact_us\": \"Contact us\""));
+    }
+}
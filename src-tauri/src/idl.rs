@@ -0,0 +1,626 @@
+// IDL - Protobuf and OpenAPI definitions linked to the generated code that uses them
+// Messages, services and schemas are matched by the names code generators give them in each language
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::file_indexer::{self, FileIndex};
+use crate::sloc;
+
+/// Languages whose identifiers are scanned for generated names
+const CODE_LANGUAGES: [&str; 8] = ["TypeScript", "JavaScript", "Rust", "Python", "Go", "Java", "C", "C++"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IdlKind {
+    Message,
+    Enum,
+    Service,
+    Rpc,
+    Schema,
+    Operation,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IdlDefinition {
+    pub name: String,
+    pub kind: IdlKind,
+    /// Enclosing message or service, or the route of an OpenAPI operation
+    pub container: Option<String>,
+    pub file: String,
+    pub line: usize,
+    /// Field types, rpc request and response messages, `$ref` targets
+    pub references: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IdlUsage {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    /// Generated identifier found in the code
+    pub identifier: String,
+    /// Definition the identifier was generated from
+    pub definition: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct IdlUsages {
+    pub definitions: Vec<IdlDefinition>,
+    /// Messages, rpcs, services and operations that refer to the definitions
+    pub dependents: Vec<IdlDefinition>,
+    pub usages: Vec<IdlUsage>,
+    /// IDL files and code files a change would touch
+    pub affected_files: Vec<String>,
+}
+
+/// Definitions from every IDL file, with the identifiers generated for them
+#[derive(Clone, Default)]
+pub struct IdlIndex {
+    definitions: Vec<IdlDefinition>,
+    /// Generated identifier to definition indexes; the flag is whether only member access counts
+    names: HashMap<String, Vec<(usize, bool)>>,
+}
+
+/// `.proto` files and OpenAPI/Swagger documents
+pub fn is_idl_file(path: &str) -> bool {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path).to_lowercase();
+    let (stem, extension) = name.rsplit_once('.').unwrap_or((&name, ""));
+    extension == "proto"
+        || (matches!(extension, "json" | "yaml" | "yml") && (stem.contains("openapi") || stem.contains("swagger")))
+}
+
+/// Code files whose identifiers may name generated IDL types
+pub fn is_source(extension: &str) -> bool {
+    CODE_LANGUAGES.contains(&FileIndex::detect_language(extension).as_str())
+}
+
+impl IdlDefinition {
+    fn qualified_name(&self) -> String {
+        match &self.container {
+            Some(container) if self.kind != IdlKind::Operation => format!("{}.{}", container, self.name),
+            _ => self.name.clone(),
+        }
+    }
+
+    /// Identifiers code generators emit for the definition, and whether they only count after `.` or `::`
+    fn generated_names(&self) -> Vec<(String, bool)> {
+        let words = file_indexer::split_identifier(&self.name);
+        let snake = words.join("_");
+        match self.kind {
+            IdlKind::Message | IdlKind::Enum | IdlKind::Schema => {
+                let mut names = vec![(self.name.clone(), false)];
+                // Nested messages: `Outer_Inner` in TS, Go and C++
+                if let Some(container) = &self.container {
+                    names.push((format!("{}_{}", container.replace('.', "_"), self.name), false));
+                }
+                names
+            }
+            IdlKind::Service => ["", "Client", "Server", "Stub", "Servicer"]
+                .iter()
+                .map(|suffix| (format!("{}{}", self.name, suffix), false))
+                .chain([(format!("{}_client", snake), false), (format!("{}_server", snake), false)])
+                .collect(),
+            IdlKind::Rpc | IdlKind::Operation => {
+                let camel: String = words
+                    .iter()
+                    .enumerate()
+                    .map(|(i, word)| if i == 0 { word.clone() } else { capitalize(word) })
+                    .collect();
+                let pascal: String = words.iter().map(|word| capitalize(word)).collect();
+                let mut names: Vec<(String, bool)> =
+                    [snake, camel, pascal].into_iter().map(|name| (name, true)).collect();
+                names.dedup();
+                names
+            }
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+/// Last segment of a possibly qualified type name: `google.protobuf.Timestamp`, `#/components/schemas/User`
+fn simple_name(name: &str) -> String {
+    name.rsplit(['.', '/']).next().unwrap_or(name).to_string()
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Punct(char),
+}
+
+/// Protobuf tokens with 1-based lines; comments and strings are dropped
+fn proto_tokens(content: &str) -> Vec<(Token, usize)> {
+    let mut tokens = Vec::new();
+    let mut chars = content.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '"' | '\'' => {
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '\n' => line += 1,
+                        _ if next == c => break,
+                        _ => {}
+                    }
+                }
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek().filter(|n| n.is_alphanumeric() || **n == '_' || **n == '.') {
+                    word.push(next);
+                    chars.next();
+                }
+                tokens.push((Token::Word(word), line));
+            }
+            c if c.is_whitespace() => {}
+            c => tokens.push((Token::Punct(c), line)),
+        }
+    }
+    tokens
+}
+
+fn proto_definitions(file: &str, content: &str) -> Vec<IdlDefinition> {
+    let tokens = proto_tokens(content);
+    let word = |i: usize| match tokens.get(i) {
+        Some((Token::Word(w), _)) => Some(w.as_str()),
+        _ => None,
+    };
+    let punct = |i: usize, c: char| matches!(tokens.get(i), Some((Token::Punct(p), _)) if *p == c);
+    let mut found: Vec<IdlDefinition> = Vec::new();
+    // Open blocks: the definition they belong to, if any, and whether fields inside belong to a message
+    let mut blocks: Vec<(Option<usize>, bool)> = Vec::new();
+    let enclosing = |blocks: &[(Option<usize>, bool)], found: &[IdlDefinition]| {
+        blocks.iter().rev().find_map(|(index, _)| index.map(|i| found[i].qualified_name()))
+    };
+    let mut i = 0;
+    while i < tokens.len() {
+        let line = tokens[i].1;
+        let in_message = blocks.last().is_some_and(|(_, fields)| *fields);
+        match (word(i), word(i + 1)) {
+            (Some(keyword @ ("message" | "enum" | "service")), Some(name)) if punct(i + 2, '{') => {
+                let kind = match keyword {
+                    "message" => IdlKind::Message,
+                    "enum" => IdlKind::Enum,
+                    _ => IdlKind::Service,
+                };
+                found.push(IdlDefinition {
+                    name: name.to_string(),
+                    kind,
+                    container: enclosing(&blocks, &found),
+                    file: file.to_string(),
+                    line,
+                    references: Vec::new(),
+                });
+                blocks.push((Some(found.len() - 1), kind == IdlKind::Message));
+                i += 3;
+                continue;
+            }
+            (Some("rpc"), Some(name)) => {
+                // rpc Name (stream Request) returns (stream Response)
+                let mut references = Vec::new();
+                let mut j = i + 2;
+                while j < tokens.len() && !punct(j, ';') && !punct(j, '{') {
+                    if let Some(w) = word(j).filter(|w| !matches!(*w, "stream" | "returns")) {
+                        references.push(simple_name(w));
+                    }
+                    j += 1;
+                }
+                found.push(IdlDefinition {
+                    name: name.to_string(),
+                    kind: IdlKind::Rpc,
+                    container: enclosing(&blocks, &found),
+                    file: file.to_string(),
+                    line,
+                    references,
+                });
+                i = j;
+                continue;
+            }
+            (Some("oneof"), Some(_)) if punct(i + 2, '{') => {
+                blocks.push((None, true));
+                i += 3;
+                continue;
+            }
+            // Field: `repeated Type name = 1;`, `map<Key, Value> name = 2;`
+            (Some(field_type), Some(_)) if in_message && punct(i + 2, '=') => {
+                if let Some(message) = blocks.iter().rev().find_map(|(index, _)| *index) {
+                    found[message].references.push(simple_name(field_type));
+                }
+            }
+            (Some("map"), _) if in_message && punct(i + 1, '<') => {
+                if let (Some(message), Some(value)) = (blocks.iter().rev().find_map(|(index, _)| *index), word(i + 4)) {
+                    found[message].references.push(simple_name(value));
+                }
+            }
+            _ if punct(i, '{') => blocks.push((None, false)),
+            _ if punct(i, '}') => {
+                blocks.pop();
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    for definition in &mut found {
+        definition.references.sort();
+        definition.references.dedup();
+    }
+    found
+}
+
+/// Schemas and operations of an OpenAPI document, fed one key at a time with its parent keys
+struct OpenApi<'a> {
+    file: &'a str,
+    found: Vec<IdlDefinition>,
+    /// Key path of a schema or operation to its definition index
+    owners: HashMap<String, usize>,
+    /// `$ref` targets by owner key path, attached once the whole document is read
+    references: HashMap<String, Vec<String>>,
+}
+
+impl OpenApi<'_> {
+    fn entry(&mut self, path: &[&str], key: &str, value: Option<&str>, line: usize) {
+        let schema_root = match path {
+            ["components", "schemas", ..] => 2,
+            ["definitions", ..] => 1,
+            _ => 0,
+        };
+        if schema_root > 0 && path.len() == schema_root {
+            self.owners.insert(format!("{}/{}", path.join("/"), key), self.found.len());
+            self.found.push(self.definition(key, IdlKind::Schema, None, line));
+        }
+        match (key, value) {
+            ("operationId", Some(operation)) if path.len() == 3 && path[0] == "paths" => {
+                self.owners.insert(path.join("/"), self.found.len());
+                let route = format!("{} {}", path[2].to_uppercase(), path[1]);
+                self.found.push(self.definition(operation, IdlKind::Operation, Some(route), line));
+            }
+            ("$ref", Some(target)) => {
+                let depth = if schema_root > 0 { schema_root + 1 } else { 3 };
+                if path.len() >= depth && (schema_root > 0 || path[0] == "paths") {
+                    let owner = path[..depth].join("/");
+                    self.references.entry(owner).or_default().push(simple_name(target));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn definition(&self, name: &str, kind: IdlKind, container: Option<String>, line: usize) -> IdlDefinition {
+        IdlDefinition {
+            name: name.to_string(),
+            kind,
+            container,
+            file: self.file.to_string(),
+            line,
+            references: Vec::new(),
+        }
+    }
+
+    fn finish(mut self) -> Vec<IdlDefinition> {
+        for (owner, mut references) in self.references {
+            if let Some(&index) = self.owners.get(&owner) {
+                references.sort();
+                references.dedup();
+                self.found[index].references = references;
+            }
+        }
+        self.found
+    }
+}
+
+/// Walk a YAML document by indentation; list items count as one level deeper than their dash
+fn openapi_yaml(openapi: &mut OpenApi, content: &str) {
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    for (i, raw) in content.lines().enumerate() {
+        let mut line = raw.trim_start();
+        let mut indent = raw.len() - line.len();
+        while let Some(rest) = line.strip_prefix("- ") {
+            let item = rest.trim_start();
+            indent += line.len() - item.len();
+            line = item;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(": ").or_else(|| line.strip_suffix(':').map(|key| (key, ""))) else {
+            continue;
+        };
+        let key = key.trim().trim_matches(['"', '\'']);
+        let value = value.split(" #").next().unwrap_or("").trim().trim_matches(['"', '\'']);
+        while stack.last().is_some_and(|(depth, _)| *depth >= indent) {
+            stack.pop();
+        }
+        let path: Vec<&str> = stack.iter().map(|(_, key)| key.as_str()).collect();
+        openapi.entry(&path, key, Some(value).filter(|v| !v.is_empty()), i + 1);
+        stack.push((indent, key.to_string()));
+    }
+}
+
+fn openapi_json(openapi: &mut OpenApi, content: &str, value: &serde_json::Value, path: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map.iter() {
+                let keys: Vec<&str> = path.iter().map(String::as_str).collect();
+                // serde_json keeps no positions, so the line is that of the first matching key or value
+                let needle = match child.as_str() {
+                    Some(text) => format!("\"{}\"", text),
+                    None => format!("\"{}\"", key),
+                };
+                let line = content.find(&needle).map(|offset| content[..offset].matches('\n').count() + 1).unwrap_or(1);
+                openapi.entry(&keys, key, child.as_str(), line);
+                path.push(key.clone());
+                openapi_json(openapi, content, child, path);
+                path.pop();
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                openapi_json(openapi, content, item, path);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Messages, enums, services and rpcs of a `.proto` file, or schemas and operations of an OpenAPI document
+pub fn definitions(file: &str, content: &str) -> Vec<IdlDefinition> {
+    if file.ends_with(".proto") {
+        return proto_definitions(file, content);
+    }
+    let mut openapi = OpenApi { file, found: Vec::new(), owners: HashMap::new(), references: HashMap::new() };
+    if file.ends_with(".json") {
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(content) {
+            openapi_json(&mut openapi, content, &value, &mut Vec::new());
+        }
+    } else {
+        openapi_yaml(&mut openapi, content);
+    }
+    openapi.finish()
+}
+
+/// Identifiers of a code file with their line, column and whether they follow `.` or `::`
+fn identifiers(file: &str, content: &str) -> Vec<(String, usize, usize, bool)> {
+    let extension = file.rsplit('.').next().unwrap_or("");
+    if !is_source(extension) {
+        return Vec::new();
+    }
+    let language = FileIndex::detect_language(extension);
+    let mut found = Vec::new();
+    for (i, line) in sloc::without_comments(content, &language).iter().enumerate() {
+        let mut start = None;
+        for (column, c) in line.char_indices().chain([(line.len(), ' ')]) {
+            let part_of_word = c.is_alphanumeric() || c == '_';
+            match (start, part_of_word) {
+                (None, true) => start = Some(column),
+                (Some(begin), false) => {
+                    let before = line[..begin].trim_end();
+                    let member = before.ends_with('.') || before.ends_with("::");
+                    found.push((line[begin..column].to_string(), i + 1, begin, member));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    found
+}
+
+impl IdlIndex {
+    /// Replace the definitions of one IDL file
+    pub fn update(&mut self, file: &str, content: &str) {
+        self.definitions.retain(|d| d.file != file);
+        self.definitions.extend(definitions(file, content));
+        self.names.clear();
+        for (index, definition) in self.definitions.iter().enumerate() {
+            for (name, member_only) in definition.generated_names() {
+                self.names.entry(name).or_default().push((index, member_only));
+            }
+        }
+    }
+
+    fn references(&self, file: &str, content: &str) -> Vec<(usize, IdlUsage)> {
+        if self.names.is_empty() || is_idl_file(file) {
+            return Vec::new();
+        }
+        let mut found = Vec::new();
+        for (identifier, line, column, member) in identifiers(file, content) {
+            for &(index, member_only) in self.names.get(&identifier).into_iter().flatten() {
+                if member_only && !member {
+                    continue;
+                }
+                let usage = IdlUsage {
+                    file: file.to_string(),
+                    line,
+                    column,
+                    identifier: identifier.clone(),
+                    definition: self.definitions[index].qualified_name(),
+                };
+                found.push((index, usage));
+            }
+        }
+        found
+    }
+
+    /// IDL files whose messages, services or schemas `content` uses through generated names
+    pub fn files_referenced(&self, file: &str, content: &str) -> HashSet<String> {
+        self.references(file, content)
+            .into_iter()
+            .filter(|(index, _)| !matches!(self.definitions[*index].kind, IdlKind::Rpc | IdlKind::Operation))
+            .map(|(index, _)| self.definitions[index].file.clone())
+            .collect()
+    }
+
+    /// Definitions named `name` (or `Container.name`), what refers to them, and the code using either
+    pub fn usages(&self, name: &str, sources: &[(String, String)]) -> IdlUsages {
+        let target = simple_name(name);
+        let matched: HashSet<usize> = self
+            .definitions
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| d.name == name || d.qualified_name() == name)
+            .map(|(i, _)| i)
+            .collect();
+        let mut dependents: BTreeSet<usize> = BTreeSet::new();
+        for (i, definition) in self.definitions.iter().enumerate() {
+            if !matched.contains(&i) && definition.references.contains(&target) {
+                dependents.insert(i);
+            }
+        }
+        // A changed request or response message changes the service serving the rpc
+        let services: Vec<usize> = dependents
+            .iter()
+            .filter(|&&i| self.definitions[i].kind == IdlKind::Rpc)
+            .filter_map(|&i| {
+                let container = self.definitions[i].container.clone();
+                let serves = |d: &IdlDefinition| d.kind == IdlKind::Service && Some(d.qualified_name()) == container;
+                self.definitions.iter().position(serves)
+            })
+            .collect();
+        dependents.extend(services);
+        dependents.retain(|i| !matched.contains(i));
+
+        let mut report = IdlUsages::default();
+        let mut affected = BTreeSet::new();
+        for (file, content) in sources {
+            for (index, usage) in self.references(file, content) {
+                if matched.contains(&index) || dependents.contains(&index) {
+                    affected.insert(usage.file.clone());
+                    report.usages.push(usage);
+                }
+            }
+        }
+        let mut indexes: Vec<usize> = matched.into_iter().collect();
+        indexes.sort();
+        report.definitions = indexes.iter().map(|&i| self.definitions[i].clone()).collect();
+        report.dependents = dependents.iter().map(|&i| self.definitions[i].clone()).collect();
+        affected.extend(report.definitions.iter().chain(&report.dependents).map(|d| d.file.clone()));
+        report.affected_files = affected.into_iter().collect();
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROTO: &str = concat!(
+        "syntax = \"proto3\";\n",
+        "package users.v1;\n",
+        "// A user profile\n",
+        "message User {\n",
+        "  string id = 1;\n",
+        "  repeated Address addresses = 2;\n",
+        "  message Address { string city = 1; }\n",
+        "}\n",
+        "message GetUserRequest { string id = 1; }\n",
+        "service UserService {\n",
+        "  rpc GetUser(GetUserRequest) returns (User);\n",
+        "  rpc WatchUsers(GetUserRequest) returns (stream User) { option deprecated = true; }\n",
+        "}\n",
+    );
+
+    #[test]
+    fn test_proto_usages_reach_services_and_generated_code() {
+        let mut index = IdlIndex::default();
+        index.update("proto/users.proto", PROTO);
+        let names: Vec<_> = definitions("proto/users.proto", PROTO).iter().map(|d| d.qualified_name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "User",
+                "User.Address",
+                "GetUserRequest",
+                "UserService",
+                "UserService.GetUser",
+                "UserService.WatchUsers",
+            ],
+        );
+
+        let sources = vec![
+            ("server/main.go", "func (s *server) GetUser(r *pb.GetUserRequest) (*pb.User, error) {\n// User\n}\n"),
+            ("client/api.ts", "const client = new UserServiceClient(url);\nclient.getUser(req);\n"),
+            ("py/app.py", "stub = users_pb2_grpc.UserServiceStub(channel)\n"),
+            ("web/other.ts", "const User = 1;\n"),
+        ];
+        let sources: Vec<_> =
+            sources.into_iter().map(|(file, content)| (file.to_string(), content.to_string())).collect();
+        let usages = index.usages("GetUserRequest", &sources);
+        assert_eq!(usages.definitions.len(), 1);
+        let dependents: Vec<_> = usages.dependents.iter().map(|d| d.qualified_name()).collect();
+        assert_eq!(dependents, vec!["UserService", "UserService.GetUser", "UserService.WatchUsers"]);
+        assert_eq!(usages.affected_files, vec!["client/api.ts", "proto/users.proto", "py/app.py", "server/main.go"]);
+        let go: Vec<_> =
+            usages.usages.iter().filter(|u| u.file == "server/main.go").map(|u| u.identifier.as_str()).collect();
+        assert_eq!(go, vec!["GetUserRequest"]);
+
+        let linked = index.files_referenced("web/other.ts", "import { User } from './gen/users';\n");
+        assert_eq!(linked.into_iter().collect::<Vec<_>>(), vec!["proto/users.proto"]);
+        assert!(index.files_referenced("web/other.ts", "getUser();\n").is_empty());
+    }
+
+    #[test]
+    fn test_openapi_yaml_schemas_and_operations() {
+        let spec = concat!(
+            "openapi: 3.0.0\n",
+            "paths:\n",
+            "  /users/{id}:\n",
+            "    get:\n",
+            "      operationId: getUser\n",
+            "      responses:\n",
+            "        '200':\n",
+            "          content:\n",
+            "            application/json:\n",
+            "              schema:\n",
+            "                $ref: '#/components/schemas/User'\n",
+            "components:\n",
+            "  schemas:\n",
+            "    User:\n",
+            "      properties:\n",
+            "        tags:\n",
+            "          items:\n",
+            "            - $ref: \"#/components/schemas/Tag\"\n",
+            "    Tag:\n",
+            "      type: object\n",
+        );
+        assert!(is_idl_file("api/openapi.yaml") && !is_idl_file("config.yaml"));
+        let found: Vec<_> = definitions("api/openapi.yaml", spec)
+            .into_iter()
+            .map(|d| (d.name, d.kind, d.container, d.line, d.references))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("getUser".into(), IdlKind::Operation, Some("GET /users/{id}".into()), 5, vec!["User".to_string()]),
+                ("User".to_string(), IdlKind::Schema, None, 14, vec!["Tag".to_string()]),
+                ("Tag".to_string(), IdlKind::Schema, None, 19, vec![]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,330 @@
+// Import Cost - Estimated bundle size of the packages a JS/TS file imports
+// Sizes are measured on the installed package; minified and gzip sizes come from precomputed ratios
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::imports::{self, ImportLanguage};
+
+/// Node built-in modules, importable without the `node:` prefix
+const NODE_BUILTINS: &[&str] = &[
+    "assert", "async_hooks", "buffer", "child_process", "cluster", "crypto", "dgram", "dns", "events", "fs",
+    "http", "http2", "https", "inspector", "module", "net", "os", "path", "perf_hooks", "process",
+    "querystring", "readline", "stream", "string_decoder", "timers", "tls", "tty", "url", "util", "v8", "vm",
+    "worker_threads", "zlib",
+];
+
+/// Minified size of unminified sources and gzip size of minified code, by extension
+const RATIOS: &[(&str, f64, f64)] = &[
+    ("js", 0.55, 0.32),
+    ("mjs", 0.55, 0.32),
+    ("cjs", 0.55, 0.32),
+    ("css", 0.8, 0.22),
+    ("json", 0.9, 0.25),
+];
+
+/// Directories of a package that never end up in a bundle
+const SKIPPED_DIRS: &[&str] = &["node_modules", "test", "tests", "__tests__", "docs", "example", "examples"];
+
+/// Files measured per package before the estimate stops growing
+const MAX_FILES: usize = 5000;
+/// Levels of `dependencies` followed from an imported package
+const MAX_DEPTH: usize = 4;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ImportCost {
+    pub line: usize,
+    pub module: String,
+    pub package: String,
+    pub version: Option<String>,
+    /// Installed size of the imported code and the dependencies it pulls in
+    pub bytes: u64,
+    pub minified_bytes: u64,
+    pub gzip_bytes: u64,
+    /// Named imports of a side-effect-free ES module package; bundlers can shake off most of the estimate
+    pub tree_shakeable: bool,
+    /// False when the package is not in node_modules; all sizes are then 0
+    pub installed: bool,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Size {
+    bytes: u64,
+    minified: u64,
+    gzip: u64,
+}
+
+impl Size {
+    fn add(&mut self, other: Size) {
+        self.bytes += other.bytes;
+        self.minified += other.minified;
+        self.gzip += other.gzip;
+    }
+}
+
+/// Measured sizes of imported entries, valid while their package manifest is unchanged
+pub struct CostCache {
+    entries: HashMap<PathBuf, (Option<SystemTime>, Size)>,
+}
+
+impl CostCache {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+/// Package name and subpath of a bare specifier; none for relative paths, aliases and Node built-ins
+fn package_of(module: &str, aliases: &[(String, PathBuf)]) -> Option<(String, Option<String>)> {
+    if module.starts_with('.')
+        || module.starts_with('/')
+        || module.starts_with("node:")
+        || module.starts_with("@/")
+        || module.starts_with("~/")
+        || aliases.iter().any(|(alias, _)| module.starts_with(alias.as_str()))
+    {
+        return None;
+    }
+    let segments = if module.starts_with('@') { 2 } else { 1 };
+    let mut parts = module.splitn(segments + 1, '/');
+    let name: Vec<&str> = parts.by_ref().take(segments).collect();
+    if name.len() < segments || (segments == 1 && NODE_BUILTINS.contains(&name[0])) {
+        return None;
+    }
+    Some((name.join("/"), parts.next().filter(|sub| !sub.is_empty()).map(str::to_string)))
+}
+
+/// `node_modules/<package>` visible from `dir` by Node's lookup, up to `workspace`
+fn find_package(workspace: &Path, dir: &Path, package: &str) -> Option<PathBuf> {
+    dir.ancestors()
+        .take_while(|ancestor| ancestor.starts_with(workspace))
+        .map(|ancestor| ancestor.join("node_modules").join(package))
+        .find(|candidate| candidate.join("package.json").is_file())
+}
+
+fn read_manifest(package_dir: &Path) -> serde_json::Value {
+    fs::read_to_string(package_dir.join("package.json"))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// File or directory bundled for `subpath` of the package, or for its entry point without one
+fn entry_of(package_dir: &Path, manifest: &serde_json::Value, subpath: Option<&str>) -> PathBuf {
+    if let Some(subpath) = subpath {
+        let base = package_dir.join(subpath);
+        let candidates = [
+            base.clone(),
+            base.with_extension("js"),
+            base.with_extension("mjs"),
+            base.join("index.js"),
+        ];
+        return candidates.into_iter().find(|c| c.is_file()).unwrap_or(base);
+    }
+    let main = manifest["module"].as_str().or(manifest["main"].as_str()).unwrap_or("index.js");
+    let entry = package_dir.join(main.trim_start_matches("./"));
+    // Builds are shipped in a directory of their own (`dist/`, `lib/esm/`), next to sources and tooling
+    match entry.parent() {
+        Some(dir) if dir != package_dir && dir.starts_with(package_dir) => dir.to_path_buf(),
+        _ => package_dir.to_path_buf(),
+    }
+}
+
+fn file_size(path: &Path) -> Size {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let Some(&(_, minify, gzip)) = RATIOS.iter().find(|(ext, _, _)| *ext == extension) else {
+        return Size::default();
+    };
+    // Manifests are read by the bundler, not bundled
+    if name == "package.json" {
+        return Size::default();
+    }
+    let bytes = fs::metadata(path).map_or(0, |metadata| metadata.len());
+    let minified = if name.contains(".min.") { bytes } else { (bytes as f64 * minify) as u64 };
+    Size {
+        bytes,
+        minified,
+        gzip: (minified as f64 * gzip) as u64,
+    }
+}
+
+/// Size of the code files of `entry`, a file or a build directory
+fn measure(entry: &Path) -> Size {
+    let mut size = Size::default();
+    if entry.is_file() {
+        size.add(file_size(entry));
+        return size;
+    }
+    let files = WalkDir::new(entry)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !SKIPPED_DIRS.contains(&&*e.file_name().to_string_lossy()))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .take(MAX_FILES);
+    for file in files {
+        size.add(file_size(file.path()));
+    }
+    size
+}
+
+fn manifest_modified(package_dir: &Path) -> Option<SystemTime> {
+    fs::metadata(package_dir.join("package.json")).and_then(|m| m.modified()).ok()
+}
+
+/// Size of the package in `package_dir` plus the dependencies it installs, each package counted once
+fn package_size(
+    cache: &mut CostCache,
+    workspace: &Path,
+    package_dir: &Path,
+    subpath: Option<&str>,
+    visited: &mut HashSet<PathBuf>,
+    depth: usize,
+) -> Size {
+    let manifest = read_manifest(package_dir);
+    let entry = entry_of(package_dir, &manifest, subpath);
+    let modified = manifest_modified(package_dir);
+    let mut size = match cache.entries.get(&entry) {
+        Some((cached_at, size)) if *cached_at == modified => *size,
+        _ => {
+            let size = measure(&entry);
+            cache.entries.insert(entry, (modified, size));
+            size
+        }
+    };
+    if depth >= MAX_DEPTH {
+        return size;
+    }
+    // Peer dependencies are the app's own; only `dependencies` come along
+    let dependencies = manifest["dependencies"].as_object().map(|deps| deps.keys().cloned().collect::<Vec<_>>());
+    for dependency in dependencies.unwrap_or_default() {
+        let Some(dir) = find_package(workspace, package_dir, &dependency) else {
+            continue;
+        };
+        if visited.insert(dir.clone()) {
+            size.add(package_size(cache, workspace, &dir, None, visited, depth + 1));
+        }
+    }
+    size
+}
+
+/// `require("pkg")` calls with a literal specifier, as (line, specifier)
+fn requires(content: &str) -> Vec<(usize, String)> {
+    let mut found = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let mut rest = line;
+        while let Some(start) = rest.find("require(") {
+            rest = &rest[start + "require(".len()..];
+            let Some(quote) = rest.chars().next().filter(|c| matches!(c, '\'' | '"' | '`')) else {
+                continue;
+            };
+            if let Some(end) = rest[1..].find(quote) {
+                found.push((i + 1, rest[1..end + 1].to_string()));
+            }
+        }
+    }
+    found
+}
+
+/// Estimated cost of each external import of `path`; type-only imports cost nothing and are left out
+pub fn estimate(cache: &mut CostCache, workspace: &Path, path: &Path, content: &str) -> Vec<ImportCost> {
+    let file = path.to_string_lossy();
+    if ImportLanguage::for_path(&file) != Some(ImportLanguage::TypeScript) {
+        return Vec::new();
+    }
+    let dir = path.parent().unwrap_or(workspace);
+    let aliases = imports::tsconfig_aliases(workspace);
+
+    let mut imported: Vec<(usize, String, bool)> = imports::parse(&file, content)
+        .into_iter()
+        .filter(|import| !import.type_only)
+        .map(|import| {
+            let named = !import.names.is_empty() && import.default.is_none() && import.namespace.is_none();
+            (import.start_line, import.module, named)
+        })
+        .collect();
+    imported.extend(requires(content).into_iter().map(|(line, module)| (line, module, false)));
+    imported.sort_by_key(|(line, _, _)| *line);
+
+    imported
+        .into_iter()
+        .filter_map(|(line, module, named)| {
+            let (package, subpath) = package_of(&module, &aliases)?;
+            let mut cost = ImportCost {
+                line,
+                module,
+                package,
+                version: None,
+                bytes: 0,
+                minified_bytes: 0,
+                gzip_bytes: 0,
+                tree_shakeable: false,
+                installed: false,
+            };
+            let Some(package_dir) = find_package(workspace, dir, &cost.package) else {
+                return Some(cost);
+            };
+            let manifest = read_manifest(&package_dir);
+            let mut visited = HashSet::from([package_dir.clone()]);
+            let size = package_size(cache, workspace, &package_dir, subpath.as_deref(), &mut visited, 0);
+            let es_module = manifest["module"].is_string() || manifest["type"] == "module";
+            cost.version = manifest["version"].as_str().map(str::to_string);
+            cost.bytes = size.bytes;
+            cost.minified_bytes = size.minified;
+            cost.gzip_bytes = size.gzip;
+            cost.tree_shakeable = named && es_module && manifest["sideEffects"] == false;
+            cost.installed = true;
+            Some(cost)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_costs_of_installed_packages_and_their_dependencies() {
+        let root = std::env::temp_dir().join(crate::storage::new_id("import-cost-test"));
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write(
+            "node_modules/chart/package.json",
+            r#"{"name": "chart", "version": "2.1.0", "main": "dist/chart.js", "dependencies": {"color": "1"}}"#,
+        );
+        write("node_modules/chart/dist/chart.js", &"x".repeat(1000));
+        write("node_modules/chart/test/chart.test.js", &"x".repeat(5000));
+        write("node_modules/color/package.json", r#"{"name": "color", "version": "1.0.0"}"#);
+        write("node_modules/color/index.js", &"x".repeat(200));
+        write(
+            "node_modules/@acme/ui/package.json",
+            r#"{"name": "@acme/ui", "module": "esm/index.js", "sideEffects": false}"#,
+        );
+        write("node_modules/@acme/ui/esm/index.js", &"x".repeat(400));
+
+        let content = "import Chart from 'chart';\nimport { Button } from '@acme/ui';\nimport fs from 'fs';\n\
+                       import { helper } from './helper';\nimport type { Props } from 'missing';\n\
+                       const left = require('left-pad');\n";
+        let mut cache = CostCache::new();
+        let costs = estimate(&mut cache, &root, &root.join("src/app.ts"), content);
+        let modules: Vec<&str> = costs.iter().map(|cost| cost.module.as_str()).collect();
+        assert_eq!(modules, vec!["chart", "@acme/ui", "left-pad"]);
+
+        assert_eq!(costs[0].version.as_deref(), Some("2.1.0"));
+        assert_eq!(costs[0].bytes, 1200);
+        assert_eq!(costs[0].minified_bytes, 550 + 110);
+        assert!(!costs[0].tree_shakeable);
+        assert_eq!(costs[1].bytes, 400);
+        assert!(costs[1].tree_shakeable);
+        assert!(!costs[2].installed);
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,921 @@
+// Imports - Parse, render and insert import statements
+// Covers ES modules, Python imports and Rust `use` declarations
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::changeset::{Position, TextEdit};
+use crate::code_analyzer::UnusedImport;
+use crate::code_context;
+use crate::settings::ImportSettings;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportLanguage {
+    TypeScript,
+    Python,
+    Rust,
+}
+
+impl ImportLanguage {
+    pub fn for_path(path: &str) -> Option<Self> {
+        match path.rsplit('.').next().unwrap_or("") {
+            "ts" | "tsx" | "mts" | "cts" | "js" | "jsx" | "mjs" | "cjs" => Some(ImportLanguage::TypeScript),
+            "py" => Some(ImportLanguage::Python),
+            "rs" => Some(ImportLanguage::Rust),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ImportedName {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+impl ImportedName {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            alias: None,
+        }
+    }
+}
+
+/// One import statement of the file header
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Import {
+    /// 1-based inclusive line range
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Module specifier (`./util`, `os.path`, `std::collections`)
+    pub module: String,
+    /// TypeScript default import
+    pub default: Option<String>,
+    /// `* as ns` (TS) or the binding of `import a.b as c` (Python)
+    pub namespace: Option<String>,
+    pub names: Vec<ImportedName>,
+    /// `import 'polyfill'`
+    pub side_effect: bool,
+    /// `import type { ... }`
+    pub type_only: bool,
+    /// `pub use` re-export
+    pub public: bool,
+    /// Constructs we do not rewrite (nested `use` groups, etc.)
+    pub opaque: bool,
+    pub raw: String,
+    /// Quote character of the specifier (TS)
+    pub quote: char,
+    pub semicolon: bool,
+}
+
+impl Import {
+    fn new(module: &str) -> Self {
+        Self {
+            start_line: 0,
+            end_line: 0,
+            module: module.to_string(),
+            default: None,
+            namespace: None,
+            names: Vec::new(),
+            side_effect: false,
+            type_only: false,
+            public: false,
+            opaque: false,
+            raw: String::new(),
+            quote: '\'',
+            semicolon: true,
+        }
+    }
+
+    /// Local identifier bound by one imported name
+    pub fn local_name(&self, name: &ImportedName) -> String {
+        match &name.alias {
+            Some(alias) => alias.clone(),
+            // `use a::b::{self}` binds `b`
+            None if name.name == "self" => self.module.rsplit("::").next().unwrap_or("").to_string(),
+            None => name.name.clone(),
+        }
+    }
+
+    /// Local identifiers introduced by this import
+    pub fn bindings(&self) -> Vec<String> {
+        let mut bindings = Vec::new();
+        bindings.extend(self.default.clone());
+        bindings.extend(self.namespace.clone());
+        for name in &self.names {
+            let local = self.local_name(name);
+            if local != "*" {
+                bindings.push(local);
+            }
+        }
+        bindings
+    }
+
+    /// Canonical source text of the statement
+    pub fn render(&self, language: ImportLanguage) -> String {
+        if self.opaque {
+            return self.raw.clone();
+        }
+        let names = self
+            .names
+            .iter()
+            .map(|n| match &n.alias {
+                Some(alias) => format!("{} as {}", n.name, alias),
+                None => n.name.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        match language {
+            ImportLanguage::TypeScript => {
+                let end = if self.semicolon { ";" } else { "" };
+                let specifier = format!("{}{}{}", self.quote, self.module, self.quote);
+                if self.side_effect {
+                    return format!("import {}{}", specifier, end);
+                }
+                let mut clause = Vec::new();
+                clause.extend(self.default.clone());
+                if let Some(namespace) = &self.namespace {
+                    clause.push(format!("* as {}", namespace));
+                }
+                if !names.is_empty() {
+                    clause.push(format!("{{ {} }}", names.join(", ")));
+                }
+                let kind = if self.type_only { "import type" } else { "import" };
+                format!("{} {} from {}{}", kind, clause.join(", "), specifier, end)
+            }
+            ImportLanguage::Python => {
+                if names.is_empty() {
+                    // `import a.b` binds `a`, which needs no alias
+                    let implicit = self.module.split('.').next().unwrap_or("");
+                    match &self.namespace {
+                        Some(alias) if alias != implicit => format!("import {} as {}", self.module, alias),
+                        _ => format!("import {}", self.module),
+                    }
+                } else {
+                    format!("from {} import {}", self.module, names.join(", "))
+                }
+            }
+            ImportLanguage::Rust => {
+                let visibility = if self.public { "pub " } else { "" };
+                if names.len() == 1 {
+                    format!("{}use {}::{};", visibility, self.module, names[0])
+                } else {
+                    format!("{}use {}::{{{}}};", visibility, self.module, names.join(", "))
+                }
+            }
+        }
+    }
+}
+
+/// Lines that may appear between imports without ending the header
+fn is_header_filler(line: &str, language: ImportLanguage) -> bool {
+    let line = line.trim();
+    if line.is_empty() {
+        return true;
+    }
+    match language {
+        ImportLanguage::TypeScript => {
+            line.starts_with("//")
+                || line.starts_with("/*")
+                || line.starts_with('*')
+                || matches!(line.trim_end_matches(';'), "'use strict'" | "\"use strict\"" | "'use client'" | "\"use client\"")
+        }
+        ImportLanguage::Python => line.starts_with('#'),
+        ImportLanguage::Rust => {
+            line.starts_with("//")
+                || line.starts_with("#[")
+                || line.starts_with("#![")
+                || line.starts_with("extern crate ")
+                || ((line.starts_with("mod ") || line.starts_with("pub mod ")) && line.ends_with(';'))
+        }
+    }
+}
+
+fn is_import_start(line: &str, language: ImportLanguage) -> bool {
+    let line = line.trim_start();
+    match language {
+        ImportLanguage::TypeScript => {
+            (line.starts_with("import ") || line.starts_with("import{") || line.starts_with("import'") || line.starts_with("import\""))
+                && !line.starts_with("import(")
+        }
+        ImportLanguage::Python => line.starts_with("import ") || line.starts_with("from "),
+        ImportLanguage::Rust => line.starts_with("use ") || line.starts_with("pub use ") || line.starts_with("pub(crate) use "),
+    }
+}
+
+/// Whether the accumulated statement text is complete
+fn is_complete(text: &str, language: ImportLanguage) -> bool {
+    let text = text.trim_end();
+    match language {
+        ImportLanguage::TypeScript => {
+            let text = text.trim_end_matches(';').trim_end();
+            (text.ends_with('\'') || text.ends_with('"')) && text.matches(|c: char| c == '\'' || c == '"').count() >= 2
+        }
+        ImportLanguage::Python => {
+            !text.ends_with('\\') && text.matches('(').count() == text.matches(')').count()
+        }
+        ImportLanguage::Rust => text.ends_with(';'),
+    }
+}
+
+fn parse_names(list: &str, separator: &str) -> Vec<ImportedName> {
+    list.split(',')
+        .map(|n| n.trim())
+        .filter(|n| !n.is_empty())
+        .map(|n| match n.split_once(separator) {
+            Some((name, alias)) => ImportedName {
+                name: name.trim().to_string(),
+                alias: Some(alias.trim().to_string()),
+            },
+            None => ImportedName::new(n),
+        })
+        .collect()
+}
+
+fn parse_typescript(text: &str) -> Option<Import> {
+    let semicolon = text.trim_end().ends_with(';');
+    let body = text.trim().trim_end_matches(';').trim();
+    let mut rest = body.strip_prefix("import")?.trim_start();
+    let type_only = rest.starts_with("type ") || rest.starts_with("type{");
+    if type_only {
+        rest = rest[4..].trim_start();
+    }
+
+    let quote = rest.chars().last()?;
+    if quote != '\'' && quote != '"' {
+        return None;
+    }
+    let specifier_start = rest[..rest.len() - 1].rfind(quote)?;
+    let module = &rest[specifier_start + 1..rest.len() - 1];
+    let mut import = Import::new(module);
+    import.quote = quote;
+    import.semicolon = semicolon;
+    import.type_only = type_only;
+
+    let clause = rest[..specifier_start].trim();
+    if clause.is_empty() {
+        import.side_effect = true;
+        return Some(import);
+    }
+    let clause = clause.strip_suffix("from")?.trim();
+
+    let (outside, braces) = match (clause.find('{'), clause.rfind('}')) {
+        (Some(open), Some(close)) if close > open => {
+            (format!("{}{}", &clause[..open], &clause[close + 1..]), Some(&clause[open + 1..close]))
+        }
+        _ => (clause.to_string(), None),
+    };
+    if let Some(braces) = braces {
+        import.names = parse_names(&braces.replace('\n', " "), " as ");
+    }
+    for part in outside.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        match part.strip_prefix("* as ") {
+            Some(namespace) => import.namespace = Some(namespace.trim().to_string()),
+            None => import.default = Some(part.to_string()),
+        }
+    }
+    Some(import)
+}
+
+fn parse_python(text: &str) -> Option<Import> {
+    let body = text.replace("\\\n", " ").replace(['(', ')'], " ");
+    let body = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    if let Some(rest) = body.strip_prefix("from ") {
+        let (module, names) = rest.split_once(" import ")?;
+        let mut import = Import::new(module.trim());
+        import.names = parse_names(names, " as ");
+        return Some(import);
+    }
+    let rest = body.strip_prefix("import ")?;
+    // `import a, b` is split into one import per module by the caller
+    let first = rest.split(',').next()?.trim();
+    let (module, alias) = match first.split_once(" as ") {
+        Some((module, alias)) => (module.trim(), alias.trim().to_string()),
+        None => (first, first.split('.').next().unwrap_or(first).to_string()),
+    };
+    let mut import = Import::new(module);
+    import.namespace = Some(alias);
+    Some(import)
+}
+
+fn parse_rust(text: &str) -> Option<Import> {
+    let body = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let (public, rest) = if let Some(rest) = body.strip_prefix("pub use ") {
+        (true, rest)
+    } else if let Some(rest) = body.strip_prefix("pub(crate) use ") {
+        (true, rest)
+    } else {
+        (false, body.strip_prefix("use ")?)
+    };
+    let path = rest.trim_end_matches(';').trim();
+
+    let mut import;
+    if let Some(open) = path.find("::{") {
+        let inner = path[open + 3..].strip_suffix('}')?;
+        import = Import::new(&path[..open]);
+        if inner.contains('{') {
+            import.opaque = true;
+        } else {
+            import.names = parse_names(inner, " as ");
+        }
+    } else if let Some((module, name)) = path.rsplit_once("::") {
+        import = Import::new(module);
+        import.names = parse_names(name, " as ");
+    } else {
+        // `use serde;` brings a crate into scope
+        import = Import::new(path);
+        import.names.push(ImportedName::new(path));
+        import.opaque = true;
+    }
+    import.public = public;
+    // `pub(crate) use` has no canonical rendering here; keep it verbatim
+    if body.starts_with("pub(") {
+        import.opaque = true;
+    }
+    Some(import)
+}
+
+/// Imports of the file header, in source order
+pub fn parse(path: &str, content: &str) -> Vec<Import> {
+    let language = match ImportLanguage::for_path(path) {
+        Some(language) => language,
+        None => return Vec::new(),
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let mut imports = Vec::new();
+    let mut i = 0;
+    let mut in_docstring = false;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+
+        // Python module docstrings precede the imports
+        if language == ImportLanguage::Python && (in_docstring || (imports.is_empty() && trimmed.starts_with("\"\"\""))) {
+            let quotes = trimmed.matches("\"\"\"").count();
+            in_docstring = if in_docstring { quotes == 0 } else { quotes == 1 };
+            i += 1;
+            continue;
+        }
+        if !is_import_start(line, language) {
+            if is_header_filler(line, language) {
+                i += 1;
+                continue;
+            }
+            break;
+        }
+
+        let start = i;
+        let mut text = line.to_string();
+        while !is_complete(&text, language) && i + 1 < lines.len() {
+            i += 1;
+            text.push('\n');
+            text.push_str(lines[i]);
+        }
+
+        let parsed = match language {
+            ImportLanguage::TypeScript => parse_typescript(&text).into_iter().collect::<Vec<_>>(),
+            ImportLanguage::Rust => parse_rust(&text).into_iter().collect(),
+            ImportLanguage::Python => {
+                let flat = text.trim();
+                match flat.strip_prefix("import ") {
+                    Some(modules) if modules.contains(',') => modules
+                        .split(',')
+                        .filter_map(|m| parse_python(&format!("import {}", m.trim())))
+                        .collect(),
+                    _ => parse_python(&text).into_iter().collect(),
+                }
+            }
+        };
+        let multiple = parsed.len() > 1;
+        for mut import in parsed {
+            import.start_line = start + 1;
+            import.end_line = i + 1;
+            import.raw = text.clone();
+            // Split statements cannot be rewritten one by one
+            import.opaque |= multiple && language != ImportLanguage::Python;
+            imports.push(import);
+        }
+        i += 1;
+    }
+    imports
+}
+
+/// Line after which a new import goes when the file has none
+fn header_end(content: &str, language: ImportLanguage) -> usize {
+    let mut line = 0;
+    for (i, text) in content.lines().enumerate() {
+        let trimmed = text.trim();
+        let is_comment = match language {
+            ImportLanguage::Python => trimmed.starts_with('#'),
+            _ => trimmed.starts_with("//") && !trimmed.starts_with("///"),
+        };
+        if is_comment {
+            line = i + 1;
+        } else {
+            break;
+        }
+    }
+    line
+}
+
+/// Sort key placing std/external/internal groups in order
+pub fn group_rank(module: &str, language: ImportLanguage) -> u8 {
+    match language {
+        ImportLanguage::Rust => {
+            if matches!(module.split("::").next(), Some("std" | "core" | "alloc")) {
+                0
+            } else if matches!(module.split("::").next(), Some("crate" | "super" | "self")) {
+                2
+            } else {
+                1
+            }
+        }
+        ImportLanguage::TypeScript => {
+            if module.starts_with('.') {
+                3
+            } else if module.starts_with("node:") {
+                0
+            } else if module.starts_with("@/") || module.starts_with("~/") {
+                2
+            } else {
+                1
+            }
+        }
+        ImportLanguage::Python => {
+            if module.starts_with('.') {
+                2
+            } else if is_python_stdlib(module) {
+                0
+            } else {
+                1
+            }
+        }
+    }
+}
+
+/// Common standard-library top-level modules
+pub fn is_python_stdlib(module: &str) -> bool {
+    const STDLIB: &[&str] = &[
+        "__future__", "abc", "argparse", "asyncio", "base64", "collections", "contextlib", "copy", "csv",
+        "dataclasses", "datetime", "decimal", "enum", "functools", "glob", "hashlib", "heapq", "http",
+        "importlib", "inspect", "io", "itertools", "json", "logging", "math", "multiprocessing", "os",
+        "pathlib", "pickle", "platform", "queue", "random", "re", "shutil", "signal", "socket", "sqlite3",
+        "string", "struct", "subprocess", "sys", "tempfile", "textwrap", "threading", "time", "traceback",
+        "types", "typing", "unittest", "urllib", "uuid", "warnings", "weakref", "xml", "zipfile",
+    ];
+    STDLIB.contains(&module.split('.').next().unwrap_or(module))
+}
+
+/// Edit adding `name` from `module`: merged into an existing import of the module,
+/// or inserted in sorted position. `None` if already imported.
+pub fn insertion_edit(path: &str, content: &str, module: &str, name: &ImportedName, default: bool) -> Option<TextEdit> {
+    let language = ImportLanguage::for_path(path)?;
+    let imports = parse(path, content);
+    let local = name.alias.clone().unwrap_or_else(|| name.name.clone());
+    if imports.iter().any(|i| i.bindings().contains(&local)) {
+        return None;
+    }
+
+    // Merge into an existing statement for the same module
+    let mergeable = imports.iter().find(|i| {
+        let open = i.module == module && !i.opaque && !i.side_effect && !i.type_only;
+        match language {
+            ImportLanguage::TypeScript if default => open && i.default.is_none(),
+            ImportLanguage::TypeScript => open && i.namespace.is_none(),
+            // `import a` cannot take names; only `from a import ...` can
+            ImportLanguage::Python => open && !i.names.is_empty(),
+            ImportLanguage::Rust => open,
+        }
+    });
+    if let Some(existing) = mergeable {
+        let mut merged = existing.clone();
+        if default && language == ImportLanguage::TypeScript {
+            merged.default = Some(local);
+        } else {
+            merged.names.push(name.clone());
+            merged.names.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        return Some(TextEdit {
+            start: Position { line: existing.start_line, column: 0 },
+            end: Position { line: existing.end_line + 1, column: 0 },
+            new_text: format!("{}\n", merged.render(language)),
+        });
+    }
+
+    let mut import = Import::new(module);
+    if let Some(first) = imports.iter().find(|i| language == ImportLanguage::TypeScript && !i.side_effect) {
+        import.quote = first.quote;
+        import.semicolon = first.semicolon;
+    }
+    if default && language == ImportLanguage::TypeScript {
+        import.default = Some(local);
+    } else {
+        import.names.push(name.clone());
+    }
+    let statement = format!("{}\n", import.render(language));
+
+    let key = (group_rank(module, language), module.to_string());
+    let line = match imports
+        .iter()
+        .find(|i| (group_rank(&i.module, language), i.module.clone()) > key)
+    {
+        Some(next) => next.start_line,
+        None => match imports.last() {
+            Some(last) => last.end_line + 1,
+            None => header_end(content, language) + 1,
+        },
+    };
+    let text = if imports.is_empty() && content.lines().nth(line - 1).map(|l| !l.trim().is_empty()).unwrap_or(false) {
+        // Separate a new import block from the code below
+        format!("{}\n", statement)
+    } else if line > content.lines().count() && !content.is_empty() && !content.ends_with('\n') {
+        format!("\n{}", statement)
+    } else {
+        statement
+    };
+    Some(TextEdit::insert(Position { line, column: 0 }, text))
+}
+
+/// Whether `b` can be folded into `a` as one statement
+fn can_merge(a: &Import, b: &Import, language: ImportLanguage) -> bool {
+    if a.module != b.module || a.public != b.public || a.type_only != b.type_only || a.opaque || b.opaque {
+        return false;
+    }
+    match language {
+        ImportLanguage::TypeScript => {
+            a.namespace.is_none() && b.namespace.is_none() && (a.default.is_none() || b.default.is_none() || a.default == b.default)
+        }
+        // `from a import x` merges; identical `import a` lines are duplicates
+        ImportLanguage::Python => a.names.is_empty() == b.names.is_empty() && (!a.names.is_empty() || a.namespace == b.namespace),
+        ImportLanguage::Rust => true,
+    }
+}
+
+/// Rewrite the import block: drop `unused` bindings, merge duplicates, sort and group.
+/// Side-effect imports keep their relative order at the top; comments inside the block
+/// move above it. `None` if the block is already organized.
+pub fn organize(path: &str, content: &str, settings: &ImportSettings, unused: &[UnusedImport]) -> Option<TextEdit> {
+    let language = ImportLanguage::for_path(path)?;
+    let imports = parse(path, content);
+    let (first, last) = (imports.first()?.start_line, imports.last()?.end_line);
+    let lines: Vec<&str> = content.lines().collect();
+    let import_lines: HashSet<usize> = imports.iter().flat_map(|i| i.start_line..=i.end_line).collect();
+
+    let mut side_effects = Vec::new();
+    let mut organized: Vec<Import> = Vec::new();
+    let mut seen_opaque = HashSet::new();
+    for mut import in imports {
+        if import.side_effect {
+            side_effects.push(import);
+            continue;
+        }
+        if import.opaque {
+            if seen_opaque.insert(import.raw.clone()) {
+                organized.push(import);
+            }
+            continue;
+        }
+
+        if settings.remove_unused {
+            let dead: Vec<&str> = unused
+                .iter()
+                .filter(|u| (import.start_line..=import.end_line).contains(&u.line))
+                .map(|u| u.name.as_str())
+                .collect();
+            if !dead.is_empty() {
+                let names = std::mem::take(&mut import.names);
+                import.names = names.into_iter().filter(|n| !dead.contains(&import.local_name(n).as_str())).collect();
+                if import.default.as_deref().map(|d| dead.contains(&d)).unwrap_or(false) {
+                    import.default = None;
+                }
+                if import.namespace.as_deref().map(|n| dead.contains(&n)).unwrap_or(false) {
+                    import.namespace = None;
+                }
+                if import.bindings().is_empty() && !import.names.iter().any(|n| n.name == "*") {
+                    continue;
+                }
+            }
+        }
+
+        if settings.merge {
+            if let Some(existing) = organized.iter_mut().find(|o| can_merge(o, &import, language)) {
+                existing.names.append(&mut import.names);
+                if existing.default.is_none() {
+                    existing.default = import.default;
+                }
+                continue;
+            }
+        }
+        organized.push(import);
+    }
+
+    for import in &mut organized {
+        import.names.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.alias.cmp(&b.alias)));
+        import.names.dedup();
+    }
+    let group = |import: &Import| if settings.group { group_rank(&import.module, language) } else { 0 };
+    organized.sort_by(|a, b| {
+        group(a)
+            .cmp(&group(b))
+            .then_with(|| a.module.cmp(&b.module))
+            .then_with(|| a.names.is_empty().cmp(&b.names.is_empty()).reverse())
+            .then_with(|| a.type_only.cmp(&b.type_only))
+    });
+
+    let mut text = String::new();
+    for line in (first..=last).filter(|l| !import_lines.contains(l)).map(|l| lines[l - 1]) {
+        if !line.trim().is_empty() {
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    for import in &side_effects {
+        text.push_str(&import.render(language));
+        text.push('\n');
+    }
+    let mut previous_group = None;
+    for import in &organized {
+        let current = group(import);
+        if previous_group.map(|g| g != current).unwrap_or(!side_effects.is_empty()) {
+            text.push('\n');
+        }
+        previous_group = Some(current);
+        text.push_str(&import.render(language));
+        text.push('\n');
+    }
+
+    let mut original = lines[first - 1..last].join("\n");
+    original.push('\n');
+    (text != original).then(|| TextEdit {
+        start: Position { line: first, column: 0 },
+        end: Position { line: last + 1, column: 0 },
+        new_text: text,
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AddImportResult {
+    /// Statement that imports the symbol once the edit is applied
+    pub statement: String,
+    /// File defining the symbol
+    pub source: String,
+    /// `None` if the symbol is already imported
+    pub edit: Option<TextEdit>,
+}
+
+/// Module specifier and name for importing `symbol` from `source` into `file`
+fn import_target(workspace: &Path, file: &str, source: &str, symbol: &str) -> Result<(String, bool)> {
+    let language = ImportLanguage::for_path(file).ok_or_else(|| anyhow!("Unsupported file type: {}", file))?;
+    match language {
+        ImportLanguage::TypeScript => {
+            let default = fs::read_to_string(source)
+                .map(|content| is_default_export(&content, symbol))
+                .unwrap_or(false);
+            Ok((typescript_specifier(workspace, Path::new(file), Path::new(source)), default))
+        }
+        ImportLanguage::Python => python_module_path(workspace, Path::new(source))
+            .map(|module| (module, false))
+            .ok_or_else(|| anyhow!("{} is outside the workspace", source)),
+        ImportLanguage::Rust => rust_module_path(Path::new(source))
+            .map(|module| (module, false))
+            .ok_or_else(|| anyhow!("{} is not part of a Cargo crate", source)),
+    }
+}
+
+/// Pick the defining file of `symbol` among `candidates`, nearest to `file` first
+pub fn find_source(file: &str, symbol: &str, candidates: &[String]) -> Option<String> {
+    let language = ImportLanguage::for_path(file)?;
+    let from = Path::new(file).parent()?;
+    candidates
+        .iter()
+        .filter(|c| c.as_str() != file && ImportLanguage::for_path(c) == Some(language))
+        .filter(|c| {
+            fs::read_to_string(c)
+                .map(|content| content.contains(symbol) && code_context::find_definition(c, &content, symbol).is_some())
+                .unwrap_or(false)
+        })
+        .min_by_key(|c| relative_path(from, Path::new(c)).components().count())
+        .cloned()
+}
+
+/// Import `symbol` from `source` into `file`, merged into the existing import block
+pub fn add_import(workspace: &Path, file: &str, content: &str, source: &str, symbol: &str) -> Result<AddImportResult> {
+    let (module, default) = import_target(workspace, file, source, symbol)?;
+    let edit = insertion_edit(file, content, &module, &ImportedName::new(symbol), default);
+
+    let statement = match &edit {
+        Some(edit) => edit.new_text.trim().to_string(),
+        None => parse(file, content)
+            .into_iter()
+            .find(|i| i.bindings().iter().any(|b| b == symbol))
+            .map(|i| i.raw)
+            .unwrap_or_default(),
+    };
+    Ok(AddImportResult {
+        statement,
+        source: source.to_string(),
+        edit,
+    })
+}
+
+/// Path of `to` relative to the directory `from`
+pub fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &to[common..] {
+        relative.push(component.as_os_str());
+    }
+    relative
+}
+
+/// `paths` aliases from tsconfig.json as (alias prefix, target directory) pairs
+pub fn tsconfig_aliases(workspace: &Path) -> Vec<(String, PathBuf)> {
+    let text = match fs::read_to_string(workspace.join("tsconfig.json")) {
+        Ok(text) => text,
+        Err(_) => return Vec::new(),
+    };
+    // tsconfig allows line comments
+    let stripped: String = text
+        .lines()
+        .filter(|l| !l.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let config: serde_json::Value = match serde_json::from_str(&stripped) {
+        Ok(config) => config,
+        Err(_) => return Vec::new(),
+    };
+    let options = &config["compilerOptions"];
+    let base = workspace.join(options["baseUrl"].as_str().unwrap_or("."));
+
+    let mut aliases = Vec::new();
+    if let Some(paths) = options["paths"].as_object() {
+        for (alias, targets) in paths {
+            let target = match targets.get(0).and_then(|t| t.as_str()) {
+                Some(target) => target,
+                None => continue,
+            };
+            if let (Some(alias), Some(target)) = (alias.strip_suffix('*'), target.strip_suffix('*')) {
+                aliases.push((alias.to_string(), base.join(target)));
+            }
+        }
+    }
+    aliases
+}
+
+fn strip_module_extension(path: &str) -> String {
+    let without = match path.rfind('.') {
+        Some(dot) if !path[dot..].contains('/') => &path[..dot],
+        _ => path,
+    };
+    without.strip_suffix("/index").unwrap_or(without).to_string()
+}
+
+/// ES module specifier for importing `target` from `importer`
+pub fn typescript_specifier(workspace: &Path, importer: &Path, target: &Path) -> String {
+    let from_dir = importer.parent().unwrap_or(workspace);
+    let relative = relative_path(from_dir, target).to_string_lossy().replace('\\', "/");
+    let relative = strip_module_extension(&relative);
+    let relative = if relative.starts_with("../") { relative } else { format!("./{}", relative) };
+
+    // Prefer an alias over climbing out of the importer's directory
+    if relative.starts_with("../") {
+        for (alias, dir) in tsconfig_aliases(workspace) {
+            if let Ok(rest) = target.strip_prefix(&dir) {
+                return format!("{}{}", alias, strip_module_extension(&rest.to_string_lossy().replace('\\', "/")));
+            }
+        }
+        if let Some(package) = workspace_package(workspace, importer, target) {
+            return package;
+        }
+    }
+    relative
+}
+
+/// Name of the workspace package containing `target` if it differs from the importer's
+fn workspace_package(workspace: &Path, importer: &Path, target: &Path) -> Option<String> {
+    let mut dir = target.parent();
+    while let Some(current) = dir {
+        if !current.starts_with(workspace) || current == workspace {
+            return None;
+        }
+        if let Ok(manifest) = fs::read_to_string(current.join("package.json")) {
+            if importer.starts_with(current) {
+                return None;
+            }
+            let package: serde_json::Value = serde_json::from_str(&manifest).ok()?;
+            return package["name"].as_str().map(str::to_string);
+        }
+        dir = current.parent();
+    }
+    None
+}
+
+/// `crate::a::b` module path of a Rust source file
+pub fn rust_module_path(file: &Path) -> Option<String> {
+    // The crate root is the nearest `src` directory below a Cargo.toml
+    let mut src = file.parent();
+    while let Some(dir) = src {
+        if dir.file_name().map(|n| n == "src").unwrap_or(false)
+            && dir.parent().map(|p| p.join("Cargo.toml").is_file()).unwrap_or(false)
+        {
+            break;
+        }
+        src = dir.parent();
+    }
+    let relative = file.strip_prefix(src?).ok()?;
+
+    let mut segments = vec!["crate".to_string()];
+    for component in relative.components() {
+        segments.push(component.as_os_str().to_string_lossy().to_string());
+    }
+    let last = segments.pop()?;
+    let stem = last.trim_end_matches(".rs");
+    if !matches!(stem, "mod" | "lib" | "main") {
+        segments.push(stem.to_string());
+    }
+    Some(segments.join("::"))
+}
+
+/// Dotted module path of a Python file relative to the workspace (or its `src/`)
+pub fn python_module_path(workspace: &Path, file: &Path) -> Option<String> {
+    let src = workspace.join("src");
+    let root = if file.starts_with(&src) { src } else { workspace.to_path_buf() };
+    let relative = file.strip_prefix(&root).ok()?;
+    let mut segments: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    let last = segments.pop()?;
+    let stem = last.trim_end_matches(".py");
+    if stem != "__init__" {
+        segments.push(stem.to_string());
+    }
+    (!segments.is_empty()).then(|| segments.join("."))
+}
+
+/// Whether `target` exports `symbol` as its default export
+pub fn is_default_export(content: &str, symbol: &str) -> bool {
+    content.lines().any(|line| {
+        let line = line.trim();
+        line == format!("export default {};", symbol)
+            || ["function ", "class ", "async function "].iter().any(|kind| {
+                line.strip_prefix("export default ")
+                    .and_then(|rest| rest.strip_prefix(kind))
+                    .map(|rest| rest.starts_with(symbol) && !rest[symbol.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(false)
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_typescript_imports() {
+        let content = "// header\nimport React, { useState as useS } from 'react';\nimport {\n  a,\n  b,\n} from \"./util\";\nimport './polyfill';\n\nconst x = 1;\nimport late from 'late';\n";
+        let imports = parse("app.tsx", content);
+        assert_eq!(imports.len(), 3);
+        assert_eq!(imports[0].default.as_deref(), Some("React"));
+        assert_eq!(imports[0].bindings(), vec!["React", "useS"]);
+        assert_eq!((imports[1].start_line, imports[1].end_line), (3, 6));
+        assert_eq!(imports[1].render(ImportLanguage::TypeScript), "import { a, b } from \"./util\";");
+        assert!(imports[2].side_effect);
+    }
+
+    #[test]
+    fn test_insert_sorted_and_merge() {
+        let content = "use std::fs;\nuse crate::b::B;\n\nfn main() {}\n";
+        let edit = insertion_edit("main.rs", content, "anyhow", &ImportedName::new("Result"), false).unwrap();
+        assert_eq!(edit.start.line, 2);
+        assert_eq!(edit.new_text, "use anyhow::Result;\n");
+
+        let edit = insertion_edit("main.rs", content, "crate::b", &ImportedName::new("A"), false).unwrap();
+        assert_eq!(edit.new_text, "use crate::b::{A, B};\n");
+        assert!(insertion_edit("main.rs", content, "std", &ImportedName::new("fs"), false).is_none());
+    }
+
+    #[test]
+    fn test_organize_merges_groups_and_drops_unused() {
+        let content = "use crate::b::B;\nuse std::fs;\nuse anyhow::Result;\nuse crate::b::A;\nuse std::io;\n\nfn main() {}\n";
+        let unused = vec![UnusedImport { line: 5, name: "io".to_string() }];
+        let edit = organize("main.rs", content, &ImportSettings::default(), &unused).unwrap();
+        assert_eq!((edit.start.line, edit.end.line), (1, 6));
+        assert_eq!(edit.new_text, "use std::fs;\n\nuse anyhow::Result;\n\nuse crate::b::{A, B};\n");
+
+        let organized = "use std::fs;\n\nuse anyhow::Result;\n\nuse crate::b::{A, B};\n\nfn main() {}\n";
+        assert!(organize("main.rs", organized, &ImportSettings::default(), &[]).is_none());
+    }
+}
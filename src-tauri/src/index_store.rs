@@ -0,0 +1,341 @@
+// Index Store - The file index and import edges persisted across sessions
+// Incremental updates go to a write-ahead log; a snapshot or log record that fails its checksum is discarded
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::file_indexer::{FileIndex, FileInfo};
+use crate::mimi_engine::CodeGraph;
+use crate::storage;
+use crate::AppState;
+
+/// Bumped when the snapshot or record layout changes; older stores are discarded
+const STORE_VERSION: u32 = 1;
+const SNAPSHOT_MAGIC: &str = "MIMI-INDEX";
+/// Log size above which the next update writes a fresh snapshot instead
+pub const MAX_WAL_BYTES: u64 = 8 * 1024 * 1024;
+
+/// One indexed file with the words it is found by
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IndexEntry {
+    pub info: FileInfo,
+    pub words: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum WalRecord {
+    /// A file indexed again; `imports` is none when it is not part of the graph
+    Upsert {
+        entry: IndexEntry,
+        imports: Option<Vec<String>>,
+    },
+    Remove {
+        path: String,
+    },
+    /// Ends one update; the records of an update without it are discarded on replay
+    Commit {
+        generation: String,
+        at: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    saved_at: u64,
+    entries: Vec<IndexEntry>,
+    dependencies: Vec<(String, Vec<String>)>,
+}
+
+/// A persisted index brought up to date with its log
+pub struct PersistedIndex {
+    pub entries: Vec<IndexEntry>,
+    pub dependencies: Vec<(String, Vec<String>)>,
+    /// Time of the newest committed update; files modified after it are stale
+    pub saved_at: SystemTime,
+}
+
+/// Serializes writers of the snapshot and the log
+pub struct IndexStore {
+    lock: Mutex<()>,
+}
+
+fn store_dir(workspace: &Path) -> PathBuf {
+    storage::data_dir(workspace).join("index")
+}
+
+fn snapshot_path(workspace: &Path) -> PathBuf {
+    store_dir(workspace).join("snapshot")
+}
+
+fn wal_path(workspace: &Path) -> PathBuf {
+    store_dir(workspace).join("wal.log")
+}
+
+fn checksum(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// A log line: checksum of the record, then the record
+fn wal_line(record: &WalRecord) -> Result<String> {
+    let json = serde_json::to_string(record)?;
+    Ok(format!("{} {}\n", &checksum(json.as_bytes())[..16], json))
+}
+
+fn parse_wal_line(line: &str) -> Option<WalRecord> {
+    let (sum, json) = line.split_once(' ')?;
+    (checksum(json.as_bytes()).get(..16) == Some(sum)).then(|| serde_json::from_str(json).ok())?
+}
+
+/// Header line of a snapshot: format, version, generation and checksum of the body
+fn snapshot_header(generation: &str, body: &str) -> String {
+    format!("{} {} {} {}\n", SNAPSHOT_MAGIC, STORE_VERSION, generation, checksum(body.as_bytes()))
+}
+
+/// Generation of a snapshot header of the current version
+fn header_generation(header: &str) -> Option<&str> {
+    let mut parts = header.trim_end().split(' ');
+    let current = parts.next() == Some(SNAPSHOT_MAGIC) && parts.next() == Some(STORE_VERSION.to_string().as_str());
+    current.then(|| parts.next())?
+}
+
+/// Generation and contents of a snapshot, or none when its header or checksum do not match
+fn parse_snapshot(text: &str) -> Option<(String, Snapshot)> {
+    let (header, body) = text.split_once('\n')?;
+    let generation = header_generation(header)?;
+    if snapshot_header(generation, body).trim_end() != header {
+        return None;
+    }
+    Some((generation.to_string(), serde_json::from_str(body).ok()?))
+}
+
+fn write_synced(path: &Path, content: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    let mut file = File::create(&tmp)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+impl IndexStore {
+    pub fn new() -> Self {
+        Self { lock: Mutex::new(()) }
+    }
+
+    /// Whether updates of `workspace` are logged, i.e. a snapshot exists to replay them onto
+    pub fn exists(&self, workspace: &Path) -> bool {
+        snapshot_path(workspace).exists()
+    }
+
+    pub fn wal_bytes(&self, workspace: &Path) -> u64 {
+        fs::metadata(wal_path(workspace)).map_or(0, |metadata| metadata.len())
+    }
+
+    /// Replace the snapshot with `index` and `graph` and start an empty log
+    pub fn save(&self, workspace: &Path, index: &FileIndex, graph: &CodeGraph) -> Result<()> {
+        let entries =
+            index.entries(|_| true).into_iter().map(|(info, words)| IndexEntry { info, words }).collect();
+        let snapshot = Snapshot {
+            saved_at: storage::now_millis(),
+            entries,
+            dependencies: graph.dependency_lists(|_| true),
+        };
+        let body = serde_json::to_string(&snapshot)?;
+        // Log records of another generation are never replayed onto this snapshot
+        let header = snapshot_header(&storage::new_id("index"), &body);
+
+        let _guard = self.lock.lock().unwrap();
+        fs::create_dir_all(store_dir(workspace))?;
+        write_synced(&snapshot_path(workspace), format!("{}{}", header, body).as_bytes())?;
+        // A crash before this leaves records of the old generation, which replay skips
+        let _ = fs::remove_file(wal_path(workspace));
+        Ok(())
+    }
+
+    /// Log the current state of `paths` as one update; paths gone from the index are logged as removed
+    pub fn append(&self, workspace: &Path, index: &FileIndex, graph: &CodeGraph, paths: &[String]) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let Some(generation) = self.generation(workspace) else {
+            return Ok(());
+        };
+        let entries: HashMap<String, IndexEntry> = index
+            .entries(|path| paths.iter().any(|p| p == path))
+            .into_iter()
+            .map(|(info, words)| (info.path.clone(), IndexEntry { info, words }))
+            .collect();
+        let dependencies: HashMap<String, Vec<String>> =
+            graph.dependency_lists(|path| paths.iter().any(|p| p == path)).into_iter().collect();
+
+        let mut lines = String::new();
+        for path in paths {
+            let record = match entries.get(path) {
+                Some(entry) => WalRecord::Upsert {
+                    entry: entry.clone(),
+                    imports: dependencies.get(path).cloned(),
+                },
+                None => WalRecord::Remove { path: path.clone() },
+            };
+            lines.push_str(&wal_line(&record)?);
+        }
+        lines.push_str(&wal_line(&WalRecord::Commit {
+            generation,
+            at: storage::now_millis(),
+        })?);
+
+        let mut wal = OpenOptions::new().create(true).append(true).open(wal_path(workspace))?;
+        wal.write_all(lines.as_bytes())?;
+        wal.sync_data()?;
+        Ok(())
+    }
+
+    /// Generation of the snapshot, from its header alone
+    fn generation(&self, workspace: &Path) -> Option<String> {
+        let mut header = String::new();
+        BufReader::new(File::open(snapshot_path(workspace)).ok()?).read_line(&mut header).ok()?;
+        header_generation(&header).map(str::to_string)
+    }
+
+    /// Load the snapshot and replay its committed log records. A damaged snapshot discards the store;
+    /// a torn or corrupt log is cut back to its last intact update.
+    pub fn load(&self, workspace: &Path) -> Result<Option<PersistedIndex>> {
+        let _guard = self.lock.lock().unwrap();
+        let text = match fs::read_to_string(snapshot_path(workspace)) {
+            Ok(text) => text,
+            Err(_) => {
+                let _ = fs::remove_file(wal_path(workspace));
+                return Ok(None);
+            }
+        };
+        let Some((generation, snapshot)) = parse_snapshot(&text) else {
+            log::warn!("Discarding the persisted index of {:?}: snapshot failed validation", workspace);
+            fs::remove_dir_all(store_dir(workspace))?;
+            return Ok(None);
+        };
+
+        let mut entries: HashMap<String, IndexEntry> =
+            snapshot.entries.into_iter().map(|entry| (entry.info.path.clone(), entry)).collect();
+        let mut dependencies: HashMap<String, Vec<String>> = snapshot.dependencies.into_iter().collect();
+        let mut saved_at = snapshot.saved_at;
+
+        let wal = fs::read_to_string(wal_path(workspace)).unwrap_or_default();
+        let mut pending: Vec<WalRecord> = Vec::new();
+        let mut intact = 0;
+        let mut offset = 0;
+        for line in wal.split_inclusive('\n') {
+            offset += line.len();
+            let Some(record) = line.strip_suffix('\n').and_then(parse_wal_line) else {
+                break;
+            };
+            let WalRecord::Commit { generation: committed, at } = record else {
+                pending.push(record);
+                continue;
+            };
+            intact = offset;
+            if committed != generation {
+                pending.clear();
+                continue;
+            }
+            for record in pending.drain(..) {
+                match record {
+                    WalRecord::Upsert { entry, imports } => {
+                        let path = entry.info.path.clone();
+                        match imports {
+                            Some(imports) => dependencies.insert(path.clone(), imports),
+                            None => dependencies.remove(&path),
+                        };
+                        entries.insert(path, entry);
+                    }
+                    WalRecord::Remove { path } => {
+                        entries.remove(&path);
+                        dependencies.remove(&path);
+                    }
+                    WalRecord::Commit { .. } => {}
+                }
+            }
+            saved_at = saved_at.max(at);
+        }
+        if intact < wal.len() {
+            log::warn!("Dropping {} bytes of incomplete index log in {:?}", wal.len() - intact, workspace);
+            write_synced(&wal_path(workspace), wal[..intact].as_bytes())?;
+        }
+
+        let mut entries: Vec<IndexEntry> = entries.into_values().collect();
+        entries.sort_by(|a, b| a.info.path.cmp(&b.info.path));
+        let mut dependencies: Vec<(String, Vec<String>)> = dependencies.into_iter().collect();
+        dependencies.sort();
+        Ok(Some(PersistedIndex {
+            entries,
+            dependencies,
+            saved_at: UNIX_EPOCH + Duration::from_millis(saved_at),
+        }))
+    }
+}
+
+/// Log the files just re-indexed in the app state; a log grown past `MAX_WAL_BYTES` becomes a new snapshot
+pub fn record(state: &AppState, workspace: &Path, paths: &[String]) {
+    let store = &state.index_store;
+    if paths.is_empty() || !store.exists(workspace) {
+        return;
+    }
+    let index = state.file_index.lock().unwrap();
+    let graph = state.code_graph.lock().unwrap().clone();
+    let result = if store.wal_bytes(workspace) > MAX_WAL_BYTES {
+        store.save(workspace, &index, &graph)
+    } else {
+        store.append(workspace, &index, &graph, paths)
+    };
+    if let Err(e) = result {
+        log::warn!("Failed to persist index updates of {:?}: {}", workspace, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replays_committed_updates_and_drops_torn_ones() {
+        let root = std::env::temp_dir().join(storage::new_id("index-store-test"));
+        fs::create_dir_all(&root).unwrap();
+        let file = |name: &str, content: &str| {
+            let path = root.join(name);
+            fs::write(&path, content).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let a = file("a.ts", "export const alpha = 1;\n");
+        let store = IndexStore::new();
+        let mut index = FileIndex::new();
+        index.update_files(&root, &[PathBuf::from(&a)]);
+        store.save(&root, &index, &CodeGraph::new()).unwrap();
+
+        let b = file("b.ts", "export const beta = 2;\n");
+        index.update_files(&root, &[PathBuf::from(&b)]);
+        store.append(&root, &index, &CodeGraph::new(), &[b.clone()]).unwrap();
+        // A crash in the middle of the next update leaves a record without its commit
+        let mut wal = OpenOptions::new().append(true).open(wal_path(&root)).unwrap();
+        wal.write_all(wal_line(&WalRecord::Remove { path: a.clone() }).unwrap().as_bytes()).unwrap();
+        wal.write_all(b"0123456789abcdef {\"op\":\"rem").unwrap();
+        drop(wal);
+
+        let loaded = store.load(&root).unwrap().unwrap();
+        let paths: Vec<&str> = loaded.entries.iter().map(|entry| entry.info.path.as_str()).collect();
+        assert_eq!(paths, vec![a.as_str(), b.as_str()]);
+        assert!(loaded.entries[1].words.contains(&"beta".to_string()));
+        assert!(fs::read_to_string(wal_path(&root)).unwrap().ends_with("\n"));
+
+        // A damaged snapshot is discarded rather than trusted
+        let snapshot = fs::read_to_string(snapshot_path(&root)).unwrap();
+        fs::write(snapshot_path(&root), snapshot.replace("alpha", "gamma")).unwrap();
+        assert!(store.load(&root).unwrap().is_none());
+        assert!(!store.exists(&root));
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,775 @@
+// Inline - Inline-variable and inline-function refactorings
+// Substitutes a definition into its usages where that is syntactically safe
+
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use tree_sitter::Node;
+
+use crate::changeset::{self, Changeset, FileChange, Position, TextEdit};
+use crate::refactor::{self, RefactorResult};
+use crate::syntax;
+
+/// Expressions that never need parentheses when substituted
+const ATOMIC_KINDS: &[&str] = &[
+    "identifier", "number", "string", "template_string", "true", "false", "null", "undefined", "this",
+    "call_expression", "member_expression", "subscript_expression", "parenthesized_expression", "array",
+    "object", "regex", "integer_literal", "float_literal", "string_literal", "raw_string_literal",
+    "char_literal", "boolean_literal", "field_expression", "index_expression", "macro_invocation",
+    "tuple_expression", "array_expression", "struct_expression", "scoped_identifier", "unit_expression",
+];
+
+/// Parents in which a substituted expression needs no parentheses
+const SAFE_CONTEXTS: &[&str] = &[
+    "arguments", "variable_declarator", "return_statement", "expression_statement", "array", "pair",
+    "parenthesized_expression", "template_substitution", "jsx_expression", "let_declaration",
+    "return_expression", "block", "array_expression", "field_initializer", "tuple_expression", "match_arm",
+];
+
+/// Expressions whose evaluation may have side effects
+const EFFECT_KINDS: &[&str] = &[
+    "call_expression", "new_expression", "await_expression", "assignment_expression",
+    "augmented_assignment_expression", "compound_assignment_expr", "update_expression",
+    "yield_expression", "macro_invocation",
+];
+
+/// Parents of an identifier that bind a new name rather than reference one
+const BINDING_PARENTS: &[&str] = &[
+    "required_parameter", "optional_parameter", "formal_parameters", "arrow_function", "parameter",
+    "closure_parameters", "variable_declarator", "let_declaration", "for_in_statement", "for_expression",
+    "catch_clause", "array_pattern", "object_pattern", "pair_pattern", "tuple_pattern",
+    "tuple_struct_pattern", "struct_pattern", "field_pattern", "function_declaration", "function_item",
+];
+
+/// Nodes that may evaluate their contents more than once
+const REPEATING_KINDS: &[&str] = &[
+    "for_statement", "for_in_statement", "while_statement", "do_statement", "arrow_function",
+    "function", "function_expression", "method_definition", "for_expression", "while_expression",
+    "loop_expression", "closure_expression",
+];
+
+/// Byte range of `content` and its replacement
+struct Replacement {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+enum Definition<'t> {
+    Variable {
+        statement: Node<'t>,
+        value: Node<'t>,
+        mutable: bool,
+    },
+    Function {
+        statement: Node<'t>,
+        params: Vec<String>,
+        body: Node<'t>,
+    },
+}
+
+impl<'t> Definition<'t> {
+    fn statement(&self) -> Node<'t> {
+        match self {
+            Definition::Variable { statement, .. } | Definition::Function { statement, .. } => *statement,
+        }
+    }
+}
+
+fn text<'a>(node: Node, content: &'a str) -> &'a str {
+    &content[node.byte_range()]
+}
+
+fn contains_kind(node: Node, kinds: &[&str]) -> bool {
+    kinds.contains(&node.kind()) || syntax::named_children(node).into_iter().any(|c| contains_kind(c, kinds))
+}
+
+fn has_child_kind(node: Node, kind: &str) -> bool {
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).any(|c| c.kind() == kind);
+    found
+}
+
+/// Identifier nodes named `name` below `node`
+fn identifiers<'t>(node: Node<'t>, content: &str, name: &str, found: &mut Vec<Node<'t>>) {
+    if matches!(node.kind(), "identifier" | "shorthand_property_identifier") && text(node, content) == name {
+        found.push(node);
+        return;
+    }
+    for child in syntax::named_children(node) {
+        identifiers(child, content, name, found);
+    }
+}
+
+/// Parameter names of a formal parameter list, `None` if any is not a plain identifier
+fn simple_params(params: Node, content: &str) -> Option<Vec<String>> {
+    // Arrow functions may take a single unparenthesized parameter
+    if params.kind() == "identifier" {
+        return Some(vec![text(params, content).to_string()]);
+    }
+    let mut names = Vec::new();
+    for param in syntax::named_children(params) {
+        let pattern = match param.kind() {
+            "identifier" => param,
+            // TS `required_parameter` and Rust `parameter`
+            "required_parameter" | "parameter" if param.child_by_field_name("value").is_none() => {
+                param.child_by_field_name("pattern")?
+            }
+            _ => return None,
+        };
+        if pattern.kind() != "identifier" || has_child_kind(param, "mutable_specifier") {
+            return None;
+        }
+        names.push(text(pattern, content).to_string());
+    }
+    Some(names)
+}
+
+/// The single returned expression of a function body
+fn returned_expression(body: Node) -> Option<Node> {
+    if !matches!(body.kind(), "statement_block" | "block") {
+        return Some(body);
+    }
+    let statements = syntax::named_children(body);
+    let only = match statements.as_slice() {
+        [only] => *only,
+        _ => return None,
+    };
+    match only.kind() {
+        "return_statement" => only.named_child(0),
+        "expression_statement" => {
+            let inner = only.named_child(0)?;
+            if inner.kind() == "return_expression" {
+                inner.named_child(0)
+            } else {
+                None
+            }
+        }
+        // Rust tail expression
+        kind if body.kind() == "block" && !matches!(kind, "let_declaration" | "empty_statement") && !kind.ends_with("_item") => {
+            Some(only)
+        }
+        _ => None,
+    }
+}
+
+fn function_definition<'t>(statement: Node<'t>, params: Node<'t>, body: Node<'t>, content: &str) -> Option<Definition<'t>> {
+    Some(Definition::Function {
+        statement,
+        params: simple_params(params, content)?,
+        body: returned_expression(body)?,
+    })
+}
+
+/// Declaration of `name` made by `node`; `Err` for declarations that cannot be inlined
+fn definition_at<'t>(node: Node<'t>, content: &str, name: &str) -> Option<Result<Definition<'t>>> {
+    let declares = |field: &str| node.child_by_field_name(field).map(|n| text(n, content) == name).unwrap_or(false);
+    let unsupported = |what: &str| Some(Err(anyhow!("Cannot inline {}: {}", name, what)));
+
+    match node.kind() {
+        "variable_declarator" if declares("name") => {
+            let statement = node.parent()?;
+            if syntax::named_children(statement).len() != 1 {
+                return unsupported("declared together with other variables");
+            }
+            let value = match node.child_by_field_name("value") {
+                Some(value) => value,
+                None => return unsupported("the variable has no initializer"),
+            };
+            if value.kind() == "arrow_function" {
+                let params = value
+                    .child_by_field_name("parameters")
+                    .or_else(|| value.child_by_field_name("parameter"))?;
+                let body = value.child_by_field_name("body")?;
+                if has_child_kind(value, "async") {
+                    return unsupported("async functions cannot be inlined");
+                }
+                return Some(function_definition(statement, params, body, content).ok_or_else(|| {
+                    anyhow!("Cannot inline {}: only single-expression functions with plain parameters", name)
+                }));
+            }
+            let mutable = !has_child_kind(statement, "const");
+            Some(Ok(Definition::Variable { statement, value, mutable }))
+        }
+        "let_declaration" if declares("pattern") => {
+            let value = match node.child_by_field_name("value") {
+                Some(value) => value,
+                None => return unsupported("the variable has no initializer"),
+            };
+            if has_child_kind(node, "mutable_specifier") {
+                return unsupported("the binding is mutable");
+            }
+            Some(Ok(Definition::Variable {
+                statement: node,
+                value,
+                mutable: false,
+            }))
+        }
+        "function_declaration" | "function_item" if declares("name") => {
+            if has_child_kind(node, "async") || has_child_kind(node, "function_modifiers") {
+                return unsupported("async and qualified functions cannot be inlined");
+            }
+            let params = node.child_by_field_name("parameters")?;
+            let body = node.child_by_field_name("body")?;
+            Some(function_definition(node, params, body, content).ok_or_else(|| {
+                anyhow!("Cannot inline {}: only single-expression functions with plain parameters", name)
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// Declaring nodes of `name` with their definitions
+fn collect_definitions<'t>(node: Node<'t>, content: &str, name: &str, found: &mut Vec<(Node<'t>, Result<Definition<'t>>)>) {
+    if let Some(definition) = definition_at(node, content, name) {
+        found.push((node, definition));
+    }
+    for child in syntax::named_children(node) {
+        collect_definitions(child, content, name, found);
+    }
+}
+
+/// Node whose extent a declaration is visible in
+fn declaration_scope(node: Node) -> Option<Node> {
+    let statement = if node.kind() == "variable_declarator" { node.parent()? } else { node };
+    statement.parent()
+}
+
+fn is_exported(statement: Node) -> bool {
+    statement.parent().map(|p| p.kind() == "export_statement").unwrap_or(false)
+        || has_child_kind(statement, "visibility_modifier")
+}
+
+fn is_atomic(node: Node) -> bool {
+    ATOMIC_KINDS.contains(&node.kind())
+}
+
+/// Whether `node` can be replaced by a compound expression without parentheses
+fn in_safe_context(node: Node) -> bool {
+    node.parent().map(|p| SAFE_CONTEXTS.contains(&p.kind())).unwrap_or(true)
+}
+
+/// Substitution of `value` for the identifier `usage`
+fn substitution(usage: Node, name: &str, value: &str, value_node: Node) -> Replacement {
+    let parent = usage.parent();
+    // `{ x }` becomes `{ x: value }`
+    if usage.kind() == "shorthand_property_identifier" {
+        return Replacement {
+            start: usage.start_byte(),
+            end: usage.end_byte(),
+            text: format!("{}: {}", name, value),
+        };
+    }
+    if let Some(parent) = parent.filter(|p| p.kind() == "shorthand_field_initializer") {
+        return Replacement {
+            start: parent.start_byte(),
+            end: parent.end_byte(),
+            text: format!("{}: {}", name, value),
+        };
+    }
+    // An object literal at statement start would parse as a block
+    let statement_object = value_node.kind() == "object" && parent.map(|p| p.kind() == "expression_statement").unwrap_or(false);
+    let text = if (is_atomic(value_node) || in_safe_context(usage)) && !statement_object {
+        value.to_string()
+    } else {
+        format!("({})", value)
+    };
+    Replacement {
+        start: usage.start_byte(),
+        end: usage.end_byte(),
+        text,
+    }
+}
+
+fn is_assignment_target(usage: Node) -> bool {
+    let parent = match usage.parent() {
+        Some(parent) => parent,
+        None => return false,
+    };
+    match parent.kind() {
+        "assignment_expression" | "augmented_assignment_expression" | "compound_assignment_expr" => {
+            parent.child_by_field_name("left").map(|l| l.id() == usage.id()).unwrap_or(false)
+        }
+        "update_expression" => true,
+        _ => false,
+    }
+}
+
+/// Whether `node` sits in a loop or nested function below `scope`
+fn repeats_within(node: Node, scope: Node) -> bool {
+    let mut current = node.parent();
+    while let Some(ancestor) = current {
+        if ancestor.id() == scope.id() {
+            return false;
+        }
+        if REPEATING_KINDS.contains(&ancestor.kind()) {
+            return true;
+        }
+        current = ancestor.parent();
+    }
+    false
+}
+
+/// Byte range removing `statement` together with its line if it stands alone
+fn removal(content: &str, start: usize, end: usize) -> Replacement {
+    let line_start = content[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = content[end..].find('\n').map(|i| end + i + 1).unwrap_or(content.len());
+    let alone = content[line_start..start].trim().is_empty() && content[end..line_end].trim().is_empty();
+    let (start, mut end) = if alone { (line_start, line_end) } else { (start, end) };
+
+    // Collapse the blank line pair the removal would leave behind
+    let blank_before = content[..start].ends_with("\n\n") || start == 0;
+    if alone && blank_before {
+        if let Some(next) = content[end..].find('\n') {
+            if content[end..end + next].trim().is_empty() {
+                end += next + 1;
+            }
+        }
+    }
+    Replacement {
+        start,
+        end,
+        text: String::new(),
+    }
+}
+
+fn inline_tree(root: Node, content: &str, offset: usize) -> Result<(String, Vec<Replacement>)> {
+    let at = |offset: usize| {
+        root.descendant_for_byte_range(offset, offset)
+            .filter(|n| matches!(n.kind(), "identifier" | "shorthand_property_identifier"))
+    };
+    let target = at(offset)
+        .or_else(|| offset.checked_sub(1).and_then(at))
+        .ok_or_else(|| anyhow!("No identifier at the cursor"))?;
+    let name = text(target, content).to_string();
+
+    let mut definitions = Vec::new();
+    collect_definitions(root, content, &name, &mut definitions);
+    let contains = |outer: Node, inner: Node| outer.start_byte() <= inner.start_byte() && inner.end_byte() <= outer.end_byte();
+    // The innermost definition in scope of the cursor, preferring earlier declarations
+    let chosen = definitions
+        .iter()
+        .enumerate()
+        .filter(|(_, (node, _))| {
+            let scope = declaration_scope(*node).unwrap_or(root);
+            scope.start_byte() <= offset && offset <= scope.end_byte()
+        })
+        .max_by_key(|(_, (node, _))| (node.start_byte() <= offset, node.start_byte()))
+        .map(|(i, _)| i)
+        .ok_or_else(|| anyhow!("No inlinable definition of {} found", name))?;
+    let (declaring, definition) = definitions.swap_remove(chosen);
+    let definition = definition?;
+    let statement = definition.statement();
+    let scope = declaration_scope(declaring).unwrap_or(root);
+    if is_exported(statement) {
+        return Err(anyhow!("Cannot inline {}: it is exported", name));
+    }
+
+    let mut usages = Vec::new();
+    identifiers(scope, content, &name, &mut usages);
+    usages.retain(|u| !contains(statement, *u));
+    let is_variable = matches!(definition, Definition::Variable { .. });
+    if is_variable && usages.iter().any(|u| u.start_byte() < statement.start_byte()) {
+        return Err(anyhow!("Cannot inline {}: it is referenced before its declaration", name));
+    }
+    let shadowed = definitions.iter().any(|(node, _)| contains(scope, *node))
+        || usages.iter().any(|u| u.parent().map(|p| BINDING_PARENTS.contains(&p.kind())).unwrap_or(false));
+    if shadowed {
+        return Err(anyhow!("Cannot inline {}: the name is shadowed or redeclared", name));
+    }
+
+    let mut replacements = Vec::new();
+    match definition {
+        Definition::Variable { value, mutable, .. } => {
+            if mutable && usages.iter().any(|u| is_assignment_target(*u)) {
+                return Err(anyhow!("Cannot inline {}: the variable is reassigned", name));
+            }
+            if contains_kind(value, EFFECT_KINDS)
+                && (usages.len() != 1 || usages.iter().any(|u| repeats_within(*u, scope)))
+            {
+                return Err(anyhow!("Cannot inline {}: its value has side effects", name));
+            }
+            let value_text = text(value, content);
+            for usage in &usages {
+                replacements.push(substitution(*usage, &name, value_text, value));
+            }
+        }
+        Definition::Function { params, body, .. } => {
+            let mut own = Vec::new();
+            identifiers(body, content, &name, &mut own);
+            if !own.is_empty() {
+                return Err(anyhow!("Cannot inline {}: the function is recursive", name));
+            }
+            if contains_kind(body, &["this"]) {
+                return Err(anyhow!("Cannot inline {}: the function uses `this`", name));
+            }
+            for usage in &usages {
+                let call = usage
+                    .parent()
+                    .filter(|p| p.kind() == "call_expression")
+                    .filter(|p| p.child_by_field_name("function").map(|f| f.id() == usage.id()).unwrap_or(false))
+                    .ok_or_else(|| anyhow!("Cannot inline {}: it is referenced without being called", name))?;
+                replacements.push(inline_call(call, content, &name, &params, body)?);
+            }
+        }
+    }
+
+    let nested = replacements
+        .iter()
+        .any(|a| replacements.iter().any(|b| a.start < b.start && b.end <= a.end));
+    if nested {
+        return Err(anyhow!("Cannot inline {}: calls are nested in each other's arguments", name));
+    }
+    replacements.push(removal(content, statement.start_byte(), statement.end_byte()));
+    Ok((name, replacements))
+}
+
+/// The body of a function with the arguments of `call` substituted
+fn inline_call(call: Node, content: &str, name: &str, params: &[String], body: Node) -> Result<Replacement> {
+    let arguments = call
+        .child_by_field_name("arguments")
+        .map(syntax::named_children)
+        .unwrap_or_default();
+    if arguments.len() != params.len() || arguments.iter().any(|a| a.kind() == "spread_element") {
+        return Err(anyhow!("Cannot inline {}: a call does not pass exactly {} arguments", name, params.len()));
+    }
+
+    let mut substitutions = Vec::new();
+    for (param, argument) in params.iter().zip(&arguments) {
+        let mut occurrences = Vec::new();
+        identifiers(body, content, param, &mut occurrences);
+        if occurrences.iter().any(|o| o.parent().map(|p| BINDING_PARENTS.contains(&p.kind())).unwrap_or(false)) {
+            return Err(anyhow!("Cannot inline {}: parameter {} is shadowed in the body", name, param));
+        }
+        if contains_kind(*argument, EFFECT_KINDS) && occurrences.len() != 1 {
+            return Err(anyhow!("Cannot inline {}: an argument with side effects is not used exactly once", name));
+        }
+        for occurrence in occurrences {
+            substitutions.push(substitution(occurrence, param, text(*argument, content), *argument));
+        }
+    }
+
+    let mut expression = text(body, content).to_string();
+    substitutions.sort_by_key(|s| std::cmp::Reverse(s.start));
+    for s in substitutions {
+        expression.replace_range(s.start - body.start_byte()..s.end - body.start_byte(), &s.text);
+    }
+    let text = if is_atomic(body) || in_safe_context(call) {
+        expression
+    } else {
+        format!("({})", expression)
+    };
+    Ok(Replacement {
+        start: call.start_byte(),
+        end: call.end_byte(),
+        text,
+    })
+}
+
+/// Byte ranges of the lines of `content` as (start, end without newline, indent)
+fn python_lines(content: &str) -> Vec<(usize, usize, usize)> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let body = line.trim_end_matches(['\n', '\r']);
+        lines.push((offset, offset + body.len(), body.len() - body.trim_start().len()));
+        offset += line.len();
+    }
+    lines
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Whether `text` needs parentheses inside a larger Python expression
+fn python_atomic(text: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quote = None;
+    for c in text.trim().chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '\'' | '"' => quote = Some(c),
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ if depth == 0 && " +-*/%<>=!&|^~@,:".contains(c) => return false,
+                _ => {}
+            },
+        }
+    }
+    true
+}
+
+/// Whether the text around `start..end` on a line lets a compound expression stand unparenthesized
+fn python_safe_context(line: &str, start: usize, end: usize) -> bool {
+    let before = line[..start].trim_end();
+    let after = line[end..].trim_start();
+    let opens = before.ends_with(['(', ',', '[', '{'])
+        || before.ends_with("return")
+        || (before.ends_with('=') && !before.ends_with("==") && !before.ends_with("!=") && !before.ends_with("<=") && !before.ends_with(">="));
+    opens && (after.is_empty() || after.starts_with([')', ',', ']', '}']))
+}
+
+/// Word occurrences of `name` in `content[from..to]` that reference it, with their line
+fn python_references(content: &str, name: &str, from: usize, to: usize) -> Result<Vec<usize>> {
+    let mut found = Vec::new();
+    for (index, _) in content[from..to].match_indices(name) {
+        let start = from + index;
+        let end = start + name.len();
+        let before = content[..start].chars().last();
+        let after = content[end..].chars().next();
+        if before.map(|c| is_word_char(c) || c == '.').unwrap_or(false) || after.map(is_word_char).unwrap_or(false) {
+            continue;
+        }
+        let line_start = content[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let prefix = &content[line_start..start];
+        if prefix.contains('#') {
+            continue;
+        }
+        if prefix.matches(['\'', '"']).count() % 2 == 1 {
+            if prefix.contains("f\"") || prefix.contains("f'") {
+                return Err(anyhow!("Cannot inline {}: it is used inside an f-string", name));
+            }
+            continue;
+        }
+        // Keyword arguments share the name without referencing it
+        let rest = content[end..].trim_start();
+        if rest.starts_with('=') && !rest.starts_with("==") {
+            continue;
+        }
+        found.push(start);
+    }
+    Ok(found)
+}
+
+fn python_substitute(expression: &str, params: &[String], arguments: &[String]) -> String {
+    let mut result = String::new();
+    let mut word = String::new();
+    let flush = |word: &mut String, result: &mut String, next: Option<char>| {
+        let position = params.iter().position(|p| p == word.as_str());
+        match position {
+            // Attribute names and keyword arguments are left alone
+            Some(i) if !result.ends_with('.') && next != Some('=') => {
+                let argument = &arguments[i];
+                if python_atomic(argument) {
+                    result.push_str(argument);
+                } else {
+                    result.push_str(&format!("({})", argument));
+                }
+            }
+            _ => result.push_str(word),
+        }
+        word.clear();
+    };
+    for c in expression.chars() {
+        if is_word_char(c) {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut result, Some(c));
+            result.push(c);
+        }
+    }
+    flush(&mut word, &mut result, None);
+    result
+}
+
+fn inline_python(content: &str, offset: usize) -> Result<(String, Vec<Replacement>)> {
+    let start = content[..offset].rfind(|c: char| !is_word_char(c)).map(|i| i + 1).unwrap_or(0);
+    let end = content[offset..].find(|c: char| !is_word_char(c)).map(|i| offset + i).unwrap_or(content.len());
+    let name = &content[start..end];
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(anyhow!("No identifier at the cursor"));
+    }
+
+    let lines = python_lines(content);
+    let line_of = |offset: usize| lines.iter().rposition(|l| l.0 <= offset).unwrap_or(0);
+    let cursor_line = line_of(offset);
+    // End of the block containing line `i`, which is indented by `indent`
+    let block_end = |i: usize, indent: usize| {
+        lines
+            .iter()
+            .skip(i + 1)
+            .find(|l| l.1 > l.0 + l.2 && l.2 < indent)
+            .map(|l| l.0)
+            .unwrap_or(content.len())
+    };
+
+    // Nearest declaration at or above the cursor in an enclosing block
+    let mut declaration = None;
+    for i in (0..lines.len()).rev() {
+        let (line_start, line_end, indent) = lines[i];
+        let line = &content[line_start + indent..line_end];
+        let is_variable = line
+            .strip_prefix(name)
+            .map(|rest| rest.trim_start().starts_with('=') && !rest.trim_start().starts_with("=="))
+            .unwrap_or(false);
+        let is_function = line.starts_with(&format!("def {}(", name));
+        // Functions are visible throughout their block, variables from their assignment on
+        if (is_variable || is_function) && block_end(i, indent) > offset && (i <= cursor_line || is_function) {
+            declaration = Some((i, is_function));
+            break;
+        }
+    }
+    let (line_index, is_function) = declaration.ok_or_else(|| anyhow!("No inlinable definition of {} found", name))?;
+    let (line_start, line_end, indent) = lines[line_index];
+    let declaration_line = &content[line_start + indent..line_end];
+
+    let mut replacements = Vec::new();
+    if is_function {
+        if line_index > 0 && content[lines[line_index - 1].0..lines[line_index - 1].1].trim_start().starts_with('@') {
+            return Err(anyhow!("Cannot inline {}: it is decorated", name));
+        }
+        let params_text = declaration_line
+            .strip_prefix(&format!("def {}(", name))
+            .and_then(|rest| rest.split_once(')'))
+            .map(|(params, _)| params)
+            .ok_or_else(|| anyhow!("Cannot inline {}: the signature spans several lines", name))?;
+        let params: Vec<String> = refactor::split_arguments(params_text)
+            .iter()
+            .map(|p| p.split(':').next().unwrap_or("").trim().to_string())
+            .collect();
+        if params.iter().any(|p| p.is_empty() || p.contains(['=', '*']) || p == "self") {
+            return Err(anyhow!("Cannot inline {}: only plain positional parameters are supported", name));
+        }
+
+        let body: Vec<usize> = (line_index + 1..lines.len())
+            .take_while(|&j| lines[j].1 == lines[j].0 + lines[j].2 || lines[j].2 > indent)
+            .filter(|&j| lines[j].1 > lines[j].0 + lines[j].2)
+            .collect();
+        let expression = match body.as_slice() {
+            [only] => content[lines[*only].0 + lines[*only].2..lines[*only].1]
+                .strip_prefix("return ")
+                .map(str::trim)
+                .ok_or_else(|| anyhow!("Cannot inline {}: the body is not a single return", name))?,
+            _ => return Err(anyhow!("Cannot inline {}: the body is not a single return", name)),
+        };
+        if !python_references(expression, name, 0, expression.len())?.is_empty() {
+            return Err(anyhow!("Cannot inline {}: the function is recursive", name));
+        }
+        let function_end = body.last().map(|&j| lines[j].1).unwrap_or(line_end);
+
+        let scope_start = (0..line_index).rev().find(|&j| lines[j].1 > lines[j].0 + lines[j].2 && lines[j].2 < indent).map(|j| lines[j].1).unwrap_or(0);
+        let scope_end = block_end(line_index, indent);
+        let mut references = python_references(content, name, scope_start, scope_end)?;
+        references.retain(|&r| r < line_start || r > function_end);
+        for reference in references {
+            let open = reference + name.len();
+            if !content[open..].starts_with('(') {
+                return Err(anyhow!("Cannot inline {}: it is referenced without being called", name));
+            }
+            let close = refactor::closing_bracket(content, open).ok_or_else(|| anyhow!("Unbalanced call of {}", name))?;
+            let arguments = refactor::split_arguments(&content[open + 1..close - 1]);
+            if arguments.len() != params.len() || arguments.iter().any(|a| a.starts_with('*')) {
+                return Err(anyhow!("Cannot inline {}: a call does not pass exactly {} arguments", name, params.len()));
+            }
+            if arguments.iter().any(|a| !a.starts_with(['\'', '"']) && a.contains('=') && !a.contains("==")) {
+                return Err(anyhow!("Cannot inline {}: keyword arguments are not supported", name));
+            }
+            let substituted = python_substitute(expression, &params, &arguments);
+            let line = line_of(reference);
+            let line_text = &content[lines[line].0..lines[line].1];
+            let safe = python_safe_context(line_text, reference - lines[line].0, close - lines[line].0);
+            replacements.push(Replacement {
+                start: reference,
+                end: close,
+                text: if python_atomic(&substituted) || safe { substituted } else { format!("({})", substituted) },
+            });
+        }
+        replacements.push(removal(content, line_start, function_end));
+    } else {
+        let value = declaration_line[name.len()..].trim_start()[1..].trim();
+        if value.is_empty() || value.ends_with(['\\', '(', '[', '{', ',']) {
+            return Err(anyhow!("Cannot inline {}: the value spans several lines", name));
+        }
+        let scope_end = block_end(line_index, indent);
+        let references = python_references(content, name, line_end, scope_end)?;
+        for &reference in &references {
+            let line = line_of(reference);
+            let line_text = content[lines[line].0 + lines[line].2..lines[line].1].to_string();
+            let rebinds = line_text.starts_with(&format!("{} ", name)) && line_text[name.len()..].trim_start().starts_with(['=', '+', '-', '*', '/'])
+                || ["for ", "def ", "global ", "nonlocal ", "lambda "].iter().any(|k| line_text.contains(&format!("{}{}", k, name)))
+                || line_text.contains(&format!(" as {}", name));
+            if rebinds {
+                return Err(anyhow!("Cannot inline {}: the name is reassigned or shadowed", name));
+            }
+        }
+        let effectful = value.contains('(') || value.contains("await ") || value.contains("yield");
+        if effectful && references.len() != 1 {
+            return Err(anyhow!("Cannot inline {}: its value has side effects", name));
+        }
+        for reference in references {
+            let line = line_of(reference);
+            let line_text = &content[lines[line].0..lines[line].1];
+            let end = reference + name.len();
+            let safe = python_safe_context(line_text, reference - lines[line].0, end - lines[line].0);
+            replacements.push(Replacement {
+                start: reference,
+                end,
+                text: if python_atomic(value) || safe { value.to_string() } else { format!("({})", value) },
+            });
+        }
+        replacements.push(removal(content, line_start, line_end));
+    }
+    Ok((name.to_string(), replacements))
+}
+
+/// Inline the variable or small function under `position` into all of its usages
+pub fn inline_symbol(workspace: &Path, path: &str, content: &str, position: Position) -> Result<RefactorResult> {
+    let offset = changeset::offset_of(content, position)?;
+    let (name, mut replacements) = if path.ends_with(".py") {
+        inline_python(content, offset)?
+    } else {
+        let tree = syntax::parse(path, content).ok_or_else(|| anyhow!("Inlining is not supported for {}", path))?;
+        inline_tree(tree.root_node(), content, offset)?
+    };
+
+    replacements.sort_by_key(|r| r.start);
+    let edits = replacements
+        .into_iter()
+        .map(|r| TextEdit {
+            start: changeset::position_at(content, r.start),
+            end: changeset::position_at(content, r.end),
+            new_text: r.text,
+        })
+        .collect();
+    let changeset = Changeset::new(
+        format!("Inline {}", name),
+        vec![FileChange::Edit {
+            path: path.to_string(),
+            edits,
+        }],
+    );
+    RefactorResult::new(workspace, changeset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inline(path: &str, content: &str, offset: usize) -> String {
+        let tree = syntax::parse(path, content);
+        let (_, mut replacements) = match tree {
+            Some(tree) => inline_tree(tree.root_node(), content, offset).unwrap(),
+            None => inline_python(content, offset).unwrap(),
+        };
+        replacements.sort_by_key(|r| std::cmp::Reverse(r.start));
+        let mut result = content.to_string();
+        for r in replacements {
+            result.replace_range(r.start..r.end, &r.text);
+        }
+        result
+    }
+
+    #[test]
+    fn test_inline_variable_and_function() {
+        let ts = "const a = b + c;\nconst d = a * 2;\nf(a);\n";
+        assert_eq!(inline("x.ts", ts, 6), "const d = (b + c) * 2;\nf(b + c);\n");
+
+        let rust = "fn double(x: i32) -> i32 {\n    x * 2\n}\n\nfn main() {\n    let y = double(1 + 2) + 1;\n}\n";
+        assert_eq!(inline("x.rs", rust, 3), "fn main() {\n    let y = ((1 + 2) * 2) + 1;\n}\n");
+
+        let py = "def f():\n    total = a + b\n    return total * 2\n";
+        assert_eq!(inline("x.py", py, 14), "def f():\n    return (a + b) * 2\n");
+    }
+}
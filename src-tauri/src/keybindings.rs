@@ -0,0 +1,451 @@
+// Keybindings - Default shortcuts, plugin contributions and user overrides in one keymap
+// Frontends pass the keys pressed so far and get the same command back everywhere
+
+use std::collections::HashSet;
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// Canonical modifier names, in the order they are written
+const MODIFIERS: &[&str] = &["ctrl", "alt", "shift", "meta"];
+
+/// Built-in bindings as (keys, command, when); `mod` is cmd on macOS and ctrl elsewhere
+const DEFAULTS: &[(&str, &str, Option<&str>)] = &[
+    ("mod+shift+p", "palette.open", None),
+    ("mod+p", "file.quickOpen", None),
+    ("mod+s", "file.save", Some("editorFocus")),
+    ("mod+w", "editor.close", Some("editorFocus")),
+    ("mod+k mod+w", "editor.closeAll", None),
+    ("mod+f", "editor.find", Some("editorFocus")),
+    ("mod+shift+f", "search.workspace", None),
+    ("f12", "editor.goToDefinition", Some("editorFocus")),
+    ("shift+f12", "editor.findReferences", Some("editorFocus")),
+    ("f2", "editor.rename", Some("editorFocus")),
+    ("mod+.", "editor.quickFix", Some("editorFocus")),
+    ("mod+/", "editor.toggleComment", Some("editorFocus")),
+    ("mod+b", "sidebar.toggle", None),
+    ("ctrl+`", "terminal.toggle", None),
+    ("mod+l", "chat.focus", None),
+    ("mod+enter", "chat.send", Some("chatFocus")),
+    ("escape", "chat.cancel", Some("chatFocus")),
+    ("mod+k mod+s", "keybindings.open", None),
+];
+
+/// A binding as written by a plugin or the user
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeybindingRule {
+    /// Strokes separated by spaces, e.g. `mod+k mod+s`
+    pub keys: String,
+    /// Command to run; in user overrides `-command` removes the binding instead
+    pub command: String,
+    /// Flags joined by `&&` and `||`, each optionally negated with `!`
+    pub when: Option<String>,
+}
+
+/// What a plugin adds to the keymap
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct PluginKeybindings {
+    /// Commands the plugin provides, bound or not
+    pub commands: Vec<String>,
+    pub bindings: Vec<KeybindingRule>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", content = "name", rename_all = "snake_case")]
+pub enum BindingSource {
+    Default,
+    Plugin(String),
+    User,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Keybinding {
+    /// Normalized strokes, e.g. `ctrl+shift+p`
+    pub keys: String,
+    pub command: String,
+    pub when: Option<String>,
+    pub source: BindingSource,
+}
+
+/// A rule that was left out of the keymap
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeybindingIssue {
+    pub source: BindingSource,
+    pub keys: String,
+    pub command: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// Same keys for different commands; the later binding wins
+    SameKeys,
+    /// One binding is the start of a chord, so the chord can't be reached
+    ChordPrefix,
+}
+
+/// Two bindings that can be active at once; `shadowed` never fires where both apply
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeybindingConflict {
+    pub kind: ConflictKind,
+    pub winner: Keybinding,
+    pub shadowed: Keybinding,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeyResolution {
+    pub keys: String,
+    pub command: Option<String>,
+    pub source: Option<BindingSource>,
+    /// The keys start a chord; wait for the next stroke
+    pub pending: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeymapReport {
+    pub bindings: Vec<Keybinding>,
+    pub conflicts: Vec<KeybindingConflict>,
+    pub issues: Vec<KeybindingIssue>,
+}
+
+fn modifier(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "ctrl" | "control" => "ctrl",
+        "alt" | "option" | "opt" => "alt",
+        "shift" => "shift",
+        "meta" | "cmd" | "command" | "super" | "win" => "meta",
+        "mod" | "cmdorctrl" | "commandorcontrol" => {
+            if cfg!(target_os = "macos") {
+                "meta"
+            } else {
+                "ctrl"
+            }
+        }
+        _ => return None,
+    })
+}
+
+/// Key names as browsers and Tauri report them, mapped to one spelling
+fn key_alias(key: &str) -> &str {
+    match key {
+        "esc" => "escape",
+        "return" => "enter",
+        "del" => "delete",
+        "ins" => "insert",
+        "spacebar" => "space",
+        "plus" => "+",
+        "arrowup" => "up",
+        "arrowdown" => "down",
+        "arrowleft" => "left",
+        "arrowright" => "right",
+        key => key,
+    }
+}
+
+fn parse_stroke(stroke: &str) -> Result<String> {
+    let lower = stroke.to_lowercase();
+    let (mods, key) = match lower.strip_suffix("++") {
+        Some(mods) => (mods, "+"),
+        None if lower == "+" => ("", "+"),
+        None => lower.rsplit_once('+').unwrap_or(("", lower.as_str())),
+    };
+    if key.is_empty() {
+        bail!("Missing key in `{}`", stroke);
+    }
+    if modifier(key).is_some() {
+        bail!("`{}` ends with a modifier instead of a key", stroke);
+    }
+    let mut held = [false; 4];
+    for name in mods.split('+').filter(|name| !name.is_empty()) {
+        let canonical = modifier(name).ok_or_else(|| anyhow!("Unknown modifier `{}` in `{}`", name, stroke))?;
+        let i = MODIFIERS.iter().position(|m| *m == canonical).unwrap_or_default();
+        if held[i] {
+            bail!("`{}` holds {} twice", stroke, canonical);
+        }
+        held[i] = true;
+    }
+    let mut parts: Vec<&str> = MODIFIERS.iter().zip(held).filter(|(_, held)| *held).map(|(m, _)| *m).collect();
+    parts.push(key_alias(key));
+    Ok(parts.join("+"))
+}
+
+/// Strokes in canonical form, so `Shift+Ctrl+P` and `ctrl+shift+p` are the same binding
+pub fn normalize(keys: &str) -> Result<Vec<String>> {
+    let strokes = keys.split_whitespace().map(parse_stroke).collect::<Result<Vec<_>>>()?;
+    if strokes.is_empty() {
+        bail!("No keys given");
+    }
+    Ok(strokes)
+}
+
+/// A when clause in disjunctive form: any clause whose flags all hold
+#[derive(Clone, Debug)]
+struct When(Vec<Vec<(String, bool)>>);
+
+impl When {
+    fn parse(expr: Option<&str>) -> Result<Self> {
+        let Some(expr) = expr.map(str::trim).filter(|expr| !expr.is_empty()) else {
+            return Ok(When(vec![Vec::new()]));
+        };
+        let term = |term: &str| {
+            let term = term.trim();
+            let (flag, expected) = match term.strip_prefix('!') {
+                Some(flag) => (flag.trim(), false),
+                None => (term, true),
+            };
+            if flag.is_empty() || !flag.chars().all(|c| c.is_ascii_alphanumeric() || "_.:-".contains(c)) {
+                bail!("Invalid term `{}` in when clause `{}`", term, expr);
+            }
+            Ok((flag.to_string(), expected))
+        };
+        expr.split("||")
+            .map(|clause| clause.split("&&").map(&term).collect::<Result<Vec<_>>>())
+            .collect::<Result<Vec<_>>>()
+            .map(When)
+    }
+
+    fn matches(&self, context: &HashSet<&str>) -> bool {
+        self.0
+            .iter()
+            .any(|clause| clause.iter().all(|(flag, expected)| context.contains(flag.as_str()) == *expected))
+    }
+
+    /// Whether some context satisfies both, i.e. no flag is required one way and forbidden the other
+    fn overlaps(&self, other: &When) -> bool {
+        self.0.iter().any(|a| {
+            other.0.iter().any(|b| !a.iter().any(|(flag, expected)| b.iter().any(|(f, e)| f == flag && e != expected)))
+        })
+    }
+}
+
+struct Entry {
+    binding: Keybinding,
+    strokes: Vec<String>,
+    when: When,
+}
+
+/// Effective bindings in priority order: defaults, plugins, then user overrides
+pub struct Keymap {
+    entries: Vec<Entry>,
+    issues: Vec<KeybindingIssue>,
+}
+
+impl Keymap {
+    fn issue(&mut self, source: BindingSource, rule: &KeybindingRule, message: String) {
+        self.issues.push(KeybindingIssue {
+            source,
+            keys: rule.keys.clone(),
+            command: rule.command.clone(),
+            message,
+        });
+    }
+
+    fn add(&mut self, rule: &KeybindingRule, source: BindingSource, commands: &HashSet<String>) {
+        let compiled = normalize(&rule.keys).and_then(|strokes| {
+            let when = When::parse(rule.when.as_deref())?;
+            if !commands.contains(&rule.command) {
+                bail!("Unknown command `{}`", rule.command);
+            }
+            Ok((strokes, when))
+        });
+        match compiled {
+            Ok((strokes, when)) => self.entries.push(Entry {
+                binding: Keybinding {
+                    keys: strokes.join(" "),
+                    command: rule.command.clone(),
+                    when: rule.when.clone(),
+                    source,
+                },
+                strokes,
+                when,
+            }),
+            Err(e) => self.issue(source, rule, e.to_string()),
+        }
+    }
+
+    /// Drop earlier bindings of `command` to the rule's keys, and its when clause if given
+    fn remove(&mut self, rule: &KeybindingRule, command: &str) {
+        let removed = normalize(&rule.keys).map(|strokes| {
+            let before = self.entries.len();
+            self.entries.retain(|entry| {
+                entry.binding.command != command
+                    || entry.strokes != strokes
+                    || rule.when.as_ref().is_some_and(|when| entry.binding.when.as_ref() != Some(when))
+            });
+            before - self.entries.len()
+        });
+        match removed {
+            Ok(0) => self.issue(BindingSource::User, rule, format!("No binding of `{}` to remove", command)),
+            Ok(_) => {}
+            Err(e) => self.issue(BindingSource::User, rule, e.to_string()),
+        }
+    }
+
+    fn active<'a>(&'a self, context: &'a HashSet<&'a str>) -> impl Iterator<Item = &'a Entry> + 'a {
+        self.entries.iter().rev().filter(move |entry| entry.when.matches(context))
+    }
+
+    /// Command for the keys pressed so far (a whole chord is passed as `mod+k mod+s`) in the given context
+    pub fn resolve(&self, keys: &str, context: &[String]) -> Result<KeyResolution> {
+        let strokes = normalize(keys)?;
+        let context: HashSet<&str> = context.iter().map(String::as_str).collect();
+        let exact = self.active(&context).find(|entry| entry.strokes == strokes);
+        let pending = exact.is_none()
+            && self
+                .active(&context)
+                .any(|entry| entry.strokes.len() > strokes.len() && entry.strokes.starts_with(&strokes));
+        Ok(KeyResolution {
+            keys: strokes.join(" "),
+            command: exact.map(|entry| entry.binding.command.clone()),
+            source: exact.map(|entry| entry.binding.source.clone()),
+            pending,
+        })
+    }
+
+    /// Pairs of bindings that shadow each other in at least one context
+    pub fn conflicts(&self) -> Vec<KeybindingConflict> {
+        let mut conflicts = Vec::new();
+        for (i, earlier) in self.entries.iter().enumerate() {
+            for later in &self.entries[i + 1..] {
+                if !earlier.when.overlaps(&later.when) {
+                    continue;
+                }
+                let (kind, winner, shadowed) = if earlier.strokes == later.strokes {
+                    if earlier.binding.command == later.binding.command {
+                        continue;
+                    }
+                    (ConflictKind::SameKeys, later, earlier)
+                } else if later.strokes.starts_with(&earlier.strokes) {
+                    (ConflictKind::ChordPrefix, earlier, later)
+                } else if earlier.strokes.starts_with(&later.strokes) {
+                    (ConflictKind::ChordPrefix, later, earlier)
+                } else {
+                    continue;
+                };
+                conflicts.push(KeybindingConflict {
+                    kind,
+                    winner: winner.binding.clone(),
+                    shadowed: shadowed.binding.clone(),
+                });
+            }
+        }
+        conflicts
+    }
+
+    pub fn issues(&self) -> &[KeybindingIssue] {
+        &self.issues
+    }
+
+    pub fn report(&self) -> KeymapReport {
+        KeymapReport {
+            bindings: self.entries.iter().map(|entry| entry.binding.clone()).collect(),
+            conflicts: self.conflicts(),
+            issues: self.issues.clone(),
+        }
+    }
+}
+
+/// Keybinding contributions by plugin, in registration order
+#[derive(Default)]
+pub struct KeybindingRegistry {
+    plugins: Vec<(String, PluginKeybindings)>,
+}
+
+impl KeybindingRegistry {
+    /// Add or replace a plugin's contribution; an empty one removes the plugin
+    pub fn register(&mut self, plugin: String, contribution: PluginKeybindings) {
+        let existing = self.plugins.iter().position(|(name, _)| *name == plugin);
+        let empty = contribution.commands.is_empty() && contribution.bindings.is_empty();
+        match (existing, empty) {
+            (Some(i), true) => {
+                self.plugins.remove(i);
+            }
+            (Some(i), false) => self.plugins[i].1 = contribution,
+            (None, true) => {}
+            (None, false) => self.plugins.push((plugin, contribution)),
+        }
+    }
+
+    /// Defaults, then plugins, then the user's overrides; invalid rules are skipped and reported
+    pub fn keymap(&self, overrides: &[KeybindingRule]) -> Keymap {
+        let mut commands: HashSet<String> = DEFAULTS.iter().map(|(_, command, _)| command.to_string()).collect();
+        for (_, contribution) in &self.plugins {
+            commands.extend(contribution.commands.iter().cloned());
+        }
+        let mut keymap = Keymap {
+            entries: Vec::new(),
+            issues: Vec::new(),
+        };
+        for (keys, command, when) in DEFAULTS {
+            let rule = KeybindingRule {
+                keys: keys.to_string(),
+                command: command.to_string(),
+                when: when.map(str::to_string),
+            };
+            keymap.add(&rule, BindingSource::Default, &commands);
+        }
+        for (plugin, contribution) in &self.plugins {
+            for rule in &contribution.bindings {
+                keymap.add(rule, BindingSource::Plugin(plugin.clone()), &commands);
+            }
+        }
+        for rule in overrides {
+            match rule.command.strip_prefix('-') {
+                Some(command) => keymap.remove(rule, command),
+                None => keymap.add(rule, BindingSource::User, &commands),
+            }
+        }
+        keymap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(keys: &str, command: &str, when: Option<&str>) -> KeybindingRule {
+        KeybindingRule {
+            keys: keys.to_string(),
+            command: command.to_string(),
+            when: when.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_resolves_overrides_and_reports_conflicts() {
+        let mut registry = KeybindingRegistry::default();
+        let defaults = registry.keymap(&[]);
+        assert!(defaults.conflicts().is_empty());
+        assert!(defaults.issues().is_empty());
+        let palette = defaults.resolve("Shift+Mod+P", &[]).unwrap();
+        assert_eq!(palette.command.as_deref(), Some("palette.open"));
+        let chord = defaults.resolve("mod+k", &[]).unwrap();
+        assert!(chord.pending && chord.command.is_none());
+        assert!(defaults.resolve("mod+s", &[]).unwrap().command.is_none());
+
+        registry.register(
+            "git".to_string(),
+            PluginKeybindings {
+                commands: vec!["git.commit".to_string()],
+                bindings: vec![rule("mod+enter", "git.commit", Some("scmFocus && !chatFocus"))],
+            },
+        );
+        let keymap = registry.keymap(&[
+            rule("mod+s", "-file.save", None),
+            rule("mod+k", "chat.focus", None),
+            rule("mod+j", "unknown.command", None),
+        ]);
+        let context = vec!["scmFocus".to_string()];
+        assert_eq!(keymap.resolve("mod+enter", &context).unwrap().command.as_deref(), Some("git.commit"));
+        let context = vec!["editorFocus".to_string()];
+        assert!(keymap.resolve("mod+s", &context).unwrap().command.is_none());
+        assert_eq!(keymap.issues().len(), 1);
+
+        let conflicts = keymap.conflicts();
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts
+            .iter()
+            .all(|c| c.kind == ConflictKind::ChordPrefix && c.winner.source == BindingSource::User));
+    }
+}
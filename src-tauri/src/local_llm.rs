@@ -0,0 +1,319 @@
+// Local Inference - Offline GGUF models via llama.cpp
+// Model files live in the app data directory; inference needs the `local-inference` feature
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::ai_provider::AiProvider;
+
+/// Registry id of the local provider
+pub const PROVIDER_ID: &str = "local";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LocalModelInfo {
+    pub name: String,
+    pub path: String,
+    pub size_bytes: u64,
+}
+
+/// Progress payload emitted as `local-model-download`
+#[derive(Serialize, Clone, Debug)]
+pub struct DownloadProgress {
+    pub name: String,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+    pub done: bool,
+}
+
+/// Directory holding downloaded GGUF files
+pub fn models_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("models")
+}
+
+/// Whether this build can run local inference
+pub fn inference_available() -> bool {
+    cfg!(feature = "local-inference")
+}
+
+/// Validate a model name so it cannot escape the models directory
+fn model_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.contains('/') || name.contains('\\') || name.contains("..") {
+        return Err(anyhow!("Invalid model name: {}", name));
+    }
+    let file = if name.ends_with(".gguf") {
+        name.to_string()
+    } else {
+        format!("{}.gguf", name)
+    };
+    Ok(dir.join(file))
+}
+
+/// List downloaded models
+pub fn list_models(dir: &Path) -> Result<Vec<LocalModelInfo>> {
+    let mut models = Vec::new();
+    if !dir.exists() {
+        return Ok(models);
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("gguf") {
+            continue;
+        }
+        models.push(LocalModelInfo {
+            name: path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path: path.to_string_lossy().to_string(),
+            size_bytes: entry.metadata()?.len(),
+        });
+    }
+
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(models)
+}
+
+/// Delete a downloaded model
+pub fn delete_model(dir: &Path, name: &str) -> Result<()> {
+    let path = model_path(dir, name)?;
+    if !path.exists() {
+        return Err(anyhow!("Model not found: {}", name));
+    }
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+/// Removes a partial download when it is dropped: on errors, on cancellation and, harmlessly, after the rename
+pub(crate) struct PartialFile(pub(crate) PathBuf);
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// SHA-256 the server publishes for the file; Hugging Face sends it as the ETag of LFS files
+fn published_sha256(response: &reqwest::Response) -> Option<String> {
+    let headers = response.headers();
+    let etag = headers.get("x-linked-etag").or_else(|| headers.get(reqwest::header::ETAG))?;
+    let value = etag.to_str().ok()?.trim_start_matches("W/").trim_matches('"').to_lowercase();
+    (value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())).then_some(value)
+}
+
+/// Download a GGUF file, reporting progress; partial downloads never appear in the model list. The file is
+/// verified against `sha256`, or else the checksum the server publishes, before it is moved into place
+pub async fn download_model(
+    dir: &Path,
+    name: &str,
+    url: &str,
+    sha256: Option<&str>,
+    on_progress: impl Fn(DownloadProgress),
+) -> Result<LocalModelInfo> {
+    let path = model_path(dir, name)?;
+    fs::create_dir_all(dir)?;
+    let partial = PartialFile(path.with_extension("gguf.part"));
+
+    let mut response = reqwest::get(url).await?.error_for_status()?;
+    let expected = sha256.map(|s| s.trim().to_lowercase()).or_else(|| published_sha256(&response));
+    let total_bytes = response.content_length();
+    let mut file = fs::File::create(&partial.0)?;
+    let mut hasher = Sha256::new();
+    let mut downloaded_bytes = 0u64;
+
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk)?;
+        hasher.update(&chunk);
+        downloaded_bytes += chunk.len() as u64;
+        on_progress(DownloadProgress {
+            name: name.to_string(),
+            downloaded_bytes,
+            total_bytes,
+            done: false,
+        });
+    }
+    file.flush()?;
+    drop(file);
+    let actual = hex::encode(hasher.finalize());
+    match expected {
+        Some(expected) if expected != actual => {
+            return Err(anyhow!("Checksum mismatch for {}: expected {}, got {}", name, expected, actual));
+        }
+        Some(_) => {}
+        None => log::warn!("No checksum published for {}; keeping it unverified", url),
+    }
+    fs::rename(&partial.0, &path)?;
+
+    on_progress(DownloadProgress {
+        name: name.to_string(),
+        downloaded_bytes,
+        total_bytes,
+        done: true,
+    });
+
+    Ok(LocalModelInfo {
+        name: name.trim_end_matches(".gguf").to_string(),
+        path: path.to_string_lossy().to_string(),
+        size_bytes: downloaded_bytes,
+    })
+}
+
+/// Load a model as an AI provider
+pub fn load_provider(dir: &Path, name: &str) -> Result<Arc<dyn AiProvider>> {
+    let path = model_path(dir, name)?;
+    if !path.exists() {
+        return Err(anyhow!("Model not found: {}", name));
+    }
+    backend::load(&path, name)
+}
+
+#[cfg(not(feature = "local-inference"))]
+mod backend {
+    use super::*;
+
+    pub fn load(_path: &Path, _name: &str) -> Result<Arc<dyn AiProvider>> {
+        Err(anyhow!(
+            "Local inference is not enabled in this build (compile with the `local-inference` feature)"
+        ))
+    }
+}
+
+#[cfg(feature = "local-inference")]
+mod backend {
+    use super::*;
+    use async_trait::async_trait;
+    use llama_cpp::standard_sampler::StandardSampler;
+    use llama_cpp::{EmbeddingsParams, LlamaModel, LlamaParams, SessionParams};
+    use tokio::sync::mpsc;
+
+    use crate::ai_provider::{ChatMessage, CompletionRequest, Role};
+
+    const DEFAULT_MAX_TOKENS: usize = 1024;
+
+    pub fn load(path: &Path, name: &str) -> Result<Arc<dyn AiProvider>> {
+        log::info!("Loading local model: {:?}", path);
+        let model = LlamaModel::load_from_file(path, LlamaParams::default())
+            .map_err(|e| anyhow!("Failed to load model: {}", e))?;
+        Ok(Arc::new(LocalProvider {
+            model,
+            name: name.trim_end_matches(".gguf").to_string(),
+        }))
+    }
+
+    pub struct LocalProvider {
+        model: LlamaModel,
+        name: String,
+    }
+
+    /// ChatML prompt format (Qwen, many fine-tunes)
+    fn format_prompt(messages: &[ChatMessage]) -> String {
+        let mut prompt = String::new();
+        for message in messages {
+            let role = match message.role {
+                Role::System => "system",
+                Role::User => "user",
+                Role::Assistant => "assistant",
+            };
+            prompt.push_str(&format!("<|im_start|>{}\n{}<|im_end|>\n", role, message.content));
+        }
+        prompt.push_str("<|im_start|>assistant\n");
+        prompt
+    }
+
+    /// Run a completion on a blocking thread, sending pieces through `tx`
+    fn generate(
+        model: LlamaModel,
+        prompt: String,
+        max_tokens: usize,
+        tx: mpsc::UnboundedSender<String>,
+    ) -> Result<()> {
+        let mut session = model
+            .create_session(SessionParams::default())
+            .map_err(|e| anyhow!("Failed to create session: {}", e))?;
+        session
+            .advance_context(prompt)
+            .map_err(|e| anyhow!("Failed to evaluate prompt: {}", e))?;
+
+        let completions = session
+            .start_completing_with(StandardSampler::default(), max_tokens)
+            .map_err(|e| anyhow!("Failed to start completion: {}", e))?
+            .into_strings();
+        for piece in completions {
+            if piece.contains("<|im_end|>") || tx.send(piece).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    #[async_trait]
+    impl AiProvider for LocalProvider {
+        fn id(&self) -> &str {
+            PROVIDER_ID
+        }
+
+        fn model(&self) -> &str {
+            &self.name
+        }
+
+        async fn complete(&self, request: &CompletionRequest) -> Result<String> {
+            self.stream(request, &|_| {}).await
+        }
+
+        async fn stream(
+            &self,
+            request: &CompletionRequest,
+            on_chunk: &(dyn Fn(&str) + Send + Sync),
+        ) -> Result<String> {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let model = self.model.clone();
+            let prompt = format_prompt(&request.messages);
+            let max_tokens = request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+            let handle = tokio::task::spawn_blocking(move || generate(model, prompt, max_tokens, tx));
+
+            let mut text = String::new();
+            while let Some(piece) = rx.recv().await {
+                on_chunk(&piece);
+                text.push_str(&piece);
+            }
+            handle.await??;
+            Ok(text)
+        }
+
+        async fn embed(&self, input: &str) -> Result<Vec<f32>> {
+            let model = self.model.clone();
+            let input = input.to_string();
+            let embeddings = tokio::task::spawn_blocking(move || {
+                model.embeddings(&[input.as_bytes()], EmbeddingsParams::default())
+            })
+            .await?
+            .map_err(|e| anyhow!("Embedding failed: {}", e))?;
+
+            embeddings
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("Model returned no embedding"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_path_rejects_traversal() {
+        let dir = Path::new("/models");
+        assert!(model_path(dir, "../secret").is_err());
+        assert_eq!(
+            model_path(dir, "qwen2.5-coder").unwrap(),
+            PathBuf::from("/models/qwen2.5-coder.gguf")
+        );
+    }
+}
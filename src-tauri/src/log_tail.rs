@@ -0,0 +1,448 @@
+// Log Tailing - Appended lines of log files streamed as filtered, highlighted batches
+// Files are polled by offset; truncation and rotation to a new file restart reading from the top
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Bytes read per poll, so a huge backlog arrives in steps
+const MAX_READ_BYTES: usize = 1024 * 1024;
+/// Lines per emitted batch
+const MAX_BATCH_LINES: usize = 500;
+/// Longer lines are cut, e.g. minified JSON dumped into a log
+const MAX_LINE_BYTES: usize = 16 * 1024;
+const DEFAULT_INITIAL_LINES: usize = 200;
+
+/// Severity detected in a line, most severe first
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn parse(word: &str) -> Option<Self> {
+        Some(match word.to_ascii_lowercase().as_str() {
+            "fatal" | "critical" | "crit" | "panic" | "error" | "err" | "e" => LogLevel::Error,
+            "warn" | "warning" | "w" => LogLevel::Warn,
+            "info" | "notice" | "i" => LogLevel::Info,
+            "debug" | "d" => LogLevel::Debug,
+            "trace" | "verbose" | "v" => LogLevel::Trace,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HighlightRule {
+    pub pattern: String,
+    /// Style name the viewer maps to a color
+    #[serde(default)]
+    pub style: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct TailFilter {
+    /// Regex a line must match
+    pub include: Option<String>,
+    /// Regex of lines to drop
+    pub exclude: Option<String>,
+    pub case_sensitive: bool,
+    /// Least severe level shown; lines without a level pass
+    pub min_level: Option<LogLevel>,
+    pub highlights: Vec<HighlightRule>,
+    /// Existing lines sent before following the file
+    pub initial_lines: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Highlight {
+    /// Byte range in the line
+    pub start: usize,
+    pub end: usize,
+    pub style: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TailLine {
+    /// Byte offset of the line in the file
+    pub offset: u64,
+    pub text: String,
+    pub level: Option<LogLevel>,
+    pub highlights: Vec<Highlight>,
+}
+
+/// Payload emitted as `log-tail`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TailEvent {
+    pub tail_id: String,
+    pub path: String,
+    /// The file was truncated or replaced; the viewer should clear what it shows
+    pub rotated: bool,
+    pub lines: Vec<TailLine>,
+    /// Reading failed and the tail stopped
+    pub closed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TailInfo {
+    pub id: String,
+    pub path: String,
+    pub started_at: u64,
+}
+
+fn build_regex(pattern: &str, case_sensitive: bool) -> Result<Regex> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(!case_sensitive)
+        .size_limit(1 << 20)
+        .build()
+        .map_err(|e| anyhow!("Invalid pattern {}: {}", pattern, e))
+}
+
+/// Level words in the usual positions: `level=warn`, `"level":"warn"`, `[warn]`, `<E>`, or an upper-case word
+fn level_regex() -> &'static Regex {
+    static LEVEL: OnceLock<Regex> = OnceLock::new();
+    LEVEL.get_or_init(|| {
+        Regex::new(concat!(
+            r#"(?i:\b(?:level|lvl|severity)"?\s*[=:]\s*"?([a-z]+))"#,
+            r"|(?i:[\[<(]([a-z]+)[\]>)])",
+            r"|\b(FATAL|CRITICAL|PANIC|ERROR|ERR|WARN|WARNING|INFO|NOTICE|DEBUG|TRACE|VERBOSE)\b",
+        ))
+        .unwrap()
+    })
+}
+
+fn detect_level(line: &str) -> Option<LogLevel> {
+    level_regex()
+        .captures_iter(line)
+        .find_map(|captures| captures.iter().skip(1).flatten().find_map(|m| LogLevel::parse(m.as_str())))
+}
+
+/// Compiled `TailFilter`
+pub struct LineFilter {
+    include: Option<Regex>,
+    exclude: Option<Regex>,
+    min_level: Option<LogLevel>,
+    highlights: Vec<(Regex, String)>,
+    /// Level of the last line with one; indented continuation lines such as stack frames share it
+    last_level: Option<LogLevel>,
+}
+
+impl LineFilter {
+    pub fn new(filter: &TailFilter) -> Result<Self> {
+        let compile = |pattern: &Option<String>| -> Result<Option<Regex>> {
+            pattern
+                .as_deref()
+                .filter(|pattern| !pattern.is_empty())
+                .map(|pattern| build_regex(pattern, filter.case_sensitive))
+                .transpose()
+        };
+        Ok(Self {
+            include: compile(&filter.include)?,
+            exclude: compile(&filter.exclude)?,
+            min_level: filter.min_level,
+            highlights: filter
+                .highlights
+                .iter()
+                .map(|rule| Ok((build_regex(&rule.pattern, filter.case_sensitive)?, rule.style.clone())))
+                .collect::<Result<_>>()?,
+            last_level: None,
+        })
+    }
+
+    /// The line as shown, or none when the filter drops it
+    fn apply(&mut self, offset: u64, text: String) -> Option<TailLine> {
+        let continuation = text.starts_with([' ', '\t']);
+        let level = if continuation { self.last_level } else { detect_level(&text) };
+        self.last_level = level;
+        if level.zip(self.min_level).is_some_and(|(level, min)| level > min) {
+            return None;
+        }
+        if self.include.as_ref().is_some_and(|include| !include.is_match(&text))
+            || self.exclude.as_ref().is_some_and(|exclude| exclude.is_match(&text))
+        {
+            return None;
+        }
+        let mut highlights: Vec<Highlight> = self
+            .highlights
+            .iter()
+            .flat_map(|(regex, style)| {
+                regex.find_iter(&text).filter(|m| m.start() < m.end()).map(|m| Highlight {
+                    start: m.start(),
+                    end: m.end(),
+                    style: style.clone(),
+                })
+            })
+            .collect();
+        highlights.sort_by_key(|h| (h.start, h.end));
+        Some(TailLine {
+            offset,
+            text,
+            level,
+            highlights,
+        })
+    }
+}
+
+/// Identity of the file behind a path, to notice a rotated-in replacement
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Offset where the last `lines` lines of `file` start
+fn offset_of_last_lines(file: &mut File, len: u64, lines: usize) -> Result<u64> {
+    if lines == 0 {
+        return Ok(len);
+    }
+    let mut newlines = 0;
+    let mut end = len;
+    let mut buffer = vec![0u8; 8192];
+    while end > 0 {
+        let start = end.saturating_sub(buffer.len() as u64);
+        let chunk = &mut buffer[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        for (i, byte) in chunk.iter().enumerate().rev() {
+            // A newline ending the file does not start another line
+            if *byte == b'\n' && start + i as u64 + 1 != len {
+                newlines += 1;
+                if newlines == lines {
+                    return Ok(start + i as u64 + 1);
+                }
+            }
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+/// Read position in one followed file
+struct Tailer {
+    path: PathBuf,
+    file: File,
+    id: Option<(u64, u64)>,
+    offset: u64,
+    /// Start and bytes of a line still being written
+    partial_start: u64,
+    partial: Vec<u8>,
+    /// The last read stopped at `MAX_READ_BYTES`
+    behind: bool,
+}
+
+impl Tailer {
+    fn open(path: &Path, initial_lines: usize) -> Result<Self> {
+        let mut file = File::open(path).map_err(|e| anyhow!("Cannot open {}: {}", path.display(), e))?;
+        let metadata = file.metadata()?;
+        if !metadata.is_file() {
+            return Err(anyhow!("{} is not a file", path.display()));
+        }
+        let offset = offset_of_last_lines(&mut file, metadata.len(), initial_lines)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            id: file_id(&metadata),
+            offset,
+            partial_start: offset,
+            partial: Vec::new(),
+            behind: false,
+        })
+    }
+
+    /// Complete lines appended since the last poll with their offsets, and whether the file was rotated
+    fn poll(&mut self) -> Result<(Vec<(u64, String)>, bool)> {
+        let mut lines = self.read_lines()?;
+        // Between a rotation's rename and the new file being created the path is missing
+        let Ok(current) = fs::metadata(&self.path) else {
+            return Ok((lines, false));
+        };
+        if current.len() >= self.offset && file_id(&current) == self.id {
+            return Ok((lines, false));
+        }
+        // Truncated in place, as by copytruncate, or renamed away and replaced; the rest of the old file was read
+        self.file = File::open(&self.path)?;
+        self.id = file_id(&current);
+        self.offset = 0;
+        self.partial.clear();
+        lines.extend(self.read_lines()?);
+        Ok((lines, true))
+    }
+
+    fn read_lines(&mut self) -> Result<Vec<(u64, String)>> {
+        self.file.seek(SeekFrom::Start(self.offset))?;
+        let mut buffer = Vec::new();
+        (&mut self.file).take(MAX_READ_BYTES as u64).read_to_end(&mut buffer)?;
+        self.behind = buffer.len() == MAX_READ_BYTES;
+        let mut lines = Vec::new();
+        let mut position = self.offset;
+        self.offset += buffer.len() as u64;
+        for piece in buffer.split_inclusive(|b| *b == b'\n') {
+            if self.partial.is_empty() {
+                self.partial_start = position;
+            }
+            position += piece.len() as u64;
+            let room = MAX_LINE_BYTES.saturating_sub(self.partial.len());
+            self.partial.extend_from_slice(&piece[..piece.len().min(room)]);
+            if piece.ends_with(b"\n") {
+                let line = std::mem::take(&mut self.partial);
+                let text = String::from_utf8_lossy(&line).trim_end_matches(['\n', '\r']).to_string();
+                lines.push((self.partial_start, text));
+            }
+        }
+        Ok(lines)
+    }
+}
+
+/// Called with each batch of a tail
+pub type TailCallback = Box<dyn Fn(TailEvent) + Send>;
+
+/// Running tails by id; each polls its file on its own thread until stopped
+pub struct TailManager {
+    tails: Arc<Mutex<HashMap<String, (TailInfo, Arc<AtomicBool>)>>>,
+}
+
+impl TailManager {
+    pub fn new() -> Self {
+        Self {
+            tails: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn start(&self, path: &Path, filter: &TailFilter, on_event: TailCallback) -> Result<TailInfo> {
+        let mut line_filter = LineFilter::new(filter)?;
+        let mut tailer = Tailer::open(path, filter.initial_lines.unwrap_or(DEFAULT_INITIAL_LINES))?;
+        let info = TailInfo {
+            id: storage::new_id("tail"),
+            path: path.to_string_lossy().to_string(),
+            started_at: storage::now_millis(),
+        };
+        let stop = Arc::new(AtomicBool::new(false));
+        self.tails.lock().unwrap().insert(info.id.clone(), (info.clone(), Arc::clone(&stop)));
+
+        let (id, shown_path) = (info.id.clone(), info.path.clone());
+        let tails = Arc::clone(&self.tails);
+        thread::spawn(move || {
+            let emit = |lines: Vec<TailLine>, rotated: bool, closed: bool| {
+                on_event(TailEvent {
+                    tail_id: id.clone(),
+                    path: shown_path.clone(),
+                    rotated,
+                    lines,
+                    closed,
+                })
+            };
+            while !stop.load(Ordering::Relaxed) {
+                let (lines, rotated) = match tailer.poll() {
+                    Ok(polled) => polled,
+                    Err(e) => {
+                        log::warn!("Stopped tailing {}: {}", tailer.path.display(), e);
+                        tails.lock().unwrap().remove(&id);
+                        emit(Vec::new(), false, true);
+                        break;
+                    }
+                };
+                let shown: Vec<TailLine> =
+                    lines.into_iter().filter_map(|(offset, text)| line_filter.apply(offset, text)).collect();
+                if rotated && shown.is_empty() {
+                    emit(Vec::new(), true, false);
+                }
+                for (i, batch) in shown.chunks(MAX_BATCH_LINES).enumerate() {
+                    emit(batch.to_vec(), rotated && i == 0, false);
+                }
+                if !tailer.behind {
+                    thread::sleep(POLL_INTERVAL);
+                }
+            }
+        });
+        Ok(info)
+    }
+
+    /// Stop a tail; false when it is not running
+    pub fn stop(&self, id: &str) -> bool {
+        match self.tails.lock().unwrap().remove(id) {
+            Some((_, stop)) => {
+                stop.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn list(&self) -> Vec<TailInfo> {
+        let mut tails: Vec<TailInfo> = self.tails.lock().unwrap().values().map(|(info, _)| info.clone()).collect();
+        tails.sort_by_key(|info| info.started_at);
+        tails
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_filters_levels_and_follows_rotation() {
+        let filter = TailFilter {
+            exclude: Some("healthcheck".to_string()),
+            min_level: Some(LogLevel::Warn),
+            highlights: vec![HighlightRule {
+                pattern: r"user=\w+".to_string(),
+                style: "accent".to_string(),
+            }],
+            ..TailFilter::default()
+        };
+        let mut filter = LineFilter::new(&filter).unwrap();
+        assert!(filter.apply(0, "2024-05-01 INFO started".to_string()).is_none());
+        let line = filter.apply(0, r#"{"level":"error","msg":"login failed user=ada"}"#.to_string()).unwrap();
+        assert_eq!(line.level, Some(LogLevel::Error));
+        assert_eq!(&line.text[line.highlights[0].start..line.highlights[0].end], "user=ada");
+        assert_eq!(filter.apply(0, "    at handler (app.js:1:2)".to_string()).unwrap().level, Some(LogLevel::Error));
+        assert!(filter.apply(0, "[warn] healthcheck slow".to_string()).is_none());
+        assert_eq!(filter.apply(0, "no level here".to_string()).unwrap().level, None);
+        assert!(LineFilter::new(&TailFilter { include: Some("(".to_string()), ..TailFilter::default() }).is_err());
+
+        let dir = std::env::temp_dir().join(format!("mimi-tail-{}", storage::new_id("t")));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+        fs::write(&path, "one\ntwo\nthree\n").unwrap();
+        let mut tailer = Tailer::open(&path, 2).unwrap();
+        let (lines, rotated) = tailer.poll().unwrap();
+        assert_eq!(lines, vec![(4, "two".to_string()), (8, "three".to_string())]);
+        assert!(!rotated);
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"four\nfi").unwrap();
+        assert_eq!(tailer.poll().unwrap().0, vec![(14, "four".to_string())]);
+        file.write_all(b"ve\n").unwrap();
+        assert_eq!(tailer.poll().unwrap().0, vec![(19, "five".to_string())]);
+
+        fs::rename(&path, dir.join("app.log.1")).unwrap();
+        fs::write(&path, "fresh\n").unwrap();
+        let (lines, rotated) = tailer.poll().unwrap();
+        assert!(rotated);
+        assert_eq!(lines.last(), Some(&(0, "fresh".to_string())));
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,289 @@
+// Logging - Engine logs through tracing, to stderr, a rolling file and an in-memory buffer
+// Levels are set per subsystem at runtime; recent entries can be inspected from within the app
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{self, Rotation};
+use tracing_log::{LogTracer, NormalizeEvent};
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::{fmt, reload, Registry};
+
+use crate::settings::LogSettings;
+use crate::storage;
+
+/// Entries kept for `get_recent_logs`
+const MAX_RECENT: usize = 2000;
+/// Daily log files kept in the log directory
+const MAX_LOG_FILES: usize = 7;
+const DEFAULT_LIMIT: usize = 500;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LogEntry {
+    pub timestamp: u64,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct LogFilter {
+    /// Least severe level to include
+    pub level: Option<String>,
+    /// Subsystem by module name or target prefix
+    pub target: Option<String>,
+    /// Case-insensitive text the message must contain
+    pub text: Option<String>,
+    /// Only entries logged at or after this time (ms)
+    pub since: Option<u64>,
+    /// Newest entries to return
+    pub limit: Option<usize>,
+}
+
+/// Ring buffer of the latest log entries
+pub struct RecentLogs {
+    entries: Mutex<VecDeque<LogEntry>>,
+}
+
+impl RecentLogs {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn push(&self, entry: LogEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_RECENT {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Entries matching `filter`, oldest first
+    pub fn query(&self, filter: &LogFilter) -> Result<Vec<LogEntry>> {
+        let level = filter.level.as_deref().map(parse_level).transpose()?;
+        let target = filter.target.as_deref().map(qualify);
+        let text = filter.text.as_deref().map(str::to_lowercase);
+        let entries = self.entries.lock().unwrap();
+        let mut matching: Vec<LogEntry> = entries
+            .iter()
+            .filter(|entry| level.is_none_or(|level| parse_level(&entry.level).is_ok_and(|l| l <= level)))
+            .filter(|entry| target.as_ref().is_none_or(|target| target.iter().any(|t| entry.target.starts_with(t))))
+            .filter(|entry| text.as_ref().is_none_or(|text| entry.message.to_lowercase().contains(text)))
+            .filter(|entry| filter.since.is_none_or(|since| entry.timestamp >= since))
+            .cloned()
+            .collect();
+        let limit = filter.limit.unwrap_or(DEFAULT_LIMIT);
+        if matching.len() > limit {
+            matching.drain(..matching.len() - limit);
+        }
+        Ok(matching)
+    }
+}
+
+struct Logger {
+    filter: reload::Handle<EnvFilter, Registry>,
+    recent: Arc<RecentLogs>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+fn parse_level(level: &str) -> Result<LevelFilter> {
+    LevelFilter::from_str(level.trim()).map_err(|_| anyhow!("Unknown log level: {}", level))
+}
+
+/// Targets a subsystem name stands for: a bare module name also matches the module in this crate
+fn qualify(target: &str) -> Vec<String> {
+    let mut targets = vec![target.to_string()];
+    if !target.contains("::") {
+        targets.push(format!("{}::{}", env!("CARGO_CRATE_NAME"), target));
+    }
+    targets
+}
+
+/// Filter directives of `settings`, with `RUST_LOG` on top when set
+fn directives(settings: &LogSettings) -> Result<String> {
+    let mut directives = vec![parse_level(&settings.level)?.to_string()];
+    for (target, level) in &settings.targets {
+        let level = parse_level(level)?;
+        directives.extend(qualify(target).into_iter().map(|target| format!("{}={}", target, level)));
+    }
+    if let Ok(env) = std::env::var("RUST_LOG") {
+        directives.push(env);
+    }
+    Ok(directives.join(","))
+}
+
+fn env_filter(settings: &LogSettings) -> Result<EnvFilter> {
+    Ok(EnvFilter::builder().parse(directives(settings)?)?)
+}
+
+/// Collects the message and other fields of an event, skipping the ones tracing-log adds
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        let name = field.name();
+        if name.starts_with("log.") {
+            return;
+        }
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        if name == "message" {
+            self.0.push_str(&format!("{:?}", value));
+        } else {
+            self.0.push_str(&format!("{}={:?}", name, value));
+        }
+    }
+}
+
+struct RecentLayer(Arc<RecentLogs>);
+
+impl<S: Subscriber> Layer<S> for RecentLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        // Records from the `log` crate carry their real target in fields
+        let normalized = event.normalized_metadata();
+        let metadata = normalized.as_ref().unwrap_or_else(|| event.metadata());
+        let mut visitor = MessageVisitor(String::new());
+        event.record(&mut visitor);
+        self.0.push(LogEntry {
+            timestamp: storage::now_millis(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: visitor.0,
+        });
+    }
+}
+
+/// Install the global subscriber, writing daily rotated files to `log_dir` when given;
+/// the returned guard flushes the file writer when dropped
+pub fn init(log_dir: Option<&Path>) -> Option<WorkerGuard> {
+    let (filter, handle) = reload::Layer::new(env_filter(&LogSettings::default()).unwrap_or_default());
+    let recent = Arc::new(RecentLogs::new());
+
+    let mut file_error = None;
+    let (file_layer, guard) = match log_dir.map(|dir| {
+        rolling::Builder::new()
+            .rotation(Rotation::DAILY)
+            .filename_prefix("engine")
+            .filename_suffix("log")
+            .max_log_files(MAX_LOG_FILES)
+            .build(dir)
+    }) {
+        Some(Ok(appender)) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            (Some(fmt::layer().with_ansi(false).with_writer(writer)), Some(guard))
+        }
+        Some(Err(e)) => {
+            file_error = Some(e);
+            (None, None)
+        }
+        None => (None, None),
+    };
+
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(file_layer)
+        .with(RecentLayer(recent.clone()));
+    if tracing::subscriber::set_global_default(subscriber).is_err() {
+        return None;
+    }
+    // Levels change at runtime, so the `log` crate forwards everything and the filter decides
+    let _ = LogTracer::builder().with_max_level(log::LevelFilter::Trace).init();
+    let _ = LOGGER.set(Logger {
+        filter: handle,
+        recent,
+    });
+    if let Some(e) = file_error {
+        log::warn!("Logging to stderr only, log directory unusable: {}", e);
+    }
+    guard
+}
+
+/// Apply the levels of `settings` to the running subscriber
+pub fn apply(settings: &LogSettings) -> Result<()> {
+    let filter = env_filter(settings)?;
+    if let Some(logger) = LOGGER.get() {
+        logger.filter.reload(filter)?;
+    }
+    Ok(())
+}
+
+/// Set the level of `target` in `settings` and apply it; an empty target sets the default level,
+/// no level drops the override of the target
+pub fn set_level(settings: &mut LogSettings, target: &str, level: Option<&str>) -> Result<()> {
+    let mut updated = settings.clone();
+    let target = target.trim();
+    match (target.is_empty(), level) {
+        (true, Some(level)) => updated.level = parse_level(level)?.to_string(),
+        (true, None) => updated.level = LogSettings::default().level,
+        (false, Some(level)) => {
+            updated.targets.insert(target.to_string(), parse_level(level)?.to_string());
+        }
+        (false, None) => {
+            updated.targets.remove(target);
+        }
+    }
+    apply(&updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+/// Recent log entries matching `filter`, oldest first
+pub fn recent(filter: &LogFilter) -> Result<Vec<LogEntry>> {
+    match LOGGER.get() {
+        Some(logger) => logger.recent.query(filter),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_and_recent_filter() {
+        let mut settings = LogSettings::default();
+        set_level(&mut settings, "file_indexer", Some("DEBUG")).unwrap();
+        assert!(set_level(&mut settings, "mimi_engine", Some("loud")).is_err());
+        let directives = directives(&settings).unwrap();
+        assert!(directives.contains(&format!("{}::file_indexer=debug", env!("CARGO_CRATE_NAME"))));
+        assert!(!directives.contains("mimi_engine"));
+
+        let recent = RecentLogs::new();
+        let entry = |level: &str, target: &str, message: &str| LogEntry {
+            timestamp: 0,
+            level: level.to_string(),
+            target: target.to_string(),
+            message: message.to_string(),
+        };
+        recent.push(entry("DEBUG", "mimiverse_ide::file_indexer", "Indexed 12 files"));
+        recent.push(entry("WARN", "mimiverse_ide::file_indexer", "Skipping unreadable file"));
+        recent.push(entry("ERROR", "mimiverse_ide::git", "Git status failed"));
+        let filter = LogFilter {
+            level: Some("warn".to_string()),
+            target: Some("file_indexer".to_string()),
+            ..LogFilter::default()
+        };
+        let found = recent.query(&filter).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].message, "Skipping unreadable file");
+        let filter = LogFilter {
+            text: Some("FAILED".to_string()),
+            ..LogFilter::default()
+        };
+        assert_eq!(recent.query(&filter).unwrap()[0].target, "mimiverse_ide::git");
+    }
+}
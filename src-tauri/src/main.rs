@@ -9,9 +9,106 @@
 mod mimi_engine;
 mod file_indexer;
 mod code_analyzer;
+mod ai_provider;
+mod ai_review;
+mod diagnostics;
+mod git;
+mod chat;
+mod storage;
+mod agent;
+mod changeset;
+mod task_runner;
+mod text_diff;
+mod tokens;
+mod local_llm;
+mod secrets;
+mod ai_middleware;
+mod settings;
+mod syntax;
+mod ai_edit;
+mod code_context;
+mod testgen;
+mod docgen;
+mod explain;
+mod related_files;
+mod data_preview;
+mod asset_metadata;
+mod scaffold;
+mod file_templates;
+mod imports;
+mod refactor;
+mod inline;
+mod symbols;
+mod search_query;
+mod history;
+mod compare;
+mod report;
+mod events;
+mod session;
+mod profiles;
+mod workspace_env;
+mod ports;
+mod processes;
+mod debugger;
+mod breakpoints;
+mod run_configs;
+mod perf_profile;
+mod resources;
+mod generated;
+mod disk_usage;
+mod stats;
+mod sloc;
+mod working_set;
+mod transaction;
+mod file_access;
+mod rope;
+mod documents;
+mod spellcheck;
+mod tasks;
+mod codeowners;
+mod rename;
+mod api_surface;
+mod version_advisor;
+mod changelog;
+mod rust_rules;
+mod ts_strictness;
+mod python_rules;
+mod framework_rules;
+mod a11y_rules;
+mod i18n;
+mod env_usage;
+mod endpoints;
+mod graphql;
+mod idl;
+mod db_inspector;
+mod http_file;
+mod containers;
+mod paths;
+mod case_collisions;
+mod sparse;
+mod standby;
+mod resolvers;
+mod modularization;
+mod renames;
+mod buffer_analysis;
+mod analysis_budget;
+mod logging;
+mod updates;
+mod index_store;
+mod import_cost;
+mod source_map;
+mod bundle_analysis;
+mod stack_trace;
+mod log_tail;
+mod toolchains;
+mod onboarding;
+mod keybindings;
+mod trust;
+mod audit;
 
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tauri::{Manager, State};
 use serde::{Deserialize, Serialize};
 
@@ -21,6 +118,31 @@ pub struct AppState {
     pub workspace_path: Mutex<Option<PathBuf>>,
     pub file_index: Mutex<file_indexer::FileIndex>,
     pub code_graph: Mutex<mimi_engine::CodeGraph>,
+    pub diagnostics: Mutex<diagnostics::DiagnosticsStore>,
+    pub ai_providers: Mutex<ai_provider::ProviderRegistry>,
+    pub chat: Mutex<chat::ChatManager>,
+    pub agent_runs: Mutex<HashMap<String, agent::AgentRun>>,
+    pub agent_tools: agent::ToolRegistry,
+    pub settings: Mutex<settings::Settings>,
+    pub line_indexes: Mutex<data_preview::LineIndexCache>,
+    pub events: events::EventBus,
+    pub processes: processes::ProcessManager,
+    pub debug_sessions: Mutex<HashMap<String, Arc<debugger::DebugSession>>>,
+    pub perf_profiles: Mutex<Vec<perf_profile::Profile>>,
+    pub resources: resources::ResourceMonitor,
+    pub transactions: transaction::TransactionManager,
+    pub documents: Mutex<documents::DocumentStore>,
+    pub spelling: Mutex<spellcheck::SpellChecker>,
+    pub standby: Mutex<standby::Standby>,
+    pub analyzer_rules: Arc<code_analyzer::RuleRegistry>,
+    pub buffer_analysis: Mutex<buffer_analysis::BufferCache>,
+    pub index_store: index_store::IndexStore,
+    pub import_costs: Mutex<import_cost::CostCache>,
+    pub bundle_analysis: Mutex<Option<bundle_analysis::BundleAnalysis>>,
+    pub source_maps: Mutex<source_map::SourceMapCache>,
+    pub log_tails: log_tail::TailManager,
+    pub keybindings: Mutex<keybindings::KeybindingRegistry>,
+    pub trust: Mutex<trust::TrustStore>,
 }
 
 impl Default for AppState {
@@ -29,83 +151,3161 @@ impl Default for AppState {
             workspace_path: Mutex::new(None),
             file_index: Mutex::new(file_indexer::FileIndex::new()),
             code_graph: Mutex::new(mimi_engine::CodeGraph::new()),
+            diagnostics: Mutex::new(diagnostics::DiagnosticsStore::new()),
+            ai_providers: Mutex::new(ai_provider::ProviderRegistry::from_env()),
+            chat: Mutex::new(chat::ChatManager::new()),
+            agent_runs: Mutex::new(HashMap::new()),
+            agent_tools: agent::ToolRegistry::with_defaults(),
+            settings: Mutex::new(settings::Settings::default()),
+            line_indexes: Mutex::new(data_preview::LineIndexCache::new()),
+            events: events::EventBus::new(),
+            processes: processes::ProcessManager::new(),
+            debug_sessions: Mutex::new(HashMap::new()),
+            perf_profiles: Mutex::new(Vec::new()),
+            resources: resources::ResourceMonitor::new(),
+            transactions: transaction::TransactionManager::new(),
+            documents: Mutex::new(documents::DocumentStore::new()),
+            spelling: Mutex::new(spellcheck::SpellChecker::new()),
+            standby: Mutex::new(standby::Standby::new()),
+            analyzer_rules: Arc::new(code_analyzer::RuleRegistry::with_defaults()),
+            buffer_analysis: Mutex::new(buffer_analysis::BufferCache::new()),
+            index_store: index_store::IndexStore::new(),
+            import_costs: Mutex::new(import_cost::CostCache::new()),
+            bundle_analysis: Mutex::new(None),
+            source_maps: Mutex::new(source_map::SourceMapCache::new()),
+            log_tails: log_tail::TailManager::new(),
+            keybindings: Mutex::new(keybindings::KeybindingRegistry::default()),
+            // Every folder is restricted until the persisted decisions load
+            trust: Mutex::new(trust::TrustStore::default()),
         }
     }
 }
 
 // ==================== COMMANDS ====================
 
+fn trust_target(path: Option<String>, state: &AppState) -> Result<PathBuf, String> {
+    match path {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => Ok(state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?),
+    }
+}
+
+/// Whether a folder (the open workspace by default) is trusted or in restricted mode
+#[tauri::command]
+async fn get_workspace_trust(
+    path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<trust::WorkspaceTrust, String> {
+    let target = trust_target(path, &state)?;
+    Ok(state.trust.lock().unwrap().status(&target))
+}
+
+/// Trust a folder and everything below it, or put it in restricted mode, where tasks, processes, debug
+/// sessions and workspace profiles are refused
+#[tauri::command]
+async fn set_workspace_trust(
+    path: Option<String>,
+    trusted: bool,
+    state: State<'_, AppState>,
+) -> Result<trust::WorkspaceTrust, String> {
+    let target = trust_target(path, &state)?;
+    state.trust.lock().unwrap().set(&target, trusted).map_err(|e| e.to_string())
+}
+
 /// Open a workspace folder
 #[tauri::command]
-async fn open_workspace(path: String, state: State<'_, AppState>) -> Result<WorkspaceInfo, String> {
-    let path = PathBuf::from(&path);
-    
-    if !path.exists() || !path.is_dir() {
-        return Err("Invalid workspace path".to_string());
-    }
+async fn open_workspace(
+    path: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<WorkspaceInfo, String> {
+    let path = PathBuf::from(&path);
+    
+    if !path.exists() || !path.is_dir() {
+        return Err("Invalid workspace path".to_string());
+    }
+
+    // Update state
+    let indexer_settings = state.settings.lock().unwrap().indexer.clone();
+    park_workspace(&state, &indexer_settings);
+    *state.workspace_path.lock().unwrap() = Some(path.clone());
+    if let Err(e) = state.spelling.lock().unwrap().load_workspace(&path) {
+        log::warn!("Failed to load the workspace dictionary: {}", e);
+    }
+    let workspace = path.to_string_lossy().to_string();
+    let started = std::time::Instant::now();
+    state.events.publish(events::Event::IndexingStarted {
+        workspace: workspace.clone(),
+    });
+
+    // A recently closed workspace comes back from standby with only its changed files re-indexed
+    if let Some(file_count) = resume_workspace(&state, &path) {
+        warm_working_set(&state, &path);
+        state.chat.lock().unwrap().load_workspace(&path).map_err(|e| e.to_string())?;
+        state.events.publish(events::Event::IndexingFinished {
+            workspace: workspace.clone(),
+            files: file_count,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+        return Ok(WorkspaceInfo {
+            path: workspace,
+            file_count,
+            indexed: true,
+            trusted: state.trust.lock().unwrap().is_trusted(&path),
+        });
+    }
+
+    let sparse = sparse_directories(&path, &indexer_settings);
+
+    // The index of the last session makes the workspace searchable at once; without one the files the user
+    // is looking at come first. The full index follows in the background either way.
+    let file_count = match restore_persisted_index(&state, &path, &indexer_settings, sparse.clone()) {
+        Some(file_count) => file_count,
+        None => index_priority_files(&state, &path, sparse.clone()),
+    };
+    warm_working_set(&state, &path);
+    state.events.publish(events::Event::IndexingPrioritized {
+        workspace: workspace.clone(),
+        files: file_count,
+        duration_ms: started.elapsed().as_millis() as u64,
+    });
+
+    // Restore persisted chat sessions
+    state.chat.lock().unwrap().load_workspace(&path).map_err(|e| e.to_string())?;
+
+    let background = path.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        if let Err(e) = index_workspace(&state, &background, indexer_settings, sparse, started) {
+            log::error!("Failed to index {:?}: {}", background, e);
+        }
+    });
+
+    Ok(WorkspaceInfo {
+        path: workspace,
+        file_count,
+        indexed: false,
+        trusted: state.trust.lock().unwrap().is_trusted(&path),
+    })
+}
+
+/// Move the complete index and graph of the workspace being closed to standby
+fn park_workspace(state: &AppState, settings: &settings::IndexerSettings) {
+    let Some(previous) = state.standby.lock().unwrap().take_indexed() else {
+        return;
+    };
+    if settings.standby_workspaces == 0 {
+        return;
+    }
+    let index = std::mem::replace(&mut *state.file_index.lock().unwrap(), file_indexer::FileIndex::new());
+    let graph = std::mem::replace(&mut *state.code_graph.lock().unwrap(), mimi_engine::CodeGraph::new());
+    let budget = settings.standby_memory_mb.saturating_mul(1024 * 1024);
+    state.standby.lock().unwrap().park(&previous, index, graph, settings.standby_workspaces, budget);
+}
+
+/// Make a kept index and graph current, re-indexing the files changed since `since`; returns the file count
+fn catch_up(
+    state: &AppState,
+    workspace: &Path,
+    mut index: file_indexer::FileIndex,
+    mut graph: mimi_engine::CodeGraph,
+    since: std::time::SystemTime,
+) -> usize {
+    let changed = standby::changed_since(workspace, &index, since);
+    let renamed = index.update_files(workspace, &changed);
+    for path in changed.iter().filter(|p| mimi_engine::CodeGraph::is_analyzed(p)) {
+        match documents::read_source(&state.documents, path) {
+            Ok(content) => graph.update_file(workspace, path, &content),
+            Err(_) => graph.remove_file(path),
+        }
+    }
+    log::info!("Caught up on {} files changed in {:?}", changed.len(), workspace);
+    let file_count = index.file_count();
+    *state.file_index.lock().unwrap() = index;
+    *state.code_graph.lock().unwrap() = graph;
+    renames::follow(state, workspace, &renamed);
+    let changed: Vec<String> = changed.iter().map(|p| p.to_string_lossy().to_string()).collect();
+    index_store::record(state, workspace, &changed);
+    file_count
+}
+
+/// Restore a parked index and graph, catching up on files changed since; `None` if none is parked
+fn resume_workspace(state: &AppState, workspace: &Path) -> Option<usize> {
+    let (index, graph, parked_at) = state.standby.lock().unwrap().take(workspace)?;
+    let file_count = catch_up(state, workspace, index, graph, parked_at);
+    state.standby.lock().unwrap().set_indexed(workspace);
+    Some(file_count)
+}
+
+/// Start from the index the last session persisted, catching up on files changed since; `None` without one.
+/// Symbols are missing until the full index replaces it.
+fn restore_persisted_index(
+    state: &AppState,
+    workspace: &Path,
+    settings: &settings::IndexerSettings,
+    sparse: Option<Vec<String>>,
+) -> Option<usize> {
+    let persisted = match state.index_store.load(workspace) {
+        Ok(persisted) => persisted?,
+        Err(e) => {
+            log::warn!("Failed to load the persisted index of {:?}: {}", workspace, e);
+            return None;
+        }
+    };
+    let entries = persisted.entries.into_iter().map(|entry| (entry.info, entry.words)).collect();
+    let mut index = file_indexer::FileIndex::from_entries(entries);
+    index.configure_sparse(sparse);
+    index.configure_generated(settings.generated_patterns.clone(), settings.index_generated);
+    let graph = mimi_engine::CodeGraph::from_dependency_lists(persisted.dependencies);
+    Some(catch_up(state, workspace, index, graph, persisted.saved_at))
+}
+
+/// Start a fresh index and graph with the open and pinned files, the files they import and the top-level manifests
+fn index_priority_files(state: &AppState, workspace: &Path, sparse: Option<Vec<String>>) -> usize {
+    let pinned = working_set::absolute_paths(workspace).unwrap_or_default();
+    let mut open = state.documents.lock().unwrap().paths();
+    open.extend(pinned);
+    open.retain(|p| p.starts_with(workspace) && p.is_file());
+
+    let mut graph = mimi_engine::CodeGraph::new();
+    let analyze = |graph: &mut mimi_engine::CodeGraph, path: &Path| {
+        if !mimi_engine::CodeGraph::is_analyzed(path) {
+            return;
+        }
+        match documents::read_source(&state.documents, path) {
+            Ok(content) => graph.update_file(workspace, path, &content),
+            Err(e) => log::debug!("Skipping analysis of {:?}: {}", path, e),
+        }
+    };
+    for path in &open {
+        analyze(&mut graph, path);
+    }
+    let neighbors: Vec<PathBuf> = open
+        .iter()
+        .flat_map(|p| graph.get_dependencies(&p.to_string_lossy()))
+        .map(PathBuf::from)
+        .filter(|p| p.is_file())
+        .collect();
+    let files = file_indexer::priority_files(workspace, &open, &neighbors);
+    for path in files.iter().filter(|p| !open.contains(p)) {
+        analyze(&mut graph, path);
+    }
+
+    let mut index = file_indexer::FileIndex::new();
+    index.configure_sparse(sparse);
+    index.update_files(workspace, &files);
+    let file_count = index.file_count();
+    *state.file_index.lock().unwrap() = index;
+    *state.code_graph.lock().unwrap() = graph;
+    file_count
+}
+
+/// Index and analyze the whole workspace, then swap the results in for the priority index
+fn index_workspace(
+    state: &AppState,
+    workspace: &Path,
+    settings: settings::IndexerSettings,
+    sparse: Option<Vec<String>>,
+    started: std::time::Instant,
+) -> Result<(), String> {
+    // Parallel indexing and analysis share a pool sized by the indexer settings
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(settings.threads)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut index = file_indexer::FileIndex::new();
+    index.configure_sparse(sparse);
+    index.configure_generated(settings.generated_patterns, settings.index_generated);
+    pool.install(|| index.index_directory(workspace)).map_err(|e| e.to_string())?;
+    let excluded = index.excluded_from_analysis();
+    let file_count = index.file_count();
+
+    // Build dependency graph; generated files found by the indexer are left out
+    let mut graph = mimi_engine::CodeGraph::new();
+    pool.install(|| graph.analyze_workspace(workspace, &excluded)).map_err(|e| e.to_string())?;
+    // Unsaved buffers win over the files on disk
+    let open = state.documents.lock().unwrap().paths();
+    for path in open.iter().filter(|p| p.starts_with(workspace) && mimi_engine::CodeGraph::is_analyzed(p)) {
+        if let Some(content) = state.documents.lock().unwrap().live_text(path) {
+            graph.update_file(workspace, path, &content);
+        }
+    }
+
+    // Another workspace may have been opened in the meantime
+    if state.workspace_path.lock().unwrap().as_deref() != Some(workspace) {
+        return Ok(());
+    }
+    index.set_pinned(&working_set::absolute_paths(workspace).unwrap_or_default());
+    if let Err(e) = state.index_store.save(workspace, &index, &graph) {
+        log::warn!("Failed to persist the index of {:?}: {}", workspace, e);
+    }
+    *state.file_index.lock().unwrap() = index;
+    *state.code_graph.lock().unwrap() = graph;
+    state.standby.lock().unwrap().set_indexed(workspace);
+
+    state.events.publish(events::Event::IndexingFinished {
+        workspace: workspace.to_string_lossy().to_string(),
+        files: file_count,
+        duration_ms: started.elapsed().as_millis() as u64,
+    });
+    Ok(())
+}
+
+/// Directories to index fully when sparse mode is on
+fn sparse_directories(workspace: &Path, settings: &settings::IndexerSettings) -> Option<Vec<String>> {
+    if !settings.sparse {
+        return None;
+    }
+    let selected = sparse::load(workspace).map(|set| set.directories).unwrap_or_else(|e| {
+        log::warn!("Failed to load the sparse index: {}", e);
+        Vec::new()
+    });
+    Some(sparse::directories(&selected, &sparse::recent_directories(workspace, settings.sparse_recent_days)))
+}
+
+/// Boost pinned files in search, build their line indexes and publish their diagnostics
+fn warm_working_set(state: &AppState, workspace: &Path) {
+    let pinned = working_set::absolute_paths(workspace).unwrap_or_else(|e| {
+        log::warn!("Failed to load the working set: {}", e);
+        Vec::new()
+    });
+    state.file_index.lock().unwrap().set_pinned(&pinned);
+    for path in pinned.iter().filter(|p| p.is_file()) {
+        if let Err(e) = state.line_indexes.lock().unwrap().get_or_build(path) {
+            log::debug!("No line index for {:?}: {}", path, e);
+        }
+        let file_path = path.to_string_lossy().to_string();
+        match documents::read_source(&state.documents, path) {
+            Ok(content) => {
+                if let Err(e) = publish_analysis(state, &file_path, &content) {
+                    log::warn!("Failed to analyze pinned file {}: {}", file_path, e);
+                }
+            }
+            Err(e) => log::debug!("Skipping analysis of {}: {}", file_path, e),
+        }
+    }
+}
+
+/// Pin a file to the working set
+#[tauri::command]
+async fn pin_file(path: String, state: State<'_, AppState>) -> Result<Vec<working_set::PinnedFile>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let files = working_set::pin(&workspace, &path).map_err(|e| e.to_string())?;
+    warm_working_set(&state, &workspace);
+    Ok(files)
+}
+
+/// Remove a file from the working set
+#[tauri::command]
+async fn unpin_file(path: String, state: State<'_, AppState>) -> Result<Vec<working_set::PinnedFile>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let files = working_set::unpin(&workspace, &path).map_err(|e| e.to_string())?;
+    let pinned: Vec<PathBuf> = files.iter().map(|f| workspace.join(&f.path)).collect();
+    state.file_index.lock().unwrap().set_pinned(&pinned);
+    Ok(files)
+}
+
+/// Pinned files of the workspace, oldest pin first
+#[tauri::command]
+async fn get_working_set(state: State<'_, AppState>) -> Result<Vec<working_set::PinnedFile>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    working_set::list(&workspace).map_err(|e| e.to_string())
+}
+
+/// Search files in workspace
+#[tauri::command]
+async fn search_files(query: String, state: State<'_, AppState>) -> Result<Vec<FileMatch>, String> {
+    let index = state.file_index.lock().unwrap();
+    Ok(index.search(&query))
+}
+
+/// Search file contents for `query` (case-insensitive), one match per line
+#[tauri::command]
+async fn grep_workspace(query: String, limit: Option<usize>, state: State<'_, AppState>) -> Result<Vec<FileMatch>, String> {
+    let paths: Vec<String> = {
+        let index = state.file_index.lock().unwrap();
+        let excluded = index.excluded_from_analysis();
+        index.paths().filter(|p| !excluded.contains(*p)).cloned().collect()
+    };
+    tauri::async_runtime::spawn_blocking(move || file_indexer::grep(&paths, &query, limit.unwrap_or(200)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Clusters of byte-identical files with the space their extra copies take
+#[tauri::command]
+async fn find_duplicate_files(state: State<'_, AppState>) -> Result<Vec<file_indexer::DuplicateCluster>, String> {
+    Ok(state.file_index.lock().unwrap().duplicate_clusters())
+}
+
+/// Directory size tree, largest files and growth since the previous analysis
+#[tauri::command]
+async fn analyze_disk_usage(
+    respect_ignores: Option<bool>,
+    top_files: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<disk_usage::DiskUsage, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let respect_ignores = respect_ignores.unwrap_or(true);
+    let top_files = top_files.unwrap_or(50);
+    tauri::async_runtime::spawn_blocking(move || disk_usage::analyze(&workspace, respect_ignores, top_files))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Search with `field:value` filters (`lang:`, `path:`, `symbol:`, `modified:`, `content:`) plus free text
+#[tauri::command]
+async fn advanced_search(query: String, limit: Option<usize>, state: State<'_, AppState>) -> Result<Vec<FileMatch>, String> {
+    let query = search_query::parse(&query).map_err(|e| e.to_string())?;
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let limit = limit.unwrap_or(200);
+
+    // Content terms are grepped over every file passing the other filters
+    let file_limit = if query.content.is_empty() { limit } else { usize::MAX };
+    let files = {
+        let index = state.file_index.lock().unwrap();
+        let graph = state.code_graph.lock().unwrap();
+        query.file_matches(&workspace, &index, &graph, file_limit)
+    };
+    if query.content.is_empty() {
+        return Ok(files);
+    }
+
+    let paths: Vec<String> = files.into_iter().map(|m| m.path).collect();
+    tauri::async_runtime::spawn_blocking(move || query.content_matches(&paths, limit))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get file dependencies
+#[tauri::command]
+async fn get_dependencies(file_path: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let graph = state.code_graph.lock().unwrap();
+    Ok(graph.get_dependencies(&file_path))
+}
+
+/// Get files that depend on this file
+#[tauri::command]
+async fn get_dependents(file_path: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let graph = state.code_graph.lock().unwrap();
+    Ok(graph.get_dependents(&file_path))
+}
+
+/// Neighborhood of files in the dependency graph, filtered, sorted and paginated
+#[tauri::command]
+async fn query_graph(
+    request: mimi_engine::GraphQuery,
+    state: State<'_, AppState>,
+) -> Result<mimi_engine::GraphQueryResult, String> {
+    let graph = state.code_graph.lock().unwrap().snapshot();
+    tauri::async_runtime::spawn_blocking(move || graph.query(&request)).await.map_err(|e| e.to_string())
+}
+
+/// Cohesive clusters of at least `min_size` files (default 3) that could become modules, with workspace-relative paths
+#[tauri::command]
+async fn suggest_modularization(
+    min_size: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<modularization::ModuleSuggestion>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let graph = state.code_graph.lock().unwrap().snapshot();
+    let relative = move |path: String| {
+        Path::new(&path).strip_prefix(&workspace).unwrap_or(Path::new(&path)).to_string_lossy().replace('\\', "/")
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let edges: Vec<(String, String)> =
+            graph.internal_edges().into_iter().map(|(from, to)| (relative(from), relative(to))).collect();
+        modularization::suggest(&edges, min_size.unwrap_or(3))
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Edges, cycles and impacted files a changeset would produce, computed on a snapshot of the graph
+#[tauri::command]
+async fn preview_graph_impact(
+    changeset: changeset::Changeset,
+    state: State<'_, AppState>,
+) -> Result<mimi_engine::GraphImpact, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    confine_changeset(&state, &changeset)?;
+    let previews = changeset.preview(&workspace).map_err(|e| e.to_string())?;
+    let live = state.code_graph.lock().unwrap().snapshot();
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut speculative = live.snapshot();
+        let touched = speculative.apply_previews(&workspace, &previews);
+        live.impact_of(&speculative, &touched)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Analyze code for suggestions; without `content`, the open buffer or the file on disk
+#[tauri::command]
+async fn analyze_code(
+    file_path: String,
+    content: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<CodeSuggestion>, String> {
+    let content = match content {
+        Some(content) => content,
+        None => documents::read_source(&state.documents, &confined(&state, &file_path)?).map_err(|e| e.to_string())?,
+    };
+    publish_analysis(&state, &file_path, &content)
+}
+
+/// Analyze an unsaved buffer again where it changed: `dirty_ranges`, lines changed since the last run
+/// and the functions around them. Fast enough to run on every debounced keystroke.
+#[tauri::command]
+async fn analyze_buffer(
+    path: String,
+    content: String,
+    dirty_ranges: Vec<buffer_analysis::LineRange>,
+    state: State<'_, AppState>,
+) -> Result<buffer_analysis::BufferAnalysis, String> {
+    if state.file_index.lock().unwrap().excluded_from_analysis().contains(&path) {
+        return Ok(buffer_analysis::BufferAnalysis::default());
+    }
+    let analyzer = configured_analyzer(&state);
+    let analysis = buffer_analysis::analyze(&state.buffer_analysis, &analyzer, &path, &content, &dirty_ranges);
+
+    let diagnostics = analysis
+        .suggestions
+        .iter()
+        .map(|s| diagnostics::Diagnostic::from_suggestion(&path, diagnostics::ANALYZER_SOURCE, s))
+        .collect();
+    let count = {
+        let mut store = state.diagnostics.lock().unwrap();
+        store.publish(&path, diagnostics::ANALYZER_SOURCE, diagnostics);
+        store.get_file(&path).len()
+    };
+    state.events.publish(events::Event::DiagnosticsChanged { file: path, count });
+    Ok(analysis)
+}
+
+/// Analyzer with the rules, frameworks, rule toggles and budget of the current settings and workspace
+fn configured_analyzer(state: &AppState) -> code_analyzer::CodeAnalyzer {
+    let analyzer_settings = state.settings.lock().unwrap().analyzer.clone();
+    let workspace = state.workspace_path.lock().unwrap().clone();
+    let frameworks = workspace.as_deref().map(framework_rules::detect).unwrap_or_default();
+    let budget = match workspace.as_deref().map(analysis_budget::load).transpose() {
+        Ok(budget) => budget.unwrap_or_default(),
+        Err(e) => {
+            log::warn!("Using the default analysis budget: {}", e);
+            analysis_budget::AnalysisBudget::default()
+        }
+    };
+    code_analyzer::CodeAnalyzer::with_rules(analyzer_settings.rules)
+        .with_frameworks(frameworks)
+        .with_registry(state.analyzer_rules.clone(), analyzer_settings.disabled_rules)
+        .with_budget(budget)
+}
+
+/// Run the analyzer on a file and publish the result to the diagnostics store
+fn publish_analysis(state: &AppState, file_path: &str, content: &str) -> Result<Vec<CodeSuggestion>, String> {
+    // Generated files are excluded from analysis unless configured otherwise
+    if state.file_index.lock().unwrap().excluded_from_analysis().contains(file_path) {
+        return Ok(Vec::new());
+    }
+    let analyzer_settings = state.settings.lock().unwrap().analyzer.clone();
+    let workspace = state.workspace_path.lock().unwrap().clone();
+    // A full run is the base the next incremental buffer analysis builds on
+    let findings = configured_analyzer(state).findings(file_path, content, false);
+    let mut suggestions: Vec<CodeSuggestion> = findings.iter().map(|f| f.suggestion.clone()).collect();
+    state.buffer_analysis.lock().unwrap().store(file_path, content, findings);
+    let misspellings = if analyzer_settings.spellcheck {
+        state.spelling.lock().unwrap().check(file_path, content)
+    } else {
+        Vec::new()
+    };
+
+    // Publish to the diagnostics store for the problems panel
+    let to_diagnostics = |source: &str, suggestions: &[CodeSuggestion]| -> Vec<diagnostics::Diagnostic> {
+        suggestions
+            .iter()
+            .map(|s| diagnostics::Diagnostic::from_suggestion(file_path, source, s))
+            .collect()
+    };
+    let count = {
+        let mut store = state.diagnostics.lock().unwrap();
+        store.publish(file_path, diagnostics::ANALYZER_SOURCE, to_diagnostics(diagnostics::ANALYZER_SOURCE, &suggestions));
+        store.publish(file_path, spellcheck::SPELLCHECK_SOURCE, to_diagnostics(spellcheck::SPELLCHECK_SOURCE, &misspellings));
+        store.get_file(file_path).len()
+    };
+    state.events.publish(events::Event::DiagnosticsChanged {
+        file: file_path.to_string(),
+        count,
+    });
+
+    if let Some(workspace) = workspace {
+        let severities: Vec<&str> = suggestions.iter().map(|s| s.severity.as_str()).collect();
+        if let Err(e) = history::record_analysis(&workspace, file_path, content, diagnostics::ANALYZER_SOURCE, &severities) {
+            log::warn!("Failed to record analysis of {}: {}", file_path, e);
+        }
+    }
+
+    suggestions.extend(misspellings);
+    Ok(suggestions)
+}
+
+/// Get workspace statistics
+#[tauri::command]
+async fn get_workspace_stats(state: State<'_, AppState>) -> Result<WorkspaceStats, String> {
+    let index = state.file_index.lock().unwrap();
+    let graph = state.code_graph.lock().unwrap();
+    let line_counts_by_language = index.line_counts_by_language();
+
+    Ok(WorkspaceStats {
+        total_files: index.file_count(),
+        total_lines: index.total_lines(),
+        generated_files: index.generated_count(),
+        line_counts: line_counts_by_language.values().fold(Default::default(), |mut total, counts| {
+            total += *counts;
+            total
+        }),
+        by_language: index.files_by_language(),
+        line_counts_by_language,
+        dependency_count: graph.edge_count(),
+    })
+}
+
+/// Statistics of a directory (absolute or workspace-relative) and each of its immediate subdirectories
+#[tauri::command]
+async fn get_directory_stats(path: String, state: State<'_, AppState>) -> Result<stats::DirectoryStats, String> {
+    let dir = confined(&state, &path)?;
+    if !dir.is_dir() {
+        return Err(format!("Not a directory in the workspace: {}", path));
+    }
+    let diagnostics = state.diagnostics.lock().unwrap().count_by_file();
+    let index = state.file_index.lock().unwrap();
+    Ok(stats::directory_stats(&dir, &index, &diagnostics))
+}
+
+/// Get diagnostics for a file, or the whole workspace
+#[tauri::command]
+async fn get_diagnostics(
+    file_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<diagnostics::Diagnostic>, String> {
+    let store = state.diagnostics.lock().unwrap();
+    Ok(match file_path {
+        Some(file) => store.get_file(&file),
+        None => store.all(),
+    })
+}
+
+/// Diagnostics in files the CODEOWNERS file assigns to `team`, e.g. `@org/frontend`
+#[tauri::command]
+async fn get_diagnostics_by_owner(team: String, state: State<'_, AppState>) -> Result<Vec<diagnostics::Diagnostic>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let owners = codeowners::CodeOwners::load(&workspace)
+        .map_err(|e| e.to_string())?
+        .ok_or("The workspace has no CODEOWNERS file")?;
+    let all = state.diagnostics.lock().unwrap().all();
+    Ok(all
+        .into_iter()
+        .filter(|d| {
+            let relative = Path::new(&d.file).strip_prefix(&workspace).unwrap_or(Path::new(&d.file));
+            owners.is_owned_by(&relative.to_string_lossy().replace('\\', "/"), &team)
+        })
+        .collect())
+}
+
+/// List configured AI providers
+#[tauri::command]
+async fn list_ai_providers(state: State<'_, AppState>) -> Result<Vec<ai_provider::ProviderInfo>, String> {
+    Ok(state.ai_providers.lock().unwrap().list())
+}
+
+/// Switch the active AI provider
+#[tauri::command]
+async fn set_active_ai_provider(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state
+        .ai_providers
+        .lock()
+        .unwrap()
+        .set_active(&id)
+        .map_err(|e| e.to_string())
+}
+
+/// Review the working tree (or a git range) with the AI provider
+#[tauri::command]
+async fn ai_review_changes(
+    range: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ai_review::ReviewComment>, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+
+    let repo = workspace.clone();
+    let diff = tauri::async_runtime::spawn_blocking(move || git::diff(&repo, range.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    let files = git::parse_diff(&diff);
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let context = {
+        let graph = state.code_graph.lock().unwrap();
+        ai_review::graph_context(&graph, &workspace, &files)
+    };
+    let provider = state.ai_providers.lock().unwrap().active().map_err(|e| e.to_string())?;
+
+    let comments = ai_review::review_changes(provider.as_ref(), &diff, &files, &context)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Replace previous review results in the diagnostics store
+    let mut store = state.diagnostics.lock().unwrap();
+    store.clear_source(ai_review::SOURCE);
+    for file in &files {
+        let absolute = workspace.join(&file.path).to_string_lossy().to_string();
+        let diagnostics = comments
+            .iter()
+            .filter(|c| c.file == file.path)
+            .map(|c| diagnostics::Diagnostic::from_suggestion(&absolute, ai_review::SOURCE, &c.to_suggestion()))
+            .collect();
+        store.publish(&absolute, ai_review::SOURCE, diagnostics);
+    }
+
+    Ok(comments)
+}
+
+/// Create a chat session
+#[tauri::command]
+async fn create_chat_session(
+    title: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<chat::ChatSession, String> {
+    state.chat.lock().unwrap().create_session(title).map_err(|e| e.to_string())
+}
+
+/// Rename a chat session
+#[tauri::command]
+async fn rename_chat_session(session_id: String, title: String, state: State<'_, AppState>) -> Result<(), String> {
+    state
+        .chat
+        .lock()
+        .unwrap()
+        .rename_session(&session_id, &title)
+        .map_err(|e| e.to_string())
+}
+
+/// Delete a chat session and its transcript
+#[tauri::command]
+async fn delete_chat_session(session_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.chat.lock().unwrap().delete_session(&session_id).map_err(|e| e.to_string())
+}
+
+/// List chat sessions of the workspace
+#[tauri::command]
+async fn list_chat_sessions(state: State<'_, AppState>) -> Result<Vec<chat::ChatSessionSummary>, String> {
+    Ok(state.chat.lock().unwrap().list_sessions())
+}
+
+/// Get a chat session with its full transcript
+#[tauri::command]
+async fn get_chat_session(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<chat::ChatSession>, String> {
+    Ok(state.chat.lock().unwrap().get_session(&session_id))
+}
+
+/// Attach a file or selection as context for the next message
+#[tauri::command]
+async fn attach_chat_context(
+    session_id: String,
+    path: String,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let content = documents::read_source(&state.documents, &confined(&state, &path)?).map_err(|e| e.to_string())?;
+    let attachment = chat::attachment(&path, content, start_line, end_line);
+    state
+        .chat
+        .lock()
+        .unwrap()
+        .attach_context(&session_id, attachment)
+        .map_err(|e| e.to_string())
+}
+
+/// Send a user message and stream the assistant response as `chat-stream` events
+#[tauri::command]
+async fn send_chat_message(
+    session_id: String,
+    content: String,
+    window: tauri::Window,
+    state: State<'_, AppState>,
+) -> Result<chat::ChatEntry, String> {
+    let provider = state.ai_providers.lock().unwrap().active().map_err(|e| e.to_string())?;
+    let messages = {
+        let mut chat = state.chat.lock().unwrap();
+        chat.append_message(&session_id, ai_provider::Role::User, &content)
+            .map_err(|e| e.to_string())?;
+        chat.prompt_messages(&session_id, provider.model())
+            .map_err(|e| e.to_string())?
+    };
+
+    let stream_session = session_id.clone();
+    let stream_window = window.clone();
+    let on_chunk = move |delta: &str| {
+        let _ = stream_window.emit(
+            "chat-stream",
+            chat::ChatStreamEvent {
+                session_id: stream_session.clone(),
+                delta: delta.to_string(),
+                done: false,
+            },
+        );
+    };
+    let request = ai_provider::CompletionRequest::new(messages);
+    let response = provider.stream(&request, &on_chunk).await.map_err(|e| e.to_string())?;
+
+    let entry = state
+        .chat
+        .lock()
+        .unwrap()
+        .append_message(&session_id, ai_provider::Role::Assistant, &response)
+        .map_err(|e| e.to_string())?;
+
+    let _ = window.emit(
+        "chat-stream",
+        chat::ChatStreamEvent {
+            session_id,
+            delta: String::new(),
+            done: true,
+        },
+    );
+
+    Ok(entry)
+}
+
+/// Start an agent run for a goal; returns when done or waiting for approval
+#[tauri::command]
+async fn start_agent_task(
+    goal: String,
+    max_steps: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<agent::AgentRun, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+    let provider = state.ai_providers.lock().unwrap().active().map_err(|e| e.to_string())?;
+
+    let tools = &state.agent_tools;
+    let mut run = agent::AgentRun::new(&goal, max_steps.unwrap_or(agent::DEFAULT_MAX_STEPS), tools);
+    let ctx = agent::ToolContext { workspace: &workspace, state: state.inner() };
+    agent::drive(&mut run, provider.as_ref(), tools, &ctx).await;
+
+    Ok(store_agent_run(run, &workspace, &state))
+}
+
+/// Approve or reject the pending action of an agent run and continue it
+#[tauri::command]
+async fn approve_agent_action(
+    run_id: String,
+    approved: bool,
+    state: State<'_, AppState>,
+) -> Result<agent::AgentRun, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+    let provider = state.ai_providers.lock().unwrap().active().map_err(|e| e.to_string())?;
+    let mut run = state
+        .agent_runs
+        .lock()
+        .unwrap()
+        .remove(&run_id)
+        .ok_or("Unknown agent run")?;
+
+    let tools = &state.agent_tools;
+    let ctx = agent::ToolContext { workspace: &workspace, state: state.inner() };
+    if let Err(e) = agent::resolve_approval(&mut run, approved, tools, &ctx).await {
+        store_agent_run(run, &workspace, &state);
+        return Err(e.to_string());
+    }
+    agent::drive(&mut run, provider.as_ref(), tools, &ctx).await;
+
+    Ok(store_agent_run(run, &workspace, &state))
+}
+
+/// Get an agent run with its audit log
+#[tauri::command]
+async fn get_agent_run(run_id: String, state: State<'_, AppState>) -> Result<Option<agent::AgentRun>, String> {
+    Ok(state.agent_runs.lock().unwrap().get(&run_id).cloned())
+}
+
+/// Cancel an agent run that is waiting for approval
+#[tauri::command]
+async fn cancel_agent_run(run_id: String, state: State<'_, AppState>) -> Result<agent::AgentRun, String> {
+    let mut runs = state.agent_runs.lock().unwrap();
+    let run = runs.get_mut(&run_id).ok_or("Unknown agent run")?;
+    if run.status == agent::AgentStatus::AwaitingApproval || run.status == agent::AgentStatus::Running {
+        run.pending = None;
+        run.status = agent::AgentStatus::Cancelled;
+    }
+    Ok(run.clone())
+}
+
+fn store_agent_run(run: agent::AgentRun, workspace: &Path, state: &AppState) -> agent::AgentRun {
+    if let Err(e) = agent::save_run(workspace, &run) {
+        log::warn!("Failed to persist agent run {}: {}", run.id, e);
+    }
+    state.agent_runs.lock().unwrap().insert(run.id.clone(), run.clone());
+    run
+}
+
+/// Count tokens of a text for a model (defaults to the active provider's model)
+#[tauri::command]
+async fn count_tokens(
+    text: String,
+    model: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<tokens::TokenCount, String> {
+    let model = match model {
+        Some(model) => model,
+        None => state
+            .ai_providers
+            .lock()
+            .unwrap()
+            .active()
+            .map(|p| p.model().to_string())
+            .map_err(|e| e.to_string())?,
+    };
+    Ok(tokens::count(&text, &model))
+}
+
+/// List downloaded local GGUF models
+#[tauri::command]
+async fn list_local_models(app: tauri::AppHandle) -> Result<Vec<local_llm::LocalModelInfo>, String> {
+    let dir = local_llm::models_dir(&app_data_dir(&app)?);
+    local_llm::list_models(&dir).map_err(|e| e.to_string())
+}
+
+/// Download a GGUF model, emitting `local-model-download` progress events; `sha256` is checked when given
+#[tauri::command]
+async fn download_local_model(
+    name: String,
+    url: String,
+    sha256: Option<String>,
+    window: tauri::Window,
+    app: tauri::AppHandle,
+) -> Result<local_llm::LocalModelInfo, String> {
+    let dir = local_llm::models_dir(&app_data_dir(&app)?);
+    let on_progress = |progress: local_llm::DownloadProgress| {
+        let _ = window.emit("local-model-download", progress);
+    };
+    local_llm::download_model(&dir, &name, &url, sha256.as_deref(), on_progress)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Delete a downloaded local model
+#[tauri::command]
+async fn delete_local_model(name: String, app: tauri::AppHandle) -> Result<(), String> {
+    let dir = local_llm::models_dir(&app_data_dir(&app)?);
+    local_llm::delete_model(&dir, &name).map_err(|e| e.to_string())
+}
+
+/// Load a local model and register it as the `local` AI provider
+#[tauri::command]
+async fn load_local_model(
+    name: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<ai_provider::ProviderInfo>, String> {
+    let dir = local_llm::models_dir(&app_data_dir(&app)?);
+    let provider = tauri::async_runtime::spawn_blocking(move || local_llm::load_provider(&dir, &name))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let mut registry = state.ai_providers.lock().unwrap();
+    registry.register(provider);
+    Ok(registry.list())
+}
+
+/// Get the engine settings
+#[tauri::command]
+async fn get_settings(state: State<'_, AppState>) -> Result<settings::Settings, String> {
+    Ok(state.settings.lock().unwrap().clone())
+}
+
+/// Replace the engine settings, persist them and apply them to all subsystems
+#[tauri::command]
+async fn update_settings(
+    settings: settings::Settings,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let path = settings::settings_path(&app_config_dir(&app)?);
+    settings::save(&path, &settings).map_err(|e| e.to_string())?;
+    apply_settings(&settings, &state);
+    *state.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+/// Analyzer rules in run order, with whether each is enabled
+#[tauri::command]
+async fn list_analyzer_rules(state: State<'_, AppState>) -> Result<Vec<code_analyzer::RuleInfo>, String> {
+    let disabled = state.settings.lock().unwrap().analyzer.disabled_rules.clone();
+    Ok(state.analyzer_rules.list(&disabled))
+}
+
+/// Switch one analyzer rule on or off and persist the choice
+#[tauri::command]
+async fn set_analyzer_rule_enabled(
+    id: String,
+    enabled: bool,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<code_analyzer::RuleInfo>, String> {
+    if !state.analyzer_rules.list(&[]).iter().any(|rule| rule.id == id) {
+        return Err(format!("Unknown analyzer rule: {}", id));
+    }
+    let mut settings = state.settings.lock().unwrap().clone();
+    settings.analyzer.disabled_rules.retain(|disabled| *disabled != id);
+    if !enabled {
+        settings.analyzer.disabled_rules.push(id);
+    }
+    settings::save(&settings::settings_path(&app_config_dir(&app)?), &settings).map_err(|e| e.to_string())?;
+    let rules = state.analyzer_rules.list(&settings.analyzer.disabled_rules);
+    *state.settings.lock().unwrap() = settings;
+    Ok(rules)
+}
+
+/// Change the log level of one subsystem at runtime and persist it; an empty target sets the default
+/// level and no level drops the subsystem's override
+#[tauri::command]
+async fn set_log_level(
+    target: String,
+    level: Option<String>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<settings::LogSettings, String> {
+    let mut settings = state.settings.lock().unwrap().clone();
+    logging::set_level(&mut settings.logging, &target, level.as_deref()).map_err(|e| e.to_string())?;
+    settings::save(&settings::settings_path(&app_config_dir(&app)?), &settings).map_err(|e| e.to_string())?;
+    let levels = settings.logging.clone();
+    *state.settings.lock().unwrap() = settings;
+    Ok(levels)
+}
+
+fn keymap(state: &AppState) -> keybindings::Keymap {
+    let overrides = state.settings.lock().unwrap().keybindings.overrides.clone();
+    state.keybindings.lock().unwrap().keymap(&overrides)
+}
+
+/// Effective keybindings with conflicts and the rules that were left out
+#[tauri::command]
+async fn list_keybindings(state: State<'_, AppState>) -> Result<keybindings::KeymapReport, String> {
+    Ok(keymap(&state).report())
+}
+
+/// Command bound to the keys pressed so far (strokes separated by spaces) in the active when-context flags
+#[tauri::command]
+async fn resolve_keybinding(
+    keys: String,
+    context: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<keybindings::KeyResolution, String> {
+    keymap(&state).resolve(&keys, &context).map_err(|e| e.to_string())
+}
+
+/// Replace the user's keybinding overrides; nothing is saved if any of them is invalid
+#[tauri::command]
+async fn set_keybinding_overrides(
+    overrides: Vec<keybindings::KeybindingRule>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<keybindings::KeymapReport, String> {
+    let keymap = state.keybindings.lock().unwrap().keymap(&overrides);
+    let invalid: Vec<String> = keymap
+        .issues()
+        .iter()
+        .filter(|issue| issue.source == keybindings::BindingSource::User)
+        .map(|issue| format!("{} ({}): {}", issue.keys, issue.command, issue.message))
+        .collect();
+    if !invalid.is_empty() {
+        return Err(invalid.join("\n"));
+    }
+    let mut settings = state.settings.lock().unwrap().clone();
+    settings.keybindings.overrides = overrides;
+    settings::save(&settings::settings_path(&app_config_dir(&app)?), &settings).map_err(|e| e.to_string())?;
+    *state.settings.lock().unwrap() = settings;
+    Ok(keymap.report())
+}
+
+/// Add or replace a plugin's commands and keybindings; an empty contribution removes the plugin
+#[tauri::command]
+async fn register_plugin_keybindings(
+    plugin: String,
+    contribution: keybindings::PluginKeybindings,
+    state: State<'_, AppState>,
+) -> Result<keybindings::KeymapReport, String> {
+    state.keybindings.lock().unwrap().register(plugin, contribution);
+    Ok(keymap(&state).report())
+}
+
+/// Ask the release endpoint of the configured channel for a newer version, regardless of the check policy
+#[tauri::command]
+async fn check_for_updates(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<updates::UpdateInfo, String> {
+    let settings = state.settings.lock().unwrap().updates.clone();
+    let dir = updates::updates_dir(&app_data_dir(&app)?);
+    updates::check(&dir, &settings).await.map_err(|e| e.to_string())
+}
+
+/// Download the update found by the last check and verify it with the updater public key; returns the package path
+#[tauri::command]
+async fn download_update(window: tauri::Window, app: tauri::AppHandle) -> Result<String, String> {
+    let dir = updates::updates_dir(&app_data_dir(&app)?);
+    let info = updates::last_check(&dir)
+        .map_err(|e| e.to_string())?
+        .ok_or("No update check yet; call check_for_updates first")?;
+    let public_key = app.config().tauri.updater.pubkey.clone();
+    let on_progress = |progress: updates::UpdateProgress| {
+        let _ = window.emit("update-download", progress);
+    };
+    let path = updates::download(&dir, &info, &public_key, on_progress)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Latest engine log entries, oldest first
+#[tauri::command]
+async fn get_recent_logs(filter: Option<logging::LogFilter>) -> Result<Vec<logging::LogEntry>, String> {
+    logging::recent(&filter.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+/// Size and time limits of analyzing one file in the open workspace
+#[tauri::command]
+async fn get_analysis_budget(state: State<'_, AppState>) -> Result<analysis_budget::AnalysisBudget, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    analysis_budget::load(&workspace).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_analysis_budget(
+    budget: analysis_budget::AnalysisBudget,
+    state: State<'_, AppState>,
+) -> Result<analysis_budget::AnalysisBudget, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    analysis_budget::save(&workspace, &budget).map_err(|e| e.to_string())?;
+    Ok(budget)
+}
+
+/// Files of the open workspace whose last analysis went over budget, slowest first
+#[tauri::command]
+async fn get_slow_files(state: State<'_, AppState>) -> Result<Vec<code_analyzer::SlowFile>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let mut files = state.analyzer_rules.slow_files();
+    files.retain(|file| Path::new(&file.path).starts_with(&workspace));
+    Ok(files)
+}
+
+/// Time spent in each analyzer rule since startup, slowest first
+#[tauri::command]
+async fn get_analyzer_rule_timings(state: State<'_, AppState>) -> Result<Vec<code_analyzer::RuleTiming>, String> {
+    Ok(state.analyzer_rules.timings())
+}
+
+/// Built-in and project configuration profiles
+#[tauri::command]
+async fn list_profiles(state: State<'_, AppState>) -> Result<Vec<profiles::Profile>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone();
+    Ok(profiles::list(workspace.as_deref(), &state.trust.lock().unwrap()))
+}
+
+/// Layer a profile over the current settings, persist and apply them immediately
+#[tauri::command]
+async fn apply_profile(
+    name: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<settings::Settings, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone();
+    let profile = profiles::list(workspace.as_deref(), &state.trust.lock().unwrap())
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Unknown profile: {}", name))?;
+    let current = state.settings.lock().unwrap().clone();
+    let settings = profiles::apply(&current, &profile).map_err(|e| e.to_string())?;
+    update_settings(settings.clone(), app, state).await?;
+    Ok(settings)
+}
+
+/// Get AI response cache statistics
+#[tauri::command]
+async fn get_ai_cache_stats(state: State<'_, AppState>) -> Result<ai_middleware::CacheStats, String> {
+    Ok(state.ai_providers.lock().unwrap().cache_stats())
+}
+
+/// Drop all cached AI responses
+#[tauri::command]
+async fn clear_ai_cache(state: State<'_, AppState>) -> Result<(), String> {
+    state.ai_providers.lock().unwrap().clear_cache();
+    Ok(())
+}
+
+/// Push settings into the subsystems that cache them
+fn apply_settings(settings: &settings::Settings, state: &AppState) {
+    state.ai_providers.lock().unwrap().set_policy(settings.ai.clone());
+    if let Err(e) = logging::apply(&settings.logging) {
+        log::warn!("Ignoring invalid log levels: {}", e);
+    }
+}
+
+/// Resolve a file command's path; anything outside the workspace and the allowed paths is refused
+fn confined(state: &AppState, path: &str) -> Result<PathBuf, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone();
+    let allowed = state.settings.lock().unwrap().sandbox.allowed_paths.clone();
+    file_access::confine(workspace.as_deref(), &allowed, path).map_err(|e| file_access::command_error(e.into()))
+}
+
+/// Refuse the whole changeset if any of its paths is outside what `confined` allows
+fn confine_changeset(state: &AppState, changeset: &changeset::Changeset) -> Result<(), String> {
+    for path in changeset.paths() {
+        confined(state, &path)?;
+    }
+    Ok(())
+}
+
+/// Refuse `action` in a restricted workspace, with the typed error the frontend offers to resolve
+fn ensure_trusted(state: &AppState, workspace: &Path, action: &str) -> Result<(), String> {
+    state.trust.lock().unwrap().ensure(workspace, action).map_err(|e| trust::command_error(e.into()))
+}
+
+fn app_config_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path_resolver()
+        .app_config_dir()
+        .ok_or_else(|| "App config directory unavailable".to_string())
+}
+
+fn app_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path_resolver()
+        .app_data_dir()
+        .ok_or_else(|| "App data directory unavailable".to_string())
+}
+
+/// Store a secret (e.g. `provider:openai:api_key`) in the OS keychain
+#[tauri::command]
+async fn set_secret(key: String, value: String, state: State<'_, AppState>) -> Result<(), String> {
+    secrets::set(&key, &value).map_err(|e| e.to_string())?;
+    if key.starts_with("provider:") {
+        state.ai_providers.lock().unwrap().refresh_credentials();
+    }
+    Ok(())
+}
+
+/// Check whether a secret is stored (values are never returned to the frontend)
+#[tauri::command]
+async fn get_secret_exists(key: String) -> Result<bool, String> {
+    secrets::exists(&key).map_err(|e| e.to_string())
+}
+
+/// Remove a secret from the OS keychain
+#[tauri::command]
+async fn delete_secret(key: String, state: State<'_, AppState>) -> Result<(), String> {
+    secrets::delete(&key).map_err(|e| e.to_string())?;
+    if key.starts_with("provider:") {
+        state.ai_providers.lock().unwrap().refresh_credentials();
+    }
+    Ok(())
+}
+
+/// Rewrite a selection with the AI provider; returns a previewable, syntax-checked edit
+#[tauri::command]
+async fn ai_edit_selection(
+    file_path: String,
+    start: changeset::Position,
+    end: changeset::Position,
+    instruction: String,
+    state: State<'_, AppState>,
+) -> Result<ai_edit::AiEditProposal, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+    let content = documents::read_source(&state.documents, &confined(&state, &file_path)?)
+        .map_err(|e| e.to_string())?;
+    let provider = state.ai_providers.lock().unwrap().active().map_err(|e| e.to_string())?;
+
+    ai_edit::edit_selection(provider.as_ref(), &workspace, &file_path, &content, start, end, &instruction)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Append to the audit log; a failed record never undoes the write
+fn audit_write(workspace: &Path, actor: audit::Actor, action: &str, description: &str, files: Vec<audit::FileTouch>) {
+    if let Err(e) = audit::record(workspace, actor, action, description, files) {
+        log::warn!("Failed to record {} in the audit log: {}", action, e);
+    }
+}
+
+/// Apply a previously previewed changeset; recorded in the audit log as the user's, since only the UI calls this
+#[tauri::command]
+async fn apply_changeset(
+    changeset: changeset::Changeset,
+    state: State<'_, AppState>,
+) -> Result<ChangesetApplication, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+    confine_changeset(&state, &changeset)?;
+    // Files edited by other tools since the changeset was computed are reported, not overwritten
+    let conflicts = changeset.conflicts(&workspace).map_err(|e| e.to_string())?;
+    if !conflicts.is_empty() {
+        return Ok(ChangesetApplication {
+            applied: false,
+            conflicts,
+        });
+    }
+    let previews = changeset.preview(&workspace).map_err(|e| e.to_string())?;
+    changeset.apply(&workspace).map_err(file_access::command_error)?;
+    let files = audit::from_previews(&previews);
+    audit_write(&workspace, audit::Actor::User, "changeset", &changeset.description, files);
+
+    let paths = changeset.paths();
+    if let Err(e) = breakpoints::reanchor_paths(&workspace, &paths) {
+        log::warn!("Failed to move breakpoints: {}", e);
+    }
+    if workspace.join(".git").exists() {
+        state.events.publish(events::Event::GitStatusChanged { paths: paths.clone() });
+    }
+    stats::refresh_after_write(&state, &workspace, &paths);
+    state.events.publish(events::Event::FilesChanged { paths });
+
+    // Local history keeps both the replaced and the written content
+    for preview in previews {
+        for content in [preview.before, preview.after].into_iter().flatten() {
+            if let Err(e) = history::record_snapshot(&workspace, &preview.path, &content, &changeset.description) {
+                log::warn!("Failed to snapshot {}: {}", preview.path, e);
+            }
+        }
+    }
+    Ok(ChangesetApplication {
+        applied: true,
+        conflicts: Vec::new(),
+    })
+}
+
+/// Start a multi-file edit transaction; nothing touches disk until it is committed
+#[tauri::command]
+async fn begin_edit_transaction(description: String, state: State<'_, AppState>) -> Result<transaction::Transaction, String> {
+    Ok(state.transactions.begin(&description))
+}
+
+/// Stage a write, rename or delete in an open transaction
+#[tauri::command]
+async fn stage_transaction_operation(
+    transaction_id: String,
+    operation: transaction::Operation,
+    state: State<'_, AppState>,
+) -> Result<transaction::Transaction, String> {
+    state.transactions.stage(&transaction_id, operation).map_err(|e| e.to_string())
+}
+
+/// Apply every staged operation atomically, rolling back on failure; audited as the user's like changesets
+#[tauri::command]
+async fn commit_transaction(
+    transaction_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let transaction = state.transactions.take(&transaction_id).map_err(|e| e.to_string())?;
+    let files = audit::from_operations(&workspace, &transaction.operations);
+    transaction.commit(&workspace).map_err(file_access::command_error)?;
+    audit_write(&workspace, audit::Actor::User, "transaction", &transaction.description, files);
+
+    let mut paths: Vec<String> = Vec::new();
+    for operation in &transaction.operations {
+        match operation {
+            transaction::Operation::Write { path, .. } | transaction::Operation::Delete { path } => paths.push(path.clone()),
+            transaction::Operation::Rename { from, to } => paths.extend([from.clone(), to.clone()]),
+        }
+    }
+    stats::refresh_after_write(&state, &workspace, &paths);
+    if workspace.join(".git").exists() {
+        state.events.publish(events::Event::GitStatusChanged { paths: paths.clone() });
+    }
+    state.events.publish(events::Event::FilesChanged { paths });
+    Ok(())
+}
+
+/// Engine writes recorded in the workspace's audit log, newest first
+#[tauri::command]
+async fn get_audit_log(
+    filter: Option<audit::AuditFilter>,
+    state: State<'_, AppState>,
+) -> Result<Vec<audit::AuditEntry>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    audit::query(&workspace, &filter.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+/// Clear the read-only flag of a file a write was refused for
+#[tauri::command]
+async fn make_writable(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let target = confined(&state, &path)?;
+    file_access::make_writable(&target).map_err(|e| e.to_string())?;
+    state.file_index.lock().unwrap().update_files(&workspace, &[target.clone()]);
+    index_store::record(&state, &workspace, &[target.to_string_lossy().to_string()]);
+    Ok(())
+}
+
+/// Discard an open transaction without applying it
+#[tauri::command]
+async fn rollback_transaction(transaction_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.transactions.take(&transaction_id).map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// Load a file into a server-side buffer the frontend edits by deltas
+#[tauri::command]
+async fn open_document(path: String, state: State<'_, AppState>) -> Result<documents::DocumentInfo, String> {
+    let path = confined(&state, &path)?;
+    state.documents.lock().unwrap().open(&path).map_err(|e| e.to_string())
+}
+
+/// Open a buffer with the editor's current text (didOpen), so the engine works on it
+#[tauri::command]
+async fn did_open_document(
+    path: String,
+    text: String,
+    state: State<'_, AppState>,
+) -> Result<documents::DocumentInfo, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let path = confined(&state, &path)?;
+    let info = state.documents.lock().unwrap().sync_open(&path, &text);
+    sync_live_document(&state, &workspace, &path, &text);
+    Ok(info)
+}
+
+/// Re-run analysis and symbol extraction of a file on the text the editor has
+fn sync_live_document(state: &AppState, workspace: &Path, path: &Path, text: &str) {
+    if text.len() > documents::LIVE_ANALYSIS_MAX_BYTES {
+        return;
+    }
+    let file_path = path.to_string_lossy().to_string();
+    state.code_graph.lock().unwrap().update_file(workspace, path, text);
+    if let Err(e) = publish_analysis(state, &file_path, text) {
+        log::debug!("Live analysis of {} failed: {}", file_path, e);
+    }
+}
+
+/// Apply edits to a document buffer (didChange) and notify other views of the change
+#[tauri::command]
+async fn apply_text_edits(
+    document_id: String,
+    edits: Vec<changeset::TextEdit>,
+    expected_version: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<documents::DocumentInfo, String> {
+    let info = state
+        .documents
+        .lock()
+        .unwrap()
+        .apply_edits(&document_id, &edits, expected_version)
+        .map_err(|e| e.to_string())?;
+    let workspace = state.workspace_path.lock().unwrap().clone();
+    if let Some(workspace) = workspace {
+        let path = PathBuf::from(&info.path);
+        let text = state.documents.lock().unwrap().live_text(&path);
+        if let Some(text) = text {
+            sync_live_document(&state, &workspace, &path, &text);
+        }
+    }
+    state.events.publish(events::Event::DocumentChanged {
+        document_id,
+        version: info.version,
+        edits,
+    });
+    Ok(info)
+}
+
+/// Lines `start_line..=end_line` (1-based) of a document buffer
+#[tauri::command]
+async fn get_text_range(
+    document_id: String,
+    start_line: usize,
+    end_line: usize,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    state
+        .documents
+        .lock()
+        .unwrap()
+        .text_range(&document_id, start_line, end_line)
+        .map_err(|e| e.to_string())
+}
+
+/// Write a document buffer back to its file
+#[tauri::command]
+async fn save_document(document_id: String, state: State<'_, AppState>) -> Result<documents::DocumentInfo, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let info = state
+        .documents
+        .lock()
+        .unwrap()
+        .save(&document_id)
+        .map_err(file_access::command_error)?;
+    stats::refresh_after_write(&state, &workspace, &[info.path.clone()]);
+    state.events.publish(events::Event::FilesChanged {
+        paths: vec![info.path.clone()],
+    });
+    Ok(info)
+}
+
+/// Drop a document buffer (didClose); unsaved edits are discarded and the file on disk
+/// is analyzed again
+#[tauri::command]
+async fn close_document(document_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let info = state.documents.lock().unwrap().close(&document_id).map_err(|e| e.to_string())?;
+    let workspace = state.workspace_path.lock().unwrap().clone();
+    if let (Some(workspace), true) = (workspace, info.dirty) {
+        let path = PathBuf::from(&info.path);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => sync_live_document(&state, &workspace, &path, &text),
+            Err(e) => log::debug!("Closed document {} is unreadable: {}", info.path, e),
+        }
+    }
+    Ok(())
+}
+
+/// Words the workspace spell checker accepts beyond the bundled dictionaries
+#[tauri::command]
+async fn get_custom_words(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    spellcheck::custom_words(&workspace).map_err(|e| e.to_string())
+}
+
+/// Accept a word in this workspace so it is no longer reported as misspelled
+#[tauri::command]
+async fn add_custom_word(word: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let words = spellcheck::add_custom_word(&workspace, &word).map_err(|e| e.to_string())?;
+    state.spelling.lock().unwrap().load_workspace(&workspace).map_err(|e| e.to_string())?;
+    Ok(words)
+}
+
+#[tauri::command]
+async fn remove_custom_word(word: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let words = spellcheck::remove_custom_word(&workspace, &word).map_err(|e| e.to_string())?;
+    state.spelling.lock().unwrap().load_workspace(&workspace).map_err(|e| e.to_string())?;
+    Ok(words)
+}
+
+/// TODO, FIXME and HACK comments of the workspace as tasks, with their persisted states
+#[tauri::command]
+async fn get_tasks(filter: Option<tasks::TaskFilter>, state: State<'_, AppState>) -> Result<Vec<tasks::Task>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let files: Vec<(String, String)> = state
+        .file_index
+        .lock()
+        .unwrap()
+        .files()
+        .filter(|f| f.generated.is_none() && sloc::syntax_for(&f.language).is_some())
+        .map(|f| (f.path.clone(), f.language.clone()))
+        .collect();
+    let mut found = Vec::new();
+    for (path, language) in files {
+        let content = match documents::read_source(&state.documents, Path::new(&path)) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let relative = Path::new(&path).strip_prefix(&workspace).unwrap_or(Path::new(&path));
+        found.extend(tasks::extract(&relative.to_string_lossy().replace('\\', "/"), &content, &language));
+    }
+    tasks::list(&workspace, found, &filter.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+/// Mark a task done, ignored or open again
+#[tauri::command]
+async fn set_task_state(id: String, task_state: tasks::TaskState, state: State<'_, AppState>) -> Result<(), String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    tasks::set_state(&workspace, &id, task_state).map_err(|e| e.to_string())
+}
+
+/// tsconfig strict flags, suppressions, non-null assertions and implicit-any parameters, with a migration plan
+#[tauri::command]
+async fn get_ts_strictness(state: State<'_, AppState>) -> Result<ts_strictness::StrictnessReport, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let files: Vec<String> = state
+        .file_index
+        .lock()
+        .unwrap()
+        .files()
+        .filter(|f| f.generated.is_none() && matches!(f.extension.as_str(), "ts" | "tsx" | "mts" | "cts"))
+        .filter(|f| !f.name.ends_with(".d.ts"))
+        .map(|f| f.path.clone())
+        .collect();
+    let mut findings = Vec::new();
+    for path in &files {
+        let content = match documents::read_source(&state.documents, Path::new(path)) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let relative = Path::new(path).strip_prefix(&workspace).unwrap_or(Path::new(path));
+        findings.extend(ts_strictness::scan(&relative.to_string_lossy().replace('\\', "/"), &content));
+    }
+    let compiler = ts_strictness::compiler_strictness(&workspace);
+    Ok(ts_strictness::report(&workspace, compiler, findings, files.len()))
+}
+
+/// Share of Python functions with full type hints, per file and for the workspace
+#[tauri::command]
+async fn get_python_type_coverage(state: State<'_, AppState>) -> Result<python_rules::TypeCoverage, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let files: Vec<String> = state
+        .file_index
+        .lock()
+        .unwrap()
+        .files()
+        .filter(|f| f.generated.is_none() && f.language == "Python")
+        .map(|f| f.path.clone())
+        .collect();
+    let mut coverage = Vec::new();
+    for path in &files {
+        let content = match documents::read_source(&state.documents, Path::new(path)) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let relative = Path::new(path).strip_prefix(&workspace).unwrap_or(Path::new(path));
+        coverage.push(python_rules::file_coverage(&relative.to_string_lossy().replace('\\', "/"), &content));
+    }
+    Ok(python_rules::workspace_coverage(coverage))
+}
+
+/// Hard-coded strings and translation catalog gaps; findings are published as diagnostics
+#[tauri::command]
+async fn get_i18n_report(state: State<'_, AppState>) -> Result<i18n::I18nReport, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let files: Vec<(String, String)> = state
+        .file_index
+        .lock()
+        .unwrap()
+        .files()
+        .filter(|f| f.generated.is_none())
+        .map(|f| (f.path.clone(), f.extension.clone()))
+        .collect();
+    let (mut sources, mut catalogs) = (Vec::new(), Vec::new());
+    for (path, extension) in &files {
+        let relative = Path::new(path).strip_prefix(&workspace).unwrap_or(Path::new(path));
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let is_catalog = i18n::is_catalog(&relative);
+        let is_source = matches!(extension.as_str(), "ts" | "tsx" | "js" | "jsx" | "vue" | "html" | "htm" | "py");
+        if !is_catalog && !is_source {
+            continue;
+        }
+        let content = match documents::read_source(&state.documents, Path::new(path)) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        if is_catalog {
+            catalogs.push((relative, content));
+        } else {
+            sources.push((relative, content));
+        }
+    }
+    let report = i18n::report(&sources, &catalogs);
+
+    let mut store = state.diagnostics.lock().unwrap();
+    store.clear_source(i18n::SOURCE);
+    for (file, suggestions) in report.suggestions() {
+        let absolute = workspace.join(&file).to_string_lossy().to_string();
+        let diagnostics = suggestions
+            .iter()
+            .map(|s| diagnostics::Diagnostic::from_suggestion(&absolute, i18n::SOURCE, s))
+            .collect();
+        store.publish(&absolute, i18n::SOURCE, diagnostics);
+    }
+    Ok(report)
+}
+
+/// Environment variables read by the code, checked against .env.example and docker-compose files
+#[tauri::command]
+async fn scan_env_usage(state: State<'_, AppState>) -> Result<env_usage::EnvUsageReport, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let files: Vec<(String, bool)> = state
+        .file_index
+        .lock()
+        .unwrap()
+        .files()
+        .filter(|f| f.generated.is_none())
+        .filter_map(|f| {
+            let documentation = env_usage::is_documentation(&f.name);
+            (documentation || env_usage::is_source(&f.extension)).then(|| (f.path.clone(), documentation))
+        })
+        .collect();
+    let (mut sources, mut documentation) = (Vec::new(), Vec::new());
+    for (path, is_documentation) in files {
+        let content = match documents::read_source(&state.documents, Path::new(&path)) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let relative = Path::new(&path).strip_prefix(&workspace).unwrap_or(Path::new(&path));
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if is_documentation {
+            documentation.push((relative, content));
+        } else {
+            sources.push((relative, content));
+        }
+    }
+    Ok(env_usage::report(&sources, &documentation))
+}
+
+/// Route declarations of every backend source file in the workspace
+fn workspace_endpoints(state: &AppState) -> Result<(PathBuf, Vec<endpoints::Endpoint>), String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let files: Vec<String> = state
+        .file_index
+        .lock()
+        .unwrap()
+        .files()
+        .filter(|f| f.generated.is_none() && endpoints::is_source(&f.extension))
+        .map(|f| f.path.clone())
+        .collect();
+    let mut found = Vec::new();
+    for path in &files {
+        let content = match documents::read_source(&state.documents, Path::new(path)) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let relative = Path::new(path).strip_prefix(&workspace).unwrap_or(Path::new(path));
+        found.extend(endpoints::extract(&relative.to_string_lossy().replace('\\', "/"), &content));
+    }
+    Ok((workspace, found))
+}
+
+/// HTTP endpoints declared in the workspace, optionally filtered by a query like `get users`
+#[tauri::command]
+async fn extract_endpoints(query: Option<String>, state: State<'_, AppState>) -> Result<Vec<endpoints::Endpoint>, String> {
+    let (_, found) = workspace_endpoints(&state)?;
+    Ok(match query.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        Some(query) => endpoints::query(&found, query),
+        None => found,
+    })
+}
+
+/// The endpoint catalog as an OpenAPI 3 stub
+#[tauri::command]
+async fn export_openapi(state: State<'_, AppState>) -> Result<String, String> {
+    let (workspace, found) = workspace_endpoints(&state)?;
+    let title = workspace.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "API".to_string());
+    serde_json::to_string_pretty(&endpoints::to_openapi(&found, &title)).map_err(|e| e.to_string())
+}
+
+/// GraphQL operations in .graphql files and gql templates, with unknown fields checked against the schema
+#[tauri::command]
+async fn validate_graphql(state: State<'_, AppState>) -> Result<graphql::GraphqlReport, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let files: Vec<String> = state
+        .file_index
+        .lock()
+        .unwrap()
+        .files()
+        .filter(|f| f.generated.is_none() && graphql::is_source(&f.extension))
+        .map(|f| f.path.clone())
+        .collect();
+    let mut sources = Vec::new();
+    for path in files {
+        let content = match documents::read_source(&state.documents, Path::new(&path)) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let relative = Path::new(&path).strip_prefix(&workspace).unwrap_or(Path::new(&path));
+        sources.push((relative.to_string_lossy().replace('\\', "/"), content));
+    }
+    let report = graphql::report(&sources);
+
+    let mut store = state.diagnostics.lock().unwrap();
+    store.clear_source(graphql::SOURCE);
+    for (file, suggestions) in report.suggestions() {
+        let absolute = workspace.join(&file).to_string_lossy().to_string();
+        let diagnostics = suggestions
+            .iter()
+            .map(|s| diagnostics::Diagnostic::from_suggestion(&absolute, graphql::SOURCE, s))
+            .collect();
+        store.publish(&absolute, graphql::SOURCE, diagnostics);
+    }
+    Ok(report)
+}
+
+/// Protobuf or OpenAPI definitions named `message`, what refers to them, and the code using their generated names
+#[tauri::command]
+async fn find_idl_usages(message: String, state: State<'_, AppState>) -> Result<idl::IdlUsages, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let files: Vec<(String, bool)> = state
+        .file_index
+        .lock()
+        .unwrap()
+        .files()
+        .filter_map(|f| {
+            let is_idl = idl::is_idl_file(&f.path);
+            // Generated stubs declare every name; the hand-written code using them is what a change affects
+            (is_idl || (f.generated.is_none() && idl::is_source(&f.extension))).then(|| (f.path.clone(), is_idl))
+        })
+        .collect();
+    let mut index = idl::IdlIndex::default();
+    let mut sources = Vec::new();
+    for (path, is_idl) in files {
+        let content = match documents::read_source(&state.documents, Path::new(&path)) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let relative = Path::new(&path).strip_prefix(&workspace).unwrap_or(Path::new(&path));
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if is_idl {
+            index.update(&relative, &content);
+        } else {
+            sources.push((relative, content));
+        }
+    }
+    Ok(index.usages(&message, &sources))
+}
+
+/// Schema of the dev database in DATABASE_URL, cached unless `refresh` is set or the URL changed
+#[tauri::command]
+async fn inspect_database(refresh: bool, state: State<'_, AppState>) -> Result<db_inspector::DbSchema, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    // The database client is launched with connection settings from the workspace env
+    ensure_trusted(&state, &workspace, "database inspection")?;
+    let schema = if refresh { db_inspector::refresh(&workspace) } else { db_inspector::schema(&workspace) };
+    schema.map_err(|e| e.to_string())
+}
+
+/// Table and column names in SQL files and query strings, checked against the dev database schema
+#[tauri::command]
+async fn validate_sql(state: State<'_, AppState>) -> Result<db_inspector::SqlReport, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    ensure_trusted(&state, &workspace, "database inspection")?;
+    let schema = db_inspector::schema(&workspace).map_err(|e| e.to_string())?;
+    let files: Vec<String> = state
+        .file_index
+        .lock()
+        .unwrap()
+        .files()
+        .filter(|f| f.generated.is_none() && db_inspector::is_source(&f.extension))
+        .map(|f| f.path.clone())
+        .collect();
+    let mut sources = Vec::new();
+    for path in files {
+        let content = match documents::read_source(&state.documents, Path::new(&path)) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let relative = Path::new(&path).strip_prefix(&workspace).unwrap_or(Path::new(&path));
+        sources.push((relative.to_string_lossy().replace('\\', "/"), content));
+    }
+    let report = db_inspector::validate(&schema, &sources);
+
+    let mut store = state.diagnostics.lock().unwrap();
+    store.clear_source(db_inspector::SOURCE);
+    for (file, suggestions) in report.suggestions() {
+        let absolute = workspace.join(&file).to_string_lossy().to_string();
+        let diagnostics = suggestions
+            .iter()
+            .map(|s| diagnostics::Diagnostic::from_suggestion(&absolute, db_inspector::SOURCE, s))
+            .collect();
+        store.publish(&absolute, db_inspector::SOURCE, diagnostics);
+    }
+    Ok(report)
+}
+
+/// Request blocks of a .http/.rest file, for run buttons above each request line
+#[tauri::command]
+async fn list_http_requests(file: String, state: State<'_, AppState>) -> Result<http_file::HttpFile, String> {
+    let path = confined(&state, &file)?;
+    let content = documents::read_source(&state.documents, &path).map_err(|e| e.to_string())?;
+    Ok(http_file::parse(&content))
+}
+
+/// Send request `block_index` of a .http/.rest file with `{{variables}}` filled in
+#[tauri::command]
+async fn execute_http_request(
+    file: String,
+    block_index: usize,
+    state: State<'_, AppState>,
+) -> Result<http_file::HttpResponse, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let path = confined(&state, &file)?;
+    if !http_file::is_http_file(&path.to_string_lossy()) {
+        return Err(format!("{} is not a .http or .rest file", file));
+    }
+    let content = documents::read_source(&state.documents, &path).map_err(|e| e.to_string())?;
+    http_file::execute(&workspace, &path, &content, block_index).await.map_err(|e| e.to_string())
+}
+
+/// Dev container configuration and compose services of the workspace
+#[tauri::command]
+async fn detect_containers(state: State<'_, AppState>) -> Result<containers::ContainerSetup, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    Ok(containers::detect(&workspace))
+}
+
+/// Running containers that belong to the workspace; tasks pick one through their `container` field
+#[tauri::command]
+async fn list_containers(state: State<'_, AppState>) -> Result<Vec<containers::Container>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    containers::list(&workspace).map_err(|e| e.to_string())
+}
+
+/// Paths that differ only in case and clash when checked out on a case-insensitive file system
+#[tauri::command]
+async fn find_case_collisions(state: State<'_, AppState>) -> Result<Vec<case_collisions::CaseCollision>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    // Git keeps both spellings even where the checkout could write only one of them
+    let files: Vec<String> = match git::run(&workspace, &["ls-files", "-z"]) {
+        Ok(listing) => listing.split('\0').filter(|f| !f.is_empty()).map(str::to_string).collect(),
+        Err(_) => state
+            .file_index
+            .lock()
+            .unwrap()
+            .paths()
+            .map(|p| Path::new(p).strip_prefix(&workspace).unwrap_or(Path::new(p)).to_string_lossy().replace('\\', "/"))
+            .collect(),
+    };
+    let collisions = case_collisions::find(&files);
+
+    let mut store = state.diagnostics.lock().unwrap();
+    store.clear_source(case_collisions::SOURCE);
+    for (file, suggestions) in case_collisions::suggestions(&collisions) {
+        let absolute = workspace.join(&file).to_string_lossy().to_string();
+        let diagnostics = suggestions
+            .iter()
+            .map(|s| diagnostics::Diagnostic::from_suggestion(&absolute, case_collisions::SOURCE, s))
+            .collect();
+        store.publish(&absolute, case_collisions::SOURCE, diagnostics);
+    }
+    Ok(collisions)
+}
+
+/// Installed toolchains against the versions the workspace declares; mismatches become diagnostics
+#[tauri::command]
+async fn detect_toolchains(state: State<'_, AppState>) -> Result<toolchains::ToolchainReport, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    // Version managers read the workspace's config and may run its hooks
+    ensure_trusted(&state, &workspace, "toolchain probes")?;
+    let probe_workspace = workspace.clone();
+    let report = tauri::async_runtime::spawn_blocking(move || toolchains::detect(&probe_workspace))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut store = state.diagnostics.lock().unwrap();
+    store.clear_source(toolchains::SOURCE);
+    for (file, suggestions) in toolchains::suggestions(&report) {
+        let absolute = workspace.join(&file).to_string_lossy().to_string();
+        let diagnostics = suggestions
+            .iter()
+            .map(|s| diagnostics::Diagnostic::from_suggestion(&absolute, toolchains::SOURCE, s))
+            .collect();
+        store.publish(&absolute, toolchains::SOURCE, diagnostics);
+    }
+    Ok(report)
+}
+
+/// Sparse index selection and how many files are indexed fully or by metadata only
+fn sparse_status(state: &AppState, workspace: &Path) -> Result<sparse::SparseStatus, String> {
+    let settings = state.settings.lock().unwrap().indexer.clone();
+    let selected = sparse::load(workspace).map_err(|e| e.to_string())?.directories;
+    let recent = sparse::recent_directories(workspace, settings.sparse_recent_days);
+    let index = state.file_index.lock().unwrap();
+    let shallow_files = index.files().filter(|f| f.shallow).count();
+    Ok(sparse::SparseStatus {
+        enabled: settings.sparse,
+        selected,
+        recent,
+        full_files: index.file_count() - shallow_files,
+        shallow_files,
+    })
+}
+
+/// Re-index the files under `directory` after the sparse index changed, and add them to or drop them from the graph
+fn reindex_sparse(state: &AppState, workspace: &Path, directory: &str) {
+    let settings = state.settings.lock().unwrap().indexer.clone();
+    let root = changeset::resolve(workspace, directory);
+    let paths: Vec<String> = {
+        let mut index = state.file_index.lock().unwrap();
+        index.configure_sparse(sparse_directories(workspace, &settings));
+        index.paths().filter(|p| Path::new(p).starts_with(&root)).cloned().collect()
+    };
+    stats::refresh_after_write(state, workspace, &paths);
+
+    let analyzed: Vec<(String, bool)> = {
+        let index = state.file_index.lock().unwrap();
+        paths
+            .iter()
+            .filter_map(|p| index.get(p))
+            .map(|f| (f.path.clone(), !f.shallow && (f.generated.is_none() || settings.index_generated)))
+            .collect()
+    };
+    let mut graph = state.code_graph.lock().unwrap();
+    for (path, analyze) in analyzed {
+        let path = Path::new(&path);
+        if !analyze || !mimi_engine::CodeGraph::is_analyzed(path) {
+            graph.remove_file(path);
+            continue;
+        }
+        match documents::read_source(&state.documents, path) {
+            Ok(content) => graph.update_file(workspace, path, &content),
+            Err(e) => log::debug!("Skipping analysis of {:?}: {}", path, e),
+        }
+    }
+    drop(graph);
+    index_store::record(state, workspace, &paths);
+}
+
+/// Directories of the sparse index, the recently changed ones and the indexed file counts
+#[tauri::command]
+async fn get_sparse_index(state: State<'_, AppState>) -> Result<sparse::SparseStatus, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    sparse_status(&state, &workspace)
+}
+
+/// Fully index and analyze a directory in sparse mode
+#[tauri::command]
+async fn expand_sparse_index(directory: String, state: State<'_, AppState>) -> Result<sparse::SparseStatus, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    sparse::expand(&workspace, &directory).map_err(|e| e.to_string())?;
+    reindex_sparse(&state, &workspace, &directory);
+    sparse_status(&state, &workspace)
+}
+
+/// Return a directory of the sparse index to metadata-only indexing
+#[tauri::command]
+async fn contract_sparse_index(directory: String, state: State<'_, AppState>) -> Result<sparse::SparseStatus, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    sparse::contract(&workspace, &directory).map_err(|e| e.to_string())?;
+    reindex_sparse(&state, &workspace, &directory);
+    sparse_status(&state, &workspace)
+}
+
+/// Generate unit tests for a symbol as a previewable changeset
+#[tauri::command]
+async fn generate_tests(
+    file_path: String,
+    symbol: String,
+    state: State<'_, AppState>,
+) -> Result<testgen::GeneratedTests, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+    // Absolute, so the test file is placed in the workspace and not next to the process's cwd
+    let source = confined(&state, &file_path)?;
+    let content = documents::read_source(&state.documents, &source).map_err(|e| e.to_string())?;
+    let definition = code_context::find_definition(&file_path, &content, &symbol)
+        .ok_or_else(|| format!("Symbol not found: {}", symbol))?;
+    let callers = {
+        let graph = state.code_graph.lock().unwrap();
+        code_context::find_callers(&graph, &file_path, &symbol, Some(&definition), 5)
+    };
+    let provider = state.ai_providers.lock().unwrap().active().map_err(|e| e.to_string())?;
+
+    let source = source.to_string_lossy();
+    testgen::generate(provider.as_ref(), &workspace, &source, &definition, &content, &callers)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Generate a doc comment for a symbol as an insertion edit above its declaration
+#[tauri::command]
+async fn generate_docs(
+    file_path: String,
+    symbol: String,
+    state: State<'_, AppState>,
+) -> Result<docgen::GeneratedDocs, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+    let content = documents::read_source(&state.documents, &confined(&state, &file_path)?).map_err(|e| e.to_string())?;
+    let definition = code_context::find_definition(&file_path, &content, &symbol)
+        .ok_or_else(|| format!("Symbol not found: {}", symbol))?;
+    let provider = state.ai_providers.lock().unwrap().active().map_err(|e| e.to_string())?;
+
+    docgen::generate(provider.as_ref(), &workspace, &file_path, &content, &definition)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Explain a diagnostic in plain language, with an optional fix edit
+#[tauri::command]
+async fn explain_diagnostic(
+    file_path: String,
+    diagnostic_id: String,
+    state: State<'_, AppState>,
+) -> Result<explain::DiagnosticExplanation, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+    let diagnostic = state
+        .diagnostics
+        .lock()
+        .unwrap()
+        .get(&file_path, &diagnostic_id)
+        .ok_or("Unknown diagnostic")?;
+    let content = documents::read_source(&state.documents, &confined(&state, &file_path)?).map_err(|e| e.to_string())?;
+    let related = {
+        let graph = state.code_graph.lock().unwrap();
+        code_context::related_definitions(&graph, &file_path, &content, diagnostic.line, 5)
+    };
+    let provider = state.ai_providers.lock().unwrap().active().map_err(|e| e.to_string())?;
+
+    explain::explain(provider.as_ref(), &workspace, diagnostic, &content, &related)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Files likely needed when editing `path`, ranked by graph, history and similarity
+#[tauri::command]
+async fn get_related_files(
+    path: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<related_files::RelatedFile>, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+    // Its content and its neighbours' go to the embedding provider
+    let path = confined(&state, &path)?.to_string_lossy().to_string();
+    let graph = related_files::graph_signals(&state.code_graph.lock().unwrap(), &path);
+    // Semantic similarity is optional; rank without it if no provider is configured
+    let provider = state.ai_providers.lock().unwrap().active().ok();
+
+    Ok(related_files::rank(provider.as_deref(), &workspace, &path, graph, limit.unwrap_or(10)).await)
+}
+
+/// Preview the first rows of a CSV/TSV/JSONL file with inferred column types
+#[tauri::command]
+async fn preview_table(
+    path: String,
+    rows: Option<usize>,
+    cols: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<data_preview::TablePreview, String> {
+    data_preview::preview_table(&confined(&state, &path)?, rows.unwrap_or(100), cols.unwrap_or(50))
+        .map_err(|e| e.to_string())
+}
+
+/// Build (or reuse) the line-offset index of a large file
+#[tauri::command]
+async fn index_lines(path: String, state: State<'_, AppState>) -> Result<data_preview::LineIndexInfo, String> {
+    let path = confined(&state, &path)?;
+    let mut indexes = state.line_indexes.lock().unwrap();
+    let index = indexes.get_or_build(&path).map_err(|e| e.to_string())?;
+    Ok(index.info(&path))
+}
+
+/// Read a window of lines from an indexed file without loading it whole
+#[tauri::command]
+async fn read_lines(
+    path: String,
+    start: usize,
+    count: usize,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let path = confined(&state, &path)?;
+    let mut indexes = state.line_indexes.lock().unwrap();
+    let index = indexes.get_or_build(&path).map_err(|e| e.to_string())?;
+    index.read_lines(&path, start, count).map_err(|e| e.to_string())
+}
+
+/// Indexed info for a file, including asset metadata for the explorer preview
+#[tauri::command]
+async fn get_file_info(path: String, state: State<'_, AppState>) -> Result<Option<file_indexer::FileInfo>, String> {
+    Ok(state.file_index.lock().unwrap().get(&path).cloned())
+}
+
+/// List built-in and user project templates
+#[tauri::command]
+async fn list_templates(app: tauri::AppHandle) -> Result<Vec<scaffold::TemplateSummary>, String> {
+    Ok(scaffold::list_templates(&app_config_dir(&app)?.join("templates")))
+}
+
+/// Create a new project from a template, optionally running its post-create commands
+#[tauri::command]
+async fn create_from_template(
+    template: String,
+    target: String,
+    vars: HashMap<String, String>,
+    run_post_create: Option<bool>,
+    app: tauri::AppHandle,
+) -> Result<scaffold::ScaffoldResult, String> {
+    let user_dir = app_config_dir(&app)?.join("templates");
+    tauri::async_runtime::spawn_blocking(move || {
+        scaffold::create_from_template(
+            &user_dir,
+            &template,
+            Path::new(&target),
+            &vars,
+            run_post_create.unwrap_or(true),
+            &app.state::<AppState>().trust,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// Create related files (e.g. component + test + styles) from a file template and wire them in
+#[tauri::command]
+async fn create_from_file_template(
+    kind: String,
+    name: String,
+    dir: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<file_templates::FileTemplateResult, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+    let user_dir = app_config_dir(&app)?.join("file-templates");
+    let dir = confined(&state, &dir)?;
+    file_templates::create(&user_dir, &workspace, &kind, &name, &dir).map_err(|e| e.to_string())
+}
+
+/// Import a workspace symbol into a file, merged into its import block
+#[tauri::command]
+async fn add_import(
+    file_path: String,
+    symbol: String,
+    state: State<'_, AppState>,
+) -> Result<imports::AddImportResult, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+    let content = documents::read_source(&state.documents, &confined(&state, &file_path)?).map_err(|e| e.to_string())?;
+    let defined_in: Vec<String> = state
+        .code_graph
+        .lock()
+        .unwrap()
+        .find_symbol(&symbol)
+        .iter()
+        .map(|s| s.file.clone())
+        .collect();
+    let indexed: Vec<String> = state.file_index.lock().unwrap().paths().cloned().collect();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        // The graph only knows exported TS symbols; fall back to scanning indexed files
+        let source = imports::find_source(&file_path, &symbol, &defined_in)
+            .or_else(|| imports::find_source(&file_path, &symbol, &indexed))
+            .ok_or_else(|| anyhow::anyhow!("No definition of {} in the workspace", symbol))?;
+        imports::add_import(&workspace, &file_path, &content, &source, &symbol)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// Estimated bundle size of each package a JS/TS file imports, so heavy imports can be shown inline
+#[tauri::command]
+async fn get_import_cost(file: String, state: State<'_, AppState>) -> Result<Vec<import_cost::ImportCost>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let path = confined(&state, &file)?;
+    let content = documents::read_source(&state.documents, &path).map_err(|e| e.to_string())?;
+    let mut cache = state.import_costs.lock().unwrap();
+    Ok(import_cost::estimate(&mut cache, &workspace, &path, &content))
+}
+
+/// Attribute the bytes of a build to workspace files from webpack/vite stats, a source map or a build directory
+#[tauri::command]
+async fn analyze_bundle(path: String, state: State<'_, AppState>) -> Result<bundle_analysis::BundleAnalysis, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let path = confined(&state, &path)?;
+    let analysis = bundle_analysis::analyze(&workspace, &path).map_err(|e| e.to_string())?;
+    *state.bundle_analysis.lock().unwrap() = Some(analysis.clone());
+    Ok(analysis)
+}
+
+/// Largest contributors to a chunk of the last analyzed bundle; only workspace files unless `include_packages`
+#[tauri::command]
+async fn get_chunk_contributors(
+    chunk: String,
+    limit: Option<usize>,
+    include_packages: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<bundle_analysis::Contribution>, String> {
+    let analysis = state.bundle_analysis.lock().unwrap();
+    let analysis = analysis.as_ref().ok_or("No bundle analyzed")?;
+    analysis
+        .contributors(&chunk, !include_packages.unwrap_or(false), limit.unwrap_or(20))
+        .map_err(|e| e.to_string())
+}
+
+/// Original workspace position of a 1-based line and column in a built file or deployed script URL
+#[tauri::command]
+async fn resolve_sourcemap_position(
+    generated_file: String,
+    line: u32,
+    column: u32,
+    state: State<'_, AppState>,
+) -> Result<Option<source_map::OriginalPosition>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let mut cache = state.source_maps.lock().unwrap();
+    cache.resolve(&workspace, &generated_file, line, column).map_err(|e| e.to_string())
+}
+
+/// Parse a pasted Rust, Node or Python stack trace into frames with workspace locations to jump to
+#[tauri::command]
+async fn parse_stack_trace(text: String, state: State<'_, AppState>) -> Result<stack_trace::StackTrace, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let indexed: Vec<String> = state.file_index.lock().unwrap().paths().cloned().collect();
+    let mut source_maps = state.source_maps.lock().unwrap();
+    let mut resolver = stack_trace::Resolver::new(&workspace, indexed.into_iter(), &mut source_maps);
+    Ok(stack_trace::parse_and_resolve(&text, &mut resolver))
+}
 
-    // Update state
-    *state.workspace_path.lock().unwrap() = Some(path.clone());
+/// Follow a log file, streaming matching lines as `log-tail` events until `stop_tail`
+#[tauri::command]
+async fn tail_file(
+    path: String,
+    filter: Option<log_tail::TailFilter>,
+    window: tauri::Window,
+    state: State<'_, AppState>,
+) -> Result<log_tail::TailInfo, String> {
+    let path = confined(&state, &path)?;
+    let on_event: log_tail::TailCallback = Box::new(move |event| {
+        let _ = window.emit("log-tail", event);
+    });
+    state.log_tails.start(&path, &filter.unwrap_or_default(), on_event).map_err(|e| e.to_string())
+}
 
-    // Index files in background
-    let mut index = state.file_index.lock().unwrap();
-    index.index_directory(&path).map_err(|e| e.to_string())?;
+#[tauri::command]
+async fn stop_tail(id: String, state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.log_tails.stop(&id))
+}
 
-    // Build dependency graph
-    let mut graph = state.code_graph.lock().unwrap();
-    graph.analyze_workspace(&path).map_err(|e| e.to_string())?;
+#[tauri::command]
+async fn list_tails(state: State<'_, AppState>) -> Result<Vec<log_tail::TailInfo>, String> {
+    Ok(state.log_tails.list())
+}
 
-    Ok(WorkspaceInfo {
-        path: path.to_string_lossy().to_string(),
-        file_count: index.file_count(),
-        indexed: true,
-    })
+/// Organize a file's imports per the import settings; `None` if nothing changes
+#[tauri::command]
+async fn organize_imports(file_path: String, state: State<'_, AppState>) -> Result<Option<changeset::TextEdit>, String> {
+    let content = documents::read_source(&state.documents, &confined(&state, &file_path)?).map_err(|e| e.to_string())?;
+    let settings = state.settings.lock().unwrap().imports.clone();
+    let unused = code_analyzer::unused_imports(&file_path, &content);
+    Ok(imports::organize(&file_path, &content, &settings, &unused))
 }
 
-/// Search files in workspace
+/// Move a file and rewrite the imports that point at it, as a previewable changeset
 #[tauri::command]
-async fn search_files(query: String, state: State<'_, AppState>) -> Result<Vec<FileMatch>, String> {
-    let index = state.file_index.lock().unwrap();
-    Ok(index.search(&query))
+async fn move_file(
+    old_path: String,
+    new_path: String,
+    state: State<'_, AppState>,
+) -> Result<refactor::RefactorResult, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+    confined(&state, &old_path)?;
+    // The target is written by the rename, so it may not land in engine data either
+    file_access::confine_write(&workspace, &new_path).map_err(|e| file_access::command_error(e.into()))?;
+    let key = changeset::resolve(&workspace, &old_path).to_string_lossy().to_string();
+    let dependents = state.code_graph.lock().unwrap().get_dependents(&key);
+
+    refactor::move_file(&workspace, &dependents, &old_path, &new_path).map_err(|e| e.to_string())
 }
 
-/// Get file dependencies
+/// Update the imports left stale by a file renamed outside the app, as a previewable changeset
 #[tauri::command]
-async fn get_dependencies(file_path: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
-    let graph = state.code_graph.lock().unwrap();
-    Ok(graph.get_dependencies(&file_path))
+async fn rewrite_renamed_imports(
+    from: String,
+    to: String,
+    state: State<'_, AppState>,
+) -> Result<refactor::RefactorResult, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let key = changeset::resolve(&workspace, &to).to_string_lossy().to_string();
+    let dependents = state.code_graph.lock().unwrap().get_dependents(&key);
+
+    refactor::rewrite_moved_imports(&workspace, &dependents, &from, &to).map_err(|e| e.to_string())
 }
 
-/// Get files that depend on this file
+/// Inline the variable or single-expression function at a position into its usages
 #[tauri::command]
-async fn get_dependents(file_path: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+async fn inline_symbol(
+    file_path: String,
+    position: changeset::Position,
+    state: State<'_, AppState>,
+) -> Result<refactor::RefactorResult, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+    let content = documents::read_source(&state.documents, &confined(&state, &file_path)?)
+        .map_err(|e| e.to_string())?;
+
+    inline::inline_symbol(&workspace, &file_path, &content, position).map_err(|e| e.to_string())
+}
+
+/// Risks of renaming a symbol: collisions, shadowing and mentions in comments and strings
+#[tauri::command]
+async fn check_rename(symbol: String, new_name: String, state: State<'_, AppState>) -> Result<rename::RenameReport, String> {
+    let old_name = rename::bare_name(&symbol).to_string();
+    let mut files = state.file_index.lock().unwrap().files_containing(&old_name);
+    {
+        let graph = state.code_graph.lock().unwrap();
+        for definition in graph.find_symbol(&old_name) {
+            files.push(definition.file.clone());
+            files.extend(graph.get_dependents(&definition.file));
+        }
+    }
+    files.sort();
+    files.dedup();
+    let sources: Vec<(String, String)> = files
+        .into_iter()
+        .filter_map(|file| {
+            let content = documents::read_source(&state.documents, Path::new(&file)).ok()?;
+            Some((file, content))
+        })
+        .collect();
     let graph = state.code_graph.lock().unwrap();
-    Ok(graph.get_dependents(&file_path))
+    rename::check(&graph, &sources, &symbol, &new_name).map_err(|e| e.to_string())
 }
 
-/// Analyze code for suggestions
+/// Reorder, add or remove a function's parameters and update its call sites
 #[tauri::command]
-async fn analyze_code(
+async fn change_signature(
     file_path: String,
-    content: String,
+    symbol: String,
+    new_params: Vec<refactor::NewParam>,
     state: State<'_, AppState>,
-) -> Result<Vec<CodeSuggestion>, String> {
-    let analyzer = code_analyzer::CodeAnalyzer::new();
-    analyzer.analyze(&file_path, &content).map_err(|e| e.to_string())
+) -> Result<refactor::SignatureChange, String> {
+    let workspace = state
+        .workspace_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No workspace open")?;
+    let content = documents::read_source(&state.documents, &confined(&state, &file_path)?)
+        .map_err(|e| e.to_string())?;
+    let definition = code_context::find_definition(&file_path, &content, &symbol)
+        .ok_or_else(|| format!("Symbol not found: {}", symbol))?;
+    let callers = {
+        let graph = state.code_graph.lock().unwrap();
+        code_context::find_callers(&graph, &file_path, &symbol, Some(&definition), usize::MAX)
+    };
+
+    refactor::change_signature(&workspace, &file_path, &content, &definition, &callers, &new_params)
+        .map_err(|e| e.to_string())
 }
 
-/// Get workspace statistics
+/// Jump to the declaration of the type of the symbol at a position
 #[tauri::command]
-async fn get_workspace_stats(state: State<'_, AppState>) -> Result<WorkspaceStats, String> {
-    let index = state.file_index.lock().unwrap();
+async fn goto_type_definition(
+    file_path: String,
+    line: usize,
+    column: usize,
+    state: State<'_, AppState>,
+) -> Result<Option<code_context::TypeDefinition>, String> {
+    let content = documents::read_source(&state.documents, &confined(&state, &file_path)?).map_err(|e| e.to_string())?;
+    let offset = changeset::offset_of(&content, changeset::Position { line, column }).map_err(|e| e.to_string())?;
+    let type_name = match code_context::type_name_at(&file_path, &content, offset) {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    if let Some(span) = code_context::find_type_definition(&file_path, &content, &type_name) {
+        return Ok(Some(code_context::TypeDefinition { file: file_path, span }));
+    }
+
+    let exported: Vec<String> = state
+        .code_graph
+        .lock()
+        .unwrap()
+        .find_symbol(&type_name)
+        .iter()
+        .map(|s| s.file.clone())
+        .collect();
+    let indexed: Vec<String> = state.file_index.lock().unwrap().paths().cloned().collect();
+    tauri::async_runtime::spawn_blocking(move || {
+        code_context::locate_type(&exported, &type_name).or_else(|| code_context::locate_type(&indexed, &type_name))
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Workspace symbols matching `query`, qualified so same-named symbols can be told apart
+#[tauri::command]
+async fn search_symbols(
+    query: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<symbols::SymbolInfo>, String> {
     let graph = state.code_graph.lock().unwrap();
+    Ok(graph
+        .search_symbols(&query, limit.unwrap_or(50))
+        .into_iter()
+        .cloned()
+        .collect())
+}
 
-    Ok(WorkspaceStats {
-        total_files: index.file_count(),
-        total_lines: index.total_lines(),
-        by_language: index.files_by_language(),
-        dependency_count: graph.edge_count(),
+/// Public symbols of a package (a workspace-relative folder or file) with signatures
+#[tauri::command]
+async fn get_public_api(package: Option<String>, state: State<'_, AppState>) -> Result<Vec<api_surface::ApiSymbol>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let graph = state.code_graph.lock().unwrap();
+    let symbols = graph.files().flat_map(|file| graph.symbols_in_file(file));
+    Ok(api_surface::surface(&workspace, package.as_deref().unwrap_or(""), symbols))
+}
+
+/// Public API changes of a package since a git ref; removals, renames and signature changes are breaking
+#[tauri::command]
+async fn diff_public_api(
+    git_ref: String,
+    package: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<api_surface::ApiDiff, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let package = package.unwrap_or_default();
+    let current = {
+        let graph = state.code_graph.lock().unwrap();
+        api_surface::surface(&workspace, &package, graph.files().flat_map(|file| graph.symbols_in_file(file)))
+    };
+    tauri::async_runtime::spawn_blocking(move || api_surface::diff_against(&workspace, &git_ref, &package, &current))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Next semver version of a package from its API changes since the last tag, with explanations
+#[tauri::command]
+async fn suggest_version_bump(
+    package: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<version_advisor::VersionAdvice, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let package = package.unwrap_or_default();
+    let current = {
+        let graph = state.code_graph.lock().unwrap();
+        api_surface::surface(&workspace, &package, graph.files().flat_map(|file| graph.symbols_in_file(file)))
+    };
+    tauri::async_runtime::spawn_blocking(move || version_advisor::suggest(&workspace, &package, &current))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Changelog draft for a git range (default: since the last tag), optionally polished by the AI provider
+#[tauri::command]
+async fn generate_changelog(
+    range: Option<String>,
+    package: Option<String>,
+    polish: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<changelog::ChangelogDraft, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let package = package.unwrap_or_default();
+    let current = {
+        let graph = state.code_graph.lock().unwrap();
+        api_surface::surface(&workspace, &package, graph.files().flat_map(|file| graph.symbols_in_file(file)))
+    };
+    let draft = tauri::async_runtime::spawn_blocking(move || changelog::draft(&workspace, range.as_deref(), &package, &current))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    if !polish.unwrap_or(false) {
+        return Ok(draft);
+    }
+    let provider = state.ai_providers.lock().unwrap().active().map_err(|e| e.to_string())?;
+    changelog::polish(provider.as_ref(), &draft).await.map_err(|e| e.to_string())
+}
+
+/// Local-history snapshots, git commits and analysis runs of a file, newest first
+#[tauri::command]
+async fn get_file_timeline(path: String, state: State<'_, AppState>) -> Result<Vec<history::TimelineEvent>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    tauri::async_runtime::spawn_blocking(move || history::timeline(&workspace, &path))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Content of a local-history snapshot
+#[tauri::command]
+async fn get_history_snapshot(path: String, snapshot_id: String, state: State<'_, AppState>) -> Result<Option<String>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    history::snapshot_content(&workspace, &path, &snapshot_id).map_err(|e| e.to_string())
+}
+
+/// Compare two folders, or two git refs of the open workspace, at index level
+#[tauri::command]
+async fn compare_workspaces(
+    path_a: String,
+    path_b: String,
+    state: State<'_, AppState>,
+) -> Result<compare::WorkspaceComparison, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let left = compare::Side::detect(&path_a, workspace.as_deref())?;
+        let right = compare::Side::detect(&path_b, workspace.as_deref())?;
+        compare::compare(&left, &right)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// Render a Markdown or standalone HTML report of the workspace
+#[tauri::command]
+async fn generate_workspace_report(
+    format: report::ReportFormat,
+    sections: Option<Vec<report::SectionKind>>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let input = {
+        let index = state.file_index.lock().unwrap();
+        let graph = state.code_graph.lock().unwrap();
+        report::ReportInput {
+            workspace,
+            total_files: index.file_count(),
+            total_lines: index.total_lines(),
+            dependency_count: graph.edge_count(),
+            by_language: index.files_by_language(),
+            file_lines: index
+                .paths()
+                .filter_map(|path| index.get(path).map(|info| (path.clone(), info.lines)))
+                .collect(),
+            cycles: graph.find_cycles(),
+            diagnostics: state.diagnostics.lock().unwrap().all(),
+        }
+    };
+    let sections = sections.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || report::generate(&input, format, &sections))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Versioned catalog of event kinds and types
+#[tauri::command]
+async fn get_event_catalog() -> Result<events::EventCatalog, String> {
+    Ok(events::catalog())
+}
+
+/// Subscribe to event kinds (all when empty); returns the subscription id
+#[tauri::command]
+async fn subscribe_events(
+    kinds: Vec<events::EventKind>,
+    capacity: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    Ok(state.events.subscribe(&kinds, capacity))
+}
+
+/// Wait up to `timeout_ms` for events of a subscription and take a batch
+#[tauri::command]
+async fn next_events(
+    subscription_id: String,
+    max: Option<usize>,
+    timeout_ms: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<events::EventBatch, String> {
+    let timeout = std::time::Duration::from_millis(timeout_ms.unwrap_or(30_000));
+    state
+        .events
+        .next(&subscription_id, max.unwrap_or(100), timeout)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn unsubscribe_events(subscription_id: String, state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.events.unsubscribe(&subscription_id))
+}
+
+/// Restore the session saved before the last exit or crash: workspace, diagnostics and interrupted runs
+#[tauri::command]
+async fn restore_last_state(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Option<session::SessionState>, String> {
+    let saved = session::load(&session::session_path(&app_data_dir(&app)?)).map_err(|e| e.to_string())?;
+    let saved = match saved {
+        Some(saved) => saved,
+        None => return Ok(None),
+    };
+
+    if let Some(workspace) = saved.workspace.clone().filter(|ws| Path::new(ws).is_dir()) {
+        open_workspace(workspace.clone(), app.clone(), state.clone()).await?;
+        session::restore_runs(&state, Path::new(&workspace), &saved.active_runs);
+    }
+    session::restore_diagnostics(&state, saved.diagnostics.clone());
+    Ok(Some(saved))
+}
+
+#[tauri::command]
+async fn get_workspace_env(state: State<'_, AppState>) -> Result<Vec<workspace_env::EnvVar>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    workspace_env::list(&workspace).map_err(|e| e.to_string())
+}
+
+/// Set a workspace variable; `value: None` removes it. Secret values go to the keychain.
+#[tauri::command]
+async fn set_workspace_env(
+    name: String,
+    value: Option<String>,
+    secret: Option<bool>,
+    precedence: Option<workspace_env::Precedence>,
+    state: State<'_, AppState>,
+) -> Result<Vec<workspace_env::EnvVar>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    workspace_env::set(
+        &workspace,
+        &name,
+        value.as_deref(),
+        secret.unwrap_or(false),
+        precedence.unwrap_or_default(),
+    )
+    .map_err(|e| e.to_string())?;
+    workspace_env::list(&workspace).map_err(|e| e.to_string())
+}
+
+/// Start a long-running task (dev server, watcher) without waiting for it
+#[tauri::command]
+async fn start_background_task(
+    spec: task_runner::TaskSpec,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<processes::ProcessInfo, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let handle = app.clone();
+    let on_url: processes::UrlCallback = Arc::new(move |process_id: &str, url: &str| {
+        handle.state::<AppState>().events.publish(events::Event::DevServerDetected {
+            process_id: process_id.to_string(),
+            url: url.to_string(),
+        });
+    });
+    let store = state.trust.lock().unwrap();
+    state.processes.start(&spec, &workspace, &store, on_url).map_err(trust::command_error)
+}
+
+#[tauri::command]
+async fn list_background_tasks(state: State<'_, AppState>) -> Result<Vec<processes::ProcessInfo>, String> {
+    Ok(state.processes.list())
+}
+
+#[tauri::command]
+async fn get_background_task_output(
+    id: String,
+    lines: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    state.processes.output(&id, lines.unwrap_or(200)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn stop_background_task(id: String, state: State<'_, AppState>) -> Result<processes::ProcessInfo, String> {
+    state.processes.stop(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_listening_ports() -> Result<Vec<ports::ListeningPort>, String> {
+    tauri::async_runtime::spawn_blocking(ports::list_listening_ports)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Background tasks serving HTTP, for "Your app is running at..." links
+#[tauri::command]
+async fn list_dev_servers(state: State<'_, AppState>) -> Result<Vec<processes::DevServer>, String> {
+    state.processes.dev_servers().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn open_in_browser(port: u16, path: Option<String>, app: tauri::AppHandle) -> Result<(), String> {
+    if port == 0 {
+        return Err("Invalid port".to_string());
+    }
+    let path = path.unwrap_or_default();
+    let url = format!("http://localhost:{}/{}", port, path.trim_start_matches('/'));
+    tauri::api::shell::open(&app.shell_scope(), url, None).map_err(|e| e.to_string())
+}
+
+fn debug_session(state: &AppState, session_id: &str) -> Result<Arc<debugger::DebugSession>, String> {
+    state
+        .debug_sessions
+        .lock()
+        .unwrap()
+        .get(session_id)
+        .cloned()
+        .ok_or_else(|| format!("Unknown debug session: {}", session_id))
+}
+
+/// Run a blocking DAP request off the async runtime
+async fn with_debug_session<T: Send + 'static>(
+    state: &AppState,
+    session_id: &str,
+    f: impl FnOnce(&debugger::DebugSession) -> anyhow::Result<T> + Send + 'static,
+) -> Result<T, String> {
+    let session = debug_session(state, session_id)?;
+    tauri::async_runtime::spawn_blocking(move || f(&session))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn debug_launch(
+    mut request: debugger::LaunchRequest,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<debugger::DebugSessionInfo, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    ensure_trusted(&state, &workspace, "debug sessions")?;
+    // Without explicit breakpoints the stored ones are used, at their current lines
+    if request.breakpoints.is_empty() {
+        request.breakpoints = breakpoints::for_launch(&workspace).map_err(|e| e.to_string())?;
+    }
+    let handle = app.clone();
+    let on_event: debugger::EventCallback = Arc::new(move |event: events::Event| handle.state::<AppState>().events.publish(event));
+    let session = tauri::async_runtime::spawn_blocking(move || debugger::DebugSession::launch(&request, &workspace, on_event))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    let info = session.info();
+    state.debug_sessions.lock().unwrap().insert(session.id.clone(), session);
+    Ok(info)
+}
+
+#[tauri::command]
+async fn list_debug_sessions(state: State<'_, AppState>) -> Result<Vec<debugger::DebugSessionInfo>, String> {
+    let mut sessions: Vec<debugger::DebugSessionInfo> =
+        state.debug_sessions.lock().unwrap().values().map(|s| s.info()).collect();
+    sessions.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(sessions)
+}
+
+#[tauri::command]
+async fn debug_set_breakpoints(
+    session_id: String,
+    path: String,
+    breakpoints: Vec<debugger::BreakpointSpec>,
+    state: State<'_, AppState>,
+) -> Result<Vec<debugger::Breakpoint>, String> {
+    with_debug_session(&state, &session_id, move |s| s.set_breakpoints(&path, &breakpoints)).await
+}
+
+#[tauri::command]
+async fn debug_control(
+    session_id: String,
+    action: debugger::StepAction,
+    thread_id: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    with_debug_session(&state, &session_id, move |s| s.control(action, thread_id)).await
+}
+
+#[tauri::command]
+async fn debug_threads(session_id: String, state: State<'_, AppState>) -> Result<Vec<debugger::Thread>, String> {
+    with_debug_session(&state, &session_id, |s| s.threads()).await
+}
+
+#[tauri::command]
+async fn debug_stack_trace(
+    session_id: String,
+    thread_id: i64,
+    levels: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<debugger::StackFrame>, String> {
+    with_debug_session(&state, &session_id, move |s| s.stack_trace(thread_id, levels.unwrap_or(50))).await
+}
+
+#[tauri::command]
+async fn debug_scopes(session_id: String, frame_id: i64, state: State<'_, AppState>) -> Result<Vec<debugger::Scope>, String> {
+    with_debug_session(&state, &session_id, move |s| s.scopes(frame_id)).await
+}
+
+#[tauri::command]
+async fn debug_variables(
+    session_id: String,
+    variables_reference: i64,
+    state: State<'_, AppState>,
+) -> Result<Vec<debugger::Variable>, String> {
+    with_debug_session(&state, &session_id, move |s| s.variables(variables_reference)).await
+}
+
+#[tauri::command]
+async fn debug_evaluate(
+    session_id: String,
+    expression: String,
+    frame_id: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<debugger::Variable, String> {
+    with_debug_session(&state, &session_id, move |s| s.evaluate(&expression, frame_id)).await
+}
+
+#[tauri::command]
+async fn debug_stop(session_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let session = state
+        .debug_sessions
+        .lock()
+        .unwrap()
+        .remove(&session_id)
+        .ok_or_else(|| format!("Unknown debug session: {}", session_id))?;
+    tauri::async_runtime::spawn_blocking(move || session.disconnect())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_breakpoint(
+    path: String,
+    line: u32,
+    condition: Option<String>,
+    hit_condition: Option<String>,
+    log_message: Option<String>,
+    enabled: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<breakpoints::StoredBreakpoint, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let spec = debugger::BreakpointSpec {
+        line,
+        condition,
+        hit_condition,
+        log_message,
+    };
+    breakpoints::set(&workspace, &path, line, &spec, enabled.unwrap_or(true)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_breakpoints(
+    path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<breakpoints::StoredBreakpoint>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    breakpoints::list(&workspace, path.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Add a breakpoint on `line`, or remove the one already there
+#[tauri::command]
+async fn toggle_breakpoint(
+    path: String,
+    line: u32,
+    state: State<'_, AppState>,
+) -> Result<Option<breakpoints::StoredBreakpoint>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    breakpoints::toggle(&workspace, &path, line).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_run_configurations(state: State<'_, AppState>) -> Result<Vec<run_configs::RunConfiguration>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    run_configs::list(&workspace).map_err(|e| e.to_string())
+}
+
+/// Create (empty id) or update a run configuration
+#[tauri::command]
+async fn save_run_configuration(
+    config: run_configs::RunConfiguration,
+    state: State<'_, AppState>,
+) -> Result<run_configs::RunConfiguration, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    run_configs::save(&workspace, config).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn delete_run_configuration(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    run_configs::delete(&workspace, &id).map_err(|e| e.to_string())
+}
+
+/// Run a task to completion off the async runtime, publishing start/finish events
+async fn run_task_to_completion(
+    state: &AppState,
+    workspace: PathBuf,
+    spec: task_runner::TaskSpec,
+    timeout_secs: u64,
+) -> Result<task_runner::TaskOutput, String> {
+    ensure_trusted(state, &workspace, "tasks")?;
+    let task_id = storage::new_id("task");
+    state.events.publish(events::Event::TaskStarted {
+        task_id: task_id.clone(),
+        command: std::iter::once(&spec.command).chain(&spec.args).cloned().collect::<Vec<_>>().join(" "),
+    });
+    let output = tauri::async_runtime::spawn_blocking(move || {
+        task_runner::run(&spec, &workspace, std::time::Duration::from_secs(timeout_secs))
     })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+    state.events.publish(events::Event::TaskFinished {
+        task_id,
+        exit_code: output.exit_code,
+        duration_ms: output.duration_ms,
+        timed_out: output.timed_out,
+    });
+    Ok(output)
+}
+
+/// Limit for one setup step; first installs and builds can take long
+const SETUP_TIMEOUT_SECS: u64 = 30 * 60;
+
+/// Setup steps a fresh checkout of the workspace still needs, in order
+#[tauri::command]
+async fn get_setup_suggestions(state: State<'_, AppState>) -> Result<Vec<onboarding::SetupStep>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    Ok(onboarding::suggestions(&workspace))
+}
+
+/// Run one setup step by task ID; copy steps finish without output
+#[tauri::command]
+async fn run_setup_step(id: String, state: State<'_, AppState>) -> Result<Option<task_runner::TaskOutput>, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let step = onboarding::suggestions(&workspace)
+        .into_iter()
+        .find(|step| step.id == id)
+        .ok_or_else(|| format!("No pending setup step {}", id))?;
+    match step.action {
+        onboarding::SetupAction::Task { spec } => {
+            Ok(Some(run_task_to_completion(&state, workspace, spec, SETUP_TIMEOUT_SECS).await?))
+        }
+        onboarding::SetupAction::CopyFile { from, to } => {
+            std::fs::copy(workspace.join(&from), workspace.join(&to)).map_err(|e| e.to_string())?;
+            let files = vec![audit::FileTouch {
+                path: to.clone(),
+                new_path: None,
+                before_hash: None,
+                after_hash: std::fs::read(workspace.join(&to)).ok().map(|bytes| changeset::content_hash(&bytes)),
+            }];
+            audit_write(&workspace, audit::Actor::User, "setup", &step.title, files);
+            stats::refresh_after_write(&state, &workspace, &[to]);
+            Ok(None)
+        }
+    }
+}
+
+/// Start a run configuration after its pre-launch task succeeded
+#[tauri::command]
+async fn run_configuration(
+    id: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<run_configs::RunOutcome, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let config = run_configs::get(&workspace, &id).map_err(|e| e.to_string())?;
+
+    if let Some(pre_launch) = config.pre_launch_task.clone() {
+        let output = run_task_to_completion(&state, workspace.clone(), pre_launch, config.task_timeout_secs()).await?;
+        if !output.success() {
+            let tail: Vec<&str> = output.stderr.lines().rev().take(20).collect();
+            return Err(format!(
+                "Pre-launch task of {} failed (exit code {:?}):\n{}",
+                config.name,
+                output.exit_code,
+                tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+            ));
+        }
+    }
+
+    match &config.kind {
+        run_configs::RunKind::Task { .. } => {
+            let output = run_task_to_completion(&state, workspace, config.task_spec(), config.task_timeout_secs()).await?;
+            Ok(run_configs::RunOutcome::Task { output })
+        }
+        run_configs::RunKind::Background => {
+            let process = start_background_task(config.task_spec(), app, state).await?;
+            Ok(run_configs::RunOutcome::Background { process })
+        }
+        run_configs::RunKind::Debug { .. } => {
+            let request = config.launch_request().ok_or("Not a debug configuration")?;
+            let session = debug_launch(request, app, state).await?;
+            Ok(run_configs::RunOutcome::Debug { session })
+        }
+    }
+}
+
+/// Profiles kept in memory; loading another drops the oldest
+const MAX_PERF_PROFILES: usize = 5;
+
+/// Import a collapsed-stack or pprof profile and resolve its frames to workspace files
+#[tauri::command]
+async fn load_profile(
+    path: String,
+    format: perf_profile::ProfileFormat,
+    state: State<'_, AppState>,
+) -> Result<perf_profile::ProfileSummary, String> {
+    let workspace = state.workspace_path.lock().unwrap().clone().ok_or("No workspace open")?;
+    let path = confined(&state, &path)?;
+    let profile = {
+        let graph = state.code_graph.lock().unwrap();
+        perf_profile::Profile::load(&workspace, &graph, &path, format).map_err(|e| e.to_string())?
+    };
+    let summary = profile.summary();
+    let mut profiles = state.perf_profiles.lock().unwrap();
+    profiles.push(profile);
+    if profiles.len() > MAX_PERF_PROFILES {
+        profiles.remove(0);
+    }
+    Ok(summary)
+}
+
+/// Per-line cost of a file in a loaded profile (the latest one by default)
+#[tauri::command]
+async fn get_file_hotspots_from_profile(
+    file_path: String,
+    profile_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<perf_profile::Hotspot>, String> {
+    let profiles = state.perf_profiles.lock().unwrap();
+    let profile = match &profile_id {
+        Some(id) => profiles.iter().find(|p| &p.id == id),
+        None => profiles.last(),
+    }
+    .ok_or("No matching profile loaded")?;
+    Ok(profile.file_hotspots(&file_path))
+}
+
+/// Memory, CPU, handles and cache sizes of the engine, with recent samples
+#[tauri::command]
+async fn get_engine_resource_usage(state: State<'_, AppState>) -> Result<resources::ResourceUsage, String> {
+    let caches = resources::cache_sizes(&state);
+    Ok(state.resources.usage(caches))
 }
 
 // ==================== TYPES ====================
@@ -115,6 +3315,8 @@ pub struct WorkspaceInfo {
     pub path: String,
     pub file_count: usize,
     pub indexed: bool,
+    /// False in restricted mode; see `set_workspace_trust`
+    pub trusted: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -124,6 +3326,20 @@ pub struct FileMatch {
     pub line: Option<usize>,
     pub snippet: Option<String>,
     pub score: f32,
+    /// Matched characters within `path`, `name` and `snippet`
+    #[serde(default)]
+    pub path_ranges: Vec<MatchRange>,
+    #[serde(default)]
+    pub name_ranges: Vec<MatchRange>,
+    #[serde(default)]
+    pub snippet_ranges: Vec<MatchRange>,
+}
+
+/// Half-open range in UTF-16 code units, matching JavaScript string indices
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct MatchRange {
+    pub start: usize,
+    pub end: usize,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -136,29 +3352,280 @@ pub struct CodeSuggestion {
     pub fix: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChangesetApplication {
+    pub applied: bool,
+    /// Externally modified files; nothing is written while any exist
+    pub conflicts: Vec<changeset::WriteConflict>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct WorkspaceStats {
     pub total_files: usize,
     pub total_lines: usize,
+    /// Minified or generated files, left out of `total_lines` and `by_language`
+    pub generated_files: usize,
+    /// `total_lines` split into code, comment and blank lines
+    pub line_counts: sloc::LineCounts,
     pub by_language: std::collections::HashMap<String, usize>,
+    pub line_counts_by_language: std::collections::HashMap<String, sloc::LineCounts>,
     pub dependency_count: usize,
 }
 
 // ==================== MAIN ====================
 
 fn main() {
-    env_logger::init();
+    let context = tauri::generate_context!();
+    let _log_guard = logging::init(tauri::api::path::app_log_dir(context.config()).as_deref());
 
     tauri::Builder::default()
         .manage(AppState::default())
+        .setup(|app| {
+            // Load persisted settings and trust decisions before the frontend issues commands
+            if let Ok(dir) = app_config_dir(&app.handle()) {
+                let state = app.state::<AppState>();
+                *state.trust.lock().unwrap() = trust::TrustStore::load(dir.join("trust.json"));
+                let loaded = settings::load(&settings::settings_path(&dir));
+                apply_settings(&loaded, &state);
+                *state.settings.lock().unwrap() = loaded;
+            }
+
+            // Periodically snapshot recoverable state for `restore_last_state`
+            if let Ok(dir) = app_data_dir(&app.handle()) {
+                let handle = app.handle();
+                let path = session::session_path(&dir);
+                let started_at = storage::now_millis();
+                tauri::async_runtime::spawn(async move {
+                    let mut interval = tokio::time::interval(session::SNAPSHOT_INTERVAL);
+                    let mut last: Option<session::SessionState> = None;
+                    loop {
+                        interval.tick().await;
+                        let current = session::capture(&handle.state::<AppState>(), started_at);
+                        // Nothing to recover until a workspace is open; keep the previous session
+                        if current.workspace.is_none() || !session::changed(last.as_ref(), &current) {
+                            continue;
+                        }
+                        if let Err(e) = session::save(&path, &current) {
+                            log::warn!("Failed to save session state: {}", e);
+                        }
+                        last = Some(current);
+                    }
+                });
+            }
+
+            // Automatic update checks on the configured channel, within the interval and metered policy
+            if let Ok(dir) = app_data_dir(&app.handle()) {
+                let handle = app.handle();
+                let dir = updates::updates_dir(&dir);
+                tauri::async_runtime::spawn(async move {
+                    let mut interval = tokio::time::interval(updates::POLL_INTERVAL);
+                    loop {
+                        interval.tick().await;
+                        let settings = handle.state::<AppState>().settings.lock().unwrap().updates.clone();
+                        let last = updates::last_check(&dir).ok().flatten();
+                        let metered = tauri::async_runtime::spawn_blocking(updates::is_metered).await.unwrap_or(true);
+                        if updates::skip_reason(&settings, last.as_ref(), metered, storage::now_millis()).is_some() {
+                            continue;
+                        }
+                        match updates::check(&dir, &settings).await {
+                            Ok(info) if info.available => {
+                                let _ = handle.emit_all("update-available", info);
+                            }
+                            Ok(_) => {}
+                            Err(e) => log::warn!("Update check failed: {}", e),
+                        }
+                    }
+                });
+            }
+
+            // Sample memory and CPU so usage spikes show up in `get_engine_resource_usage`
+            let handle = app.handle();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(resources::SAMPLE_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let monitor = handle.clone();
+                    let _ = tauri::async_runtime::spawn_blocking(move || monitor.state::<AppState>().resources.sample()).await;
+                }
+            });
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             open_workspace,
+            get_workspace_trust,
+            set_workspace_trust,
             search_files,
             get_dependencies,
             get_dependents,
+            query_graph,
+            suggest_modularization,
+            preview_graph_impact,
             analyze_code,
+            analyze_buffer,
             get_workspace_stats,
+            get_directory_stats,
+            get_diagnostics,
+            get_diagnostics_by_owner,
+            list_ai_providers,
+            set_active_ai_provider,
+            ai_review_changes,
+            create_chat_session,
+            rename_chat_session,
+            delete_chat_session,
+            list_chat_sessions,
+            get_chat_session,
+            attach_chat_context,
+            send_chat_message,
+            start_agent_task,
+            approve_agent_action,
+            get_agent_run,
+            cancel_agent_run,
+            count_tokens,
+            list_local_models,
+            download_local_model,
+            delete_local_model,
+            load_local_model,
+            set_secret,
+            get_secret_exists,
+            delete_secret,
+            get_settings,
+            update_settings,
+            get_ai_cache_stats,
+            clear_ai_cache,
+            ai_edit_selection,
+            apply_changeset,
+            generate_tests,
+            generate_docs,
+            explain_diagnostic,
+            get_related_files,
+            preview_table,
+            index_lines,
+            read_lines,
+            get_file_info,
+            list_templates,
+            create_from_template,
+            create_from_file_template,
+            add_import,
+            organize_imports,
+            get_import_cost,
+            analyze_bundle,
+            get_chunk_contributors,
+            resolve_sourcemap_position,
+            parse_stack_trace,
+            tail_file,
+            stop_tail,
+            list_tails,
+            move_file,
+            rewrite_renamed_imports,
+            inline_symbol,
+            change_signature,
+            check_rename,
+            goto_type_definition,
+            search_symbols,
+            get_public_api,
+            diff_public_api,
+            suggest_version_bump,
+            generate_changelog,
+            grep_workspace,
+            advanced_search,
+            get_file_timeline,
+            get_history_snapshot,
+            compare_workspaces,
+            generate_workspace_report,
+            get_event_catalog,
+            subscribe_events,
+            next_events,
+            unsubscribe_events,
+            restore_last_state,
+            list_profiles,
+            apply_profile,
+            get_workspace_env,
+            set_workspace_env,
+            start_background_task,
+            list_background_tasks,
+            get_background_task_output,
+            stop_background_task,
+            list_listening_ports,
+            list_dev_servers,
+            open_in_browser,
+            debug_launch,
+            list_debug_sessions,
+            debug_set_breakpoints,
+            debug_control,
+            debug_threads,
+            debug_stack_trace,
+            debug_scopes,
+            debug_variables,
+            debug_evaluate,
+            debug_stop,
+            set_breakpoint,
+            list_breakpoints,
+            toggle_breakpoint,
+            list_run_configurations,
+            save_run_configuration,
+            delete_run_configuration,
+            run_configuration,
+            get_setup_suggestions,
+            run_setup_step,
+            list_keybindings,
+            resolve_keybinding,
+            set_keybinding_overrides,
+            register_plugin_keybindings,
+            load_profile,
+            get_file_hotspots_from_profile,
+            get_engine_resource_usage,
+            find_duplicate_files,
+            analyze_disk_usage,
+            pin_file,
+            unpin_file,
+            get_working_set,
+            begin_edit_transaction,
+            stage_transaction_operation,
+            commit_transaction,
+            get_audit_log,
+            rollback_transaction,
+            make_writable,
+            open_document,
+            did_open_document,
+            apply_text_edits,
+            get_text_range,
+            save_document,
+            close_document,
+            get_custom_words,
+            add_custom_word,
+            remove_custom_word,
+            get_tasks,
+            set_task_state,
+            get_ts_strictness,
+            get_python_type_coverage,
+            get_i18n_report,
+            scan_env_usage,
+            extract_endpoints,
+            export_openapi,
+            validate_graphql,
+            find_idl_usages,
+            inspect_database,
+            validate_sql,
+            list_http_requests,
+            execute_http_request,
+            detect_containers,
+            list_containers,
+            find_case_collisions,
+            detect_toolchains,
+            get_sparse_index,
+            expand_sparse_index,
+            contract_sparse_index,
+            list_analyzer_rules,
+            set_analyzer_rule_enabled,
+            set_log_level,
+            check_for_updates,
+            download_update,
+            get_recent_logs,
+            get_analyzer_rule_timings,
+            get_analysis_budget,
+            set_analysis_budget,
+            get_slow_files,
         ])
-        .run(tauri::generate_context!())
+        .run(context)
         .expect("error while running tauri application");
 }
@@ -4,51 +4,149 @@
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::fs;
+use std::sync::Arc;
 use anyhow::Result;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
-/// Code dependency graph for intelligent code analysis
+use crate::changeset::{self, FilePreview};
+use crate::file_indexer::FileIndex;
+use crate::idl::{self, IdlIndex};
+use crate::paths::CanonicalPath;
+use crate::resolvers;
+use crate::symbols::{self, SymbolInfo, SymbolTable};
+
+/// Code dependency graph for intelligent code analysis.
+/// Tables are shared copy-on-write, so a clone is a cheap snapshot.
+#[derive(Clone)]
 pub struct CodeGraph {
     /// Map from file path to its dependencies (imports)
-    dependencies: HashMap<String, HashSet<String>>,
+    dependencies: Arc<HashMap<CanonicalPath, HashSet<CanonicalPath>>>,
     /// Map from file path to files that depend on it
-    dependents: HashMap<String, HashSet<String>>,
+    dependents: Arc<HashMap<CanonicalPath, HashSet<CanonicalPath>>>,
     /// Symbol table for cross-file resolution
-    symbols: HashMap<String, Vec<SymbolInfo>>,
+    symbols: Arc<SymbolTable>,
+    /// Protobuf and OpenAPI definitions; code using their generated names depends on the IDL file
+    idl: Arc<IdlIndex>,
+}
+
+/// An import edge between two graph nodes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+}
+
+/// How a speculative change would reshape the graph
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GraphImpact {
+    pub added_edges: Vec<GraphEdge>,
+    pub removed_edges: Vec<GraphEdge>,
+    /// Cycles that exist only after the change
+    pub new_cycles: Vec<Vec<String>>,
+    /// Changed files and everything depending on them, transitively
+    pub impacted_files: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphDirection {
+    /// Files the start files import
+    #[default]
+    Dependencies,
+    /// Files importing the start files
+    Dependents,
+    Both,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphSort {
+    #[default]
+    Path,
+    /// Most imported first
+    FanIn,
+    /// Most imports first
+    FanOut,
+    /// Closest to the start files first
+    Depth,
 }
 
-#[derive(Clone, Debug)]
-pub struct SymbolInfo {
-    pub name: String,
-    pub kind: SymbolKind,
-    pub file: String,
-    pub line: usize,
-    pub exported: bool,
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct GraphQuery {
+    /// Files to start from; every file of the graph when empty
+    pub files: Vec<String>,
+    pub direction: GraphDirection,
+    /// Hops from the start files, 1 for direct neighbors
+    pub depth: usize,
+    /// Languages of workspace files to keep, e.g. `TypeScript`; all when empty
+    pub languages: Vec<String>,
+    /// Keep packages and unresolved imports, which are not workspace files
+    pub include_external: bool,
+    pub sort: GraphSort,
+    pub offset: usize,
+    pub limit: usize,
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub enum SymbolKind {
-    Function,
-    Class,
-    Interface,
-    Variable,
-    Constant,
-    Type,
-    Module,
+impl Default for GraphQuery {
+    fn default() -> Self {
+        Self {
+            files: Vec::new(),
+            direction: GraphDirection::default(),
+            depth: 1,
+            languages: Vec::new(),
+            include_external: true,
+            sort: GraphSort::default(),
+            offset: 0,
+            limit: 100,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GraphNode {
+    pub path: String,
+    /// Hops from the nearest start file
+    pub depth: usize,
+    pub fan_in: usize,
+    pub fan_out: usize,
+    pub external: bool,
+    /// Empty for external nodes
+    pub language: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GraphQueryResult {
+    /// One page of nodes
+    pub nodes: Vec<GraphNode>,
+    /// Edges between the nodes of the page
+    pub edges: Vec<GraphEdge>,
+    /// Matching nodes across all pages
+    pub total: usize,
 }
 
 impl CodeGraph {
     pub fn new() -> Self {
         Self {
-            dependencies: HashMap::new(),
-            dependents: HashMap::new(),
-            symbols: HashMap::new(),
+            dependencies: Arc::new(HashMap::new()),
+            dependents: Arc::new(HashMap::new()),
+            symbols: Arc::new(SymbolTable::new()),
+            idl: Arc::new(IdlIndex::default()),
         }
     }
 
-    /// Analyze entire workspace and build dependency graph
-    pub fn analyze_workspace(&mut self, workspace_path: &Path) -> Result<()> {
+    /// Files of a registered language, GraphQL and IDL files take part in the graph
+    pub fn is_analyzed(path: &Path) -> bool {
+        let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+        resolvers::registry().for_path(path).is_some()
+            || crate::graphql::is_document_file(ext)
+            || idl::is_idl_file(&path.to_string_lossy())
+    }
+
+    /// Analyze entire workspace and build dependency graph, skipping `excluded` files
+    pub fn analyze_workspace(&mut self, workspace_path: &Path, excluded: &HashSet<String>) -> Result<()> {
         log::info!("Analyzing workspace: {:?}", workspace_path);
 
         // Collect all TypeScript/JavaScript files
@@ -57,32 +155,48 @@ impl CodeGraph {
             .filter_map(|e| e.ok())
             .filter(|e| {
                 let path = e.path();
-                let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-                matches!(ext, "ts" | "tsx" | "js" | "jsx" | "rs" | "py")
+                Self::is_analyzed(path)
                     && !path.to_string_lossy().contains("node_modules")
                     && !path.to_string_lossy().contains(".git")
+                    && !path.to_string_lossy().contains(crate::storage::DATA_DIR)
+                    && !excluded.contains(&*path.to_string_lossy())
             })
             .map(|e| e.path().to_path_buf())
             .collect();
 
         log::info!("Found {} source files to analyze", files.len());
 
+        // IDL definitions come first so the files using them can be linked
+        let mut idl = IdlIndex::default();
+        for path in files.iter().filter(|p| idl::is_idl_file(&p.to_string_lossy())) {
+            if let Ok(content) = fs::read_to_string(path) {
+                idl.update(&path.to_string_lossy(), &content);
+            }
+        }
+        self.idl = Arc::new(idl);
+
         // Analyze files in parallel
         let results: Vec<(String, HashSet<String>, Vec<SymbolInfo>)> = files
             .par_iter()
             .filter_map(|path| {
-                self.analyze_file(path).ok()
+                self.analyze_file(workspace_path, path).ok()
             })
             .collect();
 
         // Build graph from results
+        let dependencies = Arc::make_mut(&mut self.dependencies);
+        let dependents = Arc::make_mut(&mut self.dependents);
+        let symbols = Arc::make_mut(&mut self.symbols);
         for (file, deps, syms) in results {
+            let file = CanonicalPath::new(file);
+            let deps: HashSet<CanonicalPath> = deps.into_iter().map(CanonicalPath::new).collect();
+
             // Add dependencies
-            self.dependencies.insert(file.clone(), deps.clone());
+            dependencies.insert(file.clone(), deps.clone());
 
             // Add reverse dependencies (dependents)
             for dep in deps {
-                self.dependents
+                dependents
                     .entry(dep)
                     .or_insert_with(HashSet::new)
                     .insert(file.clone());
@@ -90,10 +204,7 @@ impl CodeGraph {
 
             // Add symbols
             for sym in syms {
-                self.symbols
-                    .entry(sym.name.clone())
-                    .or_insert_with(Vec::new)
-                    .push(sym);
+                symbols.insert(sym);
             }
         }
 
@@ -107,138 +218,211 @@ impl CodeGraph {
         Ok(())
     }
 
-    /// Analyze a single file for imports and exports
-    fn analyze_file(&self, path: &Path) -> Result<(String, HashSet<String>, Vec<SymbolInfo>)> {
-        let content = fs::read_to_string(path)?;
-        let file_path = path.to_string_lossy().to_string();
-        let mut deps = HashSet::new();
-        let mut symbols = Vec::new();
-
-        // Extract imports - TypeScript/JavaScript
-        for line in content.lines() {
-            let line = line.trim();
-
-            // import { x } from 'module'
-            if line.starts_with("import") {
-                if let Some(from_idx) = line.find("from") {
-                    let module = line[from_idx + 4..]
-                        .trim()
-                        .trim_matches(|c| c == '\'' || c == '"' || c == ';');
-                    deps.insert(self.resolve_import(path, module));
-                }
-            }
-
-            // require('module')
-            if line.contains("require(") {
-                if let Some(start) = line.find("require(") {
-                    let rest = &line[start + 8..];
-                    if let Some(end) = rest.find(')') {
-                        let module = rest[..end]
-                            .trim_matches(|c| c == '\'' || c == '"');
-                        deps.insert(self.resolve_import(path, module));
-                    }
-                }
-            }
+    /// Re-analyze one file from `content`, e.g. an unsaved editor buffer
+    pub fn update_file(&mut self, workspace_path: &Path, path: &Path, content: &str) {
+        if idl::is_idl_file(&path.to_string_lossy()) {
+            Arc::make_mut(&mut self.idl).update(&path.to_string_lossy(), content);
+        }
+        let (file_path, deps, syms) = self.analyze_content(workspace_path, path, content);
+        let file = CanonicalPath::new(&file_path);
+        let deps: HashSet<CanonicalPath> = deps.into_iter().map(CanonicalPath::new).collect();
+        self.unlink(&file);
+        let dependents = Arc::make_mut(&mut self.dependents);
+        for dep in &deps {
+            dependents.entry(dep.clone()).or_default().insert(file.clone());
+        }
+        Arc::make_mut(&mut self.dependencies).insert(file, deps);
+        let symbols = Arc::make_mut(&mut self.symbols);
+        symbols.remove_file(&file_path);
+        for sym in syms {
+            symbols.insert(sym);
+        }
+    }
 
-            // Extract exports (simplified)
-            if line.starts_with("export") {
-                if line.contains("function") || line.contains("const") || line.contains("class") {
-                    if let Some(name) = self.extract_export_name(line) {
-                        let kind = if line.contains("function") {
-                            SymbolKind::Function
-                        } else if line.contains("class") {
-                            SymbolKind::Class
-                        } else {
-                            SymbolKind::Variable
-                        };
-
-                        symbols.push(SymbolInfo {
-                            name,
-                            kind,
-                            file: file_path.clone(),
-                            line: 0, // Would need proper parsing
-                            exported: true,
-                        });
-                    }
+    /// Remove the outgoing edges of `file`
+    fn unlink(&mut self, file: &CanonicalPath) {
+        if !self.dependencies.contains_key(file) {
+            return;
+        }
+        if let Some(old) = Arc::make_mut(&mut self.dependencies).remove(file) {
+            let dependents = Arc::make_mut(&mut self.dependents);
+            for dep in old {
+                if let Some(dependents) = dependents.get_mut(&dep) {
+                    dependents.remove(file);
                 }
             }
         }
+    }
 
-        Ok((file_path, deps, symbols))
+    /// Drop a file's edges and symbols; files importing it keep their unresolved edge
+    pub fn remove_file(&mut self, path: &Path) {
+        let file_path = path.to_string_lossy().to_string();
+        self.unlink(&CanonicalPath::new(&file_path));
+        if idl::is_idl_file(&file_path) {
+            Arc::make_mut(&mut self.idl).update(&file_path, "");
+        }
+        if !self.symbols.in_file(&file_path).is_empty() {
+            Arc::make_mut(&mut self.symbols).remove_file(&file_path);
+        }
     }
 
-    /// Resolve relative import to absolute path
-    fn resolve_import(&self, from_file: &Path, import: &str) -> String {
-        if import.starts_with('.') {
-            // Relative import
-            if let Some(parent) = from_file.parent() {
-                let resolved = parent.join(import);
-                // Try common extensions
-                for ext in &["", ".ts", ".tsx", ".js", ".jsx", "/index.ts", "/index.js"] {
-                    let with_ext = format!("{}{}", resolved.to_string_lossy(), ext);
-                    if PathBuf::from(&with_ext).exists() {
-                        return with_ext;
-                    }
-                }
-                return resolved.to_string_lossy().to_string();
-            }
+    /// Move a file's node from `from` to `to`, re-analyzing it from `content`.
+    /// Files importing it keep their edge, now pointing at the new path; returns them.
+    pub fn rename_file(&mut self, workspace_path: &Path, from: &Path, to: &Path, content: &str) -> Vec<String> {
+        let old = CanonicalPath::new(from);
+        let new = CanonicalPath::new(to);
+        self.remove_file(from);
+        if Self::is_analyzed(to) {
+            self.update_file(workspace_path, to, content);
         }
-        // Package import - return as-is
-        import.to_string()
-    }
-
-    /// Extract export name from line
-    fn extract_export_name(&self, line: &str) -> Option<String> {
-        let keywords = ["function", "class", "const", "let", "var", "interface", "type"];
-        
-        for keyword in keywords {
-            if let Some(idx) = line.find(keyword) {
-                let rest = line[idx + keyword.len()..].trim();
-                let name: String = rest
-                    .chars()
-                    .take_while(|c| c.is_alphanumeric() || *c == '_')
-                    .collect();
-                if !name.is_empty() {
-                    return Some(name);
-                }
+        let Some(importers) = Arc::make_mut(&mut self.dependents).remove(&old) else {
+            return Vec::new();
+        };
+        let dependencies = Arc::make_mut(&mut self.dependencies);
+        for importer in &importers {
+            if let Some(deps) = dependencies.get_mut(importer) {
+                deps.remove(&old);
+                deps.insert(new.clone());
             }
         }
-        None
+        let mut dependents: Vec<String> = importers.iter().map(|importer| importer.as_str().to_string()).collect();
+        Arc::make_mut(&mut self.dependents).entry(new).or_default().extend(importers);
+        dependents.sort();
+        dependents
+    }
+
+    /// Analyze a single file for imports and exports
+    fn analyze_file(&self, workspace_path: &Path, path: &Path) -> Result<(String, HashSet<String>, Vec<SymbolInfo>)> {
+        let content = fs::read_to_string(path)?;
+        Ok(self.analyze_content(workspace_path, path, &content))
+    }
+
+    fn analyze_content(&self, workspace_path: &Path, path: &Path, content: &str) -> (String, HashSet<String>, Vec<SymbolInfo>) {
+        let file_path = path.to_string_lossy().to_string();
+        let (mut deps, symbols) = match resolvers::registry().for_path(path) {
+            Some(resolver) => {
+                let imports = resolver.extract_imports(content);
+                let deps: HashSet<String> =
+                    imports.iter().map(|import| resolver.resolve(workspace_path, path, import)).collect();
+                (deps, resolver.extract_symbols(workspace_path, path, content))
+            }
+            // GraphQL and IDL files have symbols but no imports of their own
+            None => (HashSet::new(), symbols::extract(workspace_path, path, content)),
+        };
+
+        deps.extend(self.idl.files_referenced(&file_path, content));
+
+        (file_path, deps, symbols)
     }
 
     /// Get dependencies of a file
     pub fn get_dependencies(&self, file_path: &str) -> Vec<String> {
         self.dependencies
-            .get(file_path)
-            .map(|deps| deps.iter().cloned().collect())
+            .get(&CanonicalPath::new(file_path))
+            .map(|deps| deps.iter().map(|dep| dep.as_str().to_string()).collect())
             .unwrap_or_default()
     }
 
     /// Get files that depend on this file
     pub fn get_dependents(&self, file_path: &str) -> Vec<String> {
         self.dependents
-            .get(file_path)
-            .map(|deps| deps.iter().cloned().collect())
+            .get(&CanonicalPath::new(file_path))
+            .map(|deps| deps.iter().map(|dep| dep.as_str().to_string()).collect())
             .unwrap_or_default()
     }
 
+    /// Imports of the files accepted by `include`, for persisting the graph
+    pub fn dependency_lists(&self, include: impl Fn(&str) -> bool) -> Vec<(String, Vec<String>)> {
+        self.dependencies
+            .iter()
+            .filter(|(file, _)| include(file.as_str()))
+            .map(|(file, deps)| {
+                let mut deps: Vec<String> = deps.iter().map(|dep| dep.as_str().to_string()).collect();
+                deps.sort();
+                (file.as_str().to_string(), deps)
+            })
+            .collect()
+    }
+
+    /// Graph of persisted import edges, as returned by `dependency_lists`; symbols come with analysis
+    pub fn from_dependency_lists(lists: Vec<(String, Vec<String>)>) -> Self {
+        let mut graph = Self::new();
+        let dependents = Arc::make_mut(&mut graph.dependents);
+        let dependencies = Arc::make_mut(&mut graph.dependencies);
+        for (file, deps) in lists {
+            let file = CanonicalPath::new(file);
+            let deps: HashSet<CanonicalPath> = deps.into_iter().map(CanonicalPath::new).collect();
+            for dep in &deps {
+                dependents.entry(dep.clone()).or_default().insert(file.clone());
+            }
+            dependencies.insert(file, deps);
+        }
+        graph
+    }
+
+    /// Files in the dependency graph
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.dependencies.keys().map(CanonicalPath::as_str)
+    }
+
+    /// Imports between files of the graph, leaving out packages and unresolved imports
+    pub fn internal_edges(&self) -> Vec<(String, String)> {
+        self.dependencies
+            .iter()
+            .flat_map(|(file, deps)| {
+                deps.iter()
+                    .filter(|dep| self.dependencies.contains_key(*dep))
+                    .map(move |dep| (file.as_str().to_string(), dep.as_str().to_string()))
+            })
+            .collect()
+    }
+
+    /// Files in the dependency graph
+    pub fn file_count(&self) -> usize {
+        self.dependencies.len()
+    }
+
+    pub fn symbol_count(&self) -> usize {
+        self.symbols.len()
+    }
+
     /// Get total edge count
     pub fn edge_count(&self) -> usize {
         self.dependencies.values().map(|v| v.len()).sum()
     }
 
-    /// Find symbol across workspace
+    /// Rough heap size of the graph, for memory budgets
+    pub fn approximate_bytes(&self) -> usize {
+        let edges: usize = self
+            .dependencies
+            .iter()
+            .chain(self.dependents.iter())
+            .map(|(file, deps)| {
+                2 * file.as_str().len() + deps.iter().map(|dep| 2 * dep.as_str().len() + 8).sum::<usize>()
+            })
+            .sum();
+        edges + self.symbols.len() * 160
+    }
+
+    /// Find symbol across workspace by bare or qualified name, exported first
     pub fn find_symbol(&self, name: &str) -> Vec<&SymbolInfo> {
-        self.symbols
-            .get(name)
-            .map(|syms| syms.iter().collect())
-            .unwrap_or_default()
+        self.symbols.find(name)
+    }
+
+    /// Symbols declared in a file
+    pub fn symbols_in_file(&self, file_path: &str) -> Vec<&SymbolInfo> {
+        self.symbols.in_file(file_path)
+    }
+
+    /// Symbols whose name matches `query`, best matches first
+    pub fn search_symbols(&self, query: &str, limit: usize) -> Vec<&SymbolInfo> {
+        self.symbols.search(query, limit)
     }
 
     /// Get all files affected by changes to a file (transitive)
     pub fn get_impact_scope(&self, file_path: &str, max_depth: usize) -> HashSet<String> {
         let mut affected = HashSet::new();
-        let mut to_process = vec![file_path.to_string()];
+        let mut to_process = vec![CanonicalPath::new(file_path)];
         let mut depth = 0;
 
         while !to_process.is_empty() && depth < max_depth {
@@ -256,7 +440,241 @@ impl CodeGraph {
             depth += 1;
         }
 
-        affected
+        affected.into_iter().map(|file| file.as_str().to_string()).collect()
+    }
+
+    /// Dependency cycles: strongly connected groups of files that import each other, largest first
+    pub fn find_cycles(&self) -> Vec<Vec<String>> {
+        // Tarjan's algorithm with an explicit stack so deep import chains cannot overflow
+        let mut index_of: HashMap<&CanonicalPath, usize> = HashMap::new();
+        let mut low: HashMap<&CanonicalPath, usize> = HashMap::new();
+        let mut on_stack: HashSet<&CanonicalPath> = HashSet::new();
+        let mut stack: Vec<&CanonicalPath> = Vec::new();
+        let mut cycles = Vec::new();
+        let empty = HashSet::new();
+
+        let mut roots: Vec<&CanonicalPath> = self.dependencies.keys().collect();
+        roots.sort();
+        for root in roots {
+            if index_of.contains_key(root) {
+                continue;
+            }
+            let mut work: Vec<(&CanonicalPath, Vec<&CanonicalPath>)> = Vec::new();
+            let next = index_of.len();
+            index_of.insert(root, next);
+            low.insert(root, next);
+            stack.push(root);
+            on_stack.insert(root);
+            work.push((root, self.dependencies.get(root).unwrap_or(&empty).iter().collect()));
+
+            while let Some((node, pending)) = work.last_mut() {
+                let node = *node;
+                if let Some(dep) = pending.pop() {
+                    if !self.dependencies.contains_key(dep) {
+                        // Packages and unresolved imports cannot close a cycle
+                        continue;
+                    }
+                    if let Some(&dep_index) = index_of.get(dep) {
+                        if on_stack.contains(dep) {
+                            let current = low[node].min(dep_index);
+                            low.insert(node, current);
+                        }
+                    } else {
+                        let next = index_of.len();
+                        index_of.insert(dep, next);
+                        low.insert(dep, next);
+                        stack.push(dep);
+                        on_stack.insert(dep);
+                        work.push((dep, self.dependencies.get(dep).unwrap_or(&empty).iter().collect()));
+                    }
+                    continue;
+                }
+
+                work.pop();
+                if let Some((parent, _)) = work.last() {
+                    let current = low[parent].min(low[node]);
+                    low.insert(parent, current);
+                }
+                if low[node] == index_of[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack.remove(member);
+                        component.push(member.as_str().to_string());
+                        if member == node {
+                            break;
+                        }
+                    }
+                    let self_import = self.dependencies.get(node).map(|d| d.contains(node)).unwrap_or(false);
+                    if component.len() > 1 || self_import {
+                        component.sort();
+                        cycles.push(component);
+                    }
+                }
+            }
+        }
+
+        cycles.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        cycles
+    }
+
+    /// Copy of the graph for speculative analysis; tables are copied only once either side changes them.
+    /// Discarding the snapshot rolls the speculation back.
+    pub fn snapshot(&self) -> CodeGraph {
+        self.clone()
+    }
+
+    /// Re-analyze the files a previewed changeset writes, deletes or renames; returns the touched paths.
+    /// Imports of files the changeset creates resolve only once those files exist on disk.
+    pub fn apply_previews(&mut self, workspace_path: &Path, previews: &[FilePreview]) -> Vec<String> {
+        let mut touched = Vec::new();
+        for preview in previews {
+            let path = changeset::resolve(workspace_path, &preview.path);
+            touched.push(path.to_string_lossy().to_string());
+            let written = match (&preview.new_path, &preview.after) {
+                (Some(to), _) => {
+                    let content = fs::read_to_string(&path).unwrap_or_default();
+                    self.remove_file(&path);
+                    Some((changeset::resolve(workspace_path, to), content))
+                }
+                (None, Some(after)) => Some((path, after.clone())),
+                (None, None) => {
+                    self.remove_file(&path);
+                    None
+                }
+            };
+            if let Some((path, content)) = written {
+                if Self::is_analyzed(&path) {
+                    self.update_file(workspace_path, &path, &content);
+                }
+                let path = path.to_string_lossy().to_string();
+                if !touched.contains(&path) {
+                    touched.push(path);
+                }
+            }
+        }
+        touched
+    }
+
+    /// Differences between this graph and `speculative`, which changed only the `touched` files
+    pub fn impact_of(&self, speculative: &CodeGraph, touched: &[String]) -> GraphImpact {
+        let edges = |graph: &CodeGraph, file: &str| -> HashSet<GraphEdge> {
+            graph
+                .get_dependencies(file)
+                .into_iter()
+                .map(|to| GraphEdge {
+                    from: file.to_string(),
+                    to,
+                })
+                .collect()
+        };
+        let (mut added_edges, mut removed_edges) = (Vec::new(), Vec::new());
+        for file in touched {
+            let (before, after) = (edges(self, file), edges(speculative, file));
+            added_edges.extend(after.difference(&before).cloned());
+            removed_edges.extend(before.difference(&after).cloned());
+        }
+        added_edges.sort();
+        removed_edges.sort();
+
+        let existing = self.find_cycles();
+        let new_cycles = speculative.find_cycles().into_iter().filter(|cycle| !existing.contains(cycle)).collect();
+
+        let impacted: HashSet<String> =
+            touched.iter().flat_map(|file| speculative.get_impact_scope(file, 10)).collect();
+        let mut impacted_files: Vec<String> = impacted.into_iter().collect();
+        impacted_files.sort();
+        GraphImpact {
+            added_edges,
+            removed_edges,
+            new_cycles,
+            impacted_files,
+        }
+    }
+
+    /// Filtered, sorted and paginated neighborhood of `query.files`, or of the whole graph
+    pub fn query(&self, query: &GraphQuery) -> GraphQueryResult {
+        let empty = HashSet::new();
+        let imports = |node: &CanonicalPath| self.dependencies.get(node).unwrap_or(&empty);
+        let importers = |node: &CanonicalPath| self.dependents.get(node).unwrap_or(&empty);
+
+        // Breadth-first, so the first depth seen is the shortest
+        let mut depths: HashMap<CanonicalPath, usize> = HashMap::new();
+        if query.files.is_empty() {
+            for (file, deps) in self.dependencies.iter() {
+                depths.insert(file.clone(), 0);
+                for dep in deps {
+                    depths.entry(dep.clone()).or_insert(0);
+                }
+            }
+        } else {
+            let mut frontier: Vec<CanonicalPath> = query.files.iter().map(CanonicalPath::new).collect();
+            for file in &frontier {
+                depths.insert(file.clone(), 0);
+            }
+            for depth in 1..=query.depth {
+                let mut next = Vec::new();
+                for node in &frontier {
+                    let mut neighbors: Vec<&CanonicalPath> = Vec::new();
+                    if matches!(query.direction, GraphDirection::Dependencies | GraphDirection::Both) {
+                        neighbors.extend(imports(node));
+                    }
+                    if matches!(query.direction, GraphDirection::Dependents | GraphDirection::Both) {
+                        neighbors.extend(importers(node));
+                    }
+                    for neighbor in neighbors {
+                        if !depths.contains_key(neighbor) {
+                            depths.insert(neighbor.clone(), depth);
+                            next.push(neighbor.clone());
+                        }
+                    }
+                }
+                frontier = next;
+            }
+        }
+
+        let mut nodes: Vec<GraphNode> = depths
+            .iter()
+            .map(|(node, &depth)| {
+                let external = !self.dependencies.contains_key(node);
+                let extension = Path::new(node.as_str()).extension().and_then(|e| e.to_str()).unwrap_or("");
+                GraphNode {
+                    path: node.as_str().to_string(),
+                    depth,
+                    fan_in: importers(node).len(),
+                    fan_out: imports(node).len(),
+                    external,
+                    language: if external { String::new() } else { FileIndex::detect_language(extension) },
+                }
+            })
+            .filter(|node| if node.external { query.include_external } else { true })
+            .filter(|node| {
+                node.external
+                    || query.languages.is_empty()
+                    || query.languages.iter().any(|language| language.eq_ignore_ascii_case(&node.language))
+            })
+            .collect();
+        let by_path = |a: &GraphNode, b: &GraphNode| a.path.cmp(&b.path);
+        match query.sort {
+            GraphSort::Path => nodes.sort_by(by_path),
+            GraphSort::FanIn => nodes.sort_by(|a, b| b.fan_in.cmp(&a.fan_in).then_with(|| by_path(a, b))),
+            GraphSort::FanOut => nodes.sort_by(|a, b| b.fan_out.cmp(&a.fan_out).then_with(|| by_path(a, b))),
+            GraphSort::Depth => nodes.sort_by(|a, b| a.depth.cmp(&b.depth).then_with(|| by_path(a, b))),
+        }
+
+        let total = nodes.len();
+        let nodes: Vec<GraphNode> = nodes.into_iter().skip(query.offset).take(query.limit).collect();
+        let page: HashSet<CanonicalPath> = nodes.iter().map(|node| CanonicalPath::new(&node.path)).collect();
+        let mut edges: Vec<GraphEdge> = page
+            .iter()
+            .flat_map(|from| {
+                imports(from).iter().filter(|to| page.contains(*to)).map(move |to| GraphEdge {
+                    from: from.as_str().to_string(),
+                    to: to.as_str().to_string(),
+                })
+            })
+            .collect();
+        edges.sort();
+        GraphQueryResult { nodes, edges, total }
     }
 }
 
@@ -269,4 +687,81 @@ mod tests {
         let graph = CodeGraph::new();
         assert_eq!(graph.edge_count(), 0);
     }
+
+    #[test]
+    fn test_find_cycles() {
+        let mut graph = CodeGraph::new();
+        for (file, deps) in [("a", vec!["b"]), ("b", vec!["c"]), ("c", vec!["a", "react"]), ("d", vec!["a"])] {
+            Arc::make_mut(&mut graph.dependencies)
+                .insert(CanonicalPath::new(file), deps.into_iter().map(CanonicalPath::new).collect());
+        }
+        assert_eq!(graph.find_cycles(), vec![vec!["a", "b", "c"]]);
+    }
+
+    #[test]
+    fn test_query_filters_sorts_and_pages() {
+        let mut graph = CodeGraph::new();
+        let files = [("a.ts", vec!["b.ts", "react"]), ("b.ts", vec!["c.py"]), ("c.py", vec![]), ("d.ts", vec!["b.ts"])];
+        for (file, deps) in files {
+            for dep in &deps {
+                let dependents = Arc::make_mut(&mut graph.dependents);
+                dependents.entry(CanonicalPath::new(dep)).or_default().insert(CanonicalPath::new(file));
+            }
+            Arc::make_mut(&mut graph.dependencies)
+                .insert(CanonicalPath::new(file), deps.into_iter().map(CanonicalPath::new).collect());
+        }
+
+        let query = GraphQuery {
+            files: vec!["a.ts".to_string()],
+            depth: 2,
+            ..GraphQuery::default()
+        };
+        let result = graph.query(&query);
+        let paths: Vec<&str> = result.nodes.iter().map(|n| n.path.as_str()).collect();
+        assert_eq!(paths, vec!["a.ts", "b.ts", "c.py", "react"]);
+        assert_eq!(result.edges.len(), 3);
+
+        let query = GraphQuery {
+            languages: vec!["typescript".to_string()],
+            include_external: false,
+            sort: GraphSort::FanIn,
+            limit: 1,
+            ..GraphQuery::default()
+        };
+        let result = graph.query(&query);
+        assert_eq!(result.total, 3);
+        assert_eq!(result.nodes[0].path, "b.ts");
+        assert_eq!(result.nodes[0].fan_in, 2);
+    }
+
+    #[test]
+    fn test_speculative_changes_leave_the_snapshot_source_alone() {
+        let root = std::env::temp_dir().join(crate::storage::new_id("graph-snapshot-test"));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.ts"), "import { b } from './b';\n").unwrap();
+        fs::write(root.join("b.ts"), "export const b = 1;\n").unwrap();
+        let mut live = CodeGraph::new();
+        live.analyze_workspace(&root, &HashSet::new()).unwrap();
+        let a = root.join("a.ts").to_string_lossy().to_string();
+        let b = root.join("b.ts").to_string_lossy().to_string();
+
+        let mut speculative = live.snapshot();
+        let preview = FilePreview {
+            path: "b.ts".to_string(),
+            new_path: None,
+            before: None,
+            after: Some("import { a } from './a';\nexport const b = 1;\n".to_string()),
+            diff: String::new(),
+        };
+        let touched = speculative.apply_previews(&root, &[preview]);
+        let impact = live.impact_of(&speculative, &touched);
+
+        assert!(live.get_dependencies(&b).is_empty());
+        assert_eq!(impact.added_edges.len(), 1);
+        assert_eq!(CanonicalPath::new(&impact.added_edges[0].to), CanonicalPath::new(&a));
+        assert!(impact.removed_edges.is_empty());
+        assert_eq!(impact.new_cycles.len(), 1);
+        assert_eq!(impact.impacted_files, vec![a, b]);
+        let _ = fs::remove_dir_all(&root);
+    }
 }
@@ -0,0 +1,166 @@
+// Modularization - Candidate modules from community detection on the dependency graph
+// Files that import each other a lot and the rest little are proposed as a package of their own
+
+use std::collections::{BTreeMap, HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::mimi_engine::GraphEdge;
+
+/// Passes over all files before community detection gives up on converging
+const MAX_PASSES: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModuleSuggestion {
+    pub files: Vec<String>,
+    /// Deepest directory containing every file
+    pub directory: String,
+    /// Imports between files of the cluster
+    pub internal_edges: usize,
+    /// Imports crossing the cluster boundary either way
+    pub external_edges: usize,
+    /// Share of the cluster's imports that stay inside it
+    pub cohesion: f64,
+    /// Imports to break or route through a public interface when extracting the cluster
+    pub cross_edges: Vec<GraphEdge>,
+}
+
+/// Communities by greedy modularity optimization (the local-move phase of Louvain), as a community id per node
+fn communities(nodes: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut adjacency: Vec<HashMap<usize, f64>> = vec![HashMap::new(); nodes];
+    for &(from, to) in edges.iter().filter(|(from, to)| from != to) {
+        *adjacency[from].entry(to).or_default() += 1.0;
+        *adjacency[to].entry(from).or_default() += 1.0;
+    }
+    let degree: Vec<f64> = adjacency.iter().map(|neighbors| neighbors.values().sum()).collect();
+    let double_edges: f64 = degree.iter().sum();
+    let mut community: Vec<usize> = (0..nodes).collect();
+    if double_edges == 0.0 {
+        return community;
+    }
+    // Sum of the degrees in each community
+    let mut total = degree.clone();
+
+    for _ in 0..MAX_PASSES {
+        let mut moved = false;
+        for node in 0..nodes {
+            let current = community[node];
+            total[current] -= degree[node];
+            let mut links: BTreeMap<usize, f64> = BTreeMap::new();
+            for (&neighbor, &weight) in &adjacency[node] {
+                *links.entry(community[neighbor]).or_default() += weight;
+            }
+            let gain = |c: usize, weight: f64| weight - total[c] * degree[node] / double_edges;
+            let mut best = (current, gain(current, links.get(&current).copied().unwrap_or(0.0)));
+            for (&candidate, &weight) in &links {
+                let candidate_gain = gain(candidate, weight);
+                if candidate_gain > best.1 + 1e-12 {
+                    best = (candidate, candidate_gain);
+                }
+            }
+            total[best.0] += degree[node];
+            if best.0 != current {
+                community[node] = best.0;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+    community
+}
+
+fn common_directory(files: &[String]) -> String {
+    let mut prefix: Vec<&str> = files[0].split('/').collect();
+    prefix.pop();
+    for file in &files[1..] {
+        let parts: Vec<&str> = file.split('/').collect();
+        let shared = prefix.iter().zip(&parts).take_while(|(a, b)| a == b).count();
+        prefix.truncate(shared.min(parts.len().saturating_sub(1)));
+    }
+    prefix.join("/")
+}
+
+/// Clusters of at least `min_size` files in the graph of `edges`, most cohesive first
+pub fn suggest(edges: &[(String, String)], min_size: usize) -> Vec<ModuleSuggestion> {
+    let mut ids: BTreeMap<&str, usize> = BTreeMap::new();
+    for (from, to) in edges {
+        ids.entry(from).or_default();
+        ids.entry(to).or_default();
+    }
+    let names: Vec<&str> = ids.keys().copied().collect();
+    for (i, name) in names.iter().enumerate() {
+        ids.insert(name, i);
+    }
+    let indexed: Vec<(usize, usize)> = edges.iter().map(|(from, to)| (ids[from.as_str()], ids[to.as_str()])).collect();
+    let community = communities(names.len(), &indexed);
+
+    let mut members: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for (node, &c) in community.iter().enumerate() {
+        members.entry(c).or_default().push(names[node].to_string());
+    }
+    let mut suggestions: Vec<ModuleSuggestion> = members
+        .into_iter()
+        .filter(|(_, files)| files.len() >= min_size.max(2))
+        .map(|(c, files)| {
+            let mut internal_edges = 0;
+            let mut cross_edges = Vec::new();
+            for (&(from, to), (from_path, to_path)) in indexed.iter().zip(edges) {
+                match (community[from] == c, community[to] == c) {
+                    (true, true) if from != to => internal_edges += 1,
+                    (true, false) | (false, true) => cross_edges.push(GraphEdge {
+                        from: from_path.clone(),
+                        to: to_path.clone(),
+                    }),
+                    _ => {}
+                }
+            }
+            cross_edges.sort();
+            let external_edges = cross_edges.len();
+            ModuleSuggestion {
+                directory: common_directory(&files),
+                files,
+                internal_edges,
+                external_edges,
+                cohesion: internal_edges as f64 / (internal_edges + external_edges).max(1) as f64,
+                cross_edges,
+            }
+        })
+        .collect();
+    suggestions.sort_by(|a, b| {
+        b.cohesion
+            .total_cmp(&a.cohesion)
+            .then_with(|| b.files.len().cmp(&a.files.len()))
+            .then_with(|| a.files.cmp(&b.files))
+    });
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_splits_loosely_coupled_clusters() {
+        let edge = |from: &str, to: &str| (from.to_string(), to.to_string());
+        let edges = vec![
+            edge("src/auth/login.ts", "src/auth/session.ts"),
+            edge("src/auth/session.ts", "src/auth/token.ts"),
+            edge("src/auth/login.ts", "src/auth/token.ts"),
+            edge("src/cart/cart.ts", "src/cart/items.ts"),
+            edge("src/cart/items.ts", "src/cart/price.ts"),
+            edge("src/cart/cart.ts", "src/cart/price.ts"),
+            edge("src/cart/cart.ts", "src/auth/session.ts"),
+        ];
+        let suggestions = suggest(&edges, 3);
+        assert_eq!(suggestions.len(), 2);
+        let auth = suggestions.iter().find(|s| s.directory == "src/auth").unwrap();
+        assert_eq!(auth.files.len(), 3);
+        assert_eq!(auth.internal_edges, 3);
+        assert_eq!(auth.cross_edges, vec![GraphEdge {
+            from: "src/cart/cart.ts".to_string(),
+            to: "src/auth/session.ts".to_string(),
+        }]);
+        assert_eq!(auth.cohesion, 0.75);
+    }
+}
@@ -0,0 +1,228 @@
+// Onboarding - Setup steps a fresh checkout still needs, in the order to run them
+// Each step has a stable task ID, so the frontend can run it through the engine
+
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+use serde::{Deserialize, Serialize};
+
+use crate::task_runner::TaskSpec;
+
+/// Templates a local `.env` is copied from
+const ENV_TEMPLATES: &[&str] = &[".env.example", ".env.sample", ".env.template", ".env.dist"];
+/// Virtual environment directories, as created by `python -m venv`
+const VENV_DIRS: &[&str] = &[".venv", "venv", "env"];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SetupAction {
+    Task { spec: TaskSpec },
+    /// Copied by the engine, so it works without a shell
+    CopyFile { from: String, to: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SetupStep {
+    /// Stable task ID, e.g. `setup:node-install`
+    pub id: String,
+    pub language: String,
+    pub title: String,
+    /// What was found that calls for the step
+    pub reason: String,
+    pub action: SetupAction,
+    /// Steps to run first
+    pub depends_on: Vec<String>,
+    /// Something to do by hand afterwards, e.g. fill in secrets
+    pub note: Option<String>,
+}
+
+fn task(command: &str, args: &[&str]) -> SetupAction {
+    SetupAction::Task {
+        spec: TaskSpec {
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            cwd: None,
+            env: Default::default(),
+            container: None,
+        },
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn step(id: &str, language: &str, title: &str, reason: String, action: SetupAction) -> SetupStep {
+    SetupStep {
+        id: format!("setup:{}", id),
+        language: language.to_string(),
+        title: title.to_string(),
+        reason,
+        action,
+        depends_on: Vec::new(),
+        note: None,
+    }
+}
+
+/// Submodules listed in `.gitmodules` whose directory is missing or empty
+fn uninitialized_submodules(workspace: &Path) -> Vec<String> {
+    let Ok(text) = fs::read_to_string(workspace.join(".gitmodules")) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix("path").map(|rest| rest.trim_start_matches([' ', '='])))
+        .map(|path| path.trim().to_string())
+        .filter(|path| fs::read_dir(workspace.join(path)).map_or(true, |mut entries| entries.next().is_none()))
+        .collect()
+}
+
+fn env_step(workspace: &Path) -> Option<SetupStep> {
+    if workspace.join(".env").exists() {
+        return None;
+    }
+    let template = ENV_TEMPLATES.iter().find(|name| workspace.join(name).is_file())?;
+    let mut env = step(
+        "env",
+        "env",
+        "Create .env",
+        format!("{} exists but .env does not", template),
+        SetupAction::CopyFile {
+            from: template.to_string(),
+            to: ".env".to_string(),
+        },
+    );
+    env.note = Some("Replace the placeholder values in .env with your own".to_string());
+    Some(env)
+}
+
+/// Install with the package manager the lockfile belongs to
+fn node_step(workspace: &Path) -> Option<SetupStep> {
+    if !workspace.join("package.json").is_file() {
+        return None;
+    }
+    let (lockfile, action) = [
+        ("pnpm-lock.yaml", task("pnpm", &["install"])),
+        ("yarn.lock", task("yarn", &["install"])),
+        ("bun.lockb", task("bun", &["install"])),
+        ("package-lock.json", task("npm", &["ci"])),
+    ]
+    .into_iter()
+    .find(|(lockfile, _)| workspace.join(lockfile).is_file())
+    .unwrap_or(("", task("npm", &["install"])));
+    let modules = workspace.join("node_modules");
+    let reason = if !modules.is_dir() {
+        "package.json exists but node_modules does not".to_string()
+    } else {
+        // npm, pnpm and yarn touch these on every install
+        let installed = [".package-lock.json", ".modules.yaml", ".yarn-integrity"]
+            .iter()
+            .filter_map(|marker| modified(&modules.join(marker)))
+            .max()
+            .or_else(|| modified(&modules));
+        let manifest = if lockfile.is_empty() { "package.json" } else { lockfile };
+        match (installed, modified(&workspace.join(manifest))) {
+            (Some(installed), Some(locked)) if locked > installed => {
+                format!("{} changed since node_modules was installed", manifest)
+            }
+            _ => return None,
+        }
+    };
+    Some(step("node-install", "javascript", "Install JavaScript dependencies", reason, action))
+}
+
+fn venv_dir(workspace: &Path) -> Option<&'static str> {
+    VENV_DIRS.iter().copied().find(|dir| workspace.join(dir).join("pyvenv.cfg").is_file())
+}
+
+/// A project manager's sync, or a virtual environment plus pip install
+fn python_steps(workspace: &Path) -> Vec<SetupStep> {
+    let has = |name: &str| workspace.join(name).is_file();
+    let requirements = has("requirements.txt");
+    if !requirements && !has("pyproject.toml") {
+        return Vec::new();
+    }
+    if venv_dir(workspace).is_some() {
+        return Vec::new();
+    }
+    let reason = format!("No virtual environment in {}", VENV_DIRS.join(", "));
+    if has("uv.lock") {
+        return vec![step("python-install", "python", "Sync the Python environment", reason, task("uv", &["sync"]))];
+    }
+    if has("poetry.lock") {
+        let install = task("poetry", &["install"]);
+        return vec![step("python-install", "python", "Install Python dependencies", reason, install)];
+    }
+    let dir = ".venv";
+    let python = if cfg!(windows) { "python" } else { "python3" };
+    let create = task(python, &["-m", "venv", dir]);
+    let venv = step("python-venv", "python", "Create a virtual environment", reason.clone(), create);
+    let pip = if cfg!(windows) { format!("{}\\Scripts\\pip", dir) } else { format!("{}/bin/pip", dir) };
+    let args: &[&str] = if requirements { &["install", "-r", "requirements.txt"] } else { &["install", "-e", "."] };
+    let mut install = step("python-install", "python", "Install Python dependencies", reason, task(&pip, args));
+    install.depends_on = vec![venv.id.clone()];
+    vec![venv, install]
+}
+
+fn rust_step(workspace: &Path) -> Option<SetupStep> {
+    if !workspace.join("Cargo.toml").is_file() || workspace.join("target").is_dir() {
+        return None;
+    }
+    let reason = "Cargo.toml exists but nothing was built yet".to_string();
+    let mut build = step("rust-build", "rust", "Build the Rust workspace", reason, task("cargo", &["build"]));
+    build.note = Some("The first build downloads and compiles all dependencies".to_string());
+    Some(build)
+}
+
+/// Steps the workspace still needs, in order; finished ones are left out
+pub fn suggestions(workspace: &Path) -> Vec<SetupStep> {
+    let mut steps = Vec::new();
+    let submodules = uninitialized_submodules(workspace);
+    if !submodules.is_empty() {
+        let reason = format!("Submodules not checked out: {}", submodules.join(", "));
+        let update = task("git", &["submodule", "update", "--init", "--recursive"]);
+        steps.push(step("git-submodules", "git", "Check out submodules", reason, update));
+    }
+    steps.extend(env_step(workspace));
+    steps.extend(node_step(workspace));
+    steps.extend(python_steps(workspace));
+    steps.extend(rust_step(workspace));
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggests_missing_setup_in_order() {
+        let dir = std::env::temp_dir().join(format!("mimi-onboarding-{}", crate::storage::new_id("t")));
+        fs::create_dir_all(dir.join("vendor/lib")).unwrap();
+        fs::write(dir.join(".gitmodules"), "[submodule \"lib\"]\n\tpath = vendor/lib\n").unwrap();
+        fs::write(dir.join(".env.example"), "API_KEY=\n").unwrap();
+        fs::write(dir.join("package.json"), "{}").unwrap();
+        fs::write(dir.join("pnpm-lock.yaml"), "").unwrap();
+        fs::write(dir.join("requirements.txt"), "requests\n").unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\n").unwrap();
+        fs::create_dir_all(dir.join("target")).unwrap();
+
+        let steps = suggestions(&dir);
+        let ids: Vec<&str> = steps.iter().map(|step| step.id.as_str()).collect();
+        assert_eq!(ids, [
+            "setup:git-submodules",
+            "setup:env",
+            "setup:node-install",
+            "setup:python-venv",
+            "setup:python-install"
+        ]);
+        assert!(matches!(&steps[2].action, SetupAction::Task { spec } if spec.command == "pnpm"));
+        assert_eq!(steps[4].depends_on, ["setup:python-venv"]);
+
+        fs::write(dir.join(".env"), "API_KEY=x\n").unwrap();
+        fs::create_dir_all(dir.join("node_modules")).unwrap();
+        fs::write(dir.join("node_modules/.modules.yaml"), "").unwrap();
+        let ids: Vec<String> = suggestions(&dir).into_iter().map(|step| step.id).collect();
+        assert!(!ids.contains(&"setup:env".to_string()));
+        assert!(!ids.contains(&"setup:node-install".to_string()));
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,310 @@
+// Paths - Canonical path keys for the file index and dependency graph
+// Separators, `.` and `..`, WSL mounts, Unicode forms and case-insensitive file systems all map to one key
+
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+use anyhow::{anyhow, Result};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+/// Windows path length limit without the `\\?\` prefix
+const MAX_PATH: usize = 260;
+
+/// Device names Windows reserves in every directory, with or without an extension
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1", "LPT2",
+    "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// A path as written, compared, ordered and hashed by its normalized form
+#[derive(Clone, Debug)]
+pub struct CanonicalPath {
+    path: String,
+    key: String,
+}
+
+impl CanonicalPath {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_string_lossy().to_string();
+        let key = normalize(&path, cfg!(windows) || is_wsl(), cfg!(windows) || cfg!(target_os = "macos"));
+        Self { path, key }
+    }
+
+    /// The path as it was first seen
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+}
+
+impl PartialEq for CanonicalPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for CanonicalPath {}
+
+impl Hash for CanonicalPath {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+impl PartialOrd for CanonicalPath {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CanonicalPath {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+/// Running as a Linux binary inside WSL
+fn is_wsl() -> bool {
+    static WSL: OnceLock<bool> = OnceLock::new();
+    *WSL.get_or_init(|| {
+        cfg!(target_os = "linux")
+            && std::fs::read_to_string("/proc/sys/kernel/osrelease")
+                .is_ok_and(|release| release.to_lowercase().contains("microsoft"))
+    })
+}
+
+/// Drive letter and remainder of `c:/...`
+fn drive(path: &str) -> Option<(char, &str)> {
+    let mut chars = path.chars();
+    let letter = chars.next().filter(char::is_ascii_alphabetic)?;
+    let rest = chars.as_str().strip_prefix(':')?;
+    (rest.is_empty() || rest.starts_with('/')).then_some((letter, rest))
+}
+
+/// Drive letter and remainder of a WSL mount: `/mnt/c/...`
+fn wsl_drive(path: &str) -> Option<(char, &str)> {
+    let rest = path.strip_prefix("/mnt/")?;
+    let mut chars = rest.chars();
+    let letter = chars.next().filter(char::is_ascii_alphabetic)?;
+    let rest = chars.as_str();
+    (rest.is_empty() || rest.starts_with('/')).then_some((letter, rest))
+}
+
+/// Comparison form of `path`: NFC, forward slashes, `.` and `..` resolved, no verbatim prefix or trailing slash.
+/// With `wsl`, `/mnt/c/x` and `\\wsl$\Distro\x` land on the keys of `C:\x` and `/x`.
+/// Drive paths live on a case-insensitive file system, other paths only when `case_insensitive`.
+fn normalize(path: &str, wsl: bool, case_insensitive: bool) -> String {
+    let mut path = composed(path).replace('\\', "/");
+    if let Some(rest) = path.strip_prefix("//?/UNC/") {
+        path = format!("//{}", rest);
+    } else if let Some(rest) = path.strip_prefix("//?/") {
+        path = rest.to_string();
+    }
+    if wsl {
+        for share in ["//wsl$/", "//wsl.localhost/"] {
+            if let Some(rest) = path.strip_prefix(share) {
+                path = rest.find('/').map_or("/", |distro_end| &rest[distro_end..]).to_string();
+                break;
+            }
+        }
+        if let Some((letter, rest)) = wsl_drive(&path) {
+            path = format!("{}:{}", letter, rest);
+        }
+    }
+
+    let (prefix, rest) = match drive(&path) {
+        Some((letter, rest)) => (format!("{}:/", letter.to_ascii_lowercase()), rest),
+        None if path.starts_with("//") => ("//".to_string(), &path[2..]),
+        None if path.starts_with('/') => ("/".to_string(), &path[1..]),
+        None => (String::new(), path.as_str()),
+    };
+    let mut parts: Vec<&str> = Vec::new();
+    for part in rest.split('/') {
+        match part {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|last| *last != "..") => {
+                parts.pop();
+            }
+            // Above the root of an absolute path is the root itself
+            ".." if !prefix.is_empty() => {}
+            part => parts.push(part),
+        }
+    }
+    let key = prefix.clone() + &parts.join("/");
+    if case_insensitive || prefix.ends_with(":/") {
+        key.to_lowercase()
+    } else {
+        key
+    }
+}
+
+/// NFC form of `text`; macOS reports decomposed names (`e` + U+0301) where editors and git use composed ones
+pub fn composed(text: &str) -> String {
+    if is_nfc(text) {
+        text.to_string()
+    } else {
+        text.nfc().collect()
+    }
+}
+
+/// Windows reserves device names like `CON` or `nul.txt` and names ending in a dot or space
+fn is_reserved(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    RESERVED_NAMES.iter().any(|reserved| stem.eq_ignore_ascii_case(reserved))
+        || (name.ends_with('.') && name != "." && name != "..")
+        || name.ends_with(' ')
+}
+
+/// A name for a new file or directory that every platform can check out
+pub fn check_new_name(name: &str) -> Result<()> {
+    if let Some(c) = name.chars().find(|c| matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*') || c.is_control()) {
+        return Err(anyhow!("{:?} contains {:?}, which Windows does not allow in file names", name, c));
+    }
+    if is_reserved(name) {
+        return Err(anyhow!("{:?} is a reserved file name on Windows", name));
+    }
+    Ok(())
+}
+
+/// Verbatim (`\\?\`) form of an absolute Windows path, which lifts MAX_PATH and device name parsing
+fn verbatim(path: &str) -> Option<String> {
+    let path = path.replace('\\', "/");
+    if path.starts_with("//?/") {
+        return None;
+    }
+    // Windows does not resolve `.` and `..` in verbatim paths
+    let resolved = |rest: &str| {
+        let mut parts: Vec<&str> = Vec::new();
+        for part in rest.split('/') {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    parts.pop();
+                }
+                part => parts.push(part),
+            }
+        }
+        parts.join("\\")
+    };
+    if let Some(share) = path.strip_prefix("//") {
+        return Some(format!(r"\\?\UNC\{}", resolved(share)));
+    }
+    let (letter, rest) = drive(&path)?;
+    Some(format!(r"\\?\{}:\{}", letter.to_ascii_uppercase(), resolved(rest)))
+}
+
+/// `path` as passed to file system calls: verbatim on Windows when it is too long for MAX_PATH
+/// or names a reserved device like `con.ts`, unchanged elsewhere
+pub fn for_io(path: &Path) -> PathBuf {
+    if !cfg!(windows) {
+        return path.to_path_buf();
+    }
+    let text = path.to_string_lossy();
+    let reserved = path
+        .components()
+        .any(|c| matches!(c, Component::Normal(name) if is_reserved(&name.to_string_lossy())));
+    if text.len() < MAX_PATH && !reserved {
+        return path.to_path_buf();
+    }
+    verbatim(&text).map(PathBuf::from).unwrap_or_else(|| path.to_path_buf())
+}
+
+/// `to` names the same path as `from` in different letter case
+pub fn is_case_change(from: &Path, to: &Path) -> bool {
+    from != to && from.to_string_lossy().to_lowercase() == to.to_string_lossy().to_lowercase()
+}
+
+/// `path` exists under exactly this spelling, not only under a differently-cased one
+pub fn exists_exactly(path: &Path) -> bool {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return path.exists();
+    };
+    std::fs::read_dir(parent).is_ok_and(|mut entries| entries.any(|e| e.is_ok_and(|e| e.file_name() == name)))
+}
+
+/// Rename through a temporary name when only the case changes, which some case-insensitive file systems ignore
+pub fn rename(from: &Path, to: &Path) -> std::io::Result<()> {
+    if !is_case_change(from, to) {
+        return std::fs::rename(from, to);
+    }
+    let name = from.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let temp = from.with_file_name(format!(".{}.case-{}", name, std::process::id()));
+    std::fs::rename(from, &temp)?;
+    std::fs::rename(&temp, to).inspect_err(|_| {
+        let _ = std::fs::rename(&temp, from);
+    })
+}
+
+/// `C:\repo\a.ts` as seen from WSL: `/mnt/c/repo/a.ts`
+fn to_wsl(path: &str) -> Option<String> {
+    let path = path.replace('\\', "/");
+    let (letter, rest) = drive(&path)?;
+    Some(format!("/mnt/{}{}", letter.to_ascii_lowercase(), rest))
+}
+
+/// `/mnt/c/repo/a.ts` as seen from Windows: `C:\repo\a.ts`
+fn from_wsl(path: &str) -> Option<String> {
+    let (letter, rest) = wsl_drive(path)?;
+    let rest = if rest.is_empty() { "/" } else { rest };
+    Some(format!("{}:{}", letter.to_ascii_uppercase(), rest.replace('/', "\\")))
+}
+
+/// A path from the other side of the WSL boundary, e.g. in tool output or from the frontend, as a native path
+pub fn native(path: &str) -> PathBuf {
+    let translated = if cfg!(windows) {
+        from_wsl(path)
+    } else if is_wsl() {
+        to_wsl(path)
+    } else {
+        None
+    };
+    translated.map(PathBuf::from).unwrap_or_else(|| PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_wsl_and_separators() {
+        let windows = normalize("C:\\Repo\\src\\.\\lib\\..\\a.ts", true, false);
+        assert_eq!(windows, "c:/repo/src/a.ts");
+        assert_eq!(normalize("/mnt/c/repo/src/a.ts", true, false), windows);
+        assert_eq!(normalize("\\\\wsl$\\Ubuntu\\home\\dev\\x.rs", true, false), "/home/dev/x.rs");
+        assert_eq!(normalize("/mnt/c/repo/src/a.ts", false, false), "/mnt/c/repo/src/a.ts");
+        assert_eq!(normalize("/Users/dev/App/", false, false), "/Users/dev/App");
+        assert_eq!(normalize("/Users/dev/App/", false, true), "/users/dev/app");
+        assert_eq!(normalize("../shared/./x.ts", false, false), "../shared/x.ts");
+        assert_eq!(normalize("/../etc", false, false), "/etc");
+    }
+
+    #[test]
+    fn test_wsl_translation() {
+        assert_eq!(to_wsl("C:\\repo\\a.ts").as_deref(), Some("/mnt/c/repo/a.ts"));
+        assert_eq!(from_wsl("/mnt/d/work/b.rs").as_deref(), Some("D:\\work\\b.rs"));
+        assert_eq!(from_wsl("/mnt/c").as_deref(), Some("C:\\"));
+        assert_eq!(from_wsl("/mnt/data/b.rs"), None);
+        assert_eq!(to_wsl("/home/dev"), None);
+        assert_eq!(CanonicalPath::new("/repo/a.ts"), CanonicalPath::new("/repo//a.ts"));
+    }
+
+    #[test]
+    fn test_long_reserved_and_decomposed_paths() {
+        let long = format!("C:\\repo\\{}a.ts", "nested-directory\\".repeat(20));
+        assert!(long.len() > MAX_PATH);
+        let extended = verbatim(&long).unwrap();
+        assert!(extended.starts_with("\\\\?\\C:\\repo\\nested-directory\\"));
+        assert_eq!(normalize(&extended, false, false), normalize(&long, false, false));
+        assert_eq!(verbatim("\\\\server\\share\\x\\..\\y").as_deref(), Some("\\\\?\\UNC\\server\\share\\y"));
+        assert_eq!(verbatim(&extended), None);
+
+        for name in ["con", "CON.ts", "nul.txt", "Lpt1", "file.", "dir "] {
+            assert!(check_new_name(name).is_err(), "{}", name);
+        }
+        for name in ["console.ts", ".gitignore", "aux-config.json"] {
+            assert!(check_new_name(name).is_ok(), "{}", name);
+        }
+        assert!(check_new_name("a:b.ts").is_err());
+        assert_eq!(normalize("/repo/cafe\u{301}.md", false, false), normalize("/repo/caf\u{e9}.md", false, false));
+    }
+}
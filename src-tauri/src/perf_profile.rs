@@ -0,0 +1,515 @@
+// Performance Profiles - Imported flamegraph and pprof data
+// Frames are resolved to workspace files so hot lines can be shown inline
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+
+use crate::mimi_engine::CodeGraph;
+use crate::storage;
+
+/// Functions listed in a profile summary
+const TOP_FUNCTIONS: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileFormat {
+    /// `outer;inner;leaf 42` lines, as produced by inferno, py-spy or stackcollapse-*
+    Collapsed,
+    /// pprof protobuf, optionally gzipped
+    Pprof,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub name: String,
+    /// Absolute path of a workspace file, when the frame could be resolved
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+/// A loaded profile: deduplicated frames and leaf-last stacks with their values
+pub struct Profile {
+    pub id: String,
+    pub path: String,
+    pub format: ProfileFormat,
+    pub unit: String,
+    frames: Vec<Frame>,
+    stacks: Vec<(Vec<usize>, u64)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FunctionCost {
+    pub frame: Frame,
+    /// Value of samples where this frame is the leaf
+    pub self_value: u64,
+    /// Value of samples where this frame is on the stack
+    pub total_value: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProfileSummary {
+    pub id: String,
+    pub path: String,
+    pub format: ProfileFormat,
+    pub unit: String,
+    pub total: u64,
+    pub samples: usize,
+    pub frames: usize,
+    pub resolved_frames: usize,
+    pub top: Vec<FunctionCost>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Hotspot {
+    pub line: u32,
+    pub function: String,
+    pub self_value: u64,
+    pub total_value: u64,
+    pub self_percent: f64,
+    pub total_percent: f64,
+}
+
+/// Interns frames by name and location
+#[derive(Default)]
+struct FrameTable {
+    frames: Vec<Frame>,
+    index: HashMap<(String, Option<String>, Option<u32>), usize>,
+}
+
+impl FrameTable {
+    fn intern(&mut self, frame: Frame) -> usize {
+        let key = (frame.name.clone(), frame.file.clone(), frame.line);
+        if let Some(&index) = self.index.get(&key) {
+            return index;
+        }
+        self.frames.push(frame);
+        self.index.insert(key, self.frames.len() - 1);
+        self.frames.len() - 1
+    }
+}
+
+/// Map a profiler's file name to a workspace file
+fn resolve_file(workspace: &Path, file: &str) -> Option<String> {
+    let path = Path::new(file);
+    let candidate = if path.is_absolute() { path.to_path_buf() } else { workspace.join(path) };
+    (candidate.starts_with(workspace) && candidate.is_file()).then(|| candidate.to_string_lossy().to_string())
+}
+
+/// Resolve a frame with a location, or else by looking its function up in the symbol table
+fn resolve_frame(workspace: &Path, graph: &CodeGraph, name: &str, file: Option<&str>, line: Option<u32>) -> Frame {
+    if let Some(file) = file.and_then(|f| resolve_file(workspace, f)) {
+        return Frame {
+            name: name.to_string(),
+            file: Some(file),
+            line,
+        };
+    }
+    // `crate::module::func::{{closure}}` and `Class.method` style names
+    let cleaned = name.split("::{{").next().unwrap_or(name).trim_end_matches("()");
+    let symbol = graph
+        .find_symbol(cleaned)
+        .into_iter()
+        .next()
+        .or_else(|| graph.find_symbol(cleaned.rsplit([':', '.']).next().unwrap_or(cleaned)).into_iter().next());
+    Frame {
+        name: name.to_string(),
+        file: symbol.map(|s| s.file.clone()),
+        line: symbol.map(|s| s.line as u32),
+    }
+}
+
+/// `name (file:line)` (py-spy), `file:line`, or a bare function name
+fn split_collapsed_frame(frame: &str) -> (&str, Option<&str>, Option<u32>) {
+    let (name, location) = match frame.strip_suffix(')').and_then(|f| f.rsplit_once(" (")) {
+        Some((name, location)) => (name, Some(location)),
+        None => (frame, None),
+    };
+    let location = location.or_else(|| (frame.contains('/') || frame.contains('\\')).then_some(frame));
+    match location.and_then(|l| l.rsplit_once(':')) {
+        Some((file, line)) if line.chars().all(|c: char| c.is_ascii_digit()) && !line.is_empty() => {
+            (name, Some(file), line.parse().ok())
+        }
+        _ => (name, location, None),
+    }
+}
+
+fn parse_collapsed(workspace: &Path, graph: &CodeGraph, text: &str) -> (FrameTable, Vec<(Vec<usize>, u64)>) {
+    let mut table = FrameTable::default();
+    let mut cache: HashMap<String, usize> = HashMap::new();
+    let mut stacks = Vec::new();
+    for line in text.lines() {
+        let (stack, count) = match line.trim_end().rsplit_once(' ') {
+            Some((stack, count)) => match count.parse::<u64>() {
+                Ok(count) => (stack, count),
+                Err(_) => continue,
+            },
+            None => continue,
+        };
+        let frames = stack
+            .split(';')
+            .filter(|f| !f.is_empty())
+            .map(|frame| match cache.get(frame) {
+                Some(&index) => index,
+                None => {
+                    let (name, file, line) = split_collapsed_frame(frame);
+                    let index = table.intern(resolve_frame(workspace, graph, name, file, line));
+                    cache.insert(frame.to_string(), index);
+                    index
+                }
+            })
+            .collect();
+        stacks.push((frames, count));
+    }
+    (table, stacks)
+}
+
+// ==================== PPROF ====================
+
+enum Wire<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Just enough protobuf decoding for the pprof schema
+struct ProtoReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ProtoReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.buf.get(self.pos).ok_or_else(|| anyhow!("Truncated varint"))?;
+            self.pos += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(anyhow!("Varint too long"))
+    }
+
+    fn skip(&mut self, n: usize) -> Result<()> {
+        if self.pos + n > self.buf.len() {
+            return Err(anyhow!("Truncated field"));
+        }
+        self.pos += n;
+        Ok(())
+    }
+
+    fn field(&mut self) -> Result<Option<(u64, Wire<'a>)>> {
+        if self.pos >= self.buf.len() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        let wire = match key & 7 {
+            0 => Wire::Varint(self.varint()?),
+            1 => {
+                self.skip(8)?;
+                Wire::Fixed
+            }
+            2 => {
+                let len = self.varint()? as usize;
+                let start = self.pos;
+                self.skip(len)?;
+                Wire::Bytes(&self.buf[start..start + len])
+            }
+            5 => {
+                self.skip(4)?;
+                Wire::Fixed
+            }
+            other => return Err(anyhow!("Unsupported wire type {}", other)),
+        };
+        Ok(Some((key >> 3, wire)))
+    }
+}
+
+/// Repeated integer field, packed or not
+fn push_varints(wire: Wire, out: &mut Vec<u64>) -> Result<()> {
+    match wire {
+        Wire::Varint(value) => out.push(value),
+        Wire::Bytes(bytes) => {
+            let mut reader = ProtoReader::new(bytes);
+            while reader.pos < bytes.len() {
+                out.push(reader.varint()?);
+            }
+        }
+        Wire::Fixed => {}
+    }
+    Ok(())
+}
+
+fn varint_fields(bytes: &[u8]) -> Result<HashMap<u64, Vec<u64>>> {
+    let mut fields: HashMap<u64, Vec<u64>> = HashMap::new();
+    let mut reader = ProtoReader::new(bytes);
+    while let Some((number, wire)) = reader.field()? {
+        push_varints(wire, fields.entry(number).or_default())?;
+    }
+    Ok(fields)
+}
+
+fn first(fields: &HashMap<u64, Vec<u64>>, number: u64) -> u64 {
+    fields.get(&number).and_then(|v| v.first()).copied().unwrap_or(0)
+}
+
+struct PprofFunction {
+    name: u64,
+    filename: u64,
+}
+
+fn gunzip(data: &[u8]) -> Result<Vec<u8>> {
+    let mut decoded = Vec::new();
+    GzDecoder::new(data)
+        .read_to_end(&mut decoded)
+        .map_err(|e| anyhow!("Invalid gzipped profile: {}", e))?;
+    Ok(decoded)
+}
+
+fn parse_pprof(workspace: &Path, graph: &CodeGraph, data: &[u8]) -> Result<(FrameTable, Vec<(Vec<usize>, u64)>, String)> {
+    let data = if data.starts_with(&[0x1f, 0x8b]) { gunzip(data)? } else { data.to_vec() };
+
+    let mut strings: Vec<String> = Vec::new();
+    let mut sample_types: Vec<(u64, u64)> = Vec::new();
+    let mut samples: Vec<(Vec<u64>, Vec<u64>)> = Vec::new();
+    let mut locations: HashMap<u64, Vec<(u64, u64)>> = HashMap::new();
+    let mut functions: HashMap<u64, PprofFunction> = HashMap::new();
+    let mut default_sample_type = 0;
+
+    let mut reader = ProtoReader::new(&data);
+    while let Some((number, wire)) = reader.field()? {
+        match (number, wire) {
+            (1, Wire::Bytes(bytes)) => {
+                let fields = varint_fields(bytes)?;
+                sample_types.push((first(&fields, 1), first(&fields, 2)));
+            }
+            (2, Wire::Bytes(bytes)) => {
+                let fields = varint_fields(bytes)?;
+                samples.push((
+                    fields.get(&1).cloned().unwrap_or_default(),
+                    fields.get(&2).cloned().unwrap_or_default(),
+                ));
+            }
+            (4, Wire::Bytes(bytes)) => {
+                let mut id = 0;
+                let mut lines = Vec::new();
+                let mut location = ProtoReader::new(bytes);
+                while let Some((number, wire)) = location.field()? {
+                    match (number, wire) {
+                        (1, Wire::Varint(value)) => id = value,
+                        (4, Wire::Bytes(line)) => {
+                            let fields = varint_fields(line)?;
+                            lines.push((first(&fields, 1), first(&fields, 2)));
+                        }
+                        _ => {}
+                    }
+                }
+                locations.insert(id, lines);
+            }
+            (5, Wire::Bytes(bytes)) => {
+                let fields = varint_fields(bytes)?;
+                functions.insert(
+                    first(&fields, 1),
+                    PprofFunction {
+                        name: first(&fields, 2),
+                        filename: first(&fields, 4),
+                    },
+                );
+            }
+            (6, Wire::Bytes(bytes)) => strings.push(String::from_utf8_lossy(bytes).to_string()),
+            (14, Wire::Varint(value)) => default_sample_type = value,
+            _ => {}
+        }
+    }
+
+    let string = |index: u64| strings.get(index as usize).map(String::as_str).unwrap_or("");
+    // The default sample type, else the last one (e.g. cpu/nanoseconds after samples/count)
+    let value_index = sample_types
+        .iter()
+        .position(|(kind, _)| default_sample_type != 0 && *kind == default_sample_type)
+        .unwrap_or(sample_types.len().saturating_sub(1));
+    let unit = sample_types
+        .get(value_index)
+        .map(|(kind, unit)| format!("{}/{}", string(*kind), string(*unit)))
+        .unwrap_or_default();
+
+    let mut table = FrameTable::default();
+    let mut location_frames: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (id, lines) in &locations {
+        // Inlined callees come first in `lines`; stacks are stored leaf-last
+        let frames = lines
+            .iter()
+            .rev()
+            .map(|(function_id, line)| {
+                let function = functions.get(function_id);
+                let name = function.map(|f| string(f.name)).unwrap_or("??");
+                let file = function.map(|f| string(f.filename)).filter(|f| !f.is_empty());
+                let line = (*line > 0).then_some(*line as u32);
+                table.intern(resolve_frame(workspace, graph, name, file, line))
+            })
+            .collect();
+        location_frames.insert(*id, frames);
+    }
+
+    let stacks = samples
+        .into_iter()
+        .filter_map(|(location_ids, values)| {
+            let value = *values.get(value_index)?;
+            // Sample locations are leaf-first
+            let frames = location_ids
+                .iter()
+                .rev()
+                .flat_map(|id| location_frames.get(id).cloned().unwrap_or_default())
+                .collect();
+            Some((frames, value))
+        })
+        .collect();
+    Ok((table, stacks, unit))
+}
+
+impl Profile {
+    pub fn load(workspace: &Path, graph: &CodeGraph, path: &Path, format: ProfileFormat) -> Result<Profile> {
+        let data = fs::read(path)?;
+        let (table, stacks, unit) = match format {
+            ProfileFormat::Collapsed => {
+                let (table, stacks) = parse_collapsed(workspace, graph, &String::from_utf8_lossy(&data));
+                (table, stacks, "samples".to_string())
+            }
+            ProfileFormat::Pprof => parse_pprof(workspace, graph, &data)?,
+        };
+        if stacks.is_empty() {
+            return Err(anyhow!("No samples found in {}", path.display()));
+        }
+        Ok(Profile {
+            id: storage::new_id("profile"),
+            path: path.to_string_lossy().to_string(),
+            format,
+            unit,
+            frames: table.frames,
+            stacks,
+        })
+    }
+
+    fn total(&self) -> u64 {
+        self.stacks.iter().map(|(_, value)| value).sum()
+    }
+
+    /// Self and total value per frame; recursive frames count once per sample
+    fn costs(&self) -> Vec<(u64, u64)> {
+        let mut costs = vec![(0u64, 0u64); self.frames.len()];
+        for (frames, value) in &self.stacks {
+            if let Some(&leaf) = frames.last() {
+                costs[leaf].0 += value;
+            }
+            let unique: HashSet<usize> = frames.iter().copied().collect();
+            for frame in unique {
+                costs[frame].1 += value;
+            }
+        }
+        costs
+    }
+
+    pub fn summary(&self) -> ProfileSummary {
+        let costs = self.costs();
+        let mut top: Vec<FunctionCost> = self
+            .frames
+            .iter()
+            .zip(&costs)
+            .map(|(frame, (self_value, total_value))| FunctionCost {
+                frame: frame.clone(),
+                self_value: *self_value,
+                total_value: *total_value,
+            })
+            .collect();
+        top.sort_by(|a, b| b.self_value.cmp(&a.self_value).then(b.total_value.cmp(&a.total_value)));
+        top.truncate(TOP_FUNCTIONS);
+        ProfileSummary {
+            id: self.id.clone(),
+            path: self.path.clone(),
+            format: self.format,
+            unit: self.unit.clone(),
+            total: self.total(),
+            samples: self.stacks.len(),
+            frames: self.frames.len(),
+            resolved_frames: self.frames.iter().filter(|f| f.file.is_some()).count(),
+            top,
+        }
+    }
+
+    /// Cost per line of `file`, hottest first
+    pub fn file_hotspots(&self, file: &str) -> Vec<Hotspot> {
+        let total = self.total().max(1) as f64;
+        let mut lines: HashMap<u32, (String, u64, u64)> = HashMap::new();
+        for (frames, value) in &self.stacks {
+            let mut seen = HashSet::new();
+            for (position, &index) in frames.iter().enumerate() {
+                let frame = &self.frames[index];
+                let line = match (frame.file.as_deref(), frame.line) {
+                    (Some(f), Some(line)) if f == file => line,
+                    _ => continue,
+                };
+                let entry = lines.entry(line).or_insert_with(|| (frame.name.clone(), 0, 0));
+                if position == frames.len() - 1 {
+                    entry.1 += value;
+                }
+                if seen.insert(line) {
+                    entry.2 += value;
+                }
+            }
+        }
+        let mut hotspots: Vec<Hotspot> = lines
+            .into_iter()
+            .map(|(line, (function, self_value, total_value))| Hotspot {
+                line,
+                function,
+                self_value,
+                total_value,
+                self_percent: self_value as f64 * 100.0 / total,
+                total_percent: total_value as f64 * 100.0 / total,
+            })
+            .collect();
+        hotspots.sort_by(|a, b| b.total_value.cmp(&a.total_value).then(a.line.cmp(&b.line)));
+        hotspots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collapsed_hotspots() {
+        let workspace = std::env::temp_dir().join(storage::new_id("perf-test"));
+        fs::create_dir_all(&workspace).unwrap();
+        fs::write(workspace.join("app.py"), "def main():\n    work()\n").unwrap();
+        let collapsed = "<module> (app.py:10);main (app.py:2);work (app.py:5) 30\n<module> (app.py:10);main (app.py:2) 10\nbroken line\n";
+        let (table, stacks) = parse_collapsed(&workspace, &CodeGraph::new(), collapsed);
+        let profile = Profile {
+            id: "p".to_string(),
+            path: "profile.txt".to_string(),
+            format: ProfileFormat::Collapsed,
+            unit: "samples".to_string(),
+            frames: table.frames,
+            stacks,
+        };
+
+        let file = workspace.join("app.py").to_string_lossy().to_string();
+        let hotspots = profile.file_hotspots(&file);
+        assert_eq!(hotspots[0].line, 2);
+        assert_eq!((hotspots[0].self_value, hotspots[0].total_value), (10, 40));
+        let work = hotspots.iter().find(|h| h.line == 5).unwrap();
+        assert_eq!((work.function.as_str(), work.self_percent), ("work", 75.0));
+        assert_eq!(profile.summary().resolved_frames, 3);
+        let _ = fs::remove_dir_all(&workspace);
+    }
+}
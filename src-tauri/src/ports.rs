@@ -0,0 +1,283 @@
+// Ports - Listening TCP sockets and dev-server URL detection
+// Reads /proc on Linux, lsof on other Unixes and netstat on Windows
+
+use std::collections::{HashMap, HashSet};
+use std::process::Command;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ListeningPort {
+    pub port: u16,
+    pub address: String,
+    pub pid: Option<u32>,
+    pub process: Option<String>,
+}
+
+/// All listening TCP ports, one entry per port and process, sorted by port
+pub fn list_listening_ports() -> Result<Vec<ListeningPort>> {
+    let mut ports = platform_ports()?;
+    ports.sort_by(|a, b| (a.port, a.pid).cmp(&(b.port, b.pid)));
+    ports.dedup_by(|a, b| a.port == b.port && a.pid == b.pid);
+    Ok(ports)
+}
+
+#[cfg(target_os = "linux")]
+fn platform_ports() -> Result<Vec<ListeningPort>> {
+    let mut sockets = Vec::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        if let Ok(content) = std::fs::read_to_string(table) {
+            sockets.extend(content.lines().skip(1).filter_map(parse_proc_net_line));
+        }
+    }
+    let owners = socket_owners();
+    Ok(sockets
+        .into_iter()
+        .map(|(address, port, inode)| {
+            let pid = owners.get(&inode).copied();
+            ListeningPort {
+                port,
+                address,
+                pid,
+                process: pid.and_then(|pid| {
+                    std::fs::read_to_string(format!("/proc/{}/comm", pid))
+                        .ok()
+                        .map(|name| name.trim().to_string())
+                }),
+            }
+        })
+        .collect())
+}
+
+/// `(address, port, inode)` of a LISTEN row of /proc/net/tcp{,6}
+#[cfg(target_os = "linux")]
+fn parse_proc_net_line(line: &str) -> Option<(String, u16, u64)> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 10 || fields[3] != "0A" {
+        return None;
+    }
+    let (address, port) = fields[1].split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    Some((decode_proc_address(address)?, port, fields[9].parse().ok()?))
+}
+
+/// Kernel addresses are hex 32-bit words in host (little-endian) byte order
+#[cfg(target_os = "linux")]
+fn decode_proc_address(hex_address: &str) -> Option<String> {
+    let bytes = hex::decode(hex_address).ok()?;
+    let mut ordered = Vec::with_capacity(bytes.len());
+    for word in bytes.chunks(4) {
+        ordered.extend(word.iter().rev());
+    }
+    match ordered.len() {
+        4 => Some(std::net::Ipv4Addr::new(ordered[0], ordered[1], ordered[2], ordered[3]).to_string()),
+        16 => {
+            let octets: [u8; 16] = ordered.try_into().ok()?;
+            Some(std::net::Ipv6Addr::from(octets).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Socket inode -> pid, for the processes we are allowed to inspect
+#[cfg(target_os = "linux")]
+fn socket_owners() -> HashMap<u64, u32> {
+    let mut owners = HashMap::new();
+    let entries = match std::fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(_) => return owners,
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let pid: u32 = match entry.file_name().to_string_lossy().parse() {
+            Ok(pid) => pid,
+            Err(_) => continue,
+        };
+        let fds = match std::fs::read_dir(entry.path().join("fd")) {
+            Ok(fds) => fds,
+            Err(_) => continue,
+        };
+        for fd in fds.filter_map(|e| e.ok()) {
+            let target = match std::fs::read_link(fd.path()) {
+                Ok(target) => target.to_string_lossy().to_string(),
+                Err(_) => continue,
+            };
+            if let Some(inode) = target.strip_prefix("socket:[").and_then(|t| t.strip_suffix(']')) {
+                if let Ok(inode) = inode.parse() {
+                    owners.insert(inode, pid);
+                }
+            }
+        }
+    }
+    owners
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn platform_ports() -> Result<Vec<ListeningPort>> {
+    let output = Command::new("lsof").args(["-nP", "-iTCP", "-sTCP:LISTEN", "-F", "pcn"]).output()?;
+    if !output.status.success() && output.stdout.is_empty() {
+        return Err(anyhow::anyhow!("lsof failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    // Field output: `p<pid>` and `c<command>` start a process, `n<address:port>` per socket
+    let mut ports = Vec::new();
+    let (mut pid, mut process) = (None, None);
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut chars = line.chars();
+        let tag = chars.next();
+        let value = chars.as_str();
+        match tag {
+            Some('p') => pid = value.parse().ok(),
+            Some('c') => process = Some(value.to_string()),
+            Some('n') => {
+                if let Some((address, port)) = split_address(value) {
+                    ports.push(ListeningPort {
+                        port,
+                        address,
+                        pid,
+                        process: process.clone(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(ports)
+}
+
+#[cfg(windows)]
+fn platform_ports() -> Result<Vec<ListeningPort>> {
+    let output = Command::new("netstat").args(["-ano", "-p", "TCP"]).output()?;
+    let ipv6 = Command::new("netstat").args(["-ano", "-p", "TCPv6"]).output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("netstat failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string() + &String::from_utf8_lossy(&ipv6.stdout);
+    // `TCP    0.0.0.0:135    0.0.0.0:0    LISTENING    1234`
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 5 || fields[3] != "LISTENING" {
+                return None;
+            }
+            let (address, port) = split_address(fields[1])?;
+            Some(ListeningPort {
+                port,
+                address,
+                pid: fields[4].parse().ok(),
+                process: None,
+            })
+        })
+        .collect())
+}
+
+/// `host:port`, `[::1]:port` or `*:port`
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+fn split_address(value: &str) -> Option<(String, u16)> {
+    let (host, port) = value.rsplit_once(':')?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    Some((if host == "*" { "0.0.0.0" } else { host }.to_string(), port.parse().ok()?))
+}
+
+/// `pid` and all its descendants; dev servers usually run under a package-manager wrapper
+pub fn process_tree(pid: u32) -> HashSet<u32> {
+    let mut tree = HashSet::from([pid]);
+    let children = child_map();
+    let mut stack = vec![pid];
+    while let Some(parent) = stack.pop() {
+        for child in children.get(&parent).into_iter().flatten() {
+            if tree.insert(*child) {
+                stack.push(*child);
+            }
+        }
+    }
+    tree
+}
+
+#[cfg(unix)]
+fn child_map() -> HashMap<u32, Vec<u32>> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    let output = match Command::new("ps").args(["-A", "-o", "pid=", "-o", "ppid="]).output() {
+        Ok(output) => output,
+        Err(_) => return children,
+    };
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut fields = line.split_whitespace().filter_map(|f| f.parse::<u32>().ok());
+        if let (Some(pid), Some(ppid)) = (fields.next(), fields.next()) {
+            children.entry(ppid).or_default().push(pid);
+        }
+    }
+    children
+}
+
+#[cfg(windows)]
+fn child_map() -> HashMap<u32, Vec<u32>> {
+    HashMap::new()
+}
+
+fn strip_ansi(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // CSI sequences end at the first letter
+            for next in chars.by_ref() {
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Local URLs printed by dev servers ("Local: http://localhost:5173/"), wildcard hosts as localhost
+pub fn detect_urls(line: &str) -> Vec<String> {
+    let line = strip_ansi(line);
+    let mut urls = Vec::new();
+    let mut rest = line.as_str();
+    while let Some(start) = [rest.find("http://"), rest.find("https://")].into_iter().flatten().min() {
+        let candidate = &rest[start..];
+        let end = candidate
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | ')' | '>' | '<' | ','))
+            .unwrap_or(candidate.len());
+        let url = candidate[..end].trim_end_matches(['.', ';', ':']);
+        rest = &candidate[end..];
+
+        let (scheme, after) = url.split_once("://").unwrap_or(("http", url));
+        let authority = after.split('/').next().unwrap_or("");
+        let host = match authority.rsplit_once(':') {
+            Some((host, port)) if port.chars().all(|c: char| c.is_ascii_digit()) => host,
+            _ => authority,
+        };
+        let local = match host {
+            "localhost" | "127.0.0.1" | "[::1]" => url.to_string(),
+            "0.0.0.0" | "[::]" => format!("{}://localhost{}", scheme, &after[host.len()..]),
+            _ => continue,
+        };
+        if !urls.contains(&local) {
+            urls.push(local);
+        }
+    }
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_urls() {
+        assert_eq!(detect_urls("  \u{1b}[32m➜\u{1b}[39m  Local:   \u{1b}[36mhttp://localhost:\u{1b}[1m5173\u{1b}[22m/\u{1b}[39m"), vec!["http://localhost:5173/"]);
+        assert_eq!(detect_urls("- Network: http://192.168.1.4:3000, ready on http://0.0.0.0:3000."), vec!["http://localhost:3000"]);
+        assert!(detect_urls("see https://nextjs.org/docs").is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_proc_net_line() {
+        let line = "   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 12345 1 0000000000000000 100 0 0 10 0";
+        assert_eq!(parse_proc_net_line(line), Some(("127.0.0.1".to_string(), 8080, 12345)));
+    }
+}
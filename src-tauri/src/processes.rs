@@ -0,0 +1,261 @@
+// Background Processes - Long-running workspace tasks such as dev servers
+// Output is kept in a ring buffer and scanned for local URLs as it arrives
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ports::{self, ListeningPort};
+use crate::storage;
+use crate::task_runner::{self, TaskSpec};
+use crate::trust::TrustStore;
+
+/// Output lines kept per process
+const MAX_OUTPUT_LINES: usize = 1000;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProcessInfo {
+    pub id: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub pid: u32,
+    pub started_at: u64,
+    pub running: bool,
+    pub exit_code: Option<i32>,
+    /// Local URLs the process printed, in order of appearance
+    pub urls: Vec<String>,
+}
+
+/// A running process that serves HTTP
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DevServer {
+    pub process: ProcessInfo,
+    /// Listening ports of the process and its descendants
+    pub ports: Vec<ListeningPort>,
+    /// Best URL to open: the first printed one, else the first listening port
+    pub url: Option<String>,
+}
+
+struct Entry {
+    info: ProcessInfo,
+    child: Child,
+    output: VecDeque<String>,
+}
+
+/// Called with `(process id, url)` the first time a process prints a URL
+pub type UrlCallback = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+pub struct ProcessManager {
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+}
+
+impl ProcessManager {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Spawn `spec` in the background, unless the workspace is restricted; stdout and stderr are captured line
+    /// by line
+    pub fn start(
+        &self,
+        spec: &TaskSpec,
+        workspace: &Path,
+        trust: &TrustStore,
+        on_url: UrlCallback,
+    ) -> Result<ProcessInfo> {
+        trust.ensure(workspace, "background processes")?;
+        log::info!("Starting background process: {} {}", spec.command, spec.args.join(" "));
+        let mut cmd = task_runner::command(spec, workspace);
+        cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        // Own process group, so stopping also reaches the server behind `npm run dev`
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        let mut child = cmd.spawn()?;
+
+        let info = ProcessInfo {
+            id: storage::new_id("proc"),
+            command: spec.command.clone(),
+            args: spec.args.clone(),
+            pid: child.id(),
+            started_at: storage::now_millis(),
+            running: true,
+            exit_code: None,
+            urls: Vec::new(),
+        };
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        self.entries.lock().unwrap().insert(
+            info.id.clone(),
+            Entry {
+                info: info.clone(),
+                child,
+                output: VecDeque::new(),
+            },
+        );
+        if let Some(stdout) = stdout {
+            self.capture(&info.id, stdout, on_url.clone());
+        }
+        if let Some(stderr) = stderr {
+            self.capture(&info.id, stderr, on_url);
+        }
+        Ok(info)
+    }
+
+    fn capture(&self, id: &str, stream: impl Read + Send + 'static, on_url: UrlCallback) {
+        let entries = Arc::clone(&self.entries);
+        let id = id.to_string();
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                let mut new_urls = Vec::new();
+                {
+                    let mut entries = entries.lock().unwrap();
+                    let entry = match entries.get_mut(&id) {
+                        Some(entry) => entry,
+                        None => break,
+                    };
+                    for url in ports::detect_urls(&line) {
+                        if !entry.info.urls.contains(&url) {
+                            entry.info.urls.push(url.clone());
+                            new_urls.push(url);
+                        }
+                    }
+                    if entry.output.len() >= MAX_OUTPUT_LINES {
+                        entry.output.pop_front();
+                    }
+                    entry.output.push_back(line);
+                }
+                for url in new_urls {
+                    on_url(&id, &url);
+                }
+            }
+        });
+    }
+
+    /// Update exit status of finished processes
+    fn refresh(entries: &mut HashMap<String, Entry>) {
+        for entry in entries.values_mut().filter(|e| e.info.running) {
+            if let Ok(Some(status)) = entry.child.try_wait() {
+                entry.info.running = false;
+                entry.info.exit_code = status.code();
+            }
+        }
+    }
+
+    pub fn list(&self) -> Vec<ProcessInfo> {
+        let mut entries = self.entries.lock().unwrap();
+        Self::refresh(&mut entries);
+        let mut processes: Vec<ProcessInfo> = entries.values().map(|e| e.info.clone()).collect();
+        processes.sort_by(|a, b| a.started_at.cmp(&b.started_at).then(a.id.cmp(&b.id)));
+        processes
+    }
+
+    pub fn get(&self, id: &str) -> Option<ProcessInfo> {
+        let mut entries = self.entries.lock().unwrap();
+        Self::refresh(&mut entries);
+        entries.get(id).map(|e| e.info.clone())
+    }
+
+    /// The last `lines` output lines of a process
+    pub fn output(&self, id: &str, lines: usize) -> Result<Vec<String>> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(id).ok_or_else(|| anyhow!("Unknown process: {}", id))?;
+        Ok(entry.output.iter().skip(entry.output.len().saturating_sub(lines)).cloned().collect())
+    }
+
+    /// Terminate a process and its group; the entry stays listed with its exit status
+    pub fn stop(&self, id: &str) -> Result<ProcessInfo> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get_mut(id).ok_or_else(|| anyhow!("Unknown process: {}", id))?;
+        if entry.info.running {
+            #[cfg(unix)]
+            let _ = Command::new("kill").args(["-TERM", &format!("-{}", entry.info.pid)]).status();
+            #[cfg(windows)]
+            let _ = Command::new("taskkill").args(["/T", "/F", "/PID", &entry.info.pid.to_string()]).status();
+            let _ = entry.child.kill();
+            let status = entry.child.wait()?;
+            entry.info.running = false;
+            entry.info.exit_code = status.code();
+        }
+        Ok(entry.info.clone())
+    }
+
+    /// Running processes that print a local URL or listen on a port
+    pub fn dev_servers(&self) -> Result<Vec<DevServer>> {
+        let listening = ports::list_listening_ports()?;
+        Ok(self
+            .list()
+            .into_iter()
+            .filter(|process| process.running)
+            .filter_map(|process| {
+                let tree = ports::process_tree(process.pid);
+                let ports: Vec<ListeningPort> = listening
+                    .iter()
+                    .filter(|p| p.pid.map(|pid| tree.contains(&pid)).unwrap_or(false))
+                    .cloned()
+                    .collect();
+                if process.urls.is_empty() && ports.is_empty() {
+                    return None;
+                }
+                let url = process
+                    .urls
+                    .first()
+                    .cloned()
+                    .or_else(|| ports.first().map(|p| format!("http://localhost:{}", p.port)));
+                Some(DevServer { process, ports, url })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_captures_urls_and_stops() {
+        let workspace = std::env::temp_dir().join(crate::storage::new_id("processes-test"));
+        std::fs::create_dir_all(&workspace).unwrap();
+        let mut trust = TrustStore::default();
+        let manager = ProcessManager::new();
+        let spec = TaskSpec {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), "echo 'ready on http://0.0.0.0:4321'; sleep 30".to_string()],
+            cwd: None,
+            env: HashMap::new(),
+            container: None,
+        };
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let on_url: UrlCallback = Arc::new(move |_, url| sink.lock().unwrap().push(url.to_string()));
+        assert!(manager.start(&spec, &workspace, &trust, on_url.clone()).is_err());
+        trust.set(&workspace, true).unwrap();
+        let info = manager.start(&spec, &workspace, &trust, on_url).unwrap();
+
+        for _ in 0..100 {
+            if !seen.lock().unwrap().is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(*seen.lock().unwrap(), vec!["http://localhost:4321"]);
+        assert_eq!(manager.get(&info.id).unwrap().urls, vec!["http://localhost:4321"]);
+        assert!(!manager.stop(&info.id).unwrap().running);
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+}
@@ -0,0 +1,139 @@
+// Profiles - Named engine configuration presets
+// Partial settings layered over the current configuration in one step
+
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::settings::Settings;
+use crate::storage;
+use crate::trust::TrustStore;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Partial `Settings`; only the fields present are changed
+    #[serde(default)]
+    pub settings: Value,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+fn builtin(name: &str, description: &str, settings: Value) -> Profile {
+    Profile {
+        name: name.to_string(),
+        description: description.to_string(),
+        settings,
+        builtin: true,
+    }
+}
+
+fn builtin_profiles() -> Vec<Profile> {
+    vec![
+        builtin(
+            "huge-monorepo",
+            "All cores for indexing, cheap rules only, polling instead of exhausting OS watch handles",
+            json!({
+                "indexer": { "threads": 0 },
+                "analyzer": { "rules": ["unused_imports", "security_patterns"] },
+                "watcher": { "mode": "polling", "poll_interval_ms": 5000 },
+                "ai": { "cache_max_entries": 2000 }
+            }),
+        ),
+        builtin(
+            "battery-saver",
+            "Two indexing threads, security rules only, slow polling and fewer AI requests",
+            json!({
+                "indexer": { "threads": 2 },
+                "analyzer": { "rules": ["security_patterns"] },
+                "watcher": { "mode": "polling", "poll_interval_ms": 10000 },
+                "ai": { "cache_enabled": true, "requests_per_minute": 20, "max_retries": 1 }
+            }),
+        ),
+        builtin(
+            "full-analysis",
+            "Every analyzer rule, native file watching and default AI limits",
+            json!({
+                "indexer": { "threads": 0 },
+                "analyzer": { "rules": [
+                    "unused_imports", "missing_types", "long_functions", "complex_conditions",
+                    "duplicate_code", "security_patterns", "performance_hints", "accessibility"
+                ] },
+                "watcher": { "mode": "native" },
+                "ai": { "requests_per_minute": 60, "max_retries": 3 }
+            }),
+        ),
+    ]
+}
+
+/// Project profiles: a JSON array in `.mimiverse/profiles.json`
+pub fn profiles_path(workspace: &Path) -> PathBuf {
+    storage::data_dir(workspace).join("profiles.json")
+}
+
+/// Built-in profiles, overridden by project profiles of the same name; restricted workspaces only get the
+/// built-in ones
+pub fn list(workspace: Option<&Path>, trust: &TrustStore) -> Vec<Profile> {
+    let mut profiles = builtin_profiles();
+    let project: Vec<Profile> = workspace
+        .filter(|ws| trust.is_trusted(ws))
+        .map(|ws| {
+            storage::read_json::<Vec<Profile>>(&profiles_path(ws)).unwrap_or_else(|e| {
+                log::warn!("Ignoring unreadable profiles in {:?}: {}", ws, e);
+                None
+            })
+        })
+        .unwrap_or_default()
+        .unwrap_or_default();
+    for mut profile in project {
+        profile.builtin = false;
+        profiles.retain(|p| p.name != profile.name);
+        profiles.push(profile);
+    }
+    profiles
+}
+
+/// Recursively overlay `patch` onto `base`; objects merge, everything else replaces
+fn merge(base: &mut Value, patch: &Value) {
+    match (base, patch) {
+        (Value::Object(base), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge(base.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (base, patch) => *base = patch.clone(),
+    }
+}
+
+/// Settings resulting from applying `profile` to `current`
+pub fn apply(current: &Settings, profile: &Profile) -> Result<Settings> {
+    let mut value = serde_json::to_value(current)?;
+    merge(&mut value, &profile.settings);
+    let mut settings: Settings =
+        serde_json::from_value(value).map_err(|e| anyhow!("Profile {} has invalid settings: {}", profile.name, e))?;
+    settings.profile = Some(profile.name.clone());
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_analyzer::AnalysisRule;
+    use crate::settings::WatcherMode;
+
+    #[test]
+    fn test_apply_overlays_only_given_fields() {
+        let profile = list(None, &TrustStore::default()).into_iter().find(|p| p.name == "battery-saver").unwrap();
+        let settings = apply(&Settings::default(), &profile).unwrap();
+        assert_eq!(settings.indexer.threads, 2);
+        assert_eq!(settings.analyzer.rules, vec![AnalysisRule::SecurityPatterns]);
+        assert_eq!(settings.watcher.mode, WatcherMode::Polling);
+        assert_eq!(settings.ai.requests_per_minute, 20);
+        assert_eq!(settings.ai.cache_ttl_secs, 3600);
+        assert!(settings.imports.group);
+        assert_eq!(settings.profile.as_deref(), Some("battery-saver"));
+    }
+}
@@ -0,0 +1,353 @@
+// Python Rules - Type-hint coverage and line-based checks for Python sources
+// Strings and comments are masked first, so their contents never look like code
+
+use serde::{Deserialize, Serialize};
+
+use crate::sloc::{self, SegmentKind};
+use crate::CodeSuggestion;
+
+/// Defaults evaluated once and shared by every call
+const MUTABLE_DEFAULTS: [&str; 5] = ["[]", "{}", "set()", "list()", "dict()"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FunctionHints {
+    pub name: String,
+    pub line: usize,
+    /// Parameters other than `self`/`cls`
+    pub params: usize,
+    pub annotated_params: usize,
+    pub returns: bool,
+}
+
+impl FunctionHints {
+    /// Every parameter and the return annotated; `__init__` needs no return annotation
+    pub fn fully_annotated(&self) -> bool {
+        self.annotated_params == self.params && (self.returns || self.name == "__init__")
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileTypeCoverage {
+    pub file: String,
+    pub functions: usize,
+    pub annotated: usize,
+    /// Some but not all hints present
+    pub partial: usize,
+    pub coverage_percent: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TypeCoverage {
+    pub functions: usize,
+    pub annotated: usize,
+    pub coverage_percent: f64,
+    /// Least covered first
+    pub files: Vec<FileTypeCoverage>,
+}
+
+/// One parameter of a signature
+struct Param {
+    name: String,
+    annotated: bool,
+    default: Option<String>,
+}
+
+struct Signature<'a> {
+    name: &'a str,
+    line: usize,
+    in_class: bool,
+    params: Vec<Param>,
+    returns: bool,
+}
+
+/// `content` line by line with comments and docstrings blanked and string contents replaced by `x`,
+/// keeping the quotes and byte columns
+fn masked_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<Vec<u8>> = content.lines().map(|l| l.as_bytes().to_vec()).collect();
+    for segment in sloc::segments(content, "Python") {
+        let fill = match segment.kind {
+            SegmentKind::Identifier => continue,
+            SegmentKind::Comment => b' ',
+            SegmentKind::Literal => b'x',
+        };
+        if let Some(line) = lines.get_mut(segment.line - 1) {
+            let end = (segment.column + segment.text.len()).min(line.len());
+            line[segment.column..end].fill(fill);
+        }
+    }
+    lines
+        .into_iter()
+        .map(|line| String::from_utf8_lossy(&line).replace("\"\"\"", "   "))
+        .collect()
+}
+
+/// Split at commas outside brackets
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0i32, 0);
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Byte offset of the first `c` outside brackets
+fn find_top_level(text: &str, target: char) -> Option<usize> {
+    let mut depth = 0i32;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if c == target && depth == 0 => return Some(i),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_param(text: &str) -> Option<Param> {
+    let text = text.trim();
+    if text.is_empty() || text == "*" || text == "/" {
+        return None;
+    }
+    let (head, default) = match find_top_level(text, '=') {
+        Some(at) => (&text[..at], Some(text[at + 1..].trim())),
+        None => (text, None),
+    };
+    let name = head.split(':').next().unwrap_or(head).trim().trim_start_matches('*');
+    Some(Param {
+        name: name.to_string(),
+        annotated: head.contains(':'),
+        default: default.map(str::to_string),
+    })
+}
+
+/// `def` statements with their parameters, joined across lines until the signature closes
+fn signatures(lines: &[String]) -> Vec<Signature<'_>> {
+    let mut found = Vec::new();
+    let mut classes: Vec<usize> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        while classes.last().is_some_and(|&level| level >= indent) {
+            classes.pop();
+        }
+        if trimmed.starts_with("class ") {
+            classes.push(indent);
+            continue;
+        }
+        let rest = match trimmed.strip_prefix("async ").unwrap_or(trimmed).strip_prefix("def ") {
+            Some(rest) => rest,
+            None => continue,
+        };
+        let open = match rest.find('(') {
+            Some(open) => open,
+            None => continue,
+        };
+        let name = rest[..open].trim();
+        // The parameter list may span lines; it is parsed from the joined text
+        let mut joined = rest[open + 1..].to_string();
+        let mut next = i + 1;
+        while find_top_level(&joined, ')').is_none() && next < lines.len() {
+            joined.push(' ');
+            joined.push_str(lines[next].trim());
+            next += 1;
+        }
+        let close = match find_top_level(&joined, ')') {
+            Some(close) => close,
+            None => continue,
+        };
+        let returns = joined[close..].contains("->");
+        found.push(Signature {
+            name,
+            line: i + 1,
+            in_class: !classes.is_empty(),
+            params: split_top_level(&joined[..close]).into_iter().filter_map(parse_param).collect(),
+            returns,
+        });
+    }
+    found
+}
+
+/// Type hints of every function in a Python file
+pub fn functions(content: &str) -> Vec<FunctionHints> {
+    let lines = masked_lines(content);
+    signatures(&lines)
+        .into_iter()
+        .map(|signature| {
+            let receiver =
+                signature.in_class && signature.params.first().is_some_and(|p| p.name == "self" || p.name == "cls");
+            let params = &signature.params[usize::from(receiver)..];
+            FunctionHints {
+                name: signature.name.to_string(),
+                line: signature.line,
+                params: params.len(),
+                annotated_params: params.iter().filter(|p| p.annotated).count(),
+                returns: signature.returns,
+            }
+        })
+        .collect()
+}
+
+fn percent(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        return 100.0;
+    }
+    (part as f64 * 1000.0 / whole as f64).round() / 10.0
+}
+
+pub fn file_coverage(file: &str, content: &str) -> FileTypeCoverage {
+    let hints = functions(content);
+    let annotated = hints.iter().filter(|h| h.fully_annotated()).count();
+    let partial = hints
+        .iter()
+        .filter(|h| !h.fully_annotated() && (h.annotated_params > 0 || h.returns))
+        .count();
+    FileTypeCoverage {
+        file: file.to_string(),
+        functions: hints.len(),
+        annotated,
+        partial,
+        coverage_percent: percent(annotated, hints.len()),
+    }
+}
+
+/// Workspace coverage from per-file figures; files without functions are left out
+pub fn workspace_coverage(files: Vec<FileTypeCoverage>) -> TypeCoverage {
+    let mut files: Vec<FileTypeCoverage> = files.into_iter().filter(|f| f.functions > 0).collect();
+    files.sort_by(|a, b| a.coverage_percent.total_cmp(&b.coverage_percent).then_with(|| b.functions.cmp(&a.functions)));
+    let functions = files.iter().map(|f| f.functions).sum();
+    let annotated = files.iter().map(|f| f.annotated).sum();
+    TypeCoverage {
+        functions,
+        annotated,
+        coverage_percent: percent(annotated, functions),
+        files,
+    }
+}
+
+fn suggestion(kind: &str, message: &str, line: usize, column: usize, fix: Option<&str>) -> CodeSuggestion {
+    CodeSuggestion {
+        kind: kind.to_string(),
+        message: message.to_string(),
+        line,
+        column,
+        severity: if kind == "performance" { "info" } else { "warning" }.to_string(),
+        fix: fix.map(str::to_string),
+    }
+}
+
+/// Whether `rhs` concatenates a plain (non-f) string literal
+fn concatenates_literal(rhs: &str) -> bool {
+    rhs.match_indices(['"', '\'']).any(|(at, _)| {
+        let prefix = rhs[..at].chars().next_back();
+        !prefix.is_some_and(|c| c == 'f' || c == 'F' || c == 'x')
+    })
+}
+
+/// Mutable default arguments, wildcard imports and string concatenation in loops
+pub fn analyze(content: &str) -> Vec<CodeSuggestion> {
+    let lines = masked_lines(content);
+    let mut suggestions = Vec::new();
+
+    for signature in signatures(&lines) {
+        let defaults = signature.params.iter().filter_map(|p| p.default.as_deref());
+        for default in defaults.filter(|d| MUTABLE_DEFAULTS.contains(d)) {
+            let message = format!("Mutable default {} is shared between calls - use None and build it inside", default);
+            suggestions.push(suggestion("quality", &message, signature.line, 0, Some("None")));
+        }
+    }
+
+    let mut loops: Vec<usize> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        while loops.last().is_some_and(|&level| level >= indent) {
+            loops.pop();
+        }
+        let code = trimmed.trim_end();
+        if code.starts_with("from ") && code.ends_with("import *") {
+            suggestions.push(suggestion(
+                "quality",
+                "Wildcard import hides where names come from - import them explicitly",
+                i + 1,
+                indent,
+                None,
+            ));
+        }
+        let loop_header = ["for ", "async for ", "while "].iter().any(|k| code.starts_with(k));
+        if loop_header && code.ends_with(':') {
+            loops.push(indent);
+            continue;
+        }
+        if loops.is_empty() {
+            continue;
+        }
+        let concatenation = match code.split_once("+=") {
+            Some((target, rhs)) if !target.trim().contains(' ') => concatenates_literal(rhs),
+            _ => code.split_once(" = ").is_some_and(|(target, rhs)| {
+                rhs.trim_start().starts_with(&format!("{} +", target.trim())) && concatenates_literal(rhs)
+            }),
+        };
+        if concatenation {
+            suggestions.push(suggestion(
+                "performance",
+                "String built with + inside a loop - collect the parts and ''.join() them, or use an f-string",
+                i + 1,
+                indent,
+                None,
+            ));
+        }
+    }
+    suggestions.sort_by_key(|s| s.line);
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_hint_coverage() {
+        let source = "class Repo:\n    def __init__(self, path: str):\n        self.path = path\n\n    def load(self, key, default=None) -> dict:\n        \"\"\"def fake(x): docstring\"\"\"\n        return {}\n\ndef save(\n    repo: Repo,\n    data: dict,\n) -> None:\n    pass\n";
+        let hints: Vec<(String, usize, usize, bool)> = functions(source)
+            .into_iter()
+            .map(|h| (h.name.clone(), h.params, h.annotated_params, h.fully_annotated()))
+            .collect();
+        assert_eq!(
+            hints,
+            vec![
+                ("__init__".to_string(), 1, 1, true),
+                ("load".to_string(), 2, 0, false),
+                ("save".to_string(), 2, 2, true),
+            ]
+        );
+        let coverage = file_coverage("repo.py", source);
+        assert_eq!((coverage.annotated, coverage.partial, coverage.coverage_percent), (2, 1, 66.7));
+    }
+
+    #[test]
+    fn test_rules() {
+        let source = "from os.path import *\n\ndef collect(items, seen=[]):\n    out = \"\"\n    for item in items:\n        out += \"<\" + item + \">\"\n        label = f\"{item}\"\n    return out  # out += \"x\"\n";
+        let found: Vec<(usize, String)> = analyze(source).into_iter().map(|s| (s.line, s.kind)).collect();
+        assert_eq!(
+            found,
+            vec![(1, "quality".to_string()), (3, "quality".to_string()), (6, "performance".to_string())]
+        );
+    }
+}
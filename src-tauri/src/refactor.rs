@@ -0,0 +1,530 @@
+// Refactor - Cross-file refactorings as previewable changesets
+// Every refactoring is computed up front and applied atomically
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::changeset::{self, Changeset, FileChange, FilePreview, Position, TextEdit};
+use crate::code_context::{SymbolReference, SymbolSpan};
+use crate::imports::{self, ImportLanguage};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RefactorResult {
+    pub changeset: Changeset,
+    pub preview: Vec<FilePreview>,
+}
+
+impl RefactorResult {
+    pub fn new(workspace: &Path, changeset: Changeset) -> Result<Self> {
+        let preview = changeset.preview(workspace)?;
+        Ok(Self { changeset, preview })
+    }
+}
+
+/// Resolve `.` and `..` without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// Path without its extension and a trailing `/index`, the form module specifiers take
+fn module_stem(path: &Path) -> String {
+    let path = path.with_extension("");
+    let text = path.to_string_lossy().replace('\\', "/");
+    text.strip_suffix("/index").map(str::to_string).unwrap_or(text)
+}
+
+/// Whether `specifier` imported from `importer` refers to `target`
+fn resolves_to(aliases: &[(String, PathBuf)], importer: &Path, specifier: &str, target: &Path) -> bool {
+    let base = if specifier.starts_with('.') {
+        importer.parent().map(|dir| dir.join(specifier))
+    } else {
+        aliases
+            .iter()
+            .find_map(|(alias, dir)| specifier.strip_prefix(alias.as_str()).map(|rest| dir.join(rest)))
+    };
+    match base {
+        // Specifiers may keep or drop the extension
+        Some(base) => {
+            let base = normalize(&base);
+            let target = normalize(target);
+            base == target || module_stem(&base) == module_stem(&target)
+        }
+        None => false,
+    }
+}
+
+/// Quoted module specifiers in import, export-from and require lines
+fn specifiers(content: &str) -> Vec<(usize, usize, String)> {
+    let mut found = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if !(trimmed.starts_with("import") || trimmed.starts_with("export") || line.contains("require(") || line.contains("import(")) {
+            continue;
+        }
+        let mut rest = 0;
+        while let Some(open) = line[rest..].find(|c: char| c == '\'' || c == '"').map(|o| o + rest) {
+            let quote = &line[open..open + 1];
+            let close = match line[open + 1..].find(quote) {
+                Some(close) => open + 1 + close,
+                None => break,
+            };
+            let column = line[..open + 1].chars().count();
+            found.push((i + 1, column, line[open + 1..close].to_string()));
+            rest = close + 1;
+        }
+    }
+    found
+}
+
+/// Edits re-pointing specifiers in `importer` from `old` to `new`.
+/// `importer_location` is where the importer will live once moved.
+fn rewrite_specifiers(workspace: &Path, importer: &Path, importer_location: &Path, content: &str, old: &Path, new: &Path) -> Vec<TextEdit> {
+    let aliases = imports::tsconfig_aliases(workspace);
+    let mut edits = Vec::new();
+    for (line, column, specifier) in specifiers(content) {
+        let replacement = if importer == old && specifier.starts_with('.') {
+            // The moved file's own relative imports resolve from its old directory
+            let target = match importer.parent() {
+                Some(dir) => normalize(&dir.join(&specifier)),
+                None => continue,
+            };
+            let resolved = candidates(&target).into_iter().find(|c| c.is_file()).unwrap_or(target);
+            imports::typescript_specifier(workspace, importer_location, &resolved)
+        } else if resolves_to(&aliases, importer, &specifier, old) {
+            imports::typescript_specifier(workspace, importer_location, new)
+        } else {
+            continue;
+        };
+        if replacement != specifier {
+            edits.push(TextEdit {
+                start: Position { line, column },
+                end: Position { line, column: column + specifier.chars().count() },
+                new_text: replacement,
+            });
+        }
+    }
+    edits
+}
+
+/// Files a module specifier without extension may denote
+fn candidates(base: &Path) -> Vec<PathBuf> {
+    let text = base.to_string_lossy();
+    ["", ".ts", ".tsx", ".js", ".jsx", "/index.ts", "/index.tsx", "/index.js"]
+        .iter()
+        .map(|ext| PathBuf::from(format!("{}{}", text, ext)))
+        .collect()
+}
+
+/// Edits re-pointing the imports of the ES module `dependents` from `old_path` to `new_path`
+fn dependent_edits(workspace: &Path, dependents: &[String], old_path: &Path, new_path: &Path) -> Vec<FileChange> {
+    let mut changes = Vec::new();
+    for dependent in dependents {
+        let path = changeset::resolve(workspace, dependent);
+        let moved = path == old_path || path == new_path;
+        if moved || ImportLanguage::for_path(dependent) != Some(ImportLanguage::TypeScript) {
+            continue;
+        }
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                log::warn!("Skipping unreadable dependent {}: {}", dependent, e);
+                continue;
+            }
+        };
+        let edits = rewrite_specifiers(workspace, &path, &path, &content, old_path, new_path);
+        if !edits.is_empty() {
+            changes.push(FileChange::Edit {
+                path: dependent.clone(),
+                edits,
+            });
+        }
+    }
+    changes
+}
+
+/// Move `old` to `new`, rewriting the imports of every dependent and of the moved file itself
+pub fn move_file(workspace: &Path, dependents: &[String], old: &str, new: &str) -> Result<RefactorResult> {
+    let old_path = changeset::resolve(workspace, old);
+    let new_path = changeset::resolve(workspace, new);
+    if !old_path.is_file() {
+        return Err(anyhow!("File not found: {}", old));
+    }
+    if new_path.exists() {
+        return Err(anyhow!("Target already exists: {}", new));
+    }
+
+    let mut changes = Vec::new();
+    let rewrites_imports = ImportLanguage::for_path(old) == Some(ImportLanguage::TypeScript);
+    if rewrites_imports {
+        // Edits to the moved file must precede the rename
+        let content = fs::read_to_string(&old_path)?;
+        let edits = rewrite_specifiers(workspace, &old_path, &new_path, &content, &old_path, &new_path);
+        if !edits.is_empty() {
+            changes.push(FileChange::Edit {
+                path: old.to_string(),
+                edits,
+            });
+        }
+        changes.extend(dependent_edits(workspace, dependents, &old_path, &new_path));
+    } else {
+        log::info!("No import rewriting for {}; only ES modules are tracked by the graph", old);
+    }
+    changes.push(FileChange::Rename {
+        from: old.to_string(),
+        to: new.to_string(),
+    });
+
+    let changeset = Changeset::new(format!("Move {} to {}", old, new), changes);
+    RefactorResult::new(workspace, changeset)
+}
+
+/// Rewrite the imports left stale by a file that was moved from `old` to `new` outside the app
+pub fn rewrite_moved_imports(workspace: &Path, dependents: &[String], old: &str, new: &str) -> Result<RefactorResult> {
+    let old_path = changeset::resolve(workspace, old);
+    let new_path = changeset::resolve(workspace, new);
+    if !new_path.is_file() {
+        return Err(anyhow!("File not found: {}", new));
+    }
+
+    let mut changes = Vec::new();
+    if ImportLanguage::for_path(new) == Some(ImportLanguage::TypeScript) {
+        // The moved file's relative imports still resolve from its old directory
+        let content = fs::read_to_string(&new_path)?;
+        let edits = rewrite_specifiers(workspace, &old_path, &new_path, &content, &old_path, &new_path);
+        if !edits.is_empty() {
+            changes.push(FileChange::Edit {
+                path: new.to_string(),
+                edits,
+            });
+        }
+        changes.extend(dependent_edits(workspace, dependents, &old_path, &new_path));
+    }
+    let changeset = Changeset::new(format!("Update imports after moving {} to {}", old, new), changes);
+    RefactorResult::new(workspace, changeset)
+}
+
+/// Byte offset just past the bracket closing the one at `open`, skipping string literals
+pub fn closing_bracket(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in text[open..].char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '\'' | '"' | '`' => quote = Some(c),
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(open + i + 1);
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+    None
+}
+
+/// Split a list at top-level commas; `angle` also nests on `<...>` generics
+fn split_list(text: &str, angle: bool) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut escaped = false;
+    let mut previous = ' ';
+    for c in text.chars() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '\'' | '"' | '`' => quote = Some(c),
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                '<' if angle => depth += 1,
+                // `->` and `=>` are not closing brackets
+                '>' if angle && previous != '-' && previous != '=' => depth -= 1,
+                ',' if depth == 0 => {
+                    items.push(std::mem::take(&mut current).trim().to_string());
+                    previous = c;
+                    continue;
+                }
+                _ => {}
+            },
+        }
+        current.push(c);
+        previous = c;
+    }
+    // A trailing comma leaves nothing behind
+    if !current.trim().is_empty() {
+        items.push(current.trim().to_string());
+    }
+    items
+}
+
+/// Split call arguments at top-level commas
+pub fn split_arguments(text: &str) -> Vec<String> {
+    split_list(text, false)
+}
+
+/// Split a parameter list at top-level commas, keeping generic types together
+pub fn split_parameters(text: &str) -> Vec<String> {
+    split_list(text, true)
+}
+
+/// One parameter of the new signature
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewParam {
+    /// Index of the existing parameter kept here, `None` for an added one
+    pub from: Option<usize>,
+    /// Declaration of an added parameter (`count: usize`)
+    pub declaration: Option<String>,
+    /// Argument passed for an added parameter at existing call sites
+    pub placeholder: Option<String>,
+}
+
+/// A call site left unchanged because it could not be rewritten safely
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FlaggedCall {
+    pub file: String,
+    pub line: usize,
+    pub snippet: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SignatureChange {
+    pub changeset: Changeset,
+    pub preview: Vec<FilePreview>,
+    pub flagged: Vec<FlaggedCall>,
+}
+
+/// `self`-style receivers that callers pass implicitly
+fn is_receiver(param: &str) -> bool {
+    let param = param.trim_start_matches('&').trim_start_matches("mut ").trim();
+    param == "self" || param.starts_with("self:") || param == "cls"
+}
+
+fn default_placeholder(language: ImportLanguage) -> &'static str {
+    match language {
+        ImportLanguage::Rust => "todo!()",
+        ImportLanguage::TypeScript => "undefined",
+        ImportLanguage::Python => "None",
+    }
+}
+
+/// Byte range of the parameter list (inside the parentheses) of `definition`
+fn parameter_range(content: &str, definition: &SymbolSpan) -> Option<(usize, usize)> {
+    let declaration = &content[definition.start_byte..definition.end_byte];
+    let name = declaration
+        .match_indices(definition.name.as_str())
+        .map(|(i, _)| i)
+        .find(|&i| {
+            let before = declaration[..i].chars().last();
+            let after = declaration[i + definition.name.len()..].chars().next();
+            !before.map(|c| c.is_alphanumeric() || c == '_').unwrap_or(false)
+                && !after.map(|c| c.is_alphanumeric() || c == '_').unwrap_or(false)
+        })?;
+    let open = definition.start_byte + name + declaration[name..].find('(')?;
+    let close = closing_bracket(content, open)?;
+    Some((open + 1, close - 1))
+}
+
+/// Reorder, add and remove parameters of `definition` and update the call sites in `references`
+pub fn change_signature(
+    workspace: &Path,
+    file: &str,
+    content: &str,
+    definition: &SymbolSpan,
+    references: &[SymbolReference],
+    new_params: &[NewParam],
+) -> Result<SignatureChange> {
+    let language = ImportLanguage::for_path(file).ok_or_else(|| anyhow!("Unsupported file type: {}", file))?;
+    let name = definition.name.as_str();
+    let (params_start, params_end) =
+        parameter_range(content, definition).ok_or_else(|| anyhow!("{} has no parameter list", name))?;
+    let mut params = split_parameters(&content[params_start..params_end]);
+    let receiver = params.first().filter(|p| is_receiver(p)).cloned();
+    let has_receiver = receiver.is_some();
+    if has_receiver {
+        params.remove(0);
+    }
+
+    let mut used = Vec::new();
+    for param in new_params {
+        match (param.from, &param.declaration) {
+            (Some(i), _) if i >= params.len() => return Err(anyhow!("{} has no parameter {}", name, i)),
+            (Some(i), _) if used.contains(&i) => return Err(anyhow!("Parameter {} is listed twice", i)),
+            (Some(i), _) => used.push(i),
+            (None, None) => return Err(anyhow!("Added parameters need a declaration")),
+            (None, Some(_)) => {}
+        }
+    }
+
+    let mut declaration: Vec<String> = receiver.into_iter().collect();
+    for param in new_params {
+        declaration.push(match param.from {
+            Some(i) => params[i].clone(),
+            None => param.declaration.clone().unwrap_or_default(),
+        });
+    }
+    let mut edits_by_file: Vec<(String, Vec<TextEdit>)> = vec![(
+        file.to_string(),
+        vec![TextEdit {
+            start: changeset::position_at(content, params_start),
+            end: changeset::position_at(content, params_end),
+            new_text: declaration.join(", "),
+        }],
+    )];
+
+    let mut flagged = Vec::new();
+    let mut contents: Vec<(String, String)> = vec![(file.to_string(), content.to_string())];
+    let mut rewritten: Vec<(String, usize, usize)> = Vec::new();
+    for reference in references {
+        let text = match contents.iter().find(|(f, _)| f == &reference.file) {
+            Some((_, text)) => text.clone(),
+            None => match fs::read_to_string(changeset::resolve(workspace, &reference.file)) {
+                Ok(text) => {
+                    contents.push((reference.file.clone(), text.clone()));
+                    text
+                }
+                Err(_) => continue,
+            },
+        };
+        let line_start: usize = text.split_inclusive('\n').take(reference.line - 1).map(str::len).sum();
+        let line_end = text[line_start..].find('\n').map(|i| line_start + i).unwrap_or(text.len());
+        let mut flag = |reason: &str| {
+            flagged.push(FlaggedCall {
+                file: reference.file.clone(),
+                line: reference.line,
+                snippet: reference.snippet.clone(),
+                reason: reason.to_string(),
+            })
+        };
+
+        for (index, _) in text[line_start..line_end].match_indices(name) {
+            let start = line_start + index;
+            let before = text[..start].chars().last();
+            if before.map(|c| c.is_alphanumeric() || c == '_').unwrap_or(false) {
+                continue;
+            }
+            // Skip generic arguments (`name::<T>(`) up to the parenthesis
+            let after = &text[start + name.len()..];
+            let open = match after.find('(') {
+                Some(i) if after[..i].trim().is_empty() || after[..i].trim_start().starts_with("::<") => start + name.len() + i,
+                _ => continue,
+            };
+            if rewritten.iter().any(|(f, s, e)| f == &reference.file && *s <= open && open < *e) {
+                flag("call is nested in the arguments of another call");
+                continue;
+            }
+            let close = match closing_bracket(&text, open) {
+                Some(close) => close,
+                None => {
+                    flag("unbalanced parentheses");
+                    continue;
+                }
+            };
+
+            let qualifier = text[..start].trim_end();
+            let through_path = qualifier.ends_with("::")
+                || (qualifier.ends_with('.') && qualifier[..qualifier.len() - 1].chars().last().map(|c| c.is_uppercase()).unwrap_or(false));
+            if has_receiver && through_path {
+                flag("receiver is passed explicitly");
+                continue;
+            }
+            if language == ImportLanguage::Rust && qualifier.ends_with('.') && !has_receiver {
+                flag("method call does not match the free function");
+                continue;
+            }
+
+            let arguments = split_arguments(&text[open + 1..close - 1]);
+            if arguments.iter().any(|a| a.starts_with("...") || a.starts_with('*') || (language == ImportLanguage::Python && a.contains('=') && !a.contains("=="))) {
+                flag("spread or keyword arguments");
+                continue;
+            }
+            if arguments.len() != params.len() {
+                flag("call passes a different number of arguments");
+                continue;
+            }
+
+            let updated: Vec<String> = new_params
+                .iter()
+                .map(|param| match param.from {
+                    Some(i) => arguments[i].clone(),
+                    None => param.placeholder.clone().unwrap_or_else(|| default_placeholder(language).to_string()),
+                })
+                .collect();
+            let edit = TextEdit {
+                start: changeset::position_at(&text, open + 1),
+                end: changeset::position_at(&text, close - 1),
+                new_text: updated.join(", "),
+            };
+            match edits_by_file.iter_mut().find(|(f, _)| f == &reference.file) {
+                Some((_, edits)) => edits.push(edit),
+                None => edits_by_file.push((reference.file.clone(), vec![edit])),
+            }
+            rewritten.push((reference.file.clone(), open, close));
+        }
+    }
+
+    let changes = edits_by_file
+        .into_iter()
+        .map(|(path, edits)| FileChange::Edit { path, edits })
+        .collect();
+    let changeset = Changeset::new(format!("Change signature of {}", name), changes);
+    let preview = changeset.preview(workspace)?;
+    Ok(SignatureChange {
+        changeset,
+        preview,
+        flagged,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_dependent_specifier() {
+        let workspace = Path::new("/ws");
+        let content = "import { a } from '../lib/util';\nexport * from \"./other\";\nconst b = require('../lib/util.ts');\n";
+        let edits = rewrite_specifiers(
+            workspace,
+            Path::new("/ws/src/app.ts"),
+            Path::new("/ws/src/app.ts"),
+            content,
+            Path::new("/ws/lib/util.ts"),
+            Path::new("/ws/src/shared/util.ts"),
+        );
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0].start, Position { line: 1, column: 19 });
+        assert_eq!(edits[0].new_text, "./shared/util");
+        assert_eq!(edits[1].start.line, 3);
+    }
+
+    #[test]
+    fn test_split_arguments() {
+        assert_eq!(split_arguments("a < b, f(c, d), \"x, y\","), vec!["a < b", "f(c, d)", "\"x, y\""]);
+        assert_eq!(split_parameters("m: HashMap<u8, u8>, f: impl Fn(u8) -> u8"), vec!["m: HashMap<u8, u8>", "f: impl Fn(u8) -> u8"]);
+    }
+}
@@ -0,0 +1,210 @@
+// Related Files - Rank files likely needed when editing a target
+// Blends dependency graph proximity, git co-change history and embeddings
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+use crate::ai_provider::AiProvider;
+use crate::file_access;
+use crate::git;
+use crate::mimi_engine::CodeGraph;
+use crate::tokens;
+
+/// Commits of history inspected for co-changes
+const CO_CHANGE_COMMITS: usize = 200;
+/// Files embedded for the semantic signal
+const MAX_EMBEDDED_CANDIDATES: usize = 40;
+/// Tokens of each file used for its embedding
+const EMBEDDING_TOKENS: usize = 1500;
+
+const GRAPH_WEIGHT: f32 = 0.4;
+const CO_CHANGE_WEIGHT: f32 = 0.35;
+const SEMANTIC_WEIGHT: f32 = 0.25;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RelatedFile {
+    pub path: String,
+    pub score: f32,
+    /// Human-readable signals that contributed, e.g. "imported by target"
+    pub reasons: Vec<String>,
+}
+
+#[derive(Default)]
+struct Candidate {
+    graph: f32,
+    co_change: f32,
+    semantic: f32,
+    reasons: Vec<String>,
+}
+
+/// Graph proximity of every file near `path`, with a reason per file
+pub fn graph_signals(graph: &CodeGraph, path: &str) -> HashMap<String, (f32, String)> {
+    let mut signals = HashMap::new();
+    for dependency in graph.get_dependencies(path) {
+        signals.insert(dependency, (1.0, "imported by target".to_string()));
+    }
+    for dependent in graph.get_dependents(path) {
+        signals.insert(dependent, (1.0, "imports target".to_string()));
+    }
+
+    // Second-degree neighbours through the files the target imports
+    let direct: Vec<String> = signals.keys().cloned().collect();
+    for neighbour in direct {
+        for sibling in graph.get_dependents(&neighbour) {
+            signals
+                .entry(sibling)
+                .or_insert_with(|| (0.5, format!("shares import {}", file_name(&neighbour))));
+        }
+    }
+    // Package imports are graph nodes too; keep real files only
+    signals.retain(|file, _| file != path && Path::new(file).is_file());
+    signals
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+async fn embed_file(provider: &dyn AiProvider, path: &str) -> Option<Vec<f32>> {
+    let content = fs::read_to_string(path).ok()?;
+    let excerpt = tokens::truncate_to_tokens(&content, EMBEDDING_TOKENS, provider.model());
+    provider.embed(&excerpt).await.ok()
+}
+
+/// Rank files related to `path` (absolute and confined), best first; only workspace files are embedded
+pub async fn rank(
+    provider: Option<&dyn AiProvider>,
+    workspace: &Path,
+    path: &str,
+    graph: HashMap<String, (f32, String)>,
+    limit: usize,
+) -> Vec<RelatedFile> {
+    let mut candidates: HashMap<String, Candidate> = HashMap::new();
+    for (file, (score, reason)) in graph {
+        let candidate = candidates.entry(file).or_default();
+        candidate.graph = score;
+        candidate.reasons.push(reason);
+    }
+
+    // Co-change history
+    let relative = Path::new(path)
+        .strip_prefix(workspace)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string());
+    let history = {
+        let workspace = workspace.to_path_buf();
+        tauri::async_runtime::spawn_blocking(move || git::co_changes(&workspace, &relative, CO_CHANGE_COMMITS))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|history| history)
+    };
+    match history {
+        Ok((commits, counts)) if commits > 0 => {
+            for (file, count) in counts {
+                let absolute = workspace.join(&file).to_string_lossy().to_string();
+                if absolute == path || !Path::new(&absolute).exists() {
+                    continue;
+                }
+                let candidate = candidates.entry(absolute).or_default();
+                candidate.co_change = count as f32 / commits as f32;
+                candidate.reasons.push(format!("changed together in {} of {} commits", count, commits));
+            }
+        }
+        Ok(_) => {}
+        Err(e) => log::debug!("No co-change history for {}: {}", path, e),
+    }
+
+    // Same-directory files are cheap semantic candidates
+    if let Some(dir) = Path::new(path).parent() {
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                let sibling = entry.path();
+                let sibling_path = sibling.to_string_lossy().to_string();
+                let inside = file_access::confine(Some(workspace), &[], &sibling_path).is_ok();
+                if inside && sibling.is_file() && sibling_path != path {
+                    candidates.entry(sibling_path).or_default();
+                }
+            }
+        }
+    }
+
+    // Semantic similarity for the strongest candidates
+    if let Some(provider) = provider {
+        if let Some(target) = embed_file(provider, path).await {
+            let mut ordered: Vec<(&String, f32)> = candidates
+                .iter()
+                .map(|(file, c)| (file, c.graph * GRAPH_WEIGHT + c.co_change * CO_CHANGE_WEIGHT))
+                .collect();
+            ordered.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+            let to_embed: Vec<String> = ordered
+                .into_iter()
+                .filter(|(file, _)| file_access::confine(Some(workspace), &[], file).is_ok())
+                .take(MAX_EMBEDDED_CANDIDATES)
+                .map(|(file, _)| file.clone())
+                .collect();
+
+            for file in to_embed {
+                if let Some(embedding) = embed_file(provider, &file).await {
+                    let similarity = cosine(&target, &embedding).max(0.0);
+                    if let Some(candidate) = candidates.get_mut(&file) {
+                        candidate.semantic = similarity;
+                        if similarity >= 0.75 {
+                            candidate.reasons.push(format!("similar content ({:.0}%)", similarity * 100.0));
+                        }
+                    }
+                }
+            }
+        } else {
+            log::debug!("Embeddings unavailable; ranking {} without semantic similarity", path);
+        }
+    }
+
+    let mut ranked: Vec<RelatedFile> = candidates
+        .into_iter()
+        .map(|(file, c)| RelatedFile {
+            score: c.graph * GRAPH_WEIGHT + c.co_change * CO_CHANGE_WEIGHT + c.semantic * SEMANTIC_WEIGHT,
+            path: file,
+            reasons: c.reasons,
+        })
+        .filter(|f| f.score > 0.0)
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine() {
+        assert!((cosine(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert_eq!(cosine(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+        assert_eq!(cosine(&[1.0], &[1.0, 2.0]), 0.0);
+    }
+}
@@ -0,0 +1,251 @@
+// Rename Safety - Risks of renaming a symbol across the workspace
+// Collisions, shadowing and mentions in comments and strings are reported before any edit
+
+use std::collections::HashSet;
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::file_indexer::FileIndex;
+use crate::mimi_engine::CodeGraph;
+use crate::sloc::{self, SegmentKind};
+use crate::symbols::SymbolInfo;
+
+/// Reserved in at least one of the analyzed languages
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "case", "catch", "class", "const", "continue", "crate", "def", "default", "delete",
+    "do", "else", "enum", "export", "extends", "false", "finally", "fn", "for", "from", "function", "if", "impl",
+    "import", "in", "instanceof", "interface", "lambda", "let", "loop", "match", "mod", "move", "mut", "new", "None",
+    "null", "pass", "pub", "raise", "ref", "return", "self", "Self", "static", "struct", "super", "switch", "this",
+    "throw", "trait", "true", "try", "type", "typeof", "use", "var", "void", "where", "while", "with", "yield",
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    None,
+    /// Only comments or strings mention the old name
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// The new name is already declared in the same scope
+    Collision,
+    /// The new name is already used where the symbol is referenced
+    Shadowing,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RenameConflict {
+    pub kind: ConflictKind,
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TextualMatch {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    /// "comment" or "string"
+    pub context: String,
+    pub snippet: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RenameReport {
+    pub symbol: String,
+    pub new_name: String,
+    pub definitions: Vec<SymbolInfo>,
+    /// Occurrences of the old name as an identifier, definitions included
+    pub code_references: usize,
+    pub conflicts: Vec<RenameConflict>,
+    /// Mentions a rename may or may not want to update
+    pub textual_matches: Vec<TextualMatch>,
+    pub warnings: Vec<String>,
+    pub risk: RiskLevel,
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Bare name of a possibly qualified symbol: `crate::graph::CodeGraph::new` is `new`
+pub fn bare_name(symbol: &str) -> &str {
+    symbol.rsplit(|c: char| c == ':' || c == '.').next().unwrap_or(symbol)
+}
+
+/// Byte offsets where `name` occurs in `text` as a whole identifier
+fn whole_word_matches<'a>(text: &'a str, name: &'a str) -> impl Iterator<Item = usize> + 'a {
+    text.match_indices(name).map(|(at, _)| at).filter(move |&at| {
+        let before = text[..at].chars().next_back();
+        let after = text[at + name.len()..].chars().next();
+        let part_of_identifier = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+        !part_of_identifier(before) && !part_of_identifier(after)
+    })
+}
+
+/// Declarations a rename of `symbol` targets: the one with that qualified name, or every one with that bare name
+fn resolve<'a>(graph: &'a CodeGraph, symbol: &str) -> Vec<&'a SymbolInfo> {
+    let named = graph.find_symbol(bare_name(symbol));
+    let qualified: Vec<&SymbolInfo> = named.iter().copied().filter(|s| s.qualified_name == symbol).collect();
+    if qualified.is_empty() {
+        named
+    } else {
+        qualified
+    }
+}
+
+/// Assess renaming `symbol` to `new_name`; `sources` are the files to scan, as (path, content)
+pub fn check(graph: &CodeGraph, sources: &[(String, String)], symbol: &str, new_name: &str) -> Result<RenameReport> {
+    let old_name = bare_name(symbol);
+    if !is_identifier(new_name) {
+        return Err(anyhow!("Not a valid identifier: {}", new_name));
+    }
+    if new_name == old_name {
+        return Err(anyhow!("{} already has that name", symbol));
+    }
+    let definitions = resolve(graph, symbol);
+    if definitions.is_empty() {
+        return Err(anyhow!("Symbol not found: {}", symbol));
+    }
+
+    let mut risk = RiskLevel::None;
+    let mut warnings = Vec::new();
+    if KEYWORDS.contains(&new_name) {
+        warnings.push(format!("'{}' is a reserved word", new_name));
+        risk = RiskLevel::High;
+    }
+    if definitions.len() > 1 {
+        let files: HashSet<&str> = definitions.iter().map(|d| d.file.as_str()).collect();
+        warnings.push(format!(
+            "{} declarations in {} files are named '{}'; pass a qualified name to rename only one",
+            definitions.len(),
+            files.len(),
+            old_name
+        ));
+        risk = risk.max(RiskLevel::Medium);
+    }
+
+    let mut conflicts = Vec::new();
+    let existing = graph.find_symbol(new_name);
+    for definition in &definitions {
+        for other in existing.iter().filter(|o| o.file == definition.file && o.container == definition.container) {
+            let kind = format!("{:?}", other.kind).to_lowercase();
+            conflicts.push(RenameConflict {
+                kind: ConflictKind::Collision,
+                file: other.file.clone(),
+                line: other.line,
+                message: format!("'{}' is already declared here as a {}", other.qualified_name, kind),
+            });
+        }
+    }
+
+    let mut code_references = 0;
+    let mut textual_matches = Vec::new();
+    for (file, content) in sources {
+        let extension = Path::new(file).extension().and_then(|e| e.to_str()).unwrap_or("");
+        let language = FileIndex::detect_language(extension);
+        let lines: Vec<&str> = content.lines().collect();
+        let mut references_here = 0;
+        let mut new_name_uses = Vec::new();
+        for segment in sloc::segments(content, &language) {
+            match segment.kind {
+                SegmentKind::Identifier if segment.text == old_name => references_here += 1,
+                SegmentKind::Identifier if segment.text == new_name => new_name_uses.push(segment.line),
+                SegmentKind::Identifier => {}
+                SegmentKind::Comment | SegmentKind::Literal => {
+                    for at in whole_word_matches(segment.text, old_name) {
+                        textual_matches.push(TextualMatch {
+                            file: file.clone(),
+                            line: segment.line,
+                            column: segment.column + at,
+                            context: if segment.kind == SegmentKind::Comment { "comment" } else { "string" }.to_string(),
+                            snippet: lines.get(segment.line - 1).map(|l| l.trim().to_string()).unwrap_or_default(),
+                        });
+                    }
+                }
+            }
+        }
+        code_references += references_here;
+        // The new name already in use next to references of the old one would capture them
+        let collided: HashSet<usize> = conflicts.iter().filter(|c| &c.file == file).map(|c| c.line).collect();
+        if references_here > 0 {
+            if let Some(&line) = new_name_uses.iter().find(|line| !collided.contains(line)) {
+                conflicts.push(RenameConflict {
+                    kind: ConflictKind::Shadowing,
+                    file: file.clone(),
+                    line,
+                    message: format!(
+                        "'{}' is already used in this file ({} times), so renamed references may resolve to it",
+                        new_name,
+                        new_name_uses.len()
+                    ),
+                });
+            }
+        }
+    }
+
+    if conflicts.iter().any(|c| c.kind == ConflictKind::Collision) {
+        risk = RiskLevel::High;
+    } else if !conflicts.is_empty() {
+        risk = risk.max(RiskLevel::Medium);
+    }
+    if !textual_matches.is_empty() {
+        risk = risk.max(RiskLevel::Low);
+    }
+
+    Ok(RenameReport {
+        symbol: symbol.to_string(),
+        new_name: new_name.to_string(),
+        definitions: definitions.into_iter().cloned().collect(),
+        code_references,
+        conflicts,
+        textual_matches,
+        warnings,
+        risk,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collision_shadowing_and_text_matches() {
+        let workspace = std::env::temp_dir().join(crate::storage::new_id("rename-test"));
+        std::fs::create_dir_all(&workspace).unwrap();
+        let lib = workspace.join("lib.py");
+        let app = workspace.join("app.py");
+        let lib_source = "def load():\n    pass\n\ndef fetch():\n    pass\n";
+        let app_source = "from lib import load\n\n# load the config\nfetch = 1\nload()\nprint(\"load failed\")\n";
+        std::fs::write(&lib, lib_source).unwrap();
+        std::fs::write(&app, app_source).unwrap();
+        let mut graph = CodeGraph::new();
+        graph.update_file(&workspace, &lib, lib_source);
+        let sources = vec![
+            (lib.to_string_lossy().to_string(), lib_source.to_string()),
+            (app.to_string_lossy().to_string(), app_source.to_string()),
+        ];
+
+        let report = check(&graph, &sources, "load", "fetch").unwrap();
+        assert_eq!(report.risk, RiskLevel::High);
+        let kinds: Vec<(ConflictKind, usize)> = report.conflicts.iter().map(|c| (c.kind, c.line)).collect();
+        assert_eq!(kinds, vec![(ConflictKind::Collision, 4), (ConflictKind::Shadowing, 4)]);
+        assert_eq!(report.code_references, 3);
+        let contexts: Vec<&str> = report.textual_matches.iter().map(|m| m.context.as_str()).collect();
+        assert_eq!(contexts, vec!["comment", "string"]);
+
+        let safe = check(&graph, &sources[..1], "load", "read_all").unwrap();
+        assert_eq!(safe.risk, RiskLevel::None);
+        assert!(check(&graph, &sources, "load", "not valid").is_err());
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+}
@@ -0,0 +1,46 @@
+// Renames - State that follows a file moved outside the app
+// Graph edges, local history, breakpoints and pins move to the new path instead of being dropped
+
+use std::path::Path;
+
+use crate::events::Event;
+use crate::file_indexer::FileRename;
+use crate::mimi_engine::CodeGraph;
+use crate::{breakpoints, documents, history, working_set, AppState};
+
+/// Migrate everything keyed by the old path of each rename the file index detected, and announce it
+pub fn follow(state: &AppState, workspace: &Path, renames: &[FileRename]) {
+    for rename in renames {
+        let (from, to) = (Path::new(&rename.from), Path::new(&rename.to));
+        let content = if CodeGraph::is_analyzed(to) {
+            documents::read_source(&state.documents, to).unwrap_or_else(|e| {
+                log::warn!("Failed to read renamed file {}: {}", rename.to, e);
+                String::new()
+            })
+        } else {
+            String::new()
+        };
+        let dependents = state.code_graph.lock().unwrap().rename_file(workspace, from, to, &content);
+
+        if let Err(e) = history::rename(workspace, &rename.from, &rename.to) {
+            log::warn!("Failed to move the history of {}: {}", rename.from, e);
+        }
+        if let Err(e) = breakpoints::rename(workspace, &rename.from, &rename.to) {
+            log::warn!("Failed to move the breakpoints of {}: {}", rename.from, e);
+        }
+        match working_set::rename(workspace, &rename.from, &rename.to) {
+            Ok(pinned) => {
+                let pinned: Vec<_> = pinned.into_iter().map(|file| workspace.join(file.path)).collect();
+                state.file_index.lock().unwrap().set_pinned(&pinned);
+            }
+            Err(e) => log::warn!("Failed to move the pin of {}: {}", rename.from, e),
+        }
+
+        log::info!("Renamed {} to {}, {} dependents", rename.from, rename.to, dependents.len());
+        state.events.publish(Event::FileRenamed {
+            from: rename.from.clone(),
+            to: rename.to.clone(),
+            dependents,
+        });
+    }
+}
@@ -0,0 +1,432 @@
+// Workspace Report - Self-contained Markdown or HTML overview
+// Stats, languages, hotspots, diagnostics, dependency cycles and licenses
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::Diagnostic;
+use crate::git;
+
+/// Commits inspected for hotspot churn
+const CHURN_COMMITS: usize = 1000;
+/// Rows shown in ranked tables
+const TOP_ROWS: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionKind {
+    Stats,
+    Languages,
+    Hotspots,
+    Diagnostics,
+    Cycles,
+    Licenses,
+}
+
+impl SectionKind {
+    pub const ALL: [SectionKind; 6] = [
+        SectionKind::Stats,
+        SectionKind::Languages,
+        SectionKind::Hotspots,
+        SectionKind::Diagnostics,
+        SectionKind::Cycles,
+        SectionKind::Licenses,
+    ];
+}
+
+/// Workspace data gathered from the index, graph and diagnostics store
+pub struct ReportInput {
+    pub workspace: PathBuf,
+    pub total_files: usize,
+    pub total_lines: usize,
+    pub dependency_count: usize,
+    pub by_language: HashMap<String, usize>,
+    /// Absolute path -> line count
+    pub file_lines: HashMap<String, usize>,
+    pub cycles: Vec<Vec<String>>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LicenseFinding {
+    pub package: String,
+    pub license: String,
+    /// File the license was read from, relative to the workspace
+    pub source: String,
+    /// Why the license needs attention (copyleft, unknown)
+    pub concern: Option<String>,
+}
+
+/// Rendered independently of the output format
+struct Section {
+    title: String,
+    paragraphs: Vec<String>,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Section {
+    fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            paragraphs: Vec::new(),
+            headers: Vec::new(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn table(mut self, headers: &[&str], rows: Vec<Vec<String>>) -> Self {
+        self.headers = headers.iter().map(|h| h.to_string()).collect();
+        self.rows = rows;
+        self
+    }
+
+    fn paragraph(mut self, text: impl Into<String>) -> Self {
+        self.paragraphs.push(text.into());
+        self
+    }
+}
+
+impl ReportInput {
+    fn relative(&self, path: &str) -> String {
+        Path::new(path)
+            .strip_prefix(&self.workspace)
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_else(|_| path.to_string())
+    }
+
+    fn stats(&self) -> Section {
+        Section::new("Overview").table(
+            &["Metric", "Value"],
+            vec![
+                vec!["Files".to_string(), self.total_files.to_string()],
+                vec!["Lines".to_string(), self.total_lines.to_string()],
+                vec!["Dependency edges".to_string(), self.dependency_count.to_string()],
+                vec!["Dependency cycles".to_string(), self.cycles.len().to_string()],
+                vec!["Diagnostics".to_string(), self.diagnostics.len().to_string()],
+            ],
+        )
+    }
+
+    fn languages(&self) -> Section {
+        let mut languages: Vec<(&String, &usize)> = self.by_language.iter().collect();
+        languages.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let total = self.total_files.max(1) as f64;
+        let rows = languages
+            .into_iter()
+            .map(|(language, files)| {
+                vec![
+                    language.clone(),
+                    files.to_string(),
+                    format!("{:.1}%", *files as f64 * 100.0 / total),
+                ]
+            })
+            .collect();
+        Section::new("Languages").table(&["Language", "Files", "Share"], rows)
+    }
+
+    /// Files changed often and large: commits in recent history times lines
+    fn hotspots(&self) -> Section {
+        let section = Section::new("Hotspots");
+        let churn = match git::churn(&self.workspace, CHURN_COMMITS) {
+            Ok(churn) => churn,
+            Err(e) => return section.paragraph(format!("No git history available: {}", e)),
+        };
+        let mut hotspots: Vec<(String, usize, usize)> = churn
+            .into_iter()
+            .filter_map(|(path, commits)| {
+                let lines = *self.file_lines.get(&self.workspace.join(&path).to_string_lossy().to_string())?;
+                Some((path, commits, lines))
+            })
+            .collect();
+        hotspots.sort_by(|a, b| (b.1 * b.2).cmp(&(a.1 * a.2)).then_with(|| a.0.cmp(&b.0)));
+        let rows = hotspots
+            .into_iter()
+            .take(TOP_ROWS)
+            .map(|(path, commits, lines)| vec![path, commits.to_string(), lines.to_string()])
+            .collect();
+        section
+            .paragraph(format!("Ranked by commits in the last {} commits times current line count.", CHURN_COMMITS))
+            .table(&["File", "Commits", "Lines"], rows)
+    }
+
+    fn diagnostics(&self) -> Section {
+        let mut by_severity: HashMap<&str, usize> = HashMap::new();
+        let mut by_file: HashMap<&str, usize> = HashMap::new();
+        for diagnostic in &self.diagnostics {
+            *by_severity.entry(&diagnostic.severity).or_insert(0) += 1;
+            *by_file.entry(&diagnostic.file).or_insert(0) += 1;
+        }
+        let summary = ["error", "warning", "info"]
+            .iter()
+            .map(|severity| format!("{} {}s", by_severity.get(severity).copied().unwrap_or(0), severity))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut files: Vec<(&str, usize)> = by_file.into_iter().collect();
+        files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        let rows = files
+            .into_iter()
+            .take(TOP_ROWS)
+            .map(|(file, count)| vec![self.relative(file), count.to_string()])
+            .collect();
+        Section::new("Diagnostics")
+            .paragraph(summary)
+            .table(&["File", "Diagnostics"], rows)
+    }
+
+    fn cycles(&self) -> Section {
+        let section = Section::new("Dependency cycles");
+        if self.cycles.is_empty() {
+            return section.paragraph("No import cycles found.");
+        }
+        let rows = self
+            .cycles
+            .iter()
+            .take(TOP_ROWS)
+            .map(|cycle| {
+                let files: Vec<String> = cycle.iter().map(|f| self.relative(f)).collect();
+                vec![cycle.len().to_string(), files.join(" → ")]
+            })
+            .collect();
+        section
+            .paragraph(format!("{} groups of files import each other.", self.cycles.len()))
+            .table(&["Files", "Members"], rows)
+    }
+
+    fn licenses(&self) -> Section {
+        let findings = license_findings(&self.workspace);
+        let concerns = findings.iter().filter(|f| f.concern.is_some()).count();
+        let section = Section::new("Licenses").paragraph(format!(
+            "{} licenses found, {} needing attention.",
+            findings.len(),
+            concerns
+        ));
+        // Findings with concerns first, so they survive truncation in long dependency lists
+        let mut findings = findings;
+        findings.sort_by_key(|f| f.concern.is_none());
+        let rows = findings
+            .into_iter()
+            .map(|f| vec![f.package, f.license, f.source, f.concern.unwrap_or_default()])
+            .collect();
+        section.table(&["Package", "License", "Source", "Concern"], rows)
+    }
+}
+
+/// SPDX-style name of a license text
+fn identify_license(text: &str) -> String {
+    let markers = [
+        ("GNU AFFERO GENERAL PUBLIC", "AGPL-3.0"),
+        ("GNU LESSER GENERAL PUBLIC", "LGPL"),
+        ("GNU GENERAL PUBLIC", "GPL"),
+        ("Apache License", "Apache-2.0"),
+        ("Mozilla Public License", "MPL-2.0"),
+        ("MIT License", "MIT"),
+        ("Permission is hereby granted, free of charge", "MIT"),
+        ("ISC License", "ISC"),
+        ("Redistribution and use in source and binary forms", "BSD"),
+        ("This is free and unencumbered software", "Unlicense"),
+    ];
+    markers
+        .iter()
+        .find(|(marker, _)| text.contains(marker))
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+fn license_concern(license: &str) -> Option<String> {
+    let upper = license.to_uppercase();
+    if upper.contains("GPL") || upper.contains("SSPL") || upper.contains("EUPL") {
+        Some("Copyleft".to_string())
+    } else if upper == "UNKNOWN" || upper == "UNLICENSED" || upper.is_empty() {
+        Some("Unknown license".to_string())
+    } else {
+        None
+    }
+}
+
+/// `license` of a package.json: a string, or the legacy `{ "type": ... }` object
+fn package_license(manifest: &serde_json::Value) -> String {
+    match &manifest["license"] {
+        serde_json::Value::String(license) => license.clone(),
+        serde_json::Value::Object(license) => license.get("type").and_then(|t| t.as_str()).unwrap_or("Unknown").to_string(),
+        _ => "Unknown".to_string(),
+    }
+}
+
+/// The workspace's own license files plus top-level npm dependencies
+pub fn license_findings(workspace: &Path) -> Vec<LicenseFinding> {
+    let mut findings = Vec::new();
+    let project = workspace
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    for entry in fs::read_dir(workspace).into_iter().flatten().filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let upper = name.to_uppercase();
+        if !(upper.starts_with("LICENSE") || upper.starts_with("LICENCE") || upper.starts_with("COPYING")) {
+            continue;
+        }
+        if let Ok(text) = fs::read_to_string(entry.path()) {
+            let license = identify_license(&text);
+            findings.push(LicenseFinding {
+                package: project.clone(),
+                concern: license_concern(&license),
+                license,
+                source: name,
+            });
+        }
+    }
+
+    // Scoped packages live one directory deeper: node_modules/@scope/name
+    let node_modules = workspace.join("node_modules");
+    let mut packages: Vec<PathBuf> = Vec::new();
+    for entry in fs::read_dir(&node_modules).into_iter().flatten().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('@') {
+            packages.extend(fs::read_dir(&path).into_iter().flatten().filter_map(|e| e.ok()).map(|e| e.path()));
+        } else {
+            packages.push(path);
+        }
+    }
+    packages.sort();
+    for package in packages {
+        let manifest_path = package.join("package.json");
+        let manifest: serde_json::Value = match fs::read_to_string(&manifest_path).ok().and_then(|m| serde_json::from_str(&m).ok()) {
+            Some(manifest) => manifest,
+            None => continue,
+        };
+        let license = package_license(&manifest);
+        findings.push(LicenseFinding {
+            package: manifest["name"].as_str().unwrap_or_default().to_string(),
+            concern: license_concern(&license),
+            license,
+            source: manifest_path
+                .strip_prefix(workspace)
+                .unwrap_or(&manifest_path)
+                .to_string_lossy()
+                .replace('\\', "/"),
+        });
+    }
+    findings
+}
+
+fn escape_markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_markdown(title: &str, sections: &[Section]) -> String {
+    let mut out = format!("# {}\n", title);
+    for section in sections {
+        out.push_str(&format!("\n## {}\n\n", section.title));
+        for paragraph in &section.paragraphs {
+            out.push_str(&format!("{}\n\n", paragraph));
+        }
+        if section.headers.is_empty() || section.rows.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("| {} |\n", section.headers.join(" | ")));
+        out.push_str(&format!("|{}\n", " --- |".repeat(section.headers.len())));
+        for row in &section.rows {
+            let cells: Vec<String> = row.iter().map(|c| escape_markdown_cell(c)).collect();
+            out.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+    }
+    out
+}
+
+fn render_html(title: &str, sections: &[Section]) -> String {
+    let mut body = format!("<h1>{}</h1>\n", escape_html(title));
+    for section in sections {
+        body.push_str(&format!("<h2>{}</h2>\n", escape_html(&section.title)));
+        for paragraph in &section.paragraphs {
+            body.push_str(&format!("<p>{}</p>\n", escape_html(paragraph)));
+        }
+        if section.headers.is_empty() || section.rows.is_empty() {
+            continue;
+        }
+        body.push_str("<table>\n<tr>");
+        for header in &section.headers {
+            body.push_str(&format!("<th>{}</th>", escape_html(header)));
+        }
+        body.push_str("</tr>\n");
+        for row in &section.rows {
+            body.push_str("<tr>");
+            for cell in row {
+                body.push_str(&format!("<td>{}</td>", escape_html(cell)));
+            }
+            body.push_str("</tr>\n");
+        }
+        body.push_str("</table>\n");
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n\
+         body {{ font-family: system-ui, sans-serif; max-width: 960px; margin: 2rem auto; color: #222; }}\n\
+         table {{ border-collapse: collapse; width: 100%; margin-bottom: 1.5rem; }}\n\
+         th, td {{ border: 1px solid #ddd; padding: 0.35rem 0.6rem; text-align: left; }}\n\
+         th {{ background: #f5f5f5; }}\n\
+         </style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        body
+    )
+}
+
+/// Render the requested sections (all when empty)
+pub fn generate(input: &ReportInput, format: ReportFormat, sections: &[SectionKind]) -> String {
+    let kinds: &[SectionKind] = if sections.is_empty() { &SectionKind::ALL } else { sections };
+    let rendered: Vec<Section> = kinds
+        .iter()
+        .map(|kind| match kind {
+            SectionKind::Stats => input.stats(),
+            SectionKind::Languages => input.languages(),
+            SectionKind::Hotspots => input.hotspots(),
+            SectionKind::Diagnostics => input.diagnostics(),
+            SectionKind::Cycles => input.cycles(),
+            SectionKind::Licenses => input.licenses(),
+        })
+        .collect();
+
+    let name = input
+        .workspace
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Workspace".to_string());
+    let title = format!("{} report", name);
+    match format {
+        ReportFormat::Markdown => render_markdown(&title, &rendered),
+        ReportFormat::Html => render_html(&title, &rendered),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_formats() {
+        let sections = vec![Section::new("Languages")
+            .paragraph("a < b")
+            .table(&["Language", "Files"], vec![vec!["Rust".to_string(), "3".to_string()]])];
+        let markdown = render_markdown("ws report", &sections);
+        assert!(markdown.contains("| Language | Files |\n| --- | --- |\n| Rust | 3 |"));
+        let html = render_html("ws report", &sections);
+        assert!(html.contains("<p>a &lt; b</p>") && html.contains("<td>Rust</td>"));
+        assert_eq!(license_concern("GPL-3.0-only").as_deref(), Some("Copyleft"));
+    }
+}
@@ -0,0 +1,342 @@
+// Language Resolvers - Per-language import extraction and resolution for the dependency graph
+// Supporting another language means adding a resolver and registering it in `with_defaults`
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::symbols::{self, SymbolInfo};
+
+pub trait LanguageResolver: Send + Sync {
+    /// Registry identifier (e.g. "rust")
+    fn id(&self) -> &'static str;
+    /// File extensions handled, without the dot
+    fn extensions(&self) -> &'static [&'static str];
+    /// Module specifiers imported by a file, as written
+    fn extract_imports(&self, content: &str) -> Vec<String>;
+    /// Declarations of a file with qualified names
+    fn extract_symbols(&self, workspace: &Path, path: &Path, content: &str) -> Vec<SymbolInfo> {
+        symbols::extract(workspace, path, content)
+    }
+    /// Graph node of an import: the file it refers to, or the package name for external modules
+    fn resolve(&self, workspace: &Path, from_file: &Path, import: &str) -> String;
+}
+
+/// Resolvers by file extension; a later registration for an extension replaces the earlier one
+pub struct ResolverRegistry {
+    resolvers: Vec<Box<dyn LanguageResolver>>,
+    by_extension: HashMap<&'static str, usize>,
+}
+
+impl ResolverRegistry {
+    pub fn new() -> Self {
+        Self {
+            resolvers: Vec::new(),
+            by_extension: HashMap::new(),
+        }
+    }
+
+    /// Registry with the built-in languages
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(TypeScriptResolver));
+        registry.register(Box::new(RustResolver));
+        registry.register(Box::new(PythonResolver));
+        registry
+    }
+
+    pub fn register(&mut self, resolver: Box<dyn LanguageResolver>) {
+        let position = self.resolvers.len();
+        for extension in resolver.extensions() {
+            self.by_extension.insert(extension, position);
+        }
+        self.resolvers.push(resolver);
+    }
+
+    pub fn for_path(&self, path: &Path) -> Option<&dyn LanguageResolver> {
+        let extension = path.extension().and_then(|e| e.to_str())?;
+        self.by_extension.get(extension).map(|&i| self.resolvers[i].as_ref())
+    }
+}
+
+/// Resolvers used by the dependency graph
+pub fn registry() -> &'static ResolverRegistry {
+    static REGISTRY: OnceLock<ResolverRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(ResolverRegistry::with_defaults)
+}
+
+/// First path in `candidates` that exists
+fn existing(candidates: impl IntoIterator<Item = PathBuf>) -> Option<PathBuf> {
+    candidates.into_iter().find(|candidate| candidate.exists())
+}
+
+/// ES modules and CommonJS
+pub struct TypeScriptResolver;
+
+impl LanguageResolver for TypeScriptResolver {
+    fn id(&self) -> &'static str {
+        "typescript"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["ts", "tsx", "js", "jsx"]
+    }
+
+    fn extract_imports(&self, content: &str) -> Vec<String> {
+        let mut imports = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+
+            // import { x } from 'module'
+            if line.starts_with("import") {
+                if let Some(from_idx) = line.find("from") {
+                    let module = line[from_idx + 4..]
+                        .trim()
+                        .trim_matches(|c| c == '\'' || c == '"' || c == ';');
+                    imports.push(module.to_string());
+                }
+            }
+
+            // require('module')
+            if let Some(start) = line.find("require(") {
+                let rest = &line[start + 8..];
+                if let Some(end) = rest.find(')') {
+                    imports.push(rest[..end].trim_matches(|c| c == '\'' || c == '"').to_string());
+                }
+            }
+        }
+        imports
+    }
+
+    fn resolve(&self, _workspace: &Path, from_file: &Path, import: &str) -> String {
+        if import.starts_with('.') {
+            // Relative import
+            if let Some(parent) = from_file.parent() {
+                let resolved = parent.join(import);
+                // Try common extensions
+                for ext in &["", ".ts", ".tsx", ".js", ".jsx", "/index.ts", "/index.js"] {
+                    let with_ext = format!("{}{}", resolved.to_string_lossy(), ext);
+                    if PathBuf::from(&with_ext).exists() {
+                        return with_ext;
+                    }
+                }
+                return resolved.to_string_lossy().to_string();
+            }
+        }
+        // Package import - return as-is
+        import.to_string()
+    }
+}
+
+/// `mod` declarations and `use` paths starting at `crate`, `self` or `super`
+pub struct RustResolver;
+
+impl RustResolver {
+    /// `src` directory of the crate containing `file`
+    fn crate_root(file: &Path) -> Option<&Path> {
+        file.ancestors().find(|dir| {
+            dir.file_name().is_some_and(|n| n == "src") && dir.parent().is_some_and(|p| p.join("Cargo.toml").is_file())
+        })
+    }
+
+    /// Directory holding the child modules of `file`
+    fn module_dir(file: &Path) -> Option<PathBuf> {
+        let parent = file.parent()?;
+        match file.file_stem().and_then(|s| s.to_str())? {
+            "main" | "lib" | "mod" => Some(parent.to_path_buf()),
+            stem => Some(parent.join(stem)),
+        }
+    }
+
+    /// Expand one level of `{a, b::c}` groups into separate paths
+    fn expand_group(path: &str) -> Vec<String> {
+        let Some((prefix, group)) = path.split_once("::{") else {
+            return vec![path.to_string()];
+        };
+        let group = group.strip_suffix('}').unwrap_or(group);
+        let mut items = Vec::new();
+        let (mut depth, mut start) = (0, 0);
+        for (i, c) in group.char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                ',' if depth == 0 => {
+                    items.push(&group[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        items.push(&group[start..]);
+        items
+            .into_iter()
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| if item == "self" { prefix.to_string() } else { format!("{}::{}", prefix, item) })
+            .collect()
+    }
+}
+
+impl LanguageResolver for RustResolver {
+    fn id(&self) -> &'static str {
+        "rust"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["rs"]
+    }
+
+    fn extract_imports(&self, content: &str) -> Vec<String> {
+        let mut imports = Vec::new();
+        let mut statement = String::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if statement.is_empty() {
+                let rest = line.strip_prefix("pub ").or_else(|| line.strip_prefix("pub(crate) ")).unwrap_or(line);
+                // `mod name;` loads a child module file; inline `mod name { .. }` does not
+                if let Some(name) = rest.strip_prefix("mod ").and_then(|r| r.strip_suffix(';')) {
+                    imports.push(format!("self::{}", name.trim()));
+                    continue;
+                }
+                if !rest.starts_with("use ") {
+                    continue;
+                }
+                statement.push_str(&rest[4..]);
+            } else {
+                statement.push_str(line);
+            }
+            // Multi-line `use` groups are joined until the semicolon
+            if let Some(path) = statement.strip_suffix(';') {
+                let path = path.split_whitespace().collect::<Vec<_>>().join(" ");
+                imports.extend(Self::expand_group(&path));
+                statement.clear();
+            }
+        }
+        imports
+    }
+
+    fn resolve(&self, _workspace: &Path, from_file: &Path, import: &str) -> String {
+        let import = import.split(" as ").next().unwrap_or(import);
+        let mut segments: Vec<&str> = import.split("::").collect();
+        let base = match segments.first().copied() {
+            Some("crate") => Self::crate_root(from_file).map(Path::to_path_buf),
+            Some("self") => Self::module_dir(from_file),
+            Some("super") => Self::module_dir(from_file).and_then(|dir| dir.parent().map(Path::to_path_buf)),
+            // External crate
+            _ => return segments.first().copied().unwrap_or(import).to_string(),
+        };
+        segments.remove(0);
+        let Some(base) = base else {
+            return import.to_string();
+        };
+        // The longest prefix naming a module file; the rest are items inside it
+        while !segments.is_empty() {
+            let module = base.join(segments.join("/"));
+            if let Some(file) = existing([module.with_extension("rs"), module.join("mod.rs")]) {
+                return file.to_string_lossy().to_string();
+            }
+            segments.pop();
+        }
+        let root = existing([base.join("lib.rs"), base.join("main.rs"), base.join("mod.rs")]);
+        root.map_or_else(|| import.to_string(), |root| root.to_string_lossy().to_string())
+    }
+}
+
+/// `import a.b` and `from .x import y`
+pub struct PythonResolver;
+
+impl LanguageResolver for PythonResolver {
+    fn id(&self) -> &'static str {
+        "python"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["py"]
+    }
+
+    fn extract_imports(&self, content: &str) -> Vec<String> {
+        let mut imports = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if let Some(rest) = line.strip_prefix("from ") {
+                if let Some((module, _)) = rest.split_once(" import") {
+                    imports.push(module.trim().to_string());
+                }
+            } else if let Some(rest) = line.strip_prefix("import ") {
+                for module in rest.split(',') {
+                    let module = module.split(" as ").next().unwrap_or(module).trim();
+                    if !module.is_empty() {
+                        imports.push(module.to_string());
+                    }
+                }
+            }
+        }
+        imports
+    }
+
+    fn resolve(&self, workspace: &Path, from_file: &Path, import: &str) -> String {
+        let dots = import.chars().take_while(|&c| c == '.').count();
+        let module = &import[dots..];
+        let bases: Vec<PathBuf> = if dots > 0 {
+            // `.` is the file's package, each further dot one package up
+            let package = from_file.ancestors().nth(dots);
+            package.map(Path::to_path_buf).into_iter().collect()
+        } else {
+            vec![workspace.to_path_buf(), workspace.join("src")]
+        };
+        for base in bases {
+            let path = module.split('.').filter(|s| !s.is_empty()).fold(base, |path, segment| path.join(segment));
+            if let Some(file) = existing([path.with_extension("py"), path.join("__init__.py")]) {
+                return file.to_string_lossy().to_string();
+            }
+        }
+        if dots > 0 {
+            return import.to_string();
+        }
+        // Installed package
+        module.split('.').next().unwrap_or(module).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_registry_picks_resolver_by_extension() {
+        let registry = ResolverRegistry::with_defaults();
+        assert_eq!(registry.for_path(Path::new("src/app.tsx")).map(|r| r.id()), Some("typescript"));
+        assert_eq!(registry.for_path(Path::new("pkg/mod.py")).map(|r| r.id()), Some("python"));
+        assert!(registry.for_path(Path::new("README.md")).is_none());
+
+        let rust = "mod graph;\nuse crate::paths::{\n    self,\n    CanonicalPath as Key,\n};\nuse std::fs;\n";
+        let imports = RustResolver.extract_imports(rust);
+        assert_eq!(imports, vec!["self::graph", "crate::paths", "crate::paths::CanonicalPath as Key", "std::fs"]);
+        let imports = PythonResolver.extract_imports("import os, json as j\nfrom .models import User\n");
+        assert_eq!(imports, vec!["os", "json", ".models"]);
+    }
+
+    #[test]
+    fn test_resolves_rust_and_python_modules() {
+        let root = std::env::temp_dir().join(crate::storage::new_id("resolver-test"));
+        fs::create_dir_all(root.join("src/graph")).unwrap();
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]").unwrap();
+        for file in ["src/main.rs", "src/paths.rs", "src/graph/mod.rs", "app/__init__.py", "app/models.py"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let main = root.join("src/main.rs");
+        let resolve = |import: &str| RustResolver.resolve(&root, &main, import);
+        assert_eq!(resolve("crate::paths::CanonicalPath"), root.join("src/paths.rs").to_string_lossy());
+        assert_eq!(resolve("self::graph"), root.join("src/graph/mod.rs").to_string_lossy());
+        assert_eq!(resolve("std::collections::HashMap"), "std");
+
+        let views = root.join("app/views.py");
+        assert_eq!(PythonResolver.resolve(&root, &views, ".models"), root.join("app/models.py").to_string_lossy());
+        assert_eq!(PythonResolver.resolve(&root, &views, "app"), root.join("app/__init__.py").to_string_lossy());
+        assert_eq!(PythonResolver.resolve(&root, &views, "requests.adapters"), "requests");
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,234 @@
+// Resource Monitor - Memory, CPU and handle usage of the engine process
+// Sampled periodically so a spike can be seen in context, plus per-subsystem cache sizes
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+use crate::AppState;
+
+/// How often the process is sampled while the app runs
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Samples kept: ten minutes at the default interval
+const MAX_SAMPLES: usize = 120;
+
+/// `/proc/self/stat` times are in USER_HZ, which is 100 on every mainstream kernel
+#[cfg(target_os = "linux")]
+const CLOCK_TICKS_PER_SEC: f64 = 100.0;
+
+/// Fields are `None` where the platform does not expose them cheaply
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ResourceSample {
+    pub timestamp: u64,
+    pub rss_bytes: Option<u64>,
+    /// Share of one core since the previous sample; above 100 when several cores are busy
+    pub cpu_percent: Option<f64>,
+    pub threads: Option<usize>,
+    pub open_files: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CacheSize {
+    pub name: String,
+    pub entries: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ResourceUsage {
+    pub pid: u32,
+    pub uptime_ms: u64,
+    pub current: ResourceSample,
+    /// Oldest first
+    pub history: Vec<ResourceSample>,
+    pub caches: Vec<CacheSize>,
+}
+
+/// Raw counters read from the OS; CPU time is cumulative
+#[derive(Default)]
+struct ProcessCounters {
+    rss_bytes: Option<u64>,
+    cpu_seconds: Option<f64>,
+    threads: Option<usize>,
+    open_files: Option<usize>,
+}
+
+#[cfg(target_os = "linux")]
+fn read_counters() -> ProcessCounters {
+    let status = std::fs::read_to_string("/proc/self/status").unwrap_or_default();
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|value| value.parse::<u64>().ok())
+    };
+    // Fields after the parenthesized command name; utime and stime are the 14th and 15th overall
+    let stat = std::fs::read_to_string("/proc/self/stat").unwrap_or_default();
+    let cpu_seconds = stat.rsplit_once(')').and_then(|(_, rest)| {
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        let utime: f64 = fields.get(11)?.parse().ok()?;
+        let stime: f64 = fields.get(12)?.parse().ok()?;
+        Some((utime + stime) / CLOCK_TICKS_PER_SEC)
+    });
+    ProcessCounters {
+        rss_bytes: field("VmRSS:").map(|kb| kb * 1024),
+        cpu_seconds,
+        threads: field("Threads:").map(|n| n as usize),
+        open_files: std::fs::read_dir("/proc/self/fd").ok().map(|entries| entries.count()),
+    }
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn read_counters() -> ProcessCounters {
+    use std::process::Command;
+    let pid = std::process::id().to_string();
+    let ps = Command::new("ps").args(["-o", "rss=,time=", "-p", &pid]).output().ok();
+    let ps = ps.map(|o| String::from_utf8_lossy(&o.stdout).to_string()).unwrap_or_default();
+    let mut fields = ps.split_whitespace();
+    let rss_bytes = fields.next().and_then(|kb| kb.parse::<u64>().ok()).map(|kb| kb * 1024);
+    // `[[dd-]hh:]mm:ss.cc`
+    let cpu_seconds = fields.next().map(|time| {
+        let (days, time) = time.split_once('-').unwrap_or(("0", time));
+        let seconds = time
+            .split(':')
+            .fold(0.0, |total, part| total * 60.0 + part.parse::<f64>().unwrap_or(0.0));
+        days.parse::<f64>().unwrap_or(0.0) * 86_400.0 + seconds
+    });
+    let threads = Command::new("ps")
+        .args(["-M", "-p", &pid])
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().count().saturating_sub(1));
+    let open_files = Command::new("lsof")
+        .args(["-p", &pid, "-F", "f"])
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().filter(|l| l.starts_with('f')).count());
+    ProcessCounters {
+        rss_bytes,
+        cpu_seconds,
+        threads,
+        open_files,
+    }
+}
+
+#[cfg(windows)]
+fn read_counters() -> ProcessCounters {
+    ProcessCounters::default()
+}
+
+struct MonitorState {
+    samples: VecDeque<ResourceSample>,
+    last_cpu: Option<(Instant, f64)>,
+}
+
+pub struct ResourceMonitor {
+    started: Instant,
+    state: Mutex<MonitorState>,
+}
+
+impl ResourceMonitor {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            state: Mutex::new(MonitorState {
+                samples: VecDeque::new(),
+                last_cpu: None,
+            }),
+        }
+    }
+
+    /// Read the process counters and keep the sample in the history
+    pub fn sample(&self) -> ResourceSample {
+        let counters = read_counters();
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let cpu_percent = match (state.last_cpu, counters.cpu_seconds) {
+            (Some((at, previous)), Some(current)) => {
+                let wall = now.duration_since(at).as_secs_f64();
+                (wall > 0.0).then(|| ((current - previous) / wall * 100.0).max(0.0))
+            }
+            _ => None,
+        };
+        if let Some(cpu) = counters.cpu_seconds {
+            state.last_cpu = Some((now, cpu));
+        }
+        let sample = ResourceSample {
+            timestamp: storage::now_millis(),
+            rss_bytes: counters.rss_bytes,
+            cpu_percent,
+            threads: counters.threads,
+            open_files: counters.open_files,
+        };
+        if state.samples.len() >= MAX_SAMPLES {
+            state.samples.pop_front();
+        }
+        state.samples.push_back(sample.clone());
+        sample
+    }
+
+    pub fn usage(&self, caches: Vec<CacheSize>) -> ResourceUsage {
+        let current = self.sample();
+        let history = self.state.lock().unwrap().samples.iter().cloned().collect();
+        ResourceUsage {
+            pid: std::process::id(),
+            uptime_ms: self.started.elapsed().as_millis() as u64,
+            current,
+            history,
+            caches,
+        }
+    }
+}
+
+/// Entry counts of the in-memory subsystems
+pub fn cache_sizes(state: &AppState) -> Vec<CacheSize> {
+    let size = |name: &str, entries: usize| CacheSize {
+        name: name.to_string(),
+        entries,
+    };
+    let (files, words) = {
+        let index = state.file_index.lock().unwrap();
+        (index.file_count(), index.indexed_word_count())
+    };
+    let (graph_files, symbols) = {
+        let graph = state.code_graph.lock().unwrap();
+        (graph.file_count(), graph.symbol_count())
+    };
+    vec![
+        size("file_index.files", files),
+        size("file_index.words", words),
+        size("code_graph.files", graph_files),
+        size("code_graph.symbols", symbols),
+        size("diagnostics", state.diagnostics.lock().unwrap().count()),
+        size("ai_response_cache", state.ai_providers.lock().unwrap().cache_stats().entries),
+        size("chat_sessions", state.chat.lock().unwrap().session_count()),
+        size("agent_runs", state.agent_runs.lock().unwrap().len()),
+        size("line_indexes", state.line_indexes.lock().unwrap().cached_count()),
+        size("event_subscriptions", state.events.subscriber_count()),
+        size("background_processes", state.processes.list().len()),
+        size("debug_sessions", state.debug_sessions.lock().unwrap().len()),
+        size("perf_profiles", state.perf_profiles.lock().unwrap().len()),
+        size("documents", state.documents.lock().unwrap().count()),
+        size("standby_workspaces", state.standby.lock().unwrap().count()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sample_reports_memory_and_cpu() {
+        let monitor = ResourceMonitor::new();
+        assert!(monitor.sample().cpu_percent.is_none());
+        std::thread::sleep(Duration::from_millis(20));
+        let sample = monitor.sample();
+        assert!(sample.rss_bytes.unwrap() > 0);
+        assert!(sample.cpu_percent.is_some());
+        assert_eq!(monitor.usage(Vec::new()).history.len(), 3);
+    }
+}
@@ -0,0 +1,202 @@
+// Rope - Chunked text buffer for editing very large files in place
+// Edits touch only the chunks they overlap; line lookups skip whole chunks
+
+use anyhow::{anyhow, Result};
+
+use crate::changeset::Position;
+
+/// Preferred chunk size; chunks split after a newline near this size when possible
+const CHUNK_BYTES: usize = 64 * 1024;
+
+struct Chunk {
+    text: String,
+    newlines: usize,
+}
+
+impl Chunk {
+    fn new(text: String) -> Self {
+        let newlines = text.bytes().filter(|&b| b == b'\n').count();
+        Self { text, newlines }
+    }
+}
+
+pub struct Rope {
+    /// Never empty; an empty document is one empty chunk
+    chunks: Vec<Chunk>,
+    len: usize,
+}
+
+/// Split text into chunks of about `CHUNK_BYTES`, on char boundaries
+fn split_chunks(text: &str) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while rest.len() > CHUNK_BYTES {
+        let mut end = CHUNK_BYTES;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        // Prefer ending at a line break in the last quarter of the chunk
+        let mut low = CHUNK_BYTES * 3 / 4;
+        while !rest.is_char_boundary(low) {
+            low -= 1;
+        }
+        if let Some(newline) = rest[low..end].rfind('\n') {
+            end = low + newline + 1;
+        }
+        chunks.push(Chunk::new(rest[..end].to_string()));
+        rest = &rest[end..];
+    }
+    chunks.push(Chunk::new(rest.to_string()));
+    chunks
+}
+
+impl Rope {
+    pub fn from_text(text: &str) -> Self {
+        Self {
+            chunks: split_chunks(text),
+            len: text.len(),
+        }
+    }
+
+    /// Length in bytes
+    pub fn len_bytes(&self) -> usize {
+        self.len
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.chunks.iter().map(|c| c.newlines).sum::<usize>() + 1
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = &str> {
+        self.chunks.iter().map(|c| c.text.as_str())
+    }
+
+    /// Chunk holding byte `offset` and the offset within it; the end maps into the last chunk
+    fn locate(&self, offset: usize) -> (usize, usize) {
+        let mut start = 0;
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            if offset < start + chunk.text.len() || i == self.chunks.len() - 1 {
+                return (i, offset - start);
+            }
+            start += chunk.text.len();
+        }
+        unreachable!("rope has at least one chunk")
+    }
+
+    /// Byte offset where 1-based `line` starts
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        if line == 0 {
+            return None;
+        }
+        let mut remaining = line - 1;
+        let mut start = 0;
+        for chunk in &self.chunks {
+            if remaining > chunk.newlines {
+                remaining -= chunk.newlines;
+                start += chunk.text.len();
+                continue;
+            }
+            if remaining == 0 {
+                return Some(start);
+            }
+            let (at, _) = chunk.text.match_indices('\n').nth(remaining - 1)?;
+            return Some(start + at + 1);
+        }
+        None
+    }
+
+    /// Byte offset of a position; columns past the end of a line clamp to it
+    pub fn offset_of(&self, position: Position) -> Result<usize> {
+        let start = self
+            .line_start(position.line)
+            .ok_or_else(|| anyhow!("Position {}:{} is out of range", position.line, position.column))?;
+        let mut offset = start;
+        let mut column = 0;
+        let (first, within) = self.locate(start);
+        'chunks: for (i, chunk) in self.chunks[first..].iter().enumerate() {
+            let text = if i == 0 { &chunk.text[within..] } else { chunk.text.as_str() };
+            for c in text.chars() {
+                if column == position.column || c == '\n' || c == '\r' {
+                    break 'chunks;
+                }
+                offset += c.len_utf8();
+                column += 1;
+            }
+        }
+        Ok(offset)
+    }
+
+    /// Text between two byte offsets
+    pub fn slice(&self, start: usize, end: usize) -> Result<String> {
+        if start > end || end > self.len {
+            return Err(anyhow!("Range {}..{} is out of bounds", start, end));
+        }
+        let mut text = String::with_capacity(end - start);
+        let mut chunk_start = 0;
+        for chunk in &self.chunks {
+            let chunk_end = chunk_start + chunk.text.len();
+            if chunk_end > start && chunk_start < end {
+                let from = start.saturating_sub(chunk_start);
+                let to = (end - chunk_start).min(chunk.text.len());
+                let part = chunk
+                    .text
+                    .get(from..to)
+                    .ok_or_else(|| anyhow!("Range {}..{} splits a character", start, end))?;
+                text.push_str(part);
+            }
+            if chunk_end >= end {
+                break;
+            }
+            chunk_start = chunk_end;
+        }
+        Ok(text)
+    }
+
+    /// Replace the bytes `start..end` with `text`
+    pub fn replace(&mut self, start: usize, end: usize, text: &str) -> Result<()> {
+        if start > end || end > self.len {
+            return Err(anyhow!("Range {}..{} is out of bounds", start, end));
+        }
+        let (first, first_offset) = self.locate(start);
+        let (last, last_offset) = self.locate(end);
+        let (head, tail) = (&self.chunks[first].text, &self.chunks[last].text);
+        if !head.is_char_boundary(first_offset) || !tail.is_char_boundary(last_offset) {
+            return Err(anyhow!("Range {}..{} splits a character", start, end));
+        }
+        let mut middle = String::with_capacity(first_offset + text.len() + tail.len() - last_offset);
+        middle.push_str(&head[..first_offset]);
+        middle.push_str(text);
+        middle.push_str(&tail[last_offset..]);
+        self.chunks.splice(first..=last, split_chunks(&middle));
+        self.chunks.retain(|c| !c.text.is_empty());
+        if self.chunks.is_empty() {
+            self.chunks.push(Chunk::new(String::new()));
+        }
+        self.len = self.len - (end - start) + text.len();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edits_across_chunks() {
+        let line = "0123456789abcdef\n";
+        let text = line.repeat(10_000);
+        let mut rope = Rope::from_text(&text);
+        assert!(rope.chunks().count() > 1);
+        assert_eq!(rope.line_count(), 10_001);
+        assert_eq!(rope.line_start(5000), Some(4999 * line.len()));
+
+        let at = rope.offset_of(Position { line: 4000, column: 10 }).unwrap();
+        assert_eq!(at, 3999 * line.len() + 10);
+        let end = at + 40 * line.len();
+        rope.replace(at, end, "XY").unwrap();
+        assert_eq!(rope.len_bytes(), text.len() - 40 * line.len() + 2);
+        assert_eq!(rope.slice(at - 10, at + 8).unwrap(), "0123456789XYabcdef");
+        let joined: String = rope.chunks().collect();
+        assert_eq!(joined.len(), rope.len_bytes());
+    }
+}
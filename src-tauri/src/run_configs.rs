@@ -0,0 +1,230 @@
+// Run Configurations - Named, validated launch settings per workspace
+// Each one runs as a task, a background process or a debug session
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::debugger::{AdapterKind, DebugSessionInfo, LaunchRequest};
+use crate::processes::ProcessInfo;
+use crate::storage;
+use crate::task_runner::{TaskOutput, TaskSpec};
+use crate::workspace_env;
+
+/// Default limit for `RunKind::Task` runs
+const DEFAULT_TASK_TIMEOUT_SECS: u64 = 600;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RunKind {
+    /// Run to completion and return the output
+    Task {
+        #[serde(default)]
+        timeout_secs: Option<u64>,
+    },
+    /// Keep running in the background, e.g. a dev server
+    Background,
+    Debug {
+        adapter: AdapterKind,
+        #[serde(default)]
+        stop_on_entry: bool,
+        #[serde(default)]
+        adapter_command: Option<Vec<String>>,
+        #[serde(default)]
+        options: Option<Value>,
+    },
+}
+
+/// What `run_configuration` started
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RunOutcome {
+    Task { output: TaskOutput },
+    Background { process: ProcessInfo },
+    Debug { session: DebugSessionInfo },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RunConfiguration {
+    /// Assigned on first save
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Relative to the workspace
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Run to completion first; a failure aborts the launch
+    #[serde(default)]
+    pub pre_launch_task: Option<TaskSpec>,
+    /// Run tasks and background processes inside this container
+    #[serde(default)]
+    pub container: Option<String>,
+    pub kind: RunKind,
+}
+
+impl RunConfiguration {
+    pub fn task_spec(&self) -> TaskSpec {
+        TaskSpec {
+            command: self.program.clone(),
+            args: self.args.clone(),
+            cwd: self.cwd.clone(),
+            env: self.env.clone(),
+            container: self.container.clone(),
+        }
+    }
+
+    pub fn task_timeout_secs(&self) -> u64 {
+        match self.kind {
+            RunKind::Task { timeout_secs } => timeout_secs.unwrap_or(DEFAULT_TASK_TIMEOUT_SECS),
+            _ => DEFAULT_TASK_TIMEOUT_SECS,
+        }
+    }
+
+    /// Debug launch for `RunKind::Debug` configurations
+    pub fn launch_request(&self) -> Option<LaunchRequest> {
+        match &self.kind {
+            RunKind::Debug {
+                adapter,
+                stop_on_entry,
+                adapter_command,
+                options,
+            } => Some(LaunchRequest {
+                adapter: *adapter,
+                program: self.program.clone(),
+                args: self.args.clone(),
+                cwd: self.cwd.clone(),
+                env: self.env.clone(),
+                stop_on_entry: *stop_on_entry,
+                breakpoints: Vec::new(),
+                adapter_command: adapter_command.clone(),
+                options: options.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+pub fn configs_path(workspace: &Path) -> PathBuf {
+    storage::data_dir(workspace).join("run_configurations.json")
+}
+
+pub fn list(workspace: &Path) -> Result<Vec<RunConfiguration>> {
+    Ok(storage::read_json(&configs_path(workspace))?.unwrap_or_default())
+}
+
+pub fn get(workspace: &Path, id: &str) -> Result<RunConfiguration> {
+    list(workspace)?
+        .into_iter()
+        .find(|c| c.id == id)
+        .ok_or_else(|| anyhow!("Unknown run configuration: {}", id))
+}
+
+fn validate_cwd(cwd: &Option<String>) -> Result<()> {
+    if let Some(cwd) = cwd {
+        let path = Path::new(cwd);
+        if path.is_absolute() || path.components().any(|c| matches!(c, Component::ParentDir)) {
+            return Err(anyhow!("Working directory must be inside the workspace: {}", cwd));
+        }
+    }
+    Ok(())
+}
+
+fn validate(workspace: &Path, config: &RunConfiguration, others: &[RunConfiguration]) -> Result<()> {
+    if config.name.trim().is_empty() {
+        return Err(anyhow!("Run configuration needs a name"));
+    }
+    if others.iter().any(|c| c.id != config.id && c.name == config.name) {
+        return Err(anyhow!("A run configuration named {} already exists", config.name));
+    }
+    if config.program.trim().is_empty() {
+        return Err(anyhow!("Run configuration {} has no program", config.name));
+    }
+    validate_cwd(&config.cwd)?;
+    let cwd = workspace.join(config.cwd.as_deref().unwrap_or(""));
+    if !cwd.is_dir() {
+        return Err(anyhow!("Working directory does not exist: {}", cwd.display()));
+    }
+    for name in config.env.keys() {
+        workspace_env::validate_name(name)?;
+    }
+    if let Some(task) = &config.pre_launch_task {
+        if task.command.trim().is_empty() {
+            return Err(anyhow!("Pre-launch task of {} has no command", config.name));
+        }
+        validate_cwd(&task.cwd)?;
+    }
+    // Debug adapters launch a file, not a command on PATH
+    if matches!(config.kind, RunKind::Debug { .. }) && !cwd.join(&config.program).exists() {
+        return Err(anyhow!("Program not found: {}", config.program));
+    }
+    Ok(())
+}
+
+/// Create (empty id) or replace a configuration after validating it
+pub fn save(workspace: &Path, mut config: RunConfiguration) -> Result<RunConfiguration> {
+    let mut configs = list(workspace)?;
+    if config.id.is_empty() {
+        config.id = storage::new_id("run");
+    } else if !configs.iter().any(|c| c.id == config.id) {
+        return Err(anyhow!("Unknown run configuration: {}", config.id));
+    }
+    validate(workspace, &config, &configs)?;
+    match configs.iter_mut().find(|c| c.id == config.id) {
+        Some(existing) => *existing = config.clone(),
+        None => configs.push(config.clone()),
+    }
+    storage::write_json(&configs_path(workspace), &configs)?;
+    Ok(config)
+}
+
+pub fn delete(workspace: &Path, id: &str) -> Result<()> {
+    let mut configs = list(workspace)?;
+    let before = configs.len();
+    configs.retain(|c| c.id != id);
+    if configs.len() == before {
+        return Err(anyhow!("Unknown run configuration: {}", id));
+    }
+    storage::write_json(&configs_path(workspace), &configs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_validates() {
+        let workspace = std::env::temp_dir().join(storage::new_id("run-configs-test"));
+        std::fs::create_dir_all(&workspace).unwrap();
+        let config = RunConfiguration {
+            id: String::new(),
+            name: "dev".to_string(),
+            program: "npm".to_string(),
+            args: vec!["run".to_string(), "dev".to_string()],
+            env: HashMap::new(),
+            cwd: None,
+            pre_launch_task: None,
+            container: None,
+            kind: RunKind::Background,
+        };
+        let saved = save(&workspace, config.clone()).unwrap();
+        assert!(saved.id.starts_with("run-"));
+        assert!(save(&workspace, config.clone()).is_err());
+        let escaping = RunConfiguration {
+            name: "other".to_string(),
+            cwd: Some("../elsewhere".to_string()),
+            ..config
+        };
+        assert!(save(&workspace, escaping).is_err());
+
+        delete(&workspace, &saved.id).unwrap();
+        assert!(list(&workspace).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+}
@@ -0,0 +1,300 @@
+// Rust Rules - Syntax-tree checks for Rust sources
+// Tests are exempt from error-handling rules; async and loop context is tracked per function
+
+use tree_sitter::Node;
+
+use crate::syntax;
+use crate::CodeSuggestion;
+
+/// Calls that block the thread, by path; an `.await` on the result means an async namesake
+const BLOCKING_CALLS: &[&str] = &["thread::sleep", "fs::", "File::open", "File::create", "reqwest::blocking::"];
+
+/// Calls whose closure argument runs off the async executor
+const BLOCKING_OFFLOAD: &[&str] = &["spawn_blocking", "block_in_place", "thread::spawn"];
+
+#[derive(Clone, Copy, Default)]
+struct Context {
+    in_test: bool,
+    in_async: bool,
+    /// Loops enclosing the node within its function
+    loops: usize,
+}
+
+struct Checker<'a> {
+    content: &'a str,
+    suggestions: Vec<CodeSuggestion>,
+}
+
+/// The expression of an expression statement, or the node itself
+fn expression(statement: Node) -> Option<Node> {
+    if statement.kind() == "expression_statement" {
+        syntax::named_children(statement).into_iter().next()
+    } else {
+        Some(statement)
+    }
+}
+
+fn has_child_kind(node: Node, kind: &str) -> bool {
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).any(|c| c.kind() == kind);
+    found
+}
+
+/// Whether a path like `std::fs::read` names one of `calls`, matching whole segments
+fn is_call_to(path: &str, calls: &[&str]) -> bool {
+    calls.iter().any(|call| {
+        let qualified = format!("::{}", call);
+        path.starts_with(call) || path.contains(&qualified)
+    })
+}
+
+impl<'a> Checker<'a> {
+    fn text(&self, node: Node) -> &'a str {
+        &self.content[node.byte_range()]
+    }
+
+    fn push(&mut self, node: Node, kind: &str, severity: &str, message: String) {
+        self.suggestions.push(CodeSuggestion {
+            kind: kind.to_string(),
+            message,
+            line: node.start_position().row + 1,
+            column: node.start_position().column,
+            severity: severity.to_string(),
+            fix: None,
+        });
+    }
+
+    /// Outer attributes of an item, without whitespace: `#[cfg(test)]`
+    fn attributes(&self, node: Node) -> Vec<String> {
+        let mut attributes = Vec::new();
+        let mut sibling = node.prev_named_sibling();
+        while let Some(previous) = sibling {
+            match previous.kind() {
+                "attribute_item" => attributes.push(self.text(previous).split_whitespace().collect()),
+                kind if kind.contains("comment") => {}
+                _ => break,
+            }
+            sibling = previous.prev_named_sibling();
+        }
+        attributes
+    }
+
+    fn is_test_item(&self, node: Node) -> bool {
+        self.attributes(node)
+            .iter()
+            .any(|a| a == "#[cfg(test)]" || a == "#[test]" || a.ends_with("::test]") || a.starts_with("#[tokio::test"))
+    }
+
+    fn walk(&mut self, node: Node, context: Context) {
+        let mut context = context;
+        match node.kind() {
+            "mod_item" if self.is_test_item(node) => context.in_test = true,
+            "function_item" => {
+                let modifiers = syntax::named_children(node).into_iter().find(|c| c.kind() == "function_modifiers");
+                let modifiers = modifiers.map(|m| self.text(m)).unwrap_or("");
+                context = Context {
+                    in_test: context.in_test || self.is_test_item(node),
+                    in_async: modifiers.contains("async"),
+                    loops: 0,
+                };
+                if modifiers.contains("unsafe") {
+                    let message = "Unsafe function - document the invariants callers must uphold".to_string();
+                    self.push(node, "security", "info", message);
+                }
+                self.check_must_use(node);
+            }
+            "async_block" => context.in_async = true,
+            "unsafe_block" => {
+                self.push(node, "security", "info", "Unsafe block detected - ensure memory safety is maintained".to_string())
+            }
+            "impl_item" if has_child_kind(node, "unsafe") => {
+                self.push(node, "security", "info", "Unsafe impl - ensure the trait's safety contract holds".to_string())
+            }
+            "block" => self.check_collect_loops(node),
+            "call_expression" => {
+                if self.check_call(node, context) {
+                    return;
+                }
+            }
+            "macro_invocation" if !context.in_test => {
+                let name = node.child_by_field_name("macro").map(|m| self.text(m)).unwrap_or("");
+                if name == "panic" || name == "std::panic" {
+                    self.push(node, "quality", "warning", "Consider returning Result instead of using panic!".to_string());
+                }
+            }
+            "for_expression" => {
+                // The iterable is evaluated once; only the body repeats
+                if let Some(value) = node.child_by_field_name("value") {
+                    self.walk(value, context);
+                }
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.walk(body, Context { loops: context.loops + 1, ..context });
+                }
+                return;
+            }
+            "while_expression" | "loop_expression" => context.loops += 1,
+            _ => {}
+        }
+        for child in syntax::named_children(node) {
+            self.walk(child, context);
+        }
+    }
+
+    /// Method and path calls; true when the arguments were walked already
+    fn check_call(&mut self, node: Node, context: Context) -> bool {
+        let function = match node.child_by_field_name("function") {
+            Some(function) => function,
+            None => return false,
+        };
+        if function.kind() == "field_expression" {
+            let method = function.child_by_field_name("field").map(|f| self.text(f)).unwrap_or("");
+            let field = function.child_by_field_name("field").unwrap_or(function);
+            if method == "unwrap" && !context.in_test {
+                let message = "Consider using ? operator or proper error handling instead of unwrap()".to_string();
+                self.push(field, "quality", "warning", message);
+            }
+            if method == "clone" && context.loops > 0 {
+                self.push(
+                    field,
+                    "performance",
+                    "info",
+                    "clone() inside a loop copies on every iteration - borrow, or clone once before the loop".to_string(),
+                );
+            }
+            return false;
+        }
+
+        let path: String = self.text(function).split_whitespace().collect();
+        if is_call_to(&path, BLOCKING_OFFLOAD) {
+            if let Some(arguments) = node.child_by_field_name("arguments") {
+                self.walk(arguments, Context { in_async: false, ..context });
+            }
+            return true;
+        }
+        let awaited = node.parent().is_some_and(|p| p.kind() == "await_expression");
+        if context.in_async && !awaited && is_call_to(&path, BLOCKING_CALLS) {
+            self.push(
+                node,
+                "performance",
+                "warning",
+                format!("{} blocks the async executor - use its async counterpart or spawn_blocking", path),
+            );
+        }
+        false
+    }
+
+    /// `let mut v = Vec::new();` directly followed by a loop that only pushes into `v`
+    fn check_collect_loops(&mut self, block: Node) {
+        let statements = syntax::named_children(block);
+        for pair in statements.windows(2) {
+            let (declaration, next) = (pair[0], pair[1]);
+            if declaration.kind() != "let_declaration" || !has_child_kind(declaration, "mutable_specifier") {
+                continue;
+            }
+            let pattern = declaration.child_by_field_name("pattern");
+            let (pattern, value) = match (pattern, declaration.child_by_field_name("value")) {
+                (Some(pattern), Some(value)) if pattern.kind() == "identifier" => (self.text(pattern), self.text(value)),
+                _ => continue,
+            };
+            if !matches!(value, "Vec::new()" | "vec![]") {
+                continue;
+            }
+            let body = match expression(next)
+                .filter(|l| l.kind() == "for_expression")
+                .and_then(|l| l.child_by_field_name("body"))
+            {
+                Some(body) => body,
+                None => continue,
+            };
+            let body_statements = syntax::named_children(body);
+            let pushes_only = body_statements.len() == 1
+                && expression(body_statements[0])
+                    .filter(|c| c.kind() == "call_expression")
+                    .and_then(|c| c.child_by_field_name("function"))
+                    .filter(|f| f.kind() == "field_expression")
+                    .is_some_and(|f| {
+                        f.child_by_field_name("value").map(|v| self.text(v)) == Some(pattern)
+                            && f.child_by_field_name("field").map(|m| self.text(m)) == Some("push")
+                    });
+            if pushes_only {
+                self.push(
+                    declaration,
+                    "quality",
+                    "info",
+                    format!("`{}` is built by a loop of push calls - collect it from an iterator instead", pattern),
+                );
+            }
+        }
+    }
+
+    /// Public builder-style methods (`fn with_x(self) -> Self`) whose result must not be dropped
+    fn check_must_use(&mut self, function: Node) {
+        let in_inherent_impl = function
+            .parent()
+            .and_then(|list| list.parent())
+            .is_some_and(|item| item.kind() == "impl_item" && item.child_by_field_name("trait").is_none());
+        let public = syntax::named_children(function)
+            .into_iter()
+            .any(|c| c.kind() == "visibility_modifier" && self.text(c) == "pub");
+        let returns_self = function.child_by_field_name("return_type").is_some_and(|t| self.text(t) == "Self");
+        let takes_self = function
+            .child_by_field_name("parameters")
+            .and_then(|p| syntax::named_children(p).into_iter().next())
+            .is_some_and(|first| first.kind() == "self_parameter" && !self.text(first).starts_with('&'));
+        if !(in_inherent_impl && public && returns_self && takes_self) {
+            return;
+        }
+        if self.attributes(function).iter().any(|a| a.starts_with("#[must_use")) {
+            return;
+        }
+        let name = function.child_by_field_name("name").map(|n| self.text(n)).unwrap_or("");
+        let spot = function.child_by_field_name("name").unwrap_or(function);
+        self.push(
+            spot,
+            "quality",
+            "info",
+            format!("`{}` returns a changed copy of self - add #[must_use] so an ignored result warns", name),
+        );
+    }
+}
+
+/// Rust findings for a file; everything in `tests/`, `benches/` and `#[cfg(test)]` code counts as test code
+pub fn analyze(file_path: &str, content: &str) -> Vec<CodeSuggestion> {
+    let tree = match syntax::parse(file_path, content) {
+        Some(tree) => tree,
+        None => return Vec::new(),
+    };
+    let normalized = file_path.replace('\\', "/");
+    let in_test = normalized.contains("/tests/") || normalized.contains("/benches/") || normalized.starts_with("tests/");
+    let mut checker = Checker {
+        content,
+        suggestions: Vec::new(),
+    };
+    checker.walk(tree.root_node(), Context { in_test, ..Context::default() });
+    checker.suggestions.sort_by_key(|s| (s.line, s.column));
+    checker.suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(content: &str) -> Vec<(usize, String)> {
+        analyze("src/lib.rs", content).into_iter().map(|s| (s.line, s.message)).collect()
+    }
+
+    #[test]
+    fn test_unwrap_outside_tests_only() {
+        let source = "fn load() -> u32 {\n    read().unwrap()\n}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn loads() {\n        read().unwrap();\n    }\n}\n";
+        let found = messages(source);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, 2);
+    }
+
+    #[test]
+    fn test_loops_async_and_builders() {
+        let source = "async fn handle(items: Vec<Item>) {\n    std::thread::sleep(DELAY);\n    let data = tokio::fs::read(PATH).await;\n    let mut names = Vec::new();\n    for item in items.clone() {\n        names.push(item.name.clone());\n    }\n}\n\nimpl Builder {\n    pub fn with_name(mut self, name: String) -> Self {\n        self.name = name;\n        self\n    }\n}\n";
+        let lines: Vec<usize> = messages(source).into_iter().map(|(line, _)| line).collect();
+        assert_eq!(lines, vec![2, 4, 6, 11]);
+    }
+}
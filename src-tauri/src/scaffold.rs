@@ -0,0 +1,428 @@
+// Scaffold - Project skeletons from built-in and user templates
+// Variables are interpolated as `{{name}}` in paths and contents
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::task_runner::{self, TaskOutput, TaskSpec};
+use crate::trust::TrustStore;
+
+/// Manifest file of a user template directory
+const MANIFEST: &str = "template.json";
+/// Timeout for each post-create command
+const POST_CREATE_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TemplateVariable {
+    pub name: String,
+    pub description: String,
+    /// Required if absent
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TemplateFile {
+    pub path: String,
+    pub content: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Template {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+    #[serde(default)]
+    pub files: Vec<TemplateFile>,
+    /// Commands run in the new project after the files are written
+    #[serde(default)]
+    pub post_create: Vec<TaskSpec>,
+    /// Built-in templates ship with the IDE
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TemplateSummary {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub variables: Vec<TemplateVariable>,
+    pub builtin: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScaffoldResult {
+    pub target: String,
+    pub files: Vec<String>,
+    pub post_create: Vec<TaskOutput>,
+}
+
+fn file(path: &str, content: &str) -> TemplateFile {
+    TemplateFile {
+        path: path.to_string(),
+        content: content.to_string(),
+    }
+}
+
+fn variable(name: &str, description: &str, default: Option<&str>) -> TemplateVariable {
+    TemplateVariable {
+        name: name.to_string(),
+        description: description.to_string(),
+        default: default.map(str::to_string),
+    }
+}
+
+fn task(command: &str, args: &[&str]) -> TaskSpec {
+    TaskSpec {
+        command: command.to_string(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        cwd: None,
+        env: HashMap::new(),
+        container: None,
+    }
+}
+
+fn builtin_templates() -> Vec<Template> {
+    let name = variable("name", "Project name", None);
+    let rust_gitignore = file(".gitignore", "/target\n");
+
+    vec![
+        Template {
+            id: "rust-bin".to_string(),
+            name: "Rust binary".to_string(),
+            description: "Cargo application with a main function".to_string(),
+            variables: vec![name.clone()],
+            files: vec![
+                file(
+                    "Cargo.toml",
+                    "[package]\nname = \"{{name_kebab}}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n",
+                ),
+                file("src/main.rs", "fn main() {\n    println!(\"Hello from {{name}}!\");\n}\n"),
+                rust_gitignore.clone(),
+            ],
+            post_create: vec![task("git", &["init"])],
+            builtin: true,
+        },
+        Template {
+            id: "rust-lib".to_string(),
+            name: "Rust library".to_string(),
+            description: "Cargo library crate with a unit test".to_string(),
+            variables: vec![name.clone()],
+            files: vec![
+                file(
+                    "Cargo.toml",
+                    "[package]\nname = \"{{name_kebab}}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n",
+                ),
+                file(
+                    "src/lib.rs",
+                    "pub fn add(left: u64, right: u64) -> u64 {\n    left + right\n}\n\n#[cfg(test)]\nmod tests {\n    use super::*;\n\n    #[test]\n    fn it_works() {\n        assert_eq!(add(2, 2), 4);\n    }\n}\n",
+                ),
+                rust_gitignore,
+            ],
+            post_create: vec![task("git", &["init"])],
+            builtin: true,
+        },
+        Template {
+            id: "vite-react".to_string(),
+            name: "Vite + React".to_string(),
+            description: "React app with TypeScript, bundled by Vite".to_string(),
+            variables: vec![name.clone()],
+            files: vec![
+                file(
+                    "package.json",
+                    "{\n  \"name\": \"{{name_kebab}}\",\n  \"private\": true,\n  \"version\": \"0.1.0\",\n  \"type\": \"module\",\n  \"scripts\": {\n    \"dev\": \"vite\",\n    \"build\": \"tsc && vite build\",\n    \"preview\": \"vite preview\"\n  },\n  \"dependencies\": {\n    \"react\": \"^18.2.0\",\n    \"react-dom\": \"^18.2.0\"\n  },\n  \"devDependencies\": {\n    \"@types/react\": \"^18.2.0\",\n    \"@types/react-dom\": \"^18.2.0\",\n    \"@vitejs/plugin-react\": \"^4.2.0\",\n    \"typescript\": \"^5.3.0\",\n    \"vite\": \"^5.0.0\"\n  }\n}\n",
+                ),
+                file(
+                    "index.html",
+                    "<!doctype html>\n<html lang=\"en\">\n  <head>\n    <meta charset=\"UTF-8\" />\n    <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\" />\n    <title>{{name}}</title>\n  </head>\n  <body>\n    <div id=\"root\"></div>\n    <script type=\"module\" src=\"/src/main.tsx\"></script>\n  </body>\n</html>\n",
+                ),
+                file(
+                    "vite.config.ts",
+                    "import { defineConfig } from 'vite';\nimport react from '@vitejs/plugin-react';\n\nexport default defineConfig({\n  plugins: [react()],\n});\n",
+                ),
+                file(
+                    "tsconfig.json",
+                    "{\n  \"compilerOptions\": {\n    \"target\": \"ES2020\",\n    \"lib\": [\"ES2020\", \"DOM\", \"DOM.Iterable\"],\n    \"module\": \"ESNext\",\n    \"moduleResolution\": \"bundler\",\n    \"jsx\": \"react-jsx\",\n    \"strict\": true,\n    \"noEmit\": true\n  },\n  \"include\": [\"src\"]\n}\n",
+                ),
+                file(
+                    "src/main.tsx",
+                    "import React from 'react';\nimport ReactDOM from 'react-dom/client';\nimport App from './App';\n\nReactDOM.createRoot(document.getElementById('root')!).render(\n  <React.StrictMode>\n    <App />\n  </React.StrictMode>,\n);\n",
+                ),
+                file(
+                    "src/App.tsx",
+                    "export default function App() {\n  return <h1>{{name}}</h1>;\n}\n",
+                ),
+                file(".gitignore", "node_modules\ndist\n"),
+            ],
+            post_create: vec![task("npm", &["install"])],
+            builtin: true,
+        },
+        Template {
+            id: "python-package".to_string(),
+            name: "Python package".to_string(),
+            description: "src-layout package with pyproject.toml and pytest".to_string(),
+            variables: vec![name, variable("description", "One-line package description", Some(""))],
+            files: vec![
+                file(
+                    "pyproject.toml",
+                    "[project]\nname = \"{{name_kebab}}\"\nversion = \"0.1.0\"\ndescription = \"{{description}}\"\nrequires-python = \">=3.9\"\n\n[build-system]\nrequires = [\"setuptools>=68\"]\nbuild-backend = \"setuptools.build_meta\"\n\n[tool.pytest.ini_options]\ntestpaths = [\"tests\"]\n",
+                ),
+                file("src/{{name_snake}}/__init__.py", "\"\"\"{{description}}\"\"\"\n\n__version__ = \"0.1.0\"\n"),
+                file(
+                    "tests/test_{{name_snake}}.py",
+                    "import {{name_snake}}\n\n\ndef test_version():\n    assert {{name_snake}}.__version__\n",
+                ),
+                file(".gitignore", "__pycache__/\n*.egg-info/\n.venv/\n"),
+            ],
+            post_create: Vec::new(),
+            builtin: true,
+        },
+    ]
+}
+
+/// Words of an identifier-like name: "My cool-app" -> ["my", "cool", "app"]
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        // Split camelCase boundaries
+        if c.is_uppercase() && previous_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Add `<var>_snake`, `<var>_kebab` and `<var>_pascal` case variants of every variable
+pub fn with_case_variants(vars: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut all = vars.clone();
+    for (key, value) in vars {
+        let parts = words(value);
+        all.entry(format!("{}_snake", key)).or_insert_with(|| parts.join("_"));
+        all.entry(format!("{}_kebab", key)).or_insert_with(|| parts.join("-"));
+        all.entry(format!("{}_pascal", key)).or_insert_with(|| {
+            parts
+                .iter()
+                .map(|w| {
+                    let mut chars = w.chars();
+                    match chars.next() {
+                        Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                        None => String::new(),
+                    }
+                })
+                .collect()
+        });
+    }
+    all
+}
+
+/// Replace `{{var}}` placeholders; unknown placeholders are an error
+pub fn interpolate(text: &str, vars: &HashMap<String, String>) -> Result<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or_else(|| anyhow!("Unclosed placeholder in template"))?;
+        let key = after[..end].trim();
+        let value = vars.get(key).ok_or_else(|| anyhow!("Unknown template variable: {}", key))?;
+        out.push_str(value);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Resolve declared variables against provided values and defaults
+pub fn resolve_variables(
+    declared: &[TemplateVariable],
+    provided: &HashMap<String, String>,
+) -> Result<HashMap<String, String>> {
+    let mut vars = provided.clone();
+    for variable in declared {
+        let missing = vars.get(&variable.name).map(|v| v.trim().is_empty()).unwrap_or(true);
+        if missing {
+            match &variable.default {
+                Some(default) => {
+                    vars.insert(variable.name.clone(), default.clone());
+                }
+                None => return Err(anyhow!("Missing template variable: {}", variable.name)),
+            }
+        }
+    }
+    Ok(with_case_variants(&vars))
+}
+
+/// Reject template paths escaping the target directory
+pub fn relative_path(path: &str) -> Result<PathBuf> {
+    let path = Path::new(path);
+    if path.is_absolute() || path.components().any(|c| matches!(c, Component::ParentDir)) {
+        return Err(anyhow!("Template path must stay inside the target: {:?}", path));
+    }
+    Ok(path.to_path_buf())
+}
+
+/// User templates: `<dir>/<id>/template.json` plus the files under `<dir>/<id>/files/`
+fn load_user_templates(dir: &Path) -> Vec<Template> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut templates = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let root = entry.path();
+        let manifest = match fs::read_to_string(root.join(MANIFEST)) {
+            Ok(manifest) => manifest,
+            Err(_) => continue,
+        };
+        let mut template: Template = match serde_json::from_str(&manifest) {
+            Ok(template) => template,
+            Err(e) => {
+                log::warn!("Skipping invalid template {:?}: {}", root, e);
+                continue;
+            }
+        };
+        template.id = entry.file_name().to_string_lossy().to_string();
+        template.builtin = false;
+
+        let files_dir = root.join("files");
+        for file in WalkDir::new(&files_dir).into_iter().filter_map(|e| e.ok()) {
+            if !file.file_type().is_file() {
+                continue;
+            }
+            let relative = file.path().strip_prefix(&files_dir).unwrap_or(file.path());
+            match fs::read_to_string(file.path()) {
+                Ok(content) => template.files.push(TemplateFile {
+                    path: relative.to_string_lossy().replace('\\', "/"),
+                    content,
+                }),
+                Err(e) => log::warn!("Skipping unreadable template file {:?}: {}", file.path(), e),
+            }
+        }
+        templates.push(template);
+    }
+    templates
+}
+
+/// All templates; user templates override built-ins with the same id
+pub fn templates(user_dir: &Path) -> Vec<Template> {
+    let mut templates = builtin_templates();
+    for user in load_user_templates(user_dir) {
+        templates.retain(|t| t.id != user.id);
+        templates.push(user);
+    }
+    templates
+}
+
+pub fn list_templates(user_dir: &Path) -> Vec<TemplateSummary> {
+    templates(user_dir)
+        .into_iter()
+        .map(|t| TemplateSummary {
+            id: t.id,
+            name: t.name,
+            description: t.description,
+            variables: t.variables,
+            builtin: t.builtin,
+        })
+        .collect()
+}
+
+/// Stamp out `template` into `target`, which must not exist or be empty
+pub fn create_from_template(
+    user_dir: &Path,
+    template_id: &str,
+    target: &Path,
+    provided: &HashMap<String, String>,
+    run_post_create: bool,
+    trust: &Mutex<TrustStore>,
+) -> Result<ScaffoldResult> {
+    let template = templates(user_dir)
+        .into_iter()
+        .find(|t| t.id == template_id)
+        .ok_or_else(|| anyhow!("Unknown template: {}", template_id))?;
+
+    let non_empty = fs::read_dir(target).map(|mut d| d.next().is_some()).unwrap_or(false);
+    if non_empty {
+        return Err(anyhow!("Target directory is not empty: {:?}", target));
+    }
+    let vars = resolve_variables(&template.variables, provided)?;
+
+    // Render everything before writing anything
+    let mut rendered = Vec::new();
+    for file in &template.files {
+        let path = relative_path(&interpolate(&file.path, &vars)?)?;
+        rendered.push((path, interpolate(&file.content, &vars)?));
+    }
+
+    let mut files = Vec::new();
+    for (path, content) in rendered {
+        let destination = target.join(&path);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&destination, content)?;
+        files.push(path.to_string_lossy().to_string());
+    }
+    log::info!("Created {} files from template {} in {:?}", files.len(), template.id, target);
+    // Everything in it came from the template the user picked
+    trust.lock().unwrap().set(target, true)?;
+
+    let mut post_create = Vec::new();
+    if run_post_create {
+        for spec in &template.post_create {
+            let output = task_runner::run(spec, target, POST_CREATE_TIMEOUT)?;
+            let failed = !output.success();
+            post_create.push(output);
+            if failed {
+                log::warn!("Post-create command {} failed; skipping the rest", spec.command);
+                break;
+            }
+        }
+    }
+
+    Ok(ScaffoldResult {
+        target: target.to_string_lossy().to_string(),
+        files,
+        post_create,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolate_with_case_variants() {
+        let mut vars = HashMap::new();
+        vars.insert("name".to_string(), "My coolApp".to_string());
+        let vars = with_case_variants(&vars);
+        assert_eq!(
+            interpolate("{{name_kebab}}/{{ name_snake }}/{{name_pascal}}", &vars).unwrap(),
+            "my-cool-app/my_cool_app/MyCoolApp"
+        );
+        assert!(interpolate("{{missing}}", &vars).is_err());
+    }
+}
@@ -0,0 +1,284 @@
+// Search Query - field:value syntax for advanced search
+// `lang:rust path:src/** symbol:fn modified:<7d content:"tokio::spawn" free text`
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::file_indexer::{self, FileIndex, FileInfo};
+use crate::mimi_engine::CodeGraph;
+use crate::symbols::SymbolKind;
+use crate::FileMatch;
+
+const FIELDS: &[&str] = &["lang", "path", "symbol", "modified", "content"];
+
+/// Structured filters parsed from a query; every populated filter must match
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SearchQuery {
+    /// Free text matched against file names and paths
+    pub text: String,
+    /// Language names or extensions (`rust`, `ts`); any may match
+    pub languages: Vec<String>,
+    /// Workspace-relative path globs (`src/**/*.rs`); any may match
+    pub paths: Vec<String>,
+    pub symbol_kinds: Vec<SymbolKind>,
+    /// Declared symbol names (`symbol:CodeGraph`), matched by substring
+    pub symbol_names: Vec<String>,
+    /// Modified within this many seconds (`modified:<7d`)
+    pub newer_than: Option<u64>,
+    /// Modified longer ago than this many seconds (`modified:>30d`)
+    pub older_than: Option<u64>,
+    /// Literal text that must appear in the file; lines of the first are returned
+    pub content: Vec<String>,
+}
+
+/// Split on whitespace outside double quotes, dropping the quotes
+fn tokens(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// `7d`, `12h`, `2w`, `30m`, `90s` in seconds
+fn parse_duration(value: &str) -> Result<u64> {
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().map_err(|_| anyhow!("Invalid duration: {}", value))?;
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" | "" => 86_400,
+        "w" => 7 * 86_400,
+        _ => return Err(anyhow!("Unknown duration unit in {} (use s, m, h, d or w)", value)),
+    };
+    amount.checked_mul(unit).ok_or_else(|| anyhow!("Invalid duration: {}", value))
+}
+
+fn symbol_kinds(value: &str) -> Option<Vec<SymbolKind>> {
+    let kinds = match value.to_lowercase().as_str() {
+        "fn" | "func" | "function" | "def" => vec![SymbolKind::Function, SymbolKind::Method],
+        "method" => vec![SymbolKind::Method],
+        "class" => vec![SymbolKind::Class],
+        "struct" => vec![SymbolKind::Struct],
+        "enum" => vec![SymbolKind::Enum],
+        "trait" => vec![SymbolKind::Trait],
+        "interface" => vec![SymbolKind::Interface],
+        "type" => vec![SymbolKind::Type],
+        "const" | "constant" | "static" => vec![SymbolKind::Constant],
+        "var" | "let" | "variable" => vec![SymbolKind::Variable],
+        "mod" | "module" | "namespace" => vec![SymbolKind::Module],
+        _ => return None,
+    };
+    Some(kinds)
+}
+
+/// Parse a query; tokens without a known `field:` prefix are free text
+pub fn parse(input: &str) -> Result<SearchQuery> {
+    let mut query = SearchQuery::default();
+    let mut text = Vec::new();
+    for token in tokens(input) {
+        let field = token
+            .split_once(':')
+            .filter(|(field, value)| FIELDS.contains(field) && !value.is_empty());
+        let (field, value) = match field {
+            Some((field, value)) => (field, value.to_string()),
+            None => {
+                text.push(token);
+                continue;
+            }
+        };
+        match field {
+            "lang" => query.languages.push(value.to_lowercase()),
+            "path" => query.paths.push(value.trim_start_matches("./").to_string()),
+            "symbol" => match symbol_kinds(&value) {
+                Some(kinds) => query.symbol_kinds.extend(kinds),
+                None => query.symbol_names.push(value),
+            },
+            "modified" => {
+                if let Some(age) = value.strip_prefix('>') {
+                    query.older_than = Some(parse_duration(age)?);
+                } else {
+                    query.newer_than = Some(parse_duration(value.trim_start_matches('<'))?);
+                }
+            }
+            _ => query.content.push(value),
+        }
+    }
+    query.text = text.join(" ");
+    Ok(query)
+}
+
+/// Whether a glob segment (`*`, `?`) matches a path segment
+fn segment_matches(pattern: &[char], text: &[char]) -> bool {
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some('*'), _) => segment_matches(&pattern[1..], text) || (!text.is_empty() && segment_matches(pattern, &text[1..])),
+        (Some('?'), Some(_)) => segment_matches(&pattern[1..], &text[1..]),
+        (Some(p), Some(t)) if p == t => segment_matches(&pattern[1..], &text[1..]),
+        _ => false,
+    }
+}
+
+fn segments_match(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => segments_match(&pattern[1..], path) || (!path.is_empty() && segments_match(pattern, &path[1..])),
+        (Some(p), Some(t)) => {
+            let (p, t): (Vec<char>, Vec<char>) = (p.chars().collect(), t.chars().collect());
+            segment_matches(&p, &t) && segments_match(&pattern[1..], &path[1..])
+        }
+        _ => false,
+    }
+}
+
+/// Match a workspace-relative path; patterns without wildcards match as a prefix
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    if !pattern.contains(['*', '?']) {
+        return path.starts_with(pattern.trim_end_matches('/'));
+    }
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    segments_match(&pattern, &path)
+}
+
+impl SearchQuery {
+    fn matches_file(&self, workspace: &Path, info: &FileInfo, graph: &CodeGraph) -> bool {
+        if !self.languages.is_empty()
+            && !self
+                .languages
+                .iter()
+                .any(|lang| info.language.eq_ignore_ascii_case(lang) || info.extension.eq_ignore_ascii_case(lang))
+        {
+            return false;
+        }
+        if !self.paths.is_empty() {
+            let relative = Path::new(&info.path).strip_prefix(workspace).unwrap_or(Path::new(&info.path));
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if !self.paths.iter().any(|pattern| glob_match(pattern, &relative)) {
+                return false;
+            }
+        }
+        if !self.symbol_kinds.is_empty() || !self.symbol_names.is_empty() {
+            let symbols = graph.symbols_in_file(&info.path);
+            let declared = symbols.iter().any(|symbol| {
+                (self.symbol_kinds.is_empty() || self.symbol_kinds.contains(&symbol.kind))
+                    && (self.symbol_names.is_empty()
+                        || self
+                            .symbol_names
+                            .iter()
+                            .any(|name| symbol.name.to_lowercase().contains(&name.to_lowercase())))
+            });
+            if !declared {
+                return false;
+            }
+        }
+        if self.newer_than.is_some() || self.older_than.is_some() {
+            let age = fs::metadata(&info.path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .unwrap_or(Duration::ZERO);
+            if self.newer_than.map(|secs| age > Duration::from_secs(secs)).unwrap_or(false)
+                || self.older_than.map(|secs| age < Duration::from_secs(secs)).unwrap_or(false)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Indexed files passing every filter except `content`, ranked by the free text
+    pub fn file_matches(&self, workspace: &Path, index: &FileIndex, graph: &CodeGraph, limit: usize) -> Vec<FileMatch> {
+        if !self.text.is_empty() {
+            return index.search_within(&self.text, limit, |info| self.matches_file(workspace, info, graph));
+        }
+        let mut paths: Vec<&String> = index
+            .paths()
+            .filter(|path| index.get(path).map(|info| self.matches_file(workspace, info, graph)).unwrap_or(false))
+            .collect();
+        paths.sort();
+        paths
+            .into_iter()
+            .take(limit)
+            .filter_map(|path| index.get(path))
+            .map(|info| FileMatch {
+                path: info.path.clone(),
+                name: info.name.clone(),
+                line: None,
+                snippet: None,
+                score: 1.0,
+                path_ranges: Vec::new(),
+                name_ranges: Vec::new(),
+                snippet_ranges: Vec::new(),
+            })
+            .collect()
+    }
+
+    /// Lines containing the first `content` term in files that contain every term
+    pub fn content_matches(&self, paths: &[String], limit: usize) -> Vec<FileMatch> {
+        let first = match self.content.first() {
+            Some(first) => first,
+            None => return Vec::new(),
+        };
+        let mut matches = file_indexer::grep(paths, first, usize::MAX);
+        if self.content.len() > 1 {
+            let files: HashSet<&String> = matches.iter().map(|m| &m.path).collect();
+            let accepted: HashSet<String> = files
+                .into_iter()
+                .filter(|path| {
+                    let content = fs::read_to_string(path).unwrap_or_default().to_lowercase();
+                    self.content[1..].iter().all(|term| content.contains(&term.to_lowercase()))
+                })
+                .cloned()
+                .collect();
+            matches.retain(|m| accepted.contains(&m.path));
+        }
+        matches.truncate(limit);
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fields_and_free_text() {
+        let query = parse(r#"lang:rust path:src/** symbol:fn modified:<7d content:"tokio::spawn" worker pool"#).unwrap();
+        assert_eq!(query.languages, vec!["rust"]);
+        assert_eq!(query.paths, vec!["src/**"]);
+        assert_eq!(query.symbol_kinds, vec![SymbolKind::Function, SymbolKind::Method]);
+        assert_eq!(query.newer_than, Some(7 * 86_400));
+        assert_eq!(query.content, vec!["tokio::spawn"]);
+        assert_eq!(query.text, "worker pool");
+        assert!(parse("modified:<7y").is_err());
+        assert!(parse("modified:<99999999999999999w").is_err());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("src/**", "src/a/b.rs"));
+        assert!(glob_match("src/**/*.rs", "src/main.rs"));
+        assert!(!glob_match("src/*.rs", "src/a/b.rs"));
+        assert!(glob_match("src/components", "src/components/Button.tsx"));
+    }
+}
@@ -0,0 +1,75 @@
+// Secrets Vault - Provider credentials in the OS keychain
+// Values are write-only from the frontend; only the engine reads them back
+
+use anyhow::{anyhow, Result};
+
+/// Keychain service name for all Mimiverse secrets
+const SERVICE: &str = "ai.mimiverse.ide";
+
+/// Key under which a provider's API key is stored
+pub fn provider_key(provider_id: &str) -> String {
+    format!("provider:{}:api_key", provider_id)
+}
+
+/// Secret keys are colon-separated scopes of `[a-z0-9_.-]`, e.g. `provider:openai:api_key`
+pub fn validate_key(key: &str) -> Result<()> {
+    let valid = !key.is_empty()
+        && key.split(':').all(|scope| {
+            !scope.is_empty()
+                && scope
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '.' | '-'))
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid secret key: {}", key))
+    }
+}
+
+fn entry(key: &str) -> Result<keyring::Entry> {
+    validate_key(key)?;
+    Ok(keyring::Entry::new(SERVICE, key)?)
+}
+
+pub fn set(key: &str, value: &str) -> Result<()> {
+    if value.is_empty() {
+        return Err(anyhow!("Secret value must not be empty"));
+    }
+    entry(key)?.set_password(value)?;
+    log::info!("Stored secret {}", key);
+    Ok(())
+}
+
+/// Read a secret; engine-internal, never exposed as a command
+pub fn get(key: &str) -> Result<Option<String>> {
+    match entry(key)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn exists(key: &str) -> Result<bool> {
+    Ok(get(key)?.is_some())
+}
+
+pub fn delete(key: &str) -> Result<()> {
+    match entry(key)?.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_key() {
+        assert!(validate_key(&provider_key("openai")).is_ok());
+        assert!(validate_key("provider::api_key").is_err());
+        assert!(validate_key("Provider:OpenAI").is_err());
+        assert!(validate_key("").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+// Session Recovery - Periodic snapshots of in-memory engine state
+// Lets `restore_last_state` bring a session back after a crash or forced quit
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::{AgentRun, AgentStatus};
+use crate::diagnostics::Diagnostic;
+use crate::history;
+use crate::storage;
+use crate::AppState;
+
+/// How often the session is written while the app runs
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Agent run that was active when the snapshot was taken
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RunRef {
+    pub id: String,
+    pub goal: String,
+    pub status: AgentStatus,
+}
+
+/// Local-history snapshot written during the session, for reverting uncommitted work
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CheckpointRef {
+    pub path: String,
+    pub snapshot_id: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SessionState {
+    pub saved_at: u64,
+    pub started_at: u64,
+    pub workspace: Option<String>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub active_runs: Vec<RunRef>,
+    pub checkpoints: Vec<CheckpointRef>,
+}
+
+pub fn session_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("session.json")
+}
+
+/// Copy the recoverable parts of the app state
+pub fn capture(state: &AppState, started_at: u64) -> SessionState {
+    let workspace = state.workspace_path.lock().unwrap().clone();
+    let diagnostics = state.diagnostics.lock().unwrap().all();
+    let mut active_runs: Vec<RunRef> = state
+        .agent_runs
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|run| matches!(run.status, AgentStatus::Running | AgentStatus::AwaitingApproval))
+        .map(|run| RunRef {
+            id: run.id.clone(),
+            goal: run.goal.clone(),
+            status: run.status.clone(),
+        })
+        .collect();
+    active_runs.sort_by(|a, b| a.id.cmp(&b.id));
+    let checkpoints = workspace
+        .as_deref()
+        .map(|ws| history::snapshots_since(ws, started_at))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(path, snapshot)| CheckpointRef {
+            path,
+            snapshot_id: snapshot.id,
+            reason: snapshot.reason,
+        })
+        .collect();
+
+    SessionState {
+        saved_at: storage::now_millis(),
+        started_at,
+        workspace: workspace.map(|ws| ws.to_string_lossy().to_string()),
+        diagnostics,
+        active_runs,
+        checkpoints,
+    }
+}
+
+/// Whether two snapshots differ in anything but their save time
+pub fn changed(previous: Option<&SessionState>, current: &SessionState) -> bool {
+    match previous {
+        Some(previous) => SessionState {
+            saved_at: current.saved_at,
+            ..previous.clone()
+        } != *current,
+        None => true,
+    }
+}
+
+pub fn save(path: &Path, session: &SessionState) -> Result<()> {
+    storage::write_json(path, session)
+}
+
+pub fn load(path: &Path) -> Result<Option<SessionState>> {
+    storage::read_json(path)
+}
+
+/// Republish saved diagnostics grouped by file and source
+pub fn restore_diagnostics(state: &AppState, diagnostics: Vec<Diagnostic>) {
+    let mut grouped: HashMap<(String, String), Vec<Diagnostic>> = HashMap::new();
+    for diagnostic in diagnostics {
+        grouped
+            .entry((diagnostic.file.clone(), diagnostic.source.clone()))
+            .or_default()
+            .push(diagnostic);
+    }
+    let mut store = state.diagnostics.lock().unwrap();
+    for ((file, source), diagnostics) in grouped {
+        store.publish(&file, &source, diagnostics);
+    }
+}
+
+/// Reload the audit trails of runs that were active, marked as interrupted.
+/// Their model conversations are not persisted, so they cannot resume.
+pub fn restore_runs(state: &AppState, workspace: &Path, runs: &[RunRef]) {
+    let mut agent_runs = state.agent_runs.lock().unwrap();
+    for run_ref in runs {
+        let path = storage::data_dir(workspace).join("agent").join(format!("{}.json", run_ref.id));
+        let run: Option<AgentRun> = storage::read_json(&path).unwrap_or_else(|e| {
+            log::warn!("Failed to read agent run {}: {}", run_ref.id, e);
+            None
+        });
+        if let Some(mut run) = run {
+            run.status = AgentStatus::Failed;
+            run.pending = None;
+            run.result = Some("Interrupted: the app exited while this run was active".to_string());
+            agent_runs.insert(run.id.clone(), run);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_ignores_save_time() {
+        let session = SessionState {
+            saved_at: 1,
+            started_at: 0,
+            workspace: Some("/ws".to_string()),
+            diagnostics: Vec::new(),
+            active_runs: Vec::new(),
+            checkpoints: Vec::new(),
+        };
+        let later = SessionState {
+            saved_at: 2,
+            ..session.clone()
+        };
+        assert!(!changed(Some(&session), &later));
+        assert!(changed(
+            Some(&session),
+            &SessionState {
+                workspace: None,
+                ..later
+            }
+        ));
+        assert!(changed(None, &session));
+    }
+}
@@ -0,0 +1,240 @@
+// Engine Settings - User configuration persisted in the app config directory
+// Every section falls back to defaults for missing fields
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::code_analyzer::AnalysisRule;
+use crate::keybindings::KeybindingRule;
+use crate::storage;
+use crate::updates::ReleaseChannel;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub ai: AiSettings,
+    pub imports: ImportSettings,
+    pub indexer: IndexerSettings,
+    pub analyzer: AnalyzerSettings,
+    pub watcher: WatcherSettings,
+    pub logging: LogSettings,
+    pub updates: UpdateSettings,
+    pub keybindings: KeybindingSettings,
+    pub sandbox: SandboxSettings,
+    /// Name of the last applied profile
+    pub profile: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct AiSettings {
+    /// Reuse responses for identical prompts
+    pub cache_enabled: bool,
+    pub cache_ttl_secs: u64,
+    pub cache_max_entries: usize,
+    /// Per-provider request limit (0 = unlimited)
+    pub requests_per_minute: u32,
+    /// Retries for timeouts, connection errors, 429 and 5xx responses
+    pub max_retries: u32,
+}
+
+impl Default for AiSettings {
+    fn default() -> Self {
+        Self {
+            cache_enabled: true,
+            cache_ttl_secs: 3600,
+            cache_max_entries: 500,
+            requests_per_minute: 60,
+            max_retries: 3,
+        }
+    }
+}
+
+/// Style applied by organize-imports
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ImportSettings {
+    /// Separate std, external and internal imports with blank lines
+    pub group: bool,
+    /// Combine imports of the same module into one statement
+    pub merge: bool,
+    pub remove_unused: bool,
+}
+
+impl Default for ImportSettings {
+    fn default() -> Self {
+        Self {
+            group: true,
+            merge: true,
+            remove_unused: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct IndexerSettings {
+    /// Worker threads for indexing and graph analysis (0 = one per core)
+    pub threads: usize,
+    /// Globs of generated files on top of the built-in heuristics, e.g. `src/gen/**`
+    pub generated_patterns: Vec<String>,
+    /// Index and analyze generated files like hand-written ones
+    pub index_generated: bool,
+    /// Fully index only the directories of the sparse index; other files keep name and size only
+    pub sparse: bool,
+    /// In sparse mode, also fully index directories with commits in this many days (0 = chosen ones only)
+    pub sparse_recent_days: u32,
+    /// Recently closed workspaces whose index stays in memory for a quick switch back (0 = none)
+    pub standby_workspaces: usize,
+    /// Memory the standby indexes may take together, in megabytes
+    pub standby_memory_mb: usize,
+}
+
+impl Default for IndexerSettings {
+    fn default() -> Self {
+        Self {
+            threads: 0,
+            generated_patterns: Vec::new(),
+            index_generated: false,
+            sparse: false,
+            sparse_recent_days: 0,
+            standby_workspaces: 3,
+            standby_memory_mb: 512,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct AnalyzerSettings {
+    pub rules: Vec<AnalysisRule>,
+    /// Ids of individual analyzer rules switched off, e.g. `long_functions`
+    pub disabled_rules: Vec<String>,
+    /// Report misspelled words in comments, strings and identifiers
+    pub spellcheck: bool,
+}
+
+impl Default for AnalyzerSettings {
+    fn default() -> Self {
+        Self {
+            rules: AnalysisRule::DEFAULT.to_vec(),
+            disabled_rules: Vec::new(),
+            spellcheck: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatcherMode {
+    /// OS file notifications
+    Native,
+    /// Periodic rescans, for network drives and trees exceeding watch limits
+    Polling,
+    Off,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct WatcherSettings {
+    pub mode: WatcherMode,
+    pub poll_interval_ms: u64,
+}
+
+impl Default for WatcherSettings {
+    fn default() -> Self {
+        Self {
+            mode: WatcherMode::Native,
+            poll_interval_ms: 2000,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct LogSettings {
+    /// Level of subsystems without an override
+    pub level: String,
+    /// Levels by module name (`file_indexer`) or full target
+    pub targets: BTreeMap<String, String>,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            targets: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct UpdateSettings {
+    pub channel: ReleaseChannel,
+    /// Hours between automatic checks (0 = only when asked)
+    pub check_interval_hours: u64,
+    /// Check automatically on metered connections too
+    pub allow_metered: bool,
+    /// Release manifest URL; `{{channel}}` is replaced by the channel name
+    pub endpoint: String,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            channel: ReleaseChannel::Stable,
+            check_interval_hours: 24,
+            allow_metered: false,
+            endpoint: "https://releases.mimiverse.ai/{{channel}}/latest.json".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct KeybindingSettings {
+    /// Applied after the defaults and plugin contributions, in order
+    pub overrides: Vec<KeybindingRule>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct SandboxSettings {
+    /// Folders outside the workspace that file commands may read and write
+    pub allowed_paths: Vec<String>,
+}
+
+pub fn settings_path(app_config_dir: &Path) -> PathBuf {
+    app_config_dir.join("settings.json")
+}
+
+/// Load settings, falling back to defaults if missing or unreadable
+pub fn load(path: &Path) -> Settings {
+    match storage::read_json::<Settings>(path) {
+        Ok(Some(settings)) => settings,
+        Ok(None) => Settings::default(),
+        Err(e) => {
+            log::warn!("Ignoring unreadable settings {:?}: {}", path, e);
+            Settings::default()
+        }
+    }
+}
+
+pub fn save(path: &Path, settings: &Settings) -> Result<()> {
+    storage::write_json(path, settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_settings_use_defaults() {
+        let settings: Settings = serde_json::from_str(r#"{"ai": {"max_retries": 5}}"#).unwrap();
+        assert_eq!(settings.ai.max_retries, 5);
+        assert!(settings.ai.cache_enabled);
+    }
+}
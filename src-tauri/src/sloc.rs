@@ -0,0 +1,260 @@
+// Line Classification - Code, comment and blank line counts per language
+// A line holding both code and a comment counts as code, as in tokei
+
+use std::ops::{AddAssign, SubAssign};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct LineCounts {
+    pub code: usize,
+    pub comment: usize,
+    pub blank: usize,
+}
+
+impl AddAssign for LineCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.code += other.code;
+        self.comment += other.comment;
+        self.blank += other.blank;
+    }
+}
+
+impl SubAssign for LineCounts {
+    fn sub_assign(&mut self, other: Self) {
+        self.code = self.code.saturating_sub(other.code);
+        self.comment = self.comment.saturating_sub(other.comment);
+        self.blank = self.blank.saturating_sub(other.blank);
+    }
+}
+
+pub struct CommentSyntax {
+    pub line: &'static [&'static str],
+    pub block: Option<(&'static str, &'static str)>,
+    /// String delimiters whose content is never a comment
+    pub quotes: &'static [char],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SegmentKind {
+    Identifier,
+    Comment,
+    /// Content of a string literal, without its quotes
+    Literal,
+}
+
+/// A comment, string literal or identifier on one line of a source file
+pub struct Segment<'a> {
+    pub line: usize,
+    pub column: usize,
+    pub kind: SegmentKind,
+    pub text: &'a str,
+}
+
+const C_STYLE: CommentSyntax = CommentSyntax {
+    line: &["//"],
+    block: Some(("/*", "*/")),
+    quotes: &['"', '\'', '`'],
+};
+
+/// Comment syntax of a language name from `FileIndex::detect_language`
+pub fn syntax_for(language: &str) -> Option<CommentSyntax> {
+    match language {
+        "TypeScript" | "JavaScript" | "Rust" | "Go" | "Java" | "C" | "C++" => Some(C_STYLE),
+        "CSS" => Some(CommentSyntax {
+            line: &["//"],
+            block: Some(("/*", "*/")),
+            quotes: &['"', '\''],
+        }),
+        "Python" => Some(CommentSyntax {
+            line: &["#"],
+            block: Some(("\"\"\"", "\"\"\"")),
+            quotes: &['"', '\''],
+        }),
+        "Shell" | "YAML" => Some(CommentSyntax {
+            line: &["#"],
+            block: None,
+            quotes: &['"', '\''],
+        }),
+        "SQL" => Some(CommentSyntax {
+            line: &["--"],
+            block: Some(("/*", "*/")),
+            quotes: &['\''],
+        }),
+        "HTML" | "Markdown" => Some(CommentSyntax {
+            line: &[],
+            block: Some(("<!--", "-->")),
+            quotes: &[],
+        }),
+        _ => None,
+    }
+}
+
+/// Classify every line of `content`; languages without known comment syntax have only code and blanks
+pub fn count(content: &str, language: &str) -> LineCounts {
+    let mut counts = LineCounts::default();
+    let syntax = syntax_for(language);
+    // Set while inside a block comment spanning lines
+    let mut block_end: Option<&str> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            counts.blank += 1;
+            continue;
+        }
+        let syntax = match &syntax {
+            Some(syntax) => syntax,
+            None => {
+                counts.code += 1;
+                continue;
+            }
+        };
+
+        let mut has_code = false;
+        let mut rest = trimmed;
+        while !rest.is_empty() {
+            if let Some(end) = block_end {
+                match rest.find(end) {
+                    Some(at) => {
+                        rest = rest[at + end.len()..].trim_start();
+                        block_end = None;
+                    }
+                    None => rest = "",
+                }
+                continue;
+            }
+            if syntax.line.iter().any(|marker| rest.starts_with(marker)) {
+                break;
+            }
+            if let Some((start, end)) = syntax.block {
+                if let Some(body) = rest.strip_prefix(start) {
+                    block_end = Some(end);
+                    rest = body;
+                    continue;
+                }
+            }
+            // Skip one token of code, stepping over string literals whole
+            has_code = true;
+            let mut chars = rest.char_indices();
+            let (_, first) = chars.next().unwrap_or((0, ' '));
+            let next = if syntax.quotes.contains(&first) {
+                let mut escaped = false;
+                chars
+                    .find(|&(_, c)| {
+                        let closes = c == first && !escaped;
+                        escaped = c == '\\' && !escaped;
+                        closes
+                    })
+                    .map(|(at, c)| at + c.len_utf8())
+                    .unwrap_or(rest.len())
+            } else {
+                first.len_utf8()
+            };
+            rest = &rest[next..];
+        }
+
+        if has_code {
+            counts.code += 1;
+        } else {
+            counts.comment += 1;
+        }
+    }
+    counts
+}
+
+/// Comments, string literals and identifiers of `content`, with 1-based lines and byte columns;
+/// empty for languages without known comment syntax
+pub fn segments<'a>(content: &'a str, language: &str) -> Vec<Segment<'a>> {
+    let mut segments = Vec::new();
+    let syntax = match syntax_for(language) {
+        Some(syntax) => syntax,
+        None => return segments,
+    };
+
+    // Set while inside a block comment spanning lines
+    let mut block_end: Option<&str> = None;
+    for (i, line) in content.lines().enumerate() {
+        let number = i + 1;
+        let mut at = 0;
+        while at < line.len() {
+            let rest = &line[at..];
+            if let Some(end) = block_end {
+                let length = rest.find(end).unwrap_or(rest.len());
+                segments.push(Segment { line: number, column: at, kind: SegmentKind::Comment, text: &rest[..length] });
+                if length < rest.len() {
+                    block_end = None;
+                    at += length + end.len();
+                } else {
+                    at = line.len();
+                }
+                continue;
+            }
+            if syntax.line.iter().any(|marker| rest.starts_with(marker)) {
+                segments.push(Segment { line: number, column: at, kind: SegmentKind::Comment, text: rest });
+                break;
+            }
+            if let Some((start, end)) = syntax.block {
+                if rest.starts_with(start) {
+                    block_end = Some(end);
+                    at += start.len();
+                    continue;
+                }
+            }
+            let first = rest.chars().next().unwrap_or(' ');
+            if syntax.quotes.contains(&first) {
+                let body = &rest[first.len_utf8()..];
+                let mut escaped = false;
+                let close = body
+                    .char_indices()
+                    .find(|&(_, c)| {
+                        let closes = c == first && !escaped;
+                        escaped = c == '\\' && !escaped;
+                        closes
+                    })
+                    .map(|(close, _)| close);
+                let length = close.unwrap_or(body.len());
+                segments.push(Segment { line: number, column: at + first.len_utf8(), kind: SegmentKind::Literal, text: &body[..length] });
+                at += first.len_utf8() + length + close.map_or(0, |_| first.len_utf8());
+                continue;
+            }
+            if first.is_alphanumeric() || first == '_' {
+                let length = rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(rest.len());
+                segments.push(Segment { line: number, column: at, kind: SegmentKind::Identifier, text: &rest[..length] });
+                at += length;
+                continue;
+            }
+            at += first.len_utf8();
+        }
+    }
+    segments
+}
+
+/// `content` line by line with comments blanked
+pub fn without_comments(content: &str, language: &str) -> Vec<String> {
+    let mut lines: Vec<Vec<u8>> = content.lines().map(|l| l.as_bytes().to_vec()).collect();
+    for segment in segments(content, language).iter().filter(|s| s.kind == SegmentKind::Comment) {
+        if let Some(line) = lines.get_mut(segment.line - 1) {
+            let end = (segment.column + segment.text.len()).min(line.len());
+            line[segment.column..end].fill(b' ');
+        }
+    }
+    lines.into_iter().map(|line| String::from_utf8_lossy(&line).into_owned()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_rust() {
+        let source = "// header\n\nfn main() { // trailing\n    let s = \"// not a comment\";\n    /* block\n       still block */\n}\n";
+        assert_eq!(count(source, "Rust"), LineCounts { code: 3, comment: 3, blank: 1 });
+    }
+
+    #[test]
+    fn test_count_python_and_unknown() {
+        let source = "\"\"\"Module doc.\n\nMore.\n\"\"\"\nimport os  # why\n# note\n";
+        assert_eq!(count(source, "Python"), LineCounts { code: 1, comment: 4, blank: 1 });
+        assert_eq!(count("{\n\n}\n", "JSON"), LineCounts { code: 2, comment: 0, blank: 1 });
+    }
+}
@@ -0,0 +1,468 @@
+// Source Maps - Decoding of source map v3 files and lookup of original positions
+// Mappings are decoded once into per-line segments; index maps are flattened into one map
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use crate::file_access;
+
+/// Source maps kept by `SourceMapCache`
+const MAX_CACHED: usize = 64;
+/// Output directories tried for the path of a deployed script URL
+const BUILD_DIRS: &[&str] = &["dist", "build", "out", "public", ".next", "target/web"];
+
+/// One mapping of a generated line; lines and columns are 0-based, columns in UTF-16 units
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segment {
+    pub column: u32,
+    /// `None` for generated code without an original
+    pub source: Option<u32>,
+    pub original_line: u32,
+    pub original_column: u32,
+    pub name: Option<u32>,
+}
+
+pub struct SourceMap {
+    /// Source paths with `sourceRoot` applied, as written in the map
+    pub sources: Vec<String>,
+    pub names: Vec<String>,
+    /// Segments of each generated line, by column
+    pub lines: Vec<Vec<Segment>>,
+}
+
+/// Where a generated position came from; lines and columns are 1-based
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OriginalPosition {
+    /// Source entry as written in the map
+    pub source: String,
+    pub path: String,
+    /// Workspace-relative path when the source is a workspace file
+    pub relative_path: Option<String>,
+    pub exists: bool,
+    pub line: u32,
+    pub column: u32,
+    /// Original identifier at the position, e.g. the function name of a minified frame
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawMap {
+    #[serde(default)]
+    sources: Vec<Option<String>>,
+    #[serde(default)]
+    source_root: Option<String>,
+    #[serde(default)]
+    names: Vec<String>,
+    #[serde(default)]
+    mappings: String,
+    #[serde(default)]
+    sections: Vec<RawSection>,
+}
+
+#[derive(Deserialize)]
+struct RawSection {
+    offset: RawOffset,
+    map: RawMap,
+}
+
+#[derive(Deserialize)]
+struct RawOffset {
+    line: u32,
+    column: u32,
+}
+
+fn base64_value(c: u8) -> Option<i64> {
+    let value = match c {
+        b'A'..=b'Z' => c - b'A',
+        b'a'..=b'z' => c - b'a' + 26,
+        b'0'..=b'9' => c - b'0' + 52,
+        b'+' => 62,
+        b'/' => 63,
+        _ => return None,
+    };
+    Some(value as i64)
+}
+
+/// Base64 VLQ fields of one segment
+fn decode_vlq(segment: &str) -> Result<Vec<i64>> {
+    let mut values = Vec::new();
+    let (mut value, mut shift) = (0i64, 0u32);
+    for c in segment.bytes() {
+        let digit = base64_value(c).ok_or_else(|| anyhow!("Invalid character in mappings: {:?}", c as char))?;
+        if shift > 60 {
+            return Err(anyhow!("Mapping value out of range"));
+        }
+        value += (digit & 31) << shift;
+        if digit & 32 != 0 {
+            shift += 5;
+            continue;
+        }
+        values.push(if value & 1 == 1 { -(value >> 1) } else { value >> 1 });
+        value = 0;
+        shift = 0;
+    }
+    if shift != 0 {
+        return Err(anyhow!("Truncated mapping segment: {}", segment));
+    }
+    Ok(values)
+}
+
+/// Relative fields are deltas from the previous segment; the column restarts on every line
+fn decode_mappings(mappings: &str, source_count: usize) -> Result<Vec<Vec<Segment>>> {
+    let mut lines = Vec::new();
+    let (mut source, mut original_line, mut original_column, mut name) = (0i64, 0i64, 0i64, 0i64);
+    for line in mappings.split(';') {
+        let mut segments = Vec::new();
+        let mut column = 0i64;
+        for field in line.split(',').filter(|field| !field.is_empty()) {
+            let values = decode_vlq(field)?;
+            column += values[0];
+            let mut segment = Segment {
+                column: column.max(0) as u32,
+                source: None,
+                original_line: 0,
+                original_column: 0,
+                name: None,
+            };
+            if values.len() >= 4 {
+                source += values[1];
+                original_line += values[2];
+                original_column += values[3];
+                if source < 0 || source as usize >= source_count {
+                    return Err(anyhow!("Mapping refers to missing source {}", source));
+                }
+                segment.source = Some(source as u32);
+                segment.original_line = original_line.max(0) as u32;
+                segment.original_column = original_column.max(0) as u32;
+            }
+            if values.len() >= 5 {
+                name += values[4];
+                segment.name = u32::try_from(name).ok();
+            }
+            segments.push(segment);
+        }
+        segments.sort_by_key(|segment| segment.column);
+        lines.push(segments);
+    }
+    Ok(lines)
+}
+
+impl SourceMap {
+    pub fn parse(text: &str) -> Result<Self> {
+        // Maps served to browsers may start with an XSSI guard line
+        let text = text.strip_prefix(")]}'").map_or(text, |rest| rest.split_once('\n').map_or("", |(_, map)| map));
+        let raw: RawMap = serde_json::from_str(text)?;
+        let mut map = SourceMap {
+            sources: Vec::new(),
+            names: Vec::new(),
+            lines: Vec::new(),
+        };
+        map.append(raw, 0, 0)?;
+        Ok(map)
+    }
+
+    /// Add the mappings of `raw` shifted by a section offset
+    fn append(&mut self, raw: RawMap, line_offset: u32, column_offset: u32) -> Result<()> {
+        for section in raw.sections {
+            self.append(section.map, line_offset + section.offset.line, section.offset.column)?;
+        }
+        if raw.mappings.is_empty() {
+            return Ok(());
+        }
+        let root = raw.source_root.unwrap_or_default();
+        let base = self.sources.len() as u32;
+        let name_base = self.names.len() as u32;
+        let name_count = raw.names.len() as u32;
+        self.names.extend(raw.names);
+        self.sources.extend(raw.sources.iter().map(|source| {
+            let source = source.clone().unwrap_or_default();
+            match root.trim_end_matches('/') {
+                "" => source,
+                root => format!("{}/{}", root, source),
+            }
+        }));
+        for (i, segments) in decode_mappings(&raw.mappings, raw.sources.len())?.into_iter().enumerate() {
+            let line = line_offset as usize + i;
+            if self.lines.len() <= line {
+                self.lines.resize(line + 1, Vec::new());
+            }
+            // The column offset of a section applies to its first line only
+            let shift = if i == 0 { column_offset } else { 0 };
+            self.lines[line].extend(segments.into_iter().map(|segment| Segment {
+                column: segment.column + shift,
+                source: segment.source.map(|source| source + base),
+                name: segment.name.filter(|name| *name < name_count).map(|name| name + name_base),
+                ..segment
+            }));
+            self.lines[line].sort_by_key(|segment| segment.column);
+        }
+        Ok(())
+    }
+
+    /// Mapped segment covering a 0-based generated line and column
+    pub fn lookup(&self, line: u32, column: u32) -> Option<&Segment> {
+        let segments = self.lines.get(line as usize)?;
+        let i = segments.partition_point(|segment| segment.column <= column);
+        segments[..i].last().filter(|segment| segment.source.is_some())
+    }
+}
+
+/// File path of a source entry, resolved against the directory of the map; bundler schemes such as
+/// `webpack://app/./src/a.ts` name a path relative to the project root instead
+pub fn source_path(map_dir: &Path, project_root: &Path, source: &str) -> PathBuf {
+    let (base, source) = match source.split_once("://") {
+        Some(("file", rest)) => (map_dir, rest),
+        Some((_, rest)) => (project_root, rest.split_once('/').map_or(rest, |(_, path)| path)),
+        None => (map_dir, source),
+    };
+    let joined = base.join(source.split('?').next().unwrap_or(source));
+    let mut normalized = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Script behind a stack frame location: a path, a `file://` URL, or the URL of a deployed asset, which is
+/// looked up under the workspace and its usual output directories; nothing outside the workspace is used
+fn generated_path(workspace: &Path, location: &str) -> Option<PathBuf> {
+    let location = location.split(['?', '#']).next().unwrap_or(location);
+    let location = match location.split_once("://") {
+        Some(("file", rest)) => rest,
+        Some((_, rest)) => rest.find('/').map_or("", |i| &rest[i..]),
+        None => location,
+    };
+    let inside = |path: &str| file_access::confine(Some(workspace), &[], path).ok().filter(|path| path.is_file());
+    if let Some(direct) = inside(location) {
+        return Some(direct);
+    }
+    let relative = location.trim_start_matches('/');
+    std::iter::once(workspace.join(relative))
+        .chain(BUILD_DIRS.iter().map(|dir| workspace.join(dir).join(relative)))
+        .find_map(|path| inside(&path.to_string_lossy()))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Map named by the `sourceMappingURL` comment of a generated file, inline or as a path next to it;
+/// without one, `<file>.map`
+enum MapLocation {
+    File(PathBuf),
+    Inline(String),
+}
+
+fn locate_map(generated: &Path) -> Result<MapLocation> {
+    let fallback = PathBuf::from(format!("{}.map", generated.display()));
+    if generated.extension().is_some_and(|ext| ext == "map") {
+        return Ok(MapLocation::File(generated.to_path_buf()));
+    }
+    let content = fs::read_to_string(generated)?;
+    let Some(at) = content.rfind("sourceMappingURL=") else {
+        return Ok(MapLocation::File(fallback));
+    };
+    let url = content[at + "sourceMappingURL=".len()..].split_whitespace().next().unwrap_or_default();
+    let url = url.trim_end_matches("*/");
+    if let Some(data) = url.strip_prefix("data:") {
+        let (header, payload) = data.split_once(',').ok_or_else(|| anyhow!("Malformed inline source map"))?;
+        if !header.ends_with(";base64") {
+            return Ok(MapLocation::Inline(payload.to_string()));
+        }
+        let bytes = base64::engine::general_purpose::STANDARD.decode(payload)?;
+        return Ok(MapLocation::Inline(String::from_utf8(bytes)?));
+    }
+    // A map URL on another origin is assumed to have been deployed next to the script
+    let url = url.split(['?', '#']).next().unwrap_or(url);
+    let url = if url.contains("://") { url.rsplit('/').next().unwrap_or(url) } else { url };
+    let path = generated.parent().map(|dir| dir.join(url)).unwrap_or_else(|| PathBuf::from(url));
+    Ok(MapLocation::File(if path.is_file() { path } else { fallback }))
+}
+
+struct CachedMap {
+    generated_modified: Option<SystemTime>,
+    /// Map file and its modification time; none for inline maps
+    file: Option<(PathBuf, Option<SystemTime>)>,
+    map: Arc<SourceMap>,
+    used: u64,
+}
+
+impl CachedMap {
+    fn is_fresh(&self, generated: &Path) -> bool {
+        modified(generated) == self.generated_modified
+            && self.file.as_ref().is_none_or(|(path, at)| modified(path) == *at)
+    }
+}
+
+/// Parsed source maps by generated file, reloaded when the file or its map changes
+pub struct SourceMapCache {
+    maps: HashMap<PathBuf, CachedMap>,
+    tick: u64,
+}
+
+impl SourceMapCache {
+    pub fn new() -> Self {
+        Self {
+            maps: HashMap::new(),
+            tick: 0,
+        }
+    }
+
+    fn load(&mut self, workspace: &Path, generated: &Path) -> Result<Arc<SourceMap>> {
+        self.tick += 1;
+        if let Some(cached) = self.maps.get_mut(generated).filter(|cached| cached.is_fresh(generated)) {
+            cached.used = self.tick;
+            return Ok(cached.map.clone());
+        }
+        let generated_modified = modified(generated);
+        let (text, file) = match locate_map(generated)? {
+            MapLocation::File(path) => {
+                // `sourceMappingURL` is file content and may point anywhere
+                let path = file_access::confine(Some(workspace), &[], &path.to_string_lossy())?;
+                let text = fs::read_to_string(&path)
+                    .map_err(|e| anyhow!("No source map for {} at {}: {}", generated.display(), path.display(), e))?;
+                let at = modified(&path);
+                (text, Some((path, at)))
+            }
+            MapLocation::Inline(text) => (text, None),
+        };
+        let map = Arc::new(SourceMap::parse(&text)?);
+        if self.maps.len() >= MAX_CACHED {
+            let oldest = self.maps.iter().min_by_key(|(_, cached)| cached.used).map(|(path, _)| path.clone());
+            if let Some(oldest) = oldest {
+                self.maps.remove(&oldest);
+            }
+        }
+        self.maps.insert(generated.to_path_buf(), CachedMap {
+            generated_modified,
+            file,
+            map: map.clone(),
+            used: self.tick,
+        });
+        Ok(map)
+    }
+
+    /// Original position of a 1-based line and column of a generated file, as stack traces report them;
+    /// none when the position is unmapped
+    pub fn resolve(
+        &mut self,
+        workspace: &Path,
+        generated: &str,
+        line: u32,
+        column: u32,
+    ) -> Result<Option<OriginalPosition>> {
+        let generated = generated_path(workspace, generated)
+            .ok_or_else(|| anyhow!("Generated file not found in workspace: {}", generated))?;
+        let map = self.load(workspace, &generated)?;
+        let Some(segment) = map.lookup(line.saturating_sub(1), column.saturating_sub(1)) else {
+            return Ok(None);
+        };
+        let source = segment.source.and_then(|s| map.sources.get(s as usize)).cloned().unwrap_or_default();
+        let map_dir = match self.maps.get(&generated).and_then(|cached| cached.file.as_ref()) {
+            Some((path, _)) => path.parent().unwrap_or(workspace).to_path_buf(),
+            None => generated.parent().unwrap_or(workspace).to_path_buf(),
+        };
+        let path = source_path(&map_dir, workspace, &source);
+        Ok(Some(OriginalPosition {
+            relative_path: path.strip_prefix(workspace).ok().map(|p| p.to_string_lossy().replace('\\', "/")),
+            exists: path.is_file(),
+            path: path.to_string_lossy().to_string(),
+            source,
+            line: segment.original_line + 1,
+            column: segment.original_column + 1,
+            name: segment.name.and_then(|n| map.names.get(n as usize)).cloned(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decodes_mappings_and_sections() {
+        assert_eq!(decode_vlq("AAgBC").unwrap(), vec![0, 0, 16, 1]);
+        assert_eq!(decode_vlq("D").unwrap(), vec![-1]);
+        assert!(decode_vlq("g").is_err());
+
+        let map = SourceMap::parse(
+            r#"{"version": 3, "sources": ["a.ts", "b.ts"], "sourceRoot": "src", "mappings": "AAAA,KACC;ACAA"}"#,
+        )
+        .unwrap();
+        assert_eq!(map.sources, vec!["src/a.ts", "src/b.ts"]);
+        assert_eq!(map.lines[0][1], Segment {
+            column: 5,
+            source: Some(0),
+            original_line: 1,
+            original_column: 1,
+            name: None,
+        });
+        assert_eq!(map.lookup(0, 7).map(|segment| segment.column), Some(5));
+        assert!(map.lookup(3, 0).is_none());
+        assert_eq!(map.lines[1][0].source, Some(1));
+
+        let indexed = SourceMap::parse(
+            r#"{"version": 3, "sections": [
+                {"offset": {"line": 0, "column": 0}, "map": {"sources": ["a.ts"], "mappings": "AAAA"}},
+                {"offset": {"line": 2, "column": 10}, "map": {"sources": ["b.ts"], "mappings": "AAAA"}}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(indexed.sources, vec!["a.ts", "b.ts"]);
+        assert_eq!(indexed.lines[2][0].column, 10);
+        assert_eq!(indexed.lines[2][0].source, Some(1));
+
+        let (dist, root) = (Path::new("/app/dist"), Path::new("/app"));
+        assert_eq!(source_path(dist, root, "webpack://app/./src/index.ts"), PathBuf::from("/app/src/index.ts"));
+        assert_eq!(source_path(dist, root, "../src/a.ts?v=2"), PathBuf::from("/app/src/a.ts"));
+    }
+
+    #[test]
+    fn test_resolves_positions_through_cached_maps() {
+        let dir = std::env::temp_dir().join(format!("mimi-sourcemap-{}", crate::storage::new_id("t")));
+        fs::create_dir_all(dir.join("dist/assets")).unwrap();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/app.ts"), "export function start() {}\n").unwrap();
+        fs::write(dir.join("dist/assets/index.js"), "x();y()\n//# sourceMappingURL=index.js.map\n").unwrap();
+        // `y()` at column 5 maps to line 3, column 3 of app.ts, named `start`
+        let map = r#"{"version": 3, "sources": ["../../src/app.ts"], "names": ["start"], "mappings": "AAAA,IAEEA"}"#;
+        fs::write(dir.join("dist/assets/index.js.map"), map).unwrap();
+
+        let mut cache = SourceMapCache::new();
+        let url = "https://app.example.com/assets/index.js?v=3";
+        let position = cache.resolve(&dir, url, 1, 5).unwrap().unwrap();
+        assert_eq!(position.relative_path.as_deref(), Some("src/app.ts"));
+        assert!(position.exists);
+        assert_eq!((position.line, position.column), (3, 3));
+        assert_eq!(position.name.as_deref(), Some("start"));
+        assert_eq!(cache.resolve(&dir, "dist/assets/index.js", 1, 2).unwrap().unwrap().line, 1);
+        assert_eq!(cache.maps.len(), 1);
+        assert!(cache.resolve(&dir, "dist/missing.js", 1, 1).is_err());
+
+        // Scripts and maps outside the workspace are not read
+        let outside_name = format!("{}-outside", dir.file_name().unwrap().to_string_lossy());
+        let outside = dir.with_file_name(&outside_name);
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("other.js"), "x()\n").unwrap();
+        fs::write(outside.join("other.js.map"), map).unwrap();
+        assert!(cache.resolve(&dir, &outside.join("other.js").to_string_lossy(), 1, 1).is_err());
+        assert!(cache.resolve(&dir, &format!("../{}/other.js", outside_name), 1, 1).is_err());
+        let escape = format!("x()\n//# sourceMappingURL=../../{}/other.js.map\n", outside_name);
+        fs::write(dir.join("dist/escape.js"), escape).unwrap();
+        assert!(cache.resolve(&dir, "dist/escape.js", 1, 1).is_err());
+        fs::remove_dir_all(&outside).ok();
+        fs::remove_dir_all(&dir).ok();
+    }
+}
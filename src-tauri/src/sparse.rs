@@ -0,0 +1,153 @@
+// Sparse Index - Directories fully indexed and analyzed in sparse mode
+// The rest of a huge monorepo keeps name and size metadata only until it is expanded
+
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::git;
+use crate::storage;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SparseSet {
+    /// Chosen by the user, relative to the workspace
+    pub directories: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SparseStatus {
+    pub enabled: bool,
+    pub selected: Vec<String>,
+    /// Directories with commits in the configured number of days
+    pub recent: Vec<String>,
+    pub full_files: usize,
+    pub shallow_files: usize,
+}
+
+fn sparse_path(workspace: &Path) -> PathBuf {
+    storage::data_dir(workspace).join("sparse.json")
+}
+
+/// Workspace-relative form of a directory inside the workspace, without trailing slashes
+fn relative_dir(workspace: &Path, directory: &str) -> Result<String> {
+    let path = Path::new(directory);
+    let relative = if path.is_absolute() {
+        path.strip_prefix(workspace).map_err(|_| anyhow!("Not in the workspace: {}", directory))?
+    } else {
+        path
+    };
+    if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(anyhow!("Not in the workspace: {}", directory));
+    }
+    Ok(relative.to_string_lossy().replace('\\', "/").trim_matches('/').to_string())
+}
+
+/// `relative` is `directory` itself or below it
+fn within(relative: &str, directory: &str) -> bool {
+    relative == directory || relative.strip_prefix(directory).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// A workspace-relative file is fully indexed: top-level files always are
+pub fn includes(directories: &[String], relative: &str) -> bool {
+    !relative.contains('/') || directories.iter().any(|directory| within(relative, directory))
+}
+
+pub fn load(workspace: &Path) -> Result<SparseSet> {
+    Ok(storage::read_json(&sparse_path(workspace))?.unwrap_or_default())
+}
+
+/// Fully index `directory` and everything below it
+pub fn expand(workspace: &Path, directory: &str) -> Result<SparseSet> {
+    let directory = relative_dir(workspace, directory)?;
+    if directory.is_empty() || !workspace.join(&directory).is_dir() {
+        return Err(anyhow!("Not a directory: {}", directory));
+    }
+    let mut set = load(workspace)?;
+    if !set.directories.iter().any(|selected| within(&directory, selected)) {
+        set.directories.retain(|selected| !within(selected, &directory));
+        set.directories.push(directory);
+        set.directories.sort();
+        storage::write_json(&sparse_path(workspace), &set)?;
+    }
+    Ok(set)
+}
+
+/// Drop `directory` and the selected directories below it back to metadata-only indexing
+pub fn contract(workspace: &Path, directory: &str) -> Result<SparseSet> {
+    let directory = relative_dir(workspace, directory)?;
+    let mut set = load(workspace)?;
+    let parent = set.directories.iter().find(|selected| **selected != directory && within(&directory, selected));
+    if let Some(parent) = parent {
+        return Err(anyhow!("{} is indexed as part of {}; contract {} instead", directory, parent, parent));
+    }
+    let before = set.directories.len();
+    set.directories.retain(|selected| !within(selected, &directory));
+    if set.directories.len() != before {
+        storage::write_json(&sparse_path(workspace), &set)?;
+    }
+    Ok(set)
+}
+
+/// Directories of the files changed by commits in the last `days` days
+pub fn recent_directories(workspace: &Path, days: u32) -> Vec<String> {
+    if days == 0 || !workspace.join(".git").exists() {
+        return Vec::new();
+    }
+    let since = format!("--since={} days ago", days);
+    let log = match git::run(workspace, &["log", &since, "--name-only", "--pretty=format:"]) {
+        Ok(log) => log,
+        Err(e) => {
+            log::warn!("No recently changed directories: {}", e);
+            return Vec::new();
+        }
+    };
+    let directories: BTreeSet<String> = log
+        .lines()
+        .filter_map(|file| file.trim().rsplit_once('/').map(|(directory, _)| directory.to_string()))
+        .filter(|directory| workspace.join(directory).is_dir())
+        .collect();
+    directories.into_iter().collect()
+}
+
+/// Everything to index fully: the selection plus recently changed directories, outermost only
+pub fn directories(selected: &[String], recent: &[String]) -> Vec<String> {
+    let mut all: Vec<&String> = selected.iter().chain(recent).collect();
+    all.sort();
+    all.dedup();
+    let mut outermost: Vec<String> = Vec::new();
+    for directory in all {
+        if !outermost.iter().any(|parent| within(directory, parent)) {
+            outermost.push(directory.clone());
+        }
+    }
+    outermost
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_expand_and_contract() {
+        let workspace = std::env::temp_dir().join(storage::new_id("sparse-test"));
+        fs::create_dir_all(workspace.join("packages/web/src")).unwrap();
+        fs::create_dir_all(workspace.join("packages/api")).unwrap();
+
+        expand(&workspace, "packages/web/src").unwrap();
+        let set = expand(&workspace, "packages/web/").unwrap();
+        assert_eq!(set.directories, vec!["packages/web".to_string()]);
+        assert!(expand(&workspace, "../elsewhere").is_err());
+        assert!(contract(&workspace, "packages/web/src").is_err());
+
+        let all = directories(&set.directories, &["packages/web/src".to_string(), "packages/api".to_string()]);
+        assert_eq!(all, vec!["packages/api".to_string(), "packages/web".to_string()]);
+        assert!(includes(&all, "packages/web/src/main.ts"));
+        assert!(includes(&all, "package.json"));
+        assert!(!includes(&all, "packages/webapp/index.ts"));
+
+        assert!(contract(&workspace, "packages").unwrap().directories.is_empty());
+        let _ = fs::remove_dir_all(&workspace);
+    }
+}
@@ -0,0 +1,368 @@
+// Spell Checking - Misspelled words in comments, strings and identifiers
+// Bundled English and programming dictionaries plus per-workspace custom words
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Result};
+
+use crate::file_indexer::FileIndex;
+use crate::sloc::{self, Segment, SegmentKind};
+use crate::storage;
+use crate::CodeSuggestion;
+
+const ENGLISH: &str = include_str!("../dictionaries/en.txt");
+const PROGRAMMING: &str = include_str!("../dictionaries/code.txt");
+
+/// Source name of spelling diagnostics
+pub const SPELLCHECK_SOURCE: &str = "spellcheck";
+
+/// Shorter words are too often abbreviations to be worth checking
+const MIN_WORD_LEN: usize = 4;
+const MAX_CANDIDATES: usize = 3;
+
+/// Inflections accepted on dictionary words: (suffix, what it replaces)
+const SUFFIXES: &[(&str, &str)] = &[
+    ("ies", "y"),
+    ("ied", "y"),
+    ("ing", ""),
+    ("ing", "e"),
+    ("ed", ""),
+    ("ed", "e"),
+    ("es", ""),
+    ("s", ""),
+    ("er", ""),
+    ("er", "e"),
+    ("ier", "y"),
+    ("est", ""),
+    ("est", "e"),
+    ("iest", "y"),
+    ("ly", ""),
+    ("ion", ""),
+    ("ion", "e"),
+    ("ive", ""),
+    ("ive", "e"),
+    ("al", ""),
+    ("al", "e"),
+    ("ness", ""),
+    ("ment", ""),
+    ("able", ""),
+    ("less", ""),
+    ("ful", ""),
+];
+const PREFIXES: &[&str] = &["un", "re", "pre", "non", "sub", "multi", "de", "over", "auto", "inter"];
+
+pub struct SpellChecker {
+    words: HashSet<String>,
+    custom: HashSet<String>,
+}
+
+fn dictionary_path(workspace: &Path) -> PathBuf {
+    storage::data_dir(workspace).join("dictionary.json")
+}
+
+/// Words the workspace accepts on top of the bundled dictionaries, sorted
+pub fn custom_words(workspace: &Path) -> Result<Vec<String>> {
+    Ok(storage::read_json(&dictionary_path(workspace))?.unwrap_or_default())
+}
+
+pub fn add_custom_word(workspace: &Path, word: &str) -> Result<Vec<String>> {
+    let word = word.trim().to_lowercase();
+    if word.is_empty() || !word.chars().all(char::is_alphabetic) {
+        return Err(anyhow!("Not a word: {}", word));
+    }
+    let mut words = custom_words(workspace)?;
+    if let Err(at) = words.binary_search(&word) {
+        words.insert(at, word);
+        storage::write_json(&dictionary_path(workspace), &words)?;
+    }
+    Ok(words)
+}
+
+pub fn remove_custom_word(workspace: &Path, word: &str) -> Result<Vec<String>> {
+    let word = word.trim().to_lowercase();
+    let mut words = custom_words(workspace)?;
+    if let Ok(at) = words.binary_search(&word) {
+        words.remove(at);
+        storage::write_json(&dictionary_path(workspace), &words)?;
+    }
+    Ok(words)
+}
+
+/// Optimal string alignment distance: edits plus adjacent transpositions
+fn distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Every string one deletion, transposition, replacement or insertion away from `word`
+fn edits(word: &str) -> Vec<String> {
+    let chars: Vec<char> = word.chars().collect();
+    let mut result = Vec::new();
+    for i in 0..=chars.len() {
+        if i < chars.len() {
+            let mut deleted = chars.clone();
+            deleted.remove(i);
+            result.push(deleted.into_iter().collect());
+        }
+        if i + 1 < chars.len() {
+            let mut swapped = chars.clone();
+            swapped.swap(i, i + 1);
+            result.push(swapped.into_iter().collect());
+        }
+        for c in 'a'..='z' {
+            if i < chars.len() && chars[i] != c {
+                let mut replaced = chars.clone();
+                replaced[i] = c;
+                result.push(replaced.into_iter().collect());
+            }
+            let mut inserted = chars.clone();
+            inserted.insert(i, c);
+            result.push(inserted.into_iter().collect());
+        }
+    }
+    result
+}
+
+/// Words of an identifier or prose token with their byte offsets: split at
+/// non-letters and case changes, so `parseHTTPHeader` yields parse, HTTP, Header
+fn sub_words(token: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let chars: Vec<(usize, char)> = token.char_indices().collect();
+    let mut start: Option<usize> = None;
+    for (i, &(at, c)) in chars.iter().enumerate() {
+        if !c.is_alphabetic() {
+            if let Some(begin) = start.take() {
+                words.push((begin, &token[begin..at]));
+            }
+            continue;
+        }
+        let previous = if i > 0 { Some(chars[i - 1].1) } else { None };
+        let next = chars.get(i + 1).map(|&(_, c)| c);
+        let boundary = match previous {
+            // fooBar
+            Some(p) if p.is_lowercase() && c.is_uppercase() => true,
+            // HTTPHeader: the last capital starts the next word
+            Some(p) if p.is_uppercase() && c.is_uppercase() => next.is_some_and(char::is_lowercase),
+            _ => false,
+        };
+        match start {
+            Some(begin) if boundary => {
+                words.push((begin, &token[begin..at]));
+                start = Some(at);
+            }
+            None => start = Some(at),
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        words.push((begin, &token[begin..]));
+    }
+    words
+}
+
+/// `word` spelled as `original` is cased: lower, Capitalized or UPPER
+fn match_case(original: &str, word: &str) -> String {
+    if original.chars().all(char::is_uppercase) {
+        return word.to_uppercase();
+    }
+    if original.chars().next().is_some_and(char::is_uppercase) {
+        let mut chars = word.chars();
+        return match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        };
+    }
+    word.to_string()
+}
+
+impl SpellChecker {
+    pub fn new() -> Self {
+        let words = ENGLISH.lines().chain(PROGRAMMING.lines()).map(str::trim).filter(|w| !w.is_empty());
+        Self {
+            words: words.map(str::to_string).collect(),
+            custom: HashSet::new(),
+        }
+    }
+
+    /// Take the custom words of a newly opened workspace
+    pub fn load_workspace(&mut self, workspace: &Path) -> Result<()> {
+        self.custom = custom_words(workspace)?.into_iter().collect();
+        Ok(())
+    }
+
+    fn in_dictionary(&self, word: &str) -> bool {
+        self.words.contains(word) || self.custom.contains(word)
+    }
+
+    /// A lowercase word, possibly inflected or prefixed, is spelled correctly
+    fn is_known(&self, word: &str) -> bool {
+        self.is_inflection(word)
+            || PREFIXES.iter().any(|prefix| match word.strip_prefix(prefix) {
+                Some(rest) if rest.len() >= 3 => self.is_inflection(rest),
+                _ => false,
+            })
+    }
+
+    fn is_inflection(&self, word: &str) -> bool {
+        if self.in_dictionary(word) {
+            return true;
+        }
+        SUFFIXES.iter().any(|(suffix, replaced)| {
+            let stem = match word.strip_suffix(suffix) {
+                Some(stem) if stem.len() >= 2 => stem,
+                _ => return false,
+            };
+            if self.in_dictionary(&format!("{}{}", stem, replaced)) {
+                return true;
+            }
+            // Doubled final consonant: running, stopped
+            let bytes = stem.as_bytes();
+            replaced.is_empty()
+                && bytes.len() >= 3
+                && bytes[bytes.len() - 1] == bytes[bytes.len() - 2]
+                && self.in_dictionary(&stem[..stem.len() - 1])
+        })
+    }
+
+    /// Likely intended spellings of an unknown lowercase word, closest first
+    pub fn candidates(&self, word: &str) -> Vec<String> {
+        let mut found: Vec<String> = edits(word).into_iter().filter(|e| self.is_known(e)).collect();
+        found.sort();
+        found.dedup();
+        let chars: Vec<char> = word.chars().collect();
+        if found.is_empty() && chars.len() > MIN_WORD_LEN {
+            found = self
+                .words
+                .iter()
+                .chain(&self.custom)
+                .filter(|w| w.len().abs_diff(word.len()) <= 2)
+                .filter(|w| distance(&chars, &w.chars().collect::<Vec<_>>()) <= 2)
+                .cloned()
+                .collect();
+        }
+        // Typos rarely change the first letter
+        let first = chars.first().copied();
+        found.sort_by(|a, b| {
+            let key = |w: &String| {
+                let candidate: Vec<char> = w.chars().collect();
+                (distance(&chars, &candidate), candidate.first().copied() != first, w.len().abs_diff(word.len()))
+            };
+            key(a).cmp(&key(b)).then_with(|| a.cmp(b))
+        });
+        found.truncate(MAX_CANDIDATES);
+        found
+    }
+
+    /// Suggestion for a misspelled word of `token` at `offset`; `None` when it is spelled fine,
+    /// an acronym, or nothing close to it is known
+    fn check_word(&self, token: &str, offset: usize, word: &str, line: usize, column: usize) -> Option<CodeSuggestion> {
+        if word.chars().count() < MIN_WORD_LEN || word.chars().all(char::is_uppercase) {
+            return None;
+        }
+        let lower = word.to_lowercase();
+        if self.is_known(&lower) {
+            return None;
+        }
+        let candidates = self.candidates(&lower);
+        let best = candidates.first()?;
+        let quoted: Vec<String> = candidates.iter().map(|c| format!("'{}'", match_case(word, c))).collect();
+        let fix = format!("{}{}{}", &token[..offset], match_case(word, best), &token[offset + word.len()..]);
+        Some(CodeSuggestion {
+            kind: "spelling".to_string(),
+            message: format!("'{}' may be misspelled; did you mean {}?", word, quoted.join(", ")),
+            line,
+            column,
+            severity: "info".to_string(),
+            fix: Some(fix),
+        })
+    }
+
+    /// Misspellings in comments, strings and identifiers; each identifier is reported once per file
+    pub fn check(&self, file_path: &str, content: &str) -> Vec<CodeSuggestion> {
+        let extension = Path::new(file_path).extension().and_then(|e| e.to_str()).unwrap_or("");
+        let language = FileIndex::detect_language(extension);
+        let segments = if matches!(extension, "md" | "txt" | "rst") {
+            content
+                .lines()
+                .enumerate()
+                .map(|(i, line)| Segment { line: i + 1, column: 0, kind: SegmentKind::Comment, text: line })
+                .collect()
+        } else {
+            sloc::segments(content, &language)
+        };
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut suggestions = Vec::new();
+
+        for span in segments {
+            match span.kind {
+                SegmentKind::Identifier => {
+                    if !seen.insert(span.text) {
+                        continue;
+                    }
+                    for (offset, word) in sub_words(span.text) {
+                        suggestions.extend(self.check_word(span.text, offset, word, span.line, span.column + offset));
+                    }
+                }
+                SegmentKind::Comment | SegmentKind::Literal => {
+                    let mut at = 0;
+                    for token in span.text.split(|c: char| c.is_whitespace()) {
+                        let start = at;
+                        at += token.len() + 1;
+                        let trimmed = token.trim_matches(|c: char| !c.is_alphanumeric());
+                        // Paths, URLs, numbers and code references are not prose
+                        if trimmed.is_empty() || !trimmed.chars().all(|c| c.is_alphabetic() || c == '-' || c == '\'') {
+                            continue;
+                        }
+                        let lead = token.find(trimmed).unwrap_or(0);
+                        for (offset, word) in sub_words(trimmed) {
+                            suggestions.extend(self.check_word(trimmed, offset, word, span.line, span.column + start + lead + offset));
+                        }
+                    }
+                }
+            }
+        }
+        suggestions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sub_words() {
+        let words: Vec<&str> = sub_words("parseHTTPHeader_v2Value").into_iter().map(|(_, w)| w).collect();
+        assert_eq!(words, vec!["parse", "HTTP", "Header", "v", "Value"]);
+    }
+
+    #[test]
+    fn test_check_comments_strings_and_identifiers() {
+        let mut checker = SpellChecker::new();
+        let source = "// Recieve the mesage\nfn load_cofnig() {\n    let s = \"wrokspace ok\";\n    load_cofnig();\n}\n";
+        let suggestions = checker.check("main.rs", source);
+        let fixes: Vec<(usize, &str)> = suggestions.iter().map(|s| (s.line, s.fix.as_deref().unwrap_or(""))).collect();
+        assert_eq!(fixes, vec![(1, "Receive"), (1, "message"), (2, "load_config"), (3, "workspace")]);
+        assert_eq!(suggestions[0].column, 3);
+        assert_eq!(suggestions[2].column, 8);
+
+        checker.custom.insert("cofnig".to_string());
+        assert_eq!(checker.check("main.rs", source).len(), 3);
+        assert!(checker.check("main.rs", "// running stopped unpinned HTTP\n").is_empty());
+    }
+}
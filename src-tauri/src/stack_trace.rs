@@ -0,0 +1,391 @@
+// Stack Traces - Frames of pasted Rust, Node and Python traces, resolved to workspace files
+// Frames are matched directly, by path suffix against the index, or through source maps of built code
+
+use std::path::{Component, Path};
+use serde::{Deserialize, Serialize};
+
+use crate::source_map::{self, SourceMapCache};
+
+/// Frames kept from one trace
+const MAX_FRAMES: usize = 200;
+/// Scripts whose frames may point into generated code
+const SCRIPT_EXTENSIONS: &[&str] = &["js", "mjs", "cjs"];
+/// Path fragments of runtime and dependency code
+const LIBRARY_MARKERS: &[&str] = &[
+    "/rustc/", ".cargo/registry", ".rustup/", "/library/std/", "/library/core/", "node_modules", "site-packages",
+    "dist-packages", "/lib/python", "<frozen ",
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceLanguage {
+    Rust,
+    /// Node and browser JavaScript
+    Node,
+    Python,
+}
+
+/// How a frame was matched to a file
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    /// The path exists as written
+    Direct,
+    /// Indexed file sharing the trailing directories of the path
+    Index,
+    /// Indexed file with the same name or stem only
+    Fuzzy,
+    SourceMap,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FrameLocation {
+    pub path: String,
+    /// Workspace-relative path when the file is in the workspace
+    pub relative_path: Option<String>,
+    pub line: u32,
+    pub column: Option<u32>,
+    pub resolution: Resolution,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StackFrame {
+    pub language: TraceLanguage,
+    pub function: Option<String>,
+    /// Location as written in the trace; lines and columns are 1-based
+    pub file: String,
+    pub line: u32,
+    pub column: Option<u32>,
+    /// 1-based line of the frame in the pasted text
+    pub text_line: usize,
+    /// Frame in the standard library, runtime internals or a dependency
+    pub library: bool,
+    pub location: Option<FrameLocation>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StackTrace {
+    /// Language of most frames
+    pub language: Option<TraceLanguage>,
+    /// Panic or exception message
+    pub message: Option<String>,
+    /// Innermost frame first for Rust and Node, as Python prints them (outermost first) for Python
+    pub frames: Vec<StackFrame>,
+}
+
+/// Split `path:line[:column]` from the right, so drive letters and URL schemes stay in the path
+fn split_location(location: &str) -> Option<(&str, u32, Option<u32>)> {
+    let location = location.trim().trim_start_matches('(').trim_end_matches([')', ':']);
+    let (rest, last) = location.rsplit_once(':')?;
+    let last: u32 = last.parse().ok()?;
+    match rest.rsplit_once(':') {
+        Some((path, line)) if !path.is_empty() && line.parse::<u32>().is_ok() => {
+            Some((path, line.parse().ok()?, Some(last)))
+        }
+        _ if !rest.is_empty() => Some((rest, last, None)),
+        _ => None,
+    }
+}
+
+fn is_library(file: &str) -> bool {
+    let file = file.replace('\\', "/");
+    file.starts_with("node:") || file.starts_with("internal/") || LIBRARY_MARKERS.iter().any(|m| file.contains(m))
+}
+
+/// `N: function` of a Rust backtrace
+fn rust_frame_function(line: &str) -> Option<&str> {
+    let (index, function) = line.split_once(": ")?;
+    (!index.is_empty() && index.chars().all(|c| c.is_ascii_digit())).then_some(function.trim())
+}
+
+/// Location and message of a Rust panic line, in the format since 1.73 and the older one
+fn rust_panic(line: &str) -> Option<(&str, Option<&str>)> {
+    let (_, rest) = line.split_once("panicked at ")?;
+    if let Some(quoted) = rest.strip_prefix('\'') {
+        let (message, location) = quoted.rsplit_once("', ")?;
+        return Some((location, Some(message)));
+    }
+    Some((rest.trim_end_matches(':'), None))
+}
+
+/// `at function (location)`, `at location` or the browser form `function@location`
+fn node_frame(line: &str) -> Option<(Option<&str>, &str)> {
+    if let Some(rest) = line.strip_prefix("at ") {
+        let rest = rest.trim_start_matches("async ");
+        return match rest.strip_suffix(')').and_then(|r| r.rsplit_once(" (")) {
+            Some((function, location)) => Some((Some(function), location)),
+            None => Some((None, rest)),
+        };
+    }
+    let (function, location) = line.split_once('@')?;
+    (location.contains(':') && !function.contains(' ')).then_some((Some(function).filter(|f| !f.is_empty()), location))
+}
+
+/// `File "path", line N, in function`
+fn python_frame(line: &str) -> Option<(&str, u32, Option<&str>)> {
+    let rest = line.strip_prefix("File \"")?;
+    let (path, rest) = rest.split_once('"')?;
+    let rest = rest.trim_start_matches(',').trim().strip_prefix("line ")?;
+    let (number, function) = match rest.split_once(',') {
+        Some((number, function)) => (number, function.trim().strip_prefix("in ")),
+        None => (rest, None),
+    };
+    Some((path, number.trim().parse().ok()?, function))
+}
+
+/// Lines that frame a trace rather than state its message
+fn is_preamble(line: &str) -> bool {
+    line.starts_with("Traceback (most recent call last)")
+        || line.starts_with("stack backtrace:")
+        || line.starts_with("note: ")
+        || line.starts_with("During handling of the above exception")
+        || line.starts_with("The above exception was the direct cause")
+}
+
+pub fn parse(text: &str) -> StackTrace {
+    let mut frames: Vec<StackFrame> = Vec::new();
+    let mut message: Option<String> = None;
+    let mut python_message: Option<String> = None;
+    let mut rust_function: Option<String> = None;
+    let frame = |language, function: Option<&str>, file: &str, line, column, text_line| StackFrame {
+        language,
+        function: function.map(str::to_string),
+        file: file.to_string(),
+        line,
+        column,
+        text_line,
+        library: is_library(file),
+        location: None,
+    };
+
+    for (i, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || frames.len() >= MAX_FRAMES {
+            continue;
+        }
+        if let Some((location, panic_message)) = rust_panic(line) {
+            if let Some((file, number, column)) = split_location(location) {
+                frames.push(frame(TraceLanguage::Rust, None, file, number, column, i + 1));
+            }
+            if let Some(panic_message) = panic_message {
+                message.get_or_insert_with(|| panic_message.to_string());
+            }
+            continue;
+        }
+        if let Some(function) = rust_frame_function(line) {
+            rust_function = Some(function.to_string());
+            continue;
+        }
+        if let Some(function) = rust_function.take() {
+            if let Some((file, number, column)) = line.strip_prefix("at ").and_then(split_location) {
+                frames.push(frame(TraceLanguage::Rust, Some(function.as_str()), file, number, column, i + 1));
+                continue;
+            }
+        }
+        if let Some((file, number, function)) = python_frame(line) {
+            frames.push(frame(TraceLanguage::Python, function, file, number, None, i + 1));
+            continue;
+        }
+        if let Some((function, location)) = node_frame(line) {
+            if let Some((file, number, column)) = split_location(location) {
+                frames.push(frame(TraceLanguage::Node, function, file, number, column, i + 1));
+                continue;
+            }
+        }
+        // The source line Python prints under each frame is indented
+        let indented = raw.starts_with(' ') || raw.starts_with('\t');
+        if is_preamble(line) || (indented && frames.last().is_some_and(|f| f.language == TraceLanguage::Python)) {
+            continue;
+        }
+        if !frames.is_empty() && !indented {
+            python_message = Some(line.to_string());
+        }
+        message.get_or_insert_with(|| line.to_string());
+    }
+
+    let count = |language| frames.iter().filter(|frame| frame.language == language).count();
+    let language = [TraceLanguage::Rust, TraceLanguage::Node, TraceLanguage::Python]
+        .into_iter()
+        .filter(|language| count(*language) > 0)
+        .max_by_key(|language| count(*language));
+    // Python prints the exception after the frames
+    if language == Some(TraceLanguage::Python) {
+        message = python_message.or(message);
+    }
+    StackTrace {
+        language,
+        message,
+        frames,
+    }
+}
+
+fn normal_parts(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn stem(name: &str) -> String {
+    name.split('.').next().unwrap_or(name).to_lowercase()
+}
+
+/// Resolves frame locations against the workspace, its indexed files and source maps
+pub struct Resolver<'a> {
+    workspace: &'a Path,
+    /// Workspace-relative components of every indexed file
+    indexed: Vec<Vec<String>>,
+    source_maps: &'a mut SourceMapCache,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(
+        workspace: &'a Path,
+        indexed: impl Iterator<Item = String>,
+        source_maps: &'a mut SourceMapCache,
+    ) -> Self {
+        let indexed = indexed
+            .filter_map(|path| Path::new(&path).strip_prefix(workspace).ok().map(normal_parts))
+            .collect();
+        Self {
+            workspace,
+            indexed,
+            source_maps,
+        }
+    }
+
+    fn location(&self, parts: &[String], line: u32, column: Option<u32>, resolution: Resolution) -> FrameLocation {
+        let path = parts.iter().fold(self.workspace.to_path_buf(), |path, part| path.join(part));
+        FrameLocation {
+            path: path.to_string_lossy().to_string(),
+            relative_path: Some(parts.join("/")),
+            line,
+            column,
+            resolution,
+        }
+    }
+
+    /// Indexed file sharing the most trailing components with `parts`; on a tie the shallowest wins.
+    /// Without any exact match, a file with the same stem (e.g. `dist/app.js` for `src/app.ts`) is taken
+    fn match_indexed(&self, parts: &[String]) -> Option<(&[String], Resolution)> {
+        let name = parts.last()?;
+        let shared = |candidate: &[String]| {
+            candidate.iter().rev().zip(parts.iter().rev()).take_while(|(a, b)| a == b).count()
+        };
+        let best = self
+            .indexed
+            .iter()
+            .map(|candidate| (shared(candidate), candidate))
+            .filter(|(score, _)| *score > 0)
+            .max_by(|(a, x), (b, y)| a.cmp(b).then_with(|| y.len().cmp(&x.len())));
+        if let Some((score, candidate)) = best {
+            let resolution = if score >= 2 || parts.len() == 1 { Resolution::Index } else { Resolution::Fuzzy };
+            return Some((candidate, resolution));
+        }
+        let stem = stem(name);
+        self.indexed
+            .iter()
+            .filter(|candidate| candidate.last().is_some_and(|last| stem(last) == stem))
+            .min_by_key(|candidate| candidate.len())
+            .map(|candidate| (candidate.as_slice(), Resolution::Fuzzy))
+    }
+
+    pub fn resolve(&mut self, frame: &StackFrame) -> Option<FrameLocation> {
+        let file = frame.file.as_str();
+        let extension = Path::new(file.split(['?', '#']).next().unwrap_or(file)).extension();
+        let script = extension.is_some_and(|ext| SCRIPT_EXTENSIONS.iter().any(|e| ext == *e));
+        if script && frame.column.is_some() {
+            let resolved = self.source_maps.resolve(self.workspace, file, frame.line, frame.column.unwrap_or(1));
+            if let Ok(Some(original)) = resolved.map(|original| original.filter(|o| o.exists)) {
+                return Some(FrameLocation {
+                    path: original.path,
+                    relative_path: original.relative_path,
+                    line: original.line,
+                    column: Some(original.column),
+                    resolution: Resolution::SourceMap,
+                });
+            }
+        }
+        // Handles `file://` URLs and bundler schemes such as `webpack://app/src/x.ts` as well
+        let path = source_map::source_path(self.workspace, self.workspace, file);
+        if path.is_file() {
+            let relative = path.strip_prefix(self.workspace).ok().map(|p| p.to_string_lossy().replace('\\', "/"));
+            return Some(FrameLocation {
+                path: path.to_string_lossy().to_string(),
+                relative_path: relative,
+                line: frame.line,
+                column: frame.column,
+                resolution: Resolution::Direct,
+            });
+        }
+        if frame.library {
+            return None;
+        }
+        let parts = normal_parts(&path);
+        let (candidate, resolution) = self.match_indexed(&parts)?;
+        Some(self.location(candidate, frame.line, frame.column, resolution))
+    }
+}
+
+/// Parse `text` and resolve each frame to a file where possible
+pub fn parse_and_resolve(text: &str, resolver: &mut Resolver) -> StackTrace {
+    let mut trace = parse(text);
+    for frame in &mut trace.frames {
+        frame.location = resolver.resolve(frame);
+    }
+    trace
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_rust_node_and_python_traces() {
+        let rust = "thread 'main' panicked at src/config.rs:42:9:\n\
+                    missing key `port`\n\
+                    stack backtrace:\n   0: rust_begin_unwind\n\
+                    \x20            at /rustc/abc/library/std/src/panicking.rs:645:5\n\
+                    \x20  1: app::config::load\n             at ./src/config.rs:42:9\n";
+        let trace = parse(rust);
+        assert_eq!(trace.language, Some(TraceLanguage::Rust));
+        assert_eq!(trace.message.as_deref(), Some("missing key `port`"));
+        assert_eq!(trace.frames.len(), 3);
+        assert!(trace.frames[1].library);
+        assert_eq!(trace.frames[2].function.as_deref(), Some("app::config::load"));
+        assert_eq!((trace.frames[2].line, trace.frames[2].column), (42, Some(9)));
+        assert_eq!(parse("thread 'main' panicked at 'boom', src/lib.rs:3:5").message.as_deref(), Some("boom"));
+
+        let node = "TypeError: Cannot read properties of undefined\n    at render (/srv/app/src/view.js:10:15)\n\
+                    \x20   at async Promise.all (index 0)\n    at node:internal/main:1:2\n\
+                    \x20   at C:\\app\\lib\\x.js:3:4";
+        let trace = parse(node);
+        assert_eq!(trace.language, Some(TraceLanguage::Node));
+        assert_eq!(trace.message.as_deref(), Some("TypeError: Cannot read properties of undefined"));
+        assert_eq!(trace.frames.len(), 3);
+        assert!(trace.frames[1].library);
+        assert_eq!(trace.frames[2].file, "C:\\app\\lib\\x.js");
+
+        let python = "Traceback (most recent call last):\n  File \"/home/ci/app/main.py\", line 8, in <module>\n\
+                      \x20   run()\n  File \"/home/ci/app/pkg/run.py\", line 3, in run\n    raise ValueError(\"bad\")\n\
+                      ValueError: bad";
+        let trace = parse(python);
+        assert_eq!(trace.language, Some(TraceLanguage::Python));
+        assert_eq!(trace.message.as_deref(), Some("ValueError: bad"));
+        assert_eq!(trace.frames[1].function.as_deref(), Some("run"));
+
+        let workspace = Path::new("/nonexistent/ws");
+        let indexed = ["/nonexistent/ws/pkg/run.py", "/nonexistent/ws/main.py", "/nonexistent/ws/src/view.ts"];
+        let mut maps = SourceMapCache::new();
+        let mut resolver = Resolver::new(workspace, indexed.iter().map(|p| p.to_string()), &mut maps);
+        let trace = parse_and_resolve(python, &mut resolver);
+        let location = trace.frames[1].location.as_ref().unwrap();
+        assert_eq!((location.relative_path.as_deref(), location.resolution), (Some("pkg/run.py"), Resolution::Index));
+        let trace = parse_and_resolve(node, &mut resolver);
+        let location = trace.frames[0].location.as_ref().unwrap();
+        assert_eq!((location.relative_path.as_deref(), location.resolution), (Some("src/view.ts"), Resolution::Fuzzy));
+        assert!(trace.frames[1].location.is_none());
+    }
+}
@@ -0,0 +1,126 @@
+// Standby - Indexes of recently closed workspaces, kept in memory for a quick switch back
+// Reopening a parked workspace re-indexes only the files that changed while it was away
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use crate::file_indexer::FileIndex;
+use crate::mimi_engine::CodeGraph;
+
+struct Parked {
+    workspace: PathBuf,
+    index: FileIndex,
+    graph: CodeGraph,
+    parked_at: SystemTime,
+    bytes: usize,
+}
+
+pub struct Standby {
+    /// Workspace whose complete index and graph are in the app state
+    indexed: Option<PathBuf>,
+    /// Most recently parked first
+    parked: VecDeque<Parked>,
+}
+
+impl Standby {
+    pub fn new() -> Self {
+        Self {
+            indexed: None,
+            parked: VecDeque::new(),
+        }
+    }
+
+    /// Record that the full index of `workspace` is now in the app state
+    pub fn set_indexed(&mut self, workspace: &Path) {
+        self.indexed = Some(workspace.to_path_buf());
+    }
+
+    /// The workspace with a complete index in the app state, forgetting it; partial indexes return `None`
+    pub fn take_indexed(&mut self) -> Option<PathBuf> {
+        self.indexed.take()
+    }
+
+    /// Keep `index` and `graph` of `workspace`, evicting the least recent ones beyond `limit` or `budget` bytes
+    pub fn park(&mut self, workspace: &Path, index: FileIndex, graph: CodeGraph, limit: usize, budget: usize) {
+        self.parked.retain(|parked| parked.workspace != workspace);
+        let bytes = index.approximate_bytes() + graph.approximate_bytes();
+        self.parked.push_front(Parked {
+            workspace: workspace.to_path_buf(),
+            index,
+            graph,
+            parked_at: SystemTime::now(),
+            bytes,
+        });
+        while self.parked.len() > limit || self.parked.iter().map(|parked| parked.bytes).sum::<usize>() > budget {
+            if let Some(evicted) = self.parked.pop_back() {
+                log::info!("Dropping the standby index of {:?}", evicted.workspace);
+            }
+        }
+    }
+
+    /// Take the parked index and graph of `workspace`, with the time they were parked
+    pub fn take(&mut self, workspace: &Path) -> Option<(FileIndex, CodeGraph, SystemTime)> {
+        let position = self.parked.iter().position(|parked| parked.workspace == workspace)?;
+        self.parked.remove(position).map(|parked| (parked.index, parked.graph, parked.parked_at))
+    }
+
+    pub fn count(&self) -> usize {
+        self.parked.len()
+    }
+}
+
+/// Files modified after `since`, files missing from `index` and indexed files that are gone
+pub fn changed_since(workspace: &Path, index: &FileIndex, since: SystemTime) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = WalkDir::new(workspace)
+        .into_iter()
+        .filter_entry(|e| !FileIndex::is_excluded(e.path()))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            let modified = e.metadata().ok().and_then(|m| m.modified().ok());
+            modified.is_none_or(|modified| modified > since) || index.get(&e.path().to_string_lossy()).is_none()
+        })
+        .map(|e| e.path().to_path_buf())
+        .collect();
+    changed.extend(index.paths().map(PathBuf::from).filter(|path| !path.exists()));
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_park_evicts_least_recent() {
+        let mut standby = Standby::new();
+        for name in ["a", "b", "c"] {
+            standby.park(Path::new(name), FileIndex::new(), CodeGraph::new(), 2, usize::MAX);
+        }
+        assert_eq!(standby.count(), 2);
+        assert!(standby.take(Path::new("a")).is_none());
+        assert!(standby.take(Path::new("b")).is_some());
+        standby.park(Path::new("d"), FileIndex::new(), CodeGraph::new(), 2, 0);
+        assert_eq!(standby.count(), 0);
+    }
+
+    #[test]
+    fn test_changed_since_finds_new_and_deleted_files() {
+        let workspace = std::env::temp_dir().join(crate::storage::new_id("standby-test"));
+        fs::create_dir_all(&workspace).unwrap();
+        fs::write(workspace.join("kept.ts"), "export const kept = 1").unwrap();
+        fs::write(workspace.join("deleted.ts"), "export const deleted = 1").unwrap();
+        let mut index = FileIndex::new();
+        index.index_directory(&workspace).unwrap();
+
+        let since = SystemTime::now();
+        fs::remove_file(workspace.join("deleted.ts")).unwrap();
+        fs::write(workspace.join("added.ts"), "export const added = 1").unwrap();
+        let mut changed = changed_since(&workspace, &index, since);
+        changed.sort();
+        assert_eq!(changed, vec![workspace.join("added.ts"), workspace.join("deleted.ts")]);
+        let _ = fs::remove_dir_all(&workspace);
+    }
+}
@@ -0,0 +1,238 @@
+// Directory Stats - Per-folder breakdown of the workspace statistics
+// Lets the UI drill down from the workspace totals one directory at a time
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::changeset;
+use crate::events::Event;
+use crate::file_indexer::{FileIndex, IndexTotals};
+use crate::index_store;
+use crate::renames;
+use crate::sloc::LineCounts;
+use crate::AppState;
+
+/// Keywords and operators that add a branch to the control flow
+const DECISION_WORDS: &[&str] = &["if", "elif", "for", "while", "case", "catch", "except", "when", "guard"];
+const DECISION_OPERATORS: &[&str] = &["&&", "||"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct FolderStats {
+    pub name: String,
+    pub path: String,
+    pub files: usize,
+    /// Lines of non-generated files
+    pub lines: usize,
+    /// `lines` split into code, comment and blank lines
+    pub line_counts: LineCounts,
+    pub languages: HashMap<String, usize>,
+    pub diagnostics: usize,
+    /// Mean of the per-file complexity estimates, if any file has one
+    pub average_complexity: Option<f64>,
+    #[serde(skip)]
+    complexity_total: usize,
+    #[serde(skip)]
+    complexity_files: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DirectoryStats {
+    pub total: FolderStats,
+    /// Immediate subdirectories, largest by line count first
+    pub subdirectories: Vec<FolderStats>,
+    /// Files directly inside the directory
+    pub direct_files: usize,
+}
+
+/// Fields of `WorkspaceStats` that changed; languages map to their new values, zero once gone
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct StatsChanges {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_files: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_lines: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated_files: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_counts: Option<LineCounts>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub by_language: HashMap<String, usize>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub line_counts_by_language: HashMap<String, LineCounts>,
+}
+
+impl FolderStats {
+    fn new(name: String, path: String) -> Self {
+        Self {
+            name,
+            path,
+            ..Default::default()
+        }
+    }
+
+    fn finish(mut self) -> Self {
+        self.average_complexity =
+            (self.complexity_files > 0).then(|| self.complexity_total as f64 / self.complexity_files as f64);
+        self
+    }
+}
+
+/// Rough cyclomatic complexity: one plus the number of decision points outside comment lines
+pub fn estimate_complexity(content: &str) -> usize {
+    let mut complexity = 1;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("//") || trimmed.starts_with('#') || trimmed.starts_with('*') {
+            continue;
+        }
+        complexity += trimmed
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|word| DECISION_WORDS.contains(word))
+            .count();
+        complexity += DECISION_OPERATORS.iter().map(|op| trimmed.matches(op).count()).sum::<usize>();
+    }
+    complexity
+}
+
+fn total_line_counts(totals: &IndexTotals) -> LineCounts {
+    let mut total = LineCounts::default();
+    for counts in totals.line_counts_by_language.values() {
+        total += *counts;
+    }
+    total
+}
+
+fn changed_entries<V: Clone + PartialEq + Default>(before: &HashMap<String, V>, after: &HashMap<String, V>) -> HashMap<String, V> {
+    let gone = before.keys().filter(|k| !after.contains_key(*k)).map(|k| (k.clone(), V::default()));
+    after
+        .iter()
+        .filter(|(k, v)| before.get(*k) != Some(*v))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .chain(gone)
+        .collect()
+}
+
+/// What changed between two sets of index totals, or `None` if nothing did
+pub fn diff_totals(before: &IndexTotals, after: &IndexTotals) -> Option<StatsChanges> {
+    let changed = |a: usize, b: usize| (a != b).then_some(b);
+    let (lines_before, lines_after) = (total_line_counts(before), total_line_counts(after));
+    let changes = StatsChanges {
+        total_files: changed(before.files, after.files),
+        total_lines: changed(before.lines, after.lines),
+        generated_files: changed(before.generated_files, after.generated_files),
+        line_counts: (lines_before != lines_after).then_some(lines_after),
+        by_language: changed_entries(&before.by_language, &after.by_language),
+        line_counts_by_language: changed_entries(&before.line_counts_by_language, &after.line_counts_by_language),
+    };
+    (changes != StatsChanges::default()).then_some(changes)
+}
+
+/// Re-index files the app just wrote, follow renames and publish `StatsUpdated` if the totals moved
+pub fn refresh_after_write(state: &AppState, workspace: &Path, paths: &[String]) {
+    let paths: Vec<PathBuf> = paths.iter().map(|p| changeset::resolve(workspace, p)).collect();
+    let (changes, renamed) = {
+        let mut index = state.file_index.lock().unwrap();
+        let before = index.totals().clone();
+        let renamed = index.update_files(workspace, &paths);
+        (diff_totals(&before, index.totals()), renamed)
+    };
+    renames::follow(state, workspace, &renamed);
+    let mut logged: Vec<String> = paths.iter().map(|p| p.to_string_lossy().to_string()).collect();
+    // A rename re-points the imports of the files depending on the moved file
+    for rename in &renamed {
+        logged.extend(state.code_graph.lock().unwrap().get_dependents(&rename.to));
+    }
+    index_store::record(state, workspace, &logged);
+    if let Some(changes) = changes {
+        state.events.publish(Event::StatsUpdated { changes });
+    }
+}
+
+/// Aggregate the indexed files under `dir`, grouped by immediate subdirectory.
+/// `diagnostics` maps file paths to their diagnostic counts.
+pub fn directory_stats(dir: &Path, index: &FileIndex, diagnostics: &HashMap<String, usize>) -> DirectoryStats {
+    let name = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let mut total = FolderStats::new(name, dir.to_string_lossy().to_string());
+    let mut subdirectories: HashMap<String, FolderStats> = HashMap::new();
+    let mut direct_files = 0;
+
+    for info in index.files() {
+        let relative = match Path::new(&info.path).strip_prefix(dir) {
+            Ok(relative) => relative,
+            Err(_) => continue,
+        };
+        let components: Vec<String> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect();
+        let folder = match components.as_slice() {
+            [] => continue,
+            [_] => {
+                direct_files += 1;
+                None
+            }
+            [first, ..] => Some(subdirectories.entry(first.clone()).or_insert_with(|| {
+                FolderStats::new(first.clone(), dir.join(first).to_string_lossy().to_string())
+            })),
+        };
+
+        let diagnostic_count = diagnostics.get(&info.path).copied().unwrap_or(0);
+        for stats in std::iter::once(&mut total).chain(folder) {
+            stats.files += 1;
+            stats.diagnostics += diagnostic_count;
+            if info.generated.is_none() {
+                stats.lines += info.lines;
+                stats.line_counts += info.line_counts;
+                *stats.languages.entry(info.language.clone()).or_insert(0) += 1;
+            }
+            if let Some(complexity) = info.complexity {
+                stats.complexity_total += complexity;
+                stats.complexity_files += 1;
+            }
+        }
+    }
+
+    let mut subdirectories: Vec<FolderStats> = subdirectories.into_values().map(FolderStats::finish).collect();
+    subdirectories.sort_by(|a, b| b.lines.cmp(&a.lines).then(a.name.cmp(&b.name)));
+    DirectoryStats {
+        total: total.finish(),
+        subdirectories,
+        direct_files,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_complexity() {
+        assert_eq!(estimate_complexity("fn main() {}\n"), 1);
+        let branchy = "if a && b {\n    for x in xs {}\n} else if c || d {\n}\n// if in a comment\n";
+        assert_eq!(estimate_complexity(branchy), 6);
+    }
+
+    #[test]
+    fn test_diff_totals_reports_changed_fields_only() {
+        let before = IndexTotals {
+            files: 2,
+            lines: 10,
+            by_language: HashMap::from([("Rust".to_string(), 1), ("Python".to_string(), 1)]),
+            ..Default::default()
+        };
+        assert_eq!(diff_totals(&before, &before), None);
+        let after = IndexTotals {
+            files: 2,
+            lines: 12,
+            by_language: HashMap::from([("Rust".to_string(), 2)]),
+            ..Default::default()
+        };
+        let changes = diff_totals(&before, &after).unwrap();
+        assert_eq!((changes.total_files, changes.total_lines), (None, Some(12)));
+        assert_eq!(changes.by_language, HashMap::from([("Rust".to_string(), 2), ("Python".to_string(), 0)]));
+    }
+}
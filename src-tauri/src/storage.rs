@@ -0,0 +1,71 @@
+// Workspace Storage - Per-workspace engine data under .mimiverse/
+// Small JSON persistence helpers shared by stateful subsystems
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Directory (relative to the workspace root) holding engine data
+pub const DATA_DIR: &str = ".mimiverse";
+
+static ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Get the engine data directory of a workspace
+pub fn data_dir(workspace: &Path) -> PathBuf {
+    workspace.join(DATA_DIR)
+}
+
+/// Read a JSON file, returning `None` if it does not exist
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+/// Write a JSON file atomically (temp file + rename), creating parent directories
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(value)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Milliseconds since the Unix epoch
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Generate a process-unique, time-ordered identifier
+pub fn new_id(prefix: &str) -> String {
+    let count = ID_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{}-{:x}-{:x}", prefix, now_millis(), count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_roundtrip() {
+        let path = std::env::temp_dir()
+            .join(new_id("storage-test"))
+            .join("value.json");
+        write_json(&path, &vec![1, 2, 3]).unwrap();
+        let value: Option<Vec<i32>> = read_json(&path).unwrap();
+        assert_eq!(value, Some(vec![1, 2, 3]));
+        assert_ne!(new_id("x"), new_id("x"));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,407 @@
+// Symbols - Workspace symbol table with qualified names
+// Declarations keyed by module path and container so same-named symbols stay apart
+
+use std::collections::HashMap;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::graphql;
+use crate::idl::{self, IdlKind};
+use crate::imports;
+use crate::syntax;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Class,
+    Interface,
+    Struct,
+    Enum,
+    Trait,
+    Variable,
+    Constant,
+    Type,
+    Module,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SymbolInfo {
+    pub name: String,
+    /// Module path, containers and name: `crate::graph::CodeGraph::new`,
+    /// `pkg.models.User.save`, `src/api/client:Client.get`
+    pub qualified_name: String,
+    /// Enclosing class, impl, trait or module within the file
+    pub container: Option<String>,
+    pub kind: SymbolKind,
+    pub file: String,
+    /// 1-based declaration line
+    pub line: usize,
+    pub exported: bool,
+    /// Declaration line, for telling overloads and same-named symbols apart
+    pub detail: String,
+}
+
+/// Symbols by qualified name, with indexes by bare name and file
+#[derive(Clone)]
+pub struct SymbolTable {
+    symbols: HashMap<String, SymbolInfo>,
+    by_name: HashMap<String, Vec<String>>,
+    by_file: HashMap<String, Vec<String>>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self {
+            symbols: HashMap::new(),
+            by_name: HashMap::new(),
+            by_file: HashMap::new(),
+        }
+    }
+
+    /// Add a symbol; a repeated qualified name (overloads, cfg variants) keeps the first
+    pub fn insert(&mut self, symbol: SymbolInfo) {
+        if self.symbols.contains_key(&symbol.qualified_name) {
+            return;
+        }
+        let qualified = symbol.qualified_name.clone();
+        self.by_name.entry(symbol.name.clone()).or_default().push(qualified.clone());
+        self.by_file.entry(symbol.file.clone()).or_default().push(qualified.clone());
+        self.symbols.insert(qualified, symbol);
+    }
+
+    /// Drop every symbol declared in `file`
+    pub fn remove_file(&mut self, file: &str) {
+        for qualified in self.by_file.remove(file).unwrap_or_default() {
+            if let Some(symbol) = self.symbols.remove(&qualified) {
+                if let Some(names) = self.by_name.get_mut(&symbol.name) {
+                    names.retain(|q| q != &qualified);
+                    if names.is_empty() {
+                        self.by_name.remove(&symbol.name);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Symbols declared in `file`, in declaration order
+    pub fn in_file(&self, file: &str) -> Vec<&SymbolInfo> {
+        self.by_file
+            .get(file)
+            .map(|qualified| qualified.iter().filter_map(|q| self.symbols.get(q)).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, qualified_name: &str) -> Option<&SymbolInfo> {
+        self.symbols.get(qualified_name)
+    }
+
+    /// Symbols matching a bare name, or a qualified name or suffix of one (`Foo::new`)
+    pub fn find(&self, name: &str) -> Vec<&SymbolInfo> {
+        if let Some(symbol) = self.symbols.get(name) {
+            return vec![symbol];
+        }
+        let mut found: Vec<&SymbolInfo> = match self.by_name.get(name) {
+            Some(qualified) => qualified.iter().filter_map(|q| self.symbols.get(q)).collect(),
+            None if name.contains(['.', ':']) => self
+                .symbols
+                .values()
+                .filter(|s| {
+                    s.qualified_name
+                        .strip_suffix(name)
+                        .map(|prefix| prefix.ends_with(['.', ':']))
+                        .unwrap_or(false)
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        found.sort_by(|a, b| b.exported.cmp(&a.exported).then_with(|| a.qualified_name.cmp(&b.qualified_name)));
+        found
+    }
+
+    /// Symbols whose name matches `query`: exact, then prefix, then substring (case-insensitive)
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&SymbolInfo> {
+        let query = query.to_lowercase();
+        let qualified = query.contains(['.', ':']);
+        let mut scored: Vec<(u8, &SymbolInfo)> = self
+            .symbols
+            .values()
+            .filter_map(|symbol| {
+                let haystack = if qualified { &symbol.qualified_name } else { &symbol.name };
+                let haystack = haystack.to_lowercase();
+                let rank = if haystack == query {
+                    0
+                } else if haystack.starts_with(&query) {
+                    1
+                } else if haystack.contains(&query) {
+                    2
+                } else {
+                    return None;
+                };
+                Some((rank, symbol))
+            })
+            .collect();
+        scored.sort_by(|(ra, a), (rb, b)| {
+            ra.cmp(rb)
+                .then_with(|| b.exported.cmp(&a.exported))
+                .then_with(|| a.qualified_name.len().cmp(&b.qualified_name.len()))
+                .then_with(|| a.qualified_name.cmp(&b.qualified_name))
+        });
+        scored.into_iter().take(limit).map(|(_, symbol)| symbol).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+}
+
+/// Module path of a file and the separator used inside qualified names
+fn module_of(workspace: &Path, path: &Path) -> (String, &'static str) {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let relative = path.strip_prefix(workspace).unwrap_or(path);
+    let stem = relative.with_extension("").to_string_lossy().replace('\\', "/");
+    match extension {
+        "rs" => (imports::rust_module_path(path).unwrap_or(stem), "::"),
+        "py" => (imports::python_module_path(workspace, path).unwrap_or(stem), "."),
+        _ => (stem.strip_suffix("/index").map(str::to_string).unwrap_or(stem), "."),
+    }
+}
+
+struct Extractor<'a> {
+    content: &'a str,
+    file: String,
+    module: String,
+    separator: &'static str,
+    symbols: Vec<SymbolInfo>,
+}
+
+impl<'a> Extractor<'a> {
+    fn qualify(&self, containers: &[String], name: &str) -> String {
+        let mut path = containers.to_vec();
+        path.push(name.to_string());
+        // TS module paths contain `/`, so the symbol path is set off by `:`
+        let joiner = if self.separator == "." && self.module.contains('/') { ":" } else { self.separator };
+        format!("{}{}{}", self.module, joiner, path.join(self.separator))
+    }
+
+    fn push(&mut self, containers: &[String], name: &str, kind: SymbolKind, line: usize, exported: bool) {
+        let detail = self.content.lines().nth(line - 1).unwrap_or("").trim().to_string();
+        self.symbols.push(SymbolInfo {
+            name: name.to_string(),
+            qualified_name: self.qualify(containers, name),
+            container: containers.last().cloned(),
+            kind,
+            file: self.file.clone(),
+            line,
+            exported,
+            detail,
+        });
+    }
+
+    fn text(&self, node: Node) -> &'a str {
+        &self.content[node.byte_range()]
+    }
+
+    fn has_child(node: Node, kind: &str) -> bool {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).any(|c| c.kind() == kind);
+        found
+    }
+
+    /// Walk the tree; `exported` is whether the enclosing container is public
+    fn walk(&mut self, node: Node, containers: &mut Vec<String>, exported: bool) {
+        let line = node.start_position().row + 1;
+        let is_exported = node.parent().map(|p| p.kind() == "export_statement").unwrap_or(false)
+            || Self::has_child(node, "visibility_modifier");
+        let name = node.child_by_field_name("name").map(|n| self.text(n));
+        let in_type = !containers.is_empty();
+
+        let (kind, pushes) = match (node.kind(), name) {
+            ("function_declaration" | "generator_function_declaration", Some(_)) => (Some(SymbolKind::Function), false),
+            ("function_item" | "function_signature_item", Some(_)) => {
+                (Some(if in_type { SymbolKind::Method } else { SymbolKind::Function }), false)
+            }
+            ("method_definition" | "method_signature" | "abstract_method_signature", Some(_)) => (Some(SymbolKind::Method), false),
+            ("class_declaration" | "abstract_class_declaration", Some(_)) => (Some(SymbolKind::Class), true),
+            ("interface_declaration", Some(_)) => (Some(SymbolKind::Interface), true),
+            ("struct_item", Some(_)) => (Some(SymbolKind::Struct), false),
+            ("enum_item" | "enum_declaration", Some(_)) => (Some(SymbolKind::Enum), false),
+            ("trait_item", Some(_)) => (Some(SymbolKind::Trait), true),
+            ("type_alias_declaration" | "type_item", Some(_)) => (Some(SymbolKind::Type), false),
+            ("const_item" | "static_item", Some(_)) => (Some(SymbolKind::Constant), false),
+            ("mod_item" | "internal_module", Some(_)) => (Some(SymbolKind::Module), true),
+            ("impl_item", _) => {
+                // Methods of `impl Trait for Type` belong to the type
+                if let Some(target) = node.child_by_field_name("type") {
+                    let target = self.text(target);
+                    let target = target.split('<').next().unwrap_or(target);
+                    let target = target.rsplit("::").next().unwrap_or(target).trim().to_string();
+                    containers.push(target);
+                    // Impl members need their own `pub`
+                    self.walk_children(node, containers, false);
+                    containers.pop();
+                }
+                return;
+            }
+            ("variable_declarator", Some(name)) if containers.is_empty() => {
+                let declaration = node.parent();
+                // Only module-level bindings are symbols
+                let top_level = declaration
+                    .and_then(|d| d.parent())
+                    .map(|p| matches!(p.kind(), "program" | "export_statement"))
+                    .unwrap_or(false);
+                if top_level {
+                    let value = node.child_by_field_name("value").map(|v| v.kind());
+                    let kind = match value {
+                        Some("arrow_function" | "function" | "function_expression") => SymbolKind::Function,
+                        _ if declaration.map(|d| Self::has_child(d, "const")).unwrap_or(false) => SymbolKind::Constant,
+                        _ => SymbolKind::Variable,
+                    };
+                    let exported = declaration
+                        .and_then(|d| d.parent())
+                        .map(|p| p.kind() == "export_statement")
+                        .unwrap_or(false);
+                    self.push(containers, name, kind, line, exported);
+                }
+                return;
+            }
+            _ => (None, false),
+        };
+
+        match (kind, name) {
+            (Some(kind), Some(name)) => {
+                // Members are exported when their container is, unless marked private
+                let private = Self::has_child(node, "accessibility_modifier")
+                    && node.child(0).map(|c| self.text(c) != "public").unwrap_or(false);
+                let visible = if in_type { (exported && !private) || is_exported } else { is_exported };
+                self.push(containers, name, kind, line, visible);
+                if pushes {
+                    containers.push(name.to_string());
+                    self.walk_children(node, containers, visible);
+                    containers.pop();
+                }
+            }
+            _ => self.walk_children(node, containers, exported),
+        }
+    }
+
+    fn walk_children(&mut self, node: Node, containers: &mut Vec<String>, exported: bool) {
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        for child in children {
+            self.walk(child, containers, exported);
+        }
+    }
+
+    /// Indentation-based classes and functions; no Python grammar is bundled
+    fn python(&mut self) {
+        let mut stack: Vec<(usize, String)> = Vec::new();
+        for (i, line) in self.content.lines().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let indent = line.len() - trimmed.len();
+            while stack.last().map(|(level, _)| *level >= indent).unwrap_or(false) {
+                stack.pop();
+            }
+            let declaration = trimmed.strip_prefix("async ").unwrap_or(trimmed);
+            let (kind, rest) = if let Some(rest) = declaration.strip_prefix("def ") {
+                (if stack.is_empty() { SymbolKind::Function } else { SymbolKind::Method }, rest)
+            } else if let Some(rest) = declaration.strip_prefix("class ") {
+                (SymbolKind::Class, rest)
+            } else {
+                continue;
+            };
+            let name: String = rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+            if name.is_empty() {
+                continue;
+            }
+            let containers: Vec<String> = stack.iter().map(|(_, n)| n.clone()).collect();
+            self.push(&containers, &name, kind, i + 1, !name.starts_with('_'));
+            stack.push((indent, name));
+        }
+    }
+
+    /// GraphQL types, root fields and named operations
+    fn graphql(&mut self) {
+        for declaration in graphql::declarations(&self.file, self.content) {
+            let containers: Vec<String> = declaration.container.into_iter().collect();
+            self.push(&containers, &declaration.name, declaration.kind, declaration.line, true);
+        }
+    }
+
+    /// Protobuf messages, enums, services and rpcs; OpenAPI schemas and operations
+    fn idl(&mut self) {
+        for definition in idl::definitions(&self.file, self.content) {
+            let kind = match definition.kind {
+                IdlKind::Message => SymbolKind::Struct,
+                IdlKind::Enum => SymbolKind::Enum,
+                IdlKind::Service => SymbolKind::Interface,
+                IdlKind::Rpc => SymbolKind::Method,
+                IdlKind::Schema => SymbolKind::Type,
+                IdlKind::Operation => SymbolKind::Function,
+            };
+            // An operation's container is its route, not a declaration
+            let containers: Vec<String> =
+                definition.container.filter(|_| kind != SymbolKind::Function).into_iter().collect();
+            self.push(&containers, &definition.name, kind, definition.line, true);
+        }
+    }
+}
+
+/// Declarations of a source file with qualified names
+pub fn extract(workspace: &Path, path: &Path, content: &str) -> Vec<SymbolInfo> {
+    let (module, separator) = module_of(workspace, path);
+    let file = path.to_string_lossy().to_string();
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let mut extractor = Extractor {
+        content,
+        file: file.clone(),
+        module,
+        separator,
+        symbols: Vec::new(),
+    };
+    if file.ends_with(".py") {
+        extractor.python();
+    } else if idl::is_idl_file(&file) {
+        extractor.idl();
+    } else if let Some(tree) = syntax::parse(&file, content) {
+        extractor.walk(tree.root_node(), &mut Vec::new(), false);
+    }
+    // Schema and operation files, plus operations in gql templates of JS/TS files
+    if graphql::is_source(extension) {
+        extractor.graphql();
+    }
+    extractor.symbols
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qualified_names_keep_same_named_symbols_apart() {
+        let workspace = Path::new("/ws");
+        let rust = "pub struct Graph;\nimpl Graph {\n    pub fn new() -> Self { Graph }\n}\nstruct Index;\nimpl Index {\n    fn new() -> Self { Index }\n}\n";
+        let ts = "export class Client {\n  get() {}\n  private retry() {}\n}\nexport function main() {}\n";
+
+        let mut table = SymbolTable::new();
+        for symbol in extract(workspace, Path::new("/ws/lib.rs"), rust) {
+            table.insert(symbol);
+        }
+        for symbol in extract(workspace, Path::new("/ws/src/api/index.ts"), ts) {
+            table.insert(symbol);
+        }
+
+        let news: Vec<&str> = table.find("new").iter().map(|s| s.qualified_name.as_str()).collect();
+        assert_eq!(news, vec!["lib::Graph::new", "lib::Index::new"]);
+        assert!(table.find("Graph::new")[0].exported);
+
+        let get = table.get("src/api:Client.get").unwrap();
+        assert_eq!((get.kind, get.line, get.exported), (SymbolKind::Method, 2, true));
+        assert!(!table.get("src/api:Client.retry").unwrap().exported);
+    }
+}
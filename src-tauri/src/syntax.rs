@@ -0,0 +1,112 @@
+// Syntax - Tree-sitter parsing for supported languages
+// Used to validate generated code before it reaches the editor
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Language, Node, Parser, Tree};
+
+/// Maximum number of syntax errors reported per check
+const MAX_ERRORS: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SyntaxError {
+    /// 1-based line
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SyntaxCheck {
+    /// Whether a grammar exists for the file type
+    pub supported: bool,
+    pub valid: bool,
+    pub errors: Vec<SyntaxError>,
+}
+
+/// Tree-sitter grammar for a file path
+pub fn language_for(path: &str) -> Option<Language> {
+    let extension = path.rsplit('.').next().unwrap_or("");
+    match extension {
+        "rs" => Some(tree_sitter_rust::language()),
+        "ts" | "mts" | "cts" => Some(tree_sitter_typescript::language_typescript()),
+        // TSX is a superset of JavaScript/JSX for parsing purposes
+        "tsx" | "js" | "jsx" | "mjs" | "cjs" => Some(tree_sitter_typescript::language_tsx()),
+        _ => None,
+    }
+}
+
+/// Parse a document, `None` if the language is unsupported
+pub fn parse(path: &str, text: &str) -> Option<Tree> {
+    let language = language_for(path)?;
+    let mut parser = Parser::new();
+    parser.set_language(language).ok()?;
+    parser.parse(text, None)
+}
+
+fn collect_errors(node: Node, errors: &mut Vec<SyntaxError>) {
+    if errors.len() >= MAX_ERRORS {
+        return;
+    }
+    if node.is_error() || node.is_missing() {
+        let position = node.start_position();
+        errors.push(SyntaxError {
+            line: position.row + 1,
+            column: position.column,
+            message: if node.is_missing() {
+                format!("Missing {}", node.kind())
+            } else {
+                "Unexpected syntax".to_string()
+            },
+        });
+        return;
+    }
+    if !node.has_error() {
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_errors(child, errors);
+    }
+}
+
+/// Check a document for syntax errors
+pub fn check(path: &str, text: &str) -> SyntaxCheck {
+    let tree = match parse(path, text) {
+        Some(tree) => tree,
+        None => {
+            return SyntaxCheck {
+                supported: language_for(path).is_some(),
+                valid: true,
+                errors: Vec::new(),
+            }
+        }
+    };
+
+    let mut errors = Vec::new();
+    collect_errors(tree.root_node(), &mut errors);
+    SyntaxCheck {
+        supported: true,
+        valid: errors.is_empty(),
+        errors,
+    }
+}
+
+/// Named children of a node, without comments
+pub(crate) fn named_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    let children = node.named_children(&mut cursor).filter(|c| !c.kind().contains("comment")).collect();
+    children
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_detects_errors() {
+        assert!(check("a.rs", "fn main() { let x = 1; }").valid);
+        assert!(!check("a.rs", "fn main() { let x = ; ").valid);
+        assert!(check("a.ts", "const x: number = 1;").valid);
+        assert!(!check("a.txt", "anything").supported);
+    }
+}
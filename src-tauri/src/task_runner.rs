@@ -0,0 +1,154 @@
+// Task Runner - Process execution for workspace tasks
+// Captures output with timeouts so callers never hang
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{containers, workspace_env};
+
+/// Maximum captured bytes per output stream
+const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TaskSpec {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory relative to the workspace (workspace root if absent)
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Container id or name to run in through `docker exec` instead of on the host
+    #[serde(default)]
+    pub container: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TaskOutput {
+    /// Exit code, `None` if the process was killed or terminated by a signal
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+    pub timed_out: bool,
+}
+
+impl TaskOutput {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+fn read_capped(mut reader: impl Read) -> String {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+    while let Ok(n) = reader.read(&mut chunk) {
+        if n == 0 {
+            break;
+        }
+        // Keep draining the pipe after the cap so the child never blocks
+        if buffer.len() < MAX_OUTPUT_BYTES {
+            buffer.extend_from_slice(&chunk[..n]);
+        }
+    }
+    String::from_utf8_lossy(&buffer).to_string()
+}
+
+/// Build the command for a task spec, with the workspace environment applied
+pub fn command(spec: &TaskSpec, workspace: &Path) -> Command {
+    let cwd = match &spec.cwd {
+        Some(cwd) => workspace.join(cwd),
+        None => workspace.to_path_buf(),
+    };
+    let mut env = workspace_env::resolve(workspace).unwrap_or_else(|e| {
+        log::warn!("Ignoring workspace environment of {:?}: {}", workspace, e);
+        HashMap::new()
+    });
+    env.extend(spec.env.clone());
+
+    let mut cmd = match &spec.container {
+        Some(container) => {
+            let mut names: Vec<String> = env.keys().cloned().collect();
+            names.sort();
+            let mut cmd = containers::exec(container, &cwd, &spec.command, &spec.args, &names);
+            cmd.current_dir(workspace);
+            cmd
+        }
+        None => {
+            let mut cmd = Command::new(&spec.command);
+            cmd.args(&spec.args);
+            cmd.current_dir(&cwd);
+            cmd
+        }
+    };
+    cmd.envs(env);
+    cmd
+}
+
+/// Run a task to completion, killing it after `timeout`; callers check that the workspace is trusted
+pub fn run(spec: &TaskSpec, workspace: &Path, timeout: Duration) -> Result<TaskOutput> {
+    log::info!("Running task: {} {}", spec.command, spec.args.join(" "));
+    let started = Instant::now();
+
+    let mut child = command(spec, workspace)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdout = child.stdout.take().map(|out| thread::spawn(move || read_capped(out)));
+    let stderr = child.stderr.take().map(|err| thread::spawn(move || read_capped(err)));
+
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if started.elapsed() >= timeout {
+            log::warn!("Task timed out after {:?}: {}", timeout, spec.command);
+            let _ = child.kill();
+            let _ = child.wait();
+            timed_out = true;
+            break None;
+        }
+        thread::sleep(Duration::from_millis(25));
+    };
+
+    let join = |handle: Option<thread::JoinHandle<String>>| {
+        handle.and_then(|h| h.join().ok()).unwrap_or_default()
+    };
+
+    Ok(TaskOutput {
+        exit_code: status.and_then(|s| s.code()),
+        stdout: join(stdout),
+        stderr: join(stderr),
+        duration_ms: started.elapsed().as_millis() as u64,
+        timed_out,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_captures_output() {
+        let spec = TaskSpec {
+            command: "git".to_string(),
+            args: vec!["--version".to_string()],
+            cwd: None,
+            env: HashMap::new(),
+            container: None,
+        };
+        let output = run(&spec, &std::env::temp_dir(), Duration::from_secs(30)).unwrap();
+        assert!(output.success());
+        assert!(output.stdout.contains("git"));
+    }
+}
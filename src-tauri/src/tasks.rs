@@ -0,0 +1,241 @@
+// Tasks - TODO, FIXME and HACK comments collected into a task list
+// Tasks keep their ID while their line moves; done and ignored states persist per workspace
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::sloc;
+use crate::storage;
+
+/// Comment markers collected as tasks
+const MARKERS: [&str; 4] = ["TODO", "FIXME", "HACK", "XXX"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    #[default]
+    Open,
+    Done,
+    Ignored,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Task {
+    /// Derived from file, marker and text, so editing around the comment keeps it
+    pub id: String,
+    /// Relative to the workspace
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    /// The marker, e.g. "TODO" or "FIXME"
+    pub kind: String,
+    pub text: String,
+    /// From `TODO(p1)`; 1 is the most urgent
+    pub priority: Option<u8>,
+    pub state: TaskState,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct TaskFilter {
+    pub state: Option<TaskState>,
+    /// Only tasks at least this urgent, e.g. 2 keeps p1 and p2
+    pub max_priority: Option<u8>,
+    pub kind: Option<String>,
+    /// Workspace-relative file or folder prefix
+    pub path: Option<String>,
+    /// Case-insensitive text search
+    pub query: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct StateRecord {
+    state: TaskState,
+    updated_at: u64,
+}
+
+fn states_path(workspace: &Path) -> PathBuf {
+    storage::data_dir(workspace).join("tasks.json")
+}
+
+fn load_states(workspace: &Path) -> Result<HashMap<String, StateRecord>> {
+    Ok(storage::read_json(&states_path(workspace))?.unwrap_or_default())
+}
+
+/// Parse `TODO(p1, alice): text` after the marker: the priority and the text
+fn parse_rest(rest: &str) -> (Option<u8>, &str) {
+    let mut priority = None;
+    let mut text = rest;
+    if let Some(inner) = rest.strip_prefix('(') {
+        if let Some(close) = inner.find(')') {
+            priority = inner[..close]
+                .split(',')
+                .map(str::trim)
+                .find_map(|tag| tag.strip_prefix(['p', 'P']).and_then(|n| n.parse().ok()));
+            text = &inner[close + 1..];
+        }
+    }
+    let text = text.trim_start_matches([':', '-', ' ', '\t']);
+    // Drop the closers of one-line block comments
+    let text = text.trim_end().trim_end_matches("*/").trim_end_matches("-->").trim_end();
+    (priority, text)
+}
+
+/// Byte column of the comment text on `line`, if it has one: after a line comment
+/// or block start marker, or on a `*` continuation line of a block comment
+fn comment_start(line: &str, syntax: &sloc::CommentSyntax) -> Option<usize> {
+    let markers = syntax.line.iter().copied().chain(syntax.block.map(|(start, _)| start));
+    let found = markers.filter_map(|marker| line.find(marker)).min();
+    if found.is_some() {
+        return found;
+    }
+    let trimmed = line.trim_start();
+    if syntax.block.is_some() && trimmed.starts_with('*') {
+        return Some(line.len() - trimmed.len());
+    }
+    None
+}
+
+/// Tasks in the comments of one file, in line order, all open
+pub fn extract(file: &str, content: &str, language: &str) -> Vec<Task> {
+    let syntax = match sloc::syntax_for(language) {
+        Some(syntax) => syntax,
+        None => return Vec::new(),
+    };
+    let mut tasks = Vec::new();
+    // Identical comments in one file are told apart by their order
+    let mut seen: HashMap<(String, String), usize> = HashMap::new();
+    for (i, line) in content.lines().enumerate() {
+        let start = match comment_start(line, &syntax) {
+            Some(start) => start,
+            None => continue,
+        };
+        let comment = &line[start..];
+        let found = MARKERS.iter().find_map(|marker| {
+            comment.match_indices(marker).find_map(|(at, _)| {
+                let before = comment[..at].chars().next_back();
+                let after = comment[at + marker.len()..].chars().next();
+                let bounded = !before.is_some_and(|c| c.is_alphanumeric() || c == '_')
+                    && matches!(after, None | Some(':') | Some('(') | Some(' ') | Some('-'));
+                bounded.then_some((*marker, at))
+            })
+        });
+        let (marker, at) = match found {
+            Some(found) => found,
+            None => continue,
+        };
+        let (priority, text) = parse_rest(&comment[at + marker.len()..]);
+        let occurrence = seen.entry((marker.to_string(), text.to_string())).or_insert(0);
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{}|{}|{}|{}", file, marker, text, occurrence).as_bytes());
+        *occurrence += 1;
+        tasks.push(Task {
+            id: format!("task-{}", &hex::encode(hasher.finalize())[..12]),
+            file: file.to_string(),
+            line: i + 1,
+            column: start + at,
+            kind: marker.to_string(),
+            text: text.to_string(),
+            priority,
+            state: TaskState::Open,
+        });
+    }
+    tasks
+}
+
+impl TaskFilter {
+    fn matches(&self, task: &Task) -> bool {
+        let query = self.query.as_ref().map(|q| q.to_lowercase());
+        self.state.is_none_or(|state| task.state == state)
+            && self.max_priority.is_none_or(|max| task.priority.is_some_and(|p| p <= max))
+            && self.kind.as_ref().is_none_or(|kind| task.kind.eq_ignore_ascii_case(kind))
+            && self.path.as_ref().is_none_or(|path| task.file.starts_with(path.trim_start_matches("./")))
+            && query.is_none_or(|query| task.text.to_lowercase().contains(&query))
+    }
+}
+
+/// Apply persisted states and the filter; most urgent first, then by location
+pub fn list(workspace: &Path, tasks: Vec<Task>, filter: &TaskFilter) -> Result<Vec<Task>> {
+    let states = load_states(workspace)?;
+    let mut tasks: Vec<Task> = tasks
+        .into_iter()
+        .map(|mut task| {
+            if let Some(record) = states.get(&task.id) {
+                task.state = record.state;
+            }
+            task
+        })
+        .filter(|task| filter.matches(task))
+        .collect();
+    tasks.sort_by(|a, b| {
+        (a.priority.unwrap_or(u8::MAX), &a.file, a.line).cmp(&(b.priority.unwrap_or(u8::MAX), &b.file, b.line))
+    });
+    Ok(tasks)
+}
+
+/// Mark a task done or ignored; setting it open again forgets the record
+pub fn set_state(workspace: &Path, id: &str, state: TaskState) -> Result<()> {
+    if !id.starts_with("task-") {
+        return Err(anyhow!("Unknown task: {}", id));
+    }
+    let mut states = load_states(workspace)?;
+    match state {
+        TaskState::Open => {
+            states.remove(id);
+        }
+        _ => {
+            states.insert(
+                id.to_string(),
+                StateRecord {
+                    state,
+                    updated_at: storage::now_millis(),
+                },
+            );
+        }
+    }
+    storage::write_json(&states_path(workspace), &states)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_priorities_and_stable_ids() {
+        let source = "// TODO(p1): handle errors\nfn main() {\n    let todo = 1; /* FIXME leaks */\n    // TODO: handle errors\n}\n";
+        let tasks = extract("src/main.rs", source, "Rust");
+        let summary: Vec<(usize, &str, &str, Option<u8>)> =
+            tasks.iter().map(|t| (t.line, t.kind.as_str(), t.text.as_str(), t.priority)).collect();
+        assert_eq!(
+            summary,
+            vec![(1, "TODO", "handle errors", Some(1)), (3, "FIXME", "leaks", None), (4, "TODO", "handle errors", None)]
+        );
+        assert_ne!(tasks[0].id, tasks[2].id);
+
+        let moved = extract("src/main.rs", &format!("\n\n{}", source), "Rust");
+        assert_eq!(moved[1].id, tasks[1].id);
+        assert_eq!(moved[1].line, 5);
+    }
+
+    #[test]
+    fn test_states_persist_and_filter() {
+        let workspace = std::env::temp_dir().join(storage::new_id("tasks-test"));
+        std::fs::create_dir_all(&workspace).unwrap();
+        let tasks = extract("a.py", "# TODO(p2) one\n# HACK two\n", "Python");
+        set_state(&workspace, &tasks[1].id, TaskState::Done).unwrap();
+
+        let open = TaskFilter { state: Some(TaskState::Open), ..Default::default() };
+        let listed = list(&workspace, tasks.clone(), &open).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].text, "one");
+        let urgent = TaskFilter { max_priority: Some(1), ..Default::default() };
+        assert!(list(&workspace, tasks.clone(), &urgent).unwrap().is_empty());
+
+        set_state(&workspace, &tasks[1].id, TaskState::Open).unwrap();
+        assert_eq!(list(&workspace, tasks, &open).unwrap().len(), 2);
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+}
@@ -0,0 +1,229 @@
+// Test Generation - AI-written unit tests for a symbol
+// Detects the workspace framework and targets its conventional test location
+
+use std::fs;
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ai_provider::{self, AiProvider, ChatMessage, CompletionRequest};
+use crate::changeset::{self, Changeset, FileChange, FilePreview, TextEdit};
+use crate::code_context::{SymbolReference, SymbolSpan};
+use crate::syntax::{self, SyntaxCheck};
+use crate::tokens::ContextBudget;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TestFramework {
+    Jest,
+    Vitest,
+    Pytest,
+    Cargo,
+}
+
+impl TestFramework {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TestFramework::Jest => "jest",
+            TestFramework::Vitest => "vitest",
+            TestFramework::Pytest => "pytest",
+            TestFramework::Cargo => "cargo test",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GeneratedTests {
+    pub framework: TestFramework,
+    pub test_path: String,
+    pub changeset: Changeset,
+    pub preview: Vec<FilePreview>,
+    /// Syntax check of the resulting test file
+    pub syntax: SyntaxCheck,
+}
+
+/// Detect the test framework used for `file` in the workspace
+pub fn detect_framework(workspace: &Path, file: &str) -> Option<TestFramework> {
+    let extension = file.rsplit('.').next().unwrap_or("");
+    match extension {
+        "rs" => Some(TestFramework::Cargo),
+        "py" => Some(TestFramework::Pytest),
+        "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs" => {
+            let manifest = fs::read_to_string(workspace.join("package.json")).unwrap_or_default();
+            let package: serde_json::Value = serde_json::from_str(&manifest).unwrap_or_default();
+            let declares = |dependency: &str| {
+                ["dependencies", "devDependencies"]
+                    .iter()
+                    .any(|section| package[section].get(dependency).is_some())
+            };
+            if declares("vitest") || workspace.join("vitest.config.ts").exists() {
+                Some(TestFramework::Vitest)
+            } else if declares("jest") || workspace.join("jest.config.js").exists() {
+                Some(TestFramework::Jest)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Conventional test file for `file`
+pub fn test_path(workspace: &Path, file: &str, framework: TestFramework) -> String {
+    let path = Path::new(file);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("module");
+    let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+    let parent = path.parent().unwrap_or(workspace);
+
+    match framework {
+        // Rust unit tests live next to the code in a `tests` module
+        TestFramework::Cargo => file.to_string(),
+        TestFramework::Pytest => {
+            let tests_dir = workspace.join("tests");
+            let dir = if tests_dir.is_dir() { tests_dir } else { parent.to_path_buf() };
+            dir.join(format!("test_{}.py", stem)).to_string_lossy().to_string()
+        }
+        TestFramework::Jest | TestFramework::Vitest => {
+            let tests_dir = parent.join("__tests__");
+            let dir = if tests_dir.is_dir() { tests_dir } else { parent.to_path_buf() };
+            dir.join(format!("{}.test.{}", stem, extension)).to_string_lossy().to_string()
+        }
+    }
+}
+
+fn build_prompt(
+    framework: TestFramework,
+    file: &str,
+    test_file: &str,
+    definition: &str,
+    callers: &[SymbolReference],
+    existing: &str,
+    model: &str,
+) -> Vec<ChatMessage> {
+    let system = format!(
+        "You write thorough, idiomatic unit tests using {}. \
+         Cover normal behaviour, edge cases and error paths. \
+         Reply with the test code only, in a single code block.",
+        framework.name()
+    );
+    let mut budget = ContextBudget::for_model(model);
+    budget.try_add(&system);
+
+    let mut user = format!("Source file: {}\nTest file: {}\n", file, test_file);
+    match framework {
+        TestFramework::Cargo => user.push_str(
+            "Return a `#[cfg(test)] mod tests { use super::*; ... }` block to append to the source file.\n",
+        ),
+        _ => user.push_str("Import the symbol with a path relative to the test file.\n"),
+    }
+    user.push_str(&format!("\nSymbol under test:\n```\n{}\n```\n", budget.add_truncated(definition)));
+
+    if !callers.is_empty() {
+        user.push_str("\nExample call sites:\n");
+        for caller in callers {
+            let snippet = format!("{}:{}\n```\n{}\n```\n", caller.file, caller.line, caller.snippet);
+            if !budget.try_add(&snippet) {
+                break;
+            }
+            user.push_str(&snippet);
+        }
+    }
+    if !existing.is_empty() {
+        let existing = format!("\nExisting tests (match their style, do not repeat them):\n```\n{}\n```\n", existing);
+        if budget.try_add(&existing) {
+            user.push_str(&existing);
+        }
+    }
+
+    vec![ChatMessage::system(system), ChatMessage::user(user)]
+}
+
+/// Ask the provider for tests of `definition` and build the changeset writing them
+pub async fn generate(
+    provider: &dyn AiProvider,
+    workspace: &Path,
+    file: &str,
+    definition: &SymbolSpan,
+    content: &str,
+    callers: &[SymbolReference],
+) -> Result<GeneratedTests> {
+    let framework = detect_framework(workspace, file)
+        .ok_or_else(|| anyhow!("No supported test framework detected for {}", file))?;
+    let test_file = test_path(workspace, file, framework);
+    let existing = fs::read_to_string(&test_file).ok();
+
+    // For in-file Rust tests the source itself is the existing file
+    let existing_tests = match framework {
+        TestFramework::Cargo => String::new(),
+        _ => existing.clone().unwrap_or_default(),
+    };
+    let messages = build_prompt(
+        framework,
+        file,
+        &test_file,
+        definition.text(content),
+        callers,
+        &existing_tests,
+        provider.model(),
+    );
+    let response = provider.complete(&CompletionRequest::new(messages)).await?;
+    let code = ai_provider::extract_code_block(&response);
+    if code.is_empty() {
+        return Err(anyhow!("The model returned no tests"));
+    }
+
+    let (change, result) = match existing {
+        Some(current) => {
+            let separator = if current.ends_with('\n') { "\n" } else { "\n\n" };
+            let addition = format!("{}{}\n", separator, code);
+            let result = format!("{}{}", current, addition);
+            let edit = TextEdit::insert(changeset::end_of(&current), addition);
+            (
+                FileChange::Edit {
+                    path: test_file.clone(),
+                    edits: vec![edit],
+                },
+                result,
+            )
+        }
+        None => {
+            let result = format!("{}\n", code);
+            (
+                FileChange::Create {
+                    path: test_file.clone(),
+                    content: result.clone(),
+                },
+                result,
+            )
+        }
+    };
+
+    let changeset = Changeset::new(format!("Generate tests for {}", definition.name), vec![change]);
+    let preview = changeset.preview(workspace)?;
+    Ok(GeneratedTests {
+        framework,
+        syntax: syntax::check(&test_file, &result),
+        test_path: test_file,
+        changeset,
+        preview,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conventional_paths() {
+        let workspace = Path::new("/nonexistent/ws");
+        assert_eq!(
+            test_path(workspace, "/nonexistent/ws/src/math.ts", TestFramework::Vitest),
+            "/nonexistent/ws/src/math.test.ts"
+        );
+        assert_eq!(
+            test_path(workspace, "/nonexistent/ws/pkg/util.py", TestFramework::Pytest),
+            "/nonexistent/ws/pkg/test_util.py"
+        );
+        assert_eq!(detect_framework(workspace, "lib.rs"), Some(TestFramework::Cargo));
+    }
+}
@@ -0,0 +1,286 @@
+// Text Diff - Line-based diffing for previews and change tracking
+// LCS diff with common prefix/suffix trimming
+
+/// A single line-level diff operation
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiffOp {
+    Equal(String),
+    Insert(String),
+    Delete(String),
+}
+
+/// Above this many line pairs the diff falls back to delete-all/insert-all
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// Compute a line diff transforming `old` into `new`
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffOp> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // Trim common prefix and suffix to keep the LCS table small
+    let prefix = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+
+    let mut ops: Vec<DiffOp> = a[..prefix].iter().map(|l| DiffOp::Equal(l.to_string())).collect();
+
+    if a_mid.len() * b_mid.len() > MAX_LCS_CELLS {
+        ops.extend(a_mid.iter().map(|l| DiffOp::Delete(l.to_string())));
+        ops.extend(b_mid.iter().map(|l| DiffOp::Insert(l.to_string())));
+    } else {
+        ops.extend(lcs_diff(a_mid, b_mid));
+    }
+
+    ops.extend(a[a.len() - suffix..].iter().map(|l| DiffOp::Equal(l.to_string())));
+    ops
+}
+
+fn lcs_diff(a: &[&str], b: &[&str]) -> Vec<DiffOp> {
+    let (n, m) = (a.len(), b.len());
+    // table[i][j] = LCS length of a[i..] and b[j..]
+    let mut table = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i][j] = if a[i] == b[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a[i] == b[j] {
+            ops.push(DiffOp::Equal(a[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            ops.push(DiffOp::Delete(a[i].to_string()));
+            i += 1;
+        } else {
+            ops.push(DiffOp::Insert(b[j].to_string()));
+            j += 1;
+        }
+    }
+    ops.extend(a[i..].iter().map(|l| DiffOp::Delete(l.to_string())));
+    ops.extend(b[j..].iter().map(|l| DiffOp::Insert(l.to_string())));
+    ops
+}
+
+/// Render a unified diff with `context` lines around each change
+pub fn unified_diff(path: &str, old: &str, new: &str, context: usize) -> String {
+    let ops = diff_lines(old, new);
+    if ops.iter().all(|op| matches!(op, DiffOp::Equal(_))) {
+        return String::new();
+    }
+
+    let mut out = format!("--- a/{}\n+++ b/{}\n", path, path);
+
+    // Indices of changed ops, grouped into hunks
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffOp::Equal(_)))
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    for &idx in &changed {
+        let start = idx.saturating_sub(context);
+        let end = (idx + context + 1).min(ops.len());
+        match groups.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => groups.push((start, end)),
+        }
+    }
+
+    for (start, end) in groups {
+        // Line numbers at the start of the hunk
+        let (mut old_line, mut new_line) = (1, 1);
+        for op in &ops[..start] {
+            match op {
+                DiffOp::Equal(_) => {
+                    old_line += 1;
+                    new_line += 1;
+                }
+                DiffOp::Delete(_) => old_line += 1,
+                DiffOp::Insert(_) => new_line += 1,
+            }
+        }
+
+        let hunk = &ops[start..end];
+        let old_count = hunk.iter().filter(|op| !matches!(op, DiffOp::Insert(_))).count();
+        let new_count = hunk.iter().filter(|op| !matches!(op, DiffOp::Delete(_))).count();
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n", old_line, old_count, new_line, new_count));
+
+        for op in hunk {
+            match op {
+                DiffOp::Equal(l) => out.push_str(&format!(" {}\n", l)),
+                DiffOp::Delete(l) => out.push_str(&format!("-{}\n", l)),
+                DiffOp::Insert(l) => out.push_str(&format!("+{}\n", l)),
+            }
+        }
+    }
+
+    out
+}
+
+/// Replacement of base lines `start..end` by `lines`
+struct Hunk {
+    start: usize,
+    end: usize,
+    lines: Vec<String>,
+}
+
+fn hunks(base: &str, other: &str) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut open = false;
+    let mut line = 0;
+    for op in diff_lines(base, other) {
+        match op {
+            DiffOp::Equal(_) => {
+                open = false;
+                line += 1;
+            }
+            DiffOp::Delete(_) | DiffOp::Insert(_) => {
+                if !open {
+                    hunks.push(Hunk {
+                        start: line,
+                        end: line,
+                        lines: Vec::new(),
+                    });
+                    open = true;
+                }
+                let hunk = hunks.last_mut().unwrap();
+                match op {
+                    DiffOp::Insert(text) => hunk.lines.push(text),
+                    _ => {
+                        line += 1;
+                        hunk.end = line;
+                    }
+                }
+            }
+        }
+    }
+    hunks
+}
+
+/// Base lines `start..end` with the hunks of one side applied
+fn render(base: &[&str], start: usize, end: usize, hunks: &[Hunk]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut at = start;
+    for hunk in hunks {
+        lines.extend(base[at..hunk.start].iter().map(|l| l.to_string()));
+        lines.extend(hunk.lines.iter().cloned());
+        at = hunk.end;
+    }
+    lines.extend(base[at..end].iter().map(|l| l.to_string()));
+    lines
+}
+
+/// Result of a three-way merge
+#[derive(Clone, Debug, PartialEq)]
+pub struct Merge {
+    /// Merged text; conflicting regions hold both versions between markers
+    pub text: String,
+    pub conflicts: usize,
+}
+
+/// Merge the changes `ours` and `theirs` each made to `base`.
+/// Overlapping changes that differ become conflict regions marked like git's.
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> Merge {
+    let base_lines: Vec<&str> = base.lines().collect();
+    let (ours_hunks, theirs_hunks) = (hunks(base, ours), hunks(base, theirs));
+    let (mut i, mut j) = (0, 0);
+    let mut at = 0;
+    let mut lines: Vec<String> = Vec::new();
+    let mut conflicts = 0;
+
+    while i < ours_hunks.len() || j < theirs_hunks.len() {
+        // Start a region with the earliest hunk, then absorb every hunk overlapping it
+        let take_ours = j >= theirs_hunks.len() || (i < ours_hunks.len() && ours_hunks[i].start <= theirs_hunks[j].start);
+        let first = if take_ours { &ours_hunks[i] } else { &theirs_hunks[j] };
+        let (start, mut end) = (first.start, first.end);
+        let (ours_from, theirs_from) = (i, j);
+        if take_ours {
+            i += 1;
+        } else {
+            j += 1;
+        }
+        loop {
+            if i < ours_hunks.len() && (ours_hunks[i].start < end || ours_hunks[i].start == start) {
+                end = end.max(ours_hunks[i].end);
+                i += 1;
+            } else if j < theirs_hunks.len() && (theirs_hunks[j].start < end || theirs_hunks[j].start == start) {
+                end = end.max(theirs_hunks[j].end);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        lines.extend(base_lines[at..start].iter().map(|l| l.to_string()));
+        let ours_region = render(&base_lines, start, end, &ours_hunks[ours_from..i]);
+        let theirs_region = render(&base_lines, start, end, &theirs_hunks[theirs_from..j]);
+        if theirs_from == j || ours_region == theirs_region {
+            lines.extend(ours_region);
+        } else if ours_from == i {
+            lines.extend(theirs_region);
+        } else {
+            conflicts += 1;
+            lines.push("<<<<<<< ours".to_string());
+            lines.extend(ours_region);
+            lines.push("=======".to_string());
+            lines.extend(theirs_region);
+            lines.push(">>>>>>> theirs".to_string());
+        }
+        at = end;
+    }
+    lines.extend(base_lines[at..].iter().map(|l| l.to_string()));
+
+    let mut text = lines.join("\n");
+    if !text.is_empty() && (ours.ends_with('\n') || theirs.ends_with('\n')) {
+        text.push('\n');
+    }
+    Merge { text, conflicts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines_minimal() {
+        let ops = diff_lines("a\nb\nc", "a\nx\nc");
+        assert_eq!(
+            ops,
+            vec![
+                DiffOp::Equal("a".to_string()),
+                DiffOp::Delete("b".to_string()),
+                DiffOp::Insert("x".to_string()),
+                DiffOp::Equal("c".to_string()),
+            ]
+        );
+        assert!(unified_diff("f.txt", "a\nb\nc", "a\nx\nc", 3).contains("@@ -1,3 +1,3 @@"));
+    }
+
+    #[test]
+    fn test_merge3() {
+        let base = "a\nb\nc\nd\n";
+        let clean = merge3(base, "a\nB\nc\nd\n", "a\nb\nc\nD\n");
+        assert_eq!(clean, Merge { text: "a\nB\nc\nD\n".to_string(), conflicts: 0 });
+
+        let conflicting = merge3(base, "a\nours\nc\nd\n", "a\ntheirs\nc\nd\n");
+        assert_eq!(conflicting.conflicts, 1);
+        assert!(conflicting.text.contains("<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs"));
+    }
+}
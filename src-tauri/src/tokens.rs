@@ -0,0 +1,189 @@
+// Token Counting - tiktoken-compatible BPE token budgets
+// Keeps prompts inside provider context windows
+
+use std::sync::OnceLock;
+use serde::{Deserialize, Serialize};
+use tiktoken_rs::CoreBPE;
+
+/// Tokens kept free for the model's answer when budgeting a prompt
+pub const RESPONSE_RESERVE: usize = 2048;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    Cl100k,
+    O200k,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TokenCount {
+    pub tokens: usize,
+    pub encoding: Encoding,
+    /// True when the model's own tokenizer is unknown and cl100k is used as estimate
+    pub approximate: bool,
+    pub context_window: usize,
+}
+
+static CL100K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+static O200K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+
+fn bpe(encoding: Encoding) -> Option<&'static CoreBPE> {
+    let cell = match encoding {
+        Encoding::Cl100k => CL100K.get_or_init(|| tiktoken_rs::cl100k_base().ok()),
+        Encoding::O200k => O200K.get_or_init(|| tiktoken_rs::o200k_base().ok()),
+    };
+    cell.as_ref()
+}
+
+/// Pick the encoding for a model and whether it only approximates the model's own tokenizer
+pub fn encoding_for(model: &str) -> (Encoding, bool) {
+    let model = model.to_lowercase();
+    if model.starts_with("gpt-4o") || model.starts_with("o1") || model.starts_with("o3") || model.starts_with("gpt-4.1") {
+        (Encoding::O200k, false)
+    } else if model.starts_with("gpt-4") || model.starts_with("gpt-3.5") || model.starts_with("text-embedding") {
+        (Encoding::Cl100k, false)
+    } else {
+        // Local models (qwen, llama, mistral...) ship their own vocabularies
+        (Encoding::Cl100k, true)
+    }
+}
+
+/// Context window sizes by model name prefix (first match wins)
+const CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("gpt-4o", 128_000),
+    ("gpt-4.1", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-3.5", 16_385),
+    ("o1", 128_000),
+    ("o3", 128_000),
+    ("qwen3", 32_768),
+    ("qwen2.5", 32_768),
+    ("llama3", 8_192),
+];
+
+/// Conservative window for unknown models
+const DEFAULT_CONTEXT_WINDOW: usize = 8_192;
+
+/// Context window size of well-known models
+pub fn context_window(model: &str) -> usize {
+    let model = model.to_lowercase();
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, window)| *window)
+        .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+}
+
+/// Count tokens of `text` for `model`
+pub fn count(text: &str, model: &str) -> TokenCount {
+    let (encoding, approximate) = encoding_for(model);
+    let tokens = match bpe(encoding) {
+        Some(bpe) => bpe.encode_with_special_tokens(text).len(),
+        // Rough fallback if the vocabulary failed to load
+        None => text.len() / 4 + 1,
+    };
+
+    TokenCount {
+        tokens,
+        encoding,
+        approximate,
+        context_window: context_window(model),
+    }
+}
+
+/// Keep the beginning of `text` that fits into `max_tokens`
+pub fn truncate_to_tokens(text: &str, max_tokens: usize, model: &str) -> String {
+    let (encoding, _) = encoding_for(model);
+    let Some(bpe) = bpe(encoding) else {
+        return text.chars().take(max_tokens * 4).collect();
+    };
+
+    let tokens = bpe.encode_with_special_tokens(text);
+    if tokens.len() <= max_tokens {
+        return text.to_string();
+    }
+    // Token boundaries can split a multi-byte character, which fails to decode; a character spans at most four
+    // tokens, so step back until the cut is whole
+    (0..=max_tokens)
+        .rev()
+        .take(4)
+        .find_map(|end| bpe.decode(tokens[..end].to_vec()).ok())
+        .unwrap_or_default()
+}
+
+/// Running token budget used while assembling prompts
+pub struct ContextBudget {
+    model: String,
+    remaining: usize,
+}
+
+impl ContextBudget {
+    /// Budget for a model's context window minus the response reserve
+    pub fn for_model(model: &str) -> Self {
+        Self::new(model, context_window(model).saturating_sub(RESPONSE_RESERVE))
+    }
+
+    pub fn new(model: &str, max_tokens: usize) -> Self {
+        Self {
+            model: model.to_string(),
+            remaining: max_tokens,
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Reserve room for `text` if it fits entirely
+    pub fn try_add(&mut self, text: &str) -> bool {
+        let tokens = count(text, &self.model).tokens;
+        if tokens > self.remaining {
+            return false;
+        }
+        self.remaining -= tokens;
+        true
+    }
+
+    /// Add `text`, truncating it to the remaining budget
+    pub fn add_truncated(&mut self, text: &str) -> String {
+        if self.try_add(text) {
+            return text.to_string();
+        }
+        let truncated = truncate_to_tokens(text, self.remaining, &self.model);
+        self.remaining = 0;
+        truncated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_and_truncate() {
+        let counted = count("hello world", "gpt-4");
+        assert_eq!(counted.tokens, 2);
+        assert!(!counted.approximate);
+
+        let text = "one two three four five six";
+        let truncated = truncate_to_tokens(text, 3, "gpt-4");
+        assert_eq!(truncated, "one two three");
+
+        let text = "日本語のテキスト 😀😀😀 と絵文字";
+        for max_tokens in 1..8 {
+            let truncated = truncate_to_tokens(text, max_tokens, "gpt-4");
+            assert!(text.starts_with(&truncated));
+            assert!(max_tokens < 3 || !truncated.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_budget_truncates_when_exhausted() {
+        let mut budget = ContextBudget::new("gpt-4", 4);
+        assert!(budget.try_add("hello world"));
+        assert_eq!(budget.remaining(), 2);
+        assert_eq!(budget.add_truncated("one two three"), "one two");
+        assert_eq!(budget.remaining(), 0);
+    }
+}
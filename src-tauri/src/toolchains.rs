@@ -0,0 +1,460 @@
+// Toolchains - Installed compilers and runtimes checked against the versions a workspace declares
+// Declarations come from rust-toolchain, Cargo, .nvmrc, package.json, pyproject and go.mod
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::code_analyzer::CodeSuggestion;
+use crate::storage;
+use crate::task_runner::{self, TaskSpec};
+
+pub const SOURCE: &str = "toolchain";
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tool, commands tried in order, and the version argument
+const PROBES: &[(&str, &[&str], &str)] = &[
+    ("rustc", &["rustc"], "--version"),
+    ("cargo", &["cargo"], "--version"),
+    ("node", &["node"], "--version"),
+    ("npm", &["npm"], "--version"),
+    ("pnpm", &["pnpm"], "--version"),
+    ("python", &["python3", "python"], "--version"),
+    ("pip", &["pip3", "pip"], "--version"),
+    ("go", &["go"], "version"),
+    ("docker", &["docker"], "--version"),
+];
+
+/// Files whose presence means the workspace needs a tool at all
+const MARKERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "cargo"),
+    ("package.json", "node"),
+    ("pnpm-lock.yaml", "pnpm"),
+    ("pyproject.toml", "python"),
+    ("requirements.txt", "python"),
+    ("go.mod", "go"),
+    ("Dockerfile", "docker"),
+    ("docker-compose.yml", "docker"),
+    ("compose.yaml", "docker"),
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ToolVersion {
+    pub tool: String,
+    /// Command that answered, e.g. `python3`
+    pub command: Option<String>,
+    pub version: Option<String>,
+    /// First line the tool printed, or why it could not run
+    pub detail: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Requirement {
+    pub tool: String,
+    /// Version or range as declared, `*` when the tool is only needed
+    pub spec: String,
+    /// Workspace-relative file and 1-based line of the declaration
+    pub file: String,
+    pub line: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Mismatch {
+    pub requirement: Requirement,
+    pub installed: Option<String>,
+    pub message: String,
+    /// Command that would install a matching version
+    pub fix_command: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ToolchainReport {
+    pub tools: Vec<ToolVersion>,
+    pub requirements: Vec<Requirement>,
+    pub mismatches: Vec<Mismatch>,
+    /// Declarations that cannot be compared, such as `nightly` or `lts/*`
+    pub unchecked: Vec<Requirement>,
+    pub checked_at: u64,
+}
+
+/// First dotted number in version output: `rustc 1.75.0 (…)`, `v20.1.0`, `go version go1.21.5 linux/amd64`
+fn extract_version(output: &str) -> Option<String> {
+    output.split_whitespace().find_map(|word| {
+        let word = word.trim_start_matches("go").trim_start_matches('v').trim_end_matches([',', ';']);
+        let valid = word.starts_with(|c: char| c.is_ascii_digit())
+            && word.contains('.')
+            && word.split('.').all(|part| part.chars().take_while(char::is_ascii_digit).count() > 0);
+        valid.then(|| word.to_string())
+    })
+}
+
+fn probe(workspace: &Path, tool: &str, commands: &[&str], argument: &str) -> ToolVersion {
+    let mut detail = format!("{} not found", tool);
+    for command in commands {
+        let spec = TaskSpec {
+            command: command.to_string(),
+            args: vec![argument.to_string()],
+            cwd: None,
+            // A rust-toolchain file would otherwise make rustup download the toolchain first
+            env: HashMap::from([("RUSTUP_AUTO_INSTALL".to_string(), "0".to_string())]),
+            container: None,
+        };
+        let output = match task_runner::run(&spec, workspace, PROBE_TIMEOUT) {
+            Ok(output) => output,
+            Err(_) => continue,
+        };
+        // Older Pythons print their version to stderr
+        let text = format!("{}\n{}", output.stdout, output.stderr);
+        let first_line = text.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or_default();
+        if output.success() {
+            return ToolVersion {
+                tool: tool.to_string(),
+                command: Some(command.to_string()),
+                version: extract_version(&text),
+                detail: first_line.to_string(),
+            };
+        }
+        detail = first_line.to_string();
+    }
+    ToolVersion {
+        tool: tool.to_string(),
+        command: None,
+        version: None,
+        detail,
+    }
+}
+
+/// Version of every known tool, probed in parallel from the workspace so version managers apply
+pub fn probe_all(workspace: &Path) -> Vec<ToolVersion> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = PROBES
+            .iter()
+            .map(|(tool, commands, argument)| scope.spawn(move || probe(workspace, tool, commands, argument)))
+            .collect();
+        handles.into_iter().filter_map(|handle| handle.join().ok()).collect()
+    })
+}
+
+/// Value of `key` in `[section]` of a TOML file with its 1-based line; only plain `key = "value"` lines
+fn toml_value(text: &str, sections: &[&str], key: &str) -> Option<(String, usize)> {
+    let mut section = String::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+        if !sections.contains(&section.as_str()) {
+            continue;
+        }
+        let Some((name, value)) = line.split_once('=') else {
+            continue;
+        };
+        if name.trim().trim_matches('"') == key {
+            let value = value.split('#').next().unwrap_or(value).trim().trim_matches(['"', '\'']);
+            return Some((value.to_string(), i + 1));
+        }
+    }
+    None
+}
+
+fn line_of(text: &str, needle: &str) -> usize {
+    text.lines().position(|line| line.contains(needle)).map_or(1, |i| i + 1)
+}
+
+/// Versions the workspace declares, and the tools its manifests need
+pub fn requirements(workspace: &Path) -> Vec<Requirement> {
+    let mut found = Vec::new();
+    let read = |name: &str| fs::read_to_string(workspace.join(name)).ok();
+    let mut require = |tool: &str, spec: &str, file: &str, line: usize| {
+        found.push(Requirement {
+            tool: tool.to_string(),
+            spec: spec.trim().to_string(),
+            file: file.to_string(),
+            line,
+        });
+    };
+
+    if let Some(text) = read("rust-toolchain.toml") {
+        if let Some((channel, line)) = toml_value(&text, &["toolchain"], "channel") {
+            require("rustc", &channel, "rust-toolchain.toml", line);
+        }
+    } else if let Some(text) = read("rust-toolchain") {
+        require("rustc", text.lines().next().unwrap_or_default(), "rust-toolchain", 1);
+    }
+    if let Some(text) = read("Cargo.toml") {
+        // The minimum supported Rust version
+        if let Some((version, line)) = toml_value(&text, &["package", "workspace.package"], "rust-version") {
+            require("rustc", &format!(">={}", version), "Cargo.toml", line);
+        }
+    }
+    for file in [".nvmrc", ".node-version"] {
+        if let Some(version) = read(file).as_deref().and_then(|text| text.lines().next()) {
+            require("node", version, file, 1);
+            break;
+        }
+    }
+    if let Some(text) = read("package.json") {
+        let manifest: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+        for tool in ["node", "npm", "pnpm"] {
+            if let Some(spec) = manifest["engines"][tool].as_str() {
+                require(tool, spec, "package.json", line_of(&text, "\"engines\""));
+            }
+        }
+        // `pnpm@8.6.0+sha512.…` pins the package manager exactly
+        if let Some((tool, version)) = manifest["packageManager"].as_str().and_then(|pm| pm.split_once('@')) {
+            if tool == "npm" || tool == "pnpm" {
+                let version = version.split('+').next().unwrap_or(version);
+                require(tool, version, "package.json", line_of(&text, "\"packageManager\""));
+            }
+        }
+    }
+    if let Some(version) = read(".python-version").as_deref().and_then(|text| text.lines().next()) {
+        require("python", version, ".python-version", 1);
+    }
+    if let Some(text) = read("pyproject.toml") {
+        if let Some((spec, line)) = toml_value(&text, &["project"], "requires-python") {
+            require("python", &spec, "pyproject.toml", line);
+        } else if let Some((spec, line)) = toml_value(&text, &["tool.poetry.dependencies"], "python") {
+            require("python", &spec, "pyproject.toml", line);
+        }
+    }
+    if let Some(text) = read("go.mod") {
+        for (i, line) in text.lines().enumerate() {
+            if let Some(version) = line.trim().strip_prefix("go ") {
+                require("go", &format!(">={}", version.trim()), "go.mod", i + 1);
+            } else if let Some(version) = line.trim().strip_prefix("toolchain go") {
+                require("go", &format!(">={}", version.trim()), "go.mod", i + 1);
+            }
+        }
+    }
+    for (marker, tool) in MARKERS {
+        if workspace.join(marker).is_file() {
+            require(tool, "*", marker, 1);
+        }
+    }
+    found
+}
+
+fn numbers(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim().trim_start_matches('v');
+    let core = version.split(['-', '+']).next()?;
+    core.split('.')
+        .filter(|part| *part != "*" && *part != "x")
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u64>>>()
+        .filter(|numbers| !numbers.is_empty())
+}
+
+/// Compare with missing components as zero
+fn compare(a: &[u64], b: &[u64]) -> std::cmp::Ordering {
+    let len = a.len().max(b.len());
+    let at = |v: &[u64], i: usize| v.get(i).copied().unwrap_or(0);
+    (0..len).map(|i| at(a, i).cmp(&at(b, i))).find(|order| order.is_ne()).unwrap_or(std::cmp::Ordering::Equal)
+}
+
+/// Next version that no longer matches `bound` under `^` (`caret`) or `~` semantics
+fn upper_bound(bound: &[u64], caret: bool) -> Vec<u64> {
+    let position = if caret {
+        bound.iter().position(|n| *n != 0).unwrap_or(bound.len().saturating_sub(1))
+    } else if bound.len() > 1 {
+        1
+    } else {
+        0
+    };
+    let mut upper = bound[..=position].to_vec();
+    upper[position] += 1;
+    upper
+}
+
+fn satisfies_comparator(version: &[u64], comparator: &str) -> Option<bool> {
+    use std::cmp::Ordering::*;
+    let comparator = comparator.trim();
+    let ops = ["~=", ">=", "<=", "==", "!=", ">", "<", "=", "^", "~"];
+    let op = ops.iter().find(|op| comparator.starts_with(**op)).copied().unwrap_or("");
+    let bound = numbers(&comparator[op.len()..])?;
+    let order = compare(version, &bound);
+    Some(match op {
+        ">=" => order != Less,
+        "<=" => order != Greater,
+        ">" => order == Greater,
+        "<" => order == Less,
+        "!=" => order != Equal,
+        "^" => order != Less && compare(version, &upper_bound(&bound, true)) == Less,
+        "~" => order != Less && compare(version, &upper_bound(&bound, false)) == Less,
+        // Python's compatible release: `~=3.10` allows 3.x from 3.10, `~=3.10.2` allows 3.10.x
+        "~=" => {
+            let prefix = &bound[..bound.len().saturating_sub(1).max(1)];
+            order != Less && version.starts_with(prefix)
+        }
+        // A bare or `==` version matches every release it is a prefix of: `20` matches 20.11.1
+        _ => version.starts_with(&bound),
+    })
+}
+
+/// Whether `version` meets `spec`; `None` when the spec names no version, as `stable` or `lts/*`
+fn satisfies(version: &str, spec: &str) -> Option<bool> {
+    let spec = spec.trim();
+    if spec == "*" || spec.is_empty() {
+        return Some(true);
+    }
+    let version = numbers(version)?;
+    let mut any_checked = false;
+    for alternative in spec.split("||") {
+        // npm writes ranges space-separated, Python comma-separated; `>= 18` puts a space after the operator
+        let mut comparators: Vec<String> = Vec::new();
+        let mut operator = String::new();
+        for token in alternative.split([',', ' ']).filter(|token| !token.is_empty()) {
+            if token.chars().all(|c| "<>=!~^".contains(c)) {
+                operator.push_str(token);
+            } else {
+                comparators.push(format!("{}{}", std::mem::take(&mut operator), token));
+            }
+        }
+        let results: Option<Vec<bool>> = comparators.iter().map(|c| satisfies_comparator(&version, c)).collect();
+        if let Some(results) = results.filter(|results| !results.is_empty()) {
+            any_checked = true;
+            if results.iter().all(|ok| *ok) {
+                return Some(true);
+            }
+        }
+    }
+    any_checked.then_some(false)
+}
+
+/// Version to install for a spec: the declared one without operators
+fn install_target(spec: &str) -> Option<String> {
+    let exact = spec.trim().trim_start_matches(['>', '=', '^', '~', 'v']).split([',', ' ']).next()?.to_string();
+    numbers(&exact).map(|_| exact)
+}
+
+fn fix_command(tool: &str, spec: &str) -> Option<String> {
+    let target = install_target(spec);
+    Some(match (tool, target) {
+        ("rustc" | "cargo", Some(version)) => format!("rustup toolchain install {}", version),
+        ("rustc" | "cargo", None) => "curl https://sh.rustup.rs -sSf | sh".to_string(),
+        ("node", Some(version)) => format!("nvm install {}", version),
+        ("npm" | "pnpm", Some(version)) => format!("corepack prepare {}@{} --activate", tool, version),
+        ("pnpm", None) => "corepack enable pnpm".to_string(),
+        ("python", Some(version)) => format!("pyenv install {}", version),
+        ("go", Some(version)) => format!("go install golang.org/dl/go{}@latest", version),
+        _ => return None,
+    })
+}
+
+/// Requirements the installed tools do not meet, and the ones that cannot be compared
+pub fn compare_requirements(tools: &[ToolVersion], requirements: &[Requirement]) -> (Vec<Mismatch>, Vec<Requirement>) {
+    let mut mismatches = Vec::new();
+    let mut unchecked = Vec::new();
+    for requirement in requirements {
+        let tool = tools.iter().find(|tool| tool.tool == requirement.tool);
+        let installed = tool.and_then(|tool| tool.version.clone());
+        let message = match (tool.and_then(|t| t.command.as_ref()), &installed) {
+            (None, _) => format!("{} needs {}, which is not installed", requirement.file, requirement.tool),
+            (Some(_), Some(version)) => match satisfies(version, &requirement.spec) {
+                Some(true) => continue,
+                Some(false) => format!(
+                    "{} requires {} {}, found {}",
+                    requirement.file, requirement.tool, requirement.spec, version
+                ),
+                None => {
+                    unchecked.push(requirement.clone());
+                    continue;
+                }
+            },
+            (Some(_), None) => {
+                unchecked.push(requirement.clone());
+                continue;
+            }
+        };
+        let fix_command = fix_command(&requirement.tool, &requirement.spec);
+        mismatches.push(Mismatch {
+            message: match &fix_command {
+                Some(command) => format!("{}; run `{}`", message, command),
+                None => message,
+            },
+            requirement: requirement.clone(),
+            installed,
+            fix_command,
+        });
+    }
+    (mismatches, unchecked)
+}
+
+pub fn detect(workspace: &Path) -> ToolchainReport {
+    let tools = probe_all(workspace);
+    let requirements = requirements(workspace);
+    let (mismatches, unchecked) = compare_requirements(&tools, &requirements);
+    ToolchainReport {
+        tools,
+        requirements,
+        mismatches,
+        unchecked,
+        checked_at: storage::now_millis(),
+    }
+}
+
+/// Mismatches as warnings at their declarations, by workspace-relative file
+pub fn suggestions(report: &ToolchainReport) -> HashMap<String, Vec<CodeSuggestion>> {
+    let mut by_file: HashMap<String, Vec<CodeSuggestion>> = HashMap::new();
+    for mismatch in &report.mismatches {
+        by_file.entry(mismatch.requirement.file.clone()).or_default().push(CodeSuggestion {
+            kind: "toolchain".to_string(),
+            message: mismatch.message.clone(),
+            line: mismatch.requirement.line,
+            column: 0,
+            severity: "warning".to_string(),
+            fix: None,
+        });
+    }
+    by_file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_ranges_and_mismatches() {
+        assert_eq!(extract_version("rustc 1.75.0 (82e1608df 2023-12-21)").as_deref(), Some("1.75.0"));
+        assert_eq!(extract_version("go version go1.21.5 linux/amd64").as_deref(), Some("1.21.5"));
+        assert_eq!(extract_version("Docker version 24.0.7, build afdd53b").as_deref(), Some("24.0.7"));
+        assert_eq!(extract_version("v20.11.1").as_deref(), Some("20.11.1"));
+
+        assert_eq!(satisfies("20.11.1", "20"), Some(true));
+        assert_eq!(satisfies("1.74.1", "1.75.0"), Some(false));
+        assert_eq!(satisfies("3.11.4", ">=3.10,<4"), Some(true));
+        assert_eq!(satisfies("3.9.1", "^3.10"), Some(false));
+        assert_eq!(satisfies("3.12.0", "~=3.10"), Some(true));
+        assert_eq!(satisfies("18.2.0", ">= 16 < 18 || >=20"), Some(false));
+        assert_eq!(satisfies("0.3.1", "^0.2"), Some(false));
+        assert_eq!(satisfies("1.76.0", "stable"), None);
+
+        let dir = std::env::temp_dir().join(format!("mimi-toolchains-{}", storage::new_id("t")));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("rust-toolchain.toml"), "[toolchain]\nchannel = \"1.75.0\"\n").unwrap();
+        fs::write(dir.join(".nvmrc"), "lts/iron\n").unwrap();
+        fs::write(dir.join("pyproject.toml"), "[project]\nname = \"app\"\nrequires-python = \">=3.11\"\n").unwrap();
+        let requirements = requirements(&dir);
+        assert_eq!(requirements[0].line, 2);
+        assert!(requirements.iter().any(|r| r.tool == "python" && r.spec == ">=3.11" && r.line == 3));
+
+        let tool = |name: &str, version: Option<&str>| ToolVersion {
+            tool: name.to_string(),
+            command: version.map(|_| name.to_string()),
+            version: version.map(str::to_string),
+            detail: String::new(),
+        };
+        let tools = [tool("rustc", Some("1.74.1")), tool("node", Some("20.11.1"))];
+        let (mismatches, unchecked) = compare_requirements(&tools, &requirements);
+        assert_eq!(mismatches.len(), 3);
+        assert_eq!(mismatches[0].fix_command.as_deref(), Some("rustup toolchain install 1.75.0"));
+        assert!(mismatches.iter().any(|m| m.requirement.file == "pyproject.toml" && m.installed.is_none()));
+        assert_eq!(unchecked[0].file, ".nvmrc");
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,344 @@
+// Edit Transactions - Staged multi-file writes applied all at once or not at all
+// Content is staged in temp files beside each target, then swapped in by renames
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::file_access;
+use crate::paths;
+use crate::storage;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Operation {
+    /// Create or replace a file
+    Write { path: String, content: String },
+    Rename { from: String, to: String },
+    Delete { path: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Transaction {
+    pub id: String,
+    pub description: String,
+    pub created_at: u64,
+    /// Applied in order on commit
+    pub operations: Vec<Operation>,
+}
+
+/// A step that reached disk, with what is needed to undo it
+enum Applied {
+    Wrote { target: PathBuf, backup: Option<PathBuf> },
+    Deleted { target: PathBuf, backup: PathBuf },
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+/// Absolute target of a transaction path, which must stay inside the workspace and out of its engine data
+fn target(workspace: &Path, path: &str) -> Result<PathBuf> {
+    if Path::new(path).components().any(|c| matches!(c, Component::ParentDir)) {
+        return Err(anyhow!("Path must stay inside the workspace: {}", path));
+    }
+    let resolved = file_access::confine_write(workspace, path)?;
+    Ok(paths::for_io(&resolved))
+}
+
+/// Target of an operation that may create `path`; names it creates are composed (NFC) and valid on every platform
+fn new_target(workspace: &Path, path: &str) -> Result<PathBuf> {
+    let existing = target(workspace, path)?;
+    if existing.exists() {
+        return Ok(existing);
+    }
+    let composed = paths::composed(path);
+    let mut current = workspace.to_path_buf();
+    for component in Path::new(&composed).components() {
+        current.push(component);
+        if let Component::Normal(name) = component {
+            if !current.exists() {
+                paths::check_new_name(&name.to_string_lossy())?;
+            }
+        }
+    }
+    target(workspace, &composed)
+}
+
+/// Ancestors of `dir` that do not exist yet, outermost first
+fn missing_dirs(dir: &Path) -> Vec<PathBuf> {
+    let mut missing: Vec<PathBuf> = dir.ancestors().take_while(|d| !d.exists()).map(Path::to_path_buf).collect();
+    missing.reverse();
+    missing
+}
+
+impl Transaction {
+    pub fn new(description: impl Into<String>) -> Self {
+        Self {
+            id: storage::new_id("txn"),
+            description: description.into(),
+            created_at: storage::now_millis(),
+            operations: Vec::new(),
+        }
+    }
+
+    pub fn with_operations(mut self, operations: Vec<Operation>) -> Self {
+        self.operations = operations;
+        self
+    }
+
+    /// Hidden file next to `path`, so renames never cross filesystems
+    fn sibling(&self, path: &Path, kind: &str) -> PathBuf {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        path.with_file_name(format!(".{}.{}-{}", name, kind, self.id))
+    }
+
+    /// Apply every operation, or restore the workspace as it was if any fails
+    pub fn commit(&self, workspace: &Path) -> Result<()> {
+        let mut created_dirs: Vec<PathBuf> = Vec::new();
+        let mut staged: Vec<Option<PathBuf>> = Vec::with_capacity(self.operations.len());
+        let result = self.stage(workspace, &mut created_dirs, &mut staged).and_then(|()| {
+            let mut applied = Vec::new();
+            match self.apply(workspace, &staged, &mut created_dirs, &mut applied) {
+                Ok(()) => Ok(applied),
+                Err(e) => {
+                    rollback(applied);
+                    Err(e)
+                }
+            }
+        });
+
+        for temp in staged.iter().flatten() {
+            let _ = fs::remove_file(temp);
+        }
+        match result {
+            Ok(applied) => {
+                for step in applied {
+                    if let Applied::Wrote { backup: Some(backup), .. } | Applied::Deleted { backup, .. } = step {
+                        let _ = fs::remove_file(backup);
+                    }
+                }
+                log::info!("Committed transaction {} ({} operations)", self.id, self.operations.len());
+                Ok(())
+            }
+            Err(e) => {
+                for dir in created_dirs.iter().rev() {
+                    let _ = fs::remove_dir(dir);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Write the new content of every `Write` to a temp file
+    fn stage(&self, workspace: &Path, created_dirs: &mut Vec<PathBuf>, staged: &mut Vec<Option<PathBuf>>) -> Result<()> {
+        for operation in &self.operations {
+            let temp = match operation {
+                Operation::Write { path, content } => {
+                    let target = new_target(workspace, path)?;
+                    file_access::check(&target, path)?;
+                    if let Some(parent) = target.parent() {
+                        created_dirs.extend(missing_dirs(parent));
+                        fs::create_dir_all(parent)?;
+                    }
+                    let temp = self.sibling(&target, "tmp");
+                    fs::write(&temp, content).with_context(|| format!("Failed to stage {}", path))?;
+                    // Keep the mode of the replaced file, e.g. the executable bit of scripts
+                    if let Ok(metadata) = fs::metadata(&target) {
+                        fs::set_permissions(&temp, metadata.permissions())?;
+                    }
+                    Some(temp)
+                }
+                Operation::Rename { from, to } => {
+                    file_access::check(&target(workspace, from)?, from)?;
+                    new_target(workspace, to)?;
+                    None
+                }
+                Operation::Delete { path } => {
+                    file_access::check(&target(workspace, path)?, path)?;
+                    None
+                }
+            };
+            staged.push(temp);
+        }
+        Ok(())
+    }
+
+    fn apply(
+        &self,
+        workspace: &Path,
+        staged: &[Option<PathBuf>],
+        created_dirs: &mut Vec<PathBuf>,
+        applied: &mut Vec<Applied>,
+    ) -> Result<()> {
+        for (operation, temp) in self.operations.iter().zip(staged) {
+            match (operation, temp) {
+                (Operation::Write { path, .. }, Some(temp)) => {
+                    let target = new_target(workspace, path)?;
+                    let backup = if target.exists() {
+                        let backup = self.sibling(&target, "bak");
+                        fs::rename(&target, &backup).with_context(|| format!("Failed to replace {}", path))?;
+                        Some(backup)
+                    } else {
+                        None
+                    };
+                    if let Err(e) = fs::rename(temp, &target) {
+                        if let Some(backup) = &backup {
+                            let _ = fs::rename(backup, &target);
+                        }
+                        return Err(anyhow!("Failed to write {}: {}", path, e));
+                    }
+                    applied.push(Applied::Wrote { target, backup });
+                }
+                (Operation::Rename { from, to }, _) => {
+                    let (source, destination) = (target(workspace, from)?, new_target(workspace, to)?);
+                    if !source.exists() {
+                        return Err(anyhow!("File not found: {}", from));
+                    }
+                    // On a case-insensitive file system `Foo.ts -> foo.ts` finds its own source as the target
+                    let case_only =
+                        paths::is_case_change(&source, &destination) && !paths::exists_exactly(&destination);
+                    if destination.exists() && !case_only {
+                        return Err(anyhow!("Rename target already exists: {}", to));
+                    }
+                    if let Some(parent) = destination.parent() {
+                        created_dirs.extend(missing_dirs(parent));
+                        fs::create_dir_all(parent)?;
+                    }
+                    paths::rename(&source, &destination)
+                        .with_context(|| format!("Failed to rename {} to {}", from, to))?;
+                    applied.push(Applied::Renamed {
+                        from: source,
+                        to: destination,
+                    });
+                }
+                (Operation::Delete { path }, _) => {
+                    let target = target(workspace, path)?;
+                    let backup = self.sibling(&target, "bak");
+                    fs::rename(&target, &backup).with_context(|| format!("Failed to delete {}", path))?;
+                    applied.push(Applied::Deleted { target, backup });
+                }
+                (Operation::Write { path, .. }, None) => return Err(anyhow!("{} was not staged", path)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Undo applied steps, newest first
+fn rollback(applied: Vec<Applied>) {
+    for step in applied.into_iter().rev() {
+        let result = match &step {
+            Applied::Wrote { target, backup } => fs::remove_file(target).and_then(|()| match backup {
+                Some(backup) => fs::rename(backup, target),
+                None => Ok(()),
+            }),
+            Applied::Deleted { target, backup } => fs::rename(backup, target),
+            Applied::Renamed { from, to } => paths::rename(to, from),
+        };
+        if let Err(e) = result {
+            log::error!("Rollback step failed: {}", e);
+        }
+    }
+}
+
+/// Transactions begun by the frontend and not yet committed or discarded
+pub struct TransactionManager {
+    open: Mutex<HashMap<String, Transaction>>,
+}
+
+impl TransactionManager {
+    pub fn new() -> Self {
+        Self {
+            open: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn begin(&self, description: &str) -> Transaction {
+        let transaction = Transaction::new(description);
+        self.open.lock().unwrap().insert(transaction.id.clone(), transaction.clone());
+        transaction
+    }
+
+    pub fn stage(&self, id: &str, operation: Operation) -> Result<Transaction> {
+        let mut open = self.open.lock().unwrap();
+        let transaction = open.get_mut(id).ok_or_else(|| anyhow!("Unknown transaction: {}", id))?;
+        transaction.operations.push(operation);
+        Ok(transaction.clone())
+    }
+
+    /// Remove a transaction to commit or discard it
+    pub fn take(&self, id: &str) -> Result<Transaction> {
+        self.open
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| anyhow!("Unknown transaction: {}", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_rolls_back_on_failure() {
+        let workspace = std::env::temp_dir().join(storage::new_id("transaction-test"));
+        fs::create_dir_all(&workspace).unwrap();
+        fs::write(workspace.join("a.txt"), "old").unwrap();
+        fs::write(workspace.join("b.txt"), "keep").unwrap();
+
+        let failing = Transaction::new("fails").with_operations(vec![
+            Operation::Write { path: "a.txt".to_string(), content: "new".to_string() },
+            Operation::Write { path: "dir/c.txt".to_string(), content: "c".to_string() },
+            Operation::Delete { path: "b.txt".to_string() },
+            Operation::Rename { from: "missing.txt".to_string(), to: "x.txt".to_string() },
+        ]);
+        assert!(failing.commit(&workspace).is_err());
+        assert_eq!(fs::read_to_string(workspace.join("a.txt")).unwrap(), "old");
+        assert_eq!(fs::read_to_string(workspace.join("b.txt")).unwrap(), "keep");
+        assert!(!workspace.join("dir").exists());
+        assert_eq!(fs::read_dir(&workspace).unwrap().count(), 2);
+
+        let ok = Transaction::new("works").with_operations(vec![
+            Operation::Write { path: "a.txt".to_string(), content: "new".to_string() },
+            Operation::Rename { from: "b.txt".to_string(), to: "sub/b.txt".to_string() },
+        ]);
+        ok.commit(&workspace).unwrap();
+        assert_eq!(fs::read_to_string(workspace.join("a.txt")).unwrap(), "new");
+        assert!(workspace.join("sub/b.txt").exists());
+        assert_eq!(fs::read_dir(&workspace).unwrap().count(), 2);
+        let _ = fs::remove_dir_all(&workspace);
+    }
+
+    #[test]
+    fn test_portable_paths() {
+        let workspace = std::env::temp_dir().join(storage::new_id("transaction-test"));
+        fs::create_dir_all(&workspace).unwrap();
+        let long = format!("{}module.ts", "deeply-nested-directory/".repeat(12));
+        assert!(workspace.join(&long).to_string_lossy().len() > 260);
+
+        let transaction = Transaction::new("long").with_operations(vec![
+            Operation::Write { path: long.clone(), content: "export {}".to_string() },
+            Operation::Write { path: "docs/cafe\u{301}.md".to_string(), content: "# Caf\u{e9}".to_string() },
+        ]);
+        transaction.commit(&workspace).unwrap();
+        assert_eq!(fs::read_to_string(workspace.join(&long)).unwrap(), "export {}");
+        assert!(workspace.join("docs/caf\u{e9}.md").exists());
+
+        let reserved = Transaction::new("reserved")
+            .with_operations(vec![Operation::Write { path: "src/con.ts".to_string(), content: String::new() }]);
+        assert!(reserved.commit(&workspace).unwrap_err().to_string().contains("reserved"));
+        assert!(!workspace.join("src").exists());
+
+        fs::write(workspace.join("Button.tsx"), "button").unwrap();
+        let case_only = Transaction::new("case").with_operations(vec![Operation::Rename {
+            from: "Button.tsx".to_string(),
+            to: "button.tsx".to_string(),
+        }]);
+        case_only.commit(&workspace).unwrap();
+        assert!(paths::exists_exactly(&workspace.join("button.tsx")));
+        assert!(!paths::exists_exactly(&workspace.join("Button.tsx")));
+        let _ = fs::remove_dir_all(&workspace);
+    }
+}
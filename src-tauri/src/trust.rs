@@ -0,0 +1,161 @@
+// Workspace Trust - Which folders may run their own tasks, processes and configuration
+// Undecided folders open in restricted mode; decisions live in the app config dir, never in the workspace
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::storage;
+
+#[derive(Error, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TrustError {
+    /// Cleared by `set_workspace_trust`
+    #[error("{workspace} is in restricted mode; trust it to allow {action}")]
+    Restricted { workspace: String, action: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TrustDecision {
+    pub trusted: bool,
+    pub decided_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WorkspaceTrust {
+    pub path: String,
+    pub trusted: bool,
+    /// Folder whose decision applies, the workspace itself or an ancestor; `None` while undecided
+    pub decided_by: Option<String>,
+    pub decided_at: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct TrustStore {
+    /// Decisions by canonical folder path
+    folders: BTreeMap<String, TrustDecision>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+/// Canonical path, so symlinks and `..` can't dodge a decision; folders that don't exist yet keep
+/// their canonical parent
+fn key(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+    match (path.parent().and_then(|parent| parent.canonicalize().ok()), path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+impl TrustStore {
+    pub fn load(path: PathBuf) -> Self {
+        let mut store = match storage::read_json::<TrustStore>(&path) {
+            Ok(Some(store)) => store,
+            Ok(None) => Self::default(),
+            Err(e) => {
+                log::warn!("Ignoring unreadable trust store {:?}: {}", path, e);
+                Self::default()
+            }
+        };
+        store.path = Some(path);
+        store
+    }
+
+    /// The nearest decision for the folder or one of its ancestors
+    pub fn status(&self, workspace: &Path) -> WorkspaceTrust {
+        let workspace = key(workspace);
+        let decision = workspace.ancestors().find_map(|dir| {
+            let dir = dir.to_string_lossy().to_string();
+            self.folders.get(&dir).map(|decision| (dir, decision))
+        });
+        let trusted = decision.as_ref().is_some_and(|(_, decision)| decision.trusted);
+        let decided_at = decision.as_ref().map(|(_, decision)| decision.decided_at);
+        WorkspaceTrust {
+            path: workspace.to_string_lossy().to_string(),
+            trusted,
+            decided_by: decision.map(|(dir, _)| dir),
+            decided_at,
+        }
+    }
+
+    /// Record a decision; nested folders follow it unless they have their own
+    pub fn set(&mut self, folder: &Path, trusted: bool) -> Result<WorkspaceTrust> {
+        log::info!("Marking {:?} as {}", folder, if trusted { "trusted" } else { "restricted" });
+        let decision = TrustDecision {
+            trusted,
+            decided_at: storage::now_millis(),
+        };
+        self.folders.insert(key(folder).to_string_lossy().to_string(), decision);
+        if let Some(path) = &self.path {
+            storage::write_json(path, self)?;
+        }
+        Ok(self.status(folder))
+    }
+
+    pub fn is_trusted(&self, workspace: &Path) -> bool {
+        self.status(workspace).trusted
+    }
+
+    /// Refuse `action` (e.g. "tasks") unless the workspace is trusted
+    pub fn ensure(&self, workspace: &Path, action: &str) -> Result<(), TrustError> {
+        let status = self.status(workspace);
+        if status.trusted {
+            return Ok(());
+        }
+        log::warn!("Refused {} in restricted workspace {}", action, status.path);
+        Err(TrustError::Restricted {
+            workspace: status.path,
+            action: action.to_string(),
+        })
+    }
+}
+
+/// Command error text: restricted mode as JSON, anything else as its message
+pub fn command_error(error: anyhow::Error) -> String {
+    match error.downcast_ref::<TrustError>() {
+        Some(trust) => serde_json::to_string(trust).unwrap_or_else(|_| trust.to_string()),
+        None => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_nearest_decision_applies_and_persists() {
+        let root = std::env::temp_dir().join(storage::new_id("trust-test"));
+        let nested = root.join("vendor").join("untrusted");
+        fs::create_dir_all(&nested).unwrap();
+        let file = root.join("trust.json");
+
+        let mut store = TrustStore::load(file.clone());
+        assert!(!store.status(&nested).trusted);
+        assert!(store.status(&nested).decided_by.is_none());
+        store.set(&root, true).unwrap();
+        assert!(store.status(&root.join("vendor")).trusted);
+        store.set(&nested, false).unwrap();
+
+        let store = TrustStore::load(file);
+        let status = store.status(&nested.join("..").join("untrusted"));
+        assert!(!status.trusted);
+        assert_eq!(status.decided_by, Some(key(&nested).to_string_lossy().to_string()));
+        assert!(store.status(&root.join("src")).trusted);
+        assert!(store.ensure(&nested, "tasks").is_err());
+
+        let error = TrustError::Restricted {
+            workspace: "/w".to_string(),
+            action: "tasks".to_string(),
+        };
+        let json = command_error(error.into());
+        assert_eq!(json, r#"{"type":"restricted","workspace":"/w","action":"tasks"}"#);
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,407 @@
+// TypeScript Strictness - tsconfig strict flags checked against the code that would break
+// Suppressions, non-null assertions and implicit-any parameters, summed into a migration plan
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::syntax;
+
+/// Flags `strict` turns on, each of which can be switched off on its own
+const STRICT_FLAGS: [&str; 8] = [
+    "noImplicitAny",
+    "strictNullChecks",
+    "strictFunctionTypes",
+    "strictBindCallApply",
+    "strictPropertyInitialization",
+    "noImplicitThis",
+    "alwaysStrict",
+    "useUnknownInCatchVariables",
+];
+
+/// How many `extends` hops are followed
+const MAX_EXTENDS: usize = 5;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    StrictDisabled,
+    TsNocheck,
+    TsIgnore,
+    NonNullAssertion,
+    ImplicitAny,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StrictnessFinding {
+    pub kind: FindingKind,
+    /// Relative to the workspace
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CompilerStrictness {
+    /// The tsconfig.json read, if the workspace has one
+    pub config_file: Option<String>,
+    pub strict: bool,
+    /// Strict-family flags in effect as off, whether by `strict: false` or explicitly
+    pub disabled: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MigrationStep {
+    pub title: String,
+    /// The compiler option this step enables, if any
+    pub option: Option<String>,
+    /// Code sites to fix or review for the step
+    pub sites: usize,
+    pub files: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileStrictness {
+    pub file: String,
+    pub counts: BTreeMap<FindingKind, usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StrictnessReport {
+    pub compiler: CompilerStrictness,
+    pub files_scanned: usize,
+    pub totals: BTreeMap<FindingKind, usize>,
+    /// Most findings first
+    pub files: Vec<FileStrictness>,
+    pub migration: Vec<MigrationStep>,
+    pub findings: Vec<StrictnessFinding>,
+}
+
+type CharStream<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+/// Rewrite `text` by visiting each character outside string literals; strings pass through untouched
+fn outside_strings(text: &str, mut visit: impl FnMut(char, &mut CharStream, &mut String)) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            visit(c, &mut chars, &mut out);
+            continue;
+        }
+        out.push(c);
+        while let Some(n) = chars.next() {
+            out.push(n);
+            match n {
+                '\\' => out.extend(chars.next()),
+                '"' => break,
+                _ => {}
+            }
+        }
+    }
+    out
+}
+
+/// JSON with comments and trailing commas, as tsconfig allows, turned into plain JSON.
+/// Comments become whitespace so line numbers survive.
+fn strip_jsonc(text: &str) -> String {
+    let uncommented = outside_strings(text, |c, chars, out| match (c, chars.peek()) {
+        ('/', Some('/')) => {
+            while chars.peek().is_some_and(|&n| n != '\n') {
+                chars.next();
+            }
+        }
+        ('/', Some('*')) => {
+            chars.next();
+            let mut previous = ' ';
+            for n in chars.by_ref() {
+                if previous == '*' && n == '/' {
+                    break;
+                }
+                if n == '\n' {
+                    out.push('\n');
+                }
+                previous = n;
+            }
+        }
+        _ => out.push(c),
+    });
+    outside_strings(&uncommented, |c, chars, out| {
+        let closes = chars.clone().find(|n| !n.is_whitespace()).is_some_and(|n| n == '}' || n == ']');
+        if c != ',' || !closes {
+            out.push(c);
+        }
+    })
+}
+
+/// `compilerOptions` of a tsconfig with those of the configs it extends underneath
+fn compiler_options(path: &Path, depth: usize) -> serde_json::Map<String, serde_json::Value> {
+    let parsed = fs::read_to_string(path).ok().and_then(|t| serde_json::from_str(&strip_jsonc(&t)).ok());
+    let config: serde_json::Value = match parsed {
+        Some(config) => config,
+        None => return serde_json::Map::new(),
+    };
+    // Package configs (`@tsconfig/node18`) are not resolved, only relative paths
+    let mut options = match config["extends"].as_str().filter(|e| e.starts_with('.')) {
+        Some(base) if depth < MAX_EXTENDS => {
+            let base = path.parent().unwrap_or(path).join(base);
+            let base = if base.extension().is_some() { base } else { base.with_extension("json") };
+            compiler_options(&base, depth + 1)
+        }
+        _ => serde_json::Map::new(),
+    };
+    if let Some(own) = config["compilerOptions"].as_object() {
+        options.extend(own.clone());
+    }
+    options
+}
+
+/// Strict flags in effect for the workspace's root tsconfig.json
+pub fn compiler_strictness(workspace: &Path) -> CompilerStrictness {
+    let path = workspace.join("tsconfig.json");
+    if !path.is_file() {
+        return CompilerStrictness::default();
+    }
+    let options = compiler_options(&path, 0);
+    let strict = options.get("strict").and_then(|v| v.as_bool()).unwrap_or(false);
+    let disabled = STRICT_FLAGS
+        .iter()
+        .filter(|flag| !options.get(**flag).and_then(|v| v.as_bool()).unwrap_or(strict))
+        .map(|flag| flag.to_string())
+        .collect();
+    CompilerStrictness {
+        config_file: Some("tsconfig.json".to_string()),
+        strict,
+        disabled,
+    }
+}
+
+struct Scanner<'a> {
+    file: &'a str,
+    content: &'a str,
+    findings: Vec<StrictnessFinding>,
+}
+
+impl<'a> Scanner<'a> {
+    fn text(&self, node: Node) -> &'a str {
+        &self.content[node.byte_range()]
+    }
+
+    fn push(&mut self, node: Node, kind: FindingKind, message: String) {
+        self.findings.push(StrictnessFinding {
+            kind,
+            file: self.file.to_string(),
+            line: node.start_position().row + 1,
+            column: node.start_position().column,
+            message,
+        });
+    }
+
+    /// Whether the parameters of a function node get no type from context:
+    /// declarations and class methods, or function values bound to an untyped variable
+    fn needs_annotations(node: Node) -> bool {
+        match node.kind() {
+            "function_declaration" | "generator_function_declaration" => true,
+            "method_definition" => node.parent().is_some_and(|p| p.kind() == "class_body"),
+            "arrow_function" | "function" | "function_expression" => node
+                .parent()
+                .is_some_and(|p| p.kind() == "variable_declarator" && p.child_by_field_name("type").is_none()),
+            _ => false,
+        }
+    }
+
+    fn check_parameters(&mut self, function: Node) {
+        if !Self::needs_annotations(function) {
+            return;
+        }
+        // `x => ...` has a bare identifier instead of a parameter list
+        if let Some(single) = function.child_by_field_name("parameter") {
+            let name = self.text(single).to_string();
+            self.push(single, FindingKind::ImplicitAny, format!("Parameter '{}' implicitly has an 'any' type", name));
+            return;
+        }
+        let parameters = match function.child_by_field_name("parameters") {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let mut cursor = parameters.walk();
+        let untyped: Vec<Node> = parameters
+            .named_children(&mut cursor)
+            .filter(|p| matches!(p.kind(), "required_parameter" | "optional_parameter"))
+            .filter(|p| p.child_by_field_name("type").is_none() && p.child_by_field_name("value").is_none())
+            .collect();
+        for parameter in untyped {
+            let name = parameter.child_by_field_name("pattern").map(|p| self.text(p)).unwrap_or("");
+            if name == "this" {
+                continue;
+            }
+            let message = format!("Parameter '{}' implicitly has an 'any' type", name);
+            self.push(parameter, FindingKind::ImplicitAny, message);
+        }
+    }
+
+    fn walk(&mut self, node: Node) {
+        match node.kind() {
+            "comment" => {
+                let comment = self.text(node);
+                if comment.contains("@ts-nocheck") {
+                    let message = "@ts-nocheck disables type checking for the whole file".to_string();
+                    self.push(node, FindingKind::TsNocheck, message);
+                } else if comment.contains("@ts-ignore") {
+                    let message = "@ts-ignore hides any error on the next line; prefer @ts-expect-error".to_string();
+                    self.push(node, FindingKind::TsIgnore, message);
+                }
+            }
+            "non_null_expression" => {
+                let message = "Non-null assertion skips the null check; narrow the value instead".to_string();
+                self.push(node, FindingKind::NonNullAssertion, message);
+            }
+            "function_declaration"
+            | "generator_function_declaration"
+            | "method_definition"
+            | "arrow_function"
+            | "function"
+            | "function_expression" => self.check_parameters(node),
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        for child in children {
+            self.walk(child);
+        }
+    }
+}
+
+/// Strictness findings in one TypeScript file; `file` is the workspace-relative path
+pub fn scan(file: &str, content: &str) -> Vec<StrictnessFinding> {
+    let tree = match syntax::parse(file, content) {
+        Some(tree) => tree,
+        None => return Vec::new(),
+    };
+    let mut scanner = Scanner {
+        file,
+        content,
+        findings: Vec::new(),
+    };
+    scanner.walk(tree.root_node());
+    scanner.findings
+}
+
+fn tally<'a>(findings: impl Iterator<Item = &'a StrictnessFinding>) -> BTreeMap<FindingKind, usize> {
+    let mut counts = BTreeMap::new();
+    for finding in findings {
+        *counts.entry(finding.kind).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Steps in the order a migration usually takes; steps with nothing to do are left out
+fn migration(compiler: &CompilerStrictness, findings: &[StrictnessFinding]) -> Vec<MigrationStep> {
+    let of_kind = |kind: FindingKind| findings.iter().filter(move |f| f.kind == kind);
+    let disabled = |flag: &str| compiler.disabled.iter().any(|d| d == flag);
+    let mut steps = Vec::new();
+    let mut step = |title: &str, option: Option<&str>, kind: Option<FindingKind>| {
+        let (count, file_count) = kind
+            .map(|k| (of_kind(k).count(), of_kind(k).map(|f| f.file.as_str()).collect::<HashSet<_>>().len()))
+            .unwrap_or((0, 0));
+        if count > 0 || option.is_some_and(disabled) {
+            steps.push(MigrationStep {
+                title: title.to_string(),
+                option: option.map(str::to_string),
+                sites: count,
+                files: file_count,
+            });
+        }
+    };
+    step("Remove @ts-nocheck so every file is type checked", None, Some(FindingKind::TsNocheck));
+    step("Annotate parameters, then enable noImplicitAny", Some("noImplicitAny"), Some(FindingKind::ImplicitAny));
+    step(
+        "Enable strictNullChecks and replace non-null assertions with narrowing",
+        Some("strictNullChecks"),
+        Some(FindingKind::NonNullAssertion),
+    );
+    step("Turn @ts-ignore into @ts-expect-error so stale suppressions surface", None, Some(FindingKind::TsIgnore));
+    let others_disabled = compiler
+        .disabled
+        .iter()
+        .any(|d| !matches!(d.as_str(), "noImplicitAny" | "strictNullChecks"));
+    if others_disabled {
+        step("Enable strict for the remaining checks", Some("strict"), None);
+    }
+    steps
+}
+
+/// Combine the compiler settings with the findings of `files_scanned` files
+pub fn report(
+    workspace: &Path,
+    compiler: CompilerStrictness,
+    mut findings: Vec<StrictnessFinding>,
+    files_scanned: usize,
+) -> StrictnessReport {
+    if let Some(config_file) = compiler.config_file.as_ref().filter(|_| !compiler.strict) {
+        let text = fs::read_to_string(workspace.join(config_file)).unwrap_or_default();
+        let line = text.lines().position(|l| l.contains("\"strict\"")).map(|i| i + 1).unwrap_or(1);
+        findings.insert(
+            0,
+            StrictnessFinding {
+                kind: FindingKind::StrictDisabled,
+                file: config_file.clone(),
+                line,
+                column: 0,
+                message: format!("strict is off; {} strict checks are disabled", compiler.disabled.len()),
+            },
+        );
+    }
+    let mut by_file: BTreeMap<&str, Vec<&StrictnessFinding>> = BTreeMap::new();
+    for finding in &findings {
+        by_file.entry(finding.file.as_str()).or_default().push(finding);
+    }
+    let mut files: Vec<FileStrictness> = by_file
+        .into_iter()
+        .map(|(file, found)| FileStrictness {
+            file: file.to_string(),
+            counts: tally(found.into_iter()),
+        })
+        .collect();
+    files.sort_by_key(|f| std::cmp::Reverse(f.counts.values().sum::<usize>()));
+
+    StrictnessReport {
+        totals: tally(findings.iter()),
+        migration: migration(&compiler, &findings),
+        compiler,
+        files_scanned,
+        files,
+        findings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_jsonc_keeps_strings_and_lines() {
+        let text = "{\n  // base\n  \"extends\": \"./base.json\", /* inline */\n  \"url\": \"http://x\",\n}\n";
+        let stripped = strip_jsonc(text);
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value["url"], "http://x");
+        assert_eq!(stripped.lines().count(), text.lines().count());
+    }
+
+    #[test]
+    fn test_scan_finds_suppressions_assertions_and_untyped_params() {
+        let source = "// @ts-ignore\nconst el = document.getElementById('a')!;\nfunction add(a, b: number, c = 1) { return a + b + c; }\nconst twice = x => x * 2;\n[1, 2].map(n => n + 1);\n";
+        let kinds: Vec<(FindingKind, usize)> = scan("src/app.ts", source).iter().map(|f| (f.kind, f.line)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (FindingKind::TsIgnore, 1),
+                (FindingKind::NonNullAssertion, 2),
+                (FindingKind::ImplicitAny, 3),
+                (FindingKind::ImplicitAny, 4),
+            ]
+        );
+    }
+}
@@ -0,0 +1,296 @@
+// Updates - Release checks on the stable or beta channel and signature-verified downloads
+// The core decides when to check; manifests and signatures follow Tauri's updater format and key
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use minisign_verify::{PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+
+use crate::local_llm::PartialFile;
+use crate::settings::UpdateSettings;
+use crate::storage;
+
+/// How often the background task asks whether an automatic check is due
+pub const POLL_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleaseChannel {
+    Stable,
+    Beta,
+}
+
+impl ReleaseChannel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReleaseChannel::Stable => "stable",
+            ReleaseChannel::Beta => "beta",
+        }
+    }
+}
+
+/// Static release manifest as served to Tauri's updater
+#[derive(Deserialize)]
+struct ReleaseManifest {
+    version: String,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    pub_date: Option<String>,
+    platforms: HashMap<String, PlatformRelease>,
+}
+
+#[derive(Deserialize)]
+struct PlatformRelease {
+    url: String,
+    /// Base64 minisign signature of the package
+    signature: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UpdateInfo {
+    pub channel: ReleaseChannel,
+    pub current_version: String,
+    pub version: String,
+    pub available: bool,
+    pub notes: Option<String>,
+    pub pub_date: Option<String>,
+    /// Package for this platform; none when the release has no build for it
+    pub url: Option<String>,
+    pub signature: Option<String>,
+    pub checked_at: u64,
+}
+
+/// Progress payload emitted as `update-download`
+#[derive(Serialize, Clone, Debug)]
+pub struct UpdateProgress {
+    pub version: String,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+    pub done: bool,
+}
+
+/// Directory holding the last check and downloaded packages
+pub fn updates_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("updates")
+}
+
+fn last_check_path(dir: &Path) -> PathBuf {
+    dir.join("last_check.json")
+}
+
+pub fn last_check(dir: &Path) -> Result<Option<UpdateInfo>> {
+    storage::read_json(&last_check_path(dir))
+}
+
+/// Platform key of the manifest, e.g. `darwin-aarch64`
+fn platform() -> String {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    format!("{}-{}", os, std::env::consts::ARCH)
+}
+
+/// Semver order; a pre-release sorts before its release and compares by dot-separated identifiers
+fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    let parse = |version: &str| -> Option<(Vec<u64>, Option<String>)> {
+        let version = version.trim().trim_start_matches('v');
+        let version = version.split('+').next()?;
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, Some(pre.to_string())),
+            None => (version, None),
+        };
+        let numbers = core.split('.').map(|part| part.parse().ok()).collect::<Option<Vec<u64>>>()?;
+        (numbers.len() == 3).then_some((numbers, pre))
+    };
+    let ((a_core, a_pre), (b_core, b_pre)) = (parse(a)?, parse(b)?);
+    let order = a_core.cmp(&b_core).then_with(|| match (&a_pre, &b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => {
+            let ids = |pre: &str| pre.split('.').map(str::to_string).collect::<Vec<_>>();
+            for (x, y) in ids(a).iter().zip(ids(b).iter()) {
+                let order = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => x.cmp(y),
+                };
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+            ids(a).len().cmp(&ids(b).len())
+        }
+    });
+    Some(order)
+}
+
+/// Whether the active connection is metered; only NetworkManager is asked, elsewhere it counts as unmetered
+pub fn is_metered() -> bool {
+    if !cfg!(target_os = "linux") {
+        return false;
+    }
+    let output = std::process::Command::new("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output();
+    // NM_METERED_YES and NM_METERED_GUESS_YES
+    match output {
+        Ok(output) if output.status.success() => {
+            matches!(String::from_utf8_lossy(&output.stdout).trim(), "u 1" | "u 3")
+        }
+        _ => false,
+    }
+}
+
+/// Why an automatic check is not made now, or none when it is due
+pub fn skip_reason(settings: &UpdateSettings, last: Option<&UpdateInfo>, metered: bool, now: u64) -> Option<String> {
+    if settings.check_interval_hours == 0 {
+        return Some("Automatic update checks are off".to_string());
+    }
+    let interval = settings.check_interval_hours * 60 * 60 * 1000;
+    let same_channel = last.filter(|last| last.channel == settings.channel);
+    if same_channel.is_some_and(|last| now.saturating_sub(last.checked_at) < interval) {
+        return Some("Checked recently".to_string());
+    }
+    if metered && !settings.allow_metered {
+        return Some("Connection is metered".to_string());
+    }
+    None
+}
+
+/// What the release manifest means for this build and platform
+fn evaluate(manifest: ReleaseManifest, channel: ReleaseChannel, current_version: &str, now: u64) -> UpdateInfo {
+    let release = manifest.platforms.get(&platform());
+    let newer = compare_versions(&manifest.version, current_version) == Some(Ordering::Greater);
+    UpdateInfo {
+        channel,
+        current_version: current_version.to_string(),
+        available: newer && release.is_some(),
+        notes: manifest.notes,
+        pub_date: manifest.pub_date,
+        url: release.map(|release| release.url.clone()),
+        signature: release.map(|release| release.signature.clone()),
+        version: manifest.version,
+        checked_at: now,
+    }
+}
+
+/// Fetch the manifest of the configured channel and remember the result as the last check
+pub async fn check(dir: &Path, settings: &UpdateSettings) -> Result<UpdateInfo> {
+    let url = settings.endpoint.replace("{{channel}}", settings.channel.as_str());
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(30)).build()?;
+    let manifest: ReleaseManifest = client.get(&url).send().await?.error_for_status()?.json().await?;
+    let info = evaluate(manifest, settings.channel, env!("CARGO_PKG_VERSION"), storage::now_millis());
+    storage::write_json(&last_check_path(dir), &info)?;
+    Ok(info)
+}
+
+/// Check `data` against a base64 minisign signature with the base64 public key of Tauri's updater config
+fn verify(data: &[u8], signature: &str, public_key: &str) -> Result<()> {
+    if public_key.trim().is_empty() {
+        return Err(anyhow!("No updater public key configured"));
+    }
+    let decode = |value: &str| -> Result<String> {
+        Ok(String::from_utf8(base64::engine::general_purpose::STANDARD.decode(value.trim())?)?)
+    };
+    let key = PublicKey::decode(&decode(public_key)?).map_err(|e| anyhow!("Invalid updater public key: {}", e))?;
+    let signature = Signature::decode(&decode(signature)?).map_err(|e| anyhow!("Invalid update signature: {}", e))?;
+    key.verify(data, &signature, true).map_err(|e| anyhow!("Update signature mismatch: {}", e))
+}
+
+/// Download the package of `info` into `dir`, reporting progress; only a package whose signature verifies is kept
+pub async fn download(
+    dir: &Path,
+    info: &UpdateInfo,
+    public_key: &str,
+    on_progress: impl Fn(UpdateProgress),
+) -> Result<PathBuf> {
+    let (Some(url), Some(signature)) = (info.url.as_deref(), info.signature.as_deref()) else {
+        return Err(anyhow!("Release {} has no package for {}", info.version, platform()));
+    };
+    if !info.available {
+        return Err(anyhow!("Version {} is already the latest", info.current_version));
+    }
+    let name = url.rsplit('/').next().filter(|name| !name.is_empty() && *name != "..").unwrap_or("update");
+    fs::create_dir_all(dir)?;
+    let path = dir.join(name);
+    let partial = PartialFile(dir.join(format!("{}.part", name)));
+
+    let mut response = reqwest::get(url).await?.error_for_status()?;
+    let total_bytes = response.content_length();
+    let mut file = fs::File::create(&partial.0)?;
+    let mut downloaded_bytes = 0u64;
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk)?;
+        downloaded_bytes += chunk.len() as u64;
+        on_progress(UpdateProgress {
+            version: info.version.clone(),
+            downloaded_bytes,
+            total_bytes,
+            done: false,
+        });
+    }
+    file.flush()?;
+    drop(file);
+
+    verify(&fs::read(&partial.0)?, signature, public_key)?;
+    fs::rename(&partial.0, &path)?;
+    on_progress(UpdateProgress {
+        version: info.version.clone(),
+        downloaded_bytes,
+        total_bytes,
+        done: true,
+    });
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_order_and_check_policy() {
+        assert_eq!(compare_versions("1.1.0", "1.0.9"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("1.1.0-beta.2", "1.1.0"), Some(Ordering::Less));
+        assert_eq!(compare_versions("1.1.0-beta.10", "1.1.0-beta.2"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("v1.0.0", "1.0.0"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("nightly", "1.0.0"), None);
+
+        let release = PlatformRelease {
+            url: "https://example.com/app.tar.gz".to_string(),
+            signature: "c2ln".to_string(),
+        };
+        let manifest = ReleaseManifest {
+            version: "1.2.0-beta.1".to_string(),
+            notes: None,
+            pub_date: None,
+            platforms: HashMap::from([(platform(), release)]),
+        };
+        let info = evaluate(manifest, ReleaseChannel::Beta, "1.1.0", 1_000);
+        assert!(info.available);
+
+        let settings = UpdateSettings::default();
+        let hour = 60 * 60 * 1000;
+        assert!(skip_reason(&settings, None, false, 0).is_none());
+        assert!(skip_reason(&settings, None, true, 0).is_some());
+        let last = UpdateInfo { channel: settings.channel, ..info };
+        assert!(skip_reason(&settings, Some(&last), false, 1_000 + hour).is_some());
+        assert!(skip_reason(&settings, Some(&last), false, 1_000 + 25 * hour).is_none());
+        assert!(verify(b"package", "c2ln", "").is_err());
+    }
+}
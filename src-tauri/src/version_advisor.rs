@@ -0,0 +1,236 @@
+// Version Advisor - Semver bump for a package from its API changes since the last tag
+// Removals, renames and signature changes are major, additions minor, anything else patch
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::api_surface::{self, ApiChange, ApiChangeKind, ApiDiff, ApiSymbol};
+use crate::git;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Bump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+impl Version {
+    /// `1.2.3`, `v1.2.3` or a monorepo tag like `core@1.2.3`; pre-release and build parts are dropped
+    fn parse(tag: &str) -> Option<Self> {
+        let start = tag.find(|c: char| c.is_ascii_digit())?;
+        let core = tag[start..].split(['-', '+']).next()?;
+        let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+        let version = Version {
+            major: parts.next()??,
+            minor: parts.next().unwrap_or(Some(0))?,
+            patch: parts.next().unwrap_or(Some(0))?,
+        };
+        Some(version)
+    }
+
+    /// Before 1.0 every change may break, so bumps shift down one place:
+    /// breaking changes raise `0.y`, everything else `0.y.z`, and `0.0.z` always moves `z`
+    fn effective(&self, bump: Bump) -> Bump {
+        match (self.major, self.minor, bump) {
+            (_, _, Bump::None) => Bump::None,
+            (0, 0, _) => Bump::Patch,
+            (0, _, Bump::Major) => Bump::Minor,
+            (0, _, _) => Bump::Patch,
+            _ => bump,
+        }
+    }
+
+    fn bumped(&self, bump: Bump) -> Version {
+        match self.effective(bump) {
+            Bump::None => self.clone(),
+            Bump::Patch => Version { patch: self.patch + 1, ..self.clone() },
+            Bump::Minor => Version { minor: self.minor + 1, patch: 0, ..self.clone() },
+            Bump::Major => Version { major: self.major + 1, minor: 0, patch: 0 },
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VersionAdvice {
+    pub package: String,
+    /// Name from Cargo.toml or package.json, when the package has one
+    pub name: Option<String>,
+    pub last_tag: String,
+    /// Version in the manifest, which may already be ahead of the tag
+    pub manifest_version: Option<String>,
+    /// Bump by semver rules, before pre-1.0 adjustment
+    pub bump: Bump,
+    pub suggested_version: Option<String>,
+    /// Commits touching the package since the tag
+    pub commits: usize,
+    /// One line per breaking change, then the reasoning for the bump
+    pub explanations: Vec<String>,
+    pub api: ApiDiff,
+}
+
+/// `name` and `version` of the `[package]` table of a Cargo.toml
+fn cargo_package(manifest: &str) -> (Option<String>, Option<String>) {
+    let (mut name, mut version) = (None, None);
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+            continue;
+        }
+        if !in_package {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim().trim_matches('"').to_string();
+            match key.trim() {
+                "name" => name = Some(value),
+                "version" => version = Some(value),
+                _ => {}
+            }
+        }
+    }
+    (name, version)
+}
+
+/// Name and version from the package's Cargo.toml or package.json
+fn manifest(dir: &Path) -> (Option<String>, Option<String>) {
+    if let Ok(cargo) = fs::read_to_string(dir.join("Cargo.toml")) {
+        return cargo_package(&cargo);
+    }
+    let package: serde_json::Value = match fs::read_to_string(dir.join("package.json")).ok().and_then(|m| serde_json::from_str(&m).ok()) {
+        Some(package) => package,
+        None => return (None, None),
+    };
+    let field = |key: &str| package[key].as_str().map(str::to_string);
+    (field("name"), field("version"))
+}
+
+/// Latest tag reachable from HEAD, preferring the package's own `name@x.y.z` or `name-vx.y.z` tags
+pub fn last_tag(workspace: &Path, name: Option<&str>) -> Result<String> {
+    if let Some(name) = name {
+        let own = git::run(
+            workspace,
+            &["describe", "--tags", "--abbrev=0", "--match", &format!("{}@*", name), "--match", &format!("{}-v*", name)],
+        );
+        if let Ok(tag) = own {
+            return Ok(tag.trim().to_string());
+        }
+    }
+    git::run(workspace, &["describe", "--tags", "--abbrev=0"])
+        .map(|tag| tag.trim().to_string())
+        .map_err(|_| anyhow!("No tag to compare against"))
+}
+
+/// Why a change breaks callers, in one line
+pub fn explain(change: &ApiChange) -> Option<String> {
+    let before = change.before.as_ref()?;
+    let location = format!("{}:{}", before.file, before.line);
+    let explanation = match (change.kind, change.after.as_ref()) {
+        (ApiChangeKind::Removed, _) => format!("`{}` was removed or made private ({})", before.qualified_name, location),
+        (ApiChangeKind::Renamed, Some(after)) => {
+            format!("`{}` was renamed to `{}`; callers of the old name break", before.qualified_name, after.name)
+        }
+        (ApiChangeKind::SignatureChanged, Some(after)) => format!(
+            "`{}` changed from `{}` to `{}`",
+            before.qualified_name, before.signature, after.signature
+        ),
+        _ => return None,
+    };
+    Some(explanation)
+}
+
+/// Semver bump for `api`: breaking changes are major, additions minor, other commits patch
+fn classify(api: &ApiDiff, commits: usize) -> (Bump, Vec<String>) {
+    let mut explanations: Vec<String> = api.changes.iter().filter(|c| c.breaking).filter_map(explain).collect();
+    let added = api.changes.iter().filter(|c| c.kind == ApiChangeKind::Added).count();
+    let bump = if api.breaking_count > 0 {
+        explanations.push(format!("Major: {} breaking API changes since {}", api.breaking_count, api.base));
+        Bump::Major
+    } else if added > 0 {
+        explanations.push(format!("Minor: {} public symbols added, none removed or changed", added));
+        Bump::Minor
+    } else if commits > 0 {
+        explanations.push(format!("Patch: {} commits without public API changes", commits));
+        Bump::Patch
+    } else {
+        explanations.push(format!("Nothing changed since {}", api.base));
+        Bump::None
+    };
+    (bump, explanations)
+}
+
+/// Suggest the next version of `package` from its public API at HEAD's working tree
+pub fn suggest(workspace: &Path, package: &str, current: &[ApiSymbol]) -> Result<VersionAdvice> {
+    let (name, manifest_version) = manifest(&workspace.join(package));
+    let tag = last_tag(workspace, name.as_deref())?;
+    let api = api_surface::diff_against(workspace, &tag, package, current)?;
+    let range = format!("{}..HEAD", tag);
+    let mut count_args = vec!["rev-list", "--count", range.as_str()];
+    if !package.is_empty() {
+        count_args.extend(["--", package]);
+    }
+    let commits = git::run(workspace, &count_args)?.trim().parse().unwrap_or(0);
+
+    let (bump, mut explanations) = classify(&api, commits);
+    let base = Version::parse(&tag).or_else(|| manifest_version.as_deref().and_then(Version::parse));
+    let suggested_version = base.as_ref().map(|base| {
+        let effective = base.effective(bump);
+        if effective != bump {
+            explanations.push(format!("{} is pre-1.0, so the bump is {:?} instead", base, effective).to_lowercase());
+        }
+        base.bumped(bump).to_string()
+    });
+
+    Ok(VersionAdvice {
+        package: package.to_string(),
+        name,
+        last_tag: tag,
+        manifest_version,
+        bump,
+        suggested_version,
+        commits,
+        explanations,
+        api,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bumps_follow_pre_1_0_rules() {
+        let stable = Version::parse("v1.4.2").unwrap();
+        assert_eq!(stable.bumped(Bump::Major).to_string(), "2.0.0");
+        assert_eq!(stable.bumped(Bump::Minor).to_string(), "1.5.0");
+        let early = Version::parse("core@0.3.1-beta.2").unwrap();
+        assert_eq!(early.bumped(Bump::Major).to_string(), "0.4.0");
+        assert_eq!(early.bumped(Bump::Minor).to_string(), "0.3.2");
+        assert_eq!(Version::parse("0.0.7").unwrap().bumped(Bump::Major).to_string(), "0.0.8");
+        assert!(Version::parse("latest").is_none());
+    }
+
+    #[test]
+    fn test_cargo_package_reads_only_the_package_table() {
+        let manifest = "[package]\nname = \"mimi\"\nversion = \"0.2.0\"\n\n[dependencies]\nserde = { version = \"1.0\" }\n";
+        assert_eq!(cargo_package(manifest), (Some("mimi".to_string()), Some("0.2.0".to_string())));
+    }
+}
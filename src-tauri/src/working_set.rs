@@ -0,0 +1,107 @@
+// Working Set - Files the user pinned as the focus of their current work
+// Pinned files rank higher in search and are analyzed and cached first
+
+use std::path::{Component, Path, PathBuf};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PinnedFile {
+    /// Relative to the workspace
+    pub path: String,
+    pub pinned_at: u64,
+}
+
+fn working_set_path(workspace: &Path) -> PathBuf {
+    storage::data_dir(workspace).join("working_set.json")
+}
+
+/// Workspace-relative form of an absolute or relative path inside the workspace
+fn relative_path(workspace: &Path, path: &str) -> Result<String> {
+    let path = Path::new(path);
+    let relative = if path.is_absolute() {
+        path.strip_prefix(workspace)
+            .map_err(|_| anyhow!("Not in the workspace: {}", path.display()))?
+    } else {
+        path
+    };
+    if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(anyhow!("Not in the workspace: {}", path.display()));
+    }
+    Ok(relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Pinned files, oldest pin first
+pub fn list(workspace: &Path) -> Result<Vec<PinnedFile>> {
+    Ok(storage::read_json(&working_set_path(workspace))?.unwrap_or_default())
+}
+
+/// Absolute paths of the pinned files, as keyed in the file index
+pub fn absolute_paths(workspace: &Path) -> Result<Vec<PathBuf>> {
+    Ok(list(workspace)?.into_iter().map(|f| workspace.join(f.path)).collect())
+}
+
+/// Pin a file; pinning it again keeps the original pin time
+pub fn pin(workspace: &Path, path: &str) -> Result<Vec<PinnedFile>> {
+    let relative = relative_path(workspace, path)?;
+    if !workspace.join(&relative).is_file() {
+        return Err(anyhow!("File not found: {}", relative));
+    }
+    let mut files = list(workspace)?;
+    if !files.iter().any(|f| f.path == relative) {
+        files.push(PinnedFile {
+            path: relative,
+            pinned_at: storage::now_millis(),
+        });
+        storage::write_json(&working_set_path(workspace), &files)?;
+    }
+    Ok(files)
+}
+
+pub fn unpin(workspace: &Path, path: &str) -> Result<Vec<PinnedFile>> {
+    let relative = relative_path(workspace, path)?;
+    let mut files = list(workspace)?;
+    let before = files.len();
+    files.retain(|f| f.path != relative);
+    if files.len() != before {
+        storage::write_json(&working_set_path(workspace), &files)?;
+    }
+    Ok(files)
+}
+
+/// Keep `from` pinned under its new path `to`, after the file moved
+pub fn rename(workspace: &Path, from: &str, to: &str) -> Result<Vec<PinnedFile>> {
+    let (from, to) = (relative_path(workspace, from)?, relative_path(workspace, to)?);
+    let mut files = list(workspace)?;
+    if from == to || !files.iter().any(|f| f.path == from) {
+        return Ok(files);
+    }
+    files.retain(|f| f.path != to);
+    for file in files.iter_mut().filter(|f| f.path == from) {
+        file.path = to.clone();
+    }
+    storage::write_json(&working_set_path(workspace), &files)?;
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_and_unpin() {
+        let workspace = std::env::temp_dir().join(storage::new_id("working-set-test"));
+        std::fs::create_dir_all(workspace.join("src")).unwrap();
+        std::fs::write(workspace.join("src/main.rs"), "fn main() {}\n").unwrap();
+
+        let absolute = workspace.join("src/main.rs").to_string_lossy().to_string();
+        assert_eq!(pin(&workspace, &absolute).unwrap()[0].path, "src/main.rs");
+        assert_eq!(pin(&workspace, "src/main.rs").unwrap().len(), 1);
+        assert!(pin(&workspace, "../outside.rs").is_err());
+        assert!(pin(&workspace, "missing.rs").is_err());
+        assert!(unpin(&workspace, "src/main.rs").unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+}
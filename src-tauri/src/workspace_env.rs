@@ -0,0 +1,239 @@
+// Workspace Environment - Variables from .mimiverse/env for spawned processes
+// Secret values live in the OS keychain; the file only marks them
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::secrets;
+use crate::storage;
+
+/// Value written to the env file for variables whose value is in the keychain
+const SECRET_MARKER: &str = "@secret";
+
+/// What the frontend sees instead of a secret value
+const MASK: &str = "********";
+
+/// How a workspace variable combines with the ambient environment
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Precedence {
+    /// `NAME=value`: replaces the ambient value
+    #[default]
+    Override,
+    /// `NAME?=value`: used only when the ambient environment lacks the variable
+    Default,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EnvVar {
+    pub name: String,
+    /// Masked for secrets
+    pub value: String,
+    pub secret: bool,
+    pub precedence: Precedence,
+}
+
+pub fn env_path(workspace: &Path) -> PathBuf {
+    storage::data_dir(workspace).join("env")
+}
+
+pub fn validate_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let valid = chars.next().map(|c| c.is_ascii_alphabetic() || c == '_').unwrap_or(false)
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid variable name: {}", name))
+    }
+}
+
+/// Keychain key of a secret variable, scoped to the workspace
+fn secret_key(workspace: &Path, name: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(workspace.to_string_lossy().as_bytes());
+    let scope = hex::encode(hasher.finalize());
+    format!("workspace:{}:env.{}", &scope[..16], name.to_ascii_lowercase())
+}
+
+fn unquote(raw: &str) -> String {
+    let quoted = raw.len() >= 2
+        && ((raw.starts_with('"') && raw.ends_with('"')) || (raw.starts_with('\'') && raw.ends_with('\'')));
+    if !quoted {
+        return raw.to_string();
+    }
+    let inner = &raw[1..raw.len() - 1];
+    if raw.starts_with('"') {
+        inner.replace("\\n", "\n").replace("\\\"", "\"").replace("\\\\", "\\")
+    } else {
+        inner.to_string()
+    }
+}
+
+fn quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value != SECRET_MARKER
+        && !value.chars().any(|c: char| c.is_whitespace() || matches!(c, '#' | '"' | '\'' | '\\'));
+    if plain {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+    }
+}
+
+/// One `NAME=value` / `NAME?=value` line; comments, blanks and junk yield `None`
+fn parse_line(line: &str) -> Option<(String, String, bool, Precedence)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let (name, raw) = line.split_once('=')?;
+    let (name, precedence) = match name.strip_suffix('?') {
+        Some(name) => (name.trim(), Precedence::Default),
+        None => (name.trim(), Precedence::Override),
+    };
+    validate_name(name).ok()?;
+    let raw = raw.trim();
+    let secret = raw == SECRET_MARKER;
+    Some((name.to_string(), unquote(raw), secret, precedence))
+}
+
+fn format_line(name: &str, value: &str, secret: bool, precedence: Precedence) -> String {
+    let op = match precedence {
+        Precedence::Override => "=",
+        Precedence::Default => "?=",
+    };
+    let value = if secret { SECRET_MARKER.to_string() } else { quote(value) };
+    format!("{}{}{}", name, op, value)
+}
+
+fn read_lines(workspace: &Path) -> Result<Vec<String>> {
+    let path = env_path(workspace);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(path)?.lines().map(str::to_string).collect())
+}
+
+/// Variables in file order; for duplicates the last line wins, as in shells
+fn parse(content_lines: &[String]) -> Vec<EnvVar> {
+    let mut vars: Vec<EnvVar> = Vec::new();
+    for (name, value, secret, precedence) in content_lines.iter().filter_map(|l| parse_line(l)) {
+        vars.retain(|v| v.name != name);
+        vars.push(EnvVar {
+            name,
+            value,
+            secret,
+            precedence,
+        });
+    }
+    vars
+}
+
+/// Workspace variables with secret values masked
+pub fn list(workspace: &Path) -> Result<Vec<EnvVar>> {
+    Ok(parse(&read_lines(workspace)?)
+        .into_iter()
+        .map(|mut var| {
+            if var.secret {
+                var.value = MASK.to_string();
+            }
+            var
+        })
+        .collect())
+}
+
+/// Set (or with `value: None`, remove) a variable, keeping comments and order of the file
+pub fn set(workspace: &Path, name: &str, value: Option<&str>, secret: bool, precedence: Precedence) -> Result<()> {
+    validate_name(name)?;
+    let key = secret_key(workspace, name);
+    let was_secret = parse(&read_lines(workspace)?).iter().any(|v| v.name == name && v.secret);
+    match value {
+        Some(value) if secret => secrets::set(&key, value)?,
+        _ if was_secret => secrets::delete(&key)?,
+        _ => {}
+    }
+
+    let mut lines: Vec<String> = read_lines(workspace)?
+        .into_iter()
+        .filter(|line| parse_line(line).map(|(n, ..)| n != name).unwrap_or(true))
+        .collect();
+    if let Some(value) = value {
+        lines.push(format_line(name, value, secret, precedence));
+    }
+
+    let path = env_path(workspace);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, lines.join("\n") + "\n")?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Apply precedence against `ambient`: the variables a child process should get set
+fn overlay(vars: Vec<(EnvVar, String)>, ambient: impl Fn(&str) -> bool) -> HashMap<String, String> {
+    vars.into_iter()
+        .filter(|(var, _)| var.precedence == Precedence::Override || !ambient(&var.name))
+        .map(|(var, value)| (var.name, value))
+        .collect()
+}
+
+/// Real values to inject into processes started in the workspace.
+/// Precedence, lowest first: `?=` defaults, ambient environment, `=` overrides, per-task env.
+pub fn resolve(workspace: &Path) -> Result<HashMap<String, String>> {
+    let mut vars = Vec::new();
+    for var in parse(&read_lines(workspace)?) {
+        let value = if var.secret {
+            match secrets::get(&secret_key(workspace, &var.name))? {
+                Some(value) => value,
+                None => {
+                    log::warn!("Secret variable {} has no value in the keychain", var.name);
+                    continue;
+                }
+            }
+        } else {
+            var.value.clone()
+        };
+        vars.push((var, value));
+    }
+    Ok(overlay(vars, |name| std::env::var_os(name).is_some()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_precedence() {
+        let lines: Vec<String> = [
+            "# comment",
+            "export API_URL=http://localhost:3000",
+            "PORT?=8080",
+            "TOKEN=@secret",
+            "GREETING=\"hello # world\"",
+            "1BAD=x",
+            "PORT?=9090",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        let vars = parse(&lines);
+        let names: Vec<&str> = vars.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["API_URL", "TOKEN", "GREETING", "PORT"]);
+        assert!(vars[1].secret);
+        assert_eq!(vars[2].value, "hello # world");
+        assert_eq!((vars[3].value.as_str(), vars[3].precedence), ("9090", Precedence::Default));
+        assert_eq!(parse_line(&format_line("GREETING", "hello # world", false, Precedence::Override)).unwrap().1, "hello # world");
+
+        let resolved = overlay(vars.into_iter().map(|v| (v.clone(), v.value)).collect(), |name| name == "PORT" || name == "API_URL");
+        assert!(!resolved.contains_key("PORT"));
+        assert_eq!(resolved["API_URL"], "http://localhost:3000");
+    }
+}